@@ -0,0 +1,325 @@
+use crate::coercion::integer_bits;
+use crate::driver::Artifact;
+use crate::error::AbiError;
+use crate::field::field_modulus;
+use crate::symbol::{Function, Symbol, SymbolType};
+use num_bigint::{BigInt, BigUint, Sign};
+use sha3::{Digest, Keccak256};
+use zoker_parser::ast::CONSTRUCTOR;
+
+pub type AbiResult<T> = Result<T, AbiError>;
+
+const WORD_SIZE: usize = 32;
+
+/// Value that can be passed to or returned from `abi.encode` and `abi.decode`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AbiValue {
+    Uint(BigUint),
+    Int(BigInt),
+    Address(Vec<u8>),
+    Bool(bool),
+    Bytes32(Vec<u8>),
+    Bytes(Vec<u8>),
+    String(String),
+}
+
+impl AbiValue {
+    pub fn symbol_type(&self) -> SymbolType {
+        match self {
+            AbiValue::Uint(_) => SymbolType::Uint256,
+            AbiValue::Int(_) => SymbolType::Int256,
+            AbiValue::Address(_) => SymbolType::Address,
+            AbiValue::Bool(_) => SymbolType::Bool,
+            AbiValue::Bytes32(_) => SymbolType::Bytes32,
+            AbiValue::Bytes(_) => SymbolType::Bytes,
+            AbiValue::String(_) => SymbolType::String,
+        }
+    }
+
+    fn is_dynamic(&self) -> bool {
+        matches!(self, AbiValue::Bytes(_) | AbiValue::String(_))
+    }
+
+    /// Head word of a static value, an error if the value doesn't fit in a word.
+    fn encode_word(&self) -> AbiResult<Vec<u8>> {
+        let bytes = match self {
+            AbiValue::Uint(value) => value.to_bytes_be(),
+            AbiValue::Int(value) => value.to_signed_bytes_be(),
+            AbiValue::Address(value) if value.len() == 20 => value.clone(),
+            AbiValue::Address(_) => {
+                return Err(AbiError::new("address must be 20 bytes long"));
+            }
+            AbiValue::Bool(value) => vec![*value as u8],
+            AbiValue::Bytes32(value) if value.len() <= WORD_SIZE => {
+                let mut word = value.clone();
+                word.resize(WORD_SIZE, 0);
+                return Ok(word);
+            }
+            AbiValue::Bytes32(_) => return Err(AbiError::new("bytes32 is longer than 32 bytes")),
+            AbiValue::Bytes(_) | AbiValue::String(_) => unreachable!(),
+        };
+        if bytes.len() > WORD_SIZE {
+            return Err(AbiError::new(&format!(
+                "`{}` value is wider than 256 bits",
+                canonical_type(&self.symbol_type())
+            )));
+        }
+        let fill = match self {
+            AbiValue::Int(value) if value.sign() == Sign::Minus => 0xff,
+            _ => 0,
+        };
+        Ok(left_pad(&bytes, fill))
+    }
+
+    fn as_bytes(&self) -> &[u8] {
+        match self {
+            AbiValue::Bytes(value) => value,
+            AbiValue::String(value) => value.as_bytes(),
+            _ => unreachable!(),
+        }
+    }
+}
+
+fn left_pad(bytes: &[u8], fill: u8) -> Vec<u8> {
+    let mut word = vec![fill; WORD_SIZE.saturating_sub(bytes.len())];
+    word.extend_from_slice(bytes);
+    word
+}
+
+/// `bytes` padded with zeros to a multiple of the word size, as the tail of a dynamic value.
+fn right_pad(bytes: &[u8]) -> Vec<u8> {
+    let mut word = bytes.to_vec();
+    let padding = (WORD_SIZE - bytes.len() % WORD_SIZE) % WORD_SIZE;
+    word.extend(vec![0; padding]);
+    word
+}
+
+fn usize_word(value: usize) -> Vec<u8> {
+    left_pad(&BigUint::from(value).to_bytes_be(), 0)
+}
+
+/// Whether `value` is a value of type `typ`, in its range for integers.
+fn has_type(value: &AbiValue, typ: &SymbolType) -> bool {
+    match (value, integer_bits(typ)) {
+        (AbiValue::Uint(value), Some((false, bits))) => value.bits() <= bits as usize,
+        (AbiValue::Uint(value), None) if *typ == SymbolType::Field => *value < field_modulus(),
+        (AbiValue::Int(value), Some((true, bits))) => {
+            let half = BigInt::from(1) << (bits as usize - 1);
+            -half.clone() <= *value && *value < half
+        }
+        (AbiValue::Address(value), None) => *typ == SymbolType::Address && value.len() == 20,
+        (AbiValue::Bytes32(value), None) => *typ == SymbolType::Bytes32 && value.len() <= 32,
+        (AbiValue::Bool(_), None) | (AbiValue::Bytes(_), None) | (AbiValue::String(_), None) => {
+            value.symbol_type() == *typ
+        }
+        _ => false,
+    }
+}
+
+/// Checks that `args` are values of the parameters `params` of `function`.
+pub fn check_arguments(function: &str, params: &[SymbolType], args: &[AbiValue]) -> AbiResult<()> {
+    if args.len() != params.len() {
+        return Err(AbiError::new(&format!(
+            "`{}` expects {} arguments but {} were given",
+            function,
+            params.len(),
+            args.len()
+        )));
+    }
+    for (i, (arg, param)) in args.iter().zip(params).enumerate() {
+        if !has_type(arg, param) {
+            return Err(AbiError::new(&format!(
+                "argument {} of `{}` expects `{}` but `{}` was given",
+                i + 1,
+                function,
+                canonical_type(param),
+                canonical_type(&arg.symbol_type())
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Encodes values as `abi.encode` does: static heads followed by dynamic tails.
+pub fn encode(values: &[AbiValue]) -> AbiResult<Vec<u8>> {
+    let mut head = vec![];
+    let mut tail = vec![];
+    let head_size = values.len() * WORD_SIZE;
+    for value in values {
+        if value.is_dynamic() {
+            head.extend(usize_word(head_size + tail.len()));
+            let bytes = value.as_bytes();
+            tail.extend(usize_word(bytes.len()));
+            tail.extend(right_pad(bytes));
+        } else {
+            head.extend(value.encode_word()?);
+        }
+    }
+    head.extend(tail);
+    Ok(head)
+}
+
+/// Encodes values as `abi.encodePacked` does: minimal size without padding of dynamic values.
+pub fn encode_packed(values: &[AbiValue]) -> AbiResult<Vec<u8>> {
+    let mut encoded = vec![];
+    for value in values {
+        match value {
+            AbiValue::Address(_) => encoded.extend_from_slice(&value.encode_word()?[12..]),
+            AbiValue::Bool(value) => encoded.push(*value as u8),
+            AbiValue::Bytes(_) | AbiValue::String(_) => encoded.extend_from_slice(value.as_bytes()),
+            _ => encoded.extend(value.encode_word()?),
+        }
+    }
+    Ok(encoded)
+}
+
+pub fn encode_with_selector(selector: &[u8; 4], values: &[AbiValue]) -> AbiResult<Vec<u8>> {
+    let mut encoded = selector.to_vec();
+    encoded.extend(encode(values)?);
+    Ok(encoded)
+}
+
+/// Name of `typ` in canonical signatures, e.g. `uint256` for `uint`.
+pub fn canonical_type(typ: &SymbolType) -> String {
+    match typ {
+        // Elements of the field are passed as their integer value
+        SymbolType::Uint256 | SymbolType::Field => "uint256".to_string(),
+        SymbolType::Int256 => "int256".to_string(),
+        SymbolType::Array(element) => format!("{}[]", canonical_type(element)),
+        SymbolType::FixedArray(element, length) => {
+            format!("{}[{}]", canonical_type(element), length)
+        }
+        SymbolType::Mapping(key, value) => {
+            format!(
+                "mapping({} => {})",
+                canonical_type(key),
+                canonical_type(value)
+            )
+        }
+        _ => typ.to_string(),
+    }
+}
+
+/// Canonical signature of a function or an event, e.g. `Transfer(address,uint256)`.
+pub fn signature(name: &str, params: &[SymbolType]) -> String {
+    let params: Vec<String> = params.iter().map(canonical_type).collect();
+    format!("{}({})", name, params.join(","))
+}
+
+/// First topic of the logs of an event, the keccak256 hash of its canonical signature.
+pub fn event_topic(signature: &str) -> [u8; 32] {
+    Keccak256::digest(signature.as_bytes()).into()
+}
+
+/// Selector of a function, the first 4 bytes of the keccak256 hash of its canonical signature.
+pub fn selector(signature: &str) -> [u8; 4] {
+    let hash = event_topic(signature);
+    [hash[0], hash[1], hash[2], hash[3]]
+}
+
+/// Function `name` callable on the contract of `artifact`.
+fn abi_function<'a>(artifact: &'a Artifact, name: &str) -> AbiResult<&'a Function> {
+    artifact
+        .contract
+        .functions
+        .iter()
+        .find(|function| function.name == name && name != CONSTRUCTOR && !function.is_free)
+        .ok_or_else(|| {
+            AbiError::new(&format!(
+                "`{}` has no function `{}`",
+                artifact.contract.name, name
+            ))
+        })
+}
+
+fn symbol_types(symbols: &[Symbol]) -> Vec<SymbolType> {
+    symbols
+        .iter()
+        .map(|symbol| symbol.symbol_type.clone())
+        .collect()
+}
+
+/// Calldata calling `function` of the contract of `artifact` with `args`.
+pub fn encode_call(artifact: &Artifact, function: &str, args: &[AbiValue]) -> AbiResult<Vec<u8>> {
+    let params = symbol_types(&abi_function(artifact, function)?.params);
+    check_arguments(function, &params, args)?;
+    encode_with_selector(&selector(&signature(function, &params)), args)
+}
+
+/// Values returned by `function` of the contract of `artifact`, decoded from `data`.
+pub fn decode_return(artifact: &Artifact, function: &str, data: &[u8]) -> AbiResult<Vec<AbiValue>> {
+    decode(
+        &symbol_types(&abi_function(artifact, function)?.returns),
+        data,
+    )
+}
+
+/// Decodes `data` produced by `encode` into values of the given types. Static values
+/// must be encoded as `encode` does, with the bits padding them to a word cleared.
+pub fn decode(types: &[SymbolType], data: &[u8]) -> AbiResult<Vec<AbiValue>> {
+    let mut values = vec![];
+    for (i, typ) in types.iter().enumerate() {
+        let word = read_word(data, i * WORD_SIZE)?;
+        let value = match typ {
+            SymbolType::Uint256 | SymbolType::Uint(_) | SymbolType::Field => {
+                AbiValue::Uint(BigUint::from_bytes_be(word))
+            }
+            SymbolType::Int256 | SymbolType::Int(_) => {
+                AbiValue::Int(BigInt::from_signed_bytes_be(word))
+            }
+            SymbolType::Address => AbiValue::Address(word[12..].to_vec()),
+            SymbolType::Bool => AbiValue::Bool(word[WORD_SIZE - 1] != 0),
+            SymbolType::Bytes32 => AbiValue::Bytes32(word.to_vec()),
+            SymbolType::Bytes | SymbolType::String => {
+                let offset = read_usize(word, data)?;
+                let length = read_usize(read_word(data, offset)?, data)?;
+                let bytes = offset
+                    .checked_add(WORD_SIZE)
+                    .and_then(|start| read_bytes(data, start, length))
+                    .ok_or_else(|| AbiError::new("dynamic value is out of range"))?
+                    .to_vec();
+                if *typ == SymbolType::Bytes {
+                    AbiValue::Bytes(bytes)
+                } else {
+                    AbiValue::String(
+                        String::from_utf8(bytes)
+                            .map_err(|_| AbiError::new("string is not valid utf-8"))?,
+                    )
+                }
+            }
+            _ => return Err(AbiError::new(&format!("type `{}` cannot be decoded", typ))),
+        };
+        if !value.is_dynamic() && (!has_type(&value, typ) || value.encode_word()? != word) {
+            return Err(AbiError::new(&format!(
+                "`{}` value is not padded with zeros",
+                canonical_type(typ)
+            )));
+        }
+        values.push(value);
+    }
+    Ok(values)
+}
+
+/// The `length` bytes of `data` from `start`, `None` if they don't all fit.
+fn read_bytes(data: &[u8], start: usize, length: usize) -> Option<&[u8]> {
+    let end = start.checked_add(length)?;
+    if end > data.len() {
+        return None;
+    }
+    data.get(start..end)
+}
+
+fn read_word(data: &[u8], offset: usize) -> AbiResult<&[u8]> {
+    read_bytes(data, offset, WORD_SIZE).ok_or_else(|| AbiError::new("data is too short"))
+}
+
+/// Offset or length held by `word`, which can't exceed the size of the data
+/// it's read from.
+fn read_usize(word: &[u8], data: &[u8]) -> AbiResult<usize> {
+    let value = BigUint::from_bytes_be(word);
+    if value > BigUint::from(data.len()) {
+        return Err(AbiError::new("offset is too large"));
+    }
+    let value = value.to_bytes_be();
+    Ok(value.iter().fold(0, |acc, &byte| acc << 8 | byte as usize))
+}
@@ -0,0 +1,156 @@
+//! Annotations of functions and contracts.
+//!
+//! An annotation is written before the item it applies to, e.g. `@inline function f() { }`.
+//! The known annotations are checked once by the symbol table and carried on the
+//! symbols and the compiled functions, where the passes consuming them find them:
+//!
+//! - `@inline` makes the inliner inline every call of a function, whatever its size,
+//! - `@test` marks a function as a test run by the test runner,
+//! - `@deprecated` or `@deprecated("message")` warns at every use of a function or contract.
+
+use crate::symbol_table::{resolve_path, SymbolTable, SymbolUsage};
+use std::fmt;
+use zoker_parser::ast;
+use zoker_parser::ast::ExpressionType;
+use zoker_parser::location::Location;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Annotation {
+    Inline,
+    Test,
+    /// Deprecated item, with a message telling what to use instead
+    Deprecated(Option<String>),
+}
+
+impl fmt::Display for Annotation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Annotation::Inline => write!(f, "@inline"),
+            Annotation::Test => write!(f, "@test"),
+            Annotation::Deprecated(None) => write!(f, "@deprecated"),
+            Annotation::Deprecated(Some(message)) => write!(f, "@deprecated({:?})", message),
+        }
+    }
+}
+
+/// Annotation which can't be applied, with the reason why.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnnotationError {
+    pub error: String,
+    pub location: Location,
+}
+
+/// Checks the annotations of a function, or of a contract if `is_contract`.
+pub fn check_annotations(
+    annotations: &[ast::Annotation],
+    is_contract: bool,
+) -> Result<Vec<Annotation>, AnnotationError> {
+    let mut checked: Vec<Annotation> = vec![];
+    for annotation in annotations {
+        let error = |error: String| AnnotationError {
+            error,
+            location: annotation.location,
+        };
+        let checked_annotation = match annotation.name.as_str() {
+            "inline" | "test" if is_contract => {
+                return Err(error(format!(
+                    "`@{}` only applies to functions",
+                    annotation.name
+                )))
+            }
+            "inline" | "test" if !annotation.arguments.is_empty() => {
+                return Err(error(format!("`@{}` takes no arguments", annotation.name)))
+            }
+            "inline" => Annotation::Inline,
+            "test" => Annotation::Test,
+            "deprecated" => match annotation.arguments.as_slice() {
+                [] => Annotation::Deprecated(None),
+                [ast::Expression {
+                    node: ExpressionType::StringLiteral { value, .. },
+                    ..
+                }] => Annotation::Deprecated(Some(value.clone())),
+                _ => {
+                    return Err(error(String::from(
+                        "`@deprecated` takes an optional message string",
+                    )))
+                }
+            },
+            name => return Err(error(format!("unknown annotation `@{}`", name))),
+        };
+        if checked.iter().any(|other| {
+            std::mem::discriminant(other) == std::mem::discriminant(&checked_annotation)
+        }) {
+            return Err(error(format!(
+                "duplicate annotation `@{}`",
+                annotation.name
+            )));
+        }
+        checked.push(checked_annotation);
+    }
+    Ok(checked)
+}
+
+/// Message of the `@deprecated` annotation among `annotations`, if any.
+pub fn deprecation(annotations: &[Annotation]) -> Option<Option<&str>> {
+    annotations.iter().find_map(|annotation| match annotation {
+        Annotation::Deprecated(message) => Some(message.as_deref()),
+        _ => None,
+    })
+}
+
+/// Warning about the use of a deprecated function or contract.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeprecationWarning {
+    /// Name as used, e.g. `old` or `math::old`
+    pub name: String,
+    pub message: Option<String>,
+    /// First use in its scope
+    pub location: Location,
+}
+
+impl fmt::Display for DeprecationWarning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "`{}` is deprecated", self.name)?;
+        if let Some(message) = &self.message {
+            write!(f, ": {}", message)?;
+        }
+        Ok(())
+    }
+}
+
+/// Warns about the uses of deprecated functions and contracts, once per scope.
+pub fn deprecated_uses(table: &SymbolTable) -> Vec<DeprecationWarning> {
+    let mut warnings = vec![];
+    let mut tables: Vec<&SymbolTable> = vec![];
+    // Tables left to check with the number of tables enclosing them
+    let mut pending = vec![(table, 0)];
+    while let Some((table, depth)) = pending.pop() {
+        tables.truncate(depth);
+        tables.push(table);
+        for symbol in table.symbols.values() {
+            if symbol.usage == SymbolUsage::Declared {
+                continue;
+            }
+            let path: Vec<String> = symbol.name.split("::").map(String::from).collect();
+            let declared = if path.len() > 1 {
+                resolve_path(&tables, &path)
+            } else {
+                tables
+                    .iter()
+                    .rev()
+                    .filter_map(|table| table.lookup(&symbol.name))
+                    .find(|declared| declared.usage == SymbolUsage::Declared)
+            };
+            if let Some(message) = declared.and_then(|declared| deprecation(&declared.annotations))
+            {
+                warnings.push(DeprecationWarning {
+                    name: symbol.name.clone(),
+                    message: message.map(String::from),
+                    location: symbol.location,
+                });
+            }
+        }
+        pending.extend(table.sub_tables.iter().rev().map(|sub| (sub, depth + 1)));
+    }
+    warnings
+}
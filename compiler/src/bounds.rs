@@ -0,0 +1,95 @@
+//! Removes bounds checks which are proven to pass.
+//!
+//! A check of `array[n]` with a constant `n` is proven by an earlier check of the
+//! same array with a constant index of at least `n`, as long as the array can't
+//! have shrunk in between: no assignment to it and no call.
+
+use crate::constant::evaluate;
+use crate::symbol::{Contract, Function, Operation, OperationType};
+use num_bigint::BigUint;
+
+/// Removes proven checks of every function of `contract`, returning how many were removed.
+pub fn eliminate_bounds_checks(contract: &mut Contract) -> usize {
+    contract
+        .functions
+        .iter_mut()
+        .map(eliminate_function_bounds_checks)
+        .sum()
+}
+
+pub fn eliminate_function_bounds_checks(function: &mut Function) -> usize {
+    let mut removed = 0;
+    function.operations = eliminate(std::mem::take(&mut function.operations), &mut removed);
+    removed
+}
+
+fn eliminate(operations: Vec<Operation>, removed: &mut usize) -> Vec<Operation> {
+    // Checked arrays with the largest constant index known to be in range
+    let mut proven: Vec<(Operation, BigUint)> = vec![];
+    let mut result = vec![];
+    for operation in operations {
+        let operation = match operation.operation {
+            OperationType::BoundsCheck { array, index } => {
+                if let Some(index) = evaluate(&index) {
+                    let is_proven = proven
+                        .iter()
+                        .any(|(checked, max)| *checked == *array && index <= *max);
+                    if is_proven {
+                        *removed += 1;
+                        continue;
+                    }
+                    match proven.iter_mut().find(|(checked, _)| *checked == *array) {
+                        Some((_, max)) => *max = index.clone(),
+                        None => proven.push((array.as_ref().clone(), index.clone())),
+                    }
+                }
+                Operation::new(OperationType::BoundsCheck { array, index })
+            }
+            OperationType::If { cond, stmts } => {
+                proven.clear();
+                Operation::new(OperationType::If {
+                    cond,
+                    stmts: eliminate(stmts, removed),
+                })
+            }
+            OperationType::Else { cond, stmts } => {
+                proven.clear();
+                Operation::new(OperationType::Else {
+                    cond,
+                    stmts: eliminate(stmts, removed),
+                })
+            }
+            OperationType::For {
+                iter,
+                vector,
+                stmts,
+            } => {
+                proven.clear();
+                Operation::new(OperationType::For {
+                    iter,
+                    vector,
+                    stmts: eliminate(stmts, removed),
+                })
+            }
+            op => {
+                let operation = Operation::new(op);
+                proven.retain(|(array, _)| !may_shrink(&operation, array));
+                operation
+            }
+        };
+        result.push(operation);
+    }
+    result
+}
+
+fn may_shrink(operation: &Operation, array: &Operation) -> bool {
+    match &operation.operation {
+        OperationType::Assign { left, .. } if left.as_ref() == array => true,
+        // Called functions may pop storage arrays.
+        OperationType::Call { .. } => true,
+        _ => operation
+            .children()
+            .into_iter()
+            .any(|child| may_shrink(child, array)),
+    }
+}
@@ -0,0 +1,305 @@
+use crate::symbol::SymbolType;
+
+/// Namespaces provided by the language itself, e.g. `abi` of `abi.encode`.
+pub const BUILTIN_NAMESPACES: &[&str] = &["abi", "msg"];
+
+/// Functions provided by the language itself.
+/// They are registered in the global symbol table and lowered to dedicated operations.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BuiltinFunction {
+    // Hash functions
+    Keccak256,
+    Sha256,
+    Blake2s,
+    // Signature verification
+    Ecrecover,
+    // ABI encoding functions
+    AbiEncode,
+    AbiEncodePacked,
+    AbiEncodeWithSelector,
+    AbiDecode,
+    // Assertions
+    /// `assert(condition)` or `assert(condition, "message")`, reverts unless the condition holds
+    Assert,
+    /// `require(condition)` or `require(condition, "message")`, reverts unless the condition holds
+    Require,
+    /// `revert()` or `revert("message")`, always reverts
+    Revert,
+    /// `selfdestruct(recipient)`, destroys the contract and sends its balance to the recipient
+    Selfdestruct,
+    // Conversions between integers and field elements
+    /// `uint_to_field(value)`, reverts unless the value is below the prime of the field
+    UintToField,
+    /// `field_to_uint(element)`, the integer value of the element, which always fits
+    FieldToUint,
+}
+
+/// How a builtin interacts with contract state, used by mutability checks.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub enum StateAccess {
+    None,
+    Read,
+    Write,
+}
+
+/// Member provided by the language for values of a type, e.g. `balance` of `address`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BuiltinMember {
+    pub name: String,
+    pub symbol_type: SymbolType,
+    pub state_access: StateAccess,
+}
+
+impl BuiltinMember {
+    fn new(name: &str, symbol_type: SymbolType, state_access: StateAccess) -> Self {
+        BuiltinMember {
+            name: name.to_string(),
+            symbol_type,
+            state_access,
+        }
+    }
+}
+
+/// Member table of `typ`, `None` if values of the type have no members.
+pub fn type_members(typ: &SymbolType) -> Option<Vec<BuiltinMember>> {
+    match typ {
+        SymbolType::Address => Some(vec![
+            BuiltinMember::new("balance", SymbolType::Uint256, StateAccess::Read),
+            BuiltinMember::new(
+                "transfer",
+                SymbolType::Function {
+                    params: vec![SymbolType::Uint256],
+                    returns: vec![],
+                },
+                StateAccess::Write,
+            ),
+            BuiltinMember::new(
+                "send",
+                SymbolType::Function {
+                    params: vec![SymbolType::Uint256],
+                    returns: vec![SymbolType::Bool],
+                },
+                StateAccess::Write,
+            ),
+            BuiltinMember::new(
+                "call",
+                SymbolType::Function {
+                    params: vec![SymbolType::Bytes],
+                    returns: vec![SymbolType::Bool, SymbolType::Bytes],
+                },
+                StateAccess::Write,
+            ),
+        ]),
+        SymbolType::Array(element) => Some(vec![
+            BuiltinMember::new("length", SymbolType::Uint256, StateAccess::Read),
+            BuiltinMember::new(
+                "push",
+                SymbolType::Function {
+                    params: vec![*element.clone()],
+                    returns: vec![],
+                },
+                StateAccess::Write,
+            ),
+            BuiltinMember::new(
+                "pop",
+                SymbolType::Function {
+                    params: vec![],
+                    returns: vec![],
+                },
+                StateAccess::Write,
+            ),
+        ]),
+        // The length of a fixed array is a constant
+        SymbolType::FixedArray(..) => Some(vec![BuiltinMember::new(
+            "length",
+            SymbolType::Uint256,
+            StateAccess::None,
+        )]),
+        // Properties of the message calling the contract
+        SymbolType::Namespace(namespace) if namespace == "msg" => Some(vec![
+            BuiltinMember::new("sender", SymbolType::Address, StateAccess::Read),
+            BuiltinMember::new("value", SymbolType::Uint256, StateAccess::Read),
+            BuiltinMember::new("data", SymbolType::Bytes, StateAccess::Read),
+        ]),
+        SymbolType::Namespace(namespace) => Some(
+            builtin_functions()
+                .into_iter()
+                .filter(|builtin| builtin.namespace() == Some(namespace.as_str()))
+                .map(|builtin| {
+                    let name = builtin.name().rsplit('.').next().unwrap();
+                    BuiltinMember::new(name, builtin.symbol_type(), builtin.state_access())
+                })
+                .collect(),
+        ),
+        _ => None,
+    }
+}
+
+pub fn builtin_functions() -> Vec<BuiltinFunction> {
+    vec![
+        BuiltinFunction::Keccak256,
+        BuiltinFunction::Sha256,
+        BuiltinFunction::Blake2s,
+        BuiltinFunction::Ecrecover,
+        BuiltinFunction::AbiEncode,
+        BuiltinFunction::AbiEncodePacked,
+        BuiltinFunction::AbiEncodeWithSelector,
+        BuiltinFunction::AbiDecode,
+        BuiltinFunction::Assert,
+        BuiltinFunction::Require,
+        BuiltinFunction::Revert,
+        BuiltinFunction::Selfdestruct,
+        BuiltinFunction::UintToField,
+        BuiltinFunction::FieldToUint,
+    ]
+}
+
+impl BuiltinFunction {
+    /// Finds a builtin by its full path such as `sha256` or `abi.encode`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        builtin_functions()
+            .into_iter()
+            .find(|builtin| builtin.name() == name)
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            BuiltinFunction::Keccak256 => "keccak256",
+            BuiltinFunction::Sha256 => "sha256",
+            BuiltinFunction::Blake2s => "blake2s",
+            BuiltinFunction::Ecrecover => "ecrecover",
+            BuiltinFunction::AbiEncode => "abi.encode",
+            BuiltinFunction::AbiEncodePacked => "abi.encodePacked",
+            BuiltinFunction::AbiEncodeWithSelector => "abi.encodeWithSelector",
+            BuiltinFunction::AbiDecode => "abi.decode",
+            BuiltinFunction::Assert => "assert",
+            BuiltinFunction::Require => "require",
+            BuiltinFunction::Revert => "revert",
+            BuiltinFunction::Selfdestruct => "selfdestruct",
+            BuiltinFunction::UintToField => "uint_to_field",
+            BuiltinFunction::FieldToUint => "field_to_uint",
+        }
+    }
+
+    /// Namespace the builtin lives in, `None` for global functions.
+    pub fn namespace(&self) -> Option<&str> {
+        let mut path = self.name().rsplitn(2, '.');
+        path.next();
+        path.next()
+    }
+
+    /// Fixed leading parameters. Variadic builtins accept any number of extra arguments.
+    pub fn params(&self) -> Vec<SymbolType> {
+        match self {
+            BuiltinFunction::Keccak256 | BuiltinFunction::Sha256 | BuiltinFunction::Blake2s => {
+                vec![SymbolType::Bytes]
+            }
+            // `ecrecover(hash, v, r, s)`, where Solidity declares `v` as `uint8`.
+            BuiltinFunction::Ecrecover => vec![
+                SymbolType::Bytes32,
+                SymbolType::Uint256,
+                SymbolType::Bytes32,
+                SymbolType::Bytes32,
+            ],
+            BuiltinFunction::AbiEncode | BuiltinFunction::AbiEncodePacked => vec![],
+            // Solidity uses `bytes4` for selectors, which is stored in a `bytes32` here.
+            BuiltinFunction::AbiEncodeWithSelector => vec![SymbolType::Bytes32],
+            // The second argument is a type list such as `(uint, bool)`.
+            BuiltinFunction::AbiDecode => vec![SymbolType::Bytes, SymbolType::None],
+            // The message is optional.
+            BuiltinFunction::Assert | BuiltinFunction::Require => vec![SymbolType::Bool],
+            BuiltinFunction::Revert => vec![],
+            BuiltinFunction::Selfdestruct => vec![SymbolType::Address],
+            BuiltinFunction::UintToField => vec![SymbolType::Uint256],
+            BuiltinFunction::FieldToUint => vec![SymbolType::Field],
+        }
+    }
+
+    pub fn is_variadic(&self) -> bool {
+        matches!(
+            self,
+            BuiltinFunction::AbiEncode
+                | BuiltinFunction::AbiEncodePacked
+                | BuiltinFunction::AbiEncodeWithSelector
+                | BuiltinFunction::Assert
+                | BuiltinFunction::Require
+                | BuiltinFunction::Revert
+        )
+    }
+
+    /// Return types. `abi.decode` returns the types of its type list, which are not known here.
+    pub fn returns(&self) -> Vec<SymbolType> {
+        match self {
+            BuiltinFunction::Keccak256 | BuiltinFunction::Sha256 | BuiltinFunction::Blake2s => {
+                vec![SymbolType::Bytes32]
+            }
+            BuiltinFunction::Ecrecover => vec![SymbolType::Address],
+            BuiltinFunction::UintToField => vec![SymbolType::Field],
+            BuiltinFunction::FieldToUint => vec![SymbolType::Uint256],
+            BuiltinFunction::AbiEncode
+            | BuiltinFunction::AbiEncodePacked
+            | BuiltinFunction::AbiEncodeWithSelector => vec![SymbolType::Bytes],
+            BuiltinFunction::AbiDecode
+            | BuiltinFunction::Assert
+            | BuiltinFunction::Require
+            | BuiltinFunction::Revert
+            | BuiltinFunction::Selfdestruct => vec![],
+        }
+    }
+
+    pub fn state_access(&self) -> StateAccess {
+        match self {
+            BuiltinFunction::Selfdestruct => StateAccess::Write,
+            _ => StateAccess::None,
+        }
+    }
+
+    /// Whether a call does more than give a value: stops the execution or writes state.
+    pub fn has_side_effects(&self) -> bool {
+        matches!(
+            self,
+            BuiltinFunction::Assert
+                | BuiltinFunction::Require
+                | BuiltinFunction::Revert
+                | BuiltinFunction::Selfdestruct
+        )
+    }
+
+    /// Whether two calls with the same arguments give the same value, so that the second
+    /// can reuse the first. Builtins hashing or encoding bytes read them from memory,
+    /// which may change in between through another reference.
+    pub fn is_pure(&self) -> bool {
+        match self {
+            BuiltinFunction::Ecrecover
+            | BuiltinFunction::UintToField
+            | BuiltinFunction::FieldToUint => true,
+            BuiltinFunction::Keccak256
+            | BuiltinFunction::Sha256
+            | BuiltinFunction::Blake2s
+            | BuiltinFunction::AbiEncode
+            | BuiltinFunction::AbiEncodePacked
+            | BuiltinFunction::AbiEncodeWithSelector
+            | BuiltinFunction::AbiDecode => false,
+            BuiltinFunction::Assert
+            | BuiltinFunction::Require
+            | BuiltinFunction::Revert
+            | BuiltinFunction::Selfdestruct => false,
+        }
+    }
+
+    /// Address of the EVM precompiled contract implementing the builtin, if any.
+    pub fn precompile_address(&self) -> Option<u8> {
+        match self {
+            BuiltinFunction::Ecrecover => Some(0x01),
+            BuiltinFunction::Sha256 => Some(0x02),
+            _ => None,
+        }
+    }
+
+    pub fn symbol_type(&self) -> SymbolType {
+        SymbolType::Function {
+            params: self.params(),
+            returns: self.returns(),
+        }
+    }
+}
@@ -0,0 +1,277 @@
+//! Control-flow graph of a function.
+//!
+//! Basic blocks hold straight-line operations and end with a terminator which
+//! names their successors. `If`, `Else` and `For` operations become branches,
+//! and every `Return` jumps to a single exit block, so the graph always has one
+//! entry and one exit. Operations following a `Return` are unreachable and left out.
+
+use crate::symbol::{Contract, Function, Operation, OperationType};
+use std::fmt;
+
+pub type BlockId = usize;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Terminator {
+    Goto(BlockId),
+    /// Jumps to `then` if `cond` holds, to `otherwise` if not
+    Branch {
+        cond: Operation,
+        then: BlockId,
+        otherwise: BlockId,
+    },
+    /// Header of a `for` loop, runs `body` once per element of `vector` then jumps to `exit`
+    Loop {
+        iter: Operation,
+        vector: Operation,
+        body: BlockId,
+        exit: BlockId,
+    },
+    /// Leaves the function through the exit block
+    Return(Option<Operation>),
+    /// End of the exit block
+    Exit,
+}
+
+impl fmt::Display for Terminator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Terminator::Goto(target) => write!(f, "goto b{}", target),
+            Terminator::Branch { cond, .. } => write!(f, "branch {}", cond),
+            Terminator::Loop { iter, vector, .. } => write!(f, "for {} in {}", iter, vector),
+            Terminator::Return(Some(value)) => write!(f, "return {}", value),
+            Terminator::Return(None) => write!(f, "return"),
+            Terminator::Exit => write!(f, "exit"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BasicBlock {
+    pub id: BlockId,
+    pub operations: Vec<Operation>,
+    pub terminator: Terminator,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ControlFlowGraph {
+    pub function: String,
+    pub blocks: Vec<BasicBlock>,
+    pub entry: BlockId,
+    pub exit: BlockId,
+}
+
+impl ControlFlowGraph {
+    pub fn new(function: &Function) -> Self {
+        let mut builder = Builder { blocks: vec![] };
+        let entry = builder.new_block();
+        if let Some(last) = builder.build(&function.operations, entry) {
+            builder.terminate(last, Terminator::Return(None));
+        }
+        let exit = builder.new_block();
+        builder.terminate(exit, Terminator::Exit);
+        let blocks = builder
+            .blocks
+            .into_iter()
+            .enumerate()
+            .map(|(id, (operations, terminator))| BasicBlock {
+                id,
+                operations,
+                terminator: terminator.expect("every block is terminated"),
+            })
+            .collect();
+        ControlFlowGraph {
+            function: function.name.clone(),
+            blocks,
+            entry,
+            exit,
+        }
+    }
+
+    pub fn block(&self, id: BlockId) -> &BasicBlock {
+        &self.blocks[id]
+    }
+
+    pub fn successors(&self, id: BlockId) -> Vec<BlockId> {
+        match &self.blocks[id].terminator {
+            Terminator::Goto(target) => vec![*target],
+            Terminator::Branch {
+                then, otherwise, ..
+            } => vec![*then, *otherwise],
+            Terminator::Loop { body, exit, .. } => vec![*body, *exit],
+            Terminator::Return(_) => vec![self.exit],
+            Terminator::Exit => vec![],
+        }
+    }
+
+    pub fn predecessors(&self, id: BlockId) -> Vec<BlockId> {
+        self.blocks
+            .iter()
+            .filter(|block| self.successors(block.id).contains(&id))
+            .map(|block| block.id)
+            .collect()
+    }
+
+    /// Edges as `(from, to)` pairs, in block order.
+    pub fn edges(&self) -> Vec<(BlockId, BlockId)> {
+        self.blocks
+            .iter()
+            .flat_map(|block| {
+                self.successors(block.id)
+                    .into_iter()
+                    .map(move |to| (block.id, to))
+            })
+            .collect()
+    }
+
+    /// Renders the graph in Graphviz DOT format.
+    pub fn to_dot(&self) -> String {
+        let mut dot = format!("digraph \"{}\" {{\n", escape(&self.function));
+        dot.push_str("    node [shape=box, fontname=monospace];\n");
+        for block in &self.blocks {
+            let mut label = format!("b{}\\l", block.id);
+            for operation in &block.operations {
+                label.push_str(&escape(&operation.to_string()));
+                label.push_str("\\l");
+            }
+            label.push_str(&escape(&block.terminator.to_string()));
+            label.push_str("\\l");
+            dot.push_str(&format!("    b{} [label=\"{}\"];\n", block.id, label));
+        }
+        for block in &self.blocks {
+            let labels: &[&str] = match block.terminator {
+                Terminator::Branch { .. } => &["true", "false"],
+                Terminator::Loop { .. } => &["body", "done"],
+                _ => &[],
+            };
+            for (i, to) in self.successors(block.id).into_iter().enumerate() {
+                match labels.get(i) {
+                    Some(label) => dot.push_str(&format!(
+                        "    b{} -> b{} [label=\"{}\"];\n",
+                        block.id, to, label
+                    )),
+                    None => dot.push_str(&format!("    b{} -> b{};\n", block.id, to)),
+                }
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+/// Graphs of every function of `contract`, in declaration order.
+pub fn contract_cfgs(contract: &Contract) -> Vec<ControlFlowGraph> {
+    contract
+        .functions
+        .iter()
+        .map(ControlFlowGraph::new)
+        .collect()
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+struct Builder {
+    blocks: Vec<(Vec<Operation>, Option<Terminator>)>,
+}
+
+impl Builder {
+    fn new_block(&mut self) -> BlockId {
+        self.blocks.push((vec![], None));
+        self.blocks.len() - 1
+    }
+
+    fn terminate(&mut self, block: BlockId, terminator: Terminator) {
+        self.blocks[block].1 = Some(terminator);
+    }
+
+    /// Appends `operations` from `current`, returning the block control falls out
+    /// of, or `None` if every path returned.
+    fn build(&mut self, operations: &[Operation], mut current: BlockId) -> Option<BlockId> {
+        let mut operations = operations.iter().peekable();
+        while let Some(operation) = operations.next() {
+            match &operation.operation {
+                OperationType::If { cond, stmts } => {
+                    let otherwise = match operations.peek().map(|op| &op.operation) {
+                        Some(OperationType::Else { stmts, .. }) => {
+                            operations.next();
+                            Some(stmts)
+                        }
+                        _ => None,
+                    };
+                    current = self.branch(current, cond, Some(stmts), otherwise)?;
+                }
+                // `Else` without its `If`, taken when `cond` does not hold
+                OperationType::Else { cond, stmts } => {
+                    current = self.branch(current, cond, None, Some(stmts))?;
+                }
+                OperationType::For {
+                    iter,
+                    vector,
+                    stmts,
+                } => {
+                    let header = self.new_block();
+                    self.terminate(current, Terminator::Goto(header));
+                    let body = self.new_block();
+                    if let Some(last) = self.build(stmts, body) {
+                        self.terminate(last, Terminator::Goto(header));
+                    }
+                    let exit = self.new_block();
+                    self.terminate(
+                        header,
+                        Terminator::Loop {
+                            iter: iter.as_ref().clone(),
+                            vector: vector.as_ref().clone(),
+                            body,
+                            exit,
+                        },
+                    );
+                    current = exit;
+                }
+                OperationType::Return { ret } => {
+                    self.terminate(current, Terminator::Return(Some(ret.as_ref().clone())));
+                    return None;
+                }
+                _ => self.blocks[current].0.push(operation.clone()),
+            }
+        }
+        Some(current)
+    }
+
+    fn branch(
+        &mut self,
+        current: BlockId,
+        cond: &Operation,
+        then: Option<&Vec<Operation>>,
+        otherwise: Option<&Vec<Operation>>,
+    ) -> Option<BlockId> {
+        let mut ends = vec![];
+        let mut arm = |builder: &mut Builder, stmts: Option<&Vec<Operation>>| {
+            let start = builder.new_block();
+            let end = match stmts {
+                Some(stmts) => builder.build(stmts, start),
+                None => Some(start),
+            };
+            ends.extend(end);
+            start
+        };
+        let then = arm(self, then);
+        let otherwise = arm(self, otherwise);
+        self.terminate(
+            current,
+            Terminator::Branch {
+                cond: cond.clone(),
+                then,
+                otherwise,
+            },
+        );
+        if ends.is_empty() {
+            return None;
+        }
+        let join = self.new_block();
+        for end in ends {
+            self.terminate(end, Terminator::Goto(join));
+        }
+        Some(join)
+    }
+}
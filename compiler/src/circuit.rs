@@ -0,0 +1,230 @@
+//! Lint of the control flow depending on private inputs.
+//!
+//! A circuit runs the same constraints whatever its inputs are, so the values of
+//! `private` parameters can't decide which code runs. Variables assigned from them
+//! are private too, in any order as in the taint analysis. The lint reports:
+//!
+//! - branches of `if` and `match` on private values,
+//! - returns inside such branches, which end the function early,
+//! - loops whose number of iterations depends on private values.
+//!
+//! Each is reported at its condition with a rewrite computing every outcome and
+//! selecting the result with arithmetic, which a circuit can express.
+
+use crate::taint::{descendants, propagate, sources};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fmt;
+use zoker_parser::ast;
+use zoker_parser::ast::{ExpressionType, StatementType};
+use zoker_parser::complexity::Node;
+use zoker_parser::location::Location;
+
+/// Control flow which can't be expressed in a circuit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PrivateControlFlow {
+    /// `if` or `match` on a private value
+    Branch,
+    /// Branch on a private value returning from the function
+    EarlyReturn,
+    /// Loop over an array whose length is private, or `do ... while` on a private condition
+    LoopBound,
+}
+
+impl PrivateControlFlow {
+    /// Rewrite of the control flow computing the same result in a circuit.
+    pub fn suggestion(self) -> &'static str {
+        match self {
+            PrivateControlFlow::Branch => {
+                "compute both branches and select the result, e.g. `c * a + (1 - c) * b`"
+            }
+            PrivateControlFlow::EarlyReturn => {
+                "select the returned value, e.g. `c * a + (1 - c) * b`, and return it once at the end"
+            }
+            PrivateControlFlow::LoopBound => {
+                "loop up to a constant bound and select whether each iteration applies"
+            }
+        }
+    }
+}
+
+impl fmt::Display for PrivateControlFlow {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PrivateControlFlow::Branch => write!(f, "branch"),
+            PrivateControlFlow::EarlyReturn => write!(f, "early return"),
+            PrivateControlFlow::LoopBound => write!(f, "loop bound"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CircuitWarning {
+    /// Qualified name of the function, e.g. `Vote.cast`, or the name of a free function
+    pub function: String,
+    pub control_flow: PrivateControlFlow,
+    /// Private parameters the condition depends on, sorted
+    pub sources: Vec<String>,
+    /// Location of the condition, or of the array looped over
+    pub location: Location,
+}
+
+impl fmt::Display for CircuitWarning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let sources: Vec<String> = self
+            .sources
+            .iter()
+            .map(|source| format!("`{}`", source))
+            .collect();
+        write!(
+            f,
+            "{} in `{}` depends on private {} and can't be expressed in a circuit: {}",
+            self.control_flow,
+            self.function,
+            sources.join(", "),
+            self.control_flow.suggestion()
+        )
+    }
+}
+
+/// Control flow depending on private inputs in the functions of `program`.
+pub fn circuit_warnings(program: &ast::Program) -> Vec<CircuitWarning> {
+    let ast::Program::GlobalStatements(statements) = program;
+    let mut warnings = vec![];
+    for statement in statements {
+        match &statement.node {
+            StatementType::ContractStatement {
+                contract_name,
+                members,
+                ..
+            } => {
+                let contract = contract_name.node.identifier_name().unwrap();
+                if let StatementType::MemberStatement { statements } = &members.node {
+                    for member in statements {
+                        analyze_function(Some(&contract), member, &mut warnings);
+                    }
+                }
+            }
+            _ => analyze_function(None, statement, &mut warnings),
+        }
+    }
+    warnings
+}
+
+/// Names of the parameters declared `private`.
+fn private_parameters(parameters: &ast::Expression) -> HashSet<String> {
+    match &parameters.node {
+        ExpressionType::Parameters { parameters } => parameters
+            .iter()
+            .filter_map(|parameter| match &parameter.node {
+                StatementType::InitializerStatement {
+                    is_private: true,
+                    variable: Some(variable),
+                    ..
+                } => variable.node.identifier_name(),
+                _ => None,
+            })
+            .collect(),
+        _ => HashSet::new(),
+    }
+}
+
+/// Whether one of `branches` returns from the function.
+fn returns(branches: Vec<Node>) -> bool {
+    descendants(branches).iter().any(|node| {
+        matches!(
+            node,
+            Node::Statement(ast::Statement {
+                node: StatementType::ReturnStatement { .. },
+                ..
+            })
+        )
+    })
+}
+
+fn analyze_function(
+    contract: Option<&str>,
+    statement: &ast::Statement,
+    warnings: &mut Vec<CircuitWarning>,
+) {
+    let (function_name, parameters, body) = match &statement.node {
+        StatementType::FunctionStatement {
+            function_name,
+            parameters,
+            statement: Some(body),
+            ..
+        } => (function_name, parameters, body),
+        _ => return,
+    };
+    let private = private_parameters(parameters);
+    if private.is_empty() {
+        return;
+    }
+    let name = function_name.node.identifier_name().unwrap();
+    let function = match contract {
+        Some(contract) => format!("{}.{}", contract, name),
+        None => name,
+    };
+    let mut tainted: HashMap<String, BTreeSet<String>> = private
+        .iter()
+        .map(|name| (name.clone(), BTreeSet::from([name.clone()])))
+        .collect();
+    let nodes = descendants(vec![Node::Statement(body)]);
+    propagate(&nodes, &mut tainted);
+
+    for node in &nodes {
+        let (control_flow, condition) = match node {
+            Node::Expression(ast::Expression {
+                node:
+                    ExpressionType::IfExpression {
+                        condition,
+                        if_statement,
+                        else_statement,
+                    },
+                ..
+            }) => {
+                let mut branches = vec![Node::Statement(if_statement)];
+                branches.extend(else_statement.as_deref().map(Node::Statement));
+                if returns(branches) {
+                    (PrivateControlFlow::EarlyReturn, condition)
+                } else {
+                    (PrivateControlFlow::Branch, condition)
+                }
+            }
+            Node::Expression(ast::Expression {
+                node: ExpressionType::MatchExpression { value, arms },
+                ..
+            }) => {
+                let branches = arms
+                    .iter()
+                    .map(|arm| Node::Statement(&arm.statement))
+                    .collect();
+                if returns(branches) {
+                    (PrivateControlFlow::EarlyReturn, value)
+                } else {
+                    (PrivateControlFlow::Branch, value)
+                }
+            }
+            Node::Expression(ast::Expression {
+                node: ExpressionType::ForEachExpression { vector, .. },
+                ..
+            }) => (PrivateControlFlow::LoopBound, vector),
+            Node::Statement(ast::Statement {
+                node: StatementType::DoWhileStatement { condition, .. },
+                ..
+            }) => (PrivateControlFlow::LoopBound, condition),
+            _ => continue,
+        };
+        let data: Vec<String> = sources(condition, &tainted)
+            .into_iter()
+            .filter(|source| private.contains(source))
+            .collect();
+        if !data.is_empty() {
+            warnings.push(CircuitWarning {
+                function: function.clone(),
+                control_flow,
+                sources: data,
+                location: condition.location,
+            });
+        }
+    }
+}
@@ -0,0 +1,221 @@
+//! Bytecode size estimation of contracts.
+//!
+//! There is no EVM code generator yet, so sizes are estimated from the IR with
+//! the instructions a straightforward lowering emits for each operation, e.g.
+//! `PUSH2 dest JUMPI` for a branch. Estimates are meant to compare functions
+//! and to warn early about contracts approaching the EIP-170 limit. Zeros are
+//! pushed with `PUSH0` from Shanghai on, and with `PUSH1 0` before it.
+
+use crate::settings::EvmVersion;
+use crate::symbol::{Contract, Function, Operation, OperationType};
+use num_bigint::BigUint;
+use num_traits::Zero;
+use std::fmt;
+
+/// Maximum size of the runtime code of a contract, see EIP-170.
+pub const MAX_RUNTIME_SIZE: usize = 24_576;
+
+/// Loads the selector from calldata: `PUSH0 CALLDATALOAD PUSH1 0xe0 SHR`, without the `PUSH0`.
+const DISPATCHER_SIZE: usize = 4;
+/// Matches a selector: `DUP1 PUSH4 selector EQ PUSH2 dest JUMPI`.
+const DISPATCH_ENTRY_SIZE: usize = 11;
+/// Copies the runtime code and returns it: `PUSH2 size DUP1 PUSH2 offset PUSH0 CODECOPY PUSH0 RETURN`,
+/// without the `PUSH0`s.
+const CONSTRUCTOR_SIZE: usize = 10;
+/// `PUSH2 dest` followed by `JUMP` or `JUMPI`.
+const JUMP_SIZE: usize = 4;
+/// Stores the value on top and returns it: `PUSH0 MSTORE PUSH1 32 PUSH0 RETURN`, without the `PUSH0`s.
+const RETURN_SIZE: usize = 5;
+/// Hashes or calls a precompile with arguments stored in memory.
+const BUILTIN_SIZE: usize = 12;
+
+/// Size of the `PUSH` instruction of `value`, `PUSH0` for zero if `evm_version` has it.
+pub fn push_size(value: &BigUint, evm_version: EvmVersion) -> usize {
+    if value.is_zero() {
+        evm_version.push_zero_size()
+    } else {
+        1 + value.bits().div_ceil(8)
+    }
+}
+
+/// Whether `operation` is a state variable, in storage or transient storage.
+fn is_storage(operation: &Operation) -> bool {
+    matches!(
+        operation.as_symbol(),
+        Some(symbol) if symbol.data_location.is_state()
+    )
+}
+
+/// Estimated size in bytes of the code evaluating `operation`.
+pub fn operation_size(operation: &Operation, evm_version: EvmVersion) -> usize {
+    let size = |operation: &Operation| operation_size(operation, evm_version);
+    let block_size = |operations: &[Operation]| block_size(operations, evm_version);
+    let children: usize = operation.children().into_iter().map(size).sum();
+    match &operation.operation {
+        // `PUSH1 slot SLOAD`, `PUSH1 slot TLOAD` or `DUPn`
+        OperationType::Symbol { .. } if is_storage(operation) => 3,
+        OperationType::Symbol { .. } => 1,
+        OperationType::Constant { value } => push_size(value, evm_version),
+        OperationType::Add { .. }
+        | OperationType::Sub { .. }
+        | OperationType::Mul { .. }
+        | OperationType::Div { .. }
+        | OperationType::Pow { .. }
+        | OperationType::Shl { .. }
+        | OperationType::Shr { .. } => children + 1,
+        OperationType::Assign { left, right } => {
+            let store = match left.operation {
+                // `PUSH1 slot SSTORE` or `PUSH1 slot TSTORE`
+                _ if is_storage(left) => 3,
+                // `SWAPn POP`
+                OperationType::Symbol { .. } => 2,
+                // Address of the element then `MSTORE`
+                _ => size(left) + 1,
+            };
+            size(right) + store
+        }
+        // `PUSH1 32 MUL ADD MLOAD`
+        OperationType::Index { .. } => children + 5,
+        // `DUP2 MLOAD LT ISZERO PUSH2 revert JUMPI`
+        OperationType::BoundsCheck { .. } => children + 4 + JUMP_SIZE,
+        // Return address, jump to the function and `JUMPDEST` to return to
+        OperationType::Call { .. } => children + 2 * JUMP_SIZE,
+        OperationType::Builtin { .. } => children + BUILTIN_SIZE,
+        // `ISZERO PUSH2 end JUMPI ... JUMPDEST`
+        OperationType::If { cond, stmts } => size(cond) + 1 + JUMP_SIZE + block_size(stmts) + 1,
+        // Jump over the else branch at the end of the `If`, the condition isn't evaluated again
+        OperationType::Else { stmts, .. } => JUMP_SIZE + block_size(stmts) + 1,
+        // Counter, length check and the jump back
+        OperationType::For { vector, stmts, .. } => {
+            size(vector) + 12 + 2 * JUMP_SIZE + block_size(stmts) + 2
+        }
+        OperationType::Return { ret } => size(ret) + RETURN_SIZE + 2 * evm_version.push_zero_size(),
+        OperationType::Nop => 0,
+    }
+}
+
+fn block_size(operations: &[Operation], evm_version: EvmVersion) -> usize {
+    operations
+        .iter()
+        .map(|operation| operation_size(operation, evm_version))
+        .sum()
+}
+
+/// Estimated size of the entry of `function` in the dispatcher.
+fn dispatch_entry_size(function: &Function) -> usize {
+    // Free functions are only called internally
+    if function.is_free {
+        0
+    } else {
+        DISPATCH_ENTRY_SIZE
+    }
+}
+
+/// Estimated size of the dispatcher of `contract`, which comes first in its runtime code.
+pub fn dispatcher_size(contract: &Contract, evm_version: EvmVersion) -> usize {
+    DISPATCHER_SIZE
+        + evm_version.push_zero_size()
+        + contract
+            .functions
+            .iter()
+            .map(dispatch_entry_size)
+            .sum::<usize>()
+}
+
+/// Estimated size of the body of `function`, from its `JUMPDEST`.
+pub fn body_size(function: &Function, evm_version: EvmVersion) -> usize {
+    1 + block_size(&function.operations, evm_version)
+}
+
+/// Estimated size of `function` including its entry in the dispatcher.
+pub fn function_size(function: &Function, evm_version: EvmVersion) -> usize {
+    dispatch_entry_size(function) + body_size(function, evm_version)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContractSize {
+    pub contract: String,
+    pub creation: usize,
+    pub runtime: usize,
+    /// Size of each function, largest first
+    pub functions: Vec<(String, usize)>,
+}
+
+impl ContractSize {
+    pub fn exceeds_limit(&self) -> bool {
+        self.runtime > MAX_RUNTIME_SIZE
+    }
+}
+
+impl fmt::Display for ContractSize {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "{}: creation {} bytes, runtime {} bytes",
+            self.contract, self.creation, self.runtime
+        )?;
+        for (function, size) in &self.functions {
+            writeln!(f, "  {}: {} bytes", function, size)?;
+        }
+        Ok(())
+    }
+}
+
+/// Estimated sizes of `contract` compiled for `evm_version`.
+pub fn contract_size(contract: &Contract, evm_version: EvmVersion) -> ContractSize {
+    let mut functions: Vec<(String, usize)> = contract
+        .functions
+        .iter()
+        .map(|function| (function.name.clone(), function_size(function, evm_version)))
+        .collect();
+    let runtime = DISPATCHER_SIZE
+        + evm_version.push_zero_size()
+        + functions.iter().map(|(_, size)| size).sum::<usize>();
+    functions.sort_by(|(_, a), (_, b)| b.cmp(a));
+    ContractSize {
+        contract: contract.name.clone(),
+        creation: CONSTRUCTOR_SIZE + 2 * evm_version.push_zero_size() + runtime,
+        runtime,
+        functions,
+    }
+}
+
+/// Warning about a contract whose runtime code can't be deployed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SizeWarning {
+    pub contract: String,
+    pub runtime: usize,
+    /// Functions of the contract, largest first
+    pub largest: Vec<(String, usize)>,
+}
+
+impl fmt::Display for SizeWarning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "runtime code of `{}` is {} bytes, over the EIP-170 limit of {} bytes",
+            self.contract, self.runtime, MAX_RUNTIME_SIZE
+        )?;
+        if !self.largest.is_empty() {
+            let largest: Vec<String> = self
+                .largest
+                .iter()
+                .map(|(function, size)| format!("`{}` ({} bytes)", function, size))
+                .collect();
+            write!(f, ", largest functions: {}", largest.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// Warns if `size` is over the EIP-170 limit, naming its three largest functions.
+pub fn check_code_size(size: &ContractSize) -> Option<SizeWarning> {
+    if !size.exceeds_limit() {
+        return None;
+    }
+    Some(SizeWarning {
+        contract: size.contract.clone(),
+        runtime: size.runtime,
+        largest: size.functions.iter().take(3).cloned().collect(),
+    })
+}
@@ -0,0 +1,255 @@
+//! Implicit conversions between types.
+//!
+//! An integer converts implicitly to an integer of the same signedness which
+//! is at least as wide, e.g. `uint8` to `uint16`, but never between signed and
+//! unsigned integers. A number literal converts to any integer type which can
+//! hold its value. Other types only convert to themselves.
+//!
+//! Number literals and constant expressions of them, e.g. `-1` or `2 ** 8`,
+//! are untyped integers: they adopt the type imposed by their context, the
+//! declared variable, the parameter or the other operand, and fall back to
+//! `uint256` (`int256` if negative) without one.
+//!
+//! String literals are `string`s which also convert to `bytes`, and to `bytes32`
+//! if they hold at most 32 bytes.
+//!
+//! Integers and `field` elements don't convert implicitly, as integers from the
+//! prime up would alias smaller elements. They convert with the `uint_to_field`
+//! and `field_to_uint` builtins, while number literals below the prime are
+//! elements too.
+
+use crate::field::field_modulus;
+use crate::symbol::{Operation, OperationType, SymbolType};
+use num_bigint::{BigInt, Sign};
+use num_traits::{One, Pow, Signed, ToPrimitive, Zero};
+use std::fmt;
+use zoker_parser::ast::{Expression, ExpressionType, Operator};
+
+/// Largest untyped integers evaluated, much larger than any type so that they are still rejected.
+const MAX_LITERAL_BITS: usize = 1024;
+
+/// Signedness and width in bits of an integer type.
+pub fn integer_bits(typ: &SymbolType) -> Option<(bool, u16)> {
+    match typ {
+        SymbolType::Uint256 => Some((false, 256)),
+        SymbolType::Int256 => Some((true, 256)),
+        SymbolType::Uint(bits) => Some((false, *bits)),
+        SymbolType::Int(bits) => Some((true, *bits)),
+        _ => None,
+    }
+}
+
+/// Signedness and width of the widest integer symbol among the operands of an
+/// arithmetic operation, `None` if it only combines literals.
+pub fn operand_bits(operation: &Operation) -> Option<(bool, u16)> {
+    match &operation.operation {
+        OperationType::Symbol { symbol } => integer_bits(&symbol.symbol_type),
+        OperationType::Add { .. }
+        | OperationType::Sub { .. }
+        | OperationType::Mul { .. }
+        | OperationType::Div { .. }
+        | OperationType::Pow { .. }
+        | OperationType::Shl { .. }
+        | OperationType::Shr { .. } => operation
+            .children()
+            .into_iter()
+            .filter_map(operand_bits)
+            .max_by_key(|(_, bits)| *bits),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConversionErrorKind {
+    /// The target type is narrower
+    Narrowing,
+    /// One of the types is signed and the other unsigned
+    Signedness,
+    /// The literal doesn't fit in the target type
+    OutOfRange,
+    /// The types are unrelated, e.g. `bool` and `uint`
+    Incompatible,
+    /// An integer and a `field` element, which only convert explicitly
+    Field,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConversionError {
+    /// Source type, or the value of a literal
+    pub from: String,
+    pub to: SymbolType,
+    pub kind: ConversionErrorKind,
+}
+
+impl ConversionError {
+    /// Why the conversion is rejected, unless the types are simply unrelated.
+    pub fn reason(&self) -> Option<&'static str> {
+        match self.kind {
+            ConversionErrorKind::Narrowing => Some("narrowing conversion may lose bits"),
+            ConversionErrorKind::Signedness => Some("signed and unsigned integers don't mix"),
+            ConversionErrorKind::OutOfRange => Some("the value is out of range"),
+            ConversionErrorKind::Incompatible => None,
+            ConversionErrorKind::Field => {
+                Some("convert with `uint_to_field` or `field_to_uint`, which check the range")
+            }
+        }
+    }
+}
+
+impl fmt::Display for ConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "cannot implicitly convert `{}` to `{}`",
+            self.from, self.to
+        )?;
+        if let Some(reason) = self.reason() {
+            write!(f, ": {}", reason)?;
+        }
+        Ok(())
+    }
+}
+
+/// Checks that a value of type `from` can be used where `to` is expected.
+pub fn implicit_conversion(from: &SymbolType, to: &SymbolType) -> Result<(), ConversionError> {
+    if from == to {
+        return Ok(());
+    }
+    let error = |kind| {
+        Err(ConversionError {
+            from: from.to_string(),
+            to: to.clone(),
+            kind,
+        })
+    };
+    match (integer_bits(from), integer_bits(to)) {
+        (Some((from_signed, _)), Some((to_signed, _))) if from_signed != to_signed => {
+            error(ConversionErrorKind::Signedness)
+        }
+        (Some((_, from_bits)), Some((_, to_bits))) if from_bits > to_bits => {
+            error(ConversionErrorKind::Narrowing)
+        }
+        (Some(_), Some(_)) => Ok(()),
+        (Some(_), None) if *to == SymbolType::Field => error(ConversionErrorKind::Field),
+        (None, Some(_)) if *from == SymbolType::Field => error(ConversionErrorKind::Field),
+        _ => error(ConversionErrorKind::Incompatible),
+    }
+}
+
+/// Value of an untyped integer: a number literal or a constant expression of them.
+/// `None` if `expression` isn't one or can't be evaluated, e.g. on division by zero.
+pub fn literal_value(expression: &Expression) -> Option<BigInt> {
+    fold_literal(expression, literal_value)
+}
+
+/// Value of `expression` as `literal_value`, with the values of its operands given by `operand`.
+pub fn fold_literal(
+    expression: &Expression,
+    operand: impl Fn(&Expression) -> Option<BigInt>,
+) -> Option<BigInt> {
+    match &expression.node {
+        ExpressionType::Number { value, .. } => {
+            Some(BigInt::from_biguint(Sign::Plus, value.clone()))
+        }
+        ExpressionType::UnaryExpression {
+            operator,
+            expression,
+        } => match operator {
+            Operator::Minus => Some(-operand(expression)?),
+            Operator::Plus => operand(expression),
+            _ => None,
+        },
+        ExpressionType::BinaryExpression {
+            left,
+            operator,
+            right,
+        } => {
+            let left = operand(left)?;
+            let right = operand(right)?;
+            match operator {
+                Operator::Add => Some(left + right),
+                Operator::Sub => Some(left - right),
+                Operator::Mul => Some(left * right),
+                Operator::Div if !right.is_zero() => Some(left / right),
+                Operator::Mod if !right.is_zero() => Some(left % right),
+                Operator::Pow if !right.is_negative() => {
+                    let exponent = right
+                        .to_u32()
+                        .filter(|e| left.bits() * *e as usize <= MAX_LITERAL_BITS)?;
+                    Some(left.pow(exponent))
+                }
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Type of an untyped integer without context.
+pub fn literal_type(value: &BigInt) -> SymbolType {
+    if value.is_negative() {
+        SymbolType::Int256
+    } else {
+        SymbolType::Uint256
+    }
+}
+
+/// Checks that the untyped integer `value` can be used where `to` is expected.
+pub fn literal_conversion(value: &BigInt, to: &SymbolType) -> Result<(), ConversionError> {
+    let error = |kind| {
+        Err(ConversionError {
+            from: value.to_string(),
+            to: to.clone(),
+            kind,
+        })
+    };
+    match integer_bits(to) {
+        Some((signed, bits)) => {
+            let (min, max) = if signed {
+                let bound = BigInt::one() << (bits as usize - 1);
+                (-bound.clone(), bound)
+            } else {
+                (BigInt::zero(), BigInt::one() << bits as usize)
+            };
+            if min <= *value && *value < max {
+                Ok(())
+            } else {
+                error(ConversionErrorKind::OutOfRange)
+            }
+        }
+        None if *to == SymbolType::Field => {
+            if !value.is_negative() && *value < BigInt::from(field_modulus()) {
+                Ok(())
+            } else {
+                error(ConversionErrorKind::OutOfRange)
+            }
+        }
+        None => error(ConversionErrorKind::Incompatible),
+    }
+}
+
+/// Checks that the string literal `value` can be used where `to` is expected.
+pub fn string_literal_conversion(value: &str, to: &SymbolType) -> Result<(), ConversionError> {
+    let error = |kind| {
+        Err(ConversionError {
+            from: format!("{:?}", value),
+            to: to.clone(),
+            kind,
+        })
+    };
+    match to {
+        SymbolType::String | SymbolType::Bytes => Ok(()),
+        SymbolType::Bytes32 if value.len() <= 32 => Ok(()),
+        SymbolType::Bytes32 => error(ConversionErrorKind::OutOfRange),
+        _ => error(ConversionErrorKind::Incompatible),
+    }
+}
+
+/// Type both operands of a binary operation convert to, the wider of the two.
+pub fn common_type(left: &SymbolType, right: &SymbolType) -> Result<SymbolType, ConversionError> {
+    match implicit_conversion(left, right) {
+        Ok(()) => Ok(right.clone()),
+        Err(error) if error.kind == ConversionErrorKind::Narrowing => Ok(left.clone()),
+        Err(error) => Err(error),
+    }
+}
@@ -0,0 +1,261 @@
+//! Compatibility of the public interface of two versions of a program.
+//!
+//! An upgraded contract must keep the functions its callers use, with the same
+//! parameter and return types, and the storage layout of the state written by
+//! the previous version. Adding functions or appending state variables is
+//! compatible. Types are compared by their canonical ABI names, so `uint` and
+//! `uint256` are the same type.
+
+use crate::abi::{canonical_type, signature};
+use crate::driver::Artifact;
+use crate::storage::{storage_layout, StorageEntry, StorageLayout};
+use crate::symbol::SymbolType;
+use crate::symbol_table::{
+    make_symbol_table, SymbolTable, SymbolTableResult, SymbolTableType, SymbolUsage,
+};
+use std::fmt;
+use zoker_parser::ast;
+use zoker_parser::ast::CONSTRUCTOR;
+
+/// Function callable on a contract.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FunctionInterface {
+    pub name: String,
+    pub params: Vec<SymbolType>,
+    pub returns: Vec<SymbolType>,
+}
+
+impl FunctionInterface {
+    /// Canonical signature, e.g. `transfer(address,uint256)`.
+    pub fn signature(&self) -> String {
+        signature(&self.name, &self.params)
+    }
+
+    fn returns_signature(&self) -> String {
+        let returns: Vec<String> = self.returns.iter().map(canonical_type).collect();
+        format!("({})", returns.join(","))
+    }
+}
+
+/// Public interface of a contract.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContractInterface {
+    pub name: String,
+    pub functions: Vec<FunctionInterface>,
+    pub storage: StorageLayout,
+}
+
+/// Interfaces of the contracts declared in `table`.
+pub fn table_interfaces(table: &SymbolTable) -> Vec<ContractInterface> {
+    table
+        .sub_tables
+        .iter()
+        .filter(|contract| contract.table_type == SymbolTableType::Contract)
+        .map(|contract| ContractInterface {
+            name: contract.name.clone(),
+            functions: contract
+                .symbols
+                .values()
+                .filter(|symbol| {
+                    symbol.usage == SymbolUsage::Declared && symbol.name != CONSTRUCTOR
+                })
+                // Public state variables are called through their getters
+                .filter_map(|symbol| match symbol.callable_type()? {
+                    SymbolType::Function { params, returns } => Some(FunctionInterface {
+                        name: symbol.name.clone(),
+                        params,
+                        returns,
+                    }),
+                    _ => None,
+                })
+                .collect(),
+            storage: storage_layout(table, &contract.name).unwrap(),
+        })
+        .collect()
+}
+
+/// Interface of the contract compiled into `artifact`.
+pub fn artifact_interface(artifact: &Artifact) -> ContractInterface {
+    let types = |symbols: &[crate::symbol::Symbol]| {
+        symbols
+            .iter()
+            .map(|symbol| symbol.symbol_type.clone())
+            .collect()
+    };
+    ContractInterface {
+        name: artifact.contract.name.clone(),
+        functions: artifact
+            .contract
+            .functions
+            .iter()
+            .filter(|function| function.name != CONSTRUCTOR)
+            .map(|function| FunctionInterface {
+                name: function.name.clone(),
+                params: types(&function.params),
+                returns: types(&function.returns),
+            })
+            .collect(),
+        storage: artifact.storage.clone(),
+    }
+}
+
+/// Change of the interface of a contract which breaks its callers or its state.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BreakingChange {
+    RemovedContract {
+        contract: String,
+    },
+    RemovedFunction {
+        contract: String,
+        signature: String,
+    },
+    /// Function kept with other parameter types, which changes its selector
+    ChangedParameters {
+        contract: String,
+        old: String,
+        new: String,
+    },
+    ChangedReturns {
+        contract: String,
+        function: String,
+        old: String,
+        new: String,
+    },
+    RemovedStateVariable {
+        contract: String,
+        name: String,
+    },
+    /// State variable moved to another slot or offset, or of another type
+    ChangedStateVariable {
+        contract: String,
+        old: StorageEntry,
+        new: StorageEntry,
+    },
+}
+
+fn entry_to_string(entry: &StorageEntry) -> String {
+    format!(
+        "{} at slot {} offset {}",
+        canonical_type(&entry.symbol_type),
+        entry.slot,
+        entry.offset
+    )
+}
+
+impl fmt::Display for BreakingChange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BreakingChange::RemovedContract { contract } => {
+                write!(f, "contract `{}` was removed", contract)
+            }
+            BreakingChange::RemovedFunction {
+                contract,
+                signature,
+            } => write!(f, "`{}.{}` was removed", contract, signature),
+            BreakingChange::ChangedParameters { contract, old, new } => {
+                write!(f, "`{}.{}` became `{}`", contract, old, new)
+            }
+            BreakingChange::ChangedReturns {
+                contract,
+                function,
+                old,
+                new,
+            } => write!(
+                f,
+                "`{}.{}` returns `{}` instead of `{}`",
+                contract, function, new, old
+            ),
+            BreakingChange::RemovedStateVariable { contract, name } => {
+                write!(f, "state variable `{}.{}` was removed", contract, name)
+            }
+            BreakingChange::ChangedStateVariable { contract, old, new } => write!(
+                f,
+                "state variable `{}.{}` moved from {} to {}",
+                contract,
+                old.name,
+                entry_to_string(old),
+                entry_to_string(new)
+            ),
+        }
+    }
+}
+
+/// Breaking changes from the interfaces `old` to `new`, contract by contract.
+pub fn compare_interfaces(
+    old: &[ContractInterface],
+    new: &[ContractInterface],
+) -> Vec<BreakingChange> {
+    let mut changes = vec![];
+    for old in old {
+        let contract = old.name.clone();
+        let new = match new.iter().find(|new| new.name == old.name) {
+            Some(new) => new,
+            None => {
+                changes.push(BreakingChange::RemovedContract { contract });
+                continue;
+            }
+        };
+        for function in &old.functions {
+            let updated = new.functions.iter().find(|f| f.name == function.name);
+            match updated {
+                None => changes.push(BreakingChange::RemovedFunction {
+                    contract: contract.clone(),
+                    signature: function.signature(),
+                }),
+                Some(updated) if updated.signature() != function.signature() => {
+                    changes.push(BreakingChange::ChangedParameters {
+                        contract: contract.clone(),
+                        old: function.signature(),
+                        new: updated.signature(),
+                    })
+                }
+                Some(updated) if updated.returns_signature() != function.returns_signature() => {
+                    changes.push(BreakingChange::ChangedReturns {
+                        contract: contract.clone(),
+                        function: function.name.clone(),
+                        old: function.returns_signature(),
+                        new: updated.returns_signature(),
+                    })
+                }
+                Some(_) => {}
+            }
+        }
+        for entry in &old.storage.entries {
+            match new.storage.entry(&entry.name) {
+                None => changes.push(BreakingChange::RemovedStateVariable {
+                    contract: contract.clone(),
+                    name: entry.name.clone(),
+                }),
+                Some(updated)
+                    if updated.slot != entry.slot
+                        || updated.offset != entry.offset
+                        || canonical_type(&updated.symbol_type)
+                            != canonical_type(&entry.symbol_type) =>
+                {
+                    changes.push(BreakingChange::ChangedStateVariable {
+                        contract: contract.clone(),
+                        old: entry.clone(),
+                        new: updated.clone(),
+                    })
+                }
+                Some(_) => {}
+            }
+        }
+    }
+    changes
+}
+
+/// Breaking changes from the program `old` to `new`.
+pub fn compare_programs(
+    old: &ast::Program,
+    new: &ast::Program,
+) -> SymbolTableResult<Vec<BreakingChange>> {
+    let old = table_interfaces(&make_symbol_table(old)?);
+    let new = table_interfaces(&make_symbol_table(new)?);
+    Ok(compare_interfaces(&old, &new))
+}
+
+/// Breaking changes from the contract compiled into `old` to the one in `new`.
+pub fn compare_artifacts(old: &Artifact, new: &Artifact) -> Vec<BreakingChange> {
+    compare_interfaces(&[artifact_interface(old)], &[artifact_interface(new)])
+}
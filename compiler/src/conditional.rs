@@ -0,0 +1,98 @@
+//! Conditional compilation.
+//!
+//! Contracts, their members, free functions and the members of modules can be
+//! marked `#[cfg(..)]`. The condition is evaluated against the defines of the
+//! compilation, and items whose condition doesn't hold are dropped before the
+//! symbol table is built:
+//!
+//! - `#[cfg(debug)]` holds if `debug` is defined,
+//! - `#[cfg(feature = "testnet")]` holds if `feature` is defined as `testnet`,
+//! - `not(..)`, `all(..)` and `any(..)` combine conditions.
+
+use std::collections::BTreeSet;
+use zoker_parser::ast::{CfgPredicate, Program, Statement, StatementType};
+
+/// Names defined for a compilation.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Defines {
+    /// Names defined without a value, e.g. `debug`
+    pub flags: BTreeSet<String>,
+    /// Names defined with a value, e.g. `feature = "testnet"`. A name can have several values.
+    pub values: BTreeSet<(String, String)>,
+}
+
+impl Defines {
+    /// Defines enabling the features `features`, e.g. `feature = "testnet"`.
+    pub fn with_features(features: &[&str]) -> Self {
+        Defines {
+            flags: BTreeSet::new(),
+            values: features
+                .iter()
+                .map(|feature| (String::from("feature"), feature.to_string()))
+                .collect(),
+        }
+    }
+}
+
+/// Whether `predicate` holds with `defines`.
+pub fn is_enabled(predicate: &CfgPredicate, defines: &Defines) -> bool {
+    match predicate {
+        CfgPredicate::Flag(name) => defines.flags.contains(name),
+        CfgPredicate::Value { name, value } => {
+            defines.values.contains(&(name.clone(), value.clone()))
+        }
+        CfgPredicate::Not(predicate) => !is_enabled(predicate, defines),
+        CfgPredicate::All(predicates) => predicates.iter().all(|p| is_enabled(p, defines)),
+        CfgPredicate::Any(predicates) => predicates.iter().any(|p| is_enabled(p, defines)),
+    }
+}
+
+/// Drops the items of `program` disabled with `defines` and unwraps the enabled ones,
+/// returning how many items were dropped.
+pub fn strip_disabled(program: &mut Program, defines: &Defines) -> usize {
+    let Program::GlobalStatements(statements) = program;
+    strip_items(statements, defines)
+}
+
+fn strip_items(items: &mut Vec<Statement>, defines: &Defines) -> usize {
+    let mut dropped = 0;
+    let mut kept = vec![];
+    for item in items.drain(..) {
+        let mut item = match enabled_item(item, defines) {
+            Some(item) => item,
+            None => {
+                dropped += 1;
+                continue;
+            }
+        };
+        match &mut item.node {
+            StatementType::ContractStatement { members, .. } => {
+                if let StatementType::MemberStatement { statements } = &mut members.node {
+                    dropped += strip_items(statements, defines);
+                }
+            }
+            StatementType::ModuleStatement { members, .. } => {
+                dropped += strip_items(members, defines);
+            }
+            _ => {}
+        }
+        kept.push(item);
+    }
+    *items = kept;
+    dropped
+}
+
+/// `item` without its attributes if all of them hold.
+fn enabled_item(mut item: Statement, defines: &Defines) -> Option<Statement> {
+    while let StatementType::ConditionalStatement {
+        condition,
+        statement,
+    } = item.node
+    {
+        if !is_enabled(&condition, defines) {
+            return None;
+        }
+        item = *statement;
+    }
+    Some(item)
+}
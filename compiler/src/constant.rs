@@ -0,0 +1,85 @@
+//! Evaluation of operations whose value is known at compile time.
+
+use crate::symbol::{Operation, OperationType};
+use num_bigint::BigUint;
+use num_traits::{Pow, ToPrimitive, Zero};
+use zoker_parser::ast::{Expression, ExpressionType, Operator};
+
+/// Exponents and shifts beyond this aren't evaluated, their result would be huge.
+const MAX_EXPONENT: usize = 256;
+
+/// Value of `operation` if it only combines constants, e.g. `2 * (3 + 1)`.
+/// Subtractions below zero and divisions by zero aren't constant, they fail at runtime.
+pub fn evaluate(operation: &Operation) -> Option<BigUint> {
+    match &operation.operation {
+        OperationType::Constant { value } => Some(value.clone()),
+        OperationType::Add { left, right } => Some(evaluate(left)? + evaluate(right)?),
+        OperationType::Sub { left, right } => {
+            let (left, right) = (evaluate(left)?, evaluate(right)?);
+            if left >= right {
+                Some(left - right)
+            } else {
+                None
+            }
+        }
+        OperationType::Mul { left, right } => Some(evaluate(left)? * evaluate(right)?),
+        OperationType::Div { left, right } => {
+            let (left, right) = (evaluate(left)?, evaluate(right)?);
+            if right.is_zero() {
+                None
+            } else {
+                Some(left / right)
+            }
+        }
+        OperationType::Pow { left, right } => Some(evaluate(left)?.pow(exponent(right)?)),
+        OperationType::Shl { left, right } => Some(evaluate(left)? << exponent(right)?),
+        OperationType::Shr { left, right } => Some(evaluate(left)? >> exponent(right)?),
+        _ => None,
+    }
+}
+
+fn exponent(operation: &Operation) -> Option<usize> {
+    evaluate(operation)?
+        .to_usize()
+        .filter(|exponent| *exponent <= MAX_EXPONENT)
+}
+
+/// Value of an expression of number literals and declared constants, e.g. `2 * N`,
+/// lowered to operations and evaluated like them. `constant` gives the value of a constant.
+pub fn evaluate_expression(
+    expression: &Expression,
+    constant: &dyn Fn(&str) -> Option<BigUint>,
+) -> Option<BigUint> {
+    evaluate(&lower(expression, constant)?)
+}
+
+fn lower(expression: &Expression, constant: &dyn Fn(&str) -> Option<BigUint>) -> Option<Operation> {
+    let operation = match &expression.node {
+        ExpressionType::Number { value, .. } => OperationType::Constant {
+            value: value.clone(),
+        },
+        ExpressionType::Identifier { value } => OperationType::Constant {
+            value: constant(value)?,
+        },
+        ExpressionType::BinaryExpression {
+            left,
+            operator,
+            right,
+        } => {
+            let left = Box::new(lower(left, constant)?);
+            let right = Box::new(lower(right, constant)?);
+            match operator {
+                Operator::Add => OperationType::Add { left, right },
+                Operator::Sub => OperationType::Sub { left, right },
+                Operator::Mul => OperationType::Mul { left, right },
+                Operator::Div => OperationType::Div { left, right },
+                Operator::Pow => OperationType::Pow { left, right },
+                Operator::LShift => OperationType::Shl { left, right },
+                Operator::RShift => OperationType::Shr { left, right },
+                _ => return None,
+            }
+        }
+        _ => return None,
+    };
+    Some(Operation::new(operation))
+}
@@ -0,0 +1,152 @@
+//! Line and branch coverage of executions of a program.
+//!
+//! `instrument` lays out a counter for each line with statements of function
+//! bodies and for each branch of the expressions which branch: both sides of an
+//! `if`, a ternary or a `for` loop, which runs its body or its `else`, and each
+//! arm of a `match`. The interpreter increments them as it runs, and the counts of
+//! several runs add up with `merge`. There is no EVM code generator yet, so the
+//! interpreter is the only one counting.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use zoker_parser::ast;
+use zoker_parser::ast::{ExpressionType, Program, StatementType};
+use zoker_parser::complexity::{program_nodes, Node};
+use zoker_parser::location::Location;
+
+/// Execution counts of the lines and branches of a program.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Coverage {
+    /// Executions of the statements starting on each line, by row
+    pub lines: BTreeMap<usize, usize>,
+    /// Executions of each branch, by row and column of the expression which branches
+    pub branches: BTreeMap<(usize, usize), Vec<usize>>,
+}
+
+/// Counters, all zero, of the lines and branches of the functions of `program`.
+pub fn instrument(program: &Program) -> Coverage {
+    let mut coverage = Coverage::default();
+    let mut pending: Vec<(Node, bool)> = program_nodes(program)
+        .into_iter()
+        .map(|node| (node, false))
+        .collect();
+    while let Some((node, in_function)) = pending.pop() {
+        let location = node.location();
+        match node {
+            Node::Statement(statement) => match &statement.node {
+                StatementType::InitializerStatement { .. }
+                | StatementType::ReturnStatement { .. }
+                | StatementType::Expression { .. }
+                    if in_function =>
+                {
+                    coverage.lines.insert(location.row(), 0);
+                }
+                _ => {}
+            },
+            Node::Expression(expression) => {
+                let branches = match &expression.node {
+                    ExpressionType::IfExpression { .. }
+                    | ExpressionType::TernaryExpression { .. }
+                    | ExpressionType::ForEachExpression { .. } => 2,
+                    ExpressionType::MatchExpression { arms, .. } => arms.len(),
+                    _ => 0,
+                };
+                if branches > 0 && in_function {
+                    let key = (location.row(), location.column());
+                    coverage.branches.insert(key, vec![0; branches]);
+                }
+            }
+        }
+        // Counts the body of functions, but not their parameters
+        let body = match node {
+            Node::Statement(ast::Statement {
+                node: StatementType::FunctionStatement { statement, .. },
+                ..
+            }) => statement.as_deref(),
+            _ => None,
+        };
+        pending.extend(node.children().into_iter().map(|child| {
+            let is_body = matches!((child, body), (Node::Statement(child), Some(body))
+                if std::ptr::eq(child, body));
+            (child, in_function || is_body)
+        }));
+    }
+    coverage
+}
+
+impl Coverage {
+    pub(crate) fn hit_line(&mut self, location: Location) {
+        if let Some(count) = self.lines.get_mut(&location.row()) {
+            *count += 1;
+        }
+    }
+
+    pub(crate) fn hit_branch(&mut self, location: Location, branch: usize) {
+        let key = (location.row(), location.column());
+        if let Some(count) = self
+            .branches
+            .get_mut(&key)
+            .and_then(|counts| counts.get_mut(branch))
+        {
+            *count += 1;
+        }
+    }
+
+    /// Adds the counts of `other`, for the same program.
+    pub fn merge(&mut self, other: &Coverage) {
+        for (row, count) in &other.lines {
+            *self.lines.entry(*row).or_default() += count;
+        }
+        for (key, counts) in &other.branches {
+            let merged = self
+                .branches
+                .entry(*key)
+                .or_insert_with(|| vec![0; counts.len()]);
+            for (merged, count) in merged.iter_mut().zip(counts) {
+                *merged += count;
+            }
+        }
+    }
+
+    pub fn lines_hit(&self) -> usize {
+        self.lines.values().filter(|count| **count > 0).count()
+    }
+
+    pub fn branch_count(&self) -> usize {
+        self.branches.values().map(Vec::len).sum()
+    }
+
+    pub fn branches_hit(&self) -> usize {
+        self.branches
+            .values()
+            .flatten()
+            .filter(|count| **count > 0)
+            .count()
+    }
+
+    /// Report in the LCOV tracefile format, for `file` as the source file.
+    pub fn to_lcov(&self, file: &str) -> String {
+        // Lines are numbered from 1 in LCOV
+        let mut lcov = format!("TN:\nSF:{}\n", file);
+        for (block, ((row, _), counts)) in self.branches.iter().enumerate() {
+            let reached = counts.iter().any(|count| *count > 0);
+            for (branch, count) in counts.iter().enumerate() {
+                let taken = if reached {
+                    count.to_string()
+                } else {
+                    String::from("-")
+                };
+                writeln!(lcov, "BRDA:{},{},{},{}", row + 1, block, branch, taken).unwrap();
+            }
+        }
+        writeln!(lcov, "BRF:{}", self.branch_count()).unwrap();
+        writeln!(lcov, "BRH:{}", self.branches_hit()).unwrap();
+        for (row, count) in &self.lines {
+            writeln!(lcov, "DA:{},{}", row + 1, count).unwrap();
+        }
+        writeln!(lcov, "LF:{}", self.lines.len()).unwrap();
+        writeln!(lcov, "LH:{}", self.lines_hit()).unwrap();
+        lcov.push_str("end_of_record\n");
+        lcov
+    }
+}
@@ -0,0 +1,135 @@
+//! Common subexpression elimination of pure calls.
+//!
+//! After `x = f(a, b)` where `f` is pure, later occurrences of `f(a, b)` in
+//! the same block are replaced by `x`, until `x`, `a` or `b` is assigned again.
+//! Builtins are pure unless they have side effects or read bytes from memory, see
+//! `BuiltinFunction::is_pure`, user functions are pure if the symbol table inferred
+//! so. Calls reading storage aren't reused after a call which isn't pure, or a
+//! builtin with side effects, as it may write storage. Nothing is reused across `If`, `Else` and `For`, whose bodies
+//! may assign.
+
+use crate::symbol::{Contract, Operation, OperationType, Symbol, SymbolLocation};
+use crate::symbol_table::{StateMutability, SymbolTable};
+
+/// Eliminates repeated pure calls in every function of `contract`, returning how many were replaced.
+pub fn eliminate_common_calls(contract: &mut Contract, table: &SymbolTable) -> usize {
+    let name = &contract.name;
+    let is_pure = |func: &str| table.function_mutability(name, func) == Some(StateMutability::Pure);
+    let mut replaced = 0;
+    for function in &mut contract.functions {
+        eliminate(&mut function.operations, &is_pure, &mut replaced);
+    }
+    replaced
+}
+
+/// Whether evaluating `operation` twice gives the same value without side effects.
+fn is_pure_expression(operation: &Operation, is_pure: &dyn Fn(&str) -> bool) -> bool {
+    let pure_node = match &operation.operation {
+        OperationType::Call { func, .. } => is_pure(func),
+        OperationType::Builtin { function, .. } => function.is_pure(),
+        OperationType::Add { .. }
+        | OperationType::Sub { .. }
+        | OperationType::Mul { .. }
+        | OperationType::Div { .. }
+        | OperationType::Pow { .. }
+        | OperationType::Shl { .. }
+        | OperationType::Shr { .. }
+        | OperationType::Symbol { .. }
+        | OperationType::Constant { .. } => true,
+        _ => false,
+    };
+    pure_node
+        && operation
+            .children()
+            .into_iter()
+            .all(|child| is_pure_expression(child, is_pure))
+}
+
+fn reads(operation: &Operation, symbol: &Symbol) -> bool {
+    match &operation.operation {
+        OperationType::Symbol { symbol: read } => read == symbol,
+        _ => operation
+            .children()
+            .into_iter()
+            .any(|child| reads(child, symbol)),
+    }
+}
+
+fn reads_storage(operation: &Operation) -> bool {
+    match &operation.operation {
+        OperationType::Symbol { symbol } => symbol.data_location == SymbolLocation::Storage,
+        _ => operation.children().into_iter().any(reads_storage),
+    }
+}
+
+/// Whether `operation` calls a function which isn't pure, or a builtin with side
+/// effects, so may write storage.
+fn calls_impure(operation: &Operation, is_pure: &dyn Fn(&str) -> bool) -> bool {
+    match &operation.operation {
+        OperationType::Call { func, .. } if !is_pure(func) => true,
+        OperationType::Builtin { function, .. } if function.has_side_effects() => true,
+        _ => operation
+            .children()
+            .into_iter()
+            .any(|child| calls_impure(child, is_pure)),
+    }
+}
+
+/// Replaces the calls of `available` found in `operation` by the variable holding their result.
+fn replace(operation: &mut Operation, available: &[(Operation, Symbol)], replaced: &mut usize) {
+    if let Some((_, symbol)) = available.iter().find(|(call, _)| call == operation) {
+        *operation = Operation::new_symbol(symbol.clone());
+        *replaced += 1;
+        return;
+    }
+    for child in operation.children_mut() {
+        replace(child, available, replaced);
+    }
+}
+
+fn eliminate(operations: &mut [Operation], is_pure: &dyn Fn(&str) -> bool, replaced: &mut usize) {
+    // Calls with the variable holding their result
+    let mut available: Vec<(Operation, Symbol)> = vec![];
+    for operation in operations {
+        if calls_impure(operation, is_pure) {
+            available.retain(|(call, _)| !reads_storage(call));
+        }
+        match &mut operation.operation {
+            OperationType::Assign { left, right } => {
+                replace(right, &available, replaced);
+                match left.as_symbol() {
+                    Some(target) => {
+                        available
+                            .retain(|(call, symbol)| *symbol != target && !reads(call, &target));
+                        let is_call = matches!(
+                            right.operation,
+                            OperationType::Call { .. } | OperationType::Builtin { .. }
+                        );
+                        if is_call && is_pure_expression(right, is_pure) && !reads(right, &target) {
+                            available.push((right.as_ref().clone(), target));
+                        }
+                    }
+                    // Writes an element of an array
+                    None => {
+                        replace(left, &available, replaced);
+                        available.retain(|(call, _)| {
+                            left.children()
+                                .into_iter()
+                                .all(|array| match array.as_symbol() {
+                                    Some(array) => !reads(call, &array),
+                                    None => true,
+                                })
+                        });
+                    }
+                }
+            }
+            OperationType::If { stmts, .. }
+            | OperationType::Else { stmts, .. }
+            | OperationType::For { stmts, .. } => {
+                eliminate(stmts, is_pure, replaced);
+                available.clear();
+            }
+            _ => replace(operation, &available, replaced),
+        }
+    }
+}
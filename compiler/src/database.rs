@@ -0,0 +1,252 @@
+//! Incremental query database over the compilation phases.
+//!
+//! Source text is the only input. Every other phase is a query whose result is
+//! memoized per file and recomputed only when one of its inputs changed:
+//!
+//! source -> tokens -> ast -> symbol table -> contracts
+//!
+//! A recomputed result equal to the previous one keeps its old revision,
+//! so the queries depending on it are not recomputed either.
+
+use crate::error::{CompileError, CompileResult};
+use crate::rewriter::rewrite_program;
+use crate::symbol::Contract;
+use crate::symbol_table::{make_symbol_table, SymbolTable};
+use std::collections::HashMap;
+use std::rc::Rc;
+use zoker_parser::ast;
+use zoker_parser::error::ParseError;
+use zoker_parser::lexer::{make_tokenizer, LexResult};
+use zoker_parser::parser::parse_program_tokens;
+
+pub type Revision = u64;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Query {
+    Tokens,
+    Ast,
+    SymbolTable,
+    Contracts,
+}
+
+struct Memo<T> {
+    value: Rc<T>,
+    /// Last revision in which the inputs of the value were checked.
+    verified_at: Revision,
+    /// Last revision in which the value actually changed.
+    changed_at: Revision,
+}
+
+struct MemoTable<T> {
+    memos: HashMap<String, Memo<T>>,
+}
+
+impl<T: PartialEq> MemoTable<T> {
+    fn new() -> Self {
+        MemoTable {
+            memos: HashMap::new(),
+        }
+    }
+
+    fn fresh(&self, file: &str, revision: Revision) -> Option<(Rc<T>, Revision)> {
+        self.memos
+            .get(file)
+            .filter(|memo| memo.verified_at == revision)
+            .map(|memo| (memo.value.clone(), memo.changed_at))
+    }
+
+    /// Reuses the memo if none of its inputs changed since it was last verified.
+    fn verify(
+        &mut self,
+        file: &str,
+        revision: Revision,
+        inputs_changed_at: Revision,
+    ) -> Option<(Rc<T>, Revision)> {
+        let memo = self.memos.get_mut(file)?;
+        if inputs_changed_at > memo.verified_at {
+            return None;
+        }
+        memo.verified_at = revision;
+        Some((memo.value.clone(), memo.changed_at))
+    }
+
+    fn store(&mut self, file: &str, value: T, revision: Revision) -> (Rc<T>, Revision) {
+        let memo = match self.memos.remove(file) {
+            Some(old) if *old.value == value => Memo {
+                value: old.value,
+                verified_at: revision,
+                changed_at: old.changed_at,
+            },
+            _ => Memo {
+                value: Rc::new(value),
+                verified_at: revision,
+                changed_at: revision,
+            },
+        };
+        let result = (memo.value.clone(), memo.changed_at);
+        self.memos.insert(file.to_string(), memo);
+        result
+    }
+
+    fn remove(&mut self, file: &str) {
+        self.memos.remove(file);
+    }
+}
+
+pub struct Database {
+    revision: Revision,
+    sources: HashMap<String, (Rc<String>, Revision)>,
+    tokens: MemoTable<Vec<LexResult>>,
+    asts: MemoTable<Result<ast::Program, ParseError>>,
+    symbol_tables: MemoTable<CompileResult<SymbolTable>>,
+    contracts: MemoTable<CompileResult<Vec<Contract>>>,
+    executed: Vec<(Query, String)>,
+}
+
+impl Default for Database {
+    fn default() -> Self {
+        Database::new()
+    }
+}
+
+impl Database {
+    pub fn new() -> Self {
+        Database {
+            revision: 0,
+            sources: HashMap::new(),
+            tokens: MemoTable::new(),
+            asts: MemoTable::new(),
+            symbol_tables: MemoTable::new(),
+            contracts: MemoTable::new(),
+            executed: vec![],
+        }
+    }
+
+    pub fn revision(&self) -> Revision {
+        self.revision
+    }
+
+    /// Sets the source of `file`. Setting the same text again invalidates nothing.
+    pub fn set_source(&mut self, file: &str, text: &str) {
+        if let Some((source, _)) = self.sources.get(file) {
+            if source.as_str() == text {
+                return;
+            }
+        }
+        self.revision += 1;
+        self.sources
+            .insert(file.to_string(), (Rc::new(text.to_string()), self.revision));
+    }
+
+    pub fn remove_source(&mut self, file: &str) {
+        if self.sources.remove(file).is_some() {
+            self.revision += 1;
+            self.tokens.remove(file);
+            self.asts.remove(file);
+            self.symbol_tables.remove(file);
+            self.contracts.remove(file);
+        }
+    }
+
+    /// # Panics
+    ///
+    /// Panics if no source was set for `file`.
+    pub fn source(&self, file: &str) -> Rc<String> {
+        self.source_memo(file).0
+    }
+
+    pub fn tokens(&mut self, file: &str) -> Rc<Vec<LexResult>> {
+        self.tokens_memo(file).0
+    }
+
+    pub fn ast(&mut self, file: &str) -> Rc<Result<ast::Program, ParseError>> {
+        self.ast_memo(file).0
+    }
+
+    pub fn symbol_table(&mut self, file: &str) -> Rc<CompileResult<SymbolTable>> {
+        self.symbol_table_memo(file).0
+    }
+
+    pub fn contracts(&mut self, file: &str) -> Rc<CompileResult<Vec<Contract>>> {
+        self.contracts_memo(file).0
+    }
+
+    /// Queries recomputed since the last call, in the order they ran.
+    pub fn take_executed(&mut self) -> Vec<(Query, String)> {
+        std::mem::take(&mut self.executed)
+    }
+
+    fn source_memo(&self, file: &str) -> (Rc<String>, Revision) {
+        match self.sources.get(file) {
+            Some((source, changed_at)) => (source.clone(), *changed_at),
+            None => panic!("no source was set for `{}`", file),
+        }
+    }
+
+    fn tokens_memo(&mut self, file: &str) -> (Rc<Vec<LexResult>>, Revision) {
+        if let Some(hit) = self.tokens.fresh(file, self.revision) {
+            return hit;
+        }
+        let (source, source_changed_at) = self.source_memo(file);
+        if let Some(hit) = self.tokens.verify(file, self.revision, source_changed_at) {
+            return hit;
+        }
+        self.executed.push((Query::Tokens, file.to_string()));
+        let value = make_tokenizer(&source).collect();
+        self.tokens.store(file, value, self.revision)
+    }
+
+    fn ast_memo(&mut self, file: &str) -> (Rc<Result<ast::Program, ParseError>>, Revision) {
+        if let Some(hit) = self.asts.fresh(file, self.revision) {
+            return hit;
+        }
+        let (tokens, tokens_changed_at) = self.tokens_memo(file);
+        if let Some(hit) = self.asts.verify(file, self.revision, tokens_changed_at) {
+            return hit;
+        }
+        self.executed.push((Query::Ast, file.to_string()));
+        let value = parse_program_tokens(tokens.iter().cloned());
+        self.asts.store(file, value, self.revision)
+    }
+
+    fn symbol_table_memo(&mut self, file: &str) -> (Rc<CompileResult<SymbolTable>>, Revision) {
+        if let Some(hit) = self.symbol_tables.fresh(file, self.revision) {
+            return hit;
+        }
+        let (program, ast_changed_at) = self.ast_memo(file);
+        if let Some(hit) = self
+            .symbol_tables
+            .verify(file, self.revision, ast_changed_at)
+        {
+            return hit;
+        }
+        self.executed.push((Query::SymbolTable, file.to_string()));
+        let value = match program.as_ref() {
+            Ok(program) => make_symbol_table(program).map_err(CompileError::from),
+            Err(err) => Err(CompileError::from(err.clone())),
+        };
+        self.symbol_tables.store(file, value, self.revision)
+    }
+
+    fn contracts_memo(&mut self, file: &str) -> (Rc<CompileResult<Vec<Contract>>>, Revision) {
+        if let Some(hit) = self.contracts.fresh(file, self.revision) {
+            return hit;
+        }
+        let (program, ast_changed_at) = self.ast_memo(file);
+        let (table, table_changed_at) = self.symbol_table_memo(file);
+        let inputs_changed_at = ast_changed_at.max(table_changed_at);
+        if let Some(hit) = self
+            .contracts
+            .verify(file, self.revision, inputs_changed_at)
+        {
+            return hit;
+        }
+        self.executed.push((Query::Contracts, file.to_string()));
+        let value = match (program.as_ref(), table.as_ref()) {
+            (Ok(program), Ok(_)) => rewrite_program(program).map_err(CompileError::from),
+            (_, Err(err)) => Err(err.clone()),
+            (Err(err), _) => Err(CompileError::from(err.clone())),
+        };
+        self.contracts.store(file, value, self.revision)
+    }
+}
@@ -0,0 +1,42 @@
+//! Payload of the transactions deploying contracts.
+//!
+//! A contract is created by a transaction without recipient whose data is the
+//! creation code followed by the arguments of the constructor, encoded like by
+//! `abi.encode`. The arguments are checked against the parameters of the
+//! constructor, none for contracts without one. There is no EVM code generator
+//! yet, so the creation code is given by the caller.
+
+use crate::abi::{check_arguments, encode, AbiResult, AbiValue};
+use crate::driver::Artifact;
+use crate::symbol::SymbolType;
+
+pub use zoker_parser::ast::CONSTRUCTOR;
+
+/// Parameter types of the constructor of the contract of `artifact`.
+pub fn constructor_params(artifact: &Artifact) -> Vec<SymbolType> {
+    artifact
+        .contract
+        .functions
+        .iter()
+        .find(|function| function.name == CONSTRUCTOR)
+        .map(|function| {
+            function
+                .params
+                .iter()
+                .map(|param| param.symbol_type.clone())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Data of the transaction creating the contract of `artifact` with `args` for its constructor.
+pub fn deployment_payload(
+    artifact: &Artifact,
+    creation_code: &[u8],
+    args: &[AbiValue],
+) -> AbiResult<Vec<u8>> {
+    check_arguments(CONSTRUCTOR, &constructor_params(artifact), args)?;
+    let mut payload = creation_code.to_vec();
+    payload.extend(encode(args)?);
+    Ok(payload)
+}
@@ -0,0 +1,325 @@
+//! Desugaring of syntactic sugar into the core language.
+//!
+//! - `a op= b` becomes `a = a op b`,
+//! - `a++;`, `++a;`, `a--;` and `--a;` as statements become `a = a + 1;` and `a = a - 1;`,
+//! - `c ? x : y;` as a statement becomes `if c { x; } else { y; };`.
+//!
+//! The target of an assignment is evaluated twice once desugared, so only
+//! targets without side effects are desugared: identifiers, members and
+//! elements indexed by identifiers or numbers. Increments whose value is used,
+//! e.g. `b = a++`, are left as they are.
+
+use num_bigint::BigUint;
+use num_traits::One;
+use zoker_parser::ast::{
+    Expression, ExpressionType, Operator, Program, Radix, Statement, StatementType,
+};
+
+/// Statement or expression of the AST being desugared.
+pub(crate) enum Node<'a> {
+    Statement(&'a mut Statement),
+    Expression(&'a mut Expression),
+}
+
+/// Desugars `program` in place, returning how many constructs were rewritten.
+pub fn desugar_program(program: &mut Program) -> usize {
+    let Program::GlobalStatements(statements) = program;
+    let mut desugared = 0;
+    // A stack rather than recursion, as for the symbol table
+    let mut pending: Vec<Node> = statements.iter_mut().map(Node::Statement).collect();
+    while let Some(node) = pending.pop() {
+        match node {
+            Node::Statement(statement) => {
+                if desugar_statement(statement) {
+                    desugared += 1;
+                }
+                pending.extend(statement_children(statement));
+            }
+            Node::Expression(expression) => {
+                if desugar_expression(expression) {
+                    desugared += 1;
+                }
+                pending.extend(expression_children(expression));
+            }
+        }
+    }
+    desugared
+}
+
+/// Whether evaluating `expression` twice is the same as evaluating it once.
+fn is_simple(expression: &Expression) -> bool {
+    match &expression.node {
+        ExpressionType::Identifier { .. } | ExpressionType::Number { .. } => true,
+        ExpressionType::MemberExpression {
+            expression: base, ..
+        } => is_simple(base),
+        ExpressionType::IndexExpression {
+            expression: base,
+            index,
+        } => is_simple(base) && is_simple(index),
+        _ => false,
+    }
+}
+
+/// Operator of the binary operation an augmented assignment applies.
+fn binary_operator(operator: &Operator) -> Option<Operator> {
+    match operator {
+        Operator::AddAssign => Some(Operator::Add),
+        Operator::SubAssign => Some(Operator::Sub),
+        Operator::MulAssign => Some(Operator::Mul),
+        Operator::DivAssign => Some(Operator::Div),
+        Operator::ModAssign => Some(Operator::Mod),
+        Operator::BitAndAssign => Some(Operator::BitAnd),
+        Operator::BitXorAssign => Some(Operator::BitXor),
+        Operator::BitOrAssign => Some(Operator::BitOr),
+        Operator::LShiftAssign => Some(Operator::LShift),
+        Operator::RShiftAssign => Some(Operator::RShift),
+        _ => None,
+    }
+}
+
+/// `target = target operator value`.
+fn assignment(target: Expression, operator: Operator, value: Expression) -> ExpressionType {
+    let location = target.location;
+    ExpressionType::AssignExpression {
+        left: Box::new(target.clone()),
+        operator: Operator::Assign,
+        right: Box::new(Expression {
+            location,
+            node: ExpressionType::BinaryExpression {
+                left: Box::new(target),
+                operator,
+                right: Box::new(value),
+            },
+        }),
+    }
+}
+
+fn desugar_expression(expression: &mut Expression) -> bool {
+    let is_augmented = match &expression.node {
+        ExpressionType::AssignExpression { left, operator, .. } => {
+            binary_operator(operator).is_some() && is_simple(left)
+        }
+        _ => false,
+    };
+    if !is_augmented {
+        return false;
+    }
+    if let ExpressionType::AssignExpression {
+        left,
+        operator,
+        right,
+    } = std::mem::replace(&mut expression.node, ExpressionType::Error)
+    {
+        expression.node = assignment(*left, binary_operator(&operator).unwrap(), *right);
+    }
+    true
+}
+
+fn desugar_statement(statement: &mut Statement) -> bool {
+    let expression = match &mut statement.node {
+        StatementType::Expression { expression } => expression,
+        _ => return false,
+    };
+    let location = expression.location;
+    match std::mem::replace(&mut expression.node, ExpressionType::Error) {
+        ExpressionType::UnaryExpression {
+            operator,
+            expression: operand,
+        } if is_simple(&operand) => {
+            let operator = match operator {
+                Operator::PrefixPlusPlus | Operator::PostfixPlusPlus => Operator::Add,
+                Operator::PrefixMinusMinus | Operator::PostfixMinusMinus => Operator::Sub,
+                operator => {
+                    expression.node = ExpressionType::UnaryExpression {
+                        operator,
+                        expression: operand,
+                    };
+                    return false;
+                }
+            };
+            let one = Expression {
+                location,
+                node: ExpressionType::Number {
+                    value: BigUint::one(),
+                    radix: Radix::Decimal,
+                },
+            };
+            expression.node = assignment(*operand, operator, one);
+            true
+        }
+        ExpressionType::TernaryExpression {
+            condition,
+            expr1,
+            expr2,
+        } => {
+            let branch = |expression: Box<Expression>| {
+                Box::new(Statement {
+                    location: expression.location,
+                    node: StatementType::CompoundStatement {
+                        statements: vec![Statement {
+                            location: expression.location,
+                            node: StatementType::Expression { expression },
+                        }],
+                        return_value: None,
+                    },
+                })
+            };
+            expression.node = ExpressionType::IfExpression {
+                condition,
+                if_statement: branch(expr1),
+                else_statement: Some(branch(expr2)),
+            };
+            true
+        }
+        node => {
+            expression.node = node;
+            false
+        }
+    }
+}
+
+pub(crate) fn statement_children(statement: &mut Statement) -> Vec<Node<'_>> {
+    let mut children = vec![];
+    match &mut statement.node {
+        StatementType::FunctionStatement {
+            parameters,
+            statement,
+            returns,
+            ..
+        } => {
+            children.push(Node::Expression(parameters));
+            children.extend(returns.as_deref_mut().map(Node::Expression));
+            children.extend(statement.as_deref_mut().map(Node::Statement));
+        }
+        StatementType::ContractStatement { members, .. } => {
+            children.push(Node::Statement(members));
+        }
+        StatementType::StructStatement { fields, .. } => {
+            children.extend(fields.iter_mut().map(Node::Statement));
+        }
+        StatementType::ModuleStatement { members, .. } => {
+            children.extend(members.iter_mut().map(Node::Statement));
+        }
+        StatementType::InitializerStatement { default, .. } => {
+            children.extend(default.as_deref_mut().map(Node::Expression));
+        }
+        StatementType::CompoundStatement {
+            statements,
+            return_value,
+        } => {
+            children.extend(statements.iter_mut().map(Node::Statement));
+            children.extend(return_value.as_deref_mut().map(Node::Expression));
+        }
+        StatementType::MemberStatement { statements } => {
+            children.extend(statements.iter_mut().map(Node::Statement));
+        }
+        StatementType::UncheckedStatement { statement }
+        | StatementType::ConditionalStatement { statement, .. } => {
+            children.push(Node::Statement(statement));
+        }
+        StatementType::DoWhileStatement {
+            statement,
+            condition,
+        } => {
+            children.push(Node::Statement(statement));
+            children.push(Node::Expression(condition));
+        }
+        StatementType::ReturnStatement { ret } => {
+            children.extend(ret.as_deref_mut().map(Node::Expression));
+        }
+        StatementType::Expression { expression } => children.push(Node::Expression(expression)),
+        StatementType::EventStatement { .. }
+        | StatementType::ImportStatement { .. }
+        | StatementType::UsingStatement { .. }
+        | StatementType::EnumStatement { .. }
+        | StatementType::Error => {}
+    }
+    children
+}
+
+pub(crate) fn expression_children(expression: &mut Expression) -> Vec<Node<'_>> {
+    let mut children = vec![];
+    match &mut expression.node {
+        ExpressionType::AssignExpression { left, right, .. }
+        | ExpressionType::BinaryExpression { left, right, .. } => {
+            children.push(Node::Expression(left));
+            children.push(Node::Expression(right));
+        }
+        ExpressionType::TernaryExpression {
+            condition,
+            expr1,
+            expr2,
+        } => {
+            children.push(Node::Expression(condition));
+            children.push(Node::Expression(expr1));
+            children.push(Node::Expression(expr2));
+        }
+        ExpressionType::FunctionCallExpression { arguments, .. } => {
+            children.push(Node::Expression(arguments));
+        }
+        ExpressionType::MemberExpression {
+            expression: base, ..
+        } => children.push(Node::Expression(base)),
+        ExpressionType::IndexExpression {
+            expression: base,
+            index,
+        } => {
+            children.push(Node::Expression(base));
+            children.push(Node::Expression(index));
+        }
+        ExpressionType::IfExpression {
+            condition,
+            if_statement,
+            else_statement,
+        } => {
+            children.push(Node::Expression(condition));
+            children.push(Node::Statement(if_statement));
+            children.extend(else_statement.as_deref_mut().map(Node::Statement));
+        }
+        ExpressionType::MatchExpression { value, arms } => {
+            children.push(Node::Expression(value));
+            children.extend(
+                arms.iter_mut()
+                    .map(|arm| Node::Statement(&mut arm.statement)),
+            );
+        }
+        ExpressionType::ForEachExpression {
+            vector,
+            statement,
+            else_statement,
+            ..
+        } => {
+            children.push(Node::Expression(vector));
+            children.push(Node::Statement(statement));
+            children.extend(else_statement.as_deref_mut().map(Node::Statement));
+        }
+        ExpressionType::UnaryExpression {
+            expression: operand,
+            ..
+        } => children.push(Node::Expression(operand)),
+        ExpressionType::Parameters { parameters } => {
+            children.extend(parameters.iter_mut().map(Node::Statement));
+        }
+        ExpressionType::Arguments { arguments } => {
+            children.extend(arguments.iter_mut().map(Node::Expression));
+        }
+        ExpressionType::Tuple { items } => {
+            children.extend(items.iter_mut().flatten().map(Node::Expression));
+        }
+        ExpressionType::StructLiteral { fields, .. } => {
+            children.extend(
+                fields
+                    .iter_mut()
+                    .map(|field| Node::Expression(&mut field.value)),
+            );
+        }
+        ExpressionType::TypeList { .. }
+        | ExpressionType::Number { .. }
+        | ExpressionType::Identifier { .. }
+        | ExpressionType::PathExpression { .. }
+        | ExpressionType::StringLiteral { .. }
+        | ExpressionType::Error => {}
+    }
+    children
+}
@@ -0,0 +1,175 @@
+//! Dominator tree and natural loops of a control-flow graph.
+//!
+//! Block `a` dominates block `b` when every path from the entry to `b` goes
+//! through `a`. An edge whose target dominates its source is a back edge, and
+//! the target is the header of a natural loop made of every block which reaches
+//! the source without going through the header.
+
+use crate::cfg::{BlockId, ControlFlowGraph};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DominatorTree {
+    entry: BlockId,
+    /// Immediate dominator of every block, `None` for the entry and unreachable blocks
+    idom: Vec<Option<BlockId>>,
+}
+
+impl DominatorTree {
+    /// Computes the tree with the iterative algorithm of Cooper, Harvey and Kennedy.
+    pub fn new(cfg: &ControlFlowGraph) -> Self {
+        let order = reverse_postorder(cfg);
+        let mut rank = vec![usize::MAX; cfg.blocks.len()];
+        for (i, block) in order.iter().enumerate() {
+            rank[*block] = i;
+        }
+        let mut idom = vec![None; cfg.blocks.len()];
+        idom[cfg.entry] = Some(cfg.entry);
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for block in order.iter().skip(1) {
+                let new_idom = cfg
+                    .predecessors(*block)
+                    .into_iter()
+                    .filter(|pred| idom[*pred].is_some())
+                    .reduce(|a, b| intersect(&idom, &rank, a, b));
+                if new_idom.is_some() && idom[*block] != new_idom {
+                    idom[*block] = new_idom;
+                    changed = true;
+                }
+            }
+        }
+        idom[cfg.entry] = None;
+        DominatorTree {
+            entry: cfg.entry,
+            idom,
+        }
+    }
+
+    pub fn immediate_dominator(&self, block: BlockId) -> Option<BlockId> {
+        self.idom[block]
+    }
+
+    pub fn is_reachable(&self, block: BlockId) -> bool {
+        block == self.entry || self.idom[block].is_some()
+    }
+
+    /// Whether `a` dominates `b`. Every block dominates itself.
+    pub fn dominates(&self, a: BlockId, b: BlockId) -> bool {
+        if !self.is_reachable(b) {
+            return false;
+        }
+        let mut current = Some(b);
+        while let Some(block) = current {
+            if block == a {
+                return true;
+            }
+            current = self.idom[block];
+        }
+        false
+    }
+
+    /// Blocks immediately dominated by `block`.
+    pub fn children(&self, block: BlockId) -> Vec<BlockId> {
+        (0..self.idom.len())
+            .filter(|child| self.idom[*child] == Some(block))
+            .collect()
+    }
+}
+
+fn intersect(idom: &[Option<BlockId>], rank: &[usize], mut a: BlockId, mut b: BlockId) -> BlockId {
+    while a != b {
+        while rank[a] > rank[b] {
+            a = idom[a].unwrap();
+        }
+        while rank[b] > rank[a] {
+            b = idom[b].unwrap();
+        }
+    }
+    a
+}
+
+/// Reachable blocks, each one before its successors except along back edges.
+fn reverse_postorder(cfg: &ControlFlowGraph) -> Vec<BlockId> {
+    let mut visited = vec![false; cfg.blocks.len()];
+    let mut postorder = vec![];
+    // Explicit stack of blocks with the successors left to visit
+    let mut stack = vec![(cfg.entry, cfg.successors(cfg.entry))];
+    visited[cfg.entry] = true;
+    while let Some((block, successors)) = stack.last_mut() {
+        match successors.pop() {
+            Some(next) if !visited[next] => {
+                visited[next] = true;
+                let successors = cfg.successors(next);
+                stack.push((next, successors));
+            }
+            Some(_) => {}
+            None => {
+                postorder.push(*block);
+                stack.pop();
+            }
+        }
+    }
+    postorder.reverse();
+    postorder
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct NaturalLoop {
+    pub header: BlockId,
+    /// Sources of the back edges to the header
+    pub latches: Vec<BlockId>,
+    /// Blocks of the loop including the header, in ascending order
+    pub blocks: Vec<BlockId>,
+}
+
+impl NaturalLoop {
+    pub fn contains(&self, block: BlockId) -> bool {
+        self.blocks.binary_search(&block).is_ok()
+    }
+}
+
+/// Natural loops of `cfg` ordered by header. Back edges to the same header form one loop.
+pub fn natural_loops(cfg: &ControlFlowGraph, dominators: &DominatorTree) -> Vec<NaturalLoop> {
+    let mut loops: Vec<NaturalLoop> = vec![];
+    for (from, to) in cfg.edges() {
+        if !dominators.dominates(to, from) {
+            continue;
+        }
+        let index = match loops.iter().position(|l| l.header == to) {
+            Some(index) => index,
+            None => {
+                loops.push(NaturalLoop {
+                    header: to,
+                    latches: vec![],
+                    blocks: vec![to],
+                });
+                loops.len() - 1
+            }
+        };
+        let natural_loop = &mut loops[index];
+        natural_loop.latches.push(from);
+        let mut worklist = vec![from];
+        while let Some(block) = worklist.pop() {
+            if natural_loop.blocks.contains(&block) {
+                continue;
+            }
+            natural_loop.blocks.push(block);
+            worklist.extend(
+                cfg.predecessors(block)
+                    .into_iter()
+                    .filter(|pred| dominators.is_reachable(*pred)),
+            );
+        }
+    }
+    for natural_loop in &mut loops {
+        natural_loop.blocks.sort_unstable();
+    }
+    loops.sort_by_key(|l| l.header);
+    loops
+}
+
+/// Number of loops `block` is nested in.
+pub fn loop_depth(loops: &[NaturalLoop], block: BlockId) -> usize {
+    loops.iter().filter(|l| l.contains(block)).count()
+}
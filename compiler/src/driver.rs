@@ -0,0 +1,240 @@
+//! Compilation pipeline.
+//!
+//! `Compiler` runs the stages in order, each on the output of the previous one:
+//!
+//! parse -> desugar -> resolve -> check -> lower -> optimize -> emit
+//!
+//! - parse: source to AST, without the items disabled by the defines,
+//! - desugar: syntactic sugar of the AST to the core language,
+//! - resolve: symbol table of the AST, with the types and mutability it infers,
+//! - check: every used symbol is declared,
+//! - lower: AST to the IR of each contract,
+//! - optimize: constant propagation and strength reduction, then common call and bounds check
+//!   elimination at the full optimization level,
+//! - emit: artifacts of the contracts.
+//!
+//! The settings of the compilation are recorded in the metadata of the artifacts.
+//! Hooks observe the output of each stage as it completes, and compilation can
+//! stop after any stage.
+
+use crate::bounds::eliminate_bounds_checks;
+use crate::codesize::{contract_size, ContractSize};
+use crate::conditional::strip_disabled;
+use crate::cse::eliminate_common_calls;
+use crate::desugar::desugar_program;
+use crate::error::CompileResult;
+use crate::evm::check_evm_version;
+use crate::intervals::eliminate_safe_bounds_checks;
+use crate::metadata::Metadata;
+use crate::rewriter::{rewrite_program_with, RewriteSettings};
+use crate::sccp::propagate_constants;
+use crate::settings::{CompileSettings, OptLevel};
+use crate::storage::{storage_layout, StorageLayout};
+use crate::strength::reduce_strength;
+use crate::symbol::Contract;
+use crate::symbol_table::{analyze_symbol_table, build_symbol_table, SymbolTable};
+use crate::trace::{source_map, SourceMap};
+use std::fmt;
+use zoker_parser::ast;
+use zoker_parser::config::ParseConfig;
+use zoker_parser::parser::parse_program_with;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Stage {
+    Parse,
+    Desugar,
+    Resolve,
+    Check,
+    Lower,
+    Optimize,
+    Emit,
+}
+
+impl fmt::Display for Stage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Stage::Parse => "parse",
+            Stage::Desugar => "desugar",
+            Stage::Resolve => "resolve",
+            Stage::Check => "check",
+            Stage::Lower => "lower",
+            Stage::Optimize => "optimize",
+            Stage::Emit => "emit",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Output of a stage, as given to the hooks.
+#[derive(Debug, Clone, Copy)]
+pub enum StageOutput<'a> {
+    Parse(&'a ast::Program),
+    Desugar(&'a ast::Program),
+    Resolve(&'a SymbolTable),
+    Check(&'a SymbolTable),
+    Lower(&'a [Contract]),
+    Optimize(&'a [Contract]),
+    Emit(&'a [Artifact]),
+}
+
+impl StageOutput<'_> {
+    pub fn stage(&self) -> Stage {
+        match self {
+            StageOutput::Parse(_) => Stage::Parse,
+            StageOutput::Desugar(_) => Stage::Desugar,
+            StageOutput::Resolve(_) => Stage::Resolve,
+            StageOutput::Check(_) => Stage::Check,
+            StageOutput::Lower(_) => Stage::Lower,
+            StageOutput::Optimize(_) => Stage::Optimize,
+            StageOutput::Emit(_) => Stage::Emit,
+        }
+    }
+}
+
+/// Compiled contract.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Artifact {
+    pub contract: Contract,
+    pub size: ContractSize,
+    /// Layout of the state variables
+    pub storage: StorageLayout,
+    /// Functions of the runtime code
+    pub source_map: SourceMap,
+    pub metadata: Metadata,
+}
+
+/// Outputs of the stages which ran, `None` for the others.
+#[derive(Debug, Default)]
+pub struct Compilation {
+    /// Program as parsed, then desugared
+    pub program: Option<ast::Program>,
+    pub symbol_table: Option<SymbolTable>,
+    /// Contracts as lowered, then optimized
+    pub contracts: Option<Vec<Contract>>,
+    pub artifacts: Option<Vec<Artifact>>,
+}
+
+type Hook<'h> = Box<dyn FnMut(StageOutput) + 'h>;
+
+pub struct Compiler<'h> {
+    /// Last stage to run
+    pub stop_after: Stage,
+    pub parse_config: ParseConfig,
+    pub settings: CompileSettings,
+    /// Name of the source in the metadata of the artifacts
+    pub source_name: String,
+    hooks: Vec<Hook<'h>>,
+}
+
+impl Default for Compiler<'_> {
+    fn default() -> Self {
+        Compiler {
+            stop_after: Stage::Emit,
+            parse_config: ParseConfig::default(),
+            settings: CompileSettings::default(),
+            source_name: String::from("main.zok"),
+            hooks: vec![],
+        }
+    }
+}
+
+impl<'h> Compiler<'h> {
+    pub fn new() -> Self {
+        Compiler::default()
+    }
+
+    pub fn with_settings(settings: CompileSettings) -> Self {
+        Compiler {
+            settings,
+            ..Compiler::default()
+        }
+    }
+
+    /// Calls `hook` with the output of each stage once it completes.
+    pub fn observe(&mut self, hook: impl FnMut(StageOutput) + 'h) {
+        self.hooks.push(Box::new(hook));
+    }
+
+    fn notify(&mut self, output: StageOutput) {
+        for hook in &mut self.hooks {
+            hook(output);
+        }
+    }
+
+    /// Runs the stages up to `stop_after`. Stages after a failing one are not run.
+    pub fn compile(&mut self, source: &str) -> CompileResult<Compilation> {
+        let mut compilation = Compilation::default();
+
+        let mut program = parse_program_with(source, &self.parse_config)?;
+        strip_disabled(&mut program, &self.settings.defines);
+        self.notify(StageOutput::Parse(&program));
+        let program = compilation.program.insert(program);
+        if self.stop_after == Stage::Parse {
+            return Ok(compilation);
+        }
+
+        desugar_program(program);
+        self.notify(StageOutput::Desugar(program));
+        if self.stop_after == Stage::Desugar {
+            return Ok(compilation);
+        }
+
+        let table = build_symbol_table(program)?;
+        self.notify(StageOutput::Resolve(&table));
+        let table = compilation.symbol_table.insert(table);
+        if self.stop_after == Stage::Resolve {
+            return Ok(compilation);
+        }
+
+        analyze_symbol_table(table)?;
+        check_evm_version(table, self.settings.evm_version)?;
+        self.notify(StageOutput::Check(table));
+        if self.stop_after == Stage::Check {
+            return Ok(compilation);
+        }
+
+        let rewrite_settings = RewriteSettings {
+            bounds_checks: self.settings.bounds_checks,
+        };
+        let mut contracts = rewrite_program_with(program, &rewrite_settings)?;
+        self.notify(StageOutput::Lower(&contracts));
+        if self.stop_after == Stage::Lower {
+            compilation.contracts = Some(contracts);
+            return Ok(compilation);
+        }
+
+        let opt_level = self.settings.opt_level;
+        for contract in &mut contracts {
+            if opt_level >= OptLevel::Basic {
+                propagate_constants(contract);
+                reduce_strength(contract);
+            }
+            if opt_level == OptLevel::Full {
+                eliminate_common_calls(contract, table);
+                eliminate_bounds_checks(contract);
+                eliminate_safe_bounds_checks(contract);
+            }
+        }
+        self.notify(StageOutput::Optimize(&contracts));
+        let contracts = compilation.contracts.insert(contracts);
+        if self.stop_after == Stage::Optimize {
+            return Ok(compilation);
+        }
+
+        let sources = [(self.source_name.as_str(), source)];
+        let artifacts: Vec<Artifact> = contracts
+            .iter()
+            .map(|contract| Artifact {
+                contract: contract.clone(),
+                size: contract_size(contract, self.settings.evm_version),
+                storage: storage_layout(table, &contract.name)
+                    .unwrap_or_else(|| StorageLayout::new(&[])),
+                source_map: source_map(contract, table, self.settings.evm_version),
+                metadata: Metadata::new(contract, self.settings.clone(), &sources),
+            })
+            .collect();
+        self.notify(StageOutput::Emit(&artifacts));
+        compilation.artifacts = Some(artifacts);
+        Ok(compilation)
+    }
+}
@@ -1,15 +1,182 @@
+use crate::evm::Opcode;
+use crate::settings::EvmVersion;
+use std::fmt;
+use zoker_parser::error::ParseError;
 use zoker_parser::location::Location;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct RewriteError {
     pub error: RewriteErrorType,
     pub location: Location,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum RewriteErrorType {
     SyntaxError(String),
     TypeError(String),
     UnsupportedError,
     Unreachable,
 }
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SymbolTableError {
+    pub error: SymbolTableErrorType,
+    pub location: Location,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SymbolTableErrorType {
+    NotDeclared(String),
+    TypeError(String),
+    /// Event declared twice in a contract with the same canonical signature
+    DuplicateEvent(String),
+    /// `match` over an enum missing some of its variants
+    NonExhaustiveMatch {
+        enum_name: String,
+        missing: Vec<String>,
+    },
+    /// `import` of a path which isn't a module of the standard library
+    UnknownImport(String),
+    /// Contract or function declared twice in the global scope. The error is at the
+    /// second declaration, in the source `source`; sources of a single program are unnamed.
+    DuplicateDeclaration {
+        name: String,
+        first_source: String,
+        first: Location,
+        source: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExecutionError {
+    pub error: ExecutionErrorType,
+    pub location: Location,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExecutionErrorType {
+    /// Failed assertion, with its message if it has one
+    Revert(Option<String>),
+    DivisionByZero,
+    IndexOutOfBounds,
+    /// Construct the interpreter can't run, e.g. a call to another contract
+    Unsupported(String),
+    /// Call nested deeper than `interpreter::MAX_CALL_DEPTH`
+    CallDepthExceeded,
+}
+
+impl fmt::Display for ExecutionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.error {
+            ExecutionErrorType::Revert(None) => write!(f, "assertion failed")?,
+            ExecutionErrorType::Revert(Some(message)) => {
+                write!(f, "assertion failed: {}", message)?
+            }
+            ExecutionErrorType::DivisionByZero => write!(f, "division by zero")?,
+            ExecutionErrorType::IndexOutOfBounds => write!(f, "index out of bounds")?,
+            ExecutionErrorType::CallDepthExceeded => write!(f, "call depth exceeded")?,
+            ExecutionErrorType::Unsupported(construct) => {
+                write!(f, "{} is not supported by the interpreter", construct)?
+            }
+        }
+        write!(f, " at {}", self.location)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AbiError {
+    pub error: String,
+}
+
+impl AbiError {
+    pub fn new(error: &str) -> Self {
+        AbiError {
+            error: error.to_string(),
+        }
+    }
+}
+
+/// Witness input file not matching the parameters of its function.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WitnessError {
+    pub error: String,
+}
+
+impl WitnessError {
+    pub fn new(error: &str) -> Self {
+        WitnessError {
+            error: error.to_string(),
+        }
+    }
+}
+
+/// Verifying key not matching the circuit of the function it verifies.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VerifierError {
+    pub error: String,
+}
+
+impl VerifierError {
+    pub fn new(error: &str) -> Self {
+        VerifierError {
+            error: error.to_string(),
+        }
+    }
+}
+
+/// Feature of the source compiled to an opcode the targeted EVM doesn't have.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EvmVersionError {
+    pub feature: String,
+    pub opcode: Opcode,
+    pub evm_version: EvmVersion,
+    pub location: Location,
+}
+
+impl fmt::Display for EvmVersionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} needs `{}`, which the `{}` EVM doesn't have: target `{}` or later",
+            self.feature,
+            self.opcode,
+            self.evm_version,
+            self.opcode.introduced_in()
+        )
+    }
+}
+
+pub type CompileResult<T> = Result<T, CompileError>;
+
+/// Error of any phase of the compilation.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CompileError {
+    Parse(ParseError),
+    SymbolTable(SymbolTableError),
+    Rewrite(RewriteError),
+    EvmVersion(EvmVersionError),
+}
+
+impl From<ParseError> for CompileError {
+    fn from(err: ParseError) -> Self {
+        CompileError::Parse(err)
+    }
+}
+
+impl From<SymbolTableError> for CompileError {
+    fn from(err: SymbolTableError) -> Self {
+        CompileError::SymbolTable(err)
+    }
+}
+
+impl From<RewriteError> for CompileError {
+    fn from(err: RewriteError) -> Self {
+        CompileError::Rewrite(err)
+    }
+}
+
+impl From<EvmVersionError> for CompileError {
+    fn from(err: EvmVersionError) -> Self {
+        CompileError::EvmVersion(err)
+    }
+}
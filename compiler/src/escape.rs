@@ -0,0 +1,211 @@
+//! Escape analysis of the values a function holds in memory.
+//!
+//! Arrays, strings, bytes and structs live in memory and locals only point to
+//! them. A memory value escapes when it is written to storage, returned, or passed
+//! to a function whose parameter escapes, so it may be used after the function
+//! ends. Assigning it to another local makes it escape wherever that local does.
+//! The memory of values which don't escape can be allocated in the frame of the
+//! function and reused once they are dead, without expanding memory. Calls of
+//! functions outside the contract, like those of the standard library, are
+//! assumed to keep their arguments.
+
+use crate::liveness::{local, Local};
+use crate::symbol::{Contract, Function, Operation, OperationType, Symbol, SymbolType};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
+
+/// Way a memory value outlives the function holding it.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Escape {
+    /// Written to a storage variable or its elements
+    Storage,
+    /// Passed to the function
+    Call(String),
+    Return,
+}
+
+impl fmt::Display for Escape {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Escape::Storage => write!(f, "stored"),
+            Escape::Call(function) => write!(f, "passed to `{}`", function),
+            Escape::Return => write!(f, "returned"),
+        }
+    }
+}
+
+/// Memory values of a function and how they escape.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EscapeAnalysis {
+    /// Locals holding memory values, parameters included
+    pub memory: BTreeSet<Local>,
+    /// Escapes of each memory value, in order of appearance, for those which escape
+    pub escapes: BTreeMap<Local, Vec<Escape>>,
+}
+
+impl EscapeAnalysis {
+    pub fn escapes(&self, local: &Local) -> bool {
+        self.escapes.contains_key(local)
+    }
+
+    /// Memory values which can be allocated in the frame of the function.
+    pub fn local_values(&self) -> Vec<&Local> {
+        self.memory
+            .iter()
+            .filter(|local| !self.escapes(local))
+            .collect()
+    }
+}
+
+impl fmt::Display for EscapeAnalysis {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for local @ (name, num, _) in &self.memory {
+            let escapes = match self.escapes.get(local) {
+                Some(escapes) => escapes
+                    .iter()
+                    .map(Escape::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                None => String::from("local"),
+            };
+            writeln!(f, "{}#{}: {}", name, num, escapes)?;
+        }
+        Ok(())
+    }
+}
+
+fn is_memory_type(symbol_type: &SymbolType) -> bool {
+    matches!(
+        symbol_type,
+        SymbolType::Array(_)
+            | SymbolType::FixedArray(..)
+            | SymbolType::Bytes
+            | SymbolType::String
+            | SymbolType::UserDefined(_)
+    )
+}
+
+/// The memory value `operation` refers to, if it is a local holding one.
+fn memory_local(operation: &Operation) -> Option<Local> {
+    match &operation.operation {
+        OperationType::Symbol { symbol } if is_memory_type(&symbol.symbol_type) => local(symbol),
+        _ => None,
+    }
+}
+
+/// Variable written by an assignment to `target`, e.g. `values` for `values[i]`.
+fn assigned_symbol(target: &Operation) -> Option<&Symbol> {
+    let mut target = target;
+    loop {
+        match &target.operation {
+            OperationType::Symbol { symbol } => return Some(symbol),
+            OperationType::Index { array, .. } => target = array,
+            _ => return None,
+        }
+    }
+}
+
+/// Escapes of the memory values of each function of `contract`, by name.
+pub fn analyze_escapes(contract: &Contract) -> BTreeMap<String, EscapeAnalysis> {
+    // Whether each parameter escapes, grown until no function changes
+    let mut params: HashMap<String, Vec<bool>> = contract
+        .functions
+        .iter()
+        .map(|function| (function.name.clone(), vec![false; function.params.len()]))
+        .collect();
+    loop {
+        let analyses: BTreeMap<String, EscapeAnalysis> = contract
+            .functions
+            .iter()
+            .map(|function| (function.name.clone(), analyze_function(function, &params)))
+            .collect();
+        let mut changed = false;
+        for function in &contract.functions {
+            let analysis = &analyses[&function.name];
+            let escaping: Vec<bool> = function
+                .params
+                .iter()
+                .map(|param| local(param).is_some_and(|param| analysis.escapes(&param)))
+                .collect();
+            if params[&function.name] != escaping {
+                params.insert(function.name.clone(), escaping);
+                changed = true;
+            }
+        }
+        if !changed {
+            return analyses;
+        }
+    }
+}
+
+fn add_escape(analysis: &mut EscapeAnalysis, local: Local, escape: Escape) {
+    let escapes = analysis.escapes.entry(local).or_default();
+    if !escapes.contains(&escape) {
+        escapes.push(escape);
+    }
+}
+
+fn analyze_function(function: &Function, params: &HashMap<String, Vec<bool>>) -> EscapeAnalysis {
+    let mut analysis = EscapeAnalysis::default();
+    for param in &function.params {
+        if is_memory_type(&param.symbol_type) {
+            analysis.memory.extend(local(param));
+        }
+    }
+    // Assignments of a memory value to another local, as `(target, value)`
+    let mut aliases: Vec<(Local, Local)> = vec![];
+    let mut pending: Vec<&Operation> = function.operations.iter().rev().collect();
+    while let Some(operation) = pending.pop() {
+        analysis.memory.extend(memory_local(operation));
+        match &operation.operation {
+            OperationType::Assign { left, right } => {
+                if let (Some(target), Some(value)) = (assigned_symbol(left), memory_local(right)) {
+                    match local(target) {
+                        Some(target) if target != value => aliases.push((target, value)),
+                        Some(_) => {}
+                        None if target.data_location.is_state() => {
+                            add_escape(&mut analysis, value, Escape::Storage)
+                        }
+                        None => {}
+                    }
+                }
+            }
+            OperationType::Return { ret } => {
+                if let Some(value) = memory_local(ret) {
+                    add_escape(&mut analysis, value, Escape::Return);
+                }
+            }
+            OperationType::Call { func, args } => {
+                for (i, arg) in args.iter().enumerate() {
+                    let kept = params
+                        .get(func)
+                        .and_then(|params| params.get(i).copied())
+                        .unwrap_or(true);
+                    if let (Some(value), true) = (memory_local(arg), kept) {
+                        add_escape(&mut analysis, value, Escape::Call(func.clone()));
+                    }
+                }
+            }
+            _ => {}
+        }
+        pending.extend(operation.children().into_iter().rev());
+    }
+
+    // A value escapes wherever the locals it is assigned to escape
+    loop {
+        let mut changed = false;
+        for (target, value) in &aliases {
+            let escapes = analysis.escapes.get(target).cloned().unwrap_or_default();
+            for target_escape in escapes {
+                let known = analysis.escapes.get(value);
+                if !known.is_some_and(|known| known.contains(&target_escape)) {
+                    add_escape(&mut analysis, value.clone(), target_escape);
+                    changed = true;
+                }
+            }
+        }
+        if !changed {
+            return analysis;
+        }
+    }
+}
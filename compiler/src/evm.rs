@@ -0,0 +1,105 @@
+//! Opcodes of the EVM which depend on the targeted hard fork.
+//!
+//! Code generated for an older `EvmVersion` replaces `PUSH0` with `PUSH1 0`.
+//! The other opcodes have no replacement, so features of the source needing
+//! them are rejected with an `EvmVersionError` naming the version to target,
+//! e.g. transient state variables before Cancun.
+
+use crate::error::EvmVersionError;
+use crate::settings::EvmVersion;
+use crate::symbol::SymbolLocation;
+use crate::symbol_table::{SymbolTable, SymbolTableType};
+use std::fmt;
+use zoker_parser::location::Location;
+
+/// Opcode missing from some of the targeted hard forks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Opcode {
+    /// Pushes zero, see EIP-3855
+    Push0,
+    /// Copies memory, see EIP-5656
+    Mcopy,
+    /// Loads from transient storage, see EIP-1153
+    Tload,
+    /// Stores to transient storage, see EIP-1153
+    Tstore,
+}
+
+impl Opcode {
+    /// First hard fork with the opcode.
+    pub fn introduced_in(self) -> EvmVersion {
+        match self {
+            Opcode::Push0 => EvmVersion::Shanghai,
+            Opcode::Mcopy | Opcode::Tload | Opcode::Tstore => EvmVersion::Cancun,
+        }
+    }
+}
+
+impl fmt::Display for Opcode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Opcode::Push0 => write!(f, "PUSH0"),
+            Opcode::Mcopy => write!(f, "MCOPY"),
+            Opcode::Tload => write!(f, "TLOAD"),
+            Opcode::Tstore => write!(f, "TSTORE"),
+        }
+    }
+}
+
+impl EvmVersion {
+    pub fn supports(self, opcode: Opcode) -> bool {
+        self >= opcode.introduced_in()
+    }
+
+    /// Size of pushing zero, `PUSH1 0` before `PUSH0` was introduced.
+    pub fn push_zero_size(self) -> usize {
+        if self.supports(Opcode::Push0) {
+            1
+        } else {
+            2
+        }
+    }
+}
+
+/// Checks that `feature` of the source at `location`, compiled to `opcode`, can target `evm_version`.
+pub fn require_opcode(
+    evm_version: EvmVersion,
+    opcode: Opcode,
+    feature: &str,
+    location: Location,
+) -> Result<(), EvmVersionError> {
+    if evm_version.supports(opcode) {
+        Ok(())
+    } else {
+        Err(EvmVersionError {
+            feature: feature.to_string(),
+            opcode,
+            evm_version,
+            location,
+        })
+    }
+}
+
+/// Checks that the contracts declared in `table` only need opcodes of `evm_version`.
+pub fn check_evm_version(
+    table: &SymbolTable,
+    evm_version: EvmVersion,
+) -> Result<(), EvmVersionError> {
+    let contracts = table
+        .sub_tables
+        .iter()
+        .filter(|table| table.table_type == SymbolTableType::Contract);
+    for contract in contracts {
+        for symbol in contract.symbols_in_source_order() {
+            if symbol.data_location == SymbolLocation::Transient {
+                require_opcode(
+                    evm_version,
+                    Opcode::Tstore,
+                    &format!("transient state variable `{}`", symbol.name),
+                    symbol.location,
+                )?;
+            }
+        }
+    }
+    Ok(())
+}
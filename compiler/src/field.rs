@@ -0,0 +1,191 @@
+//! Elements of the `field` type, the scalar field of BN254.
+//!
+//! Arithmetic on elements wraps modulo the prime of the field rather than
+//! 2^256, so operations relying on the order or the bits of integers don't mean
+//! the same on elements: `a - 1 < a` fails for zero, and the bits of `-a` are
+//! those of `p - a`. Such operations are reported, converting the elements with
+//! `field_to_uint` first gives the integer semantics.
+
+use crate::taint::descendants;
+use num_bigint::BigUint;
+use std::collections::HashSet;
+use std::fmt;
+use zoker_parser::ast::{
+    Expression, ExpressionType, Operator, Program, Statement, StatementType, Type,
+};
+use zoker_parser::complexity::Node;
+use zoker_parser::location::Location;
+
+/// Prime of the scalar field of BN254, the curve the proofs are verified on.
+pub fn field_modulus() -> BigUint {
+    BigUint::parse_bytes(
+        b"30644e72e131a029b85045b68181585d2833e84879b9709143e1f593f0000001",
+        16,
+    )
+    .unwrap()
+}
+
+/// Operation on elements assuming the semantics of integers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldOperation {
+    /// `<`, `<=`, `>` or `>=`, elements have no order compatible with their arithmetic
+    Comparison,
+    /// Bitwise operation or shift, of the bits of the representative below the prime
+    Bitwise,
+    /// `%`, division of elements has no remainder
+    Remainder,
+}
+
+impl fmt::Display for FieldOperation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FieldOperation::Comparison => write!(f, "comparison"),
+            FieldOperation::Bitwise => write!(f, "bitwise operation"),
+            FieldOperation::Remainder => write!(f, "remainder"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldWarning {
+    pub operation: FieldOperation,
+    /// Location of the operation
+    pub location: Location,
+}
+
+impl fmt::Display for FieldWarning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} of `field` elements assumes integer semantics, which don't hold modulo the prime: \
+             convert them with `field_to_uint` first",
+            self.operation
+        )
+    }
+}
+
+/// Operations on `field` elements in the functions of `program` assuming integer semantics.
+pub fn field_warnings(program: &Program) -> Vec<FieldWarning> {
+    let Program::GlobalStatements(statements) = program;
+    let mut warnings = vec![];
+    check_scope(statements, &HashSet::new(), &mut warnings);
+    warnings
+}
+
+/// Name of the variable declared by `statement` if it is a `field` element.
+fn declared_field(statement: &Statement) -> Option<String> {
+    match &statement.node {
+        StatementType::InitializerStatement {
+            variable_type: Type::Field,
+            variable: Some(variable),
+            ..
+        } => variable.node.identifier_name(),
+        _ => None,
+    }
+}
+
+/// Checks the functions among `statements`, with the elements `outer` declared around them.
+fn check_scope(
+    statements: &[Statement],
+    outer: &HashSet<String>,
+    warnings: &mut Vec<FieldWarning>,
+) {
+    let mut fields = outer.clone();
+    fields.extend(statements.iter().filter_map(declared_field));
+    for statement in statements {
+        match &statement.node {
+            StatementType::FunctionStatement {
+                parameters,
+                statement: body,
+                ..
+            } => {
+                let mut nodes = vec![Node::Expression(parameters)];
+                nodes.extend(body.as_deref().map(Node::Statement));
+                let nodes = descendants(nodes);
+                let mut fields = fields.clone();
+                fields.extend(nodes.iter().filter_map(|node| match node {
+                    Node::Statement(statement) => declared_field(statement),
+                    Node::Expression(_) => None,
+                }));
+                for node in nodes {
+                    if let Node::Expression(expression) = node {
+                        check_expression(expression, &fields, warnings);
+                    }
+                }
+            }
+            StatementType::ContractStatement { members, .. } => {
+                if let StatementType::MemberStatement { statements } = &members.node {
+                    check_scope(statements, &fields, warnings);
+                }
+            }
+            StatementType::ModuleStatement { members, .. } => {
+                check_scope(members, &fields, warnings)
+            }
+            StatementType::ConditionalStatement { statement, .. } => {
+                check_scope(std::slice::from_ref(statement), &fields, warnings)
+            }
+            _ => {}
+        }
+    }
+}
+
+fn check_expression(
+    expression: &Expression,
+    fields: &HashSet<String>,
+    warnings: &mut Vec<FieldWarning>,
+) {
+    let (left, operator, right) = match &expression.node {
+        ExpressionType::BinaryExpression {
+            left,
+            operator,
+            right,
+        }
+        | ExpressionType::AssignExpression {
+            left,
+            operator,
+            right,
+        } => (left, operator, right),
+        _ => return,
+    };
+    let operation = match operator {
+        Operator::Lt | Operator::Le | Operator::Gt | Operator::Ge => FieldOperation::Comparison,
+        Operator::BitAnd
+        | Operator::BitOr
+        | Operator::BitXor
+        | Operator::LShift
+        | Operator::RShift
+        | Operator::BitAndAssign
+        | Operator::BitOrAssign
+        | Operator::BitXorAssign
+        | Operator::LShiftAssign
+        | Operator::RShiftAssign => FieldOperation::Bitwise,
+        Operator::Mod | Operator::ModAssign => FieldOperation::Remainder,
+        _ => return,
+    };
+    if is_field(left, fields) || is_field(right, fields) {
+        warnings.push(FieldWarning {
+            operation,
+            location: expression.location,
+        });
+    }
+}
+
+/// Whether `expression` is obviously a `field` element.
+fn is_field(expression: &Expression, fields: &HashSet<String>) -> bool {
+    match &expression.node {
+        ExpressionType::Identifier { value } => fields.contains(value),
+        ExpressionType::FunctionCallExpression { function_name, .. } => {
+            function_name.node.identifier_name().as_deref() == Some("uint_to_field")
+        }
+        ExpressionType::BinaryExpression {
+            left,
+            operator: Operator::Add | Operator::Sub | Operator::Mul | Operator::Div | Operator::Pow,
+            right,
+        } => is_field(left, fields) || is_field(right, fields),
+        ExpressionType::UnaryExpression {
+            operator: Operator::Minus,
+            expression,
+        } => is_field(expression, fields),
+        _ => false,
+    }
+}
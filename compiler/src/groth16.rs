@@ -0,0 +1,214 @@
+//! Solidity verifiers of Groth16 proofs over BN254.
+//!
+//! The verifier of a function checks the pairing equation of its verifying key
+//! with the `ecAdd`, `ecMul` and `ecPairing` precompiles. The public inputs of the
+//! circuit are the public parameters of the function followed by its return
+//! values, so the key must have one more point in `ic` than there are of them.
+//!
+//! The keys come from the trusted setup of the circuit, which isn't run here.
+//! Coordinates of G2 points are in the order of the pairing precompile, the
+//! imaginary part first, for the keys and the proofs alike.
+
+use crate::error::VerifierError;
+use crate::field::field_modulus;
+use crate::symbol::Function;
+use num_bigint::BigUint;
+use std::fmt::Write;
+
+/// Point of G1, in affine coordinates.
+#[derive(Debug, Clone, PartialEq)]
+pub struct G1Point {
+    pub x: BigUint,
+    pub y: BigUint,
+}
+
+/// Point of G2, each coordinate as `[imaginary, real]`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct G2Point {
+    pub x: [BigUint; 2],
+    pub y: [BigUint; 2],
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct VerifyingKey {
+    pub alpha: G1Point,
+    pub beta: G2Point,
+    pub gamma: G2Point,
+    pub delta: G2Point,
+    /// Point of the constant term, then one per public input
+    pub ic: Vec<G1Point>,
+}
+
+/// Prime of the base field of BN254, the coordinates of the points.
+fn base_modulus() -> BigUint {
+    BigUint::parse_bytes(
+        b"30644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd47",
+        16,
+    )
+    .unwrap()
+}
+
+/// Number of public inputs of the circuit of `function`.
+pub fn public_inputs(function: &Function) -> usize {
+    let params = function.params.iter().filter(|param| !param.is_private);
+    params.count() + function.returns.len()
+}
+
+fn g1(point: &G1Point) -> String {
+    format!("G1Point({}, {})", point.x, point.y)
+}
+
+/// The first items are cast, so that arrays of small values are still of `uint256`.
+fn g2(point: &G2Point) -> String {
+    format!(
+        "G2Point([uint256({}), {}], [uint256({}), {}])",
+        point.x[0], point.x[1], point.y[0], point.y[1]
+    )
+}
+
+const HELPERS: &str = "    struct G1Point {
+        uint256 x;
+        uint256 y;
+    }
+
+    struct G2Point {
+        uint256[2] x;
+        uint256[2] y;
+    }
+
+    function negate(G1Point memory p) internal pure returns (G1Point memory) {
+        if (p.x == 0 && p.y == 0) {
+            return G1Point(0, 0);
+        }
+        return G1Point(p.x, PRIME_Q - (p.y % PRIME_Q));
+    }
+
+    function add(G1Point memory p, G1Point memory q) internal view returns (G1Point memory r) {
+        uint256[4] memory input = [p.x, p.y, q.x, q.y];
+        bool success;
+        assembly {
+            success := staticcall(gas(), 6, input, 0x80, r, 0x40)
+        }
+        require(success, \"ecAdd failed\");
+    }
+
+    function mul(G1Point memory p, uint256 s) internal view returns (G1Point memory r) {
+        uint256[3] memory input = [p.x, p.y, s];
+        bool success;
+        assembly {
+            success := staticcall(gas(), 7, input, 0x60, r, 0x40)
+        }
+        require(success, \"ecMul failed\");
+    }
+
+    function pairing(G1Point[4] memory a, G2Point[4] memory b) internal view returns (bool) {
+        uint256[24] memory input;
+        for (uint256 i = 0; i < 4; i++) {
+            input[i * 6] = a[i].x;
+            input[i * 6 + 1] = a[i].y;
+            input[i * 6 + 2] = b[i].x[0];
+            input[i * 6 + 3] = b[i].x[1];
+            input[i * 6 + 4] = b[i].y[0];
+            input[i * 6 + 5] = b[i].y[1];
+        }
+        uint256[1] memory out;
+        bool success;
+        assembly {
+            success := staticcall(gas(), 8, input, 768, out, 0x20)
+        }
+        require(success, \"ecPairing failed\");
+        return out[0] == 1;
+    }
+";
+
+/// Solidity source of the contract `name` verifying the proofs of `function` with `key`.
+pub fn verifier_contract(
+    name: &str,
+    function: &Function,
+    key: &VerifyingKey,
+) -> Result<String, VerifierError> {
+    let inputs = public_inputs(function);
+    if key.ic.len() != inputs + 1 {
+        return Err(VerifierError::new(&format!(
+            "verifying key has {} points in `ic` but `{}` has {} public inputs, which need {}",
+            key.ic.len(),
+            function.name,
+            inputs,
+            inputs + 1
+        )));
+    }
+    let g1_points = std::iter::once(&key.alpha).chain(&key.ic);
+    let g2_points = [&key.beta, &key.gamma, &key.delta];
+    let base = base_modulus();
+    if g1_points.clone().any(|p| p.x >= base || p.y >= base)
+        || g2_points
+            .iter()
+            .any(|p| p.x.iter().chain(&p.y).any(|c| *c >= base))
+    {
+        return Err(VerifierError::new(
+            "verifying key has coordinates outside the base field of BN254",
+        ));
+    }
+
+    let mut source = String::new();
+    writeln!(source, "// SPDX-License-Identifier: MIT").unwrap();
+    writeln!(source, "pragma solidity ^0.8.0;\n").unwrap();
+    writeln!(
+        source,
+        "/// Verifier of the Groth16 proofs of `{}`",
+        function.name
+    )
+    .unwrap();
+    writeln!(source, "contract {} {{", name).unwrap();
+    writeln!(source, "    uint256 constant PRIME_Q = {};", base).unwrap();
+    writeln!(
+        source,
+        "    uint256 constant SNARK_SCALAR_FIELD = {};\n",
+        field_modulus()
+    )
+    .unwrap();
+    writeln!(source, "{}", HELPERS).unwrap();
+
+    let input = if inputs > 0 {
+        format!(", uint256[{}] calldata input", inputs)
+    } else {
+        String::new()
+    };
+    writeln!(
+        source,
+        "    function verifyProof(uint256[2] calldata a, uint256[2][2] calldata b, \
+         uint256[2] calldata c{}) external view returns (bool) {{",
+        input
+    )
+    .unwrap();
+    writeln!(source, "        G1Point memory x = {};", g1(&key.ic[0])).unwrap();
+    for (i, point) in key.ic.iter().enumerate().skip(1) {
+        writeln!(
+            source,
+            "        require(input[{}] < SNARK_SCALAR_FIELD, \"input not in field\");",
+            i - 1
+        )
+        .unwrap();
+        writeln!(
+            source,
+            "        x = add(x, mul({}, input[{}]));",
+            g1(point),
+            i - 1
+        )
+        .unwrap();
+    }
+    writeln!(source, "        G1Point[4] memory p;").unwrap();
+    writeln!(source, "        G2Point[4] memory q;").unwrap();
+    writeln!(source, "        p[0] = negate(G1Point(a[0], a[1]));").unwrap();
+    writeln!(source, "        q[0] = G2Point(b[0], b[1]);").unwrap();
+    writeln!(source, "        p[1] = {};", g1(&key.alpha)).unwrap();
+    writeln!(source, "        q[1] = {};", g2(&key.beta)).unwrap();
+    writeln!(source, "        p[2] = x;").unwrap();
+    writeln!(source, "        q[2] = {};", g2(&key.gamma)).unwrap();
+    writeln!(source, "        p[3] = G1Point(c[0], c[1]);").unwrap();
+    writeln!(source, "        q[3] = {};", g2(&key.delta)).unwrap();
+    writeln!(source, "        return pairing(p, q);").unwrap();
+    writeln!(source, "    }}").unwrap();
+    writeln!(source, "}}").unwrap();
+    Ok(source)
+}
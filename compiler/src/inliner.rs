@@ -0,0 +1,446 @@
+//! Inlines calls of small, single-use or `@inline` functions into their callers.
+//!
+//! Circuit backends can't call functions, so every call they see must be inlined.
+//! Locals and parameters of the inlined function are renamed to `{callee}.{name}`
+//! and numbered after the variables of the caller, so they never clash with them.
+//! Recursive calls are never inlined.
+
+use crate::annotation::Annotation;
+use crate::symbol::{Contract, Function, Operation, OperationType, Symbol};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone)]
+pub struct InlineConfig {
+    /// Functions with at most this many operations are inlined.
+    pub size_threshold: usize,
+    /// Functions called once are inlined regardless of their size.
+    pub inline_single_use: bool,
+    /// Maximum nesting of inlined calls.
+    pub max_depth: usize,
+}
+
+impl Default for InlineConfig {
+    fn default() -> Self {
+        InlineConfig {
+            size_threshold: 16,
+            inline_single_use: true,
+            max_depth: 8,
+        }
+    }
+}
+
+impl InlineConfig {
+    /// Inlines every call which isn't recursive, as required by circuit backends.
+    pub fn all() -> Self {
+        InlineConfig {
+            size_threshold: usize::MAX,
+            inline_single_use: true,
+            max_depth: usize::MAX,
+        }
+    }
+}
+
+/// Inlines the calls between functions of `contract`.
+pub fn inline_contract(contract: &mut Contract, config: &InlineConfig) {
+    let originals = contract
+        .functions
+        .iter()
+        .map(|function| (function.name.clone(), function.clone()))
+        .collect::<HashMap<_, _>>();
+    let mut calls = HashMap::new();
+    for function in &contract.functions {
+        count_calls(&function.operations, &mut calls);
+    }
+    for function in &mut contract.functions {
+        let mut inliner = Inliner {
+            functions: &originals,
+            calls: &calls,
+            config,
+            stack: vec![function.name.clone()],
+            next_num: next_nums(function),
+        };
+        function.operations = inliner.inline_operations(std::mem::take(&mut function.operations));
+    }
+}
+
+/// Number of operations of a tree of operations.
+pub fn operation_size(operation: &Operation) -> usize {
+    1 + operation
+        .children()
+        .iter()
+        .map(|op| operation_size(op))
+        .sum::<usize>()
+}
+
+fn count_calls(operations: &[Operation], calls: &mut HashMap<String, usize>) {
+    for operation in operations {
+        visit(operation, &mut |op| {
+            if let OperationType::Call { func, .. } = &op.operation {
+                *calls.entry(func.clone()).or_insert(0) += 1;
+            }
+        });
+    }
+}
+
+fn collect_symbols<'a>(operation: &'a Operation, symbols: &mut Vec<&'a Symbol>) {
+    if let OperationType::Symbol { symbol } = &operation.operation {
+        symbols.push(symbol);
+    }
+    for child in operation.children() {
+        collect_symbols(child, symbols);
+    }
+}
+
+/// Next free variable numbers of a function, for public and private variables.
+fn next_nums(function: &Function) -> (u32, u32) {
+    let mut symbols = function.params.iter().collect::<Vec<_>>();
+    for operation in &function.operations {
+        collect_symbols(operation, &mut symbols);
+    }
+    symbols
+        .iter()
+        .filter(|symbol| !symbol.name.is_empty())
+        .fold((0, 0), |(public, private), symbol| {
+            if symbol.is_private {
+                (public, private.max(symbol.num + 1))
+            } else {
+                (public.max(symbol.num + 1), private)
+            }
+        })
+}
+
+/// Body of an inlinable function, split into its statements and returned value.
+/// Functions returning from anywhere but their last operation aren't inlinable.
+fn split_body(function: &Function) -> Option<(Vec<Operation>, Operation)> {
+    let mut body = function.operations.clone();
+    let value = match body.last().map(|op| &op.operation) {
+        Some(OperationType::Return { ret }) => {
+            let ret = ret.as_ref().clone();
+            body.pop();
+            ret
+        }
+        // The last expression of a block is its value.
+        Some(_) if !function.returns.is_empty() => body.pop().unwrap(),
+        _ => Operation::new(OperationType::Nop),
+    };
+    let mut returns_early = false;
+    for operation in &body {
+        visit(operation, &mut |op| {
+            returns_early |= matches!(op.operation, OperationType::Return { .. })
+        });
+    }
+    if returns_early {
+        None
+    } else {
+        Some((body, value))
+    }
+}
+
+fn visit(operation: &Operation, f: &mut impl FnMut(&Operation)) {
+    f(operation);
+    for child in operation.children() {
+        visit(child, f);
+    }
+}
+
+struct Inliner<'a> {
+    functions: &'a HashMap<String, Function>,
+    calls: &'a HashMap<String, usize>,
+    config: &'a InlineConfig,
+    /// Functions being inlined, the caller first.
+    stack: Vec<String>,
+    next_num: (u32, u32),
+}
+
+impl<'a> Inliner<'a> {
+    fn inline_operations(&mut self, operations: Vec<Operation>) -> Vec<Operation> {
+        let mut result = vec![];
+        for operation in operations {
+            let mut prelude = vec![];
+            let operation = self.inline_operation(operation, &mut prelude);
+            result.extend(prelude);
+            result.push(operation);
+        }
+        result
+    }
+
+    /// Inlines calls in `operation`, putting the statements of inlined bodies in `prelude`.
+    fn inline_operation(
+        &mut self,
+        operation: Operation,
+        prelude: &mut Vec<Operation>,
+    ) -> Operation {
+        let op = match operation.operation {
+            OperationType::Add { left, right } => OperationType::Add {
+                left: Box::new(self.inline_operation(*left, prelude)),
+                right: Box::new(self.inline_operation(*right, prelude)),
+            },
+            OperationType::Sub { left, right } => OperationType::Sub {
+                left: Box::new(self.inline_operation(*left, prelude)),
+                right: Box::new(self.inline_operation(*right, prelude)),
+            },
+            OperationType::Mul { left, right } => OperationType::Mul {
+                left: Box::new(self.inline_operation(*left, prelude)),
+                right: Box::new(self.inline_operation(*right, prelude)),
+            },
+            OperationType::Div { left, right } => OperationType::Div {
+                left: Box::new(self.inline_operation(*left, prelude)),
+                right: Box::new(self.inline_operation(*right, prelude)),
+            },
+            OperationType::Pow { left, right } => OperationType::Pow {
+                left: Box::new(self.inline_operation(*left, prelude)),
+                right: Box::new(self.inline_operation(*right, prelude)),
+            },
+            OperationType::Shl { left, right } => OperationType::Shl {
+                left: Box::new(self.inline_operation(*left, prelude)),
+                right: Box::new(self.inline_operation(*right, prelude)),
+            },
+            OperationType::Shr { left, right } => OperationType::Shr {
+                left: Box::new(self.inline_operation(*left, prelude)),
+                right: Box::new(self.inline_operation(*right, prelude)),
+            },
+            OperationType::Assign { left, right } => OperationType::Assign {
+                left: Box::new(self.inline_operation(*left, prelude)),
+                right: Box::new(self.inline_operation(*right, prelude)),
+            },
+            OperationType::Index { array, index } => OperationType::Index {
+                array: Box::new(self.inline_operation(*array, prelude)),
+                index: Box::new(self.inline_operation(*index, prelude)),
+            },
+            OperationType::BoundsCheck { array, index } => OperationType::BoundsCheck {
+                array: Box::new(self.inline_operation(*array, prelude)),
+                index: Box::new(self.inline_operation(*index, prelude)),
+            },
+            OperationType::For {
+                iter,
+                vector,
+                stmts,
+            } => OperationType::For {
+                iter,
+                vector: Box::new(self.inline_operation(*vector, prelude)),
+                stmts: self.inline_operations(stmts),
+            },
+            OperationType::If { cond, stmts } => OperationType::If {
+                cond: Box::new(self.inline_operation(*cond, prelude)),
+                stmts: self.inline_operations(stmts),
+            },
+            OperationType::Else { cond, stmts } => OperationType::Else {
+                cond: Box::new(self.inline_operation(*cond, prelude)),
+                stmts: self.inline_operations(stmts),
+            },
+            OperationType::Return { ret } => OperationType::Return {
+                ret: Box::new(self.inline_operation(*ret, prelude)),
+            },
+            OperationType::Builtin { function, args } => OperationType::Builtin {
+                function,
+                args: args
+                    .into_iter()
+                    .map(|arg| self.inline_operation(arg, prelude))
+                    .collect(),
+            },
+            OperationType::Call { func, args } => {
+                let args = args
+                    .into_iter()
+                    .map(|arg| self.inline_operation(arg, prelude))
+                    .collect::<Vec<_>>();
+                return match self.inlinable(&func, args.len()) {
+                    Some((body, value)) => self.inline_call(&func, args, body, value, prelude),
+                    None => Operation::new_call(func, args),
+                };
+            }
+            op @ OperationType::Symbol { .. }
+            | op @ OperationType::Constant { .. }
+            | op @ OperationType::Nop => op,
+        };
+        Operation::new(op)
+    }
+
+    fn inlinable(&self, name: &str, arity: usize) -> Option<(Vec<Operation>, Operation)> {
+        if self.stack.iter().any(|caller| caller == name)
+            || self.stack.len() > self.config.max_depth
+        {
+            return None;
+        }
+        let function = self.functions.get(name)?;
+        if function.params.len() != arity {
+            return None;
+        }
+        let size = function
+            .operations
+            .iter()
+            .map(operation_size)
+            .sum::<usize>();
+        let single_use = self.config.inline_single_use && self.calls.get(name) == Some(&1);
+        // `@inline` functions are inlined whatever their size
+        let annotated = function.annotations.contains(&Annotation::Inline);
+        if size > self.config.size_threshold && !single_use && !annotated {
+            return None;
+        }
+        split_body(function)
+    }
+
+    fn inline_call(
+        &mut self,
+        name: &str,
+        args: Vec<Operation>,
+        body: Vec<Operation>,
+        value: Operation,
+        prelude: &mut Vec<Operation>,
+    ) -> Operation {
+        let function = &self.functions[name];
+        let mut renames = HashMap::new();
+        for (param, arg) in function.params.iter().zip(args) {
+            let local = self.rename(name, param, &mut renames);
+            prelude.push(Operation::new(OperationType::Assign {
+                left: Box::new(Operation::new_symbol(local)),
+                right: Box::new(arg),
+            }));
+        }
+        let body = body
+            .into_iter()
+            .map(|op| self.remap(name, op, &mut renames))
+            .collect::<Vec<_>>();
+        let value = self.remap(name, value, &mut renames);
+
+        // Calls of the inlined body are inlined too, with the callee on the stack.
+        self.stack.push(name.to_string());
+        prelude.extend(self.inline_operations(body));
+        let value = self.inline_operation(value, prelude);
+        self.stack.pop();
+        value
+    }
+
+    fn remap(
+        &mut self,
+        callee: &str,
+        operation: Operation,
+        renames: &mut HashMap<(String, u32, bool), Symbol>,
+    ) -> Operation {
+        let remap_box = |inliner: &mut Self, op: Box<Operation>, renames: &mut _| {
+            Box::new(inliner.remap(callee, *op, renames))
+        };
+        let op = match operation.operation {
+            OperationType::Symbol { symbol } => OperationType::Symbol {
+                // Type symbols and storage variables are shared with the caller.
+                symbol: if symbol.name.is_empty() || symbol.data_location.is_state() {
+                    symbol
+                } else {
+                    self.rename(callee, &symbol, renames)
+                },
+            },
+            OperationType::Add { left, right } => OperationType::Add {
+                left: remap_box(self, left, renames),
+                right: remap_box(self, right, renames),
+            },
+            OperationType::Sub { left, right } => OperationType::Sub {
+                left: remap_box(self, left, renames),
+                right: remap_box(self, right, renames),
+            },
+            OperationType::Mul { left, right } => OperationType::Mul {
+                left: remap_box(self, left, renames),
+                right: remap_box(self, right, renames),
+            },
+            OperationType::Div { left, right } => OperationType::Div {
+                left: remap_box(self, left, renames),
+                right: remap_box(self, right, renames),
+            },
+            OperationType::Pow { left, right } => OperationType::Pow {
+                left: remap_box(self, left, renames),
+                right: remap_box(self, right, renames),
+            },
+            OperationType::Shl { left, right } => OperationType::Shl {
+                left: remap_box(self, left, renames),
+                right: remap_box(self, right, renames),
+            },
+            OperationType::Shr { left, right } => OperationType::Shr {
+                left: remap_box(self, left, renames),
+                right: remap_box(self, right, renames),
+            },
+            OperationType::Assign { left, right } => OperationType::Assign {
+                left: remap_box(self, left, renames),
+                right: remap_box(self, right, renames),
+            },
+            OperationType::Index { array, index } => OperationType::Index {
+                array: remap_box(self, array, renames),
+                index: remap_box(self, index, renames),
+            },
+            OperationType::BoundsCheck { array, index } => OperationType::BoundsCheck {
+                array: remap_box(self, array, renames),
+                index: remap_box(self, index, renames),
+            },
+            OperationType::For {
+                iter,
+                vector,
+                stmts,
+            } => OperationType::For {
+                iter: remap_box(self, iter, renames),
+                vector: remap_box(self, vector, renames),
+                stmts: stmts
+                    .into_iter()
+                    .map(|op| self.remap(callee, op, renames))
+                    .collect(),
+            },
+            OperationType::If { cond, stmts } => OperationType::If {
+                cond: remap_box(self, cond, renames),
+                stmts: stmts
+                    .into_iter()
+                    .map(|op| self.remap(callee, op, renames))
+                    .collect(),
+            },
+            OperationType::Else { cond, stmts } => OperationType::Else {
+                cond: remap_box(self, cond, renames),
+                stmts: stmts
+                    .into_iter()
+                    .map(|op| self.remap(callee, op, renames))
+                    .collect(),
+            },
+            OperationType::Return { ret } => OperationType::Return {
+                ret: remap_box(self, ret, renames),
+            },
+            OperationType::Call { func, args } => OperationType::Call {
+                func,
+                args: args
+                    .into_iter()
+                    .map(|op| self.remap(callee, op, renames))
+                    .collect(),
+            },
+            OperationType::Builtin { function, args } => OperationType::Builtin {
+                function,
+                args: args
+                    .into_iter()
+                    .map(|op| self.remap(callee, op, renames))
+                    .collect(),
+            },
+            op @ OperationType::Constant { .. } | op @ OperationType::Nop => op,
+        };
+        Operation::new(op)
+    }
+
+    /// Fresh variable of the caller standing for `symbol` of the callee.
+    fn rename(
+        &mut self,
+        callee: &str,
+        symbol: &Symbol,
+        renames: &mut HashMap<(String, u32, bool), Symbol>,
+    ) -> Symbol {
+        let key = (symbol.name.clone(), symbol.num, symbol.is_private);
+        if let Some(renamed) = renames.get(&key) {
+            return renamed.clone();
+        }
+        let num = if symbol.is_private {
+            &mut self.next_num.1
+        } else {
+            &mut self.next_num.0
+        };
+        let renamed = Symbol::new(
+            format!("{}.{}", callee, symbol.name),
+            *num,
+            symbol.symbol_type.clone(),
+            symbol.data_location.clone(),
+            symbol.is_private,
+        );
+        *num += 1;
+        renames.insert(key, renamed.clone());
+        renamed
+    }
+}
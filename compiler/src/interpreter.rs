@@ -0,0 +1,1709 @@
+//! Interpreter of the AST, used to run tests without deploying contracts.
+//!
+//! The program is expected as checked by the symbol table, after `strip_disabled`.
+//! Every contract has a single instance whose state is initialized on its first
+//! use. Constructs with no meaning outside the EVM, e.g. calls to other contracts,
+//! are reported as unsupported. Hash functions take the bytes of a string, or the
+//! 32 bytes of an integer, and give their digest as an integer. `ecrecover` needs
+//! the `secp256k1` feature. The builtins of `abi` encode values as the types of
+//! their declarations, and decoding malformed data reverts.
+//!
+//! Arithmetic is done in the type of its operands, known from the declarations of
+//! the variables, parameters and state variables they read:
+//!
+//! - results out of the bounds of an integer type revert, or wrap around inside
+//!   `unchecked` blocks, as do the values assigned to integer variables,
+//! - shifts wrap around, a shift by more than the width of the type giving 0,
+//! - `field` elements are reduced modulo the prime of the field,
+//! - operands of no known type, e.g. literals or constants, are bounded by
+//!   256 bits in magnitude.
+//!
+//! Exponents and shift amounts are capped before computing, so that evaluating
+//! `2 ** 4000000000` reverts instead of allocating the result.
+//!
+//! Arguments out of the range of their parameter revert, as would calldata failing
+//! to decode. Recursion fails with `CallDepthExceeded` once it is `MAX_CALL_DEPTH`
+//! calls deep, or earlier if it takes too much of the stack.
+//!
+//! A debugger observes the execution through a hook, called before the statements
+//! on which execution pauses: every statement while stepping, or those on the
+//! lines of the breakpoints. The hook sees the statement, the local variables and
+//! the state changed since the call started, and tells how to go on.
+//!
+//! With coverage counters set, e.g. from `coverage::instrument`, the lines and
+//! branches of the program are counted as they run.
+
+use crate::abi::{self, AbiValue};
+use crate::builtin::BuiltinFunction;
+use crate::coverage::Coverage;
+use crate::error::{AbiError, ExecutionError, ExecutionErrorType};
+use crate::field::field_modulus;
+#[cfg(feature = "secp256k1")]
+use crate::precompile;
+use crate::stdlib::{std_module, STD};
+use crate::symbol::resolve_type;
+use blake2::Blake2s256;
+use num_bigint::{BigInt, Sign};
+use num_traits::{One, Signed, ToPrimitive, Zero};
+use sha2::Sha256;
+use sha3::{Digest, Keccak256};
+use std::collections::HashMap;
+use std::fmt;
+use zoker_parser::ast;
+use zoker_parser::ast::{ExpressionType, Operator, StatementType};
+use zoker_parser::location::Location;
+
+pub type ExecutionResult<T> = Result<T, ExecutionError>;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Int(BigInt),
+    Bool(bool),
+    String(String),
+    /// Bytes computed by the program, e.g. by `abi.encode`
+    Bytes(Vec<u8>),
+    Array(Vec<Value>),
+    Tuple(Vec<Value>),
+    /// Values of the keys written so far, the others having the default value
+    Mapping {
+        entries: Vec<(Value, Value)>,
+        default: Box<Value>,
+    },
+    /// Value of statements and functions returning nothing
+    Unit,
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Int(value) => write!(f, "{}", value),
+            Value::Bool(value) => write!(f, "{}", value),
+            Value::String(value) => write!(f, "{:?}", value),
+            Value::Bytes(bytes) => {
+                write!(f, "0x")?;
+                bytes.iter().try_for_each(|byte| write!(f, "{:02x}", byte))
+            }
+            Value::Array(items) => {
+                let items: Vec<String> = items.iter().map(|item| item.to_string()).collect();
+                write!(f, "[{}]", items.join(", "))
+            }
+            Value::Tuple(items) => {
+                let items: Vec<String> = items.iter().map(|item| item.to_string()).collect();
+                write!(f, "({})", items.join(", "))
+            }
+            Value::Mapping { entries, .. } => {
+                let entries: Vec<String> = entries
+                    .iter()
+                    .map(|(key, value)| format!("{} => {}", key, value))
+                    .collect();
+                write!(f, "{{{}}}", entries.join(", "))
+            }
+            Value::Unit => write!(f, "()"),
+        }
+    }
+}
+
+/// Function declared in the program.
+#[derive(Debug, Clone)]
+pub struct FunctionItem<'a> {
+    /// Name qualified by its contract or modules, e.g. `Token.transfer` or `math::clamp`
+    pub name: String,
+    pub contract: Option<String>,
+    pub modules: Vec<String>,
+    pub statement: &'a ast::Statement,
+}
+
+/// Function being run.
+struct Frame {
+    /// Qualified name of the function, `None` for the initializers of constants and state
+    function: Option<String>,
+    contract: Option<String>,
+    modules: Vec<String>,
+    scopes: Vec<HashMap<String, Value>>,
+    /// Declared types of the variables of each scope, for the bounds of their arithmetic
+    types: Vec<HashMap<String, ast::Type>>,
+}
+
+impl Frame {
+    fn push_scope(&mut self) {
+        self.scopes.push(HashMap::new());
+        self.types.push(HashMap::new());
+    }
+
+    fn pop_scope(&mut self) {
+        self.scopes.pop();
+        self.types.pop();
+    }
+
+    /// Declares `name` in the innermost scope, with its type if it is known.
+    fn declare(&mut self, name: String, value: Value, typ: Option<&ast::Type>) {
+        if let Some(typ) = typ {
+            self.types
+                .last_mut()
+                .unwrap()
+                .insert(name.clone(), typ.clone());
+        }
+        self.scopes.last_mut().unwrap().insert(name, value);
+    }
+}
+
+/// Width of the widest integers, bounding the magnitude of untyped ones.
+const MAX_BITS: usize = 256;
+
+/// Depth of the calls being run past which a call fails, as in the EVM.
+pub const MAX_CALL_DEPTH: usize = 1024;
+
+/// Bytes of stack the calls being run may take, counted from the outermost one. Each
+/// level of recursion takes tens of kilobytes in debug builds, so the stack runs
+/// out well before `MAX_CALL_DEPTH`, half of the 2 MiB of a test thread is kept.
+const STACK_BUDGET: usize = 1 << 20;
+
+/// Integers the arithmetic of an expression is done in, from the type of its operands.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Domain {
+    /// Literals and constants, bounded by `MAX_BITS` in magnitude
+    Untyped,
+    /// Integers of a type, which wrap around if they aren't checked
+    Integer {
+        signed: bool,
+        bits: u16,
+        checked: bool,
+    },
+    /// Elements of the scalar field, reduced modulo its prime
+    Field,
+}
+
+impl Domain {
+    fn of(typ: Option<&ast::Type>, checked: bool) -> Self {
+        let (signed, bits) = match typ {
+            Some(ast::Type::Field) => return Domain::Field,
+            Some(ast::Type::Uint256) => (false, 256),
+            Some(ast::Type::Int256) => (true, 256),
+            Some(ast::Type::Uint(bits)) => (false, *bits),
+            Some(ast::Type::Int(bits)) => (true, *bits),
+            _ => return Domain::Untyped,
+        };
+        Domain::Integer {
+            signed,
+            bits,
+            checked,
+        }
+    }
+
+    /// `value` wrapped around or reduced into the domain, or an error if it overflows.
+    fn fit(self, value: BigInt, location: Location) -> ExecutionResult<BigInt> {
+        match self {
+            Domain::Untyped if value.bits() > MAX_BITS => overflow(location),
+            Domain::Untyped => Ok(value),
+            Domain::Integer {
+                signed,
+                bits,
+                checked,
+            } => {
+                let modulus = BigInt::one() << bits as usize;
+                let (min, max) = bounds(signed, bits);
+                if min <= value && value <= max {
+                    return Ok(value);
+                }
+                if checked {
+                    return overflow(location);
+                }
+                let wrapped = reduce(value, &modulus);
+                Ok(if wrapped > max {
+                    wrapped - modulus
+                } else {
+                    wrapped
+                })
+            }
+            Domain::Field => Ok(reduce(value, &BigInt::from(field_modulus()))),
+        }
+    }
+
+    /// Whether `value` is in the domain as it is, without wrapping around or reducing it.
+    fn contains(self, value: &BigInt) -> bool {
+        match self {
+            Domain::Untyped => value.bits() <= MAX_BITS,
+            Domain::Integer { signed, bits, .. } => {
+                let (min, max) = bounds(signed, bits);
+                min <= *value && *value <= max
+            }
+            Domain::Field => !value.is_negative() && *value < BigInt::from(field_modulus()),
+        }
+    }
+
+    /// Modulus the domain wraps around, `None` if its results are checked.
+    fn modulus(self) -> Option<BigInt> {
+        match self {
+            Domain::Integer {
+                bits,
+                checked: false,
+                ..
+            } => Some(BigInt::one() << bits as usize),
+            Domain::Field => Some(BigInt::from(field_modulus())),
+            _ => None,
+        }
+    }
+}
+
+/// Smallest and largest integers of `bits` bits.
+fn bounds(signed: bool, bits: u16) -> (BigInt, BigInt) {
+    if signed {
+        let half = BigInt::one() << (bits as usize - 1);
+        (-half.clone(), half - 1)
+    } else {
+        (BigInt::zero(), (BigInt::one() << bits as usize) - 1)
+    }
+}
+
+/// ABI value of `value`, whose declaration has type `typ` if it is known.
+fn to_abi(value: Value, typ: Option<&ast::Type>, location: Location) -> ExecutionResult<AbiValue> {
+    Ok(match (value, typ) {
+        (Value::Bool(value), _) => AbiValue::Bool(value),
+        (Value::Bytes(bytes), _) => AbiValue::Bytes(bytes),
+        (Value::String(value), Some(ast::Type::Bytes)) => AbiValue::Bytes(value.into_bytes()),
+        (Value::String(value), _) => AbiValue::String(value),
+        (Value::Int(value), Some(ast::Type::Address)) => {
+            AbiValue::Address(to_word(&value, location)?[12..].to_vec())
+        }
+        (Value::Int(value), Some(ast::Type::Bytes32)) => {
+            AbiValue::Bytes32(to_word(&value, location)?.to_vec())
+        }
+        (Value::Int(value), Some(ast::Type::Int256 | ast::Type::Int(_))) => AbiValue::Int(value),
+        (Value::Int(value), _) => match value.to_biguint() {
+            Some(value) => AbiValue::Uint(value),
+            None => AbiValue::Int(value),
+        },
+        _ => return unsupported("encoding a composite value", location),
+    })
+}
+
+/// Value of a decoded ABI value, where addresses and `bytes32` are integers.
+fn from_abi(value: AbiValue) -> Value {
+    match value {
+        AbiValue::Uint(value) => Value::Int(value.into()),
+        AbiValue::Int(value) => Value::Int(value),
+        AbiValue::Address(bytes) | AbiValue::Bytes32(bytes) => {
+            Value::Int(BigInt::from_bytes_be(Sign::Plus, &bytes))
+        }
+        AbiValue::Bool(value) => Value::Bool(value),
+        AbiValue::Bytes(bytes) => Value::Bytes(bytes),
+        AbiValue::String(value) => Value::String(value),
+    }
+}
+
+/// Digest of `data` by the hash function `builtin`.
+fn digest(builtin: BuiltinFunction, data: &[u8]) -> [u8; 32] {
+    match builtin {
+        BuiltinFunction::Keccak256 => Keccak256::digest(data).into(),
+        BuiltinFunction::Sha256 => Sha256::digest(data).into(),
+        BuiltinFunction::Blake2s => Blake2s256::digest(data).into(),
+        _ => unreachable!(),
+    }
+}
+
+/// Big-endian bytes of `value` as a 32-byte word, e.g. a `bytes32` hashed again.
+fn to_word(value: &BigInt, location: Location) -> ExecutionResult<[u8; 32]> {
+    let (sign, bytes) = value.to_bytes_be();
+    if sign == Sign::Minus || bytes.len() > 32 {
+        return overflow(location);
+    }
+    let mut word = [0; 32];
+    word[32 - bytes.len()..].copy_from_slice(&bytes);
+    Ok(word)
+}
+
+/// Address on the stack of the caller, which grows downwards on the supported targets.
+#[inline(never)]
+fn stack_position() -> usize {
+    let marker = 0u8;
+    std::hint::black_box(&marker) as *const u8 as usize
+}
+
+/// Remainder of `value` divided by `modulus`, between 0 and `modulus`.
+fn reduce(value: BigInt, modulus: &BigInt) -> BigInt {
+    let remainder = value % modulus;
+    if remainder.is_negative() {
+        remainder + modulus
+    } else {
+        remainder
+    }
+}
+
+/// What stops the evaluation of a function body.
+enum Interrupt {
+    Return(Value),
+    Error(ExecutionError),
+}
+
+impl From<ExecutionError> for Interrupt {
+    fn from(err: ExecutionError) -> Self {
+        Interrupt::Error(err)
+    }
+}
+
+type Flow<T> = Result<T, Interrupt>;
+
+fn error<T>(error: ExecutionErrorType, location: Location) -> ExecutionResult<T> {
+    Err(ExecutionError { error, location })
+}
+
+fn overflow<T>(location: Location) -> ExecutionResult<T> {
+    error(
+        ExecutionErrorType::Revert(Some(String::from("arithmetic overflow"))),
+        location,
+    )
+}
+
+fn unsupported<T>(construct: &str, location: Location) -> ExecutionResult<T> {
+    error(
+        ExecutionErrorType::Unsupported(construct.to_string()),
+        location,
+    )
+}
+
+/// Name of a function or constant as known to the interpreter, e.g. `Token.transfer`.
+pub(crate) fn qualified_name(contract: Option<&str>, modules: &[String], name: &str) -> String {
+    match contract {
+        Some(contract) => format!("{}.{}", contract, name),
+        None if modules.is_empty() => name.to_string(),
+        None => format!("{}::{}", modules.join("::"), name),
+    }
+}
+
+/// Default value of a variable declared without one.
+fn default_value(typ: &ast::Type) -> Value {
+    match typ {
+        ast::Type::Bool => Value::Bool(false),
+        ast::Type::String | ast::Type::Bytes => Value::String(String::new()),
+        ast::Type::Array(_) | ast::Type::FixedArray(..) => Value::Array(vec![]),
+        ast::Type::UserDefined(_) => Value::Unit,
+        ast::Type::Mapping(_, value) => Value::Mapping {
+            entries: vec![],
+            default: Box::new(default_value(value)),
+        },
+        _ => Value::Int(BigInt::zero()),
+    }
+}
+
+/// Change of a state variable since the call started.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StorageChange {
+    pub contract: String,
+    pub name: String,
+    pub old: Value,
+    pub new: Value,
+}
+
+/// Statement on which execution paused, as seen by the debug hook.
+#[derive(Debug)]
+pub struct StepEvent<'s> {
+    pub statement: &'s ast::Statement,
+    /// Qualified name of the function being run, e.g. `Token.transfer`
+    pub function: &'s str,
+    /// Local variables in scope, sorted by name
+    pub locals: Vec<(String, Value)>,
+    /// State variables changed since the call started, sorted by contract and name
+    pub storage_diff: Vec<StorageChange>,
+}
+
+/// How execution goes on after a pause.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StepAction {
+    /// Pauses on the next statement
+    Step,
+    /// Runs up to the next breakpoint
+    Continue,
+}
+
+type DebugHook<'a> = Box<dyn FnMut(&StepEvent) -> StepAction + 'a>;
+
+pub struct Interpreter<'a> {
+    functions: HashMap<String, FunctionItem<'a>>,
+    /// Constants declared outside contracts by qualified name, e.g. `math::MAX`
+    constants: HashMap<String, &'a ast::Expression>,
+    /// Names of the libraries
+    libraries: Vec<String>,
+    /// Libraries attached to types in each contract or library, by `using`
+    using: HashMap<String, Vec<(String, &'a ast::Type)>>,
+    /// State variables declared in each contract
+    state_variables: HashMap<String, Vec<&'a ast::Statement>>,
+    /// State of each contract used so far
+    state: HashMap<String, HashMap<String, Value>>,
+    /// State when the call started, to tell what it changed
+    initial_state: HashMap<String, HashMap<String, Value>>,
+    frames: Vec<Frame>,
+    /// Position of the stack when the outermost frame was pushed, see `STACK_BUDGET`
+    stack_base: usize,
+    /// Depth of the `unchecked` blocks being run in the current function
+    unchecked: usize,
+    hook: Option<DebugHook<'a>>,
+    /// Lines of the breakpoints
+    breakpoints: Vec<usize>,
+    stepping: bool,
+    coverage: Option<Coverage>,
+}
+
+impl<'a> Interpreter<'a> {
+    pub fn new(program: &'a ast::Program) -> Self {
+        let mut interpreter = Interpreter {
+            functions: HashMap::new(),
+            constants: HashMap::new(),
+            libraries: vec![],
+            using: HashMap::new(),
+            state_variables: HashMap::new(),
+            state: HashMap::new(),
+            initial_state: HashMap::new(),
+            frames: vec![],
+            stack_base: 0,
+            unchecked: 0,
+            hook: None,
+            breakpoints: vec![],
+            stepping: false,
+            coverage: None,
+        };
+        let ast::Program::GlobalStatements(statements) = program;
+        interpreter.declare(statements, None, &[]);
+        interpreter
+    }
+
+    fn declare(
+        &mut self,
+        statements: &'a [ast::Statement],
+        contract: Option<&str>,
+        modules: &[String],
+    ) {
+        for statement in statements {
+            match &statement.node {
+                StatementType::FunctionStatement {
+                    function_name,
+                    statement: Some(_),
+                    ..
+                } => {
+                    let name = function_name.node.identifier_name().unwrap();
+                    let name = qualified_name(contract, modules, &name);
+                    let function = FunctionItem {
+                        name: name.clone(),
+                        contract: contract.map(String::from),
+                        modules: modules.to_vec(),
+                        statement,
+                    };
+                    self.functions.insert(name, function);
+                }
+                StatementType::ContractStatement {
+                    kind,
+                    contract_name,
+                    members,
+                    ..
+                } => {
+                    let name = contract_name.node.identifier_name().unwrap();
+                    if *kind == ast::ContractKind::Library {
+                        self.libraries.push(name.clone());
+                    }
+                    self.state_variables.entry(name.clone()).or_default();
+                    if let StatementType::MemberStatement { statements } = &members.node {
+                        self.declare(statements, Some(&name), &[]);
+                    }
+                }
+                StatementType::ModuleStatement {
+                    module_name,
+                    members,
+                } => {
+                    let mut modules = modules.to_vec();
+                    modules.push(module_name.node.identifier_name().unwrap());
+                    self.declare(members, None, &modules);
+                }
+                StatementType::UsingStatement { library, target } => {
+                    if let Some(contract) = contract {
+                        let library = library.node.identifier_name().unwrap();
+                        self.using
+                            .entry(contract.to_string())
+                            .or_default()
+                            .push((library, target));
+                    }
+                }
+                StatementType::ImportStatement { path } => {
+                    if let Some(module) = std_module(path) {
+                        let statements = std::slice::from_ref(&module.statement);
+                        self.declare(statements, None, &[STD.to_string()]);
+                    }
+                }
+                StatementType::InitializerStatement {
+                    variable: Some(variable),
+                    ..
+                } => match contract {
+                    Some(contract) => self
+                        .state_variables
+                        .entry(contract.to_string())
+                        .or_default()
+                        .push(statement),
+                    None => {
+                        if let StatementType::InitializerStatement {
+                            default: Some(default),
+                            ..
+                        } = &statement.node
+                        {
+                            let name = variable.node.identifier_name().unwrap();
+                            let name = qualified_name(None, modules, &name);
+                            self.constants.insert(name, default);
+                        }
+                    }
+                },
+                _ => {}
+            }
+        }
+    }
+
+    /// Functions of the program, in no particular order.
+    pub fn functions(&self) -> impl Iterator<Item = &FunctionItem<'a>> {
+        self.functions.values()
+    }
+
+    /// Calls the function named `name`, e.g. `Token.transfer` or `math::clamp`, or
+    /// the getter of a state variable declared `public`, e.g. `Token.balances`.
+    pub fn call(&mut self, name: &str, args: Vec<Value>) -> ExecutionResult<Value> {
+        let function = match self.functions.get(name) {
+            Some(function) => function.clone(),
+            None => return self.call_getter(name, args),
+        };
+        if let Some(contract) = &function.contract {
+            self.contract_state(contract)?;
+        }
+        self.initial_state = self.state.clone();
+        self.call_function(&function, args, Location::default())
+    }
+
+    /// Value of the public state variable `name` reached by indexing it with `args`.
+    fn call_getter(&mut self, name: &str, args: Vec<Value>) -> ExecutionResult<Value> {
+        let location = Location::default();
+        let (contract, variable) = name.split_once('.').unwrap_or(("", name));
+        let is_public = self
+            .state_variables
+            .get(contract)
+            .is_some_and(|declarations| {
+                declarations.iter().any(|declaration| {
+                    matches!(
+                        &declaration.node,
+                        StatementType::InitializerStatement {
+                            variable: Some(declared),
+                            visibility: Some(ast::Visibility::Public),
+                            ..
+                        } if declared.node.identifier_name().as_deref() == Some(variable)
+                    )
+                })
+            });
+        if !is_public {
+            return unsupported(&format!("`{}`", name), location);
+        }
+        let mut value = self.contract_state(contract)?[variable].clone();
+        for key in args {
+            value = match value {
+                Value::Array(items) => match items.into_iter().nth(to_index(key, location)?) {
+                    Some(item) => item,
+                    None => return error(ExecutionErrorType::IndexOutOfBounds, location),
+                },
+                Value::Mapping { entries, default } => entries
+                    .into_iter()
+                    .find(|(entry, _)| *entry == key)
+                    .map_or(*default, |(_, value)| value),
+                _ => return unsupported("indexing a non-array", location),
+            };
+        }
+        Ok(value)
+    }
+
+    /// Calls `hook` on every pause of the execution.
+    pub fn debug(&mut self, hook: impl FnMut(&StepEvent) -> StepAction + 'a) {
+        self.hook = Some(Box::new(hook));
+    }
+
+    /// Pauses on the statements starting on the line `row`, counted from 0 like in `Location`.
+    pub fn add_breakpoint(&mut self, row: usize) {
+        if !self.breakpoints.contains(&row) {
+            self.breakpoints.push(row);
+        }
+    }
+
+    pub fn remove_breakpoint(&mut self, row: usize) {
+        self.breakpoints.retain(|breakpoint| *breakpoint != row);
+    }
+
+    /// Pauses on every statement, until the hook continues.
+    pub fn set_stepping(&mut self, stepping: bool) {
+        self.stepping = stepping;
+    }
+
+    /// Counts the executions of the lines and branches of `coverage` from now on.
+    pub fn set_coverage(&mut self, coverage: Coverage) {
+        self.coverage = Some(coverage);
+    }
+
+    pub fn coverage(&self) -> Option<&Coverage> {
+        self.coverage.as_ref()
+    }
+
+    /// Counters to increment, unless running the standard library or initializers.
+    fn counters(&mut self) -> Option<&mut Coverage> {
+        let frame = self.frames.last()?;
+        if frame.function.is_none() || frame.modules.first().map(String::as_str) == Some(STD) {
+            return None;
+        }
+        self.coverage.as_mut()
+    }
+
+    fn hit_branch(&mut self, location: Location, branch: usize) {
+        if let Some(coverage) = self.counters() {
+            coverage.hit_branch(location, branch);
+        }
+    }
+
+    /// Calls the debug hook if execution pauses on `statement`.
+    fn pause(&mut self, statement: &ast::Statement) {
+        if self.hook.is_none()
+            || matches!(
+                statement.node,
+                StatementType::CompoundStatement { .. } | StatementType::MemberStatement { .. }
+            )
+        {
+            return;
+        }
+        let frame = self.frames.last().unwrap();
+        let function = match &frame.function {
+            Some(function) => function.clone(),
+            None => return,
+        };
+        if !self.stepping && !self.breakpoints.contains(&statement.location.row()) {
+            return;
+        }
+        let mut locals: HashMap<&String, &Value> = HashMap::new();
+        for scope in &frame.scopes {
+            locals.extend(scope.iter());
+        }
+        let mut locals: Vec<(String, Value)> = locals
+            .into_iter()
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect();
+        locals.sort_by(|a, b| a.0.cmp(&b.0));
+        let event = StepEvent {
+            statement,
+            function: &function,
+            locals,
+            storage_diff: self.storage_diff(),
+        };
+        let action = (self.hook.as_mut().unwrap())(&event);
+        self.stepping = action == StepAction::Step;
+    }
+
+    /// State variables changed since the call started.
+    pub fn storage_diff(&self) -> Vec<StorageChange> {
+        let mut changes = vec![];
+        for (contract, state) in &self.state {
+            let initial = self.initial_state.get(contract);
+            for (name, new) in state {
+                let old = initial
+                    .and_then(|initial| initial.get(name))
+                    .cloned()
+                    .unwrap_or(Value::Unit);
+                if old != *new {
+                    changes.push(StorageChange {
+                        contract: contract.clone(),
+                        name: name.clone(),
+                        old,
+                        new: new.clone(),
+                    });
+                }
+            }
+        }
+        changes.sort_by(|a, b| (&a.contract, &a.name).cmp(&(&b.contract, &b.name)));
+        changes
+    }
+
+    /// Value of the state variable `name` of `contract`.
+    pub fn state_variable(&mut self, contract: &str, name: &str) -> ExecutionResult<Value> {
+        Ok(self
+            .contract_state(contract)?
+            .get(name)
+            .cloned()
+            .unwrap_or(Value::Unit))
+    }
+
+    /// Runs `function` called at `location`. Arguments of the wrong number, or out of
+    /// the range of their parameter, revert like calldata failing to decode.
+    fn call_function(
+        &mut self,
+        function: &FunctionItem<'a>,
+        args: Vec<Value>,
+        location: Location,
+    ) -> ExecutionResult<Value> {
+        let (parameters, body) = match &function.statement.node {
+            StatementType::FunctionStatement {
+                parameters,
+                statement: Some(statement),
+                ..
+            } => (parameters, statement),
+            _ => unreachable!(),
+        };
+        let probe = 0u8;
+        eprintln!("STACK {} {:p}", self.frames.len(), &probe);
+        self.check_depth(location)?;
+        let parameters = match &parameters.node {
+            ExpressionType::Parameters { parameters } => parameters.as_slice(),
+            _ => &[],
+        };
+        if args.len() != parameters.len() {
+            let message = format!(
+                "`{}` takes {} argument(s) but {} were given",
+                function.name,
+                parameters.len(),
+                args.len()
+            );
+            return error(ExecutionErrorType::Revert(Some(message)), location);
+        }
+        let mut frame = Frame {
+            function: Some(function.name.clone()),
+            contract: function.contract.clone(),
+            modules: function.modules.clone(),
+            scopes: vec![],
+            types: vec![],
+        };
+        frame.push_scope();
+        for (parameter, arg) in parameters.iter().zip(args) {
+            if let StatementType::InitializerStatement {
+                variable_type,
+                variable: Some(variable),
+                ..
+            } = &parameter.node
+            {
+                let name = variable.node.identifier_name().unwrap();
+                let in_range = match (&arg, Domain::of(Some(variable_type), true)) {
+                    (_, Domain::Untyped) => true,
+                    (Value::Int(value), domain) => domain.contains(value),
+                    _ => false,
+                };
+                if !in_range {
+                    let message = format!("argument `{}` is out of range", name);
+                    return error(ExecutionErrorType::Revert(Some(message)), location);
+                }
+                frame.declare(name, arg, Some(variable_type));
+            }
+        }
+        if let Some(contract) = &function.contract {
+            self.contract_state(contract)?;
+        }
+        self.frames.push(frame);
+        // Blocks of the caller don't uncheck the arithmetic of the function
+        let unchecked = std::mem::take(&mut self.unchecked);
+        let result = self.execute(body);
+        self.unchecked = unchecked;
+        self.frames.pop();
+        match result {
+            Ok(value) | Err(Interrupt::Return(value)) => Ok(value),
+            Err(Interrupt::Error(err)) => Err(err),
+        }
+    }
+
+    /// Fails if the calls being run are nested too deep, in number or in the stack
+    /// they take, instead of overflowing the stack.
+    fn check_depth(&mut self, location: Location) -> ExecutionResult<()> {
+        let position = stack_position();
+        if self.frames.is_empty() {
+            self.stack_base = position;
+        }
+        if self.frames.len() >= MAX_CALL_DEPTH
+            || self.stack_base.saturating_sub(position) > STACK_BUDGET
+        {
+            return error(ExecutionErrorType::CallDepthExceeded, location);
+        }
+        Ok(())
+    }
+
+    /// State of `contract`, initialized from the declarations of its state variables.
+    fn contract_state(&mut self, contract: &str) -> ExecutionResult<&mut HashMap<String, Value>> {
+        if !self.state.contains_key(contract) {
+            self.state.insert(contract.to_string(), HashMap::new());
+            let declarations = self
+                .state_variables
+                .get(contract)
+                .cloned()
+                .unwrap_or_default();
+            for declaration in declarations {
+                if let StatementType::InitializerStatement {
+                    variable_type,
+                    variable: Some(variable),
+                    default,
+                    ..
+                } = &declaration.node
+                {
+                    let value = match default {
+                        Some(default) => {
+                            let value = self.evaluate_in(Some(contract), vec![], default)?;
+                            self.fit(value, Some(variable_type), default.location)?
+                        }
+                        None => default_value(variable_type),
+                    };
+                    let name = variable.node.identifier_name().unwrap();
+                    self.state.get_mut(contract).unwrap().insert(name, value);
+                }
+            }
+        }
+        Ok(self.state.get_mut(contract).unwrap())
+    }
+
+    /// Value of `expression` outside any function, in `contract` or `modules`.
+    fn evaluate_in(
+        &mut self,
+        contract: Option<&str>,
+        modules: Vec<String>,
+        expression: &ast::Expression,
+    ) -> ExecutionResult<Value> {
+        self.check_depth(expression.location)?;
+        self.frames.push(Frame {
+            function: None,
+            contract: contract.map(String::from),
+            modules,
+            scopes: vec![],
+            types: vec![],
+        });
+        let value = self.evaluate(expression);
+        self.frames.pop();
+        match value {
+            Ok(value) | Err(Interrupt::Return(value)) => Ok(value),
+            Err(Interrupt::Error(err)) => Err(err),
+        }
+    }
+
+    fn frame(&mut self) -> &mut Frame {
+        self.frames.last_mut().unwrap()
+    }
+
+    fn execute(&mut self, statement: &ast::Statement) -> Flow<Value> {
+        self.pause(statement);
+        if let StatementType::InitializerStatement { .. }
+        | StatementType::ReturnStatement { .. }
+        | StatementType::Expression { .. } = statement.node
+        {
+            if let Some(coverage) = self.counters() {
+                coverage.hit_line(statement.location);
+            }
+        }
+        match &statement.node {
+            StatementType::CompoundStatement {
+                statements,
+                return_value,
+            } => {
+                self.frame().push_scope();
+                let result = self.execute_block(statements, return_value.as_deref());
+                self.frame().pop_scope();
+                result
+            }
+            StatementType::InitializerStatement {
+                variable_type,
+                variable: Some(variable),
+                default,
+                ..
+            } => {
+                let value = match default {
+                    Some(default) => {
+                        let value = self.evaluate(default)?;
+                        self.fit(value, Some(variable_type), default.location)?
+                    }
+                    None => default_value(variable_type),
+                };
+                let name = variable.node.identifier_name().unwrap();
+                self.frame().declare(name, value, Some(variable_type));
+                Ok(Value::Unit)
+            }
+            StatementType::UncheckedStatement { statement } => {
+                self.unchecked += 1;
+                let result = self.execute(statement);
+                self.unchecked -= 1;
+                result
+            }
+            StatementType::DoWhileStatement {
+                statement,
+                condition,
+            } => loop {
+                self.execute(statement)?;
+                if !self.condition(condition)? {
+                    return Ok(Value::Unit);
+                }
+            },
+            StatementType::ReturnStatement { ret } => {
+                let value = match ret {
+                    Some(ret) => self.evaluate(ret)?,
+                    None => Value::Unit,
+                };
+                Err(Interrupt::Return(value))
+            }
+            StatementType::Expression { expression } => {
+                self.evaluate(expression)?;
+                Ok(Value::Unit)
+            }
+            _ => Ok(unsupported("statement", statement.location)?),
+        }
+    }
+
+    fn execute_block(
+        &mut self,
+        statements: &[ast::Statement],
+        return_value: Option<&ast::Expression>,
+    ) -> Flow<Value> {
+        for statement in statements {
+            self.execute(statement)?;
+        }
+        match return_value {
+            Some(value) => self.evaluate(value),
+            None => Ok(Value::Unit),
+        }
+    }
+
+    fn evaluate(&mut self, expression: &ast::Expression) -> Flow<Value> {
+        let location = expression.location;
+        let value = match &expression.node {
+            ExpressionType::Number { value, .. } => Value::Int(BigInt::from(value.clone())),
+            ExpressionType::StringLiteral { value, .. } => Value::String(value.clone()),
+            ExpressionType::Identifier { value } => self.variable(value, location)?,
+            ExpressionType::PathExpression { .. } => {
+                let path = expression.node.module_path().unwrap().join("::");
+                self.variable(&path, location)?
+            }
+            ExpressionType::Tuple { items } => {
+                let mut values = vec![];
+                for item in items {
+                    values.push(match item {
+                        Some(item) => self.evaluate(item)?,
+                        None => Value::Unit,
+                    });
+                }
+                Value::Tuple(values)
+            }
+            ExpressionType::AssignExpression {
+                left,
+                operator,
+                right,
+            } => {
+                let mut value = self.evaluate(right)?;
+                if let ExpressionType::Tuple { items } = &left.node {
+                    let values = match &value {
+                        Value::Tuple(values) => values.clone(),
+                        _ => return Ok(unsupported("destructuring a single value", location)?),
+                    };
+                    for (item, value) in items.iter().zip(values) {
+                        if let Some(item) = item.as_ref().filter(|item| !item.node.is_placeholder())
+                        {
+                            let value =
+                                self.fit(value, self.operand_type(item).as_ref(), location)?;
+                            *self.place(item)? = value;
+                        }
+                    }
+                    value
+                } else {
+                    let typ = self.operand_type(left);
+                    if let Some(operator) = augmented_operator(operator) {
+                        let current = self.evaluate(left)?;
+                        value = binary(
+                            &operator,
+                            current,
+                            value,
+                            self.domain(typ.as_ref()),
+                            location,
+                        )?;
+                    }
+                    let value = self.fit(value, typ.as_ref(), location)?;
+                    *self.place(left)? = value.clone();
+                    value
+                }
+            }
+            ExpressionType::BinaryExpression {
+                left,
+                operator: Operator::And,
+                right,
+            } => Value::Bool(self.condition(left)? && self.condition(right)?),
+            ExpressionType::BinaryExpression {
+                left,
+                operator: Operator::Or,
+                right,
+            } => Value::Bool(self.condition(left)? || self.condition(right)?),
+            ExpressionType::BinaryExpression {
+                left,
+                operator,
+                right,
+            } => {
+                let domain = self.domain(self.operand_type(expression).as_ref());
+                let left = self.evaluate(left)?;
+                let right = self.evaluate(right)?;
+                binary(operator, left, right, domain, location)?
+            }
+            ExpressionType::UnaryExpression {
+                operator,
+                expression: operand,
+            } => self.unary(operator, operand, location)?,
+            ExpressionType::TernaryExpression {
+                condition,
+                expr1,
+                expr2,
+            } => {
+                if self.condition(condition)? {
+                    self.hit_branch(location, 0);
+                    self.evaluate(expr1)?
+                } else {
+                    self.hit_branch(location, 1);
+                    self.evaluate(expr2)?
+                }
+            }
+            ExpressionType::IfExpression {
+                condition,
+                if_statement,
+                else_statement,
+            } => {
+                if self.condition(condition)? {
+                    self.hit_branch(location, 0);
+                    self.execute(if_statement)?
+                } else {
+                    self.hit_branch(location, 1);
+                    match else_statement {
+                        Some(else_statement) => self.execute(else_statement)?,
+                        None => Value::Unit,
+                    }
+                }
+            }
+            ExpressionType::MatchExpression { value, arms } => {
+                let value = self.evaluate(value)?;
+                for (i, arm) in arms.iter().enumerate() {
+                    if arm.is_wildcard() || self.evaluate(&arm.pattern)? == value {
+                        self.hit_branch(location, i);
+                        return self.execute(&arm.statement);
+                    }
+                }
+                Value::Unit
+            }
+            ExpressionType::ForEachExpression {
+                iterator,
+                vector,
+                statement,
+                else_statement,
+            } => {
+                let items = match self.evaluate(vector)? {
+                    Value::Array(items) | Value::Tuple(items) => items,
+                    _ => return Ok(unsupported("iteration over a non-array", vector.location)?),
+                };
+                self.hit_branch(location, items.is_empty() as usize);
+                if items.is_empty() {
+                    if let Some(else_statement) = else_statement {
+                        self.execute(else_statement)?;
+                    }
+                }
+                let name = iterator.node.identifier_name().unwrap();
+                let typ = match self.operand_type(vector) {
+                    Some(ast::Type::Array(element)) | Some(ast::Type::FixedArray(element, _)) => {
+                        Some(*element)
+                    }
+                    _ => None,
+                };
+                for item in items {
+                    self.frame().push_scope();
+                    self.frame().declare(name.clone(), item, typ.as_ref());
+                    let result = self.execute(statement);
+                    self.frame().pop_scope();
+                    result?;
+                }
+                Value::Unit
+            }
+            ExpressionType::IndexExpression {
+                expression: array,
+                index,
+            } => {
+                let array = self.evaluate(array)?;
+                let key = self.evaluate(index)?;
+                match array {
+                    Value::Array(items) | Value::Tuple(items) => {
+                        match items.get(to_index(key, index.location)?) {
+                            Some(item) => item.clone(),
+                            None => {
+                                return Ok(error(ExecutionErrorType::IndexOutOfBounds, location)?)
+                            }
+                        }
+                    }
+                    Value::Mapping { entries, default } => entries
+                        .into_iter()
+                        .find(|(entry, _)| *entry == key)
+                        .map_or(*default, |(_, value)| value),
+                    _ => return Ok(unsupported("indexing a non-array", location)?),
+                }
+            }
+            ExpressionType::MemberExpression {
+                expression: base,
+                member,
+            } => match (
+                self.evaluate(base)?,
+                member.node.identifier_name().as_deref(),
+            ) {
+                (Value::Array(items), Some("length")) => Value::Int(BigInt::from(items.len())),
+                _ => return Ok(unsupported("member access", location)?),
+            },
+            ExpressionType::FunctionCallExpression {
+                function_name,
+                arguments,
+            } => self.call_expression(function_name, arguments)?,
+            _ => return Ok(unsupported("expression", location)?),
+        };
+        Ok(value)
+    }
+
+    fn condition(&mut self, expression: &ast::Expression) -> Flow<bool> {
+        match self.evaluate(expression)? {
+            Value::Bool(value) => Ok(value),
+            _ => Ok(unsupported("non-boolean condition", expression.location)?),
+        }
+    }
+
+    fn unary(
+        &mut self,
+        operator: &Operator,
+        operand: &ast::Expression,
+        location: Location,
+    ) -> Flow<Value> {
+        let domain = self.domain(self.operand_type(operand).as_ref());
+        let value = self.evaluate(operand)?;
+        let step = match operator {
+            Operator::PrefixPlusPlus | Operator::PostfixPlusPlus => 1,
+            Operator::PrefixMinusMinus | Operator::PostfixMinusMinus => -1,
+            Operator::Plus => return Ok(value),
+            Operator::Minus => {
+                return Ok(binary(
+                    &Operator::Sub,
+                    Value::Int(BigInt::zero()),
+                    value,
+                    domain,
+                    location,
+                )?)
+            }
+            Operator::Not => match value {
+                Value::Bool(value) => return Ok(Value::Bool(!value)),
+                _ => return Ok(unsupported("operator", location)?),
+            },
+            _ => return Ok(unsupported("operator", location)?),
+        };
+        let updated = binary(
+            &Operator::Add,
+            value.clone(),
+            Value::Int(BigInt::from(step)),
+            domain,
+            location,
+        )?;
+        *self.place(operand)? = updated.clone();
+        match operator {
+            Operator::PostfixPlusPlus | Operator::PostfixMinusMinus => Ok(value),
+            _ => Ok(updated),
+        }
+    }
+
+    fn call_expression(
+        &mut self,
+        function_name: &ast::Expression,
+        arguments: &ast::Expression,
+    ) -> Flow<Value> {
+        let location = function_name.location;
+        let builtin = function_name
+            .node
+            .path_name()
+            .and_then(|path| BuiltinFunction::from_name(&path));
+        if let Some(
+            builtin @ (BuiltinFunction::AbiEncode
+            | BuiltinFunction::AbiEncodePacked
+            | BuiltinFunction::AbiEncodeWithSelector
+            | BuiltinFunction::AbiDecode),
+        ) = builtin
+        {
+            return self.call_abi(builtin, arguments, location);
+        }
+        let mut args = vec![];
+        if let ExpressionType::Arguments { arguments } = &arguments.node {
+            for argument in arguments {
+                args.push(self.evaluate(argument)?);
+            }
+        }
+        if let ExpressionType::MemberExpression {
+            expression: base,
+            member,
+        } = &function_name.node
+        {
+            let member = member.node.identifier_name();
+            // Functions of libraries, e.g. `SafeMath.add(a, b)`
+            if let Some(library) = base.node.identifier_name() {
+                if self.libraries.contains(&library) {
+                    let name = format!("{}.{}", library, member.as_deref().unwrap_or_default());
+                    return match self.functions.get(&name).cloned() {
+                        Some(function) => Ok(self.call_function(&function, args, location)?),
+                        None => Ok(unsupported(&format!("`{}`", name), location)?),
+                    };
+                }
+            }
+            // Functions attached by `using`, called with the value first, e.g. `a.add(b)`
+            if !matches!(member.as_deref(), Some("push" | "pop")) {
+                let value = self.evaluate(base)?;
+                let function = member
+                    .as_deref()
+                    .and_then(|member| self.attached_function(&value, member));
+                return match function {
+                    Some(function) => {
+                        args.insert(0, value);
+                        Ok(self.call_function(&function, args, location)?)
+                    }
+                    None => Ok(unsupported("member call", location)?),
+                };
+            }
+            // Members of arrays, e.g. `values.push(1)`
+            let array = match self.place(base) {
+                Ok(Value::Array(items)) => items,
+                _ => return Ok(unsupported("member call", location)?),
+            };
+            return match (member.as_deref(), args.pop()) {
+                (Some("push"), Some(value)) => {
+                    array.push(value);
+                    Ok(Value::Unit)
+                }
+                (Some("pop"), None) => match array.pop() {
+                    Some(_) => Ok(Value::Unit),
+                    None => Ok(error(ExecutionErrorType::IndexOutOfBounds, location)?),
+                },
+                _ => Ok(unsupported("member call", location)?),
+            };
+        }
+        let path = match function_name.node.module_path() {
+            Some(path) => path.join("::"),
+            None => function_name.node.path_name().unwrap_or_default(),
+        };
+        if let Some(function) = self.resolve_function(&path) {
+            return Ok(self.call_function(&function, args, location)?);
+        }
+        match BuiltinFunction::from_name(&path) {
+            Some(BuiltinFunction::Assert | BuiltinFunction::Require) => {
+                let mut args = args.into_iter();
+                if args.next() == Some(Value::Bool(true)) {
+                    return Ok(Value::Unit);
+                }
+                let message = match args.next() {
+                    Some(Value::String(message)) => Some(message),
+                    _ => None,
+                };
+                Ok(error(ExecutionErrorType::Revert(message), location)?)
+            }
+            Some(BuiltinFunction::Revert) => {
+                let mut args = args.into_iter();
+                let message = match args.next() {
+                    Some(Value::String(message)) => Some(message),
+                    _ => None,
+                };
+                Ok(error(ExecutionErrorType::Revert(message), location)?)
+            }
+            Some(BuiltinFunction::UintToField) => match args.into_iter().next() {
+                Some(Value::Int(value)) if value < BigInt::from(field_modulus()) => {
+                    Ok(Value::Int(value))
+                }
+                _ => Ok(error(
+                    ExecutionErrorType::Revert(Some(String::from(
+                        "value is not below the prime of the field",
+                    ))),
+                    location,
+                )?),
+            },
+            Some(BuiltinFunction::FieldToUint) => Ok(args.into_iter().next().unwrap()),
+            Some(
+                builtin @ (BuiltinFunction::Keccak256
+                | BuiltinFunction::Sha256
+                | BuiltinFunction::Blake2s),
+            ) => {
+                let data = match args.into_iter().next() {
+                    Some(Value::String(data)) => data.into_bytes(),
+                    Some(Value::Bytes(data)) => data,
+                    Some(Value::Int(value)) => to_word(&value, location)?.to_vec(),
+                    _ => return Ok(unsupported("hashing a non-bytes value", location)?),
+                };
+                let hash = digest(builtin, &data);
+                Ok(Value::Int(BigInt::from_bytes_be(Sign::Plus, &hash)))
+            }
+            // A malformed signature recovers the zero address, as in Solidity
+            #[cfg(feature = "secp256k1")]
+            Some(BuiltinFunction::Ecrecover) => {
+                let mut words = vec![];
+                for arg in args {
+                    match arg {
+                        Value::Int(value) => words.push(to_word(&value, location)?),
+                        _ => return Ok(unsupported("`ecrecover` of non-integers", location)?),
+                    }
+                }
+                let v = BigInt::from_bytes_be(Sign::Plus, &words[1]).to_u8();
+                let address =
+                    v.and_then(|v| precompile::ecrecover(&words[0], v, &words[2], &words[3]));
+                let address = address.map_or_else(BigInt::zero, |address| {
+                    BigInt::from_bytes_be(Sign::Plus, &address)
+                });
+                Ok(Value::Int(address))
+            }
+            Some(builtin) => Ok(unsupported(&format!("`{}`", builtin.name()), location)?),
+            None => Ok(unsupported(&format!("`{}`", path), location)?),
+        }
+    }
+
+    /// Calls a builtin of the `abi` namespace. The values are encoded as the types of
+    /// their declarations tell, or as `uint256` and `string` for literals.
+    fn call_abi(
+        &mut self,
+        builtin: BuiltinFunction,
+        arguments: &ast::Expression,
+        location: Location,
+    ) -> Flow<Value> {
+        let arguments = match &arguments.node {
+            ExpressionType::Arguments { arguments } => arguments.as_slice(),
+            _ => &[],
+        };
+        let revert = |error: AbiError| ExecutionError {
+            error: ExecutionErrorType::Revert(Some(error.error)),
+            location,
+        };
+        if builtin == BuiltinFunction::AbiDecode {
+            let data = match self.evaluate(&arguments[0])? {
+                Value::Bytes(data) => data,
+                Value::String(data) => data.into_bytes(),
+                _ => return Ok(unsupported("decoding a non-bytes value", location)?),
+            };
+            let types = match &arguments[1].node {
+                ExpressionType::TypeList { types } => types
+                    .iter()
+                    .map(|typ| resolve_type(typ, &|_| None))
+                    .collect::<Result<Vec<_>, _>>()
+                    .ok(),
+                _ => None,
+            };
+            let types = match types {
+                Some(types) => types,
+                None => return Ok(unsupported("decoding these types", location)?),
+            };
+            let mut values: Vec<Value> = abi::decode(&types, &data)
+                .map_err(revert)?
+                .into_iter()
+                .map(from_abi)
+                .collect();
+            return Ok(match values.len() {
+                1 => values.pop().unwrap(),
+                _ => Value::Tuple(values),
+            });
+        }
+        let mut values = vec![];
+        for argument in arguments {
+            let value = self.evaluate(argument)?;
+            let typ = self.operand_type(argument);
+            values.push(to_abi(value, typ.as_ref(), argument.location)?);
+        }
+        let encoded = match builtin {
+            BuiltinFunction::AbiEncode => abi::encode(&values),
+            BuiltinFunction::AbiEncodePacked => abi::encode_packed(&values),
+            // The selector is held in the first 4 bytes of a `bytes32`
+            _ => {
+                let selector = match values.remove(0) {
+                    AbiValue::Bytes32(word) => [word[0], word[1], word[2], word[3]],
+                    _ => return Ok(unsupported("selector of a non-bytes32 value", location)?),
+                };
+                abi::encode_with_selector(&selector, &values)
+            }
+        };
+        Ok(Value::Bytes(encoded.map_err(revert)?))
+    }
+
+    /// Function called as `path` from the current frame, searched in its contract
+    /// then in its modules from the innermost.
+    fn resolve_function(&self, path: &str) -> Option<FunctionItem<'a>> {
+        let frame = self.frames.last()?;
+        if let Some(contract) = &frame.contract {
+            if let Some(function) = self.functions.get(&format!("{}.{}", contract, path)) {
+                return Some(function.clone());
+            }
+        }
+        (0..=frame.modules.len()).rev().find_map(|depth| {
+            let name = qualified_name(None, &frame.modules[..depth], path);
+            self.functions.get(&name).cloned()
+        })
+    }
+
+    /// Function `name` of a library attached by `using` in the current contract to the
+    /// type of `value`. Values don't tell integer types apart, the first library
+    /// attached to an integer type and declaring `name` is taken.
+    fn attached_function(&self, value: &Value, name: &str) -> Option<FunctionItem<'a>> {
+        let contract = self.frames.last()?.contract.as_ref()?;
+        self.using
+            .get(contract)?
+            .iter()
+            .filter(|(_, target)| {
+                std::mem::discriminant(value) == std::mem::discriminant(&default_value(target))
+            })
+            .find_map(|(library, _)| self.functions.get(&format!("{}.{}", library, name)))
+            .cloned()
+    }
+
+    /// Domain of the arithmetic on operands of type `typ`.
+    fn domain(&self, typ: Option<&ast::Type>) -> Domain {
+        Domain::of(typ, self.unchecked == 0)
+    }
+
+    /// `value` assigned to a variable of type `typ`, reverting if it overflows it.
+    fn fit(
+        &self,
+        value: Value,
+        typ: Option<&ast::Type>,
+        location: Location,
+    ) -> ExecutionResult<Value> {
+        match (value, self.domain(typ)) {
+            (Value::Int(value), domain @ Domain::Integer { .. }) => {
+                Ok(Value::Int(domain.fit(value, location)?))
+            }
+            (value, _) => Ok(value),
+        }
+    }
+
+    /// Type of the values of `expression` as far as the declarations tell, the widest of
+    /// its operands for an arithmetic expression, `None` for literals and constants.
+    fn operand_type(&self, expression: &ast::Expression) -> Option<ast::Type> {
+        match &expression.node {
+            ExpressionType::Identifier { value } => self.declared_type(value),
+            ExpressionType::IndexExpression {
+                expression: base, ..
+            } => match self.operand_type(base)? {
+                ast::Type::Array(element) | ast::Type::FixedArray(element, _) => Some(*element),
+                ast::Type::Mapping(_, value) => Some(*value),
+                _ => None,
+            },
+            ExpressionType::BinaryExpression { left, right, .. } => {
+                match (self.operand_type(left), self.operand_type(right)) {
+                    (Some(left), Some(right)) if width(&right) > width(&left) => Some(right),
+                    (left, right) => left.or(right),
+                }
+            }
+            ExpressionType::UnaryExpression {
+                expression: operand,
+                ..
+            } => self.operand_type(operand),
+            ExpressionType::AssignExpression { left, .. } => self.operand_type(left),
+            ExpressionType::TernaryExpression { expr1, expr2, .. } => self
+                .operand_type(expr1)
+                .or_else(|| self.operand_type(expr2)),
+            _ => None,
+        }
+    }
+
+    /// Declared type of the variable `name` seen from the current frame.
+    fn declared_type(&self, name: &str) -> Option<ast::Type> {
+        let frame = self.frames.last()?;
+        if let Some(depth) = frame.scopes.iter().rposition(|s| s.contains_key(name)) {
+            return frame.types[depth].get(name).cloned();
+        }
+        let declarations = self.state_variables.get(frame.contract.as_ref()?)?;
+        declarations
+            .iter()
+            .find_map(|declaration| match &declaration.node {
+                StatementType::InitializerStatement {
+                    variable_type,
+                    variable: Some(variable),
+                    ..
+                } if variable.node.identifier_name().as_deref() == Some(name) => {
+                    Some(variable_type.clone())
+                }
+                _ => None,
+            })
+    }
+
+    fn variable(&mut self, name: &str, location: Location) -> ExecutionResult<Value> {
+        let frame = self.frames.last().unwrap();
+        if let Some(value) = frame.scopes.iter().rev().find_map(|scope| scope.get(name)) {
+            return Ok(value.clone());
+        }
+        if let Some(contract) = &frame.contract {
+            if let Some(value) = self.state.get(contract).and_then(|state| state.get(name)) {
+                return Ok(value.clone());
+            }
+        }
+        let modules = frame.modules.clone();
+        for depth in (0..=modules.len()).rev() {
+            let qualified = qualified_name(None, &modules[..depth], name);
+            if let Some(constant) = self.constants.get(&qualified).copied() {
+                // Evaluated in the module declaring the constant
+                let mut scope: Vec<String> = qualified.split("::").map(String::from).collect();
+                scope.pop();
+                return self.evaluate_in(None, scope, constant);
+            }
+        }
+        unsupported(&format!("`{}`", name), location)
+    }
+
+    /// Variable or array element assigned by `target`.
+    fn place(&mut self, target: &ast::Expression) -> ExecutionResult<&mut Value> {
+        match &target.node {
+            ExpressionType::Identifier { value: name } => {
+                let Interpreter { frames, state, .. } = self;
+                let frame = frames.last_mut().unwrap();
+                let state = match &frame.contract {
+                    Some(contract) => state.get_mut(contract),
+                    None => None,
+                };
+                let scope = frame.scopes.iter_mut().rev().find(|s| s.contains_key(name));
+                let value = match scope {
+                    Some(scope) => scope.get_mut(name),
+                    None => state.and_then(|state| state.get_mut(name)),
+                };
+                match value {
+                    Some(value) => Ok(value),
+                    None => unsupported(&format!("`{}`", name), target.location),
+                }
+            }
+            ExpressionType::IndexExpression { expression, index } => {
+                let key = match self.evaluate(index) {
+                    Ok(key) => key,
+                    Err(Interrupt::Error(err)) => return Err(err),
+                    Err(Interrupt::Return(_)) => return unsupported("return", target.location),
+                };
+                match self.place(expression)? {
+                    Value::Array(items) => match items.get_mut(to_index(key, index.location)?) {
+                        Some(item) => Ok(item),
+                        None => error(ExecutionErrorType::IndexOutOfBounds, target.location),
+                    },
+                    Value::Mapping { entries, default } => {
+                        let position = match entries.iter().position(|(entry, _)| *entry == key) {
+                            Some(position) => position,
+                            None => {
+                                entries.push((key, *default.clone()));
+                                entries.len() - 1
+                            }
+                        };
+                        Ok(&mut entries[position].1)
+                    }
+                    _ => unsupported("indexing a non-array", target.location),
+                }
+            }
+            _ => unsupported("assignment target", target.location),
+        }
+    }
+}
+
+/// Position in an array given by the value of an index.
+fn to_index(index: Value, location: Location) -> ExecutionResult<usize> {
+    match index {
+        Value::Int(index) => match index.to_usize() {
+            Some(index) => Ok(index),
+            None => error(ExecutionErrorType::IndexOutOfBounds, location),
+        },
+        _ => unsupported("non-integer index", location),
+    }
+}
+
+/// Operator applied by an augmented assignment, e.g. `Add` for `+=`.
+fn augmented_operator(operator: &Operator) -> Option<Operator> {
+    match operator {
+        Operator::AddAssign => Some(Operator::Add),
+        Operator::SubAssign => Some(Operator::Sub),
+        Operator::MulAssign => Some(Operator::Mul),
+        Operator::DivAssign => Some(Operator::Div),
+        Operator::ModAssign => Some(Operator::Mod),
+        Operator::BitAndAssign => Some(Operator::BitAnd),
+        Operator::BitOrAssign => Some(Operator::BitOr),
+        Operator::BitXorAssign => Some(Operator::BitXor),
+        Operator::LShiftAssign => Some(Operator::LShift),
+        Operator::RShiftAssign => Some(Operator::RShift),
+        _ => None,
+    }
+}
+
+/// Width of the integers of type `typ`, 0 if it isn't an integer type.
+fn width(typ: &ast::Type) -> u16 {
+    match Domain::of(Some(typ), true) {
+        Domain::Integer { bits, .. } => bits,
+        Domain::Field => 254,
+        Domain::Untyped => 0,
+    }
+}
+
+/// Value of `left ** exponent` in `domain`, without computing powers wider than `MAX_BITS`.
+fn power(
+    left: BigInt,
+    exponent: &BigInt,
+    domain: Domain,
+    location: Location,
+) -> ExecutionResult<BigInt> {
+    if exponent.is_negative() {
+        return unsupported("exponent", location);
+    }
+    if let Some(modulus) = domain.modulus() {
+        let base = reduce(left, &modulus).to_biguint().unwrap();
+        let value = base.modpow(
+            &exponent.to_biguint().unwrap(),
+            &modulus.to_biguint().unwrap(),
+        );
+        return domain.fit(BigInt::from(value), location);
+    }
+    if left.abs() <= BigInt::one() {
+        // 0, 1 and -1 stay as small whatever the exponent, only its parity matters
+        let exponent = match exponent.is_zero() {
+            true => 0,
+            false => 2 - (exponent % 2u8).to_usize().unwrap(),
+        };
+        return Ok(num_traits::pow(left, exponent));
+    }
+    match exponent.to_usize() {
+        Some(exponent) if exponent <= MAX_BITS => {
+            domain.fit(num_traits::pow(left, exponent), location)
+        }
+        _ => overflow(location),
+    }
+}
+
+fn binary(
+    operator: &Operator,
+    left: Value,
+    right: Value,
+    domain: Domain,
+    location: Location,
+) -> ExecutionResult<Value> {
+    match operator {
+        Operator::Eq => return Ok(Value::Bool(left == right)),
+        Operator::NotEq => return Ok(Value::Bool(left != right)),
+        _ => {}
+    }
+    let (left, right) = match (left, right) {
+        (Value::Int(left), Value::Int(right)) => (left, right),
+        _ => return unsupported("operator", location),
+    };
+    // Shifting by more than `MAX_BITS` gives the same value as by `MAX_BITS + 1`
+    let shift = |amount: &BigInt| match amount.sign() {
+        Sign::Minus => unsupported("shift amount", location),
+        _ => Ok(amount.to_usize().unwrap_or(usize::MAX).min(MAX_BITS + 1)),
+    };
+    // Shifted bits are dropped rather than checked
+    let shifted = match domain {
+        Domain::Integer { signed, bits, .. } => Domain::Integer {
+            signed,
+            bits,
+            checked: false,
+        },
+        domain => domain,
+    };
+    let value = match operator {
+        Operator::Add => Value::Int(domain.fit(left + right, location)?),
+        Operator::Sub => Value::Int(domain.fit(left - right, location)?),
+        Operator::Mul => Value::Int(domain.fit(left * right, location)?),
+        Operator::Div | Operator::Mod if right.is_zero() => {
+            return error(ExecutionErrorType::DivisionByZero, location)
+        }
+        Operator::Div => Value::Int(domain.fit(left / right, location)?),
+        Operator::Mod => Value::Int(left % right),
+        Operator::Pow => Value::Int(power(left, &right, domain, location)?),
+        Operator::LShift => Value::Int(shifted.fit(left << shift(&right)?, location)?),
+        Operator::RShift => Value::Int(left >> shift(&right)?),
+        Operator::BitAnd => Value::Int(left & right),
+        Operator::BitOr => Value::Int(left | right),
+        Operator::BitXor => Value::Int(left ^ right),
+        Operator::Lt => Value::Bool(left < right),
+        Operator::Le => Value::Bool(left <= right),
+        Operator::Gt => Value::Bool(left > right),
+        Operator::Ge => Value::Bool(left >= right),
+        _ => return unsupported("operator", location),
+    };
+    Ok(value)
+}
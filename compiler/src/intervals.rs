@@ -0,0 +1,482 @@
+//! Ranges of the values of integer expressions.
+//!
+//! An abstract interpretation where each integer expression takes its values in
+//! an interval: the range of its type for variables, a single value for literals
+//! and constants, and for operations the interval of their results. Arithmetic is
+//! checked as in Solidity, a result out of range reverts, so results are clamped
+//! to the range of 256-bit integers. The analysis doesn't follow the control flow:
+//! a variable is known by the range of its type, whatever was assigned to it.
+//!
+//! Intervals prove bounds checks of fixed arrays needless, e.g. of `values[i]`
+//! with `uint8 i` and `uint[256] values`, and find comparisons whose result is
+//! known, e.g. `x >= 0` with `uint x`. The IR has no overflow checks yet.
+
+use crate::constant::evaluate_expression;
+use crate::symbol::{resolve_type, Contract, Operation, OperationType, SymbolType};
+use crate::taint::descendants;
+use num_bigint::{BigInt, BigUint};
+use num_traits::{One, Signed, ToPrimitive, Zero};
+use std::collections::HashMap;
+use std::fmt;
+use zoker_parser::ast::{
+    Expression, ExpressionType, Operator, Program, Statement, StatementType, Type,
+};
+use zoker_parser::complexity::Node;
+use zoker_parser::location::Location;
+
+/// Shifts by more bits than this aren't evaluated.
+const MAX_SHIFT: usize = 256;
+
+/// Values from `min` to `max`, both included.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Interval {
+    pub min: BigInt,
+    pub max: BigInt,
+}
+
+impl fmt::Display for Interval {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "[{}, {}]", self.min, self.max)
+    }
+}
+
+impl Interval {
+    pub fn new(min: BigInt, max: BigInt) -> Self {
+        Interval { min, max }
+    }
+
+    pub fn constant(value: BigInt) -> Self {
+        Interval::new(value.clone(), value)
+    }
+
+    /// Range of unsigned integers of `bits` bits.
+    pub fn unsigned(bits: u16) -> Self {
+        Interval::new(BigInt::zero(), (BigInt::one() << usize::from(bits)) - 1)
+    }
+
+    /// Range of signed integers of `bits` bits.
+    pub fn signed(bits: u16) -> Self {
+        let half = BigInt::one() << usize::from(bits - 1);
+        Interval::new(-half.clone(), half - 1)
+    }
+
+    pub fn is_constant(&self) -> bool {
+        self.min == self.max
+    }
+
+    pub fn contains(&self, value: &BigInt) -> bool {
+        self.min <= *value && *value <= self.max
+    }
+
+    pub fn is_nonnegative(&self) -> bool {
+        !self.min.is_negative()
+    }
+
+    /// Smallest interval containing both.
+    pub fn hull(&self, other: &Interval) -> Interval {
+        Interval::new(
+            self.min.clone().min(other.min.clone()),
+            self.max.clone().max(other.max.clone()),
+        )
+    }
+
+    pub fn intersect(&self, other: &Interval) -> Option<Interval> {
+        let min = self.min.clone().max(other.min.clone());
+        let max = self.max.clone().min(other.max.clone());
+        if min <= max {
+            Some(Interval::new(min, max))
+        } else {
+            None
+        }
+    }
+
+    /// Interval of the values `operation` gives over the bounds of both.
+    fn corners(&self, other: &Interval, operation: impl Fn(&BigInt, &BigInt) -> BigInt) -> Self {
+        let values = [
+            operation(&self.min, &other.min),
+            operation(&self.min, &other.max),
+            operation(&self.max, &other.min),
+            operation(&self.max, &other.max),
+        ];
+        Interval::new(
+            values.iter().min().unwrap().clone(),
+            values.iter().max().unwrap().clone(),
+        )
+    }
+
+    pub fn add(&self, other: &Interval) -> Interval {
+        Interval::new(&self.min + &other.min, &self.max + &other.max)
+    }
+
+    pub fn sub(&self, other: &Interval) -> Interval {
+        Interval::new(&self.min - &other.max, &self.max - &other.min)
+    }
+
+    pub fn mul(&self, other: &Interval) -> Interval {
+        self.corners(other, |a, b| a * b)
+    }
+
+    /// Quotients rounded toward zero, `None` if the divisor may be zero.
+    pub fn div(&self, other: &Interval) -> Option<Interval> {
+        if other.contains(&BigInt::zero()) {
+            return None;
+        }
+        Some(self.corners(other, |a, b| a / b))
+    }
+
+    /// Remainders of nonnegative integers, `None` if the divisor may be zero.
+    pub fn rem(&self, other: &Interval) -> Option<Interval> {
+        if !self.is_nonnegative() || !other.is_nonnegative() || other.min.is_zero() {
+            return None;
+        }
+        if self.max < other.min {
+            return Some(self.clone());
+        }
+        let max = self.max.clone().min(&other.max - 1);
+        Some(Interval::new(BigInt::zero(), max))
+    }
+
+    pub fn shl(&self, other: &Interval) -> Option<Interval> {
+        let (min, max) = shifts(self, other)?;
+        Some(Interval::new(&self.min << min, &self.max << max))
+    }
+
+    pub fn shr(&self, other: &Interval) -> Option<Interval> {
+        let (min, max) = shifts(self, other)?;
+        Some(Interval::new(&self.min >> max, &self.max >> min))
+    }
+
+    /// Values of nonnegative integers with bits of both, at most the smallest of them.
+    pub fn bit_and(&self, other: &Interval) -> Option<Interval> {
+        if !self.is_nonnegative() || !other.is_nonnegative() {
+            return None;
+        }
+        Some(Interval::new(
+            BigInt::zero(),
+            self.max.clone().min(other.max.clone()),
+        ))
+    }
+
+    pub fn neg(&self) -> Interval {
+        Interval::new(-&self.max, -&self.min)
+    }
+}
+
+/// Bounds of the shift by `amount` of the nonnegative `value`.
+fn shifts(value: &Interval, amount: &Interval) -> Option<(usize, usize)> {
+    if !value.is_nonnegative() || !amount.is_nonnegative() {
+        return None;
+    }
+    let max = amount.max.to_usize().filter(|max| *max <= MAX_SHIFT)?;
+    Some((amount.min.to_usize()?, max))
+}
+
+/// Range of the values of `typ`, `None` if it isn't an integer type.
+pub fn type_interval(typ: &SymbolType) -> Option<Interval> {
+    match typ {
+        SymbolType::Uint256 => Some(Interval::unsigned(256)),
+        SymbolType::Int256 => Some(Interval::signed(256)),
+        SymbolType::Uint(bits) => Some(Interval::unsigned(*bits)),
+        SymbolType::Int(bits) => Some(Interval::signed(*bits)),
+        _ => None,
+    }
+}
+
+/// Values a checked operation can give without reverting, `None` if it always reverts.
+fn checked(interval: Interval) -> Option<Interval> {
+    let any = Interval::signed(256).hull(&Interval::unsigned(256));
+    interval.intersect(&any)
+}
+
+fn unsigned_checked(interval: Interval) -> Option<Interval> {
+    interval.intersect(&Interval::unsigned(256))
+}
+
+/// Interval of the values of `operation`, `None` if they aren't integers or are unknown.
+pub fn operation_interval(operation: &Operation) -> Option<Interval> {
+    let binary = |left: &Operation, right: &Operation| {
+        Some((operation_interval(left)?, operation_interval(right)?))
+    };
+    match &operation.operation {
+        OperationType::Constant { value } => Some(Interval::constant(BigInt::from(value.clone()))),
+        OperationType::Symbol { symbol } => type_interval(&symbol.symbol_type),
+        OperationType::Add { left, right } => {
+            let (left, right) = binary(left, right)?;
+            unsigned_checked(left.add(&right))
+        }
+        OperationType::Sub { left, right } => {
+            let (left, right) = binary(left, right)?;
+            unsigned_checked(left.sub(&right))
+        }
+        OperationType::Mul { left, right } => {
+            let (left, right) = binary(left, right)?;
+            unsigned_checked(left.mul(&right))
+        }
+        OperationType::Div { left, right } => {
+            let (left, right) = binary(left, right)?;
+            left.div(&right)
+        }
+        OperationType::Shl { left, right } => {
+            let (left, right) = binary(left, right)?;
+            unsigned_checked(left.shl(&right)?)
+        }
+        OperationType::Shr { left, right } => {
+            let (left, right) = binary(left, right)?;
+            left.shr(&right)
+        }
+        _ => None,
+    }
+}
+
+/// Whether `index` is always in the range of `array`, a fixed array.
+fn is_in_range(array: &Operation, index: &Operation) -> bool {
+    let length = match &array.operation {
+        OperationType::Symbol { symbol } => match symbol.symbol_type {
+            SymbolType::FixedArray(_, length) => length,
+            _ => return false,
+        },
+        _ => return false,
+    };
+    match operation_interval(index) {
+        Some(index) => index.is_nonnegative() && index.max < BigInt::from(length),
+        None => false,
+    }
+}
+
+/// Removes the bounds checks of fixed arrays indexed within their length, returning how many.
+pub fn eliminate_safe_bounds_checks(contract: &mut Contract) -> usize {
+    let mut removed = 0;
+    for function in &mut contract.functions {
+        function.operations = eliminate(std::mem::take(&mut function.operations), &mut removed);
+    }
+    removed
+}
+
+fn eliminate(operations: Vec<Operation>, removed: &mut usize) -> Vec<Operation> {
+    let mut result = vec![];
+    for mut operation in operations {
+        match &mut operation.operation {
+            OperationType::BoundsCheck { array, index } if is_in_range(array, index) => {
+                *removed += 1;
+                continue;
+            }
+            OperationType::If { stmts, .. }
+            | OperationType::Else { stmts, .. }
+            | OperationType::For { stmts, .. } => {
+                *stmts = eliminate(std::mem::take(stmts), removed);
+            }
+            _ => {}
+        }
+        result.push(operation);
+    }
+    result
+}
+
+/// Comparison whose result is the same for all values of its operands.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComparisonWarning {
+    pub location: Location,
+    pub always: bool,
+}
+
+impl fmt::Display for ComparisonWarning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "comparison is always {}", self.always)
+    }
+}
+
+/// Intervals of the variables in scope by name, `None` for those which aren't integers.
+type Names = HashMap<String, Option<Interval>>;
+
+fn declare(names: &mut Names, name: String, interval: Option<Interval>) {
+    // Declarations of the same name in different scopes share the hull of their ranges
+    let interval = match (names.get(&name), interval) {
+        (Some(Some(declared)), Some(interval)) => Some(declared.hull(&interval)),
+        (Some(None), _) | (_, None) => None,
+        (None, interval) => interval,
+    };
+    names.insert(name, interval);
+}
+
+/// Declares the variable of `statement`, if any, with the range of its type or its constant value.
+fn declare_statement(names: &mut Names, statement: &Statement) {
+    if let StatementType::InitializerStatement {
+        variable_type,
+        is_constant,
+        variable: Some(variable),
+        default,
+        ..
+    } = &statement.node
+    {
+        let name = match variable.node.identifier_name() {
+            Some(name) => name,
+            None => return,
+        };
+        let value = default
+            .as_ref()
+            .filter(|_| *is_constant)
+            .and_then(|default| evaluate_expression(default, &|name| constant_value(names, name)));
+        let interval = match value {
+            Some(value) => Some(Interval::constant(BigInt::from(value))),
+            None => type_interval(&declared_type(names, variable_type)),
+        };
+        declare(names, name, interval);
+    }
+}
+
+fn declared_type(names: &Names, typ: &Type) -> SymbolType {
+    resolve_type(typ, &|name| constant_value(names, name)).unwrap_or(SymbolType::None)
+}
+
+fn constant_value(names: &Names, name: &str) -> Option<BigUint> {
+    match names.get(name) {
+        Some(Some(interval)) if interval.is_constant() => interval.min.to_biguint(),
+        _ => None,
+    }
+}
+
+/// Interval of the values of `expression`, `None` if unknown.
+fn expression_interval(expression: &Expression, names: &Names) -> Option<Interval> {
+    let interval = |expression| expression_interval(expression, names);
+    match &expression.node {
+        ExpressionType::Number { value, .. } => {
+            Some(Interval::constant(BigInt::from(value.clone())))
+        }
+        ExpressionType::Identifier { value } => names.get(value).cloned().flatten(),
+        ExpressionType::UnaryExpression {
+            operator,
+            expression,
+        } => match operator {
+            Operator::Plus => interval(expression),
+            Operator::Minus => checked(interval(expression)?.neg()),
+            _ => None,
+        },
+        ExpressionType::TernaryExpression { expr1, expr2, .. } => {
+            Some(interval(expr1)?.hull(&interval(expr2)?))
+        }
+        ExpressionType::BinaryExpression {
+            left,
+            operator,
+            right,
+        } => {
+            let (left, right) = (interval(left)?, interval(right)?);
+            match operator {
+                Operator::Add => checked(left.add(&right)),
+                Operator::Sub => checked(left.sub(&right)),
+                Operator::Mul => checked(left.mul(&right)),
+                Operator::Div => left.div(&right),
+                Operator::Mod => left.rem(&right),
+                Operator::LShift => checked(left.shl(&right)?),
+                Operator::RShift => left.shr(&right),
+                Operator::BitAnd => left.bit_and(&right),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Result of comparing values of `left` with values of `right` by `operator`, if always the same.
+fn known_comparison(operator: &Operator, left: &Interval, right: &Interval) -> Option<bool> {
+    let (below, above) = match operator {
+        // Always true if `left` is below `right`, always false if it is above
+        Operator::Lt => (left.max < right.min, left.min >= right.max),
+        Operator::Le => (left.max <= right.min, left.min > right.max),
+        Operator::Gt => return known_comparison(&Operator::Lt, right, left),
+        Operator::Ge => return known_comparison(&Operator::Le, right, left),
+        Operator::Eq | Operator::NotEq => {
+            let equal = left.is_constant() && left == right;
+            let disjoint = left.intersect(right).is_none();
+            let is_eq = *operator == Operator::Eq;
+            return match (equal, disjoint) {
+                (true, _) => Some(is_eq),
+                (_, true) => Some(!is_eq),
+                _ => None,
+            };
+        }
+        _ => return None,
+    };
+    match (below, above) {
+        (true, _) => Some(true),
+        (_, true) => Some(false),
+        _ => None,
+    }
+}
+
+/// Comparisons in the functions of `program` whose result is known from the ranges of their operands.
+/// Comparisons of constants with each other aren't reported, they are usually deliberate.
+pub fn comparison_warnings(program: &Program) -> Vec<ComparisonWarning> {
+    let Program::GlobalStatements(statements) = program;
+    let mut warnings = vec![];
+    check_scope(statements, &HashMap::new(), &mut warnings);
+    warnings
+}
+
+/// Checks the functions among `statements`, in a scope nested in `outer`.
+fn check_scope(statements: &[Statement], outer: &Names, warnings: &mut Vec<ComparisonWarning>) {
+    let mut names = outer.clone();
+    for statement in statements {
+        declare_statement(&mut names, statement);
+    }
+    for statement in statements {
+        match &statement.node {
+            StatementType::FunctionStatement {
+                parameters,
+                statement: body,
+                ..
+            } => {
+                let mut names = names.clone();
+                let mut nodes = vec![Node::Expression(parameters)];
+                nodes.extend(body.as_deref().map(Node::Statement));
+                let nodes = descendants(nodes);
+                for node in &nodes {
+                    match node {
+                        Node::Statement(statement) => declare_statement(&mut names, statement),
+                        Node::Expression(expression) => {
+                            if let ExpressionType::ForEachExpression { iterator, .. } =
+                                &expression.node
+                            {
+                                if let Some(name) = iterator.node.identifier_name() {
+                                    declare(&mut names, name, None);
+                                }
+                            }
+                        }
+                    }
+                }
+                for node in nodes {
+                    if let Node::Expression(expression) = node {
+                        if let ExpressionType::BinaryExpression {
+                            left,
+                            operator,
+                            right,
+                        } = &expression.node
+                        {
+                            let left = expression_interval(left, &names);
+                            let right = expression_interval(right, &names);
+                            if let (Some(left), Some(right)) = (left, right) {
+                                if left.is_constant() && right.is_constant() {
+                                    continue;
+                                }
+                                if let Some(always) = known_comparison(operator, &left, &right) {
+                                    warnings.push(ComparisonWarning {
+                                        location: expression.location,
+                                        always,
+                                    });
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            StatementType::ContractStatement { members, .. } => {
+                if let StatementType::MemberStatement { statements } = &members.node {
+                    check_scope(statements, &names, warnings);
+                }
+            }
+            StatementType::ModuleStatement { members, .. } => {
+                check_scope(members, &names, warnings);
+            }
+            _ => {}
+        }
+    }
+}
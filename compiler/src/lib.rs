@@ -1,5 +1,51 @@
+pub mod abi;
+pub mod annotation;
+pub mod bounds;
+pub mod builtin;
+pub mod cfg;
+pub mod circuit;
+pub mod codesize;
+pub mod coercion;
+pub mod compat;
+pub mod conditional;
+pub mod constant;
+pub mod coverage;
+pub mod cse;
+pub mod database;
+pub mod deploy;
+pub mod desugar;
+pub mod dominators;
+pub mod driver;
 pub mod error;
+pub mod escape;
+pub mod evm;
+pub mod field;
+pub mod groth16;
+pub mod inliner;
+pub mod interpreter;
+pub mod intervals;
+pub mod liveness;
+pub mod metadata;
+pub mod mutate;
+pub mod peephole;
+pub mod precompile;
 pub mod prover;
 pub mod rewriter;
+pub mod sccp;
+pub mod settings;
+pub mod snapshot;
+pub mod stack;
+pub mod stdlib;
+pub mod storage;
+pub mod strength;
+pub mod structs;
 pub mod symbol;
+pub mod symbol_table;
+pub mod taint;
+pub mod test_runner;
+pub mod timings;
+pub mod trace;
+pub mod unroller;
 pub mod verifier;
+pub mod verify;
+pub mod witness;
@@ -0,0 +1,239 @@
+//! Liveness of local variables and reuse of their memory slots.
+//!
+//! A local is live at a point if its current value may be read later. Two locals
+//! interfere when one is assigned while the other is live, and locals which
+//! never interfere can share the same 32 byte memory slot, which keeps memory
+//! expansion small. Storage variables and type symbols are not locals.
+
+use crate::cfg::{BlockId, ControlFlowGraph, Terminator};
+use crate::symbol::{Function, Operation, OperationType, Symbol};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+/// Local variable as `(name, num, is_private)`.
+pub type Local = (String, u32, bool);
+
+pub const SLOT_SIZE: usize = 32;
+
+/// The local `symbol` refers to, if it isn't a storage variable or a type symbol.
+pub(crate) fn local(symbol: &Symbol) -> Option<Local> {
+    if symbol.name.is_empty() || symbol.data_location.is_state() {
+        None
+    } else {
+        Some((symbol.name.clone(), symbol.num, symbol.is_private))
+    }
+}
+
+/// Reads and writes of locals by one operation or terminator, reads first.
+#[derive(Debug, Default)]
+struct Access {
+    uses: Vec<Local>,
+    defs: Vec<Local>,
+}
+
+impl Access {
+    fn of_operation(operation: &Operation) -> Self {
+        let mut access = Access::default();
+        match &operation.operation {
+            OperationType::Assign { left, right } => {
+                access.read(right);
+                match &left.operation {
+                    OperationType::Symbol { symbol } => access.defs.extend(local(symbol)),
+                    // An element write keeps the rest of the array alive
+                    _ => access.read(left),
+                }
+            }
+            _ => access.read(operation),
+        }
+        access
+    }
+
+    fn of_terminator(terminator: &Terminator) -> Self {
+        let mut access = Access::default();
+        match terminator {
+            Terminator::Branch { cond, .. } => access.read(cond),
+            Terminator::Loop { iter, vector, .. } => {
+                access.read(vector);
+                if let Some(symbol) = iter.as_symbol() {
+                    access.defs.extend(local(&symbol));
+                }
+            }
+            Terminator::Return(Some(value)) => access.read(value),
+            Terminator::Goto(_) | Terminator::Return(None) | Terminator::Exit => {}
+        }
+        access
+    }
+
+    fn read(&mut self, operation: &Operation) {
+        if let OperationType::Symbol { symbol } = &operation.operation {
+            self.uses.extend(local(symbol));
+        }
+        for child in operation.children() {
+            self.read(child);
+        }
+    }
+}
+
+/// Accesses of a block in execution order.
+fn block_accesses(cfg: &ControlFlowGraph, block: BlockId) -> Vec<Access> {
+    let block = cfg.block(block);
+    let mut accesses: Vec<Access> = block.operations.iter().map(Access::of_operation).collect();
+    accesses.push(Access::of_terminator(&block.terminator));
+    accesses
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Liveness {
+    live_in: Vec<BTreeSet<Local>>,
+    live_out: Vec<BTreeSet<Local>>,
+}
+
+impl Liveness {
+    pub fn new(cfg: &ControlFlowGraph) -> Self {
+        let count = cfg.blocks.len();
+        let mut uses = vec![BTreeSet::new(); count];
+        let mut defs = vec![BTreeSet::new(); count];
+        for block in 0..count {
+            for access in block_accesses(cfg, block) {
+                for used in access.uses {
+                    if !defs[block].contains(&used) {
+                        uses[block].insert(used);
+                    }
+                }
+                defs[block].extend(access.defs);
+            }
+        }
+
+        let mut live_in: Vec<BTreeSet<Local>> = vec![BTreeSet::new(); count];
+        let mut live_out: Vec<BTreeSet<Local>> = vec![BTreeSet::new(); count];
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for block in (0..count).rev() {
+                let out: BTreeSet<Local> = cfg
+                    .successors(block)
+                    .into_iter()
+                    .flat_map(|succ| live_in[succ].iter().cloned())
+                    .collect();
+                let mut input = uses[block].clone();
+                input.extend(out.difference(&defs[block]).cloned());
+                if input != live_in[block] || out != live_out[block] {
+                    live_in[block] = input;
+                    live_out[block] = out;
+                    changed = true;
+                }
+            }
+        }
+        Liveness { live_in, live_out }
+    }
+
+    pub fn live_in(&self, block: BlockId) -> &BTreeSet<Local> {
+        &self.live_in[block]
+    }
+
+    pub fn live_out(&self, block: BlockId) -> &BTreeSet<Local> {
+        &self.live_out[block]
+    }
+}
+
+fn format_locals(locals: &BTreeSet<Local>) -> String {
+    locals
+        .iter()
+        .map(|(name, num, is_private)| {
+            let visibility = if *is_private { "private" } else { "public" };
+            format!("{}#{}({})", name, num, visibility)
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+impl fmt::Display for Liveness {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for block in 0..self.live_in.len() {
+            writeln!(f, "b{}:", block)?;
+            writeln!(f, "  in:  [{}]", format_locals(&self.live_in[block]))?;
+            writeln!(f, "  out: [{}]", format_locals(&self.live_out[block]))?;
+        }
+        Ok(())
+    }
+}
+
+/// Memory slot of every local of a function.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SlotAllocation {
+    pub slots: BTreeMap<Local, usize>,
+    pub slot_count: usize,
+}
+
+impl SlotAllocation {
+    pub fn slot(&self, local: &Local) -> Option<usize> {
+        self.slots.get(local).copied()
+    }
+
+    /// Memory offset of the slot of `local`.
+    pub fn offset(&self, local: &Local) -> Option<usize> {
+        self.slot(local).map(|slot| slot * SLOT_SIZE)
+    }
+}
+
+/// Assigns slots to the locals of `function`, sharing them between locals which never interfere.
+pub fn allocate_slots(function: &Function) -> SlotAllocation {
+    let cfg = ControlFlowGraph::new(function);
+    let liveness = Liveness::new(&cfg);
+
+    // Locals in order of appearance, parameters first
+    let mut locals: Vec<Local> = vec![];
+    let mut interference: BTreeMap<Local, BTreeSet<Local>> = BTreeMap::new();
+    let add_local = |locals: &mut Vec<Local>, local: &Local| {
+        if !locals.contains(local) {
+            locals.push(local.clone());
+        }
+    };
+    let mut interfere = |a: &Local, live: &BTreeSet<Local>| {
+        for b in live.iter().filter(|b| *b != a) {
+            interference.entry(a.clone()).or_default().insert(b.clone());
+            interference.entry(b.clone()).or_default().insert(a.clone());
+        }
+    };
+
+    // Parameters are all written on entry
+    let params: BTreeSet<Local> = function.params.iter().filter_map(local).collect();
+    for param in function.params.iter().filter_map(local) {
+        add_local(&mut locals, &param);
+        let mut live = liveness.live_in(cfg.entry).clone();
+        live.extend(params.iter().cloned());
+        interfere(&param, &live);
+    }
+    for block in &cfg.blocks {
+        let accesses = block_accesses(&cfg, block.id);
+        for access in &accesses {
+            for local in access.uses.iter().chain(&access.defs) {
+                add_local(&mut locals, local);
+            }
+        }
+        let mut live = liveness.live_out(block.id).clone();
+        for access in accesses.iter().rev() {
+            for def in &access.defs {
+                // A dead store still needs a slot while it is written
+                interfere(def, &live);
+                live.remove(def);
+            }
+            live.extend(access.uses.iter().cloned());
+        }
+    }
+
+    let mut slots: BTreeMap<Local, usize> = BTreeMap::new();
+    let mut slot_count = 0;
+    for local in locals {
+        let taken: BTreeSet<usize> = interference
+            .get(&local)
+            .into_iter()
+            .flatten()
+            .filter_map(|other| slots.get(other).copied())
+            .collect();
+        let slot = (0..).find(|slot| !taken.contains(slot)).unwrap();
+        slot_count = slot_count.max(slot + 1);
+        slots.insert(local, slot);
+    }
+    SlotAllocation { slots, slot_count }
+}
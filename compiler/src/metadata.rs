@@ -0,0 +1,198 @@
+//! Metadata of compiled contracts, to verify them against their sources.
+//!
+//! The metadata records what produced a contract: the compiler version, the
+//! settings, the keccak256 hash of each source and the ABI. It is serialized as
+//! compact JSON with sorted keys, so the same inputs always give the same bytes,
+//! and its keccak256 hash is appended to the bytecode in a CBOR trailer like solc
+//! does, followed by the length of the trailer in two bytes:
+//!
+//! `{"keccak256": <32 bytes>, "zoker": <major, minor, patch>}`
+//!
+//! There is no IPFS hash: the metadata is identified by its keccak256 hash only.
+
+use crate::abi::canonical_type;
+use crate::settings::CompileSettings;
+use crate::symbol::{Contract, Symbol};
+use sha3::{Digest, Keccak256};
+use std::convert::TryInto;
+use std::fmt::Write;
+use zoker_parser::ast::CONSTRUCTOR;
+
+/// Version of the compiler, recorded in the metadata and the bytecode.
+pub const COMPILER_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Function of the ABI, with the canonical names of its types.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AbiFunction {
+    pub name: String,
+    pub inputs: Vec<String>,
+    pub outputs: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Metadata {
+    pub compiler_version: String,
+    pub contract: String,
+    pub settings: CompileSettings,
+    /// keccak256 hash of each source, by name
+    pub sources: Vec<(String, [u8; 32])>,
+    pub abi: Vec<AbiFunction>,
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+pub(crate) fn json_string(value: &str) -> String {
+    let mut json = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            c if (c as u32) < 0x20 => write!(json, "\\u{:04x}", c as u32).unwrap(),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+fn json_types(types: &[String]) -> String {
+    let types: Vec<String> = types
+        .iter()
+        .map(|typ| format!("{{\"type\":{}}}", json_string(typ)))
+        .collect();
+    format!("[{}]", types.join(","))
+}
+
+impl Metadata {
+    /// Metadata of `contract` compiled from `sources`, given as `(name, content)`.
+    pub fn new(contract: &Contract, settings: CompileSettings, sources: &[(&str, &str)]) -> Self {
+        let types = |symbols: &[Symbol]| {
+            symbols
+                .iter()
+                .map(|symbol| canonical_type(&symbol.symbol_type))
+                .collect()
+        };
+        Metadata {
+            compiler_version: COMPILER_VERSION.to_string(),
+            contract: contract.name.clone(),
+            settings,
+            sources: sources
+                .iter()
+                .map(|(name, content)| {
+                    (
+                        name.to_string(),
+                        Keccak256::digest(content.as_bytes()).into(),
+                    )
+                })
+                .collect(),
+            abi: contract
+                .functions
+                .iter()
+                .filter(|function| !function.is_free)
+                .map(|function| AbiFunction {
+                    name: function.name.clone(),
+                    inputs: types(&function.params),
+                    outputs: types(&function.returns),
+                })
+                .collect(),
+        }
+    }
+
+    /// Compact JSON with sorted keys.
+    pub fn to_json(&self) -> String {
+        let mut abi: Vec<&AbiFunction> = self.abi.iter().collect();
+        abi.sort_by(|a, b| a.name.cmp(&b.name));
+        let abi: Vec<String> = abi
+            .iter()
+            .map(|function| {
+                if function.name == CONSTRUCTOR {
+                    return format!(
+                        "{{\"inputs\":{},\"type\":\"constructor\"}}",
+                        json_types(&function.inputs)
+                    );
+                }
+                format!(
+                    "{{\"inputs\":{},\"name\":{},\"outputs\":{},\"type\":\"function\"}}",
+                    json_types(&function.inputs),
+                    json_string(&function.name),
+                    json_types(&function.outputs)
+                )
+            })
+            .collect();
+        let mut sources: Vec<&(String, [u8; 32])> = self.sources.iter().collect();
+        sources.sort();
+        let sources: Vec<String> = sources
+            .iter()
+            .map(|(name, hash)| {
+                format!(
+                    "{}:{{\"keccak256\":\"0x{}\"}}",
+                    json_string(name),
+                    to_hex(hash)
+                )
+            })
+            .collect();
+        format!(
+            "{{\"compiler\":{{\"version\":{}}},\"contract\":{},\"language\":\"Zoker\",\
+             \"output\":{{\"abi\":[{}]}},\
+             \"settings\":{},\"sources\":{{{}}},\"version\":1}}",
+            json_string(&self.compiler_version),
+            json_string(&self.contract),
+            abi.join(","),
+            self.settings.to_json(),
+            sources.join(",")
+        )
+    }
+
+    /// keccak256 hash of the JSON.
+    pub fn hash(&self) -> [u8; 32] {
+        Keccak256::digest(self.to_json().as_bytes()).into()
+    }
+}
+
+/// Version as `[major, minor, patch]`, parts which aren't numbers as 0.
+fn version_bytes(version: &str) -> [u8; 3] {
+    let mut bytes = [0; 3];
+    for (byte, part) in bytes.iter_mut().zip(version.split('.')) {
+        *byte = part.parse().unwrap_or(0);
+    }
+    bytes
+}
+
+/// `code` followed by the CBOR trailer holding the hash of `metadata`.
+pub fn append_metadata(code: &[u8], metadata: &Metadata) -> Vec<u8> {
+    let mut trailer = vec![0xa2];
+    // Text string of 9 bytes, then byte string of 32 bytes
+    trailer.push(0x69);
+    trailer.extend_from_slice(b"keccak256");
+    trailer.extend_from_slice(&[0x58, 0x20]);
+    trailer.extend_from_slice(&metadata.hash());
+    // Text string of 5 bytes, then byte string of 3 bytes
+    trailer.push(0x65);
+    trailer.extend_from_slice(b"zoker");
+    trailer.push(0x43);
+    trailer.extend_from_slice(&version_bytes(&metadata.compiler_version));
+
+    let mut bytecode = code.to_vec();
+    bytecode.extend_from_slice(&trailer);
+    bytecode.extend_from_slice(&(trailer.len() as u16).to_be_bytes());
+    bytecode
+}
+
+fn trailer_hash(bytecode: &[u8]) -> Option<(&[u8], [u8; 32])> {
+    let (rest, length) = bytecode.split_at(bytecode.len().checked_sub(2)?);
+    let length = u16::from_be_bytes([length[0], length[1]]) as usize;
+    let (code, trailer) = rest.split_at(rest.len().checked_sub(length)?);
+    let prefix = [&[0xa2, 0x69][..], b"keccak256", &[0x58, 0x20]].concat();
+    let hash = trailer.strip_prefix(prefix.as_slice())?.get(..32)?;
+    Some((code, hash.try_into().ok()?))
+}
+
+/// Splits `bytecode` into its code and the metadata hash of its trailer, if it has one.
+pub fn split_metadata(bytecode: &[u8]) -> (&[u8], Option<[u8; 32]>) {
+    match trailer_hash(bytecode) {
+        Some((code, hash)) => (code, Some(hash)),
+        None => (bytecode, None),
+    }
+}
@@ -0,0 +1,238 @@
+//! Mutants of a program, to measure how well its tests detect changes.
+//!
+//! Each mutant differs from the program by a single mutation: an operator swapped
+//! for a close one, e.g. `<` for `<=`, the condition of an `if` or a ternary
+//! negated, or a number literal off by one. A mutant is killed when one of the
+//! tests fails on it; the mutation score is the share of mutants killed. The
+//! `@test` functions themselves are left as they are.
+
+use crate::desugar::{expression_children, statement_children, Node};
+use crate::test_runner::run_program_tests;
+use num_bigint::BigUint;
+use num_traits::{One, Zero};
+use std::fmt;
+use zoker_parser::ast::{Expression, ExpressionType, Operator, Program, StatementType};
+use zoker_parser::location::Location;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Mutation {
+    SwapOperator {
+        from: Operator,
+        to: Operator,
+    },
+    /// Condition of an `if` or a ternary negated
+    NegateCondition,
+    OffByOne {
+        from: BigUint,
+        to: BigUint,
+    },
+}
+
+impl fmt::Display for Mutation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Mutation::SwapOperator { from, to } => write!(f, "replaced {:?} by {:?}", from, to),
+            Mutation::NegateCondition => write!(f, "negated condition"),
+            Mutation::OffByOne { from, to } => write!(f, "replaced {} by {}", from, to),
+        }
+    }
+}
+
+/// Program changed by a single mutation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mutant {
+    pub mutation: Mutation,
+    /// Location of the mutated expression
+    pub location: Location,
+    pub program: Program,
+}
+
+/// Operators a mutant uses instead of `operator`.
+fn swapped_operators(operator: &Operator) -> Vec<Operator> {
+    match operator {
+        Operator::Add => vec![Operator::Sub],
+        Operator::Sub => vec![Operator::Add],
+        Operator::Mul => vec![Operator::Div],
+        Operator::Div => vec![Operator::Mul],
+        Operator::Mod => vec![Operator::Mul],
+        Operator::Lt => vec![Operator::Le, Operator::Gt],
+        Operator::Le => vec![Operator::Lt, Operator::Ge],
+        Operator::Gt => vec![Operator::Ge, Operator::Lt],
+        Operator::Ge => vec![Operator::Gt, Operator::Le],
+        Operator::Eq => vec![Operator::NotEq],
+        Operator::NotEq => vec![Operator::Eq],
+        Operator::And => vec![Operator::Or],
+        Operator::Or => vec![Operator::And],
+        Operator::AddAssign => vec![Operator::SubAssign],
+        Operator::SubAssign => vec![Operator::AddAssign],
+        Operator::MulAssign => vec![Operator::DivAssign],
+        Operator::DivAssign => vec![Operator::MulAssign],
+        _ => vec![],
+    }
+}
+
+/// Mutations of `expression` itself, not of its operands.
+fn mutations(expression: &Expression) -> Vec<Mutation> {
+    match &expression.node {
+        ExpressionType::BinaryExpression { operator, .. }
+        | ExpressionType::AssignExpression { operator, .. } => swapped_operators(operator)
+            .into_iter()
+            .map(|to| Mutation::SwapOperator {
+                from: operator.clone(),
+                to,
+            })
+            .collect(),
+        ExpressionType::IfExpression { .. } | ExpressionType::TernaryExpression { .. } => {
+            vec![Mutation::NegateCondition]
+        }
+        ExpressionType::Number { value, .. } => {
+            let mut mutations = vec![Mutation::OffByOne {
+                from: value.clone(),
+                to: value + 1u32,
+            }];
+            if !value.is_zero() {
+                mutations.push(Mutation::OffByOne {
+                    from: value.clone(),
+                    to: value - BigUint::one(),
+                });
+            }
+            mutations
+        }
+        _ => vec![],
+    }
+}
+
+fn apply(expression: &mut Expression, mutation: &Mutation) {
+    match (&mut expression.node, mutation) {
+        (
+            ExpressionType::BinaryExpression { operator, .. }
+            | ExpressionType::AssignExpression { operator, .. },
+            Mutation::SwapOperator { to, .. },
+        ) => *operator = to.clone(),
+        (
+            ExpressionType::IfExpression { condition, .. }
+            | ExpressionType::TernaryExpression { condition, .. },
+            Mutation::NegateCondition,
+        ) => {
+            let location = condition.location;
+            let original = std::mem::replace(
+                condition.as_mut(),
+                Expression {
+                    location,
+                    node: ExpressionType::Error,
+                },
+            );
+            **condition = Expression {
+                location,
+                node: ExpressionType::UnaryExpression {
+                    operator: Operator::Not,
+                    expression: Box::new(original),
+                },
+            };
+        }
+        (ExpressionType::Number { value, .. }, Mutation::OffByOne { to, .. }) => {
+            *value = to.clone()
+        }
+        _ => unreachable!(),
+    }
+}
+
+/// Mutations of `program` in order, applying the one numbered `target` if any.
+fn walk(program: &mut Program, target: Option<usize>) -> Vec<(Mutation, Location)> {
+    let Program::GlobalStatements(statements) = program;
+    let mut found = vec![];
+    let mut pending: Vec<Node> = statements.iter_mut().rev().map(Node::Statement).collect();
+    while let Some(node) = pending.pop() {
+        let children = match node {
+            Node::Statement(statement) => {
+                if let StatementType::FunctionStatement { .. } = statement.node {
+                    if statement.node.annotation("test").is_some() {
+                        continue;
+                    }
+                }
+                statement_children(statement)
+            }
+            Node::Expression(expression) => {
+                for mutation in mutations(expression) {
+                    if target == Some(found.len()) {
+                        apply(expression, &mutation);
+                        return found;
+                    }
+                    found.push((mutation, expression.location));
+                }
+                expression_children(expression)
+            }
+        };
+        pending.extend(children.into_iter().rev());
+    }
+    found
+}
+
+/// Mutants of `program`, in order of the mutated expressions in the source.
+pub fn mutate(program: &Program) -> Vec<Mutant> {
+    let mutations = walk(&mut program.clone(), None);
+    mutations
+        .into_iter()
+        .enumerate()
+        .map(|(i, (mutation, location))| {
+            let mut mutant = program.clone();
+            walk(&mut mutant, Some(i));
+            Mutant {
+                mutation,
+                location,
+                program: mutant,
+            }
+        })
+        .collect()
+}
+
+/// Mutants killed and survived by the tests of a program.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MutationReport {
+    pub killed: usize,
+    /// Mutations none of the tests detected
+    pub survived: Vec<(Mutation, Location)>,
+}
+
+impl MutationReport {
+    pub fn total(&self) -> usize {
+        self.killed + self.survived.len()
+    }
+
+    /// Share of the mutants killed, 1 if there is none.
+    pub fn score(&self) -> f64 {
+        if self.total() == 0 {
+            return 1.0;
+        }
+        self.killed as f64 / self.total() as f64
+    }
+}
+
+impl fmt::Display for MutationReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "mutation score: {:.1}% ({} of {} killed)",
+            self.score() * 100.0,
+            self.killed,
+            self.total()
+        )?;
+        for (mutation, location) in &self.survived {
+            write!(f, "\n  survived at {}: {}", location, mutation)?;
+        }
+        Ok(())
+    }
+}
+
+/// Runs the tests of `program` on each of its mutants. The tests are expected to pass on `program`.
+pub fn mutation_score(program: &Program) -> MutationReport {
+    let mut report = MutationReport::default();
+    for mutant in mutate(program) {
+        if run_program_tests(&mutant.program).is_success() {
+            report.survived.push((mutant.mutation, mutant.location));
+        } else {
+            report.killed += 1;
+        }
+    }
+    report
+}
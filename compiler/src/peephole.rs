@@ -0,0 +1,81 @@
+//! Peephole optimization of emitted stack instructions.
+//!
+//! Short instruction windows with a cheaper equivalent are rewritten until no
+//! rule applies anymore. Arithmetic wraps modulo 2^256 as on the EVM.
+
+use crate::stack::StackOp;
+use num_bigint::BigUint;
+use num_traits::{One, Zero};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, PartialOrd)]
+pub enum OptimizationLevel {
+    /// Instructions are emitted as scheduled
+    None,
+    /// Removes instructions without effect, e.g. `SWAP1 SWAP1` or `PUSH 0 ADD`
+    #[default]
+    Basic,
+    /// Also folds arithmetic on pushed constants
+    Full,
+}
+
+fn modulus() -> BigUint {
+    BigUint::one() << 256
+}
+
+/// Rewrites the window at the start of `ops`, returning the replacement and the
+/// number of instructions it replaces.
+fn rewrite(ops: &[StackOp], level: OptimizationLevel) -> Option<(Vec<StackOp>, usize)> {
+    use StackOp::*;
+    let basic = match ops {
+        [Swap(a), Swap(b), ..] if a == b => Some((vec![], 2)),
+        [Dup(_), Pop, ..] | [Push(_), Pop, ..] => Some((vec![], 2)),
+        [Push(value), Add, ..] if value.is_zero() => Some((vec![], 2)),
+        [Push(value), Mul, ..] if value.is_one() => Some((vec![], 2)),
+        [Push(value), Mul, ..] if value.is_zero() => Some((vec![Pop, Push(BigUint::zero())], 2)),
+        _ => None,
+    };
+    if basic.is_some() || level < OptimizationLevel::Full {
+        return basic;
+    }
+    match ops {
+        [Push(a), Push(b), Add, ..] => Some((vec![Push((a + b) % modulus())], 3)),
+        [Push(a), Push(b), Mul, ..] => Some((vec![Push((a * b) % modulus())], 3)),
+        // SUB takes its left operand from the top
+        [Push(a), Push(b), Sub, ..] => Some((vec![Push((b + modulus() - a) % modulus())], 3)),
+        // Left for the basic rule if the swap is undone right after
+        [Push(a), Push(b), Swap(1), rest @ ..] if rest.first() != Some(&Swap(1)) => {
+            Some((vec![Push(b.clone()), Push(a.clone())], 3))
+        }
+        _ => None,
+    }
+}
+
+/// Optimizes `ops` at `level`, returning the number of rewrites applied.
+pub fn optimize(ops: &mut Vec<StackOp>, level: OptimizationLevel) -> usize {
+    if level == OptimizationLevel::None {
+        return 0;
+    }
+    let mut rewrites = 0;
+    let mut changed = true;
+    while changed {
+        changed = false;
+        let mut result = Vec::with_capacity(ops.len());
+        let mut i = 0;
+        while i < ops.len() {
+            match rewrite(&ops[i..], level) {
+                Some((replacement, length)) => {
+                    result.extend(replacement);
+                    i += length;
+                    rewrites += 1;
+                    changed = true;
+                }
+                None => {
+                    result.push(ops[i].clone());
+                    i += 1;
+                }
+            }
+        }
+        *ops = result;
+    }
+    rewrites
+}
@@ -0,0 +1,19 @@
+//! Native implementations of the EVM precompiled contracts used by builtins.
+
+/// Recovers the signer address of `hash` from an `(v, r, s)` signature.
+/// Returns `None` for malformed signatures, as the precompile returns no data.
+#[cfg(feature = "secp256k1")]
+pub fn ecrecover(hash: &[u8; 32], v: u8, r: &[u8; 32], s: &[u8; 32]) -> Option<[u8; 20]> {
+    use k256::ecdsa::{RecoveryId, Signature, VerifyingKey};
+    use sha3::{Digest, Keccak256};
+
+    let recovery_id = RecoveryId::from_byte(v.checked_sub(27)?)?;
+    let signature = Signature::from_scalars(*r, *s).ok()?;
+    let key = VerifyingKey::recover_from_prehash(hash, &signature, recovery_id).ok()?;
+    let point = key.to_encoded_point(false);
+    // Drop the `0x04` prefix of the uncompressed point before hashing.
+    let hash = Keccak256::digest(&point.as_bytes()[1..]);
+    let mut address = [0; 20];
+    address.copy_from_slice(&hash[12..]);
+    Some(address)
+}
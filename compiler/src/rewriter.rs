@@ -1,11 +1,17 @@
+use crate::annotation::check_annotations;
+use crate::builtin::BuiltinFunction;
+use crate::constant::evaluate_expression;
 use crate::error::{RewriteError, RewriteErrorType};
+use crate::stdlib::{std_module, STD};
 use crate::symbol::{
-    specifier_to_location, token_to_type, Contract, Function, Operation, OperationType, Symbol,
+    resolve_type, specifier_to_location, Contract, Function, Operation, OperationType, Symbol,
     SymbolLocation, SymbolType,
 };
 use indexmap::map::IndexMap;
+use num_bigint::BigUint;
+use std::collections::HashMap;
 use zoker_parser::ast;
-use zoker_parser::ast::{ExpressionType, Operator, StatementType};
+use zoker_parser::ast::{ContractKind, ExpressionType, Operator, StatementType};
 use zoker_parser::location::Location;
 
 pub type RewriterResult<T> = Result<T, RewriteError>;
@@ -21,11 +27,33 @@ pub type RewriterResult<T> = Result<T, RewriteError>;
 // }
 
 pub fn rewrite_program(ast: &ast::Program) -> RewriterResult<Vec<Contract>> {
-    let mut rewriter = Rewriter::new();
+    rewrite_program_with(ast, &RewriteSettings::default())
+}
+
+pub fn rewrite_program_with(
+    ast: &ast::Program,
+    settings: &RewriteSettings,
+) -> RewriterResult<Vec<Contract>> {
+    let mut rewriter = Rewriter::new(settings.clone());
     rewriter.compile_program(ast)?;
     Ok(rewriter.contracts)
 }
 
+#[derive(Debug, Clone)]
+pub struct RewriteSettings {
+    /// Inserts a `BoundsCheck` before each statement indexing an array,
+    /// except inside `unchecked` blocks.
+    pub bounds_checks: bool,
+}
+
+impl Default for RewriteSettings {
+    fn default() -> Self {
+        RewriteSettings {
+            bounds_checks: true,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 struct RewriterContext {
     public_map: IndexMap<String, Symbol>,
@@ -69,24 +97,129 @@ impl RewriterContext {
 
 struct Rewriter {
     context: RewriterContext,
+    settings: RewriteSettings,
+    /// Depth of nested `unchecked` blocks
+    unchecked: usize,
+    /// Bounds checks of the statement being compiled
+    bounds_checks: Vec<Operation>,
+    /// Whether the members of a contract are being compiled
+    in_contract: bool,
+    /// Functions declared outside contracts
+    free_functions: Vec<Function>,
+    /// Modules being compiled, outermost first
+    modules: Vec<String>,
+    /// Paths of the functions of every module, e.g. `math::clamp`
+    module_functions: Vec<String>,
+    /// Paths of the modules of the standard library compiled so far
+    imported: Vec<String>,
+    /// Values of the constants declared so far, for the lengths of fixed arrays
+    constants: HashMap<String, BigUint>,
     pub contracts: Vec<Contract>,
 }
 
 impl Rewriter {
-    fn new() -> Self {
+    fn new(settings: RewriteSettings) -> Self {
         Rewriter {
             context: RewriterContext::new(),
+            settings,
+            unchecked: 0,
+            bounds_checks: vec![],
+            in_contract: false,
+            free_functions: vec![],
+            modules: vec![],
+            module_functions: vec![],
+            imported: vec![],
+            constants: HashMap::new(),
             contracts: vec![],
         }
     }
 
     fn compile_program(&mut self, ast: &ast::Program) -> RewriterResult<()> {
         match ast {
-            ast::Program::GlobalStatements(stmts) => self.compile_statements(stmts)?,
+            ast::Program::GlobalStatements(stmts) => {
+                self.module_functions = module_functions(stmts);
+                self.compile_declarations(stmts)?;
+            }
         }
+        self.link_free_functions();
         Ok(())
     }
 
+    /// Adds to each contract the free functions it calls, directly or through other
+    /// free functions. Functions of the contract shadow free functions of the same name.
+    fn link_free_functions(&mut self) {
+        for contract in &mut self.contracts {
+            let mut linked: Vec<Function> = vec![];
+            let mut pending = called_functions(&contract.functions);
+            while let Some(name) = pending.pop() {
+                let is_defined = contract.functions.iter().any(|f| f.name == name)
+                    || linked.iter().any(|f| f.name == name);
+                if is_defined {
+                    continue;
+                }
+                if let Some(function) = self.free_functions.iter().find(|f| f.name == name) {
+                    pending.extend(called_functions(std::slice::from_ref(function)));
+                    linked.push(function.clone());
+                }
+            }
+            contract.functions.extend(linked);
+        }
+    }
+
+    /// Compiles the statements of the file or of a module.
+    fn compile_declarations(&mut self, statements: &[ast::Statement]) -> RewriterResult<()> {
+        for statement in statements {
+            match statement.node {
+                // Constants outside contracts are evaluated by the symbol table
+                StatementType::InitializerStatement { .. } => self.declare_constant(statement),
+                _ => self.compile_statement(statement)?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Keeps the value of `statement` if it declares a constant, for the array lengths using it.
+    fn declare_constant(&mut self, statement: &ast::Statement) {
+        if let StatementType::InitializerStatement {
+            is_constant: true,
+            variable: Some(variable),
+            default: Some(default),
+            ..
+        } = &statement.node
+        {
+            let constants = &self.constants;
+            if let Some(value) = evaluate_expression(default, &|name| constants.get(name).cloned())
+            {
+                let name = variable.node.identifier_name().unwrap();
+                self.constants.insert(name, value);
+            }
+        }
+    }
+
+    /// Type of `typ` with the lengths of its arrays evaluated.
+    fn resolve_type(&self, typ: &ast::Type) -> RewriterResult<SymbolType> {
+        resolve_type(typ, &|name| self.constants.get(name).cloned()).map_err(|length| {
+            RewriteError {
+                error: RewriteErrorType::TypeError(String::from("array length is not a constant")),
+                location: length.location,
+            }
+        })
+    }
+
+    /// Name of the function called by `path`: the path of a module function,
+    /// relative to the innermost module being compiled which declares it, or
+    /// `path` itself for other functions.
+    fn function_name(&self, path: &[String]) -> String {
+        let path = path.join("::");
+        for depth in (1..=self.modules.len()).rev() {
+            let name = format!("{}::{}", self.modules[..depth].join("::"), path);
+            if self.module_functions.contains(&name) {
+                return name;
+            }
+        }
+        path
+    }
+
     fn compile_statements(&mut self, statements: &[ast::Statement]) -> RewriterResult<()> {
         for statement in statements {
             self.compile_statement(statement)?;
@@ -97,43 +230,100 @@ impl Rewriter {
     fn compile_statement(&mut self, statement: &ast::Statement) -> RewriterResult<()> {
         match &statement.node {
             StatementType::FunctionStatement {
+                annotations,
                 function_name,
                 parameters,
                 statement,
                 returns,
+                ..
             } => {
                 self.context = RewriterContext::new();
-                let name = function_name.node.identifier_name().unwrap();
+                let mut name = function_name.node.identifier_name().unwrap();
+                if !self.modules.is_empty() {
+                    name = format!("{}::{}", self.modules.join("::"), name);
+                }
                 let params = self.compile_param_symbols(parameters)?;
                 let ret = if let Some(return_type) = returns {
                     self.compile_param_symbols(return_type)?
                 } else {
                     vec![]
                 };
-                let function = Function::new(name, params, ret);
-                self.current_contract().add_function(function);
+                let mut function = Function::new(name, params, ret);
+                // Checked by the symbol table
+                function.annotations = check_annotations(annotations, false).unwrap_or_default();
+                if self.in_contract {
+                    self.current_contract().add_function(function);
+                } else {
+                    function.is_free = true;
+                    self.free_functions.push(function);
+                }
 
                 self.enter_scope();
-                self.compile_statement(statement)?;
+                if let Some(statement) = statement {
+                    self.compile_statement(statement)?;
+                }
                 self.add_operation_all();
             }
+            // Interfaces only declare what other contracts implement, libraries
+            // and the calls through them aren't lowered yet
+            StatementType::ContractStatement {
+                kind: ContractKind::Interface | ContractKind::Library,
+                ..
+            } => {}
+            StatementType::UsingStatement { .. } => {}
             StatementType::ContractStatement {
+                annotations,
                 contract_name,
                 members,
+                ..
             } => {
                 let name = contract_name.node.identifier_name().unwrap();
-                let contract = Contract::new(name);
+                let mut contract = Contract::new(name);
+                contract.annotations = check_annotations(annotations, true).unwrap_or_default();
                 self.add_contract(contract);
+                self.in_contract = true;
                 self.compile_statement(members)?;
+                self.in_contract = false;
+            }
+            StatementType::ImportStatement { path } => {
+                let module = std_module(path).ok_or_else(|| RewriteError {
+                    error: RewriteErrorType::TypeError(format!("unknown import `{}`", path)),
+                    location: statement.location,
+                })?;
+                if !self.imported.contains(path) {
+                    self.imported.push(path.clone());
+                    self.modules.push(STD.to_string());
+                    let result = self.compile_statement(&module.statement);
+                    self.modules.pop();
+                    result?;
+                }
+            }
+            StatementType::ModuleStatement {
+                module_name,
+                members,
+            } => {
+                let name = module_name.node.identifier_name().unwrap();
+                self.modules.push(name);
+                let result = self.compile_declarations(members);
+                self.modules.pop();
+                result?;
             }
+            // Items of a program not stripped by `strip_disabled` are left out
+            StatementType::ConditionalStatement { .. } => {}
+            // Events only appear in the ABI, see `SymbolTable::events`
+            StatementType::EventStatement { .. } => {}
+            // Enum values are resolved by the symbol table
+            StatementType::EnumStatement { .. } => {}
+            StatementType::StructStatement { .. } => {}
             StatementType::InitializerStatement {
                 variable_type,
                 is_private,
                 data_location,
                 variable,
                 default,
+                ..
             } => {
-                let typ = token_to_type(variable_type);
+                let typ = self.resolve_type(variable_type)?;
                 let loc = if let Some(location) = data_location {
                     specifier_to_location(location)
                 } else {
@@ -145,17 +335,20 @@ impl Rewriter {
                     let symbol = self.get_variable(&name);
                     let left = Operation::new_symbol(symbol);
 
-                    let operation = if let Some(var) = default {
-                        self.compile_expression(var)?;
-                        let right = self.pop_operation();
-                        Operation::new(OperationType::Assign {
-                            left: Box::new(left),
-                            right: Box::new(right),
-                        })
-                    } else {
-                        left
-                    };
-                    self.push_operation(operation);
+                    self.compile_checked(|rewriter| {
+                        let operation = if let Some(var) = default {
+                            rewriter.compile_expression(var)?;
+                            let right = rewriter.pop_operation();
+                            Operation::new(OperationType::Assign {
+                                left: Box::new(left),
+                                right: Box::new(right),
+                            })
+                        } else {
+                            left
+                        };
+                        rewriter.push_operation(operation);
+                        Ok(())
+                    })?;
                 } else {
                     self.push_operation(Operation::new_symbol(Symbol::new_type_symbol(typ)))
                 }
@@ -173,18 +366,56 @@ impl Rewriter {
                 self.push_operation_all(operations);
             }
             StatementType::MemberStatement { statements } => {
-                self.compile_statements(statements)?;
+                for member in statements {
+                    match member.node {
+                        // Constants of contracts are evaluated by the symbol table too
+                        StatementType::InitializerStatement {
+                            is_constant: true, ..
+                        } => self.declare_constant(member),
+                        // State variables aren't lowered yet
+                        StatementType::InitializerStatement { .. } => {
+                            return Err(RewriteError {
+                                error: RewriteErrorType::UnsupportedError,
+                                location: member.location,
+                            });
+                        }
+                        _ => self.compile_statement(member)?,
+                    }
+                }
             }
             StatementType::ReturnStatement { ret } => {
                 if let Some(returns) = ret {
-                    self.compile_expression(returns)?;
-                    let ret = self.pop_operation();
-                    let operation = Operation::new(OperationType::Return { ret: Box::new(ret) });
-                    self.push_operation(operation);
+                    self.compile_checked(|rewriter| {
+                        rewriter.compile_expression(returns)?;
+                        let ret = rewriter.pop_operation();
+                        let operation =
+                            Operation::new(OperationType::Return { ret: Box::new(ret) });
+                        rewriter.push_operation(operation);
+                        Ok(())
+                    })?;
                 }
             }
             StatementType::Expression { expression } => {
-                self.compile_expression(expression)?;
+                self.compile_checked(|rewriter| rewriter.compile_expression(expression))?;
+            }
+            StatementType::UncheckedStatement { statement } => {
+                self.unchecked += 1;
+                let result = self.compile_statement(statement);
+                self.unchecked -= 1;
+                result?;
+            }
+            // Circuits can't loop for a number of iterations known only at runtime
+            StatementType::DoWhileStatement { .. } => {
+                return Err(RewriteError {
+                    error: RewriteErrorType::UnsupportedError,
+                    location: statement.location,
+                });
+            }
+            StatementType::Error => {
+                return Err(RewriteError {
+                    error: RewriteErrorType::SyntaxError(String::from("invalid statement")),
+                    location: statement.location,
+                });
             }
         }
         Ok(())
@@ -235,6 +466,26 @@ impl Rewriter {
                         left: Box::new(left),
                         right: Box::new(right),
                     },
+                    Operator::Mul => OperationType::Mul {
+                        left: Box::new(left),
+                        right: Box::new(right),
+                    },
+                    Operator::Div => OperationType::Div {
+                        left: Box::new(left),
+                        right: Box::new(right),
+                    },
+                    Operator::Pow => OperationType::Pow {
+                        left: Box::new(left),
+                        right: Box::new(right),
+                    },
+                    Operator::LShift => OperationType::Shl {
+                        left: Box::new(left),
+                        right: Box::new(right),
+                    },
+                    Operator::RShift => OperationType::Shr {
+                        left: Box::new(left),
+                        right: Box::new(right),
+                    },
                     _ => {
                         return Err(RewriteError {
                             error: RewriteErrorType::UnsupportedError,
@@ -249,11 +500,40 @@ impl Rewriter {
                 function_name,
                 arguments,
             } => {
-                let name = function_name.node.identifier_name().unwrap();
+                let name = match function_name.node.module_path() {
+                    Some(path) => self.function_name(&path),
+                    None => function_name.node.path_name().unwrap(),
+                };
                 let args = self.compile_params(arguments)?;
-                let operation = Operation::new_call(name, args);
+                let operation = if let Some(builtin) = BuiltinFunction::from_name(&name) {
+                    Operation::new_builtin(builtin, args)
+                } else {
+                    Operation::new_call(name, args)
+                };
                 self.push_operation(operation);
             }
+            ExpressionType::IndexExpression {
+                expression: array,
+                index,
+            } => {
+                self.compile_expression(array)?;
+                let array = self.pop_operation();
+                self.compile_expression(index)?;
+                let index = self.pop_operation();
+                if self.settings.bounds_checks && self.unchecked == 0 {
+                    let check = Operation::new(OperationType::BoundsCheck {
+                        array: Box::new(array.clone()),
+                        index: Box::new(index.clone()),
+                    });
+                    if !self.bounds_checks.contains(&check) {
+                        self.bounds_checks.push(check);
+                    }
+                }
+                self.push_operation(Operation::new(OperationType::Index {
+                    array: Box::new(array),
+                    index: Box::new(index),
+                }));
+            }
             ExpressionType::IfExpression {
                 condition,
                 if_statement,
@@ -307,7 +587,7 @@ impl Rewriter {
                     });
                 }
             }
-            ExpressionType::Number { value } => {
+            ExpressionType::Number { value, .. } => {
                 self.push_operation(Operation::new(OperationType::Constant {
                     value: value.clone(),
                 }));
@@ -334,6 +614,28 @@ impl Rewriter {
                     location: Location::new(0, 0),
                 });
             }
+            ExpressionType::MemberExpression { .. }
+            | ExpressionType::PathExpression { .. }
+            | ExpressionType::StringLiteral { .. }
+            | ExpressionType::MatchExpression { .. }
+            | ExpressionType::StructLiteral { .. } => {
+                return Err(RewriteError {
+                    error: RewriteErrorType::UnsupportedError,
+                    location: expression.location,
+                });
+            }
+            ExpressionType::Error => {
+                return Err(RewriteError {
+                    error: RewriteErrorType::SyntaxError(String::from("invalid expression")),
+                    location: expression.location,
+                });
+            }
+            ExpressionType::TypeList { types } => {
+                for typ in types {
+                    let symbol = Symbol::new_type_symbol(self.resolve_type(typ)?);
+                    self.push_operation(Operation::new_symbol(symbol));
+                }
+            }
             ExpressionType::Tuple { .. } => {
                 return Err(RewriteError {
                     error: RewriteErrorType::UnsupportedError,
@@ -350,6 +652,24 @@ impl Rewriter {
         Ok(())
     }
 
+    /// Compiles a statement, preceded by the bounds checks of its array accesses.
+    fn compile_checked<F>(&mut self, compile: F) -> RewriterResult<()>
+    where
+        F: FnOnce(&mut Self) -> RewriterResult<()>,
+    {
+        let outer_checks = std::mem::take(&mut self.bounds_checks);
+        let start = self.context.operations.last().unwrap().len();
+        let result = compile(self);
+        let checks = std::mem::replace(&mut self.bounds_checks, outer_checks);
+        result?;
+        self.context
+            .operations
+            .last_mut()
+            .unwrap()
+            .splice(start..start, checks);
+        Ok(())
+    }
+
     fn compile_params(&mut self, expression: &ast::Expression) -> RewriterResult<Vec<Operation>> {
         match &expression.node {
             ExpressionType::Parameters { parameters } => {
@@ -415,7 +735,12 @@ impl Rewriter {
 
     fn add_operation_all(&mut self) {
         let operation = self.exit_scope();
-        self.current_contract().add_operation_all(operation);
+        if self.in_contract {
+            self.current_contract().add_operation_all(operation);
+        } else {
+            let function = self.free_functions.last_mut().unwrap();
+            function.add_operations(operation);
+        }
     }
 
     fn init_variable(
@@ -454,3 +779,54 @@ impl Rewriter {
         }
     }
 }
+
+/// Paths of the functions declared in the modules of `statements`.
+fn module_functions(statements: &[ast::Statement]) -> Vec<String> {
+    let mut paths = vec![];
+    let mut pending: Vec<(&ast::Statement, String)> =
+        statements.iter().map(|s| (s, String::new())).collect();
+    while let Some((statement, prefix)) = pending.pop() {
+        match &statement.node {
+            StatementType::ModuleStatement {
+                module_name,
+                members,
+            } => {
+                let name = module_name.node.identifier_name().unwrap();
+                let prefix = format!("{}{}::", prefix, name);
+                pending.extend(members.iter().map(|member| (member, prefix.clone())));
+            }
+            StatementType::ImportStatement { path } => {
+                if let Some(module) = std_module(path) {
+                    pending.push((&module.statement, format!("{}::", STD)));
+                }
+            }
+            StatementType::FunctionStatement { function_name, .. } if !prefix.is_empty() => {
+                let name = function_name.node.identifier_name().unwrap();
+                paths.push(format!("{}{}", prefix, name));
+            }
+            _ => {}
+        }
+    }
+    paths
+}
+
+/// Names of the functions called by `functions`, without duplicates.
+fn called_functions(functions: &[Function]) -> Vec<String> {
+    fn visit(operation: &Operation, names: &mut Vec<String>) {
+        if let OperationType::Call { func, .. } = &operation.operation {
+            if !names.contains(func) {
+                names.push(func.clone());
+            }
+        }
+        for child in operation.children() {
+            visit(child, names);
+        }
+    }
+    let mut names = vec![];
+    for function in functions {
+        for operation in &function.operations {
+            visit(operation, &mut names);
+        }
+    }
+    names
+}
@@ -0,0 +1,337 @@
+//! Conditional constant propagation.
+//!
+//! Locals are tracked as either a known constant or unknown while walking the
+//! operations of a function. Only the branches which may be taken contribute
+//! to the values after an `If`, so a constant condition removes the dead
+//! branch and keeps propagating through the live one. Loops are iterated until
+//! the values at their head no longer change.
+//!
+//! Reads of constant locals are replaced by their value and operations on
+//! constants are folded, unless the value doesn't fit the type of their operands,
+//! which is left to overflow at runtime. Operations following a `Return` are removed.
+
+use crate::coercion::{integer_bits, operand_bits};
+use crate::constant::evaluate;
+use crate::liveness::{local, Local};
+use crate::symbol::{Contract, Function, Operation, OperationType};
+use num_bigint::BigUint;
+use num_traits::Zero;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+    Constant(BigUint),
+    Unknown,
+}
+
+type Values = HashMap<Local, Value>;
+
+/// Whether `value` is in the range of an integer of `bits`.
+fn fits(value: &BigUint, bits: Option<(bool, u16)>) -> bool {
+    match bits {
+        Some((signed, bits)) => value.bits() <= (bits - signed as u16) as usize,
+        None => true,
+    }
+}
+
+/// Values after two paths join, a local is only constant if it is on both.
+fn join(a: &Values, b: &Values) -> Values {
+    let mut values = Values::new();
+    for (local, value) in a {
+        let joined = match b.get(local) {
+            Some(other) if other == value => value.clone(),
+            _ => Value::Unknown,
+        };
+        values.insert(local.clone(), joined);
+    }
+    for local in b.keys() {
+        values.entry(local.clone()).or_insert(Value::Unknown);
+    }
+    values
+}
+
+fn join_paths(a: Option<Values>, b: Option<Values>) -> Option<Values> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(join(&a, &b)),
+        (a, None) => a,
+        (None, b) => b,
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct PropagationStats {
+    /// Reads and operations replaced by a constant
+    pub folded: usize,
+    /// `If` and `Else` operations replaced by the taken branch of a constant condition
+    pub removed_branches: usize,
+    /// Operations removed because they follow a `Return`
+    pub removed_unreachable: usize,
+}
+
+impl PropagationStats {
+    fn add(&mut self, other: &PropagationStats) {
+        self.folded += other.folded;
+        self.removed_branches += other.removed_branches;
+        self.removed_unreachable += other.removed_unreachable;
+    }
+}
+
+pub fn propagate_constants(contract: &mut Contract) -> PropagationStats {
+    let mut stats = PropagationStats::default();
+    for function in &mut contract.functions {
+        stats.add(&propagate_function_constants(function));
+    }
+    stats
+}
+
+pub fn propagate_function_constants(function: &mut Function) -> PropagationStats {
+    let mut propagator = Propagator::default();
+    let operations = std::mem::take(&mut function.operations);
+    function.operations = propagator.block(operations, Values::new()).0;
+    propagator.stats
+}
+
+#[derive(Default)]
+struct Propagator {
+    stats: PropagationStats,
+}
+
+impl Propagator {
+    /// Rewrites `operations`, returning them with the values at their end, or
+    /// `None` if every path returned.
+    fn block(
+        &mut self,
+        operations: Vec<Operation>,
+        mut values: Values,
+    ) -> (Vec<Operation>, Option<Values>) {
+        let mut result = vec![];
+        let mut operations = operations.into_iter().peekable();
+        while let Some(operation) = operations.next() {
+            match operation.operation {
+                OperationType::If { cond, stmts } => {
+                    let otherwise = match operations.peek().map(|op| &op.operation) {
+                        Some(OperationType::Else { .. }) => operations.next(),
+                        _ => None,
+                    };
+                    let cond = self.fold(*cond, &values);
+                    match evaluate(&cond) {
+                        Some(value) => {
+                            self.stats.removed_branches += 1 + otherwise.is_some() as usize;
+                            let taken = if value.is_zero() {
+                                match otherwise.map(|op| op.operation) {
+                                    Some(OperationType::Else { stmts, .. }) => stmts,
+                                    _ => vec![],
+                                }
+                            } else {
+                                stmts
+                            };
+                            let (stmts, end) = self.block(taken, values);
+                            result.extend(stmts);
+                            match end {
+                                Some(end) => values = end,
+                                None => return self.unreachable(result, operations),
+                            }
+                        }
+                        None => {
+                            let (stmts, then_end) = self.block(stmts, values.clone());
+                            result.push(Operation::new(OperationType::If {
+                                cond: Box::new(cond.clone()),
+                                stmts,
+                            }));
+                            let else_end = match otherwise.map(|op| op.operation) {
+                                Some(OperationType::Else { stmts, .. }) => {
+                                    let (stmts, end) = self.block(stmts, values.clone());
+                                    result.push(Operation::new(OperationType::Else {
+                                        cond: Box::new(cond),
+                                        stmts,
+                                    }));
+                                    end
+                                }
+                                _ => Some(values),
+                            };
+                            match join_paths(then_end, else_end) {
+                                Some(end) => values = end,
+                                None => return self.unreachable(result, operations),
+                            }
+                        }
+                    }
+                }
+                // `Else` without its `If`, which may or may not run
+                OperationType::Else { cond, stmts } => {
+                    let cond = self.fold(*cond, &values);
+                    let (stmts, end) = self.block(stmts, values.clone());
+                    result.push(Operation::new(OperationType::Else {
+                        cond: Box::new(cond),
+                        stmts,
+                    }));
+                    values = join_paths(end, Some(values)).unwrap();
+                }
+                OperationType::For {
+                    iter,
+                    vector,
+                    stmts,
+                } => {
+                    let vector = self.fold(*vector, &values);
+                    let mut head = values;
+                    if let Some(iter) = iter.as_symbol().as_ref().and_then(local) {
+                        head.insert(iter, Value::Unknown);
+                    }
+                    // The head is reached before the first iteration and after each one
+                    loop {
+                        let (_, end) = Propagator::default().block(stmts.clone(), head.clone());
+                        let next = match end {
+                            Some(end) => join(&head, &end),
+                            None => head.clone(),
+                        };
+                        if next == head {
+                            break;
+                        }
+                        head = next;
+                    }
+                    let (stmts, _) = self.block(stmts, head.clone());
+                    result.push(Operation::new(OperationType::For {
+                        iter,
+                        vector: Box::new(vector),
+                        stmts,
+                    }));
+                    values = head;
+                }
+                OperationType::Return { ret } => {
+                    let ret = self.fold(*ret, &values);
+                    result.push(Operation::new(OperationType::Return { ret: Box::new(ret) }));
+                    return self.unreachable(result, operations);
+                }
+                OperationType::Assign { left, right } => {
+                    let right = self.fold(*right, &values);
+                    let left = match left.as_symbol().as_ref().and_then(local) {
+                        Some(target) => {
+                            let bits = integer_bits(&left.as_symbol().unwrap().symbol_type);
+                            let value = match &right.operation {
+                                OperationType::Constant { value } if fits(value, bits) => {
+                                    Value::Constant(value.clone())
+                                }
+                                _ => Value::Unknown,
+                            };
+                            values.insert(target, value);
+                            left
+                        }
+                        // Element of an array
+                        None => Box::new(self.fold(*left, &values)),
+                    };
+                    result.push(Operation::new(OperationType::Assign {
+                        left,
+                        right: Box::new(right),
+                    }));
+                }
+                // Declaration without a value
+                OperationType::Symbol { symbol } => {
+                    if let Some(declared) = local(&symbol) {
+                        values.insert(declared, Value::Unknown);
+                    }
+                    result.push(Operation::new(OperationType::Symbol { symbol }));
+                }
+                op => result.push(self.fold(Operation::new(op), &values)),
+            }
+        }
+        (result, Some(values))
+    }
+
+    fn unreachable(
+        &mut self,
+        result: Vec<Operation>,
+        rest: impl Iterator<Item = Operation>,
+    ) -> (Vec<Operation>, Option<Values>) {
+        self.stats.removed_unreachable += rest.count();
+        (result, None)
+    }
+
+    /// Replaces constant reads and operations of `operation` by their value.
+    fn fold(&mut self, operation: Operation, values: &Values) -> Operation {
+        let bits = operand_bits(&operation);
+        let unfolded = (self.stats.folded, bits.map(|_| operation.clone()));
+        let fold_box =
+            |propagator: &mut Self, op: Box<Operation>| Box::new(propagator.fold(*op, values));
+        let op = match operation.operation {
+            OperationType::Symbol { symbol } => {
+                let value = local(&symbol).and_then(|local| values.get(&local));
+                match value {
+                    Some(Value::Constant(value)) => {
+                        self.stats.folded += 1;
+                        OperationType::Constant {
+                            value: value.clone(),
+                        }
+                    }
+                    _ => OperationType::Symbol { symbol },
+                }
+            }
+            OperationType::Add { left, right } => OperationType::Add {
+                left: fold_box(self, left),
+                right: fold_box(self, right),
+            },
+            OperationType::Sub { left, right } => OperationType::Sub {
+                left: fold_box(self, left),
+                right: fold_box(self, right),
+            },
+            OperationType::Mul { left, right } => OperationType::Mul {
+                left: fold_box(self, left),
+                right: fold_box(self, right),
+            },
+            OperationType::Div { left, right } => OperationType::Div {
+                left: fold_box(self, left),
+                right: fold_box(self, right),
+            },
+            OperationType::Pow { left, right } => OperationType::Pow {
+                left: fold_box(self, left),
+                right: fold_box(self, right),
+            },
+            OperationType::Shl { left, right } => OperationType::Shl {
+                left: fold_box(self, left),
+                right: fold_box(self, right),
+            },
+            OperationType::Shr { left, right } => OperationType::Shr {
+                left: fold_box(self, left),
+                right: fold_box(self, right),
+            },
+            OperationType::Index { array, index } => OperationType::Index {
+                array,
+                index: fold_box(self, index),
+            },
+            OperationType::BoundsCheck { array, index } => OperationType::BoundsCheck {
+                array,
+                index: fold_box(self, index),
+            },
+            OperationType::Call { func, args } => OperationType::Call {
+                func,
+                args: args.into_iter().map(|arg| self.fold(arg, values)).collect(),
+            },
+            OperationType::Builtin { function, args } => OperationType::Builtin {
+                function,
+                args: args.into_iter().map(|arg| self.fold(arg, values)).collect(),
+            },
+            op => op,
+        };
+        let operation = Operation::new(op);
+        match &operation.operation {
+            OperationType::Add { .. }
+            | OperationType::Sub { .. }
+            | OperationType::Mul { .. }
+            | OperationType::Div { .. }
+            | OperationType::Pow { .. }
+            | OperationType::Shl { .. }
+            | OperationType::Shr { .. } => match evaluate(&operation) {
+                Some(value) if fits(&value, bits) => {
+                    self.stats.folded += 1;
+                    Operation::new(OperationType::Constant { value })
+                }
+                // Kept with its operands, so that it overflows at runtime
+                Some(_) => {
+                    let (folded, unfolded) = unfolded;
+                    self.stats.folded = folded;
+                    unfolded.unwrap()
+                }
+                None => operation,
+            },
+            _ => operation,
+        }
+    }
+}
@@ -0,0 +1,123 @@
+//! Settings of a compilation which change its output.
+//!
+//! The settings are recorded in the metadata of the artifacts, so that the same
+//! sources compiled again with them give the same bytes. They are serialized as
+//! compact JSON with sorted keys, and identified by the keccak256 hash of it.
+
+use crate::conditional::Defines;
+use crate::metadata::json_string;
+use sha3::{Digest, Keccak256};
+use std::fmt;
+
+/// Optimizations run on the lowered contracts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum OptLevel {
+    /// Contracts are left as lowered
+    None,
+    /// Constant propagation and strength reduction, which only rewrites the
+    /// divisions of unsigned integers
+    Basic,
+    /// Every optimization, also eliminating common calls and bounds checks
+    Full,
+}
+
+impl fmt::Display for OptLevel {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            OptLevel::None => write!(f, "none"),
+            OptLevel::Basic => write!(f, "basic"),
+            OptLevel::Full => write!(f, "full"),
+        }
+    }
+}
+
+/// Platform the contracts are compiled for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Target {
+    /// The only one until the prover of the contracts is generated
+    Evm,
+}
+
+impl fmt::Display for Target {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Target::Evm => write!(f, "evm"),
+        }
+    }
+}
+
+/// Hard fork of the EVM the contracts are deployed on, from oldest to newest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum EvmVersion {
+    London,
+    Paris,
+    Shanghai,
+    Cancun,
+}
+
+impl fmt::Display for EvmVersion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EvmVersion::London => write!(f, "london"),
+            EvmVersion::Paris => write!(f, "paris"),
+            EvmVersion::Shanghai => write!(f, "shanghai"),
+            EvmVersion::Cancun => write!(f, "cancun"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CompileSettings {
+    pub opt_level: OptLevel,
+    pub target: Target,
+    /// Names the `#[cfg(..)]` attributes are evaluated against
+    pub defines: Defines,
+    pub evm_version: EvmVersion,
+    /// Checks the indices of arrays, except inside `unchecked` blocks
+    pub bounds_checks: bool,
+}
+
+impl Default for CompileSettings {
+    fn default() -> Self {
+        CompileSettings {
+            opt_level: OptLevel::Full,
+            target: Target::Evm,
+            defines: Defines::default(),
+            evm_version: EvmVersion::Cancun,
+            bounds_checks: true,
+        }
+    }
+}
+
+impl CompileSettings {
+    /// Compact JSON with sorted keys.
+    pub fn to_json(&self) -> String {
+        let flags: Vec<String> = self
+            .defines
+            .flags
+            .iter()
+            .map(|flag| json_string(flag))
+            .collect();
+        let values: Vec<String> = self
+            .defines
+            .values
+            .iter()
+            .map(|(name, value)| format!("[{},{}]", json_string(name), json_string(value)))
+            .collect();
+        format!(
+            "{{\"boundsChecks\":{},\"defines\":{{\"flags\":[{}],\"values\":[{}]}},\
+             \"evmVersion\":\"{}\",\"optLevel\":\"{}\",\"target\":\"{}\"}}",
+            self.bounds_checks,
+            flags.join(","),
+            values.join(","),
+            self.evm_version,
+            self.opt_level,
+            self.target
+        )
+    }
+
+    /// keccak256 hash of the JSON, the same for equal settings.
+    pub fn hash(&self) -> [u8; 32] {
+        Keccak256::digest(self.to_json().as_bytes()).into()
+    }
+}
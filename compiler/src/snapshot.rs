@@ -0,0 +1,108 @@
+//! Canonical text of symbol tables, for golden tests of the compiler front end.
+//!
+//! Tables are written in the order of their scopes, each indented under the one
+//! enclosing it, and their symbols sorted by name so that the text doesn't depend
+//! on the order in which the builder registers them. Locations are written like
+//! in the snapshots of the AST, and left out with the same `SnapshotConfig`.
+
+use crate::symbol::{SymbolLocation, SymbolType};
+use crate::symbol_table::{Symbol, SymbolKind, SymbolTable, SymbolUsage};
+use zoker_parser::snapshot::{location, SnapshotConfig};
+
+/// Canonical text of `table` and the tables nested in it.
+pub fn snapshot_symbol_table(table: &SymbolTable, config: &SnapshotConfig) -> String {
+    let mut snapshot = String::new();
+    let mut pending = vec![(table, 0)];
+    while let Some((table, depth)) = pending.pop() {
+        let indent = "  ".repeat(depth);
+        snapshot.push_str(&format!(
+            "{}table {:?} {}",
+            indent, table.table_type, table.name
+        ));
+        if let Some(mutability) = table.mutability {
+            snapshot.push_str(&format!(" {}", mutability));
+        }
+        snapshot.push('\n');
+        for event in &table.events {
+            snapshot.push_str(&format!("{}  event {}", indent, event.signature));
+            if config.locations {
+                snapshot.push_str(&format!(" @{}", location(event.location)));
+            }
+            snapshot.push('\n');
+        }
+        for symbol in table.symbols_sorted_by_name() {
+            snapshot.push_str(&format!("{}  {}\n", indent, symbol_line(symbol, config)));
+        }
+        pending.extend(table.sub_tables.iter().rev().map(|sub| (sub, depth + 1)));
+    }
+    snapshot
+}
+
+fn symbol_line(symbol: &Symbol, config: &SnapshotConfig) -> String {
+    let usage = match symbol.usage {
+        SymbolUsage::Declared => "declared",
+        SymbolUsage::Read => "read",
+        SymbolUsage::Written => "written",
+        SymbolUsage::ReadWritten => "read written",
+    };
+    let mut line = format!(
+        "symbol {}: {} {}",
+        symbol.name,
+        type_name(&symbol.symbol_type),
+        usage
+    );
+    if symbol.kind == SymbolKind::Parameter {
+        line.push_str(" parameter");
+    }
+    match symbol.data_location {
+        SymbolLocation::Storage => line.push_str(" storage"),
+        SymbolLocation::Memory => line.push_str(" memory"),
+        SymbolLocation::Transient => line.push_str(" transient"),
+        SymbolLocation::Unknown => {}
+    }
+    if let Some(constant) = &symbol.constant {
+        line.push_str(&format!(" = {}", constant));
+    }
+    if symbol.is_private {
+        line.push_str(" private");
+    }
+    for annotation in &symbol.annotations {
+        line.push_str(&format!(" {}", annotation));
+    }
+    if config.locations {
+        line.push_str(&format!(" @{}", location(symbol.location)));
+    }
+    line
+}
+
+/// Type of a symbol, with the signatures of functions and the members of types.
+fn type_name(typ: &SymbolType) -> String {
+    let list = |types: &[SymbolType]| {
+        let types: Vec<String> = types.iter().map(type_name).collect();
+        types.join(", ")
+    };
+    match typ {
+        SymbolType::Function { params, returns } if returns.is_empty() => {
+            format!("function({})", list(params))
+        }
+        SymbolType::Function { params, returns } => {
+            format!("function({}) returns ({})", list(params), list(returns))
+        }
+        SymbolType::Contract(name) => format!("contract {}", name),
+        SymbolType::Module(name) => format!("module {}", name),
+        SymbolType::Namespace(name) => format!("namespace {}", name),
+        SymbolType::Enum { name, variants } => {
+            format!("enum {} {{ {} }}", name, variants.join(", "))
+        }
+        SymbolType::Struct { name, fields } => {
+            let fields: Vec<String> = fields
+                .iter()
+                .map(|(field, typ)| format!("{}: {}", field, type_name(typ)))
+                .collect();
+            format!("struct {} {{ {} }}", name, fields.join(", "))
+        }
+        SymbolType::Array(element) => format!("{}[]", type_name(element)),
+        SymbolType::FixedArray(element, length) => format!("{}[{}]", type_name(element), length),
+        _ => typ.to_string(),
+    }
+}
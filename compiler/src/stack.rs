@@ -0,0 +1,318 @@
+//! Stack scheduling of straight-line IR for the EVM.
+//!
+//! Locals live in stack slots instead of memory, as Yul variables do. The
+//! scheduler tracks the layout of the stack while it evaluates each statement
+//! and picks the `DUP`/`SWAP`/`POP` instructions moving values into place:
+//!
+//! - the last read of a local on top of the stack consumes it instead of copying it,
+//! - a binary operation on the last reads of the two topmost locals uses them in place,
+//! - a local is popped as soon as it is dead.
+//!
+//! `DUP` and `SWAP` only reach 16 slots deep, deeper accesses fail to schedule.
+
+use crate::liveness::{local, Local};
+use crate::symbol::{Operation, OperationType};
+use num_bigint::BigUint;
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+
+pub const MAX_STACK_ACCESS: usize = 16;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum StackOp {
+    Push(BigUint),
+    /// Copies the `n`th slot from the top, `Dup(1)` copies the top
+    Dup(usize),
+    /// Exchanges the top with the slot `n` below it
+    Swap(usize),
+    Pop,
+    Add,
+    Sub,
+    Mul,
+}
+
+impl StackOp {
+    /// Whether the instruction only moves values around.
+    pub fn is_shuffle(&self) -> bool {
+        matches!(self, StackOp::Dup(_) | StackOp::Swap(_) | StackOp::Pop)
+    }
+}
+
+impl fmt::Display for StackOp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StackOp::Push(value) => write!(f, "PUSH {}", value),
+            StackOp::Dup(n) => write!(f, "DUP{}", n),
+            StackOp::Swap(n) => write!(f, "SWAP{}", n),
+            StackOp::Pop => write!(f, "POP"),
+            StackOp::Add => write!(f, "ADD"),
+            StackOp::Sub => write!(f, "SUB"),
+            StackOp::Mul => write!(f, "MUL"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Schedule {
+    pub ops: Vec<StackOp>,
+    /// Layout after the block from bottom to top, `None` for the returned value
+    pub stack: Vec<Option<Local>>,
+}
+
+impl Schedule {
+    /// Number of `DUP`, `SWAP` and `POP` instructions.
+    pub fn shuffles(&self) -> usize {
+        self.ops.iter().filter(|op| op.is_shuffle()).count()
+    }
+}
+
+/// Schedules `operations` starting with the locals of `inputs` on the stack, bottom first.
+/// Locals of `live_out` are kept on the stack for the following blocks.
+///
+/// Returns `None` if the block reads a slot out of reach or uses an operation other
+/// than assignments to locals, arithmetic, constants and `Return`.
+pub fn schedule_block(
+    inputs: &[Local],
+    operations: &[Operation],
+    live_out: &BTreeSet<Local>,
+) -> Option<Schedule> {
+    let mut scheduler = Scheduler {
+        stack: inputs.iter().cloned().map(Some).collect(),
+        ops: vec![],
+    };
+    let live = live_before(operations, live_out);
+    scheduler.cleanup(&live[0])?;
+    for (operation, live) in operations.iter().zip(&live[1..]) {
+        let mut remaining = HashMap::new();
+        for local in reads(operation) {
+            *remaining.entry(local).or_insert(0) += 1;
+        }
+        match &operation.operation {
+            OperationType::Assign { left, right } => {
+                let target = match &left.operation {
+                    OperationType::Symbol { symbol } => local(symbol)?,
+                    _ => return None,
+                };
+                scheduler.evaluate(right, &mut remaining, live)?;
+                scheduler.assign(target)?;
+                scheduler.cleanup(live)?;
+            }
+            OperationType::Return { ret } => {
+                scheduler.evaluate(ret, &mut remaining, live)?;
+                // Only the returned value is left
+                while scheduler.stack.len() > 1 {
+                    scheduler.emit(StackOp::Swap(1));
+                    scheduler.emit(StackOp::Pop);
+                    let top = scheduler.stack.pop();
+                    scheduler.stack.pop();
+                    scheduler.stack.push(top.flatten());
+                }
+                return Some(scheduler.finish());
+            }
+            OperationType::Nop => {}
+            _ => return None,
+        }
+    }
+    Some(scheduler.finish())
+}
+
+fn collect_reads(operation: &Operation, reads: &mut Vec<Local>) {
+    if let OperationType::Symbol { symbol } = &operation.operation {
+        reads.extend(local(symbol));
+    }
+    for child in operation.children() {
+        collect_reads(child, reads);
+    }
+}
+
+/// Locals read by a statement, once per read.
+fn reads(operation: &Operation) -> Vec<Local> {
+    let mut reads = vec![];
+    match &operation.operation {
+        OperationType::Assign { left, right } => {
+            collect_reads(right, &mut reads);
+            if left.as_symbol().is_none() {
+                collect_reads(left, &mut reads);
+            }
+        }
+        _ => collect_reads(operation, &mut reads),
+    }
+    reads
+}
+
+/// Locals live before each statement, followed by `live_out`.
+fn live_before(operations: &[Operation], live_out: &BTreeSet<Local>) -> Vec<BTreeSet<Local>> {
+    let mut live = live_out.clone();
+    let mut result = vec![live.clone()];
+    for operation in operations.iter().rev() {
+        if let OperationType::Assign { left, .. } = &operation.operation {
+            if let Some(target) = left.as_symbol().as_ref().and_then(local) {
+                live.remove(&target);
+            }
+        }
+        live.extend(reads(operation));
+        result.push(live.clone());
+    }
+    result.reverse();
+    result
+}
+
+struct Scheduler {
+    /// Bottom to top, `None` for temporaries
+    stack: Vec<Option<Local>>,
+    ops: Vec<StackOp>,
+}
+
+impl Scheduler {
+    fn emit(&mut self, op: StackOp) {
+        self.ops.push(op);
+    }
+
+    fn depth_of(&self, local: &Local) -> Option<usize> {
+        self.stack
+            .iter()
+            .rposition(|slot| slot.as_ref() == Some(local))
+            .map(|position| self.stack.len() - 1 - position)
+    }
+
+    /// Reads of `operation` which are the last use of a local on the stack.
+    fn last_read(
+        &self,
+        operation: &Operation,
+        remaining: &HashMap<Local, usize>,
+        live: &BTreeSet<Local>,
+    ) -> Option<(Local, usize)> {
+        let local = operation.as_symbol().as_ref().and_then(local)?;
+        if remaining.get(&local) != Some(&1) || live.contains(&local) {
+            return None;
+        }
+        self.depth_of(&local).map(|depth| (local, depth))
+    }
+
+    fn evaluate(
+        &mut self,
+        operation: &Operation,
+        remaining: &mut HashMap<Local, usize>,
+        live: &BTreeSet<Local>,
+    ) -> Option<()> {
+        match &operation.operation {
+            OperationType::Constant { value } => {
+                self.emit(StackOp::Push(value.clone()));
+                self.stack.push(None);
+            }
+            OperationType::Symbol { symbol } => {
+                let local = local(symbol)?;
+                let depth = self.depth_of(&local)?;
+                let count = remaining.get_mut(&local)?;
+                *count -= 1;
+                if *count == 0 && !live.contains(&local) && depth == 0 {
+                    // Consumed in place
+                    *self.stack.last_mut().unwrap() = None;
+                } else {
+                    if depth >= MAX_STACK_ACCESS {
+                        return None;
+                    }
+                    self.emit(StackOp::Dup(depth + 1));
+                    self.stack.push(None);
+                }
+            }
+            OperationType::Add { left, right }
+            | OperationType::Sub { left, right }
+            | OperationType::Mul { left, right } => {
+                let op = match operation.operation {
+                    OperationType::Add { .. } => StackOp::Add,
+                    OperationType::Sub { .. } => StackOp::Sub,
+                    _ => StackOp::Mul,
+                };
+                let commutative = op != StackOp::Sub;
+                let in_place = match (
+                    self.last_read(left, remaining, live),
+                    self.last_read(right, remaining, live),
+                ) {
+                    (Some((l, 0)), Some((r, 1))) if l != r => Some(false),
+                    (Some((l, 1)), Some((r, 0))) if l != r => Some(true),
+                    _ => None,
+                };
+                match in_place {
+                    // SUB takes the left operand from the top
+                    Some(left_below) => {
+                        if left_below && !commutative {
+                            self.emit(StackOp::Swap(1));
+                        }
+                        for operand in [left, right] {
+                            let local = operand.as_symbol().as_ref().and_then(local)?;
+                            remaining.insert(local, 0);
+                        }
+                        self.stack.pop();
+                        self.stack.pop();
+                    }
+                    None => {
+                        // The operand consuming the top goes first so that it stays in place
+                        let right_first = !commutative
+                            || matches!(self.last_read(right, remaining, live), Some((_, 0)));
+                        let (first, second) = if right_first {
+                            (right, left)
+                        } else {
+                            (left, right)
+                        };
+                        self.evaluate(first, remaining, live)?;
+                        self.evaluate(second, remaining, live)?;
+                        self.stack.pop();
+                        self.stack.pop();
+                    }
+                }
+                self.emit(op);
+                self.stack.push(None);
+            }
+            _ => return None,
+        }
+        Some(())
+    }
+
+    /// Names the value on top after `target`, replacing its previous slot.
+    fn assign(&mut self, target: Local) -> Option<()> {
+        let top = self.stack.len() - 1;
+        if let Some(position) = self.stack[..top]
+            .iter()
+            .rposition(|slot| slot.as_ref() == Some(&target))
+        {
+            let depth = top - position;
+            if depth > MAX_STACK_ACCESS {
+                return None;
+            }
+            self.emit(StackOp::Swap(depth));
+            self.emit(StackOp::Pop);
+            self.stack.pop();
+            self.stack[position] = Some(target);
+        } else {
+            self.stack[top] = Some(target);
+        }
+        Some(())
+    }
+
+    /// Pops every slot not holding a local of `live`.
+    fn cleanup(&mut self, live: &BTreeSet<Local>) -> Option<()> {
+        let is_dead = |slot: &Option<Local>| !matches!(slot, Some(l) if live.contains(l));
+        while let Some(position) = self.stack.iter().rposition(is_dead) {
+            let depth = self.stack.len() - 1 - position;
+            if depth > 0 {
+                if depth > MAX_STACK_ACCESS {
+                    return None;
+                }
+                self.emit(StackOp::Swap(depth));
+                let top = self.stack.len() - 1;
+                self.stack.swap(position, top);
+            }
+            self.emit(StackOp::Pop);
+            self.stack.pop();
+        }
+        Some(())
+    }
+
+    fn finish(self) -> Schedule {
+        Schedule {
+            ops: self.ops,
+            stack: self.stack,
+        }
+    }
+}
@@ -0,0 +1,68 @@
+//! Standard library of modules written in Zoker and embedded in the compiler.
+//!
+//! `import "std/math";` makes the module `math` available as `std::math`,
+//! e.g. `std::math::square(x)`. Each module is parsed and its symbol table
+//! built once, then shared by every compilation importing it.
+
+use crate::symbol_table::{build_symbol_table, SymbolTable};
+use std::sync::OnceLock;
+use zoker_parser::ast;
+use zoker_parser::parser::parse_program;
+
+/// Module containing the modules of the standard library.
+pub const STD: &str = "std";
+
+const SOURCES: &[(&str, &str)] = &[
+    ("math", include_str!("stdlib/math.zok")),
+    ("hash", include_str!("stdlib/hash.zok")),
+    ("utils", include_str!("stdlib/utils.zok")),
+];
+
+/// Module of the standard library.
+#[derive(Debug)]
+pub struct StdModule {
+    pub name: &'static str,
+    pub source: &'static str,
+    /// Declaration of the module
+    pub statement: ast::Statement,
+    /// Table of the module, as nested in the global table
+    pub table: SymbolTable,
+}
+
+impl StdModule {
+    fn load(name: &'static str, source: &'static str) -> Self {
+        let program = parse_program(source)
+            .unwrap_or_else(|error| panic!("std/{} doesn't parse: {:?}", name, error));
+        let table = build_symbol_table(&program)
+            .unwrap_or_else(|error| panic!("std/{} doesn't resolve: {:?}", name, error));
+        let ast::Program::GlobalStatements(mut statements) = program;
+        StdModule {
+            name,
+            source,
+            statement: statements.remove(0),
+            table: table.module_table(name).unwrap().clone(),
+        }
+    }
+
+    /// Path which imports the module, e.g. `std/math`.
+    pub fn path(&self) -> String {
+        format!("{}/{}", STD, self.name)
+    }
+}
+
+/// Every module of the standard library, loaded on first use.
+pub fn std_modules() -> &'static [StdModule] {
+    static MODULES: OnceLock<Vec<StdModule>> = OnceLock::new();
+    MODULES.get_or_init(|| {
+        SOURCES
+            .iter()
+            .map(|(name, source)| StdModule::load(name, source))
+            .collect()
+    })
+}
+
+/// Module imported by `path`, e.g. `std/math`.
+pub fn std_module(path: &str) -> Option<&'static StdModule> {
+    let name = path.strip_prefix(STD)?.strip_prefix('/')?;
+    std_modules().iter().find(|module| module.name == name)
+}
@@ -0,0 +1,264 @@
+//! Storage layout of state variables.
+//!
+//! State variables are laid out in order of declaration as Solidity does:
+//! a variable smaller than a slot shares the previous slot if it fits in the
+//! remaining bytes, and dynamic types always take a slot of their own.
+//! Fixed arrays and structs start a new slot and take whole slots, their
+//! elements and fields being packed the same way inside them.
+//! Transient state variables are laid out the same way in slots of their own,
+//! as transient storage is separate from storage.
+
+use crate::symbol::{SymbolLocation, SymbolType};
+use crate::symbol_table::{SymbolTable, SymbolTableType, SymbolUsage};
+use std::fmt;
+use zoker_parser::location::Location;
+
+pub const STORAGE_SLOT_SIZE: usize = 32;
+
+/// Declaration of a type declared in the program, e.g. the `Struct` named by a `UserDefined`.
+type TypeResolver<'a> = &'a dyn Fn(&str) -> Option<SymbolType>;
+
+/// Storage taken by a value.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Footprint {
+    /// Bytes which can share a slot with other values
+    Packed(usize),
+    /// Whole slots of its own
+    Slots(usize),
+}
+
+/// Storage taken by a value of `typ`.
+fn footprint(typ: &SymbolType, resolve: TypeResolver) -> Footprint {
+    match typ {
+        SymbolType::Bool | SymbolType::Enum { .. } => Footprint::Packed(1),
+        SymbolType::Uint(bits) | SymbolType::Int(bits) => Footprint::Packed(*bits as usize / 8),
+        SymbolType::Address | SymbolType::Contract(_) => Footprint::Packed(20),
+        SymbolType::Uint256 | SymbolType::Int256 | SymbolType::Bytes32 | SymbolType::Field => {
+            Footprint::Packed(STORAGE_SLOT_SIZE)
+        }
+        SymbolType::FixedArray(element, length) => {
+            let slots = match footprint(element, resolve) {
+                Footprint::Packed(size) => {
+                    let per_slot = STORAGE_SLOT_SIZE / size;
+                    length.div_ceil(per_slot)
+                }
+                Footprint::Slots(slots) => length * slots,
+            };
+            Footprint::Slots(slots.max(1))
+        }
+        SymbolType::Struct { fields, .. } => {
+            let layout = StorageLayout::with_types(fields, resolve);
+            Footprint::Slots(layout.slot_count.max(1))
+        }
+        SymbolType::UserDefined(name) => match resolve(name) {
+            Some(declaration) => footprint(&declaration, resolve),
+            None => Footprint::Slots(1),
+        },
+        _ => Footprint::Slots(1),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StorageEntry {
+    pub name: String,
+    pub symbol_type: SymbolType,
+    pub slot: usize,
+    /// Offset in bytes from the lowest-order byte of the slot
+    pub offset: usize,
+    pub size: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StorageLayout {
+    pub entries: Vec<StorageEntry>,
+    pub slot_count: usize,
+}
+
+impl StorageLayout {
+    /// Packs `variables` in the given order.
+    pub fn new(variables: &[(String, SymbolType)]) -> Self {
+        StorageLayout::with_types(variables, &|_| None)
+    }
+
+    /// Packs `variables` in the given order, `resolve` giving the declarations
+    /// of the structs, enums and contracts they refer to by name.
+    fn with_types(variables: &[(String, SymbolType)], resolve: TypeResolver) -> Self {
+        let mut entries = vec![];
+        let mut slot = 0;
+        // Bytes used in `slot`
+        let mut used = 0;
+        for (name, typ) in variables {
+            let footprint = footprint(typ, resolve);
+            let fits =
+                matches!(footprint, Footprint::Packed(size) if used + size <= STORAGE_SLOT_SIZE);
+            if used > 0 && !fits {
+                slot += 1;
+                used = 0;
+            }
+            let size = match footprint {
+                Footprint::Packed(size) => size,
+                Footprint::Slots(slots) => slots * STORAGE_SLOT_SIZE,
+            };
+            entries.push(StorageEntry {
+                name: name.clone(),
+                symbol_type: typ.clone(),
+                slot,
+                offset: used,
+                size,
+            });
+            match footprint {
+                Footprint::Packed(size) => used += size,
+                // Nothing is packed after a value taking slots of its own
+                Footprint::Slots(slots) => {
+                    slot += slots - 1;
+                    used = STORAGE_SLOT_SIZE;
+                }
+            }
+        }
+        let slot_count = if entries.is_empty() { 0 } else { slot + 1 };
+        StorageLayout {
+            entries,
+            slot_count,
+        }
+    }
+
+    pub fn entry(&self, name: &str) -> Option<&StorageEntry> {
+        self.entries.iter().find(|entry| entry.name == name)
+    }
+}
+
+/// State variables of `contract` in `location`, in order of declaration.
+fn state_variables(contract: &SymbolTable, location: SymbolLocation) -> Vec<(String, SymbolType)> {
+    contract
+        .symbols_in_source_order()
+        .into_iter()
+        .filter(|symbol| symbol.usage == SymbolUsage::Declared && symbol.data_location == location)
+        .map(|symbol| (symbol.name.clone(), symbol.symbol_type.clone()))
+        .collect()
+}
+
+/// Declaration of the type `name` used in `contract`, declared in it or outside contracts.
+fn declared_type(table: &SymbolTable, contract: &SymbolTable, name: &str) -> Option<SymbolType> {
+    let symbol = contract.lookup(name).or_else(|| table.lookup(name))?;
+    match symbol.symbol_type {
+        SymbolType::Struct { .. } | SymbolType::Enum { .. } | SymbolType::Contract(_) => {
+            Some(symbol.symbol_type.clone())
+        }
+        _ => None,
+    }
+}
+
+/// Layout of the state variables of `contract` in `location`.
+fn layout(table: &SymbolTable, contract: &SymbolTable, location: SymbolLocation) -> StorageLayout {
+    StorageLayout::with_types(&state_variables(contract, location), &|name| {
+        declared_type(table, contract, name)
+    })
+}
+
+/// Storage layout of the state variables of the contract `contract`.
+pub fn storage_layout(table: &SymbolTable, contract: &str) -> Option<StorageLayout> {
+    let contract = table.contract_table(contract)?;
+    Some(layout(table, contract, SymbolLocation::Storage))
+}
+
+/// Transient storage layout of the transient state variables of the contract `contract`.
+pub fn transient_layout(table: &SymbolTable, contract: &str) -> Option<StorageLayout> {
+    let contract = table.contract_table(contract)?;
+    Some(layout(table, contract, SymbolLocation::Transient))
+}
+
+/// Order of `variables` packing them in few slots: whole slots first, then the
+/// smaller variables grouped by first fit decreasing.
+fn packed_order(
+    variables: &[(String, SymbolType)],
+    resolve: TypeResolver,
+) -> Vec<(String, SymbolType)> {
+    let small_size = |typ: &SymbolType| match footprint(typ, resolve) {
+        Footprint::Packed(size) if size < STORAGE_SLOT_SIZE => Some(size),
+        _ => None,
+    };
+    let (mut small, whole): (Vec<_>, Vec<_>) = variables
+        .iter()
+        .cloned()
+        .partition(|(_, typ)| small_size(typ).is_some());
+    // Stable, so variables of the same size keep their order
+    small.sort_by_key(|(_, typ)| std::cmp::Reverse(small_size(typ)));
+    let mut bins: Vec<(usize, Vec<(String, SymbolType)>)> = vec![];
+    for variable in small {
+        let size = small_size(&variable.1).unwrap();
+        match bins
+            .iter_mut()
+            .find(|(used, _)| used + size <= STORAGE_SLOT_SIZE)
+        {
+            Some((used, bin)) => {
+                *used += size;
+                bin.push(variable);
+            }
+            None => bins.push((size, vec![variable])),
+        }
+    }
+    whole
+        .into_iter()
+        .chain(bins.into_iter().flat_map(|(_, bin)| bin))
+        .collect()
+}
+
+/// Suggestion to reorder the state variables of a contract to use fewer storage slots.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PackingSuggestion {
+    pub contract: String,
+    /// State variables in the suggested order
+    pub order: Vec<String>,
+    pub slots: usize,
+    pub packed_slots: usize,
+    pub location: Location,
+}
+
+impl PackingSuggestion {
+    /// Slots saved by the suggested order.
+    pub fn savings(&self) -> usize {
+        self.slots - self.packed_slots
+    }
+}
+
+impl fmt::Display for PackingSuggestion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "state variables of `{}` use {} slots, declaring them as `{}` uses {} (saves {})",
+            self.contract,
+            self.slots,
+            self.order.join(", "),
+            self.packed_slots,
+            self.savings()
+        )
+    }
+}
+
+/// Contracts whose state variables would take fewer slots in another order.
+pub fn suggest_packing(table: &SymbolTable) -> Vec<PackingSuggestion> {
+    let mut suggestions = vec![];
+    for contract in &table.sub_tables {
+        if contract.table_type != SymbolTableType::Contract {
+            continue;
+        }
+        let resolve = |name: &str| declared_type(table, contract, name);
+        let variables = state_variables(contract, SymbolLocation::Storage);
+        let slots = StorageLayout::with_types(&variables, &resolve).slot_count;
+        let order = packed_order(&variables, &resolve);
+        let packed_slots = StorageLayout::with_types(&order, &resolve).slot_count;
+        if packed_slots < slots {
+            let location = table
+                .lookup(&contract.name)
+                .map_or_else(Location::default, |symbol| symbol.location);
+            suggestions.push(PackingSuggestion {
+                contract: contract.name.clone(),
+                order: order.into_iter().map(|(name, _)| name).collect(),
+                slots,
+                packed_slots,
+                location,
+            });
+        }
+    }
+    suggestions
+}
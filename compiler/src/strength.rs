@@ -0,0 +1,107 @@
+//! Strength reduction of multiplications, divisions and powers.
+//!
+//! - `x / 2^k` becomes `x >> k` when `x` is an unsigned integer, shifting
+//!   would round negative values down and divide `field` elements wrongly,
+//! - `x ** 2` becomes `x * x` when `x` is a symbol or a constant, so it isn't evaluated twice,
+//! - multiplying or dividing by 1 and raising to the power 1 are removed,
+//!   as is raising a symbol or a constant to the power 0.
+//!
+//! Multiplications by `2^k` are kept, a shift would drop their overflow check.
+//! Each multiplication removed saves a constraint in the R1CS backend.
+
+use crate::coercion::operand_bits;
+use crate::symbol::{Contract, Function, Operation, OperationType};
+use num_bigint::BigUint;
+use num_traits::{One, Zero};
+
+pub fn reduce_strength(contract: &mut Contract) -> usize {
+    contract
+        .functions
+        .iter_mut()
+        .map(reduce_function_strength)
+        .sum()
+}
+
+/// Reduces the operations of `function`, returning how many were rewritten.
+pub fn reduce_function_strength(function: &mut Function) -> usize {
+    let mut reduced = 0;
+    for operation in &mut function.operations {
+        reduce(operation, &mut reduced);
+    }
+    reduced
+}
+
+fn constant(operation: &Operation) -> Option<&BigUint> {
+    match &operation.operation {
+        OperationType::Constant { value } => Some(value),
+        _ => None,
+    }
+}
+
+fn new_constant(value: BigUint) -> Box<Operation> {
+    Box::new(Operation::new(OperationType::Constant { value }))
+}
+
+/// `k` if `operation` is the constant `2^k`.
+fn log2(operation: &Operation) -> Option<BigUint> {
+    let value = constant(operation)?;
+    if value.is_zero() || !(value & (value - BigUint::one())).is_zero() {
+        return None;
+    }
+    Some(BigUint::from(value.bits() - 1))
+}
+
+fn reduce(operation: &mut Operation, reduced: &mut usize) {
+    for child in operation.children_mut() {
+        reduce(child, reduced);
+    }
+    let placeholder = OperationType::Nop;
+    let op = match std::mem::replace(&mut operation.operation, placeholder) {
+        OperationType::Mul { left, right } => match (log2(&left), log2(&right)) {
+            (_, Some(k)) if k.is_zero() => left.operation,
+            (Some(k), _) if k.is_zero() => right.operation,
+            _ => {
+                operation.operation = OperationType::Mul { left, right };
+                return;
+            }
+        },
+        OperationType::Div { left, right } => match log2(&right) {
+            Some(k) if k.is_zero() => left.operation,
+            Some(k) if matches!(operand_bits(&left), Some((false, _))) => OperationType::Shr {
+                left,
+                right: new_constant(k),
+            },
+            _ => {
+                operation.operation = OperationType::Div { left, right };
+                return;
+            }
+        },
+        OperationType::Pow { left, right } => {
+            let exponent = constant(&right).cloned();
+            let is_simple = matches!(
+                left.operation,
+                OperationType::Symbol { .. } | OperationType::Constant { .. }
+            );
+            match exponent {
+                Some(e) if e.is_zero() && is_simple => OperationType::Constant {
+                    value: BigUint::one(),
+                },
+                Some(e) if e.is_one() => left.operation,
+                Some(e) if e == BigUint::from(2u32) && is_simple => OperationType::Mul {
+                    left: left.clone(),
+                    right: left,
+                },
+                _ => {
+                    operation.operation = OperationType::Pow { left, right };
+                    return;
+                }
+            }
+        }
+        op => {
+            operation.operation = op;
+            return;
+        }
+    };
+    operation.operation = op;
+    *reduced += 1;
+}
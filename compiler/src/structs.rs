@@ -0,0 +1,166 @@
+//! Struct literals.
+//!
+//! A struct is built either with named fields in any order, `Point({x: 1, y: 2})`,
+//! or with one value per field in order of declaration, `Point(1, 2)`. Both
+//! forms are checked against the declaration and normalized into a
+//! `StructLiteral` listing every field with its type and value.
+
+use crate::symbol::SymbolType;
+use std::fmt;
+use zoker_parser::ast::{Expression, ExpressionType};
+use zoker_parser::location::Location;
+
+/// Field of a struct literal with its declared type.
+#[derive(Debug, PartialEq)]
+pub struct StructLiteralField<'a> {
+    pub name: String,
+    pub field_type: SymbolType,
+    pub value: &'a Expression,
+}
+
+/// Struct literal with its values in order of declaration of the fields.
+#[derive(Debug, PartialEq)]
+pub struct StructLiteral<'a> {
+    pub name: String,
+    pub fields: Vec<StructLiteralField<'a>>,
+}
+
+impl StructLiteral<'_> {
+    pub fn symbol_type(&self) -> SymbolType {
+        SymbolType::UserDefined(self.name.clone())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum StructLiteralErrorKind {
+    /// Positional literal without exactly one value per field
+    Count {
+        expected: usize,
+        given: usize,
+    },
+    UnknownField(String),
+    DuplicateField(String),
+    /// Fields left out of a literal with named fields, in order of declaration
+    MissingFields(Vec<String>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StructLiteralError {
+    pub struct_name: String,
+    pub kind: StructLiteralErrorKind,
+    pub location: Location,
+}
+
+impl fmt::Display for StructLiteralError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.kind {
+            StructLiteralErrorKind::Count { expected, given } => write!(
+                f,
+                "`{}` has {} field(s) but {} value(s) were given",
+                self.struct_name, expected, given
+            ),
+            StructLiteralErrorKind::UnknownField(field) => {
+                write!(f, "`{}` has no field `{}`", self.struct_name, field)
+            }
+            StructLiteralErrorKind::DuplicateField(field) => {
+                write!(
+                    f,
+                    "field `{}` of `{}` is given twice",
+                    field, self.struct_name
+                )
+            }
+            StructLiteralErrorKind::MissingFields(fields) => {
+                let fields: Vec<String> = fields.iter().map(|f| format!("`{}`", f)).collect();
+                write!(
+                    f,
+                    "missing field(s) {} of `{}`",
+                    fields.join(", "),
+                    self.struct_name
+                )
+            }
+        }
+    }
+}
+
+/// Matches `expression`, a struct literal or a call of the struct, against `declaration`.
+/// `None` if `declaration` isn't a struct or `expression` doesn't build one.
+pub fn struct_literal<'a>(
+    declaration: &SymbolType,
+    expression: &'a Expression,
+) -> Option<Result<StructLiteral<'a>, StructLiteralError>> {
+    let (name, declared) = match declaration {
+        SymbolType::Struct { name, fields } => (name, fields),
+        _ => return None,
+    };
+    let error = |kind, location| {
+        Err(StructLiteralError {
+            struct_name: name.clone(),
+            kind,
+            location,
+        })
+    };
+    let field =
+        |(field, field_type): &(String, SymbolType), value: &'a Expression| StructLiteralField {
+            name: field.clone(),
+            field_type: field_type.clone(),
+            value,
+        };
+    let fields = match &expression.node {
+        ExpressionType::FunctionCallExpression { arguments, .. } => {
+            let arguments = match &arguments.node {
+                ExpressionType::Arguments { arguments } => arguments,
+                _ => return None,
+            };
+            if arguments.len() != declared.len() {
+                let kind = StructLiteralErrorKind::Count {
+                    expected: declared.len(),
+                    given: arguments.len(),
+                };
+                return Some(error(kind, expression.location));
+            }
+            declared
+                .iter()
+                .zip(arguments)
+                .map(|(declared, value)| field(declared, value))
+                .collect()
+        }
+        ExpressionType::StructLiteral { fields, .. } => {
+            let mut values: Vec<Option<&Expression>> = vec![None; declared.len()];
+            for value in fields {
+                let field_name = value.name.node.identifier_name().unwrap();
+                let index = match declared.iter().position(|(f, _)| *f == field_name) {
+                    Some(index) => index,
+                    None => {
+                        let kind = StructLiteralErrorKind::UnknownField(field_name);
+                        return Some(error(kind, value.name.location));
+                    }
+                };
+                if values[index].is_some() {
+                    let kind = StructLiteralErrorKind::DuplicateField(field_name);
+                    return Some(error(kind, value.name.location));
+                }
+                values[index] = Some(&value.value);
+            }
+            let missing: Vec<String> = declared
+                .iter()
+                .zip(&values)
+                .filter(|(_, value)| value.is_none())
+                .map(|((f, _), _)| f.clone())
+                .collect();
+            if !missing.is_empty() {
+                let kind = StructLiteralErrorKind::MissingFields(missing);
+                return Some(error(kind, expression.location));
+            }
+            declared
+                .iter()
+                .zip(values)
+                .map(|(declared, value)| field(declared, value.unwrap()))
+                .collect()
+        }
+        _ => return None,
+    };
+    Some(Ok(StructLiteral {
+        name: name.clone(),
+        fields,
+    }))
+}
@@ -1,45 +1,163 @@
+use crate::annotation::Annotation;
+use crate::builtin::BuiltinFunction;
+use crate::constant::evaluate_expression;
 use num_bigint::BigUint;
+use num_traits::ToPrimitive;
 use std::fmt;
-use zoker_parser::ast::{Specifier, Type};
+use zoker_parser::ast::{Expression, Specifier, Type};
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum SymbolType {
     Uint256,
     Int256,
+    /// Unsigned integer of the given width, narrower than 256 bits
+    Uint(u16),
+    /// Signed integer of the given width, narrower than 256 bits
+    Int(u16),
     String,
     Address,
     Bytes32,
+    Bytes,
     Bool,
+    /// Element of the scalar field of BN254, see `field::field_modulus`
+    Field,
+    Contract(String),
+    Namespace(String),
+    /// Module declared in the program, e.g. `math` of `math::clamp`
+    Module(String),
+    Array(Box<SymbolType>),
+    /// Array of a fixed length
+    FixedArray(Box<SymbolType>, usize),
+    /// Mapping from the key type to the value type, only in storage
+    Mapping(Box<SymbolType>, Box<SymbolType>),
+    /// Value of a type declared in the program, resolved by its name
+    UserDefined(String),
+    /// Declaration of an enum with its variants in order
+    Enum {
+        name: String,
+        variants: Vec<String>,
+    },
+    /// Declaration of a struct with its fields in order
+    Struct {
+        name: String,
+        fields: Vec<(String, SymbolType)>,
+    },
+    Function {
+        params: Vec<SymbolType>,
+        returns: Vec<SymbolType>,
+    },
     None,
 }
 
-pub fn token_to_type(typ: &Type) -> SymbolType {
-    match typ {
+impl SymbolType {
+    /// Whether a value of the type fits in a single word, without referring to other data.
+    pub fn is_value_type(&self) -> bool {
+        matches!(
+            self,
+            SymbolType::Uint256
+                | SymbolType::Int256
+                | SymbolType::Uint(_)
+                | SymbolType::Int(_)
+                | SymbolType::Address
+                | SymbolType::Bytes32
+                | SymbolType::Bool
+                | SymbolType::Field
+        )
+    }
+
+    /// Type of the getter of a public state variable of this type. It takes the
+    /// index or key of each array or mapping level, and returns the value reached.
+    pub fn getter_type(&self) -> SymbolType {
+        let mut params = vec![];
+        let mut value = self;
+        loop {
+            match value {
+                SymbolType::Array(element) | SymbolType::FixedArray(element, _) => {
+                    params.push(SymbolType::Uint256);
+                    value = element;
+                }
+                SymbolType::Mapping(key, element) => {
+                    params.push(*key.clone());
+                    value = element;
+                }
+                _ => break,
+            }
+        }
+        SymbolType::Function {
+            params,
+            returns: vec![value.clone()],
+        }
+    }
+}
+
+/// Type of `typ` with the lengths of its arrays evaluated, `constant` giving the value
+/// of declared constants. Fails with the length which isn't a compile-time constant.
+pub fn resolve_type<'a>(
+    typ: &'a Type,
+    constant: &dyn Fn(&str) -> Option<BigUint>,
+) -> Result<SymbolType, &'a Expression> {
+    Ok(match typ {
         Type::Uint256 => SymbolType::Uint256,
         Type::Int256 => SymbolType::Int256,
+        Type::Uint(bits) => SymbolType::Uint(*bits),
+        Type::Int(bits) => SymbolType::Int(*bits),
         Type::Bytes32 => SymbolType::Bytes32,
         Type::Bool => SymbolType::Bool,
-        Type::Bytes => SymbolType::Bytes32,
+        Type::Field => SymbolType::Field,
+        Type::Bytes => SymbolType::Bytes,
         Type::String => SymbolType::String,
         Type::Address => SymbolType::Address,
-    }
+        Type::Array(element) => SymbolType::Array(Box::new(resolve_type(element, constant)?)),
+        Type::FixedArray(element, length) => {
+            let element = resolve_type(element, constant)?;
+            let length = evaluate_expression(length, constant)
+                .and_then(|length| length.to_usize())
+                .ok_or(length.as_ref())?;
+            SymbolType::FixedArray(Box::new(element), length)
+        }
+        Type::UserDefined(name) => SymbolType::UserDefined(name.clone()),
+        Type::Mapping(key, value) => SymbolType::Mapping(
+            Box::new(resolve_type(key, constant)?),
+            Box::new(resolve_type(value, constant)?),
+        ),
+    })
 }
 
 pub fn symbol_to_string(typ: &SymbolType) -> &str {
     match typ {
         SymbolType::Uint256 => "uint",
         SymbolType::Int256 => "int",
+        SymbolType::Uint(_) => "uint",
+        SymbolType::Int(_) => "int",
         SymbolType::String => "string",
         SymbolType::Address => "address",
-        SymbolType::Bytes32 => "bytes",
+        SymbolType::Bytes32 => "bytes32",
+        SymbolType::Bytes => "bytes",
         SymbolType::Bool => "bool",
+        SymbolType::Field => "field",
+        SymbolType::Contract(name)
+        | SymbolType::Namespace(name)
+        | SymbolType::Module(name)
+        | SymbolType::UserDefined(name)
+        | SymbolType::Enum { name, .. }
+        | SymbolType::Struct { name, .. } => name.as_str(),
+        SymbolType::Array(_) | SymbolType::FixedArray(..) => "array",
+        SymbolType::Mapping(..) => "mapping",
+        SymbolType::Function { .. } => "function",
         SymbolType::None => "null",
     }
 }
 
 impl fmt::Display for SymbolType {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", symbol_to_string(self))
+        match self {
+            SymbolType::Array(element) => write!(f, "{}[]", element),
+            SymbolType::FixedArray(element, length) => write!(f, "{}[{}]", element, length),
+            SymbolType::Mapping(key, value) => write!(f, "mapping({} => {})", key, value),
+            SymbolType::Uint(bits) => write!(f, "uint{}", bits),
+            SymbolType::Int(bits) => write!(f, "int{}", bits),
+            _ => write!(f, "{}", symbol_to_string(self)),
+        }
     }
 }
 
@@ -47,6 +165,7 @@ impl fmt::Display for SymbolType {
 pub struct Contract {
     pub name: String,
     pub functions: Vec<Function>,
+    pub annotations: Vec<Annotation>,
 }
 
 impl Contract {
@@ -54,6 +173,7 @@ impl Contract {
         Contract {
             name,
             functions: vec![],
+            annotations: vec![],
         }
     }
 
@@ -77,6 +197,10 @@ pub struct Function {
     pub returns: Vec<Symbol>,
     pub private_num: u32,
     pub public_num: u32,
+    /// Declared outside contracts, only called internally
+    pub is_free: bool,
+    /// Annotations of the function, e.g. `@inline`
+    pub annotations: Vec<Annotation>,
 }
 
 impl Function {
@@ -88,6 +212,8 @@ impl Function {
             returns,
             private_num: 0,
             public_num: 0,
+            is_free: false,
+            annotations: vec![],
         }
     }
 
@@ -114,6 +240,12 @@ impl Operation {
         }
     }
 
+    pub fn new_builtin(function: BuiltinFunction, args: Vec<Operation>) -> Self {
+        Operation {
+            operation: OperationType::Builtin { function, args },
+        }
+    }
+
     pub fn new(operation: OperationType) -> Self {
         Operation { operation }
     }
@@ -124,6 +256,82 @@ impl Operation {
             _ => None,
         }
     }
+
+    /// Operands and nested statements, in evaluation order.
+    pub fn children(&self) -> Vec<&Operation> {
+        match &self.operation {
+            OperationType::Add { left, right }
+            | OperationType::Sub { left, right }
+            | OperationType::Mul { left, right }
+            | OperationType::Div { left, right }
+            | OperationType::Pow { left, right }
+            | OperationType::Shl { left, right }
+            | OperationType::Shr { left, right }
+            | OperationType::Assign { left, right } => vec![left, right],
+            OperationType::Index { array, index } | OperationType::BoundsCheck { array, index } => {
+                vec![array, index]
+            }
+            OperationType::For {
+                iter,
+                vector,
+                stmts,
+            } => {
+                let mut children = vec![iter.as_ref(), vector.as_ref()];
+                children.extend(stmts);
+                children
+            }
+            OperationType::If { cond, stmts } | OperationType::Else { cond, stmts } => {
+                let mut children = vec![cond.as_ref()];
+                children.extend(stmts);
+                children
+            }
+            OperationType::Return { ret } => vec![ret],
+            OperationType::Call { args, .. } | OperationType::Builtin { args, .. } => {
+                args.iter().collect()
+            }
+            OperationType::Symbol { .. } | OperationType::Constant { .. } | OperationType::Nop => {
+                vec![]
+            }
+        }
+    }
+
+    /// Mutable `children`.
+    pub fn children_mut(&mut self) -> Vec<&mut Operation> {
+        match &mut self.operation {
+            OperationType::Add { left, right }
+            | OperationType::Sub { left, right }
+            | OperationType::Mul { left, right }
+            | OperationType::Div { left, right }
+            | OperationType::Pow { left, right }
+            | OperationType::Shl { left, right }
+            | OperationType::Shr { left, right }
+            | OperationType::Assign { left, right } => vec![left, right],
+            OperationType::Index { array, index } | OperationType::BoundsCheck { array, index } => {
+                vec![array, index]
+            }
+            OperationType::For {
+                iter,
+                vector,
+                stmts,
+            } => {
+                let mut children = vec![iter.as_mut(), vector.as_mut()];
+                children.extend(stmts);
+                children
+            }
+            OperationType::If { cond, stmts } | OperationType::Else { cond, stmts } => {
+                let mut children = vec![cond.as_mut()];
+                children.extend(stmts);
+                children
+            }
+            OperationType::Return { ret } => vec![ret],
+            OperationType::Call { args, .. } | OperationType::Builtin { args, .. } => {
+                args.iter_mut().collect()
+            }
+            OperationType::Symbol { .. } | OperationType::Constant { .. } | OperationType::Nop => {
+                vec![]
+            }
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -140,6 +348,24 @@ pub enum OperationType {
         left: Box<Operation>,
         right: Box<Operation>,
     },
+    Div {
+        left: Box<Operation>,
+        right: Box<Operation>,
+    },
+    Pow {
+        left: Box<Operation>,
+        right: Box<Operation>,
+    },
+    /// `left` shifted left by `right` bits
+    Shl {
+        left: Box<Operation>,
+        right: Box<Operation>,
+    },
+    /// `left` shifted right by `right` bits
+    Shr {
+        left: Box<Operation>,
+        right: Box<Operation>,
+    },
     Assign {
         left: Box<Operation>,
         right: Box<Operation>,
@@ -164,6 +390,20 @@ pub enum OperationType {
         func: String,
         args: Vec<Operation>,
     },
+    Builtin {
+        function: BuiltinFunction,
+        args: Vec<Operation>,
+    },
+    /// Element of `array` at `index`
+    Index {
+        array: Box<Operation>,
+        index: Box<Operation>,
+    },
+    /// Reverts if `index` is out of the range of `array`
+    BoundsCheck {
+        array: Box<Operation>,
+        index: Box<Operation>,
+    },
     Symbol {
         symbol: Symbol,
     },
@@ -178,12 +418,22 @@ pub enum SymbolLocation {
     Unknown,
     Storage,
     Memory,
+    /// Storage cleared at the end of each transaction, read and written with `TLOAD` and `TSTORE`
+    Transient,
+}
+
+impl SymbolLocation {
+    /// Whether variables in the location are state of the contract rather than locals.
+    pub fn is_state(&self) -> bool {
+        matches!(self, SymbolLocation::Storage | SymbolLocation::Transient)
+    }
 }
 
 pub fn specifier_to_location(loc: &Specifier) -> SymbolLocation {
     match loc {
         Specifier::Memory => SymbolLocation::Memory,
         Specifier::Storage => SymbolLocation::Storage,
+        Specifier::Transient => SymbolLocation::Transient,
     }
 }
 
@@ -223,3 +473,51 @@ impl Symbol {
         }
     }
 }
+
+impl fmt::Display for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.name.is_empty() {
+            write!(f, "{}", self.symbol_type)
+        } else {
+            write!(f, "{}", self.name)
+        }
+    }
+}
+
+fn join(operations: &[Operation]) -> String {
+    operations
+        .iter()
+        .map(|op| op.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.operation {
+            OperationType::Add { left, right } => write!(f, "({} + {})", left, right),
+            OperationType::Sub { left, right } => write!(f, "({} - {})", left, right),
+            OperationType::Mul { left, right } => write!(f, "({} * {})", left, right),
+            OperationType::Div { left, right } => write!(f, "({} / {})", left, right),
+            OperationType::Pow { left, right } => write!(f, "({} ** {})", left, right),
+            OperationType::Shl { left, right } => write!(f, "({} << {})", left, right),
+            OperationType::Shr { left, right } => write!(f, "({} >> {})", left, right),
+            OperationType::Assign { left, right } => write!(f, "{} = {}", left, right),
+            OperationType::For { iter, vector, .. } => write!(f, "for {} in {}", iter, vector),
+            OperationType::If { cond, .. } => write!(f, "if {}", cond),
+            OperationType::Else { cond, .. } => write!(f, "else of {}", cond),
+            OperationType::Return { ret } => write!(f, "return {}", ret),
+            OperationType::Call { func, args } => write!(f, "{}({})", func, join(args)),
+            OperationType::Builtin { function, args } => {
+                write!(f, "{}({})", function.name(), join(args))
+            }
+            OperationType::Index { array, index } => write!(f, "{}[{}]", array, index),
+            OperationType::BoundsCheck { array, index } => {
+                write!(f, "check {}[{}]", array, index)
+            }
+            OperationType::Symbol { symbol } => write!(f, "{}", symbol),
+            OperationType::Constant { value } => write!(f, "{}", value),
+            OperationType::Nop => write!(f, "nop"),
+        }
+    }
+}
@@ -0,0 +1,3000 @@
+use crate::abi::{canonical_type, event_topic, signature};
+use crate::annotation::{check_annotations, Annotation, AnnotationError};
+use crate::builtin::{
+    builtin_functions, type_members, BuiltinFunction, StateAccess, BUILTIN_NAMESPACES,
+};
+use crate::coercion::{
+    common_type, fold_literal, implicit_conversion, integer_bits, literal_conversion, literal_type,
+    literal_value, string_literal_conversion, ConversionError, ConversionErrorKind,
+};
+use crate::constant::evaluate_expression;
+use crate::error::{SymbolTableError, SymbolTableErrorType};
+use crate::stdlib::{std_module, STD};
+use crate::structs::struct_literal;
+use crate::symbol::{resolve_type, specifier_to_location, SymbolLocation, SymbolType};
+use indexmap::map::IndexMap;
+use num_bigint::{BigInt, BigUint};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use zoker_parser::ast;
+use zoker_parser::ast::{ExpressionType, Operator, StatementType, Visibility};
+use zoker_parser::complexity::Node;
+use zoker_parser::location::Location;
+
+pub type SymbolTableResult<T> = Result<T, SymbolTableError>;
+
+pub fn make_symbol_table(program: &ast::Program) -> SymbolTableResult<SymbolTable> {
+    let table = build_symbol_table(program)?;
+    analyze_symbol_table(&table)?;
+    Ok(table)
+}
+
+/// Symbol table of programs compiled together, given as `(source name, program)`,
+/// whose declarations share the global scope.
+pub fn make_sources_symbol_table(
+    programs: &[(&str, &ast::Program)],
+) -> SymbolTableResult<SymbolTable> {
+    let table = build_sources_symbol_table(programs)?;
+    analyze_symbol_table(&table)?;
+    Ok(table)
+}
+
+/// Builds the symbol table without checking that used symbols are declared.
+pub fn build_symbol_table(program: &ast::Program) -> SymbolTableResult<SymbolTable> {
+    build_sources_symbol_table(&[("", program)])
+}
+
+/// Builds the symbol table of programs compiled together without checking that
+/// used symbols are declared.
+pub fn build_sources_symbol_table(
+    programs: &[(&str, &ast::Program)],
+) -> SymbolTableResult<SymbolTable> {
+    let mut builder = SymbolTableBuilder::new();
+    builder.prepare();
+    // Everything is declared first, so that sources can refer to those which follow them
+    for (source, ast::Program::GlobalStatements(statements)) in programs {
+        builder.declare_globals(source, statements)?;
+        builder.declare_members(statements)?;
+    }
+    for (_, ast::Program::GlobalStatements(statements)) in programs {
+        builder.scan_statements(statements)?;
+    }
+    let table = builder.finish();
+    check_mutability(&table)?;
+    Ok(table)
+}
+
+/// Checks that every used symbol is declared in its scope or an enclosing one.
+pub fn analyze_symbol_table(table: &SymbolTable) -> SymbolTableResult<()> {
+    SymbolAnalyzer::default().analyze_symbol_table(table)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SymbolTableType {
+    Global,
+    Contract,
+    /// Functions without bodies which the contracts implementing it must declare
+    Interface,
+    /// Functions without state, called through the library or attached to types with `using`
+    Library,
+    /// Functions, constants and modules referred to by paths, e.g. `math::clamp`
+    Module,
+    Function,
+    Local,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SymbolUsage {
+    Declared,
+    /// Declared in an enclosing scope and only read in this one
+    Read,
+    /// Declared in an enclosing scope and only assigned in this one
+    Written,
+    ReadWritten,
+}
+
+impl SymbolUsage {
+    pub fn is_read(&self) -> bool {
+        matches!(self, SymbolUsage::Read | SymbolUsage::ReadWritten)
+    }
+
+    pub fn is_written(&self) -> bool {
+        matches!(self, SymbolUsage::Written | SymbolUsage::ReadWritten)
+    }
+
+    /// Usage of a symbol used both ways.
+    fn merge(&self, other: &SymbolUsage) -> SymbolUsage {
+        match (
+            self.is_read() || other.is_read(),
+            self.is_written() || other.is_written(),
+        ) {
+            (true, true) => SymbolUsage::ReadWritten,
+            (false, true) => SymbolUsage::Written,
+            _ => SymbolUsage::Read,
+        }
+    }
+}
+
+/// What a symbol was declared as.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SymbolKind {
+    /// Function, type, contract, module or namespace
+    Item,
+    /// Parameter of a function
+    Parameter,
+    /// State variable, constant, local or loop iterator
+    Variable,
+}
+
+/// How a function interacts with the contract state, from least to most restrictive
+/// for the callers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum StateMutability {
+    /// Neither reads nor writes state
+    Pure,
+    /// Reads state
+    View,
+    /// Writes state
+    NonPayable,
+    /// Writes state and receives ether, only declared
+    Payable,
+}
+
+impl fmt::Display for StateMutability {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StateMutability::Pure => write!(f, "pure"),
+            StateMutability::View => write!(f, "view"),
+            StateMutability::NonPayable => write!(f, "nonpayable"),
+            StateMutability::Payable => write!(f, "payable"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Symbol {
+    pub name: String,
+    pub symbol_type: SymbolType,
+    pub usage: SymbolUsage,
+    /// `Item` for the uses of names declared in an enclosing scope
+    pub kind: SymbolKind,
+    pub data_location: SymbolLocation,
+    pub location: Location,
+    /// Value of a constant
+    pub constant: Option<BigUint>,
+    /// Declared with a leading `private`, a private input for parameters
+    pub is_private: bool,
+    /// Visibility of a function or state variable, `public` for any other symbol
+    pub visibility: Visibility,
+    /// State variable declared `public`, which callers read through its getter
+    pub has_getter: bool,
+    /// Annotations of a function or contract, e.g. `@inline`
+    pub annotations: Vec<Annotation>,
+}
+
+impl Symbol {
+    fn new(name: String, symbol_type: SymbolType, usage: SymbolUsage, location: Location) -> Self {
+        Symbol {
+            name,
+            symbol_type,
+            usage,
+            kind: SymbolKind::Item,
+            data_location: SymbolLocation::Unknown,
+            location,
+            constant: None,
+            is_private: false,
+            visibility: Visibility::Public,
+            has_getter: false,
+            annotations: vec![],
+        }
+    }
+
+    /// Type of the getter of a state variable declared `public`.
+    pub fn getter(&self) -> Option<SymbolType> {
+        if self.has_getter {
+            Some(self.symbol_type.getter_type())
+        } else {
+            None
+        }
+    }
+
+    /// Type of a function or getter of a contract, as called through a value of it.
+    pub fn callable_type(&self) -> Option<SymbolType> {
+        match &self.symbol_type {
+            SymbolType::Function { .. } => Some(self.symbol_type.clone()),
+            _ => self.getter(),
+        }
+    }
+}
+
+/// Library whose functions are attached to a type by `using`, e.g. `using SafeMath for uint256;`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UsingFor {
+    pub library: String,
+    pub target: SymbolType,
+    pub location: Location,
+}
+
+/// Event declared in a contract.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Event {
+    pub name: String,
+    pub params: Vec<SymbolType>,
+    /// Canonical signature, e.g. `Transfer(address,uint256)`
+    pub signature: String,
+    /// keccak256 hash of the signature
+    pub topic: [u8; 32],
+    pub location: Location,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SymbolTable {
+    pub name: String,
+    pub table_type: SymbolTableType,
+    pub symbols: IndexMap<String, Symbol>,
+    pub sub_tables: Vec<SymbolTable>,
+    /// Inferred state mutability of a function, including the functions it calls
+    pub mutability: Option<StateMutability>,
+    /// State mutability declared by a function, e.g. `pure`
+    pub declared_mutability: Option<StateMutability>,
+    /// Events declared in a contract, in order of declaration
+    pub events: Vec<Event>,
+    /// Libraries attached to types in a contract or library, in order of declaration
+    pub using: Vec<UsingFor>,
+}
+
+impl SymbolTable {
+    fn new(name: String, table_type: SymbolTableType) -> Self {
+        SymbolTable {
+            name,
+            table_type,
+            symbols: Default::default(),
+            sub_tables: vec![],
+            mutability: None,
+            declared_mutability: None,
+            events: vec![],
+            using: vec![],
+        }
+    }
+
+    pub fn lookup(&self, name: &str) -> Option<&Symbol> {
+        self.symbols.get(name)
+    }
+
+    /// Constructor of a contract, if it declares one.
+    pub fn constructor(&self) -> Option<&Symbol> {
+        self.lookup(ast::CONSTRUCTOR)
+    }
+
+    /// Symbols in order of their locations, whatever order the builder found them in.
+    /// Symbols of the root table built from several sources are in order within each source.
+    pub fn symbols_in_source_order(&self) -> Vec<&Symbol> {
+        let mut symbols: Vec<&Symbol> = self.symbols.values().collect();
+        // Stable, so the source order holds for symbols at the same location
+        symbols.sort_by_key(|symbol| (symbol.location.row(), symbol.location.column()));
+        symbols
+    }
+
+    /// Symbols in alphabetical order of their names.
+    pub fn symbols_sorted_by_name(&self) -> Vec<&Symbol> {
+        let mut symbols: Vec<&Symbol> = self.symbols.values().collect();
+        symbols.sort_by(|a, b| a.name.cmp(&b.name));
+        symbols
+    }
+
+    /// Parameters of a function table, in order.
+    pub fn parameters(&self) -> Vec<&Symbol> {
+        self.symbols
+            .values()
+            .filter(|symbol| symbol.kind == SymbolKind::Parameter)
+            .collect()
+    }
+
+    pub fn contract_table(&self, contract: &str) -> Option<&SymbolTable> {
+        self.sub_tables.iter().find(|table| {
+            matches!(
+                table.table_type,
+                SymbolTableType::Contract | SymbolTableType::Interface | SymbolTableType::Library
+            ) && table.name == contract
+        })
+    }
+
+    /// Table of the function `function` declared in the contract `contract`.
+    pub fn function_table(&self, contract: &str, function: &str) -> Option<&SymbolTable> {
+        self.contract_table(contract)?
+            .sub_tables
+            .iter()
+            .find(|table| table.table_type == SymbolTableType::Function && table.name == function)
+    }
+
+    pub fn function_mutability(&self, contract: &str, function: &str) -> Option<StateMutability> {
+        self.function_table(contract, function)?.mutability
+    }
+
+    /// Table of the module `module` declared in this table.
+    pub fn module_table(&self, module: &str) -> Option<&SymbolTable> {
+        self.sub_tables
+            .iter()
+            .find(|table| table.table_type == SymbolTableType::Module && table.name == module)
+    }
+
+    /// Declaration of `path` relative to this table, e.g. `["math", "clamp"]`.
+    pub fn lookup_path(&self, path: &[String]) -> Option<&Symbol> {
+        let (name, modules) = path.split_last()?;
+        let mut table = self;
+        for module in modules {
+            table = table.module_table(module)?;
+        }
+        table
+            .lookup(name)
+            .filter(|symbol| symbol.usage == SymbolUsage::Declared)
+    }
+}
+
+/// Declaration of `path` resolved from the innermost of `tables` declaring its
+/// first segment, e.g. the module `math` of `math::clamp`.
+pub(crate) fn resolve_path<'a>(tables: &[&'a SymbolTable], path: &[String]) -> Option<&'a Symbol> {
+    let first = path.first()?;
+    let table = tables.iter().rev().find(|table| {
+        table
+            .lookup(first)
+            .is_some_and(|symbol| symbol.usage == SymbolUsage::Declared)
+    })?;
+    table.lookup_path(path)
+}
+
+fn visibility_error(name: &str, location: Location) -> SymbolTableError {
+    SymbolTableError {
+        error: SymbolTableErrorType::TypeError(format!(
+            "`{}` can't have a visibility outside a contract",
+            name
+        )),
+        location,
+    }
+}
+
+/// `count` values, e.g. `2 values`.
+fn value_count(count: usize) -> String {
+    match count {
+        1 => String::from("1 value"),
+        count => format!("{} values", count),
+    }
+}
+
+fn annotation_error(err: AnnotationError) -> SymbolTableError {
+    SymbolTableError {
+        error: SymbolTableErrorType::TypeError(err.error),
+        location: err.location,
+    }
+}
+
+/// Function whose body is being scanned.
+struct FunctionScan {
+    /// Name of the function, for the errors of its `return` statements
+    name: String,
+    returns: Vec<SymbolType>,
+    mutability: StateMutability,
+    callees: Vec<String>,
+}
+
+/// Step of the traversal of the AST by `SymbolTableBuilder::scan`.
+enum Scan<'a> {
+    Statement(&'a ast::Statement),
+    Expression(&'a ast::Expression),
+    /// Body of a `for` loop, scanned in the scope of its iterator
+    ForEachBody(&'a ast::Expression),
+    /// Checks of a node once its children are scanned
+    ExitStatement(&'a ast::Statement),
+    ExitExpression(&'a ast::Expression),
+    LeaveScope,
+}
+
+struct SymbolTableBuilder {
+    tables: Vec<SymbolTable>,
+    function: Option<FunctionScan>,
+    /// Functions called by each function, per enclosing table
+    callees: HashMap<(String, String), Vec<String>>,
+    /// Values of the expressions scanned so far which are untyped integers
+    literal_values: HashMap<*const ast::Expression, Option<BigInt>>,
+    /// Types of the expressions scanned so far
+    expression_types: HashMap<*const ast::Expression, Option<SymbolType>>,
+    /// Usage of the variables assigned by the assignments being scanned
+    assigned: HashMap<*const ast::Expression, SymbolUsage>,
+    /// Source and location of the contracts and functions of the global scope
+    globals: HashMap<String, (String, Location)>,
+    /// Expressions whose value isn't used: expression statements and values of blocks
+    discarded: HashSet<*const ast::Expression>,
+}
+
+impl SymbolTableBuilder {
+    fn new() -> Self {
+        SymbolTableBuilder {
+            tables: vec![],
+            function: None,
+            callees: HashMap::new(),
+            literal_values: HashMap::new(),
+            expression_types: HashMap::new(),
+            assigned: HashMap::new(),
+            globals: HashMap::new(),
+            discarded: HashSet::new(),
+        }
+    }
+
+    fn prepare(&mut self) {
+        self.enter_scope("#Global", SymbolTableType::Global);
+        for namespace in BUILTIN_NAMESPACES {
+            self.register_name(
+                namespace,
+                SymbolType::Namespace(namespace.to_string()),
+                SymbolUsage::Declared,
+                Location::default(),
+            );
+        }
+        for builtin in builtin_functions() {
+            if builtin.namespace().is_some() {
+                continue;
+            }
+            self.register_name(
+                builtin.name(),
+                builtin.symbol_type(),
+                SymbolUsage::Declared,
+                Location::default(),
+            );
+        }
+    }
+
+    fn finish(mut self) -> SymbolTable {
+        assert_eq!(self.tables.len(), 1);
+        let mut table = self.tables.pop().unwrap();
+        // Functions of modules and contracts call each other across tables
+        while propagate_mutability(&mut table, &self.callees, &HashMap::new()) {}
+        table
+    }
+
+    fn enter_scope(&mut self, name: &str, table_type: SymbolTableType) {
+        self.tables
+            .push(SymbolTable::new(name.to_string(), table_type));
+    }
+
+    fn current_table(&mut self) -> &mut SymbolTable {
+        self.tables.last_mut().unwrap()
+    }
+
+    fn current_table_type(&self) -> SymbolTableType {
+        self.tables.last().unwrap().table_type.clone()
+    }
+
+    fn leave_scope(&mut self) {
+        let table = self.tables.pop().unwrap();
+        self.tables.last_mut().unwrap().sub_tables.push(table);
+    }
+
+    fn scan_statements(&mut self, statements: &[ast::Statement]) -> SymbolTableResult<()> {
+        for statement in statements {
+            self.scan_statement(statement)?;
+        }
+        Ok(())
+    }
+
+    fn scan_statement(&mut self, statement: &ast::Statement) -> SymbolTableResult<()> {
+        self.scan(Scan::Statement(statement))
+    }
+
+    /// Scans the AST from `root` depth-first, with a stack of steps instead of
+    /// recursion so that deeply nested programs don't overflow the call stack.
+    fn scan(&mut self, root: Scan) -> SymbolTableResult<()> {
+        let mut steps = vec![root];
+        while let Some(step) = steps.pop() {
+            let next = match step {
+                Scan::Statement(statement) => self.enter_statement(statement)?,
+                Scan::Expression(expression) => self.enter_expression(expression),
+                Scan::ForEachBody(expression) => self.enter_for_each_body(expression),
+                Scan::ExitStatement(statement) => {
+                    self.exit_statement(statement)?;
+                    vec![]
+                }
+                Scan::ExitExpression(expression) => {
+                    self.exit_expression(expression)?;
+                    vec![]
+                }
+                Scan::LeaveScope => {
+                    self.leave_scope();
+                    vec![]
+                }
+            };
+            // The first of the next steps is taken first
+            steps.extend(next.into_iter().rev());
+        }
+        Ok(())
+    }
+
+    /// Scans `statement` up to its children, giving the steps which follow in order.
+    fn enter_statement<'a>(
+        &mut self,
+        statement: &'a ast::Statement,
+    ) -> SymbolTableResult<Vec<Scan<'a>>> {
+        let steps = match &statement.node {
+            StatementType::FunctionStatement {
+                annotations,
+                function_name,
+                parameters,
+                statement: body,
+                returns,
+                visibility,
+                mutability,
+                ..
+            } => {
+                let name = function_name.node.identifier_name().unwrap();
+                let in_interface = self.current_table_type() == SymbolTableType::Interface;
+                if body.is_some() == in_interface {
+                    let error = if in_interface {
+                        format!(
+                            "function `{}` of interface `{}` can't have a body",
+                            name,
+                            self.current_table().name
+                        )
+                    } else {
+                        format!("function `{}` has no body", name)
+                    };
+                    return Err(SymbolTableError {
+                        error: SymbolTableErrorType::TypeError(error),
+                        location: function_name.location,
+                    });
+                }
+                self.declare_function(
+                    annotations,
+                    function_name,
+                    parameters,
+                    returns.as_deref(),
+                    *visibility,
+                )?;
+                let returns = match &self.current_table().symbols[&name].symbol_type {
+                    SymbolType::Function { returns, .. } => returns.clone(),
+                    _ => vec![],
+                };
+                self.enter_scope(&name, SymbolTableType::Function);
+                self.current_table().declared_mutability =
+                    mutability.map(|mutability| match mutability {
+                        ast::Mutability::Pure => StateMutability::Pure,
+                        ast::Mutability::View => StateMutability::View,
+                        ast::Mutability::Payable => StateMutability::Payable,
+                    });
+                self.function = Some(FunctionScan {
+                    name: name.clone(),
+                    returns,
+                    mutability: StateMutability::Pure,
+                    callees: vec![],
+                });
+                let mut steps = vec![Scan::Expression(parameters)];
+                steps.extend(body.as_deref().map(Scan::Statement));
+                steps.push(Scan::ExitStatement(statement));
+                steps
+            }
+            StatementType::ContractStatement {
+                annotations,
+                kind,
+                contract_name,
+                bases,
+                members,
+                ..
+            } => {
+                let name = contract_name.node.identifier_name().unwrap();
+                let annotations = check_annotations(annotations, true).map_err(annotation_error)?;
+                self.register_name(
+                    &name,
+                    SymbolType::Contract(name.clone()),
+                    SymbolUsage::Declared,
+                    contract_name.location,
+                );
+                self.current_table().symbols[&name].annotations = annotations;
+                let statements = match &members.node {
+                    StatementType::MemberStatement { statements } => statements.as_slice(),
+                    _ => &[],
+                };
+                let table_type = match kind {
+                    ast::ContractKind::Contract => SymbolTableType::Contract,
+                    ast::ContractKind::Interface => SymbolTableType::Interface,
+                    ast::ContractKind::Library => SymbolTableType::Library,
+                };
+                if *kind != ast::ContractKind::Contract {
+                    check_stateless(*kind, &name, bases, statements)?;
+                }
+                self.enter_scope(&name, table_type);
+                self.declare_members(statements)?;
+                for base in bases {
+                    self.check_implementation(base)?;
+                }
+                vec![Scan::Statement(members), Scan::LeaveScope]
+            }
+            StatementType::ModuleStatement {
+                module_name,
+                members,
+            } => {
+                let name = module_name.node.identifier_name().unwrap();
+                self.register_name(
+                    &name,
+                    SymbolType::Module(name.clone()),
+                    SymbolUsage::Declared,
+                    module_name.location,
+                );
+                self.enter_scope(&name, SymbolTableType::Module);
+                self.declare_members(members)?;
+                let mut steps: Vec<Scan> = members.iter().map(Scan::Statement).collect();
+                steps.push(Scan::LeaveScope);
+                steps
+            }
+            StatementType::EventStatement {
+                event_name,
+                parameters,
+            } => {
+                let name = event_name.node.identifier_name().unwrap();
+                let params = self.parameter_types(parameters)?;
+                let signature = signature(&name, &params);
+                if self
+                    .current_table()
+                    .events
+                    .iter()
+                    .any(|event| event.signature == signature)
+                {
+                    return Err(SymbolTableError {
+                        error: SymbolTableErrorType::DuplicateEvent(signature),
+                        location: event_name.location,
+                    });
+                }
+                let event = Event {
+                    name,
+                    params,
+                    topic: event_topic(&signature),
+                    signature,
+                    location: event_name.location,
+                };
+                self.current_table().events.push(event);
+                vec![]
+            }
+            StatementType::EnumStatement {
+                enum_name,
+                variants,
+            } => {
+                let name = enum_name.node.identifier_name().unwrap();
+                let mut names: Vec<String> = vec![];
+                for variant in variants {
+                    let variant_name = variant.node.identifier_name().unwrap();
+                    if names.contains(&variant_name) {
+                        return Err(SymbolTableError {
+                            error: SymbolTableErrorType::TypeError(format!(
+                                "variant `{}` of `{}` is declared twice",
+                                variant_name, name
+                            )),
+                            location: variant.location,
+                        });
+                    }
+                    names.push(variant_name);
+                }
+                self.register_name(
+                    &name,
+                    SymbolType::Enum {
+                        name: name.clone(),
+                        variants: names,
+                    },
+                    SymbolUsage::Declared,
+                    enum_name.location,
+                );
+                vec![]
+            }
+            StatementType::StructStatement {
+                struct_name,
+                fields,
+            } => {
+                let name = struct_name.node.identifier_name().unwrap();
+                let mut declared: Vec<(String, SymbolType)> = vec![];
+                for field in fields {
+                    let (variable_type, variable) = match &field.node {
+                        StatementType::InitializerStatement {
+                            variable_type,
+                            variable: Some(variable),
+                            default: None,
+                            ..
+                        } => (variable_type, variable),
+                        _ => {
+                            return Err(SymbolTableError {
+                                error: SymbolTableErrorType::TypeError(format!(
+                                    "fields of `{}` need a name and no default value",
+                                    name
+                                )),
+                                location: field.location,
+                            })
+                        }
+                    };
+                    let field_name = variable.node.identifier_name().unwrap();
+                    if declared.iter().any(|(declared, _)| *declared == field_name) {
+                        return Err(SymbolTableError {
+                            error: SymbolTableErrorType::TypeError(format!(
+                                "field `{}` of `{}` is declared twice",
+                                field_name, name
+                            )),
+                            location: variable.location,
+                        });
+                    }
+                    if let ast::Type::UserDefined(type_name) = variable_type {
+                        self.register_name(
+                            type_name,
+                            SymbolType::None,
+                            SymbolUsage::Read,
+                            field.location,
+                        );
+                    }
+                    declared.push((field_name, self.resolve_type(variable_type)?));
+                }
+                self.register_name(
+                    &name,
+                    SymbolType::Struct {
+                        name: name.clone(),
+                        fields: declared,
+                    },
+                    SymbolUsage::Declared,
+                    struct_name.location,
+                );
+                vec![]
+            }
+            StatementType::InitializerStatement {
+                variable_type,
+                default,
+                ..
+            } => {
+                if let ast::Type::UserDefined(type_name) = variable_type {
+                    self.register_name(
+                        type_name,
+                        SymbolType::None,
+                        SymbolUsage::Read,
+                        statement.location,
+                    );
+                }
+                let mut steps: Vec<Scan> = default
+                    .as_deref()
+                    .map(Scan::Expression)
+                    .into_iter()
+                    .collect();
+                steps.push(Scan::ExitStatement(statement));
+                steps
+            }
+            StatementType::CompoundStatement {
+                statements,
+                return_value,
+            } => {
+                self.enter_scope("#Compound", SymbolTableType::Local);
+                if let Some(value) = return_value {
+                    self.discarded.insert(&**value);
+                }
+                let mut steps: Vec<Scan> = statements.iter().map(Scan::Statement).collect();
+                steps.extend(return_value.as_deref().map(Scan::Expression));
+                steps.push(Scan::LeaveScope);
+                steps
+            }
+            StatementType::UncheckedStatement { statement } => vec![Scan::Statement(statement)],
+            // The condition is in the scope of the loop, not of its body
+            StatementType::DoWhileStatement {
+                statement: body,
+                condition,
+            } => {
+                self.enter_scope("#DoWhile", SymbolTableType::Local);
+                vec![
+                    Scan::Statement(body),
+                    Scan::Expression(condition),
+                    Scan::ExitStatement(statement),
+                    Scan::LeaveScope,
+                ]
+            }
+            // Declared with the other members, see `declare_members`
+            StatementType::ImportStatement { .. } | StatementType::UsingStatement { .. } => {
+                vec![]
+            }
+            // Items of a program not stripped by `strip_disabled` are left out
+            StatementType::ConditionalStatement { .. } => vec![],
+            StatementType::MemberStatement { statements } => {
+                statements.iter().map(Scan::Statement).collect()
+            }
+            StatementType::ReturnStatement { ret } => {
+                let mut steps: Vec<Scan> =
+                    ret.as_deref().map(Scan::Expression).into_iter().collect();
+                steps.push(Scan::ExitStatement(statement));
+                steps
+            }
+            StatementType::Expression { expression } => {
+                self.discarded.insert(&**expression);
+                vec![Scan::Expression(expression)]
+            }
+            // Placeholders of syntax errors are reported by the parser.
+            StatementType::Error => vec![],
+        };
+        Ok(steps)
+    }
+
+    /// Checks of `statement` once its children are scanned.
+    fn exit_statement(&mut self, statement: &ast::Statement) -> SymbolTableResult<()> {
+        match &statement.node {
+            StatementType::FunctionStatement { function_name, .. } => {
+                let name = function_name.node.identifier_name().unwrap();
+                let scan = self.function.take().unwrap();
+                // Functions of interfaces may do anything they don't declare otherwise
+                let table = self.current_table();
+                table.mutability = match statement.node {
+                    StatementType::FunctionStatement {
+                        statement: None, ..
+                    } => Some(
+                        table
+                            .declared_mutability
+                            .unwrap_or(StateMutability::NonPayable),
+                    ),
+                    _ => Some(scan.mutability),
+                };
+                let parent = self.tables[self.tables.len() - 2].name.clone();
+                self.callees.insert((parent, name), scan.callees);
+                self.leave_scope();
+            }
+            StatementType::InitializerStatement {
+                variable_type,
+                default,
+                ..
+            } => {
+                if let Some(default) = default {
+                    self.check_conversion(default, &self.resolve_type(variable_type)?)?;
+                }
+                self.declare_variable(statement)?;
+            }
+            StatementType::DoWhileStatement { condition, .. } => {
+                self.check_condition(condition, "while")?;
+            }
+            StatementType::ReturnStatement { ret } => {
+                self.check_return(ret.as_deref(), statement.location)?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Records the contracts and functions declared by `source` in the global scope,
+    /// which must not be declared by it or another source already.
+    fn declare_globals(
+        &mut self,
+        source: &str,
+        statements: &[ast::Statement],
+    ) -> SymbolTableResult<()> {
+        for statement in statements {
+            let name = match &statement.node {
+                StatementType::ContractStatement { contract_name, .. } => contract_name,
+                StatementType::FunctionStatement { function_name, .. } => function_name,
+                _ => continue,
+            };
+            let identifier = name.node.identifier_name().unwrap();
+            if let Some((first_source, first)) = self.globals.get(&identifier) {
+                return Err(SymbolTableError {
+                    error: SymbolTableErrorType::DuplicateDeclaration {
+                        name: identifier,
+                        first_source: first_source.clone(),
+                        first: *first,
+                        source: source.to_string(),
+                    },
+                    location: name.location,
+                });
+            }
+            self.globals
+                .insert(identifier, (source.to_string(), name.location));
+        }
+        Ok(())
+    }
+
+    /// Registers the declarations of a contract before its members are scanned, so
+    /// that members can refer to declarations which follow them.
+    fn declare_members(&mut self, members: &[ast::Statement]) -> SymbolTableResult<()> {
+        for member in members {
+            if let StatementType::ImportStatement { path } = &member.node {
+                self.import(path, member.location)?;
+            }
+        }
+        for member in members {
+            if let StatementType::EnumStatement { .. } = member.node {
+                self.scan_statement(member)?;
+            }
+        }
+        // Constants as soon as their value can be evaluated, array lengths may use them.
+        // Those which can't are reported when scanned.
+        let mut constants: Vec<&ast::Statement> = members
+            .iter()
+            .filter(|member| {
+                matches!(
+                    member.node,
+                    StatementType::InitializerStatement {
+                        is_constant: true,
+                        ..
+                    }
+                )
+            })
+            .collect();
+        loop {
+            let mut pending = vec![];
+            for constant in &constants {
+                match &constant.node {
+                    StatementType::InitializerStatement {
+                        default: Some(default),
+                        ..
+                    } if evaluate_expression(default, &|name| self.find_constant(name))
+                        .is_some() =>
+                    {
+                        self.declare_variable(constant)?
+                    }
+                    _ => pending.push(*constant),
+                }
+            }
+            if pending.len() == constants.len() {
+                break;
+            }
+            constants = pending;
+        }
+        for member in members {
+            if let StatementType::StructStatement { .. } = member.node {
+                self.scan_statement(member)?;
+            }
+        }
+        for member in members {
+            if let StatementType::StructStatement { struct_name, .. } = &member.node {
+                self.check_recursive_struct(struct_name)?;
+            }
+        }
+        for member in members {
+            if let StatementType::UsingStatement { library, target } = &member.node {
+                self.declare_using(library, target, member.location)?;
+            }
+        }
+        if let Some(duplicate) = members
+            .iter()
+            .filter(|member| member.node.is_constructor())
+            .nth(1)
+        {
+            return Err(SymbolTableError {
+                error: SymbolTableErrorType::TypeError(format!(
+                    "`{}` has more than one constructor",
+                    self.current_table().name
+                )),
+                location: duplicate.location,
+            });
+        }
+        for member in members {
+            match &member.node {
+                StatementType::FunctionStatement {
+                    annotations,
+                    function_name,
+                    parameters,
+                    returns,
+                    visibility,
+                    ..
+                } => self.declare_function(
+                    annotations,
+                    function_name,
+                    parameters,
+                    returns.as_deref(),
+                    *visibility,
+                )?,
+                StatementType::InitializerStatement {
+                    is_constant: false, ..
+                } => self.declare_variable(member)?,
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Attaches the functions of `library` to `target` in the current contract or library.
+    fn declare_using(
+        &mut self,
+        library: &ast::Expression,
+        target: &ast::Type,
+        location: Location,
+    ) -> SymbolTableResult<()> {
+        let name = library.node.identifier_name().unwrap();
+        let is_library = self.tables[..self.tables.len() - 1]
+            .iter()
+            .rev()
+            .find_map(|table| table.contract_table(&name))
+            .is_some_and(|table| table.table_type == SymbolTableType::Library);
+        if !is_library {
+            return Err(SymbolTableError {
+                error: SymbolTableErrorType::TypeError(format!(
+                    "`{}` is not a library declared before `{}`",
+                    name,
+                    self.current_table().name
+                )),
+                location: library.location,
+            });
+        }
+        self.register_name(&name, SymbolType::None, SymbolUsage::Read, library.location);
+        if let ast::Type::UserDefined(type_name) = target {
+            self.register_name(type_name, SymbolType::None, SymbolUsage::Read, location);
+        }
+        let target = self.resolve_type(target)?;
+        self.current_table().using.push(UsingFor {
+            library: name,
+            target,
+            location,
+        });
+        Ok(())
+    }
+
+    /// Declares the module of the standard library imported by `path` in the
+    /// module `std`, with the symbol table built when the module was loaded.
+    fn import(&mut self, path: &str, location: Location) -> SymbolTableResult<()> {
+        let module = std_module(path).ok_or_else(|| SymbolTableError {
+            error: SymbolTableErrorType::UnknownImport(path.to_string()),
+            location,
+        })?;
+        self.register_name(
+            STD,
+            SymbolType::Module(STD.to_string()),
+            SymbolUsage::Declared,
+            Location::default(),
+        );
+        let table = self.current_table();
+        if table.module_table(STD).is_none() {
+            table
+                .sub_tables
+                .push(SymbolTable::new(STD.to_string(), SymbolTableType::Module));
+        }
+        let std = table
+            .sub_tables
+            .iter_mut()
+            .find(|table| table.table_type == SymbolTableType::Module && table.name == STD)
+            .unwrap();
+        if std.module_table(module.name).is_none() {
+            let symbol = Symbol::new(
+                module.name.to_string(),
+                SymbolType::Module(module.name.to_string()),
+                SymbolUsage::Declared,
+                Location::default(),
+            );
+            std.symbols.insert(module.name.to_string(), symbol);
+            std.sub_tables.push(module.table.clone());
+        }
+        Ok(())
+    }
+
+    fn declare_function(
+        &mut self,
+        annotations: &[ast::Annotation],
+        function_name: &ast::Expression,
+        parameters: &ast::Expression,
+        returns: Option<&ast::Expression>,
+        visibility: Option<Visibility>,
+    ) -> SymbolTableResult<()> {
+        let name = function_name.node.identifier_name().unwrap();
+        if visibility.is_some()
+            && !matches!(
+                self.current_table_type(),
+                SymbolTableType::Contract | SymbolTableType::Interface | SymbolTableType::Library
+            )
+        {
+            return Err(visibility_error(&name, function_name.location));
+        }
+        let annotations = check_annotations(annotations, false).map_err(annotation_error)?;
+        let params = self.parameter_types(parameters)?;
+        if annotations.contains(&Annotation::Test) && !params.is_empty() {
+            return Err(SymbolTableError {
+                error: SymbolTableErrorType::TypeError(format!(
+                    "test function `{}` can't take parameters",
+                    name
+                )),
+                location: function_name.location,
+            });
+        }
+        let returns = match returns {
+            Some(returns) => self.parameter_types(returns)?,
+            None => vec![],
+        };
+        self.register_name(
+            &name,
+            SymbolType::Function { params, returns },
+            SymbolUsage::Declared,
+            function_name.location,
+        );
+        let symbol = &mut self.current_table().symbols[&name];
+        symbol.annotations = annotations;
+        symbol.visibility = visibility.unwrap_or(Visibility::Public);
+        Ok(())
+    }
+
+    /// Registers the variable of an initializer, with its value if it is a constant.
+    fn declare_variable(&mut self, statement: &ast::Statement) -> SymbolTableResult<()> {
+        let (variable_type, is_private, visibility, is_constant, data_location, variable, default) =
+            match &statement.node {
+                StatementType::InitializerStatement {
+                    variable_type,
+                    is_private,
+                    visibility,
+                    is_constant,
+                    data_location,
+                    variable: Some(variable),
+                    default,
+                    ..
+                } => (
+                    variable_type,
+                    *is_private,
+                    *visibility,
+                    *is_constant,
+                    data_location,
+                    variable,
+                    default,
+                ),
+                _ => return Ok(()),
+            };
+        let symbol_type = self.resolve_type(variable_type)?;
+        let name = variable.node.identifier_name().unwrap();
+        let table_type = self.current_table_type();
+        if visibility.is_some() && table_type != SymbolTableType::Contract {
+            return Err(visibility_error(&name, variable.location));
+        }
+        // State variables are never called, so only functions can be `external`
+        if visibility == Some(Visibility::External) {
+            return Err(SymbolTableError {
+                error: SymbolTableErrorType::TypeError(format!(
+                    "state variable `{}` can't be `external`",
+                    name
+                )),
+                location: variable.location,
+            });
+        }
+        if !is_constant
+            && (table_type == SymbolTableType::Global || table_type == SymbolTableType::Module)
+        {
+            return Err(SymbolTableError {
+                error: SymbolTableErrorType::TypeError(format!(
+                    "`{}` must be a constant to be declared outside contracts",
+                    name
+                )),
+                location: variable.location,
+            });
+        }
+        // Mappings only exist in storage, with all their values initially zero
+        if let SymbolType::Mapping(..) = symbol_type {
+            if table_type != SymbolTableType::Contract || is_constant || default.is_some() {
+                return Err(SymbolTableError {
+                    error: SymbolTableErrorType::TypeError(format!(
+                        "mapping `{}` can only be declared as a state variable without a value",
+                        name
+                    )),
+                    location: variable.location,
+                });
+            }
+        }
+        // Transient storage is cleared after each transaction, so there is no value to initialize
+        if data_location == &Some(ast::Specifier::Transient)
+            && (table_type != SymbolTableType::Contract
+                || is_constant
+                || default.is_some()
+                || !symbol_type.is_value_type())
+        {
+            return Err(SymbolTableError {
+                error: SymbolTableErrorType::TypeError(format!(
+                    "transient `{}` can only be declared as a state variable of a value type \
+                     without a value",
+                    name
+                )),
+                location: variable.location,
+            });
+        }
+        // State variables are initialized before any state is known
+        if table_type == SymbolTableType::Contract && !is_constant {
+            if let Some(read) = default
+                .as_ref()
+                .and_then(|default| self.state_read(default))
+            {
+                return Err(SymbolTableError {
+                    error: SymbolTableErrorType::TypeError(format!(
+                        "value of state variable `{}` reads state variable `{}`",
+                        name,
+                        read.node.identifier_name().unwrap()
+                    )),
+                    location: read.location,
+                });
+            }
+        }
+        let constant = if is_constant {
+            let value = default
+                .as_ref()
+                .and_then(|default| evaluate_expression(default, &|name| self.find_constant(name)));
+            match value {
+                Some(value) => Some(value),
+                None => {
+                    return Err(SymbolTableError {
+                        error: SymbolTableErrorType::TypeError(format!(
+                            "value of constant `{}` is not a compile-time constant",
+                            name
+                        )),
+                        location: variable.location,
+                    })
+                }
+            }
+        } else {
+            None
+        };
+        self.register_name(&name, symbol_type, SymbolUsage::Declared, variable.location);
+        // State variables always live in storage.
+        let data_location = match data_location {
+            Some(specifier) => specifier_to_location(specifier),
+            None if self.current_table_type() == SymbolTableType::Contract => {
+                SymbolLocation::Storage
+            }
+            None => SymbolLocation::Unknown,
+        };
+        // Parameters are the only variables declared in the scope of a function itself
+        let kind = if table_type == SymbolTableType::Function {
+            SymbolKind::Parameter
+        } else {
+            SymbolKind::Variable
+        };
+        let symbol = &mut self.current_table().symbols[&name];
+        symbol.kind = kind;
+        symbol.constant = constant;
+        symbol.is_private = is_private;
+        // A leading `private` already hides a state variable from other contracts
+        symbol.visibility = match visibility {
+            Some(visibility) => visibility,
+            None if is_private => Visibility::Private,
+            None => Visibility::Public,
+        };
+        symbol.has_getter =
+            table_type == SymbolTableType::Contract && visibility == Some(Visibility::Public);
+        symbol.data_location = data_location;
+        Ok(())
+    }
+
+    /// Scans `expression` up to its children, giving the steps which follow in order.
+    fn enter_expression<'a>(&mut self, expression: &'a ast::Expression) -> Vec<Scan<'a>> {
+        let mut steps = match &expression.node {
+            ExpressionType::AssignExpression {
+                left,
+                operator,
+                right,
+            } => {
+                let usage = if *operator == Operator::Assign {
+                    SymbolUsage::Written
+                } else {
+                    SymbolUsage::ReadWritten
+                };
+                if let ExpressionType::Tuple { items } = &left.node {
+                    let targets: Vec<&ast::Expression> = destructured(items).flatten().collect();
+                    let mut steps = vec![];
+                    for target in targets {
+                        self.assigned
+                            .insert(assigned_variable(target), usage.clone());
+                        steps.push(Scan::Expression(target));
+                    }
+                    steps.push(Scan::Expression(right));
+                    steps
+                } else {
+                    self.assigned.insert(assigned_variable(left), usage);
+                    vec![Scan::Expression(left), Scan::Expression(right)]
+                }
+            }
+            ExpressionType::BinaryExpression { left, right, .. } => {
+                vec![Scan::Expression(left), Scan::Expression(right)]
+            }
+            ExpressionType::TernaryExpression {
+                condition,
+                expr1,
+                expr2,
+            } => vec![
+                Scan::Expression(condition),
+                Scan::Expression(expr1),
+                Scan::Expression(expr2),
+            ],
+            ExpressionType::FunctionCallExpression {
+                function_name,
+                arguments,
+            } => vec![Scan::Expression(function_name), Scan::Expression(arguments)],
+            ExpressionType::StructLiteral {
+                struct_name,
+                fields,
+            } => {
+                let mut steps = vec![Scan::Expression(struct_name)];
+                steps.extend(fields.iter().map(|field| Scan::Expression(&field.value)));
+                steps
+            }
+            ExpressionType::IfExpression {
+                condition,
+                if_statement,
+                else_statement,
+            } => {
+                let mut steps = vec![Scan::Expression(condition), Scan::Statement(if_statement)];
+                steps.extend(else_statement.as_deref().map(Scan::Statement));
+                steps
+            }
+            ExpressionType::MatchExpression { value, arms } => {
+                let mut steps = vec![Scan::Expression(value)];
+                for arm in arms {
+                    if !arm.is_wildcard() {
+                        steps.push(Scan::Expression(&arm.pattern));
+                    }
+                    steps.push(Scan::Statement(&arm.statement));
+                }
+                steps
+            }
+            ExpressionType::ForEachExpression { vector, .. } => {
+                vec![Scan::Expression(vector), Scan::ForEachBody(expression)]
+            }
+            ExpressionType::UnaryExpression {
+                operator,
+                expression: operand,
+            } => {
+                if matches!(
+                    operator,
+                    Operator::PrefixPlusPlus
+                        | Operator::PrefixMinusMinus
+                        | Operator::PostfixPlusPlus
+                        | Operator::PostfixMinusMinus
+                ) {
+                    self.assigned
+                        .insert(assigned_variable(operand), SymbolUsage::ReadWritten);
+                }
+                vec![Scan::Expression(operand)]
+            }
+            ExpressionType::Parameters { parameters } => {
+                parameters.iter().map(Scan::Statement).collect()
+            }
+            ExpressionType::Arguments { arguments } => {
+                arguments.iter().map(Scan::Expression).collect()
+            }
+            ExpressionType::Tuple { items } => {
+                items.iter().flatten().map(Scan::Expression).collect()
+            }
+            ExpressionType::MemberExpression {
+                expression: base, ..
+            } => vec![Scan::Expression(base)],
+            ExpressionType::IndexExpression {
+                expression: base,
+                index,
+            } => vec![Scan::Expression(base), Scan::Expression(index)],
+            ExpressionType::Number { .. }
+            | ExpressionType::StringLiteral { .. }
+            | ExpressionType::TypeList { .. }
+            | ExpressionType::Error => vec![],
+            // Resolved once the modules are all declared, see `SymbolAnalyzer`
+            ExpressionType::PathExpression { .. } => {
+                let path = expression.node.module_path().unwrap_or_default();
+                self.register_name(
+                    &path.join("::"),
+                    SymbolType::None,
+                    SymbolUsage::Read,
+                    expression.location,
+                );
+                vec![]
+            }
+            ExpressionType::Identifier { value } => {
+                let key: *const ast::Expression = expression;
+                let usage = self.assigned.remove(&key).unwrap_or(SymbolUsage::Read);
+                self.register_name(value, SymbolType::None, usage, expression.location);
+                if self.is_state(expression) {
+                    self.mark_mutability(StateMutability::View);
+                }
+                vec![]
+            }
+        };
+        steps.push(Scan::ExitExpression(expression));
+        steps
+    }
+
+    /// Enters the scope of the iterator of a `for` loop once its vector is scanned.
+    fn enter_for_each_body<'a>(&mut self, expression: &'a ast::Expression) -> Vec<Scan<'a>> {
+        let (iterator, statement, else_statement) = match &expression.node {
+            ExpressionType::ForEachExpression {
+                iterator,
+                statement,
+                else_statement,
+                ..
+            } => (iterator, statement, else_statement),
+            _ => return vec![],
+        };
+        self.enter_scope("#ForEach", SymbolTableType::Local);
+        let name = iterator.node.identifier_name().unwrap();
+        self.register_name(
+            &name,
+            SymbolType::Uint256,
+            SymbolUsage::Declared,
+            iterator.location,
+        );
+        self.current_table().symbols[&name].kind = SymbolKind::Variable;
+        let mut steps = vec![Scan::Statement(statement), Scan::LeaveScope];
+        steps.extend(else_statement.as_deref().map(Scan::Statement));
+        steps
+    }
+
+    /// Checks of `expression` once its children are scanned. Its value and type are
+    /// then recorded, so that the checks of the enclosing expression don't walk it again.
+    fn exit_expression(&mut self, expression: &ast::Expression) -> SymbolTableResult<()> {
+        match &expression.node {
+            ExpressionType::AssignExpression {
+                left,
+                operator,
+                right,
+            } => {
+                if let ExpressionType::Tuple { items } = &left.node {
+                    return self.check_destructuring(items, right);
+                }
+                self.check_assignable(left)?;
+                self.check_state_write(left);
+                if *operator == Operator::Assign {
+                    if let Some(target) = self.expression_type(left) {
+                        self.check_conversion(right, &target)?;
+                    }
+                }
+            }
+            ExpressionType::BinaryExpression {
+                left,
+                operator,
+                right,
+            } => self.check_operands(left, operator, right, expression.location)?,
+            ExpressionType::FunctionCallExpression { function_name, .. }
+                if self.resolve_struct(function_name).is_some() =>
+            {
+                self.check_struct_literal(function_name, expression)?;
+            }
+            ExpressionType::StructLiteral { struct_name, .. } => {
+                self.check_struct_literal(struct_name, expression)?;
+            }
+            ExpressionType::FunctionCallExpression {
+                function_name,
+                arguments,
+            } => {
+                self.check_internal_call(function_name)?;
+                self.check_call(function_name, arguments)?;
+                self.record_call(function_name);
+            }
+            ExpressionType::MatchExpression { value, arms } => {
+                self.check_match(value, arms, expression.location)?;
+            }
+            ExpressionType::IfExpression {
+                condition,
+                if_statement,
+                else_statement,
+            } => {
+                self.check_condition(condition, "if")?;
+                match else_statement {
+                    Some(else_statement) => {
+                        if let (Some(first), Some(second)) =
+                            (block_value(if_statement), block_value(else_statement))
+                        {
+                            self.check_branches(first, second, "if")?;
+                        }
+                    }
+                    None if !self.discarded.contains(&(expression as *const _)) => {
+                        return Err(SymbolTableError {
+                            error: SymbolTableErrorType::TypeError(String::from(
+                                "`if` used as a value must have an `else` branch",
+                            )),
+                            location: expression.location,
+                        });
+                    }
+                    None => {}
+                }
+            }
+            ExpressionType::TernaryExpression {
+                condition,
+                expr1,
+                expr2,
+            } => {
+                self.check_condition(condition, "ternary")?;
+                self.check_branches(expr1, expr2, "ternary")?;
+            }
+            ExpressionType::ForEachExpression { vector, .. } => {
+                match self.expression_type(vector) {
+                    Some(SymbolType::Array(_)) | Some(SymbolType::FixedArray(..)) | None => {}
+                    Some(vector_type) => {
+                        return Err(SymbolTableError {
+                            error: SymbolTableErrorType::TypeError(format!(
+                                "`for` loop can't iterate over `{}`, which is not an array",
+                                vector_type
+                            )),
+                            location: vector.location,
+                        })
+                    }
+                }
+            }
+            ExpressionType::UnaryExpression {
+                operator:
+                    Operator::PrefixPlusPlus
+                    | Operator::PrefixMinusMinus
+                    | Operator::PostfixPlusPlus
+                    | Operator::PostfixMinusMinus,
+                expression: operand,
+            } => {
+                self.check_assignable(operand)?;
+                self.check_state_write(operand);
+            }
+            ExpressionType::MemberExpression {
+                expression: base,
+                member,
+            } => {
+                self.check_member_access(base, member)?;
+                self.record_member_access(base, member);
+            }
+            ExpressionType::IndexExpression {
+                expression: base, ..
+            } => match self.expression_type(base) {
+                Some(SymbolType::Array(_))
+                | Some(SymbolType::FixedArray(..))
+                | Some(SymbolType::Mapping(..))
+                | None => {}
+                Some(base_type) => {
+                    return Err(SymbolTableError {
+                        error: SymbolTableErrorType::TypeError(format!(
+                            "`{}` cannot be indexed",
+                            base_type
+                        )),
+                        location: expression.location,
+                    })
+                }
+            },
+            _ => {}
+        }
+        let key: *const ast::Expression = expression;
+        let value = fold_literal(expression, |operand| self.literal_value(operand));
+        self.literal_values.insert(key, value);
+        let symbol_type = self.expression_type(expression);
+        self.expression_types.insert(key, symbol_type);
+        Ok(())
+    }
+
+    /// Checks that the condition of an `if`, a ternary expression or a loop is a `bool`.
+    fn check_condition(
+        &self,
+        condition: &ast::Expression,
+        construct: &str,
+    ) -> SymbolTableResult<()> {
+        match self.expression_type(condition) {
+            Some(SymbolType::Bool) | None => Ok(()),
+            Some(condition_type) => Err(SymbolTableError {
+                error: SymbolTableErrorType::TypeError(format!(
+                    "condition of `{}` must be `bool`, found `{}`",
+                    construct, condition_type
+                )),
+                location: condition.location,
+            }),
+        }
+    }
+
+    /// Checks that `return` gives as many values as the enclosing function declares,
+    /// each convertible to its declared type. A call to a function returning several
+    /// values forwards them, e.g. `return swap(a, b);`.
+    fn check_return(
+        &self,
+        ret: Option<&ast::Expression>,
+        location: Location,
+    ) -> SymbolTableResult<()> {
+        let function = match &self.function {
+            Some(function) => function,
+            None => return Ok(()),
+        };
+        let values: Vec<Option<&ast::Expression>> = match ret {
+            None => vec![],
+            Some(ast::Expression {
+                node: ExpressionType::Tuple { items },
+                ..
+            }) => items.iter().map(Option::as_ref).collect(),
+            Some(value) => match self.returned_types(value) {
+                Some(returns) if returns.len() != 1 => vec![None; returns.len()],
+                _ => vec![Some(value)],
+            },
+        };
+        if values.len() != function.returns.len() {
+            return Err(SymbolTableError {
+                error: SymbolTableErrorType::TypeError(format!(
+                    "`{}` returns {} but `return` gives {}",
+                    function.name,
+                    value_count(function.returns.len()),
+                    value_count(values.len())
+                )),
+                location: ret.map_or(location, |ret| ret.location),
+            });
+        }
+        for (value, to) in values.iter().zip(&function.returns) {
+            if let Some(value) = value {
+                self.check_conversion(value, to)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks the destructuring of `right` into the targets `items`, where `_` and
+    /// empty items ignore their value. `right` is either a tuple or a call to a
+    /// declared function, whose values must match the targets in number and type.
+    fn check_destructuring(
+        &mut self,
+        items: &[Option<ast::Expression>],
+        right: &ast::Expression,
+    ) -> SymbolTableResult<()> {
+        let targets: Vec<Option<&ast::Expression>> = destructured(items).collect();
+        for target in targets.iter().flatten() {
+            if !matches!(
+                target.node,
+                ExpressionType::Identifier { .. }
+                    | ExpressionType::IndexExpression { .. }
+                    | ExpressionType::MemberExpression { .. }
+            ) {
+                return Err(SymbolTableError {
+                    error: SymbolTableErrorType::TypeError(String::from(
+                        "destructuring can only assign to variables and their elements",
+                    )),
+                    location: target.location,
+                });
+            }
+            self.check_assignable(target)?;
+            self.check_state_write(target);
+        }
+
+        let mismatch = |given: String| SymbolTableError {
+            error: SymbolTableErrorType::TypeError(format!(
+                "{} but destructuring takes {}",
+                given,
+                value_count(targets.len())
+            )),
+            location: right.location,
+        };
+        if let ExpressionType::Tuple { items: values } = &right.node {
+            if values.len() != targets.len() {
+                return Err(mismatch(format!("tuple has {}", value_count(values.len()))));
+            }
+            for (target, value) in targets.iter().zip(values) {
+                if let (Some(target), Some(value)) = (target, value) {
+                    if let Some(to) = self.expression_type(target) {
+                        self.check_conversion(value, &to)?;
+                    }
+                }
+            }
+            return Ok(());
+        }
+        let returns = match self.returned_types(right) {
+            Some(returns) => returns,
+            None => {
+                return Err(SymbolTableError {
+                    error: SymbolTableErrorType::TypeError(String::from(
+                        "only a tuple or a call to a declared function can be destructured",
+                    )),
+                    location: right.location,
+                })
+            }
+        };
+        if returns.len() != targets.len() {
+            let callee = match &right.node {
+                ExpressionType::FunctionCallExpression { function_name, .. } => {
+                    function_name.node.path_name()
+                }
+                _ => None,
+            };
+            return Err(mismatch(format!(
+                "`{}` returns {}",
+                callee.unwrap_or_default(),
+                value_count(returns.len())
+            )));
+        }
+        for (target, from) in targets.iter().zip(&returns) {
+            if let Some(target) = target {
+                if let Some(to) = self.expression_type(target) {
+                    implicit_conversion(from, &to).map_err(|error| SymbolTableError {
+                        error: SymbolTableErrorType::TypeError(error.to_string()),
+                        location: target.location,
+                    })?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks that the contract being declared implements the interface `base`: it
+    /// declares each function of the interface, with the same parameter and return
+    /// types, callable by other contracts. Getters of public state variables count.
+    fn check_implementation(&self, base: &ast::Expression) -> SymbolTableResult<()> {
+        let contract = self.tables.last().unwrap();
+        let name = base.node.identifier_name().unwrap();
+        let error = |message: String| SymbolTableError {
+            error: SymbolTableErrorType::TypeError(message),
+            location: base.location,
+        };
+        let interface = self.tables[..self.tables.len() - 1]
+            .iter()
+            .rev()
+            .find_map(|table| table.contract_table(&name))
+            .filter(|table| table.table_type == SymbolTableType::Interface)
+            .ok_or_else(|| {
+                error(format!(
+                    "`{}` is not an interface declared before `{}`",
+                    name, contract.name
+                ))
+            })?;
+        for function in interface.symbols.values() {
+            let (params, returns) = match &function.symbol_type {
+                SymbolType::Function { params, returns } => (params, returns),
+                _ => continue,
+            };
+            let declaration = format!(
+                "{} returns ({})",
+                signature(&function.name, params),
+                returns
+                    .iter()
+                    .map(canonical_type)
+                    .collect::<Vec<_>>()
+                    .join(",")
+            );
+            let implementation = contract
+                .lookup(&function.name)
+                .filter(|symbol| symbol.usage == SymbolUsage::Declared);
+            let symbol = match implementation {
+                Some(symbol) => symbol,
+                None => {
+                    return Err(error(format!(
+                        "`{}` doesn't implement `{}` of `{}`",
+                        contract.name, declaration, name
+                    )))
+                }
+            };
+            if symbol.callable_type().as_ref() != Some(&function.symbol_type) {
+                return Err(error(format!(
+                    "`{}.{}` doesn't match `{}` of `{}`",
+                    contract.name, function.name, declaration, name
+                )));
+            }
+            if matches!(
+                symbol.visibility,
+                Visibility::Private | Visibility::Internal
+            ) {
+                return Err(error(format!(
+                    "`{}.{}` is {} but implements a function of `{}`",
+                    contract.name, function.name, symbol.visibility, name
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Types of the values returned by `expression` if it calls a declared function.
+    fn returned_types(&self, expression: &ast::Expression) -> Option<Vec<SymbolType>> {
+        let function_name = match &expression.node {
+            ExpressionType::FunctionCallExpression { function_name, .. } => function_name,
+            _ => return None,
+        };
+        let function = match self
+            .resolve_contract_member(function_name)
+            .or_else(|| self.resolve_attached(function_name))
+        {
+            Some((_, symbol)) => symbol.callable_type()?,
+            None => self
+                .find_symbol(&function_name.node.identifier_name()?)?
+                .symbol_type
+                .clone(),
+        };
+        match function {
+            SymbolType::Function { returns, .. } => Some(returns),
+            _ => None,
+        }
+    }
+
+    /// Checks that the values of the branches of an `if` or ternary expression have a
+    /// common type. The error is at the second branch.
+    fn check_branches(
+        &self,
+        first: &ast::Expression,
+        second: &ast::Expression,
+        construct: &str,
+    ) -> SymbolTableResult<()> {
+        // An untyped integer adopts the type of the other branch
+        for (literal, other) in [(first, second), (second, first)] {
+            if let (Some(value), None) = (self.literal_value(literal), self.literal_value(other)) {
+                if let Some(other_type) = self.expression_type(other) {
+                    self.check_literal(&value, &other_type, literal.location)?;
+                }
+                return Ok(());
+            }
+        }
+        if let (Some(first_type), Some(second_type)) =
+            (self.expression_type(first), self.expression_type(second))
+        {
+            if common_type(&first_type, &second_type).is_err() {
+                return Err(SymbolTableError {
+                    error: SymbolTableErrorType::TypeError(format!(
+                        "branches of `{}` have different types `{}` and `{}`",
+                        construct, first_type, second_type
+                    )),
+                    location: second.location,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Type of the value of an `if` or ternary expression, the common type of its branches.
+    fn branch_type(&self, first: &ast::Expression, second: &ast::Expression) -> Option<SymbolType> {
+        match (self.literal_value(first), self.literal_value(second)) {
+            (Some(_), Some(_)) => None,
+            (Some(_), None) => self.expression_type(second),
+            (None, Some(_)) => self.expression_type(first),
+            (None, None) => common_type(
+                &self.expression_type(first)?,
+                &self.expression_type(second)?,
+            )
+            .ok(),
+        }
+    }
+
+    /// Checks that the operands of a binary operation mix.
+    fn check_operands(
+        &self,
+        left: &ast::Expression,
+        operator: &Operator,
+        right: &ast::Expression,
+        location: Location,
+    ) -> SymbolTableResult<()> {
+        if matches!(operator, Operator::LShift | Operator::RShift) {
+            return Ok(());
+        }
+        // Untyped integers compare as integers of any size, e.g. `1 - 2 < 3`
+        if self.literal_value(left).is_some() && self.literal_value(right).is_some() {
+            return Ok(());
+        }
+        // An untyped integer adopts the type of the other operand
+        for (literal, other) in [(left, right), (right, left)] {
+            if let (Some(value), None) = (self.literal_value(literal), self.literal_value(other)) {
+                match self.expression_type(other) {
+                    Some(other_type)
+                        if is_integer(&other_type) || other_type == SymbolType::Field =>
+                    {
+                        self.check_literal(&value, &other_type, literal.location)?;
+                    }
+                    _ => {}
+                }
+                return Ok(());
+            }
+        }
+        if let (Some(left_type), Some(right_type)) =
+            (self.expression_type(left), self.expression_type(right))
+        {
+            match common_type(&left_type, &right_type) {
+                Err(error)
+                    if matches!(
+                        error.kind,
+                        ConversionErrorKind::Signedness | ConversionErrorKind::Field
+                    ) =>
+                {
+                    return Err(SymbolTableError {
+                        error: SymbolTableErrorType::TypeError(format!(
+                            "operands of types `{}` and `{}` don't mix: {}",
+                            left_type,
+                            right_type,
+                            error.reason().unwrap()
+                        )),
+                        location,
+                    });
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn mark_mutability(&mut self, mutability: StateMutability) {
+        if let Some(function) = &mut self.function {
+            function.mutability = function.mutability.max(mutability);
+        }
+    }
+
+    /// First state variable read by `expression` which isn't a constant, if any.
+    fn state_read<'e>(&self, expression: &'e ast::Expression) -> Option<&'e ast::Expression> {
+        let mut pending = vec![Node::Expression(expression)];
+        while let Some(node) = pending.pop() {
+            match node {
+                Node::Expression(
+                    identifier @ ast::Expression {
+                        node: ExpressionType::Identifier { value },
+                        ..
+                    },
+                ) => {
+                    let symbol = self.find_symbol(value);
+                    if symbol.is_some_and(|symbol| {
+                        symbol.data_location.is_state() && symbol.constant.is_none()
+                    }) {
+                        return Some(identifier);
+                    }
+                }
+                // The member is a name of the base, not a variable
+                Node::Expression(ast::Expression {
+                    node: ExpressionType::MemberExpression { expression, .. },
+                    ..
+                }) => pending.push(Node::Expression(expression)),
+                _ => pending.extend(node.children().into_iter().rev()),
+            }
+        }
+        None
+    }
+
+    /// Whether `expression` is a storage variable or an element or member of one.
+    fn is_state(&self, expression: &ast::Expression) -> bool {
+        match &expression.node {
+            ExpressionType::IndexExpression {
+                expression: base, ..
+            }
+            | ExpressionType::MemberExpression {
+                expression: base, ..
+            } => self.is_state(base),
+            _ => self
+                .expression_symbol(expression)
+                .is_some_and(|symbol| symbol.data_location.is_state()),
+        }
+    }
+
+    /// Rejects writes to constants.
+    fn check_assignable(&self, target: &ast::Expression) -> SymbolTableResult<()> {
+        match self.expression_symbol(target) {
+            Some(symbol) if symbol.constant.is_some() => Err(SymbolTableError {
+                error: SymbolTableErrorType::TypeError(format!(
+                    "cannot assign to constant `{}`",
+                    symbol.name
+                )),
+                location: target.location,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    fn check_state_write(&mut self, target: &ast::Expression) {
+        if self.is_state(target) {
+            self.mark_mutability(StateMutability::NonPayable);
+        }
+    }
+
+    /// Records the callee of a call, or its state access if it is a builtin or
+    /// a function of another contract.
+    fn record_call(&mut self, function_name: &ast::Expression) {
+        if let Some(builtin) = self.resolve_builtin(function_name) {
+            self.mark_state_access(builtin.state_access());
+        } else if let Some((contract, function)) = self
+            .resolve_contract_member(function_name)
+            .or_else(|| self.resolve_attached(function_name))
+        {
+            // Functions of other contracts and libraries aren't propagated, unknown ones may write
+            let mutability = if function.getter().is_some() {
+                StateMutability::View
+            } else {
+                contract
+                    .sub_tables
+                    .iter()
+                    .find(|table| table.name == function.name)
+                    .and_then(|table| table.mutability)
+                    .unwrap_or(StateMutability::NonPayable)
+            };
+            self.mark_mutability(mutability);
+        } else if let Some(path) = function_name.node.module_path() {
+            if let Some(function) = &mut self.function {
+                function.callees.push(path.join("::"));
+            }
+        }
+    }
+
+    /// Records the state access of a builtin member, e.g. `balance` or `push`.
+    fn record_member_access(&mut self, base: &ast::Expression, member: &ast::Expression) {
+        let base_type = match self.expression_type(base) {
+            Some(base_type) => base_type,
+            None => return,
+        };
+        let name = member.node.identifier_name();
+        let access = type_members(&base_type)
+            .into_iter()
+            .flatten()
+            .find(|m| Some(&m.name) == name.as_ref())
+            .map(|m| m.state_access);
+        match (base_type, access) {
+            // Members of arrays only access state if the array is in storage
+            (SymbolType::Array(_), Some(_)) if !self.is_state(base) => {}
+            (_, Some(access)) => self.mark_state_access(access),
+            _ => {}
+        }
+    }
+
+    fn mark_state_access(&mut self, access: StateAccess) {
+        match access {
+            StateAccess::None => {}
+            StateAccess::Read => self.mark_mutability(StateMutability::View),
+            StateAccess::Write => self.mark_mutability(StateMutability::NonPayable),
+        }
+    }
+
+    fn check_member_access(
+        &self,
+        base: &ast::Expression,
+        member: &ast::Expression,
+    ) -> SymbolTableResult<()> {
+        let base_type = match self.expression_type(base) {
+            Some(base_type) => base_type,
+            None => return Ok(()),
+        };
+        let name = member.node.identifier_name().unwrap();
+        if let SymbolType::Enum {
+            name: enum_name,
+            variants,
+        } = &base_type
+        {
+            if !variants.contains(&name) {
+                return Err(SymbolTableError {
+                    error: SymbolTableErrorType::TypeError(format!(
+                        "`{}` has no variant `{}`",
+                        enum_name, name
+                    )),
+                    location: member.location,
+                });
+            }
+        }
+        if let Some((struct_name, fields)) = self.struct_fields(&base_type) {
+            if !fields.iter().any(|(field, _)| *field == name) {
+                return Err(SymbolTableError {
+                    error: SymbolTableErrorType::TypeError(format!(
+                        "`{}` has no field `{}`",
+                        struct_name, name
+                    )),
+                    location: member.location,
+                });
+            }
+            return Ok(());
+        }
+        if let Some(contract) = self.resolve_contract(&base_type) {
+            let error = match contract.lookup(&name) {
+                Some(symbol) if symbol.usage == SymbolUsage::Declared => match symbol.visibility {
+                    Visibility::Public | Visibility::External => return Ok(()),
+                    Visibility::Internal if contract.table_type == SymbolTableType::Library => {
+                        return Ok(())
+                    }
+                    visibility => format!("`{}` is {} in `{}`", name, visibility, contract.name),
+                },
+                _ => format!("`{}` has no member `{}`", contract.name, name),
+            };
+            return Err(SymbolTableError {
+                error: SymbolTableErrorType::TypeError(error),
+                location: member.location,
+            });
+        }
+        if let SymbolType::Array(_) = base_type {
+            let in_storage = self
+                .expression_symbol(base)
+                .is_some_and(|symbol| symbol.data_location == SymbolLocation::Storage);
+            if (name == "push" || name == "pop") && !in_storage {
+                return Err(SymbolTableError {
+                    error: SymbolTableErrorType::TypeError(format!(
+                        "`{}` is only available on storage arrays",
+                        name
+                    )),
+                    location: member.location,
+                });
+            }
+        }
+        if self.attached_function(&base_type, &name).is_some() {
+            return Ok(());
+        }
+        // Value types have no members but the builtin and attached ones
+        let members = type_members(&base_type).or_else(|| base_type.is_value_type().then(Vec::new));
+        match members {
+            Some(members) if !members.iter().any(|m| m.name == name) => Err(SymbolTableError {
+                error: SymbolTableErrorType::TypeError(format!(
+                    "`{}` has no member `{}`",
+                    base_type, name
+                )),
+                location: member.location,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks that the arms of a `match` over an enum cover every variant, or include `_`.
+    fn check_match(
+        &self,
+        value: &ast::Expression,
+        arms: &[ast::MatchArm],
+        location: Location,
+    ) -> SymbolTableResult<()> {
+        let value_type = match self.expression_type(value) {
+            Some(value_type) => value_type,
+            None => return Ok(()),
+        };
+        let variants = match self.resolve_enum(&value_type) {
+            Some(variants) => variants,
+            None => return Ok(()),
+        };
+        let mut covered = vec![];
+        for arm in arms.iter().filter(|arm| !arm.is_wildcard()) {
+            match self.expression_type(&arm.pattern) {
+                Some(pattern_type) if pattern_type != value_type => {
+                    return Err(SymbolTableError {
+                        error: SymbolTableErrorType::TypeError(format!(
+                            "pattern of type `{}` doesn't match a value of type `{}`",
+                            pattern_type, value_type
+                        )),
+                        location: arm.pattern.location,
+                    });
+                }
+                _ => {}
+            }
+            if let ExpressionType::MemberExpression { member, .. } = &arm.pattern.node {
+                covered.extend(member.node.identifier_name());
+            }
+        }
+        if arms.iter().any(|arm| arm.is_wildcard()) {
+            return Ok(());
+        }
+        let missing: Vec<String> = variants
+            .into_iter()
+            .filter(|variant| !covered.contains(variant))
+            .collect();
+        if missing.is_empty() {
+            return Ok(());
+        }
+        Err(SymbolTableError {
+            error: SymbolTableErrorType::NonExhaustiveMatch {
+                enum_name: value_type.to_string(),
+                missing,
+            },
+            location,
+        })
+    }
+
+    /// Variants of the enum named by `typ`.
+    fn resolve_enum(&self, typ: &SymbolType) -> Option<Vec<String>> {
+        let name = match typ {
+            SymbolType::UserDefined(name) => name,
+            _ => return None,
+        };
+        match &self.find_symbol(name)?.symbol_type {
+            SymbolType::Enum { variants, .. } => Some(variants.clone()),
+            _ => None,
+        }
+    }
+
+    /// Checks the fields of a struct literal and the types of their values.
+    fn check_struct_literal(
+        &self,
+        struct_name: &ast::Expression,
+        expression: &ast::Expression,
+    ) -> SymbolTableResult<()> {
+        let declaration = match self.resolve_struct(struct_name) {
+            Some(declaration) => declaration,
+            None => return Ok(()),
+        };
+        let literal = match struct_literal(&declaration, expression) {
+            Some(literal) => literal.map_err(|error| SymbolTableError {
+                error: SymbolTableErrorType::TypeError(error.to_string()),
+                location: error.location,
+            })?,
+            None => return Ok(()),
+        };
+        for field in &literal.fields {
+            if let Err(error) = self.conversion(field.value, &field.field_type) {
+                let mut message = format!(
+                    "`{}` expects `{}` for field `{}` but `{}` was given",
+                    literal.name, field.field_type, field.name, error.from
+                );
+                if let Some(reason) = error.reason() {
+                    message.push_str(&format!(" ({})", reason));
+                }
+                return Err(SymbolTableError {
+                    error: SymbolTableErrorType::TypeError(message),
+                    location: field.value.location,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Declaration of the struct named by `struct_name`.
+    fn resolve_struct(&self, struct_name: &ast::Expression) -> Option<SymbolType> {
+        self.expression_symbol(struct_name)
+            .map(|symbol| symbol.symbol_type.clone())
+            .filter(|typ| matches!(typ, SymbolType::Struct { .. }))
+    }
+
+    /// Checks that the struct named `struct_name` doesn't contain itself, in a field of
+    /// its own or of the structs it contains, as its values would have no end.
+    fn check_recursive_struct(&self, struct_name: &ast::Expression) -> SymbolTableResult<()> {
+        let name = struct_name.node.identifier_name().unwrap();
+        let (_, fields) = match self.struct_fields(&SymbolType::UserDefined(name.clone())) {
+            Some(declaration) => declaration,
+            None => return Ok(()),
+        };
+        // Types of the fields reached, with the field of the struct they're reached through
+        let mut pending: Vec<(SymbolType, &str)> = fields
+            .iter()
+            .map(|(field, typ)| (typ.clone(), field.as_str()))
+            .collect();
+        let mut visited: Vec<String> = vec![];
+        while let Some((typ, field)) = pending.pop() {
+            let (inner, inner_fields) = match self.struct_fields(&typ) {
+                Some(declaration) => declaration,
+                None => continue,
+            };
+            if inner == name {
+                return Err(SymbolTableError {
+                    error: SymbolTableErrorType::TypeError(format!(
+                        "struct `{}` contains itself through field `{}`",
+                        name, field
+                    )),
+                    location: struct_name.location,
+                });
+            }
+            if !visited.contains(&inner) {
+                visited.push(inner);
+                pending.extend(inner_fields.into_iter().map(|(_, typ)| (typ, field)));
+            }
+        }
+        Ok(())
+    }
+
+    /// Name and fields of the struct of values of type `typ`, if it is one.
+    fn struct_fields(&self, typ: &SymbolType) -> Option<(String, Vec<(String, SymbolType)>)> {
+        let name = match typ {
+            SymbolType::UserDefined(name) => name,
+            _ => return None,
+        };
+        match &self.find_symbol(name)?.symbol_type {
+            SymbolType::Struct { name, fields } => Some((name.clone(), fields.clone())),
+            _ => None,
+        }
+    }
+
+    /// Checks that a function called by its name, from its own contract, isn't `external`.
+    fn check_internal_call(&self, function_name: &ast::Expression) -> SymbolTableResult<()> {
+        let name = match function_name.node.identifier_name() {
+            Some(name) => name,
+            None => return Ok(()),
+        };
+        match self.find_symbol(&name) {
+            Some(symbol) if symbol.visibility == Visibility::External => Err(SymbolTableError {
+                error: SymbolTableErrorType::TypeError(format!(
+                    "`{}` is external and can only be called through a value of the contract",
+                    name
+                )),
+                location: function_name.location,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    fn check_call(
+        &self,
+        function_name: &ast::Expression,
+        arguments: &ast::Expression,
+    ) -> SymbolTableResult<()> {
+        let (name, params, is_variadic) = match self.resolve_callee(function_name) {
+            Some(callee) => callee,
+            None => return Ok(()),
+        };
+        let arguments = match &arguments.node {
+            ExpressionType::Arguments { arguments } => arguments,
+            _ => return Ok(()),
+        };
+        let expected = params.len();
+        if arguments.len() < expected || (!is_variadic && arguments.len() > expected) {
+            let expected = if is_variadic {
+                format!("at least {}", expected)
+            } else {
+                expected.to_string()
+            };
+            return Err(SymbolTableError {
+                error: SymbolTableErrorType::TypeError(format!(
+                    "`{}` takes {} argument(s) but {} were given",
+                    name,
+                    expected,
+                    arguments.len()
+                )),
+                location: function_name.location,
+            });
+        }
+        if self.resolve_builtin(function_name) == Some(BuiltinFunction::AbiDecode)
+            && !matches!(arguments[1].node, ExpressionType::TypeList { .. })
+        {
+            return Err(SymbolTableError {
+                error: SymbolTableErrorType::TypeError(String::from(
+                    "`abi.decode` expects a type list such as `(uint, bool)`",
+                )),
+                location: arguments[1].location,
+            });
+        }
+        for (i, (argument, param)) in arguments.iter().zip(params).enumerate() {
+            if let Err(error) = self.conversion(argument, &param) {
+                let mut message = format!(
+                    "`{}` expects `{}` for argument {} but `{}` was given",
+                    name,
+                    param,
+                    i + 1,
+                    error.from
+                );
+                if let Some(reason) = error.reason() {
+                    message.push_str(&format!(" ({})", reason));
+                }
+                return Err(SymbolTableError {
+                    error: SymbolTableErrorType::TypeError(message),
+                    location: argument.location,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks that `expression` converts implicitly to `to` when its type is known.
+    fn conversion(
+        &self,
+        expression: &ast::Expression,
+        to: &SymbolType,
+    ) -> Result<(), ConversionError> {
+        if *to == SymbolType::None {
+            return Ok(());
+        }
+        if let Some(value) = self.literal_value(expression) {
+            return literal_conversion(&value, to);
+        }
+        if let ExpressionType::StringLiteral { value, .. } = &expression.node {
+            return string_literal_conversion(value, to);
+        }
+        match self.expression_type(expression) {
+            Some(from) => implicit_conversion(&from, to),
+            None => Ok(()),
+        }
+    }
+
+    fn check_literal(
+        &self,
+        value: &BigInt,
+        to: &SymbolType,
+        location: Location,
+    ) -> SymbolTableResult<()> {
+        literal_conversion(value, to).map_err(|error| SymbolTableError {
+            error: SymbolTableErrorType::TypeError(error.to_string()),
+            location,
+        })
+    }
+
+    fn check_conversion(
+        &self,
+        expression: &ast::Expression,
+        to: &SymbolType,
+    ) -> SymbolTableResult<()> {
+        self.conversion(expression, to)
+            .map_err(|error| SymbolTableError {
+                error: SymbolTableErrorType::TypeError(error.to_string()),
+                location: expression.location,
+            })
+    }
+
+    /// Name, parameter types and variadic flag of a called function: a builtin, a
+    /// function declared in scope or a member of a value.
+    fn resolve_callee(
+        &self,
+        function_name: &ast::Expression,
+    ) -> Option<(String, Vec<SymbolType>, bool)> {
+        if let Some(builtin) = self.resolve_builtin(function_name) {
+            return Some((
+                builtin.name().to_string(),
+                builtin.params(),
+                builtin.is_variadic(),
+            ));
+        }
+        if let ExpressionType::Identifier { value } = &function_name.node {
+            return match &self.find_symbol(value)?.symbol_type {
+                SymbolType::Function { params, .. } => Some((value.clone(), params.clone(), false)),
+                _ => None,
+            };
+        }
+        if let ExpressionType::MemberExpression {
+            expression: base,
+            member,
+        } = &function_name.node
+        {
+            if let Some((contract, function)) = self.resolve_contract_member(function_name) {
+                if let Some(SymbolType::Function { params, .. }) = function.callable_type() {
+                    let name = format!("{}.{}", contract.name, function.name);
+                    return Some((name, params, false));
+                }
+            }
+            let base_type = self.expression_type(base)?;
+            let name = member.node.identifier_name()?;
+            let member = type_members(&base_type)
+                .into_iter()
+                .flatten()
+                .find(|m| m.name == name);
+            if let Some(member) = member {
+                if let SymbolType::Function { params, .. } = member.symbol_type {
+                    return Some((format!("{}.{}", base_type, name), params, false));
+                }
+            }
+            // The value the function is called on is its first argument
+            let (library, function) = self.attached_function(&base_type, &name)?;
+            if let SymbolType::Function { params, .. } = &function.symbol_type {
+                let name = format!("{}.{}", library.name, function.name);
+                return Some((name, params[1..].to_vec(), false));
+            }
+        }
+        None
+    }
+
+    /// Value of `expression` if it is an untyped integer, see `literal_value`.
+    fn literal_value(&self, expression: &ast::Expression) -> Option<BigInt> {
+        match self.literal_values.get(&(expression as *const _)) {
+            Some(value) => value.clone(),
+            None => literal_value(expression),
+        }
+    }
+
+    /// Type of an expression when it is obvious without type inference.
+    fn expression_type(&self, expression: &ast::Expression) -> Option<SymbolType> {
+        if let Some(symbol_type) = self.expression_types.get(&(expression as *const _)) {
+            return symbol_type.clone();
+        }
+        if let Some(value) = self.literal_value(expression) {
+            return Some(literal_type(&value));
+        }
+        match &expression.node {
+            ExpressionType::BinaryExpression {
+                left,
+                operator,
+                right,
+            } => match operator {
+                Operator::Lt
+                | Operator::Le
+                | Operator::Gt
+                | Operator::Ge
+                | Operator::Eq
+                | Operator::NotEq
+                | Operator::And
+                | Operator::Or => Some(SymbolType::Bool),
+                Operator::LShift | Operator::RShift => self.expression_type(left),
+                // An untyped integer adopts the type of the other operand
+                _ if self.literal_value(left).is_some() => self.expression_type(right),
+                _ if self.literal_value(right).is_some() => self.expression_type(left),
+                _ => common_type(&self.expression_type(left)?, &self.expression_type(right)?).ok(),
+            },
+            ExpressionType::IndexExpression {
+                expression: base, ..
+            } => match self.expression_type(base)? {
+                SymbolType::Array(element) | SymbolType::FixedArray(element, _) => Some(*element),
+                SymbolType::Mapping(_, value) => Some(*value),
+                _ => None,
+            },
+            ExpressionType::FunctionCallExpression { function_name, .. } => {
+                if let Some(function) = self
+                    .resolve_contract_member(function_name)
+                    .or_else(|| self.resolve_attached(function_name))
+                    .map(|(_, function)| function)
+                    // Builtins accept the values of each other, e.g. `sha256(blake2s(data))`
+                    .or_else(|| {
+                        self.expression_symbol(function_name).filter(|function| {
+                            matches!(function.symbol_type, SymbolType::Function { .. })
+                                && self.resolve_builtin(function_name).is_none()
+                        })
+                    })
+                {
+                    return match function.callable_type() {
+                        Some(SymbolType::Function { mut returns, .. }) if returns.len() == 1 => {
+                            returns.pop()
+                        }
+                        _ => None,
+                    };
+                }
+                match self.resolve_struct(function_name)? {
+                    SymbolType::Struct { name, .. } => Some(SymbolType::UserDefined(name)),
+                    _ => None,
+                }
+            }
+            ExpressionType::StructLiteral { struct_name, .. } => {
+                match self.resolve_struct(struct_name)? {
+                    SymbolType::Struct { name, .. } => Some(SymbolType::UserDefined(name)),
+                    _ => None,
+                }
+            }
+            ExpressionType::IfExpression {
+                if_statement,
+                else_statement: Some(else_statement),
+                ..
+            } => self.branch_type(block_value(if_statement)?, block_value(else_statement)?),
+            ExpressionType::TernaryExpression { expr1, expr2, .. } => {
+                self.branch_type(expr1, expr2)
+            }
+            ExpressionType::StringLiteral { .. } => Some(SymbolType::String),
+            // Variant of an enum or field of a struct
+            ExpressionType::MemberExpression {
+                expression: base,
+                member,
+            } => match self.expression_type(base)? {
+                SymbolType::Enum { name, .. } => Some(SymbolType::UserDefined(name)),
+                base_type => {
+                    let (_, fields) = self.struct_fields(&base_type)?;
+                    let member = member.node.identifier_name()?;
+                    fields
+                        .into_iter()
+                        .find(|(field, _)| *field == member)
+                        .map(|(_, field_type)| field_type)
+                }
+            },
+            _ => self
+                .expression_symbol(expression)
+                .map(|symbol| symbol.symbol_type.clone()),
+        }
+    }
+
+    /// Declaration an expression refers to, if it is a plain identifier or a path
+    /// into a module declared before it.
+    fn expression_symbol(&self, expression: &ast::Expression) -> Option<&Symbol> {
+        match &expression.node {
+            ExpressionType::Identifier { value } => self.find_symbol(value),
+            ExpressionType::PathExpression { .. } => {
+                let tables: Vec<&SymbolTable> = self.tables.iter().collect();
+                resolve_path(&tables, &expression.node.module_path()?)
+            }
+            _ => None,
+        }
+    }
+
+    /// Finds the builtin called by `function_name` unless a user declaration shadows it.
+    fn resolve_builtin(&self, function_name: &ast::Expression) -> Option<BuiltinFunction> {
+        let path = function_name.node.path_name()?;
+        let builtin = BuiltinFunction::from_name(&path)?;
+        let (root, root_type) = match builtin.namespace() {
+            Some(namespace) => (namespace, SymbolType::Namespace(namespace.to_string())),
+            None => (builtin.name(), builtin.symbol_type()),
+        };
+        match self.find_symbol(root) {
+            Some(symbol) if symbol.symbol_type == root_type => Some(builtin),
+            _ => None,
+        }
+    }
+
+    /// Type of `typ` with the lengths of its arrays evaluated.
+    fn resolve_type(&self, typ: &ast::Type) -> SymbolTableResult<SymbolType> {
+        let resolved = resolve_type(typ, &|name| self.find_constant(name)).map_err(|length| {
+            SymbolTableError {
+                error: SymbolTableErrorType::TypeError(String::from(
+                    "array length is not a compile-time constant",
+                )),
+                location: length.location,
+            }
+        })?;
+        if let Some(length) = zero_length(typ, &resolved) {
+            return Err(SymbolTableError {
+                error: SymbolTableErrorType::TypeError(String::from(
+                    "array length must be greater than zero",
+                )),
+                location: length.location,
+            });
+        }
+        Ok(resolved)
+    }
+
+    fn parameter_types(&self, parameters: &ast::Expression) -> SymbolTableResult<Vec<SymbolType>> {
+        let mut types = vec![];
+        if let ExpressionType::Parameters { parameters } = &parameters.node {
+            for parameter in parameters {
+                if let StatementType::InitializerStatement { variable_type, .. } = &parameter.node {
+                    types.push(self.resolve_type(variable_type)?);
+                }
+            }
+        }
+        Ok(types)
+    }
+
+    /// Value of the nearest declaration of `name` if it is a constant.
+    fn find_constant(&self, name: &str) -> Option<BigUint> {
+        self.find_symbol(name)?.constant.clone()
+    }
+
+    /// Table of the contract whose instances have type `typ`, or of the library
+    /// named by `typ`, whose functions are called through its name, e.g. `SafeMath.add`.
+    fn resolve_contract(&self, typ: &SymbolType) -> Option<&SymbolTable> {
+        let (name, is_library) = match typ {
+            SymbolType::UserDefined(name) => (name, false),
+            SymbolType::Contract(name) => (name, true),
+            _ => return None,
+        };
+        match self.find_symbol(name)?.symbol_type {
+            SymbolType::Contract(_) => self
+                .tables
+                .iter()
+                .rev()
+                .find_map(|table| table.contract_table(name))
+                .filter(|table| (table.table_type == SymbolTableType::Library) == is_library),
+            _ => None,
+        }
+    }
+
+    /// Contract and declaration of a member accessed on a contract instance, e.g. `token.transfer`.
+    fn resolve_contract_member(
+        &self,
+        expression: &ast::Expression,
+    ) -> Option<(&SymbolTable, &Symbol)> {
+        if let ExpressionType::MemberExpression {
+            expression: base,
+            member,
+        } = &expression.node
+        {
+            let contract = self.resolve_contract(&self.expression_type(base)?)?;
+            let symbol = contract
+                .lookup(&member.node.identifier_name()?)
+                .filter(|symbol| symbol.usage == SymbolUsage::Declared)?;
+            return Some((contract, symbol));
+        }
+        None
+    }
+
+    /// Library and function called as a member of a value, e.g. `SafeMath.add` for `x.add(y)`.
+    fn resolve_attached(&self, expression: &ast::Expression) -> Option<(&SymbolTable, &Symbol)> {
+        match &expression.node {
+            ExpressionType::MemberExpression {
+                expression: base,
+                member,
+            } => self.attached_function(
+                &self.expression_type(base)?,
+                &member.node.identifier_name()?,
+            ),
+            _ => None,
+        }
+    }
+
+    /// Function `name` of a library attached to `typ` by `using` in the enclosing tables,
+    /// whose first parameter takes values of `typ`.
+    fn attached_function(&self, typ: &SymbolType, name: &str) -> Option<(&SymbolTable, &Symbol)> {
+        self.tables
+            .iter()
+            .rev()
+            .flat_map(|table| &table.using)
+            .filter(|using| using.target == *typ)
+            .find_map(|using| {
+                let library = self
+                    .tables
+                    .iter()
+                    .rev()
+                    .find_map(|table| table.contract_table(&using.library))?;
+                let function = library
+                    .lookup(name)
+                    .filter(|symbol| symbol.usage == SymbolUsage::Declared)
+                    .filter(|symbol| symbol.visibility != Visibility::Private)?;
+                match &function.symbol_type {
+                    SymbolType::Function { params, .. }
+                        if params
+                            .first()
+                            .is_some_and(|first| implicit_conversion(typ, first).is_ok()) =>
+                    {
+                        Some((library, function))
+                    }
+                    _ => None,
+                }
+            })
+    }
+
+    /// Finds the nearest declaration of `name`.
+    fn find_symbol(&self, name: &str) -> Option<&Symbol> {
+        self.tables.iter().rev().find_map(|table| {
+            table
+                .lookup(name)
+                .filter(|symbol| symbol.usage == SymbolUsage::Declared)
+        })
+    }
+
+    fn register_name(
+        &mut self,
+        name: &str,
+        symbol_type: SymbolType,
+        usage: SymbolUsage,
+        location: Location,
+    ) {
+        let table = self.tables.last_mut().unwrap();
+        match table.symbols.get_mut(name) {
+            // A declaration hides the uses of the name in its own scope
+            Some(symbol) if usage != SymbolUsage::Declared => {
+                if symbol.usage != SymbolUsage::Declared {
+                    symbol.usage = symbol.usage.merge(&usage);
+                }
+            }
+            _ => {
+                let symbol = Symbol::new(name.to_string(), symbol_type, usage, location);
+                table.symbols.insert(name.to_string(), symbol);
+            }
+        }
+    }
+}
+
+/// Checks that no function accesses more state than its declared mutability allows,
+/// including through the functions it calls.
+fn check_mutability(table: &SymbolTable) -> SymbolTableResult<()> {
+    for sub_table in &table.sub_tables {
+        if let (Some(declared), Some(inferred)) =
+            (sub_table.declared_mutability, sub_table.mutability)
+        {
+            if inferred > declared {
+                let access = if inferred == StateMutability::View {
+                    "reads"
+                } else {
+                    "writes"
+                };
+                return Err(SymbolTableError {
+                    error: SymbolTableErrorType::TypeError(format!(
+                        "`{}` is declared `{}` but {} state",
+                        sub_table.name, declared, access
+                    )),
+                    location: table
+                        .lookup(&sub_table.name)
+                        .map_or_else(Location::default, |symbol| symbol.location),
+                });
+            }
+        }
+        check_mutability(sub_table)?;
+    }
+    Ok(())
+}
+
+/// Raises the mutability of every function to the one of the functions it calls,
+/// declared in `table` or in the enclosing scope with the mutabilities `outer`.
+/// Returns whether any mutability changed.
+fn propagate_mutability(
+    table: &mut SymbolTable,
+    callees: &HashMap<(String, String), Vec<String>>,
+    outer: &HashMap<String, StateMutability>,
+) -> bool {
+    let mut changed = false;
+    let mut changing = true;
+    while changing {
+        changing = false;
+        let mutabilities = function_mutabilities(table, outer);
+        for sub_table in &mut table.sub_tables {
+            let key = (table.name.clone(), sub_table.name.clone());
+            let (current, called) = match (sub_table.mutability, callees.get(&key)) {
+                (Some(current), Some(called)) => (current, called),
+                _ => continue,
+            };
+            let mutability = called
+                .iter()
+                .filter_map(|callee| mutabilities.get(callee))
+                .fold(current, |a, b| a.max(*b));
+            if mutability != current {
+                sub_table.mutability = Some(mutability);
+                changing = true;
+                changed = true;
+            }
+        }
+    }
+    // Contracts and modules call the functions declared outside them
+    let mutabilities = function_mutabilities(table, outer);
+    for sub_table in &mut table.sub_tables {
+        if matches!(
+            sub_table.table_type,
+            SymbolTableType::Contract | SymbolTableType::Module
+        ) {
+            changed |= propagate_mutability(sub_table, callees, &mutabilities);
+        }
+    }
+    changed
+}
+
+/// Mutabilities of the functions declared in `table`, which shadow the ones of `outer`.
+/// Functions of modules are named by their path, e.g. `math::clamp`.
+fn function_mutabilities(
+    table: &SymbolTable,
+    outer: &HashMap<String, StateMutability>,
+) -> HashMap<String, StateMutability> {
+    let mut mutabilities = outer.clone();
+    let mut pending = vec![(table, String::new())];
+    while let Some((table, prefix)) = pending.pop() {
+        for sub_table in &table.sub_tables {
+            match (&sub_table.table_type, sub_table.mutability) {
+                (SymbolTableType::Function, Some(mutability)) => {
+                    mutabilities.insert(format!("{}{}", prefix, sub_table.name), mutability);
+                }
+                (SymbolTableType::Module, _) => {
+                    pending.push((sub_table, format!("{}{}::", prefix, sub_table.name)));
+                }
+                _ => {}
+            }
+        }
+    }
+    mutabilities
+}
+
+/// Suggestion to annotate a function whose mutability is more restrictive than the default.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MutabilitySuggestion {
+    pub contract: String,
+    pub function: String,
+    pub mutability: StateMutability,
+    pub location: Location,
+}
+
+impl fmt::Display for MutabilitySuggestion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "function `{}.{}` can be declared `{}`",
+            self.contract, self.function, self.mutability
+        )
+    }
+}
+
+/// Functions of every contract which could be declared `pure` or `view`.
+pub fn suggest_mutability(table: &SymbolTable) -> Vec<MutabilitySuggestion> {
+    let mut suggestions = vec![];
+    for contract in &table.sub_tables {
+        if contract.table_type != SymbolTableType::Contract {
+            continue;
+        }
+        for function in &contract.sub_tables {
+            // A function declared `payable` receives ether whatever state it accesses
+            let declared = function
+                .declared_mutability
+                .unwrap_or(StateMutability::NonPayable);
+            match function.mutability {
+                Some(mutability)
+                    if mutability < declared && declared != StateMutability::Payable =>
+                {
+                    let location = contract
+                        .lookup(&function.name)
+                        .map_or_else(Location::default, |symbol| symbol.location);
+                    suggestions.push(MutabilitySuggestion {
+                        contract: contract.name.clone(),
+                        function: function.name.clone(),
+                        mutability,
+                        location,
+                    });
+                }
+                _ => {}
+            }
+        }
+    }
+    suggestions
+}
+
+/// Value of a block, the expression ending it.
+fn block_value(statement: &ast::Statement) -> Option<&ast::Expression> {
+    match &statement.node {
+        StatementType::CompoundStatement { return_value, .. } => return_value.as_deref(),
+        _ => None,
+    }
+}
+
+/// Checks that the interface or library `name` declares no implemented interfaces,
+/// constructor or state variables. Libraries may declare constants and attach other
+/// libraries. Functions are checked when scanned.
+fn check_stateless(
+    kind: ast::ContractKind,
+    name: &str,
+    bases: &[ast::Expression],
+    members: &[ast::Statement],
+) -> SymbolTableResult<()> {
+    let error = |message: String, location: Location| SymbolTableError {
+        error: SymbolTableErrorType::TypeError(message),
+        location,
+    };
+    let is_interface = kind == ast::ContractKind::Interface;
+    if let Some(base) = bases.first() {
+        let bases = if is_interface {
+            "other interfaces"
+        } else {
+            "interfaces"
+        };
+        return Err(error(
+            format!("{} `{}` can't implement {}", kind, name, bases),
+            base.location,
+        ));
+    }
+    for member in members {
+        match &member.node {
+            StatementType::InitializerStatement {
+                variable: Some(variable),
+                is_constant,
+                ..
+            } if is_interface || !is_constant => {
+                return Err(error(
+                    format!(
+                        "{} `{}` can't declare variable `{}`",
+                        kind,
+                        name,
+                        variable.node.identifier_name().unwrap()
+                    ),
+                    variable.location,
+                ))
+            }
+            StatementType::UsingStatement { .. } if is_interface => {
+                return Err(error(
+                    format!("interface `{}` can't attach libraries", name),
+                    member.location,
+                ))
+            }
+            _ if member.node.is_constructor() => {
+                return Err(error(
+                    format!("{} `{}` can't have a constructor", kind, name),
+                    member.location,
+                ))
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Targets of a destructuring assignment, `None` for the values it ignores.
+fn destructured(
+    items: &[Option<ast::Expression>],
+) -> impl Iterator<Item = Option<&ast::Expression>> {
+    items
+        .iter()
+        .map(|item| item.as_ref().filter(|item| !item.node.is_placeholder()))
+}
+
+/// Identifier of the variable an assignment to `target` writes, e.g. `values` for `values[i]`.
+fn assigned_variable(target: &ast::Expression) -> *const ast::Expression {
+    match &target.node {
+        ExpressionType::IndexExpression { expression, .. }
+        | ExpressionType::MemberExpression { expression, .. } => assigned_variable(expression),
+        _ => target,
+    }
+}
+
+fn is_integer(typ: &SymbolType) -> bool {
+    integer_bits(typ).is_some()
+}
+
+/// Length expression of the first array of `typ` resolved to no elements, if any.
+fn zero_length<'a>(typ: &'a ast::Type, resolved: &SymbolType) -> Option<&'a ast::Expression> {
+    match (typ, resolved) {
+        (ast::Type::FixedArray(_, length), SymbolType::FixedArray(_, 0)) => Some(length),
+        (ast::Type::FixedArray(element, _), SymbolType::FixedArray(resolved, _))
+        | (ast::Type::Array(element), SymbolType::Array(resolved)) => {
+            zero_length(element, resolved)
+        }
+        (ast::Type::Mapping(key, value), SymbolType::Mapping(resolved_key, resolved_value)) => {
+            zero_length(key, resolved_key).or_else(|| zero_length(value, resolved_value))
+        }
+        _ => None,
+    }
+}
+
+/// Checks that every used symbol is declared in its own or an enclosing scope.
+#[derive(Default)]
+struct SymbolAnalyzer<'a> {
+    tables: Vec<&'a SymbolTable>,
+}
+
+impl<'a> SymbolAnalyzer<'a> {
+    fn analyze_symbol_table(&mut self, table: &'a SymbolTable) -> SymbolTableResult<()> {
+        // Tables left to analyze with the number of tables enclosing them
+        let mut pending = vec![(table, 0)];
+        while let Some((table, depth)) = pending.pop() {
+            self.tables.truncate(depth);
+            self.tables.push(table);
+            for symbol in table.symbols.values() {
+                self.analyze_symbol(symbol)?;
+            }
+            pending.extend(table.sub_tables.iter().rev().map(|sub| (sub, depth + 1)));
+        }
+        Ok(())
+    }
+
+    fn analyze_symbol(&self, symbol: &Symbol) -> SymbolTableResult<()> {
+        if symbol.usage == SymbolUsage::Declared {
+            return Ok(());
+        }
+        // Paths into modules, e.g. `math::clamp`
+        let path: Vec<String> = symbol.name.split("::").map(String::from).collect();
+        let is_declared = if path.len() > 1 {
+            resolve_path(&self.tables, &path).is_some()
+        } else {
+            self.tables.iter().rev().any(|table| {
+                table
+                    .lookup(&symbol.name)
+                    .is_some_and(|s| s.usage == SymbolUsage::Declared)
+            })
+        };
+        if is_declared {
+            Ok(())
+        } else {
+            Err(SymbolTableError {
+                error: SymbolTableErrorType::NotDeclared(symbol.name.clone()),
+                location: symbol.location,
+            })
+        }
+    }
+}
@@ -0,0 +1,322 @@
+//! Taint analysis of the data flowing from callers to sensitive operations.
+//!
+//! The parameters of contract functions and the properties of `msg` are chosen by
+//! whoever calls the contract. Variables assigned from them are tainted too, in
+//! any order: the analysis doesn't follow the control flow. Tainted data reaching
+//! a sink is reported unless the function checks `msg.sender` in an `assert` or
+//! in the condition of an `if`, the usual access control. The sinks are:
+//!
+//! - external calls, e.g. `to.transfer(amount)` or `token.approve(spender)`,
+//! - writes to state variables holding ownership, e.g. `owner = to`,
+//! - `selfdestruct`.
+
+use crate::builtin::BUILTIN_NAMESPACES;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fmt;
+use zoker_parser::ast;
+use zoker_parser::ast::{ExpressionType, StatementType};
+use zoker_parser::complexity::Node;
+use zoker_parser::location::Location;
+
+/// Parts of names of the state variables holding ownership.
+const OWNERSHIP_NAMES: &[&str] = &["owner", "admin"];
+
+/// Sensitive operation reached by tainted data.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Sink {
+    /// Call of the member of an address or contract, e.g. `transfer`
+    ExternalCall(String),
+    /// Write to the state variable
+    OwnershipWrite(String),
+    Selfdestruct,
+}
+
+impl fmt::Display for Sink {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Sink::ExternalCall(member) => write!(f, "external call `{}`", member),
+            Sink::OwnershipWrite(variable) => write!(f, "write to `{}`", variable),
+            Sink::Selfdestruct => write!(f, "`selfdestruct`"),
+        }
+    }
+}
+
+/// Flow of caller data to a sink, with no check of the caller.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TaintWarning {
+    /// Qualified name of the function, e.g. `Wallet.withdraw`
+    pub function: String,
+    pub sink: Sink,
+    /// Parameters and properties of `msg` the data comes from, sorted
+    pub sources: Vec<String>,
+    pub location: Location,
+}
+
+impl fmt::Display for TaintWarning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let sources: Vec<String> = self
+            .sources
+            .iter()
+            .map(|source| format!("`{}`", source))
+            .collect();
+        write!(
+            f,
+            "{} in `{}` depends on {} without a check of `msg.sender`",
+            self.sink,
+            self.function,
+            sources.join(", ")
+        )
+    }
+}
+
+/// Unguarded flows of caller data to sinks in the contracts of `program`.
+pub fn taint_warnings(program: &ast::Program) -> Vec<TaintWarning> {
+    let ast::Program::GlobalStatements(statements) = program;
+    let mut warnings = vec![];
+    for statement in statements {
+        if let StatementType::ContractStatement {
+            contract_name,
+            members,
+            ..
+        } = &statement.node
+        {
+            let contract = contract_name.node.identifier_name().unwrap();
+            if let StatementType::MemberStatement { statements } = &members.node {
+                let state: HashSet<String> = statements.iter().filter_map(declared_name).collect();
+                for member in statements {
+                    analyze_function(&contract, &state, member, &mut warnings);
+                }
+            }
+        }
+    }
+    warnings
+}
+
+/// Name of the variable declared by `statement`, if any.
+fn declared_name(statement: &ast::Statement) -> Option<String> {
+    match &statement.node {
+        StatementType::InitializerStatement {
+            variable: Some(variable),
+            ..
+        } => variable.node.identifier_name(),
+        _ => None,
+    }
+}
+
+/// Nodes of the trees rooted at `roots`, in order of the source.
+pub(crate) fn descendants(roots: Vec<Node>) -> Vec<Node> {
+    let mut nodes = vec![];
+    let mut pending: Vec<Node> = roots.into_iter().rev().collect();
+    while let Some(node) = pending.pop() {
+        pending.extend(node.children().into_iter().rev());
+        nodes.push(node);
+    }
+    nodes
+}
+
+/// Variable written by an assignment to `target`, e.g. `balances` for `balances[to]`.
+fn assigned_name(target: &ast::Expression) -> Option<String> {
+    let mut target = target;
+    loop {
+        match &target.node {
+            ExpressionType::Identifier { value } => return Some(value.clone()),
+            ExpressionType::IndexExpression { expression, .. }
+            | ExpressionType::MemberExpression { expression, .. } => target = expression,
+            _ => return None,
+        }
+    }
+}
+
+/// Sources of the data `expression` is computed from.
+pub(crate) fn sources(
+    expression: &ast::Expression,
+    tainted: &HashMap<String, BTreeSet<String>>,
+) -> BTreeSet<String> {
+    let mut sources = BTreeSet::new();
+    for node in descendants(vec![Node::Expression(expression)]) {
+        match node {
+            Node::Expression(ast::Expression {
+                node: ExpressionType::Identifier { value },
+                ..
+            }) => sources.extend(tainted.get(value).into_iter().flatten().cloned()),
+            Node::Expression(ast::Expression {
+                node: ExpressionType::MemberExpression { expression, member },
+                ..
+            }) if expression.node.identifier_name().as_deref() == Some("msg") => {
+                let member = member.node.identifier_name().unwrap_or_default();
+                sources.insert(format!("msg.{}", member));
+            }
+            _ => {}
+        }
+    }
+    sources
+}
+
+/// Propagates the sources of the `tainted` variables through the assignments of
+/// `nodes`, in any order, until no variable changes.
+pub(crate) fn propagate(nodes: &[Node], tainted: &mut HashMap<String, BTreeSet<String>>) {
+    loop {
+        let mut changed = false;
+        for node in nodes {
+            let flow = match node {
+                Node::Statement(ast::Statement {
+                    node:
+                        StatementType::InitializerStatement {
+                            variable: Some(variable),
+                            default: Some(default),
+                            ..
+                        },
+                    ..
+                }) => variable.node.identifier_name().zip(Some(default)),
+                Node::Expression(ast::Expression {
+                    node: ExpressionType::AssignExpression { left, right, .. },
+                    ..
+                }) => assigned_name(left).zip(Some(right)),
+                Node::Expression(ast::Expression {
+                    node:
+                        ExpressionType::ForEachExpression {
+                            iterator, vector, ..
+                        },
+                    ..
+                }) => iterator.node.identifier_name().zip(Some(vector)),
+                _ => None,
+            };
+            if let Some((name, value)) = flow {
+                let value = sources(value, tainted);
+                let sources = tainted.entry(name).or_default();
+                if !value.is_subset(sources) {
+                    sources.extend(value);
+                    changed = true;
+                }
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+}
+
+/// Whether `expression` checks the caller.
+fn checks_sender(expression: &ast::Expression) -> bool {
+    sources(expression, &HashMap::new()).contains("msg.sender")
+}
+
+fn analyze_function(
+    contract: &str,
+    state: &HashSet<String>,
+    statement: &ast::Statement,
+    warnings: &mut Vec<TaintWarning>,
+) {
+    let (function_name, parameters, body) = match &statement.node {
+        StatementType::FunctionStatement {
+            function_name,
+            parameters,
+            statement: Some(body),
+            ..
+        } => (function_name, parameters, body),
+        _ => return,
+    };
+    let function = format!(
+        "{}.{}",
+        contract,
+        function_name.node.identifier_name().unwrap()
+    );
+    let mut tainted: HashMap<String, BTreeSet<String>> = HashMap::new();
+    if let ExpressionType::Parameters { parameters } = &parameters.node {
+        for name in parameters.iter().filter_map(declared_name) {
+            tainted.insert(name.clone(), BTreeSet::from([name]));
+        }
+    }
+    let nodes = descendants(vec![Node::Statement(body)]);
+    let locals: HashSet<String> = nodes
+        .iter()
+        .filter_map(|node| match node {
+            Node::Statement(statement) => declared_name(statement),
+            Node::Expression(_) => None,
+        })
+        .collect();
+
+    propagate(&nodes, &mut tainted);
+
+    let guarded = nodes.iter().any(|node| match node {
+        Node::Expression(ast::Expression {
+            node:
+                ExpressionType::FunctionCallExpression {
+                    function_name,
+                    arguments,
+                },
+            ..
+        }) => {
+            matches!(
+                function_name.node.identifier_name().as_deref(),
+                Some("assert" | "require")
+            ) && checks_sender(arguments)
+        }
+        Node::Expression(ast::Expression {
+            node:
+                ExpressionType::IfExpression { condition, .. }
+                | ExpressionType::TernaryExpression { condition, .. },
+            ..
+        }) => checks_sender(condition),
+        _ => false,
+    });
+    if guarded {
+        return;
+    }
+
+    for node in &nodes {
+        let expression = match node {
+            Node::Expression(expression) => expression,
+            Node::Statement(_) => continue,
+        };
+        let (sink, data) = match &expression.node {
+            ExpressionType::FunctionCallExpression {
+                function_name,
+                arguments,
+            } => match &function_name.node {
+                ExpressionType::Identifier { value } if value == "selfdestruct" => {
+                    (Sink::Selfdestruct, sources(arguments, &tainted))
+                }
+                ExpressionType::MemberExpression {
+                    expression: base,
+                    member,
+                } => {
+                    let member = member.node.identifier_name().unwrap_or_default();
+                    let base_name = base.node.identifier_name().unwrap_or_default();
+                    // Arrays and the namespaces of the language aren't other accounts
+                    if member == "push"
+                        || member == "pop"
+                        || BUILTIN_NAMESPACES.contains(&base_name.as_str())
+                    {
+                        continue;
+                    }
+                    let mut data = sources(base, &tainted);
+                    data.extend(sources(arguments, &tainted));
+                    (Sink::ExternalCall(member), data)
+                }
+                _ => continue,
+            },
+            ExpressionType::AssignExpression { left, right, .. } => match assigned_name(left) {
+                Some(name)
+                    if state.contains(&name)
+                        && !locals.contains(&name)
+                        && OWNERSHIP_NAMES
+                            .iter()
+                            .any(|part| name.to_lowercase().contains(part)) =>
+                {
+                    (Sink::OwnershipWrite(name), sources(right, &tainted))
+                }
+                _ => continue,
+            },
+            _ => continue,
+        };
+        if !data.is_empty() {
+            warnings.push(TaintWarning {
+                function: function.clone(),
+                sink,
+                sources: data.into_iter().collect(),
+                location: expression.location,
+            });
+        }
+    }
+}
@@ -0,0 +1,186 @@
+//! Runs the `@test` functions of a program.
+//!
+//! The program is compiled up to the check stage with `test` defined, so that
+//! `#[cfg(test)]` items are included, then each test function is run by the
+//! interpreter on fresh contract state. A test passes unless it reverts, e.g.
+//! on a failed `assert`. The coverage of the tests adds up the counts of each run.
+
+use crate::coverage::{instrument, Coverage};
+use crate::driver::{Compiler, Stage};
+use crate::error::{CompileResult, ExecutionError};
+use crate::interpreter::{qualified_name, Interpreter};
+use std::fmt;
+use zoker_parser::ast;
+use zoker_parser::ast::StatementType;
+use zoker_parser::location::Location;
+
+/// Name defined when compiling tests, for `#[cfg(test)]`.
+pub const TEST_FLAG: &str = "test";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TestOutcome {
+    Passed,
+    Failed(ExecutionError),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TestResult {
+    /// Name qualified by its contract or modules, e.g. `Token.test_transfer`
+    pub name: String,
+    pub location: Location,
+    pub outcome: TestOutcome,
+}
+
+/// Results of the tests of a program, in order of declaration.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TestReport {
+    pub results: Vec<TestResult>,
+}
+
+impl TestReport {
+    pub fn passed(&self) -> usize {
+        self.results
+            .iter()
+            .filter(|result| result.outcome == TestOutcome::Passed)
+            .count()
+    }
+
+    pub fn failed(&self) -> usize {
+        self.results.len() - self.passed()
+    }
+
+    pub fn is_success(&self) -> bool {
+        self.failed() == 0
+    }
+}
+
+impl fmt::Display for TestReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "running {} test(s)", self.results.len())?;
+        for result in &self.results {
+            let status = match result.outcome {
+                TestOutcome::Passed => "ok",
+                TestOutcome::Failed(_) => "FAILED",
+            };
+            writeln!(f, "test {} ... {}", result.name, status)?;
+        }
+        let failures: Vec<_> = self
+            .results
+            .iter()
+            .filter_map(|result| match &result.outcome {
+                TestOutcome::Failed(err) => Some((&result.name, err)),
+                TestOutcome::Passed => None,
+            })
+            .collect();
+        if !failures.is_empty() {
+            writeln!(f, "\nfailures:")?;
+            for (name, err) in failures {
+                writeln!(f, "    {}: {}", name, err)?;
+            }
+        }
+        let status = if self.is_success() { "ok" } else { "FAILED" };
+        write!(
+            f,
+            "\ntest result: {}. {} passed; {} failed",
+            status,
+            self.passed(),
+            self.failed()
+        )
+    }
+}
+
+/// Compiles `source` for testing and runs its tests.
+pub fn run_tests(source: &str) -> CompileResult<TestReport> {
+    let mut compiler = Compiler::new();
+    compiler.stop_after = Stage::Check;
+    compiler
+        .settings
+        .defines
+        .flags
+        .insert(TEST_FLAG.to_string());
+    let compilation = compiler.compile(source)?;
+    Ok(run_program_tests(compilation.program.as_ref().unwrap()))
+}
+
+/// Runs the tests of `program`, as checked by the symbol table.
+pub fn run_program_tests(program: &ast::Program) -> TestReport {
+    run(program, None)
+}
+
+/// Runs the tests of `program` and counts the lines and branches they run.
+pub fn run_program_tests_with_coverage(program: &ast::Program) -> (TestReport, Coverage) {
+    let mut coverage = instrument(program);
+    let report = run(program, Some(&mut coverage));
+    (report, coverage)
+}
+
+fn run(program: &ast::Program, mut coverage: Option<&mut Coverage>) -> TestReport {
+    let ast::Program::GlobalStatements(statements) = program;
+    let mut tests = vec![];
+    collect_tests(statements, None, &[], &mut tests);
+    // Zero counters for each run
+    let counters = coverage.as_ref().map(|_| instrument(program));
+    let results = tests
+        .into_iter()
+        .map(|(name, location)| {
+            let mut interpreter = Interpreter::new(program);
+            if let Some(counters) = &counters {
+                interpreter.set_coverage(counters.clone());
+            }
+            let outcome = match interpreter.call(&name, vec![]) {
+                Ok(_) => TestOutcome::Passed,
+                Err(err) => TestOutcome::Failed(err),
+            };
+            if let (Some(coverage), Some(counts)) =
+                (coverage.as_deref_mut(), interpreter.coverage())
+            {
+                coverage.merge(counts);
+            }
+            TestResult {
+                name,
+                location,
+                outcome,
+            }
+        })
+        .collect();
+    TestReport { results }
+}
+
+/// Qualified names and locations of the `@test` functions among `statements`.
+fn collect_tests(
+    statements: &[ast::Statement],
+    contract: Option<&str>,
+    modules: &[String],
+    tests: &mut Vec<(String, Location)>,
+) {
+    for statement in statements {
+        match &statement.node {
+            StatementType::FunctionStatement { function_name, .. }
+                if statement.node.annotation("test").is_some() =>
+            {
+                let name = function_name.node.identifier_name().unwrap();
+                let name = qualified_name(contract, modules, &name);
+                tests.push((name, function_name.location));
+            }
+            StatementType::ContractStatement {
+                contract_name,
+                members,
+                ..
+            } => {
+                let name = contract_name.node.identifier_name().unwrap();
+                if let StatementType::MemberStatement { statements } = &members.node {
+                    collect_tests(statements, Some(&name), &[], tests);
+                }
+            }
+            StatementType::ModuleStatement {
+                module_name,
+                members,
+            } => {
+                let mut modules = modules.to_vec();
+                modules.push(module_name.node.identifier_name().unwrap());
+                collect_tests(members, None, &modules, tests);
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,87 @@
+//! Opt-in profiling of the compilation phases.
+
+use crate::error::CompileResult;
+use crate::rewriter::rewrite_program;
+use crate::symbol::Contract;
+use crate::symbol_table::{analyze_symbol_table, build_symbol_table};
+use std::fmt;
+use std::time::{Duration, Instant};
+use zoker_parser::error::{ParseError, ParseErrorType};
+use zoker_parser::lexer::make_tokenizer;
+use zoker_parser::parser::parse_program;
+
+pub const LEXING: &str = "lexing";
+pub const PARSING: &str = "parsing";
+pub const SYMBOL_TABLE: &str = "symbol table";
+pub const SYMBOL_ANALYSIS: &str = "symbol analysis";
+pub const REWRITING: &str = "rewriting";
+
+/// Time spent in each phase, in the order the phases ran.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct CompileTimings {
+    pub phases: Vec<(String, Duration)>,
+}
+
+impl CompileTimings {
+    pub fn new() -> Self {
+        CompileTimings::default()
+    }
+
+    /// Runs `phase` and records how long it took.
+    pub fn time<T>(&mut self, name: &str, phase: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let result = phase();
+        self.phases.push((name.to_string(), start.elapsed()));
+        result
+    }
+
+    pub fn get(&self, name: &str) -> Option<Duration> {
+        self.phases
+            .iter()
+            .find(|(phase, _)| phase == name)
+            .map(|(_, duration)| *duration)
+    }
+
+    pub fn total(&self) -> Duration {
+        self.phases.iter().map(|(_, duration)| *duration).sum()
+    }
+}
+
+impl fmt::Display for CompileTimings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let total = self.total();
+        for (phase, duration) in &self.phases {
+            let ratio = if total.as_nanos() == 0 {
+                0.0
+            } else {
+                duration.as_secs_f64() / total.as_secs_f64() * 100.0
+            };
+            writeln!(f, "{:<16} {:>12.3?} {:>6.1}%", phase, duration, ratio)?;
+        }
+        writeln!(f, "{:<16} {:>12.3?}", "total", total)
+    }
+}
+
+/// Compiles `source` while timing each phase. Phases after a failing one are not run.
+pub fn compile_with_timings(source: &str) -> (CompileResult<Vec<Contract>>, CompileTimings) {
+    let mut timings = CompileTimings::new();
+    let result = compile_phases(source, &mut timings);
+    (result, timings)
+}
+
+fn compile_phases(source: &str, timings: &mut CompileTimings) -> CompileResult<Vec<Contract>> {
+    // Parsing lexes again, lexing is measured on its own to split the cost.
+    timings.time(LEXING, || {
+        make_tokenizer(source)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|err| ParseError {
+                error: ParseErrorType::Lexical(err.error),
+                location: err.location,
+            })
+    })?;
+    let program = timings.time(PARSING, || parse_program(source))?;
+    let table = timings.time(SYMBOL_TABLE, || build_symbol_table(&program))?;
+    timings.time(SYMBOL_ANALYSIS, || analyze_symbol_table(&table))?;
+    let contracts = timings.time(REWRITING, || rewrite_program(&program))?;
+    Ok(contracts)
+}
@@ -0,0 +1,106 @@
+//! Mapping of execution traces of deployed contracts back to the source.
+//!
+//! There is no EVM code generator yet, so the source map follows the layout the
+//! size estimates assume: the dispatcher, then the body of each function in order
+//! of declaration. The IR doesn't keep the locations of statements, so code maps
+//! to the functions it belongs to and to the line where they're declared.
+
+use crate::codesize::{body_size, dispatcher_size};
+use crate::driver::Artifact;
+use crate::settings::EvmVersion;
+use crate::symbol::Contract;
+use crate::symbol_table::SymbolTable;
+use std::fmt;
+use zoker_parser::location::Location;
+
+/// Code of a function in the runtime code.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SourceMapEntry {
+    /// Offset of the first instruction
+    pub start: usize,
+    pub length: usize,
+    /// Name of the function, qualified by its modules, e.g. `math::clamp`
+    pub function: String,
+    /// Location of the declaration of the function
+    pub location: Location,
+}
+
+/// Functions of the runtime code of a contract, ordered by offset.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SourceMap {
+    pub entries: Vec<SourceMapEntry>,
+}
+
+impl SourceMap {
+    /// Function whose code contains the instruction at `pc`.
+    pub fn lookup(&self, pc: usize) -> Option<&SourceMapEntry> {
+        self.entries
+            .iter()
+            .find(|entry| entry.start <= pc && pc < entry.start + entry.length)
+    }
+}
+
+/// Source map of the runtime code of `contract` compiled for `evm_version`, as declared in `table`.
+pub fn source_map(contract: &Contract, table: &SymbolTable, evm_version: EvmVersion) -> SourceMap {
+    let mut start = dispatcher_size(contract, evm_version);
+    let mut entries = vec![];
+    for function in &contract.functions {
+        let declaration = if function.is_free {
+            let path: Vec<String> = function.name.split("::").map(String::from).collect();
+            table.lookup_path(&path)
+        } else {
+            table
+                .contract_table(&contract.name)
+                .and_then(|contract| contract.lookup(&function.name))
+        };
+        let length = body_size(function, evm_version);
+        entries.push(SourceMapEntry {
+            start,
+            length,
+            function: function.name.clone(),
+            location: declaration
+                .map(|symbol| symbol.location)
+                .unwrap_or_default(),
+        });
+        start += length;
+    }
+    SourceMap { entries }
+}
+
+/// Consecutive steps of a trace in the same function.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SourceFrame {
+    /// Function run, `None` for the dispatcher or code outside of the functions
+    pub function: Option<String>,
+    pub location: Option<Location>,
+    /// Program counters of the steps
+    pub pcs: Vec<usize>,
+}
+
+impl fmt::Display for SourceFrame {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (&self.function, &self.location) {
+            (Some(function), Some(location)) => write!(f, "{} at {}", function, location)?,
+            _ => write!(f, "<dispatcher>")?,
+        }
+        write!(f, " ({} steps)", self.pcs.len())
+    }
+}
+
+/// Frames of the functions run by `trace`, the program counters of a transaction on `artifact`.
+pub fn symbolize(trace: &[usize], artifact: &Artifact) -> Vec<SourceFrame> {
+    let mut frames: Vec<SourceFrame> = vec![];
+    for &pc in trace {
+        let entry = artifact.source_map.lookup(pc);
+        let function = entry.map(|entry| entry.function.clone());
+        match frames.last_mut() {
+            Some(frame) if frame.function == function => frame.pcs.push(pc),
+            _ => frames.push(SourceFrame {
+                function,
+                location: entry.map(|entry| entry.location),
+                pcs: vec![pc],
+            }),
+        }
+    }
+    frames
+}
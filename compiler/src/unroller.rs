@@ -0,0 +1,107 @@
+//! Unrolls loops whose trip count is known at compile time.
+//!
+//! A loop over a number `n`, e.g. `for i in 4 { .. }`, iterates `i` over `0..n`.
+//! Each iteration becomes an assignment of the iterator followed by a copy of the body.
+//! The R1CS backend requires every loop to be unrolled.
+
+use crate::constant::evaluate;
+use crate::inliner::operation_size;
+use crate::symbol::{Contract, Function, Operation, OperationType};
+use num_bigint::BigUint;
+use num_traits::ToPrimitive;
+
+#[derive(Debug, Clone)]
+pub struct UnrollConfig {
+    /// Loops with more iterations are kept.
+    pub max_iterations: usize,
+    /// Loops which would grow beyond this many operations once unrolled are kept.
+    pub max_size: usize,
+}
+
+impl Default for UnrollConfig {
+    fn default() -> Self {
+        UnrollConfig {
+            max_iterations: 64,
+            max_size: 4096,
+        }
+    }
+}
+
+/// Unrolls the loops of every function of `contract`, returning how many were unrolled.
+pub fn unroll_contract(contract: &mut Contract, config: &UnrollConfig) -> usize {
+    contract
+        .functions
+        .iter_mut()
+        .map(|function| unroll_function(function, config))
+        .sum()
+}
+
+pub fn unroll_function(function: &mut Function, config: &UnrollConfig) -> usize {
+    let mut unrolled = 0;
+    function.operations = unroll_operations(
+        std::mem::take(&mut function.operations),
+        config,
+        &mut unrolled,
+    );
+    unrolled
+}
+
+/// Trip count of a loop over `vector`, if known at compile time.
+pub fn trip_count(vector: &Operation) -> Option<usize> {
+    evaluate(vector)?.to_usize()
+}
+
+fn unroll_operations(
+    operations: Vec<Operation>,
+    config: &UnrollConfig,
+    unrolled: &mut usize,
+) -> Vec<Operation> {
+    let mut result = vec![];
+    for operation in operations {
+        match operation.operation {
+            OperationType::For {
+                iter,
+                vector,
+                stmts,
+            } => {
+                // Inner loops first, so that nested constant loops are unrolled completely.
+                let stmts = unroll_operations(stmts, config, unrolled);
+                let body_size = stmts.iter().map(operation_size).sum::<usize>() + 3;
+                match trip_count(&vector) {
+                    Some(count)
+                        if count <= config.max_iterations
+                            && count.saturating_mul(body_size) <= config.max_size =>
+                    {
+                        *unrolled += 1;
+                        for i in 0..count {
+                            result.push(Operation::new(OperationType::Assign {
+                                left: iter.clone(),
+                                right: Box::new(Operation::new(OperationType::Constant {
+                                    value: BigUint::from(i),
+                                })),
+                            }));
+                            result.extend(stmts.iter().cloned());
+                        }
+                    }
+                    _ => result.push(Operation::new(OperationType::For {
+                        iter,
+                        vector,
+                        stmts,
+                    })),
+                }
+            }
+            OperationType::If { cond, stmts } => result.push(Operation::new(OperationType::If {
+                cond,
+                stmts: unroll_operations(stmts, config, unrolled),
+            })),
+            OperationType::Else { cond, stmts } => {
+                result.push(Operation::new(OperationType::Else {
+                    cond,
+                    stmts: unroll_operations(stmts, config, unrolled),
+                }))
+            }
+            op => result.push(Operation::new(op)),
+        }
+    }
+    result
+}
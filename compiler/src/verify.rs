@@ -0,0 +1,62 @@
+//! Verification of deployed contracts against their sources.
+//!
+//! The sources are compiled again with the settings of the deployment, and the
+//! deployed bytecode matches if the metadata hash of its trailer is the one of a
+//! contract compiled from them. The metadata commits to the compiler version,
+//! the settings, the hash of the sources and the ABI. There is no EVM code
+//! generator yet, so the code before the trailer isn't compared.
+
+use crate::driver::Compiler;
+use crate::error::CompileResult;
+use crate::metadata::split_metadata;
+use crate::settings::CompileSettings;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum VerificationResult {
+    /// The bytecode was compiled from the contract of the source
+    Match { source: String, contract: String },
+    /// No contract of the sources has the metadata of the bytecode
+    Mismatch,
+    /// The bytecode has no metadata trailer to compare
+    NoMetadata,
+}
+
+impl fmt::Display for VerificationResult {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            VerificationResult::Match { source, contract } => {
+                write!(f, "matches contract `{}` of `{}`", contract, source)
+            }
+            VerificationResult::Mismatch => write!(f, "doesn't match the sources"),
+            VerificationResult::NoMetadata => write!(f, "has no metadata"),
+        }
+    }
+}
+
+/// Verifies `bytecode` against the contracts compiled from `sources`, given as `(name, content)`.
+pub fn matches(
+    sources: &[(&str, &str)],
+    settings: &CompileSettings,
+    bytecode: &[u8],
+) -> CompileResult<VerificationResult> {
+    let hash = match split_metadata(bytecode) {
+        (_, Some(hash)) => hash,
+        (_, None) => return Ok(VerificationResult::NoMetadata),
+    };
+    for (name, content) in sources {
+        let mut compiler = Compiler::with_settings(settings.clone());
+        compiler.source_name = name.to_string();
+        let artifacts = compiler.compile(content)?.artifacts.unwrap_or_default();
+        if let Some(artifact) = artifacts
+            .iter()
+            .find(|artifact| artifact.metadata.hash() == hash)
+        {
+            return Ok(VerificationResult::Match {
+                source: name.to_string(),
+                contract: artifact.contract.name.clone(),
+            });
+        }
+    }
+    Ok(VerificationResult::Mismatch)
+}
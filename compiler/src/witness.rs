@@ -0,0 +1,517 @@
+//! Witness input files of functions.
+//!
+//! The witness of a function is computed from the values of its parameters, read
+//! from a JSON object with a `private` and a `public` object mapping the names of
+//! the parameters declared so to their values:
+//!
+//! `{"private": {"vote": "1"}, "public": {"round": "0x2a"}}`
+//!
+//! JSON numbers can't hold 256-bit integers exactly, so integers are strings in
+//! decimal, or in hexadecimal with a `0x` prefix. `address`, `bytes32` and `bytes`
+//! are hexadecimal strings of their bytes.
+//!
+//! `witness_schema` describes the file expected by a function as a JSON schema,
+//! and `parse_witness_input` validates a file against it before any witness is
+//! computed, giving the values of the parameters in order.
+
+use crate::coercion::integer_bits;
+use crate::error::WitnessError;
+use crate::field::field_modulus;
+use crate::interpreter::Value;
+use crate::metadata::json_string;
+use crate::symbol::{Function, Symbol, SymbolType};
+use num_bigint::{BigInt, Sign};
+use num_traits::{One, Zero};
+
+pub type WitnessResult<T> = Result<T, WitnessError>;
+
+const SCHEMA_DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// Groups of the parameters in the input file, in the order they are written.
+const GROUPS: [&str; 2] = ["private", "public"];
+
+fn group(symbol: &Symbol) -> &'static str {
+    if symbol.is_private {
+        "private"
+    } else {
+        "public"
+    }
+}
+
+/// Hexadecimal string of exactly `bytes` bytes, any number of bytes if `None`.
+fn hex_pattern(bytes: Option<usize>) -> String {
+    match bytes {
+        Some(bytes) => format!("^0x[0-9a-fA-F]{{{}}}$", bytes * 2),
+        None => String::from("^0x([0-9a-fA-F]{2})*$"),
+    }
+}
+
+fn type_schema(typ: &SymbolType) -> WitnessResult<String> {
+    let description = json_string(&typ.to_string());
+    let schema = match typ {
+        SymbolType::Bool => String::from("{\"type\":\"boolean\"}"),
+        SymbolType::String => String::from("{\"type\":\"string\"}"),
+        SymbolType::Uint256 | SymbolType::Uint(_) | SymbolType::Field => format!(
+            "{{\"description\":{},\"pattern\":\"^(0x[0-9a-fA-F]+|[0-9]+)$\",\"type\":\"string\"}}",
+            description
+        ),
+        SymbolType::Int256 | SymbolType::Int(_) => format!(
+            "{{\"description\":{},\"pattern\":\"^-?(0x[0-9a-fA-F]+|[0-9]+)$\",\"type\":\"string\"}}",
+            description
+        ),
+        SymbolType::Address | SymbolType::Bytes32 | SymbolType::Bytes => format!(
+            "{{\"description\":{},\"pattern\":{},\"type\":\"string\"}}",
+            description,
+            json_string(&hex_pattern(byte_length(typ)))
+        ),
+        SymbolType::Array(item) => {
+            format!("{{\"items\":{},\"type\":\"array\"}}", type_schema(item)?)
+        }
+        SymbolType::FixedArray(item, length) => format!(
+            "{{\"items\":{},\"maxItems\":{},\"minItems\":{},\"type\":\"array\"}}",
+            type_schema(item)?,
+            length,
+            length
+        ),
+        _ => {
+            return Err(WitnessError::new(&format!(
+                "`{}` can't be a witness input",
+                typ
+            )))
+        }
+    };
+    Ok(schema)
+}
+
+/// Number of bytes of a hexadecimal type, `None` for `bytes`.
+fn byte_length(typ: &SymbolType) -> Option<usize> {
+    match typ {
+        SymbolType::Address => Some(20),
+        SymbolType::Bytes32 => Some(32),
+        _ => None,
+    }
+}
+
+/// JSON schema of the witness input file of `function`, compact with sorted keys.
+pub fn witness_schema(function: &Function) -> WitnessResult<String> {
+    let mut groups = vec![];
+    for name in GROUPS.iter() {
+        let params: Vec<&Symbol> = function
+            .params
+            .iter()
+            .filter(|param| group(param) == *name)
+            .collect();
+        let mut properties = vec![];
+        for param in &params {
+            properties.push(format!(
+                "{}:{}",
+                json_string(&param.name),
+                type_schema(&param.symbol_type)?
+            ));
+        }
+        let required: Vec<String> = params
+            .iter()
+            .map(|param| json_string(&param.name))
+            .collect();
+        groups.push(format!(
+            "{}:{{\"additionalProperties\":false,\"properties\":{{{}}},\"required\":[{}],\
+             \"type\":\"object\"}}",
+            json_string(name),
+            properties.join(","),
+            required.join(",")
+        ));
+    }
+    Ok(format!(
+        "{{\"$schema\":{},\"additionalProperties\":false,\"properties\":{{{}}},\
+         \"required\":[\"private\",\"public\"],\"title\":{},\"type\":\"object\"}}",
+        json_string(SCHEMA_DIALECT),
+        groups.join(","),
+        json_string(&function.name)
+    ))
+}
+
+/// Values of the parameters of `function` given by the witness input file `input`,
+/// in the order of the parameters.
+pub fn parse_witness_input(function: &Function, input: &str) -> WitnessResult<Vec<Value>> {
+    let json = JsonParser::new(input).parse()?;
+    let groups = match &json {
+        Json::Object(groups) => groups,
+        json => {
+            return Err(WitnessError::new(&format!(
+                "witness input of `{}` must be an object, found {}",
+                function.name,
+                json.kind()
+            )))
+        }
+    };
+    for (name, _) in groups {
+        if !GROUPS.contains(&name.as_str()) {
+            return Err(WitnessError::new(&format!(
+                "`{}`: expected `private` or `public`",
+                name
+            )));
+        }
+    }
+    if let Some(name) = GROUPS.iter().find(|name| json.get(name).is_none()) {
+        return Err(WitnessError::new(&format!("`{}`: missing", name)));
+    }
+    for (name, values) in groups {
+        let values = match values {
+            Json::Object(values) => values,
+            json => {
+                return Err(WitnessError::new(&format!(
+                    "`{}`: expected an object, found {}",
+                    name,
+                    json.kind()
+                )))
+            }
+        };
+        for (param, _) in values {
+            if !function
+                .params
+                .iter()
+                .any(|symbol| symbol.name == *param && group(symbol) == name)
+            {
+                return Err(WitnessError::new(&format!(
+                    "`{}.{}`: `{}` has no {} parameter `{}`",
+                    name, param, function.name, name, param
+                )));
+            }
+        }
+    }
+    function
+        .params
+        .iter()
+        .map(|param| {
+            let path = format!("{}.{}", group(param), param.name);
+            let value = json
+                .get(group(param))
+                .and_then(|values| values.get(&param.name))
+                .ok_or_else(|| WitnessError::new(&format!("`{}`: missing", path)))?;
+            witness_value(&param.symbol_type, value, &path)
+        })
+        .collect()
+}
+
+fn mismatch(typ: &SymbolType, json: &Json, path: &str) -> WitnessError {
+    WitnessError::new(&format!(
+        "`{}`: expected `{}`, found {}",
+        path,
+        typ,
+        json.kind()
+    ))
+}
+
+/// Integer written in decimal or hexadecimal, negative if `signed`.
+fn parse_integer(text: &str, signed: bool) -> Option<BigInt> {
+    let (negative, digits) = match text.strip_prefix('-') {
+        Some(digits) if signed => (true, digits),
+        Some(_) => return None,
+        None => (false, text),
+    };
+    let value = match digits.strip_prefix("0x") {
+        Some(hex) => BigInt::parse_bytes(hex.as_bytes(), 16)?,
+        None if !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit()) => {
+            BigInt::parse_bytes(digits.as_bytes(), 10)?
+        }
+        None => return None,
+    };
+    Some(if negative { -value } else { value })
+}
+
+fn parse_hex(text: &str) -> Option<Vec<u8>> {
+    let hex = text.strip_prefix("0x")?;
+    if hex.len() % 2 != 0 || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok())
+        .collect()
+}
+
+fn witness_value(typ: &SymbolType, json: &Json, path: &str) -> WitnessResult<Value> {
+    match (typ, json) {
+        (SymbolType::Bool, Json::Bool(value)) => Ok(Value::Bool(*value)),
+        (SymbolType::String, Json::String(value)) => Ok(Value::String(value.clone())),
+        (SymbolType::Field, Json::String(text)) => {
+            let value = parse_integer(text, false).ok_or_else(|| mismatch(typ, json, path))?;
+            if value >= BigInt::from(field_modulus()) {
+                return Err(out_of_range(typ, text, path));
+            }
+            Ok(Value::Int(value))
+        }
+        (_, Json::String(text)) if integer_bits(typ).is_some() => {
+            let (signed, bits) = integer_bits(typ).unwrap();
+            let value = parse_integer(text, signed).ok_or_else(|| mismatch(typ, json, path))?;
+            let bound = BigInt::one() << (bits as usize - signed as usize);
+            let min = if signed {
+                -bound.clone()
+            } else {
+                BigInt::zero()
+            };
+            if value < min || value >= bound {
+                return Err(out_of_range(typ, text, path));
+            }
+            Ok(Value::Int(value))
+        }
+        (SymbolType::Address | SymbolType::Bytes32, Json::String(text)) => match parse_hex(text) {
+            Some(bytes) if Some(bytes.len()) == byte_length(typ) => {
+                Ok(Value::Int(BigInt::from_bytes_be(Sign::Plus, &bytes)))
+            }
+            _ => Err(mismatch(typ, json, path)),
+        },
+        // Bytes are kept as written, like the literals of the interpreter
+        (SymbolType::Bytes, Json::String(text)) => match parse_hex(text) {
+            Some(_) => Ok(Value::String(text.clone())),
+            None => Err(mismatch(typ, json, path)),
+        },
+        (SymbolType::Array(item), Json::Array(items)) => items
+            .iter()
+            .enumerate()
+            .map(|(i, value)| witness_value(item, value, &format!("{}[{}]", path, i)))
+            .collect::<WitnessResult<_>>()
+            .map(Value::Array),
+        (SymbolType::FixedArray(item, length), Json::Array(items)) => {
+            if items.len() != *length {
+                return Err(WitnessError::new(&format!(
+                    "`{}`: expected {} items, found {}",
+                    path,
+                    length,
+                    items.len()
+                )));
+            }
+            witness_value(&SymbolType::Array(item.clone()), json, path)
+        }
+        _ => Err(mismatch(typ, json, path)),
+    }
+}
+
+fn out_of_range(typ: &SymbolType, text: &str, path: &str) -> WitnessError {
+    WitnessError::new(&format!(
+        "`{}`: {} is out of range of `{}`",
+        path, text, typ
+    ))
+}
+
+/// Value of a JSON document, with the members of objects in order.
+#[derive(Debug, Clone, PartialEq)]
+enum Json {
+    Null,
+    Bool(bool),
+    Number(String),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    fn kind(&self) -> &'static str {
+        match self {
+            Json::Null => "null",
+            Json::Bool(_) => "a boolean",
+            Json::Number(_) => "a number",
+            Json::String(_) => "a string",
+            Json::Array(_) => "an array",
+            Json::Object(_) => "an object",
+        }
+    }
+
+    /// Member `key` of an object.
+    fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Object(members) => members
+                .iter()
+                .find(|(name, _)| name == key)
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+}
+
+/// Depth of the arrays and objects of a JSON document past which it is rejected,
+/// before parsing them recursively overflows the stack. Parameters nest far less.
+const MAX_JSON_DEPTH: usize = 128;
+
+struct JsonParser<'a> {
+    text: &'a str,
+    position: usize,
+    /// Arrays and objects being parsed
+    depth: usize,
+}
+
+impl<'a> JsonParser<'a> {
+    fn new(text: &'a str) -> Self {
+        JsonParser {
+            text,
+            position: 0,
+            depth: 0,
+        }
+    }
+
+    fn parse(mut self) -> WitnessResult<Json> {
+        let value = self.value()?;
+        self.skip_whitespace();
+        if self.position < self.text.len() {
+            return Err(self.error("end of input"));
+        }
+        Ok(value)
+    }
+
+    fn error(&self, expected: &str) -> WitnessError {
+        WitnessError::new(&format!(
+            "invalid JSON at byte {}: expected {}",
+            self.position, expected
+        ))
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.text[self.position..].chars().next()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(c) = self.peek() {
+            if !c.is_ascii_whitespace() {
+                break;
+            }
+            self.position += 1;
+        }
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        self.skip_whitespace();
+        if self.peek() == Some(c) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, c: char) -> WitnessResult<()> {
+        if self.eat(c) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("`{}`", c)))
+        }
+    }
+
+    fn value(&mut self) -> WitnessResult<Json> {
+        self.skip_whitespace();
+        let rest = &self.text[self.position..];
+        for (keyword, value) in [
+            ("null", Json::Null),
+            ("true", Json::Bool(true)),
+            ("false", Json::Bool(false)),
+        ] {
+            if rest.starts_with(keyword) {
+                self.position += keyword.len();
+                return Ok(value);
+            }
+        }
+        match self.peek() {
+            Some(c @ ('{' | '[')) => {
+                if self.depth == MAX_JSON_DEPTH {
+                    return Err(WitnessError::new(&format!(
+                        "invalid JSON at byte {}: nested deeper than {} levels",
+                        self.position, MAX_JSON_DEPTH
+                    )));
+                }
+                self.depth += 1;
+                let value = if c == '{' {
+                    self.object()
+                } else {
+                    self.array()
+                };
+                self.depth -= 1;
+                value
+            }
+            Some('"') => self.string().map(Json::String),
+            Some(c) if c == '-' || c.is_ascii_digit() => {
+                let length = rest
+                    .find(|c: char| !(c.is_ascii_digit() || "+-.eE".contains(c)))
+                    .unwrap_or(rest.len());
+                self.position += length;
+                Ok(Json::Number(rest[..length].to_string()))
+            }
+            _ => Err(self.error("a value")),
+        }
+    }
+
+    fn object(&mut self) -> WitnessResult<Json> {
+        self.expect('{')?;
+        let mut members: Vec<(String, Json)> = vec![];
+        if self.eat('}') {
+            return Ok(Json::Object(members));
+        }
+        loop {
+            self.skip_whitespace();
+            let key = self.string()?;
+            if members.iter().any(|(name, _)| *name == key) {
+                return Err(WitnessError::new(&format!("duplicate key `{}`", key)));
+            }
+            self.expect(':')?;
+            members.push((key, self.value()?));
+            if self.eat('}') {
+                return Ok(Json::Object(members));
+            }
+            self.expect(',')?;
+        }
+    }
+
+    fn array(&mut self) -> WitnessResult<Json> {
+        self.expect('[')?;
+        let mut items = vec![];
+        if self.eat(']') {
+            return Ok(Json::Array(items));
+        }
+        loop {
+            items.push(self.value()?);
+            if self.eat(']') {
+                return Ok(Json::Array(items));
+            }
+            self.expect(',')?;
+        }
+    }
+
+    fn string(&mut self) -> WitnessResult<String> {
+        if self.peek() != Some('"') {
+            return Err(self.error("a string"));
+        }
+        self.position += 1;
+        let mut value = String::new();
+        loop {
+            let c = self.peek().ok_or_else(|| self.error("`\"`"))?;
+            self.position += c.len_utf8();
+            match c {
+                '"' => return Ok(value),
+                '\\' => {
+                    let escape = self.peek().ok_or_else(|| self.error("an escape"))?;
+                    self.position += 1;
+                    value.push(match escape {
+                        '"' => '"',
+                        '\\' => '\\',
+                        '/' => '/',
+                        'b' => '\u{8}',
+                        'f' => '\u{c}',
+                        'n' => '\n',
+                        'r' => '\r',
+                        't' => '\t',
+                        'u' => {
+                            let code = self
+                                .text
+                                .get(self.position..self.position + 4)
+                                .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                                .and_then(std::char::from_u32)
+                                .ok_or_else(|| self.error("a unicode escape"))?;
+                            self.position += 4;
+                            code
+                        }
+                        _ => return Err(self.error("an escape")),
+                    });
+                }
+                c => value.push(c),
+            }
+        }
+    }
+}
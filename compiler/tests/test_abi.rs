@@ -0,0 +1,160 @@
+use num_bigint::{BigInt, BigUint};
+use zoker_compiler::abi::{
+    decode, decode_return, encode, encode_call, encode_packed, encode_with_selector, event_topic,
+    signature, AbiValue,
+};
+use zoker_compiler::driver::Compiler;
+use zoker_compiler::symbol::SymbolType;
+
+fn word(last: u8) -> Vec<u8> {
+    let mut word = vec![0; 31];
+    word.push(last);
+    word
+}
+
+#[test]
+fn test_encode_static_values() {
+    let encoded = encode(&[
+        AbiValue::Uint(BigUint::from(1u32)),
+        AbiValue::Bool(true),
+        AbiValue::Int(BigInt::from(-1)),
+    ])
+    .unwrap();
+    let mut expected = word(1);
+    expected.extend(word(1));
+    expected.extend(vec![0xff; 32]);
+    assert_eq!(encoded, expected);
+
+    // Every static value takes a full word, even when empty
+    let encoded = encode(&[AbiValue::Bytes32(vec![]), AbiValue::Bool(true)]).unwrap();
+    let mut expected = word(0);
+    expected.extend(word(1));
+    assert_eq!(encoded, expected);
+
+    let wide = AbiValue::Uint(BigUint::from(1u32) << 256);
+    assert_eq!(
+        encode(&[wide]).unwrap_err().error,
+        "`uint256` value is wider than 256 bits"
+    );
+}
+
+#[test]
+fn test_encode_dynamic_values() {
+    let values = vec![
+        AbiValue::Uint(BigUint::from(1u32)),
+        AbiValue::String(String::from("a")),
+    ];
+    let encoded = encode(&values).unwrap();
+    let mut expected = word(1);
+    expected.extend(word(0x40));
+    expected.extend(word(1));
+    expected.push(b'a');
+    expected.extend(vec![0; 31]);
+    assert_eq!(encoded, expected);
+
+    let decoded = decode(&[SymbolType::Uint256, SymbolType::String], &encoded).unwrap();
+    assert_eq!(decoded, values);
+}
+
+#[test]
+fn test_encode_packed_and_selector() {
+    let encoded = encode_packed(&[AbiValue::Bool(true), AbiValue::Bytes(vec![1, 2])]).unwrap();
+    assert_eq!(encoded, vec![1, 1, 2]);
+
+    let encoded =
+        encode_with_selector(&[0xa9, 0x05, 0x9c, 0xbb], &[AbiValue::Bool(false)]).unwrap();
+    assert_eq!(encoded.len(), 36);
+    assert_eq!(encoded[..4], [0xa9, 0x05, 0x9c, 0xbb]);
+}
+
+#[test]
+fn test_decode_short_data() {
+    assert!(decode(&[SymbolType::Uint256], &[0; 16]).is_err());
+}
+
+#[test]
+fn test_decode_dirty_padding() {
+    let error = |typ: SymbolType, data: &[u8]| decode(&[typ], data).unwrap_err().error;
+    let mut data = word(0xff);
+    assert_eq!(
+        decode(&[SymbolType::Uint(8)], &data).unwrap(),
+        vec![AbiValue::Uint(BigUint::from(0xffu32))]
+    );
+    data[30] = 1;
+    assert_eq!(
+        error(SymbolType::Uint(8), &data),
+        "`uint8` value is not padded with zeros"
+    );
+    assert_eq!(
+        error(SymbolType::Int(8), &data),
+        "`int8` value is not padded with zeros"
+    );
+    assert_eq!(
+        error(SymbolType::Bool, &word(2)),
+        "`bool` value is not padded with zeros"
+    );
+    let mut data = word(1);
+    data[0] = 1;
+    assert_eq!(
+        error(SymbolType::Address, &data),
+        "`address` value is not padded with zeros"
+    );
+}
+
+#[test]
+fn test_decode_crafted_offsets() {
+    // Offset and length near `usize::MAX` must not overflow
+    let mut data = vec![0xff; 32];
+    assert!(decode(&[SymbolType::Bytes], &data).is_err());
+    data[..24].fill(0);
+    assert!(decode(&[SymbolType::Bytes], &data).is_err());
+    let mut data = vec![0; 64];
+    data[31] = 32;
+    data[56..].fill(0xff);
+    assert!(decode(&[SymbolType::String], &data).is_err());
+}
+
+#[test]
+fn test_canonical_signature() {
+    let params = vec![
+        SymbolType::Uint256,
+        SymbolType::Array(Box::new(SymbolType::Int256)),
+        SymbolType::Bytes32,
+    ];
+    assert_eq!(signature("Log", &params), "Log(uint256,int256[],bytes32)");
+    assert_eq!(signature("Ping", &[]), "Ping()");
+    // The selector of a function is the start of the same hash
+    assert_eq!(
+        event_topic("balanceOf(address)")[..4],
+        [0x70, 0xa0, 0x82, 0x31]
+    );
+}
+
+#[test]
+fn test_encode_call() {
+    let source = "contract Token {\
+           function transfer(address to, uint amount) returns (uint) { return amount; }\
+        }";
+    let mut compiler = Compiler::new();
+    let artifact = compiler
+        .compile(source)
+        .unwrap()
+        .artifacts
+        .unwrap()
+        .remove(0);
+    let args = vec![
+        AbiValue::Address(vec![0x11; 20]),
+        AbiValue::Uint(BigUint::from(5u32)),
+    ];
+    let calldata = encode_call(&artifact, "transfer", &args).unwrap();
+    assert_eq!(calldata[..4], [0xa9, 0x05, 0x9c, 0xbb]);
+    assert_eq!(calldata[4..], encode(&args).unwrap()[..]);
+    assert_eq!(
+        encode_call(&artifact, "mint", &args).unwrap_err().error,
+        "`Token` has no function `mint`"
+    );
+    assert!(encode_call(&artifact, "transfer", &args[..1]).is_err());
+
+    let returned = decode_return(&artifact, "transfer", &word(1)).unwrap();
+    assert_eq!(returned, vec![AbiValue::Uint(BigUint::from(1u32))]);
+}
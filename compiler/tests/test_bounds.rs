@@ -0,0 +1,83 @@
+use zoker_compiler::bounds::eliminate_bounds_checks;
+use zoker_compiler::rewriter::{rewrite_program, rewrite_program_with, RewriteSettings};
+use zoker_compiler::symbol::{Contract, Operation, OperationType};
+use zoker_parser::parser;
+
+fn compile(source: &str) -> Contract {
+    let program = parser::parse_program(source).unwrap();
+    rewrite_program(&program).unwrap().remove(0)
+}
+
+fn count_checks(operations: &[Operation]) -> usize {
+    operations
+        .iter()
+        .map(|op| match &op.operation {
+            OperationType::BoundsCheck { .. } => 1,
+            _ => count_checks(&op.children().into_iter().cloned().collect::<Vec<_>>()),
+        })
+        .sum()
+}
+
+#[test]
+fn test_bounds_checks_are_inserted() {
+    let contract = compile(
+        "contract Test {\
+           function get(uint[] values, uint i) returns (uint) {\
+             values[i] = values[i] + values[0];\
+             unchecked { values[i + 1]; };\
+             return values[i];\
+           }\
+        }",
+    );
+    let operations = &contract.functions[0].operations;
+    assert_eq!(count_checks(operations), 3);
+    assert!(matches!(
+        operations[0].operation,
+        OperationType::BoundsCheck { .. }
+    ));
+    assert!(matches!(
+        operations[2].operation,
+        OperationType::Assign { .. }
+    ));
+    assert!(matches!(
+        operations[3].operation,
+        OperationType::Index { .. }
+    ));
+    assert!(matches!(
+        operations[4].operation,
+        OperationType::BoundsCheck { .. }
+    ));
+}
+
+#[test]
+fn test_bounds_checks_setting() {
+    let source = "contract Test {\
+           function get(uint[] values) returns (uint) {\
+             return values[1];\
+           }\
+        }";
+    let program = parser::parse_program(source).unwrap();
+    let settings = RewriteSettings {
+        bounds_checks: false,
+    };
+    let contracts = rewrite_program_with(&program, &settings).unwrap();
+    assert_eq!(count_checks(&contracts[0].functions[0].operations), 0);
+}
+
+#[test]
+fn test_proven_bounds_checks_are_removed() {
+    let mut contract = compile(
+        "contract Test {\
+           function get(uint[] values, uint i) returns (uint) {\
+             uint a = values[3];\
+             uint b = values[2] + values[1];\
+             uint c = values[i];\
+             values = values;\
+             return values[1];\
+           }\
+        }",
+    );
+    assert_eq!(count_checks(&contract.functions[0].operations), 5);
+    assert_eq!(eliminate_bounds_checks(&mut contract), 2);
+    assert_eq!(count_checks(&contract.functions[0].operations), 3);
+}
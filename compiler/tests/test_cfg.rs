@@ -0,0 +1,82 @@
+use zoker_compiler::cfg::{contract_cfgs, ControlFlowGraph, Terminator};
+use zoker_compiler::rewriter::rewrite_program;
+use zoker_parser::parser;
+
+fn cfgs(source: &str) -> Vec<ControlFlowGraph> {
+    let program = parser::parse_program(source).unwrap();
+    contract_cfgs(&rewrite_program(&program).unwrap()[0])
+}
+
+#[test]
+fn test_straight_line_cfg() {
+    let cfg = cfgs(
+        "contract Test {\
+           function add(uint a, uint b) returns (uint) {\
+             uint c = a + b;\
+             return c;\
+           }\
+        }",
+    )
+    .remove(0);
+    assert_eq!(cfg.function, "add");
+    assert_eq!(cfg.blocks.len(), 2);
+    assert_eq!(cfg.edges(), vec![(cfg.entry, cfg.exit)]);
+    assert!(matches!(
+        cfg.block(cfg.entry).terminator,
+        Terminator::Return(Some(_))
+    ));
+    assert_eq!(cfg.block(cfg.exit).terminator, Terminator::Exit);
+}
+
+#[test]
+fn test_branch_and_loop_cfg() {
+    let cfg = cfgs(
+        "contract Test {\
+           function f(uint a, bool c) returns (uint) {\
+             if c { a = a + 1; } else { return a; };\
+             for i in a { a = a + i; };\
+             return a;\
+           }\
+        }",
+    )
+    .remove(0);
+    let then = match &cfg.block(cfg.entry).terminator {
+        Terminator::Branch {
+            then, otherwise, ..
+        } => {
+            assert_eq!(cfg.successors(*otherwise), vec![cfg.exit]);
+            *then
+        }
+        terminator => panic!("unexpected terminator {:?}", terminator),
+    };
+    let join = cfg.successors(then)[0];
+    let header = cfg.successors(join)[0];
+    let (body, exit) = match &cfg.block(header).terminator {
+        Terminator::Loop { body, exit, .. } => (*body, *exit),
+        terminator => panic!("unexpected terminator {:?}", terminator),
+    };
+    // The else arm returns, so only the then arm reaches the join
+    assert_eq!(cfg.predecessors(join), vec![then]);
+    assert_eq!(cfg.predecessors(header), vec![join, body]);
+    assert_eq!(cfg.successors(body), vec![header]);
+    assert_eq!(cfg.successors(exit), vec![cfg.exit]);
+}
+
+#[test]
+fn test_cfg_to_dot() {
+    let cfg = cfgs(
+        "contract Test {\
+           function f(uint a, bool c) returns (uint) {\
+             if c { a = a + 1; };\
+             return a;\
+           }\
+        }",
+    )
+    .remove(0);
+    let dot = cfg.to_dot();
+    assert!(dot.starts_with("digraph \"f\" {\n"));
+    assert!(dot.contains("b0 -> b1 [label=\"true\"];"));
+    assert!(dot.contains("b0 -> b2 [label=\"false\"];"));
+    assert!(dot.contains("a = (a + 1)\\l"));
+    assert!(dot.ends_with("}\n"));
+}
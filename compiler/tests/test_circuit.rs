@@ -0,0 +1,48 @@
+use zoker_compiler::circuit::{circuit_warnings, PrivateControlFlow};
+use zoker_parser::parser;
+
+#[test]
+fn test_circuit_warnings() {
+    let source = "contract Vote {\
+           function cast(private uint choice, uint round) returns (uint) {\
+             uint weight = choice * 2;\
+             if weight { round = round + 1; };\
+             if round { round = 0; };\
+             if choice { return 1; };\
+             return round;\
+           }\
+           function tally(private uint[] votes, uint[] rounds, private uint n) {\
+             uint total = 0;\
+             for vote in votes { total = total + vote; };\
+             for round in rounds { total = total + round; };\
+             do { n -= 1; } while (n);\
+           }\
+           function open(uint round) { if round { round = 0; }; }\
+         }\
+         function select(private uint c, uint a) returns (uint) {\
+           return c * a + (1 - c) * a;\
+         }";
+    let program = parser::parse_program(source).unwrap();
+    let warnings = circuit_warnings(&program);
+    let flows: Vec<(&str, PrivateControlFlow, Vec<&str>)> = warnings
+        .iter()
+        .map(|warning| {
+            let sources = warning.sources.iter().map(String::as_str).collect();
+            (warning.function.as_str(), warning.control_flow, sources)
+        })
+        .collect();
+    assert_eq!(
+        flows,
+        vec![
+            ("Vote.cast", PrivateControlFlow::Branch, vec!["choice"]),
+            ("Vote.cast", PrivateControlFlow::EarlyReturn, vec!["choice"]),
+            ("Vote.tally", PrivateControlFlow::LoopBound, vec!["votes"]),
+            ("Vote.tally", PrivateControlFlow::LoopBound, vec!["n"]),
+        ]
+    );
+    assert_eq!(
+        warnings[0].to_string(),
+        "branch in `Vote.cast` depends on private `choice` and can't be expressed in a circuit: \
+         compute both branches and select the result, e.g. `c * a + (1 - c) * b`"
+    );
+}
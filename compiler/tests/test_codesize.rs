@@ -0,0 +1,91 @@
+use num_bigint::BigUint;
+use zoker_compiler::codesize::{
+    check_code_size, contract_size, operation_size, push_size, MAX_RUNTIME_SIZE,
+};
+use zoker_compiler::rewriter::rewrite_program;
+use zoker_compiler::settings::EvmVersion;
+use zoker_compiler::symbol::{Contract, Function, Operation, OperationType, Symbol, SymbolType};
+use zoker_parser::parser;
+
+fn compile(source: &str) -> Contract {
+    let program = parser::parse_program(source).unwrap();
+    rewrite_program(&program).unwrap().remove(0)
+}
+
+#[test]
+fn test_push_size() {
+    assert_eq!(push_size(&BigUint::from(0u32), EvmVersion::Cancun), 1);
+    // `PUSH1 0` before Shanghai
+    assert_eq!(push_size(&BigUint::from(0u32), EvmVersion::London), 2);
+    assert_eq!(push_size(&BigUint::from(255u32), EvmVersion::Cancun), 2);
+    assert_eq!(push_size(&BigUint::from(256u32), EvmVersion::Cancun), 3);
+    assert_eq!(
+        push_size(&((BigUint::from(1u32) << 256) - 1u32), EvmVersion::Cancun),
+        33
+    );
+}
+
+#[test]
+fn test_contract_size() {
+    let contract = compile(
+        "contract Test {\
+           function small(uint a) returns (uint) { return a; }\
+           function large(uint a, bool c) returns (uint) {\
+             uint b = a * 1000;\
+             if c { b = b + a; };\
+             return b;\
+           }\
+        }",
+    );
+    let size = contract_size(&contract, EvmVersion::Cancun);
+    let names: Vec<&str> = size
+        .functions
+        .iter()
+        .map(|(name, _)| name.as_str())
+        .collect();
+    assert_eq!(names, vec!["large", "small"]);
+    let total: usize = size.functions.iter().map(|(_, size)| size).sum();
+    assert!(size.runtime > total);
+    assert!(size.creation > size.runtime);
+    assert!(check_code_size(&size).is_none());
+
+    let contract = compile("contract Id { function id(uint a) returns (uint) { return a; } }");
+    assert_eq!(
+        contract_size(&contract, EvmVersion::Cancun).to_string(),
+        "Id: creation 37 bytes, runtime 25 bytes\n  id: 20 bytes\n"
+    );
+    assert_eq!(
+        contract_size(&contract, EvmVersion::London).to_string(),
+        "Id: creation 42 bytes, runtime 28 bytes\n  id: 22 bytes\n"
+    );
+}
+
+#[test]
+fn test_size_limit_warning() {
+    // Each assignment of a 32-byte constant takes 35 bytes
+    let target = Symbol::new_type_symbol(SymbolType::Uint256);
+    let assign = Operation::new(OperationType::Assign {
+        left: Box::new(Operation::new_symbol(Symbol {
+            name: "x".to_string(),
+            ..target
+        })),
+        right: Box::new(Operation::new(OperationType::Constant {
+            value: (BigUint::from(1u32) << 255) + 1u32,
+        })),
+    });
+    assert_eq!(operation_size(&assign, EvmVersion::Cancun), 35);
+
+    let mut contract = Contract::new("Big".to_string());
+    for (name, count) in &[("huge", 601), ("medium", 100), ("tiny", 1)] {
+        let mut function = Function::new(name.to_string(), vec![], vec![]);
+        function.add_operations(vec![assign.clone(); *count]);
+        contract.add_function(function);
+    }
+    let size = contract_size(&contract, EvmVersion::Cancun);
+    assert!(size.runtime > MAX_RUNTIME_SIZE);
+    let warning = check_code_size(&size).unwrap();
+    assert_eq!(warning.largest[0].0, "huge");
+    assert!(warning
+        .to_string()
+        .starts_with("runtime code of `Big` is 24611 bytes, over the EIP-170 limit of 24576 bytes, largest functions: `huge` (21047 bytes)"));
+}
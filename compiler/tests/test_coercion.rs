@@ -0,0 +1,206 @@
+use num_bigint::BigInt;
+use zoker_compiler::coercion::{
+    common_type, implicit_conversion, literal_conversion, ConversionErrorKind,
+};
+use zoker_compiler::error::SymbolTableErrorType;
+use zoker_compiler::symbol::SymbolType;
+use zoker_compiler::symbol_table::make_symbol_table;
+use zoker_parser::parser;
+
+#[test]
+fn test_integer_conversions() {
+    let kind = |from, to| implicit_conversion(&from, &to).map_err(|error| error.kind);
+    assert_eq!(kind(SymbolType::Uint(8), SymbolType::Uint(16)), Ok(()));
+    assert_eq!(kind(SymbolType::Uint(8), SymbolType::Uint256), Ok(()));
+    assert_eq!(kind(SymbolType::Int(64), SymbolType::Int256), Ok(()));
+    assert_eq!(
+        kind(SymbolType::Uint(16), SymbolType::Uint(8)),
+        Err(ConversionErrorKind::Narrowing)
+    );
+    assert_eq!(
+        kind(SymbolType::Uint(8), SymbolType::Int256),
+        Err(ConversionErrorKind::Signedness)
+    );
+    assert_eq!(
+        kind(SymbolType::Bool, SymbolType::Uint256),
+        Err(ConversionErrorKind::Incompatible)
+    );
+    assert_eq!(
+        implicit_conversion(&SymbolType::Int256, &SymbolType::Uint(8))
+            .unwrap_err()
+            .to_string(),
+        "cannot implicitly convert `int` to `uint8`: signed and unsigned integers don't mix"
+    );
+
+    assert_eq!(
+        common_type(&SymbolType::Uint(8), &SymbolType::Uint(32)),
+        Ok(SymbolType::Uint(32))
+    );
+    assert_eq!(
+        common_type(&SymbolType::Uint256, &SymbolType::Uint(32)),
+        Ok(SymbolType::Uint256)
+    );
+    assert!(common_type(&SymbolType::Int(8), &SymbolType::Uint(8)).is_err());
+}
+
+#[test]
+fn test_literal_conversions() {
+    let fits = |value: i64, to| literal_conversion(&BigInt::from(value), &to).is_ok();
+    assert!(fits(255, SymbolType::Uint(8)));
+    assert!(!fits(256, SymbolType::Uint(8)));
+    assert!(fits(127, SymbolType::Int(8)));
+    assert!(!fits(128, SymbolType::Int(8)));
+    assert!(fits(-128, SymbolType::Int(8)));
+    assert!(!fits(-129, SymbolType::Int(8)));
+    assert!(!fits(-1, SymbolType::Uint256));
+    assert!(fits(i64::MAX, SymbolType::Uint256));
+    assert!(!fits(1, SymbolType::Bool));
+    assert_eq!(
+        literal_conversion(&BigInt::from(300), &SymbolType::Uint(8))
+            .unwrap_err()
+            .to_string(),
+        "cannot implicitly convert `300` to `uint8`: the value is out of range"
+    );
+}
+
+fn type_error(source: &str) -> String {
+    let program = parser::parse_program(source).unwrap();
+    match make_symbol_table(&program).unwrap_err().error {
+        SymbolTableErrorType::TypeError(message) => message,
+        error => panic!("unexpected error {:?}", error),
+    }
+}
+
+#[test]
+fn test_conversions_in_type_checker() {
+    let source = "contract Test {\
+           function f(uint8 a, uint16 b, uint c) returns (uint) {\
+             uint32 d = a;\
+             c = b + d;\
+             uint8 e = 255;\
+             return c;\
+           }\
+        }";
+    let program = parser::parse_program(source).unwrap();
+    assert!(make_symbol_table(&program).is_ok());
+
+    assert_eq!(
+        type_error("contract Test { function f(uint16 a) { uint8 b = a; } }"),
+        "cannot implicitly convert `uint16` to `uint8`: narrowing conversion may lose bits"
+    );
+    assert_eq!(
+        type_error("contract Test { function f(uint8 a) { a = 256; } }"),
+        "cannot implicitly convert `256` to `uint8`: the value is out of range"
+    );
+    assert_eq!(
+        type_error("contract Test { function f(int a, uint b) { a + b; } }"),
+        "operands of types `int` and `uint` don't mix: signed and unsigned integers don't mix"
+    );
+    assert_eq!(
+        type_error("contract Test { uint8[] values; function f(uint16 a) { values.push(a); } }"),
+        "`uint8[].push` expects `uint8` for argument 1 but `uint16` was given \
+         (narrowing conversion may lose bits)"
+    );
+}
+
+#[test]
+fn test_conversions_in_calls() {
+    let source = "function free(uint8 a) returns (uint8) { return a; }\
+        contract Test {\
+           function f(uint8 a) returns (uint16) {\
+             g(255, a);\
+             return free(g(1, a));\
+           }\
+           function g(uint16 a, uint8 b) returns (uint8) { return b; }\
+        }";
+    let program = parser::parse_program(source).unwrap();
+    assert!(make_symbol_table(&program).is_ok());
+
+    assert_eq!(
+        type_error("contract Test { function f() { g(1); } function g() { } }"),
+        "`g` takes 0 argument(s) but 1 were given"
+    );
+    assert_eq!(
+        type_error("contract Test { function f() { g(300); } function g(uint8 a) { } }"),
+        "`g` expects `uint8` for argument 1 but `300` was given (the value is out of range)"
+    );
+    assert_eq!(
+        type_error("function g(bool a) { } contract Test { function f(uint8 a) { g(a); } }"),
+        "`g` expects `bool` for argument 1 but `uint8` was given"
+    );
+    assert_eq!(
+        type_error(
+            "contract Test {\
+               function f() returns (uint8) { return g(); }\
+               function g() returns (bool) { }\
+             }"
+        ),
+        "cannot implicitly convert `bool` to `uint8`"
+    );
+}
+
+#[test]
+fn test_untyped_integer_literals() {
+    let source = "contract Test {\
+           function f(uint8 a, int8 b) returns (uint8) {\
+             uint8 c = 2 ** 8 - 1;\
+             int8 d = -128;\
+             b = b + -1;\
+             int e = -1;\
+             a == 255;\
+             (658 - 910) <= (884 % 186);\
+             return a + 1;\
+           }\
+        }";
+    let program = parser::parse_program(source).unwrap();
+    assert!(make_symbol_table(&program).is_ok());
+
+    // The context forces a value out of range
+    assert_eq!(
+        type_error("contract Test { function f(uint8 a) { uint8 b = 2 ** 8; } }"),
+        "cannot implicitly convert `256` to `uint8`: the value is out of range"
+    );
+    assert_eq!(
+        type_error("contract Test { function f(uint8 a) { a < 300; } }"),
+        "cannot implicitly convert `300` to `uint8`: the value is out of range"
+    );
+    assert_eq!(
+        type_error("contract Test { function f(uint a) { a = -1; } }"),
+        "cannot implicitly convert `-1` to `uint`: the value is out of range"
+    );
+    // Constant expressions are evaluated before they are converted
+    assert_eq!(
+        type_error("contract Test { function f(uint a) { uint b = 1 - 2; } }"),
+        "cannot implicitly convert `-1` to `uint`: the value is out of range"
+    );
+    assert_eq!(
+        type_error("contract Test { uint8[] values; function f() { values.push(256); } }"),
+        "`uint8[].push` expects `uint8` for argument 1 but `256` was given \
+         (the value is out of range)"
+    );
+}
+
+#[test]
+fn test_string_literal_conversions() {
+    let source = "contract Test {\
+           function f() {\
+             string s = \"hello\";\
+             bytes b = \"x\";\
+             bytes32 h = \"0123456789abcdef0123456789abcdef\";\
+           }\
+        }";
+    let program = parser::parse_program(source).unwrap();
+    assert!(make_symbol_table(&program).is_ok());
+
+    assert_eq!(
+        type_error("contract Test { function f() { uint u = \"no\"; } }"),
+        "cannot implicitly convert `\"no\"` to `uint`"
+    );
+    assert_eq!(
+        type_error(
+            "contract Test { function f() { bytes32 h = \"0123456789abcdef0123456789abcdef!\"; } }"
+        ),
+        "cannot implicitly convert `\"0123456789abcdef0123456789abcdef!\"` to `bytes32`: \
+         the value is out of range"
+    );
+}
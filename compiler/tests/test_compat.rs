@@ -0,0 +1,98 @@
+use zoker_compiler::compat::{compare_artifacts, compare_programs, BreakingChange};
+use zoker_compiler::driver::Compiler;
+use zoker_parser::parser;
+
+const OLD: &str = "contract Token {\
+       uint total;\
+       address owner;\
+       bool paused;\
+       function mint(uint amount) returns (uint) { total = total + amount; return total; }\
+       function burn(uint amount) { total = total - amount; }\
+       function supply() returns (uint) { return total; }\
+     }\
+     contract Legacy { }";
+
+fn compare(old: &str, new: &str) -> Vec<String> {
+    let old = parser::parse_program(old).unwrap();
+    let new = parser::parse_program(new).unwrap();
+    compare_programs(&old, &new)
+        .unwrap()
+        .iter()
+        .map(|change| change.to_string())
+        .collect()
+}
+
+#[test]
+fn test_compatible_upgrade() {
+    // New functions and state variables appended after the old ones
+    let new = "contract Token {\
+           uint256 total;\
+           address owner;\
+           bool paused;\
+           uint fee;\
+           function mint(uint256 amount) returns (uint) { total = total + amount; return total; }\
+           function burn(uint amount) { total = total - amount; }\
+           function supply() returns (uint) { return total; }\
+           function setFee(uint value) { fee = value; }\
+         }\
+         contract Legacy { }";
+    assert!(compare(OLD, new).is_empty());
+}
+
+#[test]
+fn test_breaking_changes() {
+    let new = "contract Token {\
+           bool paused;\
+           uint total;\
+           function mint(uint amount, address to) returns (uint) { total = total + amount; return total; }\
+           function supply() returns (bool) { return paused; }\
+         }";
+    assert_eq!(
+        compare(OLD, new),
+        vec![
+            "`Token.mint(uint256)` became `mint(uint256,address)`",
+            "`Token.burn(uint256)` was removed",
+            "`Token.supply` returns `(bool)` instead of `(uint256)`",
+            "state variable `Token.total` moved from uint256 at slot 0 offset 0 to uint256 at slot 1 offset 0",
+            "state variable `Token.owner` was removed",
+            "state variable `Token.paused` moved from bool at slot 1 offset 20 to bool at slot 0 offset 0",
+            "contract `Legacy` was removed",
+        ]
+    );
+}
+
+#[test]
+fn test_compare_artifacts() {
+    let artifact = |source: &str| {
+        let compilation = Compiler::new().compile(source).unwrap();
+        compilation.artifacts.unwrap().remove(0)
+    };
+    let old = artifact("contract Test { function f(uint x) returns (uint) { return x; } }");
+    let new =
+        artifact("contract Test { function f(uint x, uint y) returns (uint) { return x + y; } }");
+    assert!(compare_artifacts(&old, &old).is_empty());
+    assert_eq!(
+        compare_artifacts(&old, &new),
+        vec![BreakingChange::ChangedParameters {
+            contract: "Test".to_string(),
+            old: "f(uint256)".to_string(),
+            new: "f(uint256,uint256)".to_string(),
+        }]
+    );
+}
+
+#[test]
+fn test_getter_changes() {
+    let old = "contract Token { mapping(address => uint) public balances; uint public supply; }";
+    let new = "contract Token { mapping(address => uint) public balances; uint supply; }";
+    assert_eq!(compare(old, new), vec!["`Token.supply()` was removed"]);
+    let new = "contract Token { mapping(uint => uint) public balances; uint public supply; }";
+    assert_eq!(
+        compare(old, new),
+        vec![
+            "`Token.balances(address)` became `balances(uint256)`",
+            "state variable `Token.balances` moved from mapping(address => uint256) at slot 0 \
+             offset 0 to mapping(uint256 => uint256) at slot 0 offset 0",
+        ]
+    );
+}
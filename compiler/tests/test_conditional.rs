@@ -0,0 +1,96 @@
+use std::collections::BTreeSet;
+use zoker_compiler::conditional::{is_enabled, strip_disabled, Defines};
+use zoker_compiler::driver::Compiler;
+use zoker_compiler::symbol_table::make_symbol_table;
+use zoker_parser::ast::CfgPredicate;
+use zoker_parser::parser;
+
+const SOURCE: &str = "#[cfg(feature = \"testnet\")]\
+    function faucet() returns (uint) { return 1; }\
+    contract Token {\
+      #[cfg(debug)]\
+      uint counter;\
+      #[cfg(not(debug))]\
+      function fee() returns (uint) { return 2; }\
+      #[cfg(all(debug, feature = \"testnet\"))]\
+      #[cfg(any(trace, debug))]\
+      function fee() returns (uint) { return faucet() + counter; }\
+    }\
+    #[cfg(feature = \"mainnet\")]\
+    contract Mainnet { }";
+
+#[test]
+fn test_cfg_predicates() {
+    let flag = |name: &str| CfgPredicate::Flag(name.to_string());
+    let defines = Defines {
+        flags: BTreeSet::from(["debug".to_string()]),
+        ..Defines::with_features(&["testnet"])
+    };
+    assert!(is_enabled(&flag("debug"), &defines));
+    assert!(!is_enabled(&flag("trace"), &defines));
+    assert!(is_enabled(
+        &CfgPredicate::Value {
+            name: "feature".to_string(),
+            value: "testnet".to_string()
+        },
+        &defines
+    ));
+    assert!(is_enabled(
+        &CfgPredicate::Any(vec![flag("trace"), flag("debug")]),
+        &defines
+    ));
+    assert!(!is_enabled(
+        &CfgPredicate::All(vec![flag("trace"), flag("debug")]),
+        &defines
+    ));
+    assert!(is_enabled(
+        &CfgPredicate::Not(Box::new(flag("trace"))),
+        &defines
+    ));
+    // `all()` holds and `any()` doesn't, as in Rust
+    assert!(is_enabled(&CfgPredicate::All(vec![]), &defines));
+    assert!(!is_enabled(&CfgPredicate::Any(vec![]), &defines));
+}
+
+#[test]
+fn test_strip_disabled() {
+    // Without defines, the `fee` without `counter` remains
+    let mut program = parser::parse_program(SOURCE).unwrap();
+    assert_eq!(strip_disabled(&mut program, &Defines::default()), 4);
+    let table = make_symbol_table(&program).unwrap();
+    assert!(table.lookup("faucet").is_none());
+    assert!(table.contract_table("Mainnet").is_none());
+    assert!(table
+        .contract_table("Token")
+        .unwrap()
+        .lookup("counter")
+        .is_none());
+
+    let defines = Defines {
+        flags: BTreeSet::from(["debug".to_string()]),
+        ..Defines::with_features(&["testnet"])
+    };
+    let mut program = parser::parse_program(SOURCE).unwrap();
+    assert_eq!(strip_disabled(&mut program, &defines), 2);
+    let table = make_symbol_table(&program).unwrap();
+    assert!(table.lookup("faucet").is_some());
+    assert!(table
+        .contract_table("Token")
+        .unwrap()
+        .lookup("counter")
+        .is_some());
+}
+
+#[test]
+fn test_compiler_defines() {
+    let mut compiler = Compiler::new();
+    compiler.settings.defines = Defines::with_features(&["mainnet"]);
+    let compilation = compiler.compile(SOURCE).unwrap();
+    let names: Vec<String> = compilation
+        .artifacts
+        .unwrap()
+        .iter()
+        .map(|artifact| artifact.contract.name.clone())
+        .collect();
+    assert_eq!(names, vec!["Token", "Mainnet"]);
+}
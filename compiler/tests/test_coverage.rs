@@ -0,0 +1,63 @@
+use num_bigint::BigInt;
+use zoker_compiler::coverage::instrument;
+use zoker_compiler::interpreter::{Interpreter, Value};
+use zoker_compiler::test_runner::run_program_tests_with_coverage;
+use zoker_parser::parser;
+
+const SOURCE: &str = "function pick(uint x) returns (uint) {\n\
+       uint y = x > 2 ? 1 : 0;\n\
+       if y == 1 {\n\
+         return 10;\n\
+       };\n\
+       return 20;\n\
+     }\n\
+     @test\n\
+     function test_pick() { assert(pick(5) == 10); }";
+
+#[test]
+fn test_interpreter_coverage() {
+    let program = parser::parse_program(SOURCE).unwrap();
+    let coverage = instrument(&program);
+    assert_eq!(
+        coverage.lines.keys().copied().collect::<Vec<_>>(),
+        vec![1, 2, 3, 5, 8]
+    );
+    assert_eq!(coverage.branch_count(), 4);
+
+    let mut interpreter = Interpreter::new(&program);
+    interpreter.set_coverage(coverage);
+    let result = interpreter.call("pick", vec![Value::Int(BigInt::from(3))]);
+    assert_eq!(result, Ok(Value::Int(BigInt::from(10))));
+    let mut coverage = interpreter.coverage().unwrap().clone();
+    assert_eq!(coverage.lines[&3], 1);
+    assert_eq!(coverage.lines[&5], 0);
+    assert_eq!(coverage.branches_hit(), 2);
+    let lcov = coverage.to_lcov("pick.zok");
+    assert!(lcov.starts_with("TN:\nSF:pick.zok\n"));
+    assert!(lcov.contains("DA:4,1\n"));
+    assert!(lcov.contains("DA:6,0\n"));
+    assert!(lcov.contains("BRF:4\nBRH:2\n"));
+    assert!(lcov.contains("LF:5\nLH:3\n"));
+    assert!(lcov.ends_with("end_of_record\n"));
+
+    let mut interpreter = Interpreter::new(&program);
+    interpreter.set_coverage(instrument(&program));
+    interpreter
+        .call("pick", vec![Value::Int(BigInt::from(1))])
+        .unwrap();
+    coverage.merge(interpreter.coverage().unwrap());
+    assert_eq!(coverage.lines[&1], 2);
+    assert_eq!(coverage.lines[&5], 1);
+    assert_eq!(coverage.branches_hit(), 4);
+}
+
+#[test]
+fn test_tests_coverage() {
+    let program = parser::parse_program(SOURCE).unwrap();
+    let (report, coverage) = run_program_tests_with_coverage(&program);
+    assert!(report.is_success());
+    assert_eq!(coverage.lines[&8], 1);
+    assert_eq!(coverage.lines[&3], 1);
+    assert_eq!(coverage.lines[&5], 0);
+    assert_eq!(coverage.lines_hit(), 4);
+}
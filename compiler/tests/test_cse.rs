@@ -0,0 +1,171 @@
+use zoker_compiler::builtin::BuiltinFunction;
+use zoker_compiler::cse::eliminate_common_calls;
+use zoker_compiler::rewriter::rewrite_program;
+use zoker_compiler::symbol::{
+    Contract, Function, Operation, OperationType, Symbol, SymbolLocation, SymbolType,
+};
+use zoker_compiler::symbol_table::make_symbol_table;
+use zoker_parser::parser;
+
+#[test]
+fn test_pure_calls_are_reused() {
+    let source = "contract Test {\
+           function square(uint x) returns (uint) { return x * x; }\
+           function f(uint a) returns (uint) {\
+             uint b = square(a);\
+             uint c = square(a) + 1;\
+             a = a + 1;\
+             uint d = square(a);\
+             return b + c + d + square(a);\
+           }\
+        }";
+    let program = parser::parse_program(source).unwrap();
+    let table = make_symbol_table(&program).unwrap();
+    let mut contract = rewrite_program(&program).unwrap().remove(0);
+    assert_eq!(eliminate_common_calls(&mut contract, &table), 2);
+    let operations: Vec<String> = contract.functions[1]
+        .operations
+        .iter()
+        .map(|operation| match &operation.operation {
+            OperationType::Assign { right, .. } => right.to_string(),
+            OperationType::Return { ret } => ret.to_string(),
+            op => panic!("unexpected operation {:?}", op),
+        })
+        .collect();
+    // `a` changes before `d`, so `square(a)` is computed again
+    assert_eq!(
+        operations,
+        vec![
+            "square(a)",
+            "(b + 1)",
+            "(a + 1)",
+            "square(a)",
+            "(((b + c) + d) + d)"
+        ]
+    );
+}
+
+#[test]
+fn test_callee_purity_is_inherited() {
+    let source = "contract Test {\
+           function f(uint a) returns (uint) {\
+             uint b = g(a);\
+             return g(a);\
+           }\
+           function g(uint x) returns (uint) { return h(x); }\
+           function h(uint x) returns (uint) { return x; }\
+        }";
+    let program = parser::parse_program(source).unwrap();
+    let table = make_symbol_table(&program).unwrap();
+    let mut contract = rewrite_program(&program).unwrap().remove(0);
+    assert_eq!(eliminate_common_calls(&mut contract, &table), 1);
+}
+
+#[test]
+fn test_storage_reads_are_not_reused_across_calls() {
+    let source = "contract Test {\
+           uint s;\
+           function square(uint x) returns (uint) { return x * x; }\
+           function bump() { s = s + 1; }\
+        }";
+    let program = parser::parse_program(source).unwrap();
+    let table = make_symbol_table(&program).unwrap();
+    let symbol = |name: &str, num: u32, location: SymbolLocation| {
+        Operation::new_symbol(Symbol::new(
+            name.to_string(),
+            num,
+            SymbolType::Uint256,
+            location,
+            false,
+        ))
+    };
+    let assign = |target: &str, num: u32, right: Operation| {
+        Operation::new(OperationType::Assign {
+            left: Box::new(symbol(target, num, SymbolLocation::Memory)),
+            right: Box::new(right),
+        })
+    };
+    // b = square(s); <call>; c = square(s);
+    let contract = |call: &str| {
+        let square = || {
+            Operation::new_call(
+                "square".to_string(),
+                vec![symbol("s", 0, SymbolLocation::Storage)],
+            )
+        };
+        let mut function = Function::new("f".to_string(), vec![], vec![]);
+        function.add_operations(vec![
+            assign("b", 1, square()),
+            Operation::new_call(call.to_string(), vec![]),
+            assign("c", 2, square()),
+        ]);
+        let mut contract = Contract::new("Test".to_string());
+        contract.add_function(function);
+        contract
+    };
+    assert_eq!(eliminate_common_calls(&mut contract("square"), &table), 1);
+    // `bump` may write `s`
+    assert_eq!(eliminate_common_calls(&mut contract("bump"), &table), 0);
+}
+
+#[test]
+fn test_builtins_with_side_effects_are_not_reused() {
+    let program = parser::parse_program("contract Test { uint s; }").unwrap();
+    let table = make_symbol_table(&program).unwrap();
+    let symbol = |name: &str, num: u32, location: SymbolLocation| {
+        Operation::new_symbol(Symbol::new(
+            name.to_string(),
+            num,
+            SymbolType::Uint256,
+            location,
+            false,
+        ))
+    };
+    let assign = |target: &str, num: u32, right: Operation| {
+        Operation::new(OperationType::Assign {
+            left: Box::new(symbol(target, num, SymbolLocation::Memory)),
+            right: Box::new(right),
+        })
+    };
+    let eliminate = |operations: Vec<Operation>| {
+        let mut function = Function::new("f".to_string(), vec![], vec![]);
+        function.add_operations(operations);
+        let mut contract = Contract::new("Test".to_string());
+        contract.add_function(function);
+        eliminate_common_calls(&mut contract, &table)
+    };
+    // b = builtin(a); c = builtin(a);
+    let twice = |function: BuiltinFunction| {
+        let call =
+            || Operation::new_builtin(function, vec![symbol("a", 0, SymbolLocation::Memory)]);
+        eliminate(vec![assign("b", 1, call()), assign("c", 2, call())])
+    };
+    assert_eq!(twice(BuiltinFunction::UintToField), 1);
+    for function in [
+        BuiltinFunction::Selfdestruct,
+        BuiltinFunction::Revert,
+        BuiltinFunction::Keccak256,
+    ] {
+        assert_eq!(twice(function), 0, "{:?}", function);
+    }
+
+    // b = uint_to_field(s); selfdestruct(a); c = uint_to_field(s);
+    let read = || {
+        Operation::new_builtin(
+            BuiltinFunction::UintToField,
+            vec![symbol("s", 0, SymbolLocation::Storage)],
+        )
+    };
+    let selfdestruct = Operation::new_builtin(
+        BuiltinFunction::Selfdestruct,
+        vec![symbol("a", 3, SymbolLocation::Memory)],
+    );
+    assert_eq!(
+        eliminate(vec![
+            assign("b", 1, read()),
+            selfdestruct,
+            assign("c", 2, read())
+        ]),
+        0
+    );
+}
@@ -0,0 +1,101 @@
+use zoker_compiler::database::{Database, Query};
+use zoker_compiler::error::CompileError;
+
+const SOURCE: &str = "contract Test {\
+       function add(uint a, uint b) returns (uint) {\
+         return a + b;\
+       }\
+    }";
+
+fn executed(db: &mut Database) -> Vec<Query> {
+    db.take_executed()
+        .into_iter()
+        .map(|(query, _)| query)
+        .collect()
+}
+
+#[test]
+fn test_queries_are_memoized() {
+    let mut db = Database::new();
+    db.set_source("test.zk", SOURCE);
+    assert_eq!(db.contracts("test.zk").as_ref().as_ref().unwrap().len(), 1);
+    assert_eq!(
+        executed(&mut db),
+        vec![
+            Query::Tokens,
+            Query::Ast,
+            Query::SymbolTable,
+            Query::Contracts
+        ]
+    );
+    // The AST is parsed from the memoized tokens
+    db.tokens("test.zk");
+    assert!(executed(&mut db).is_empty());
+
+    assert!(db.contracts("test.zk").is_ok());
+    assert!(db.symbol_table("test.zk").is_ok());
+    assert!(executed(&mut db).is_empty());
+
+    // Same text doesn't invalidate anything.
+    db.set_source("test.zk", SOURCE);
+    assert_eq!(db.revision(), 1);
+    assert!(db.contracts("test.zk").is_ok());
+    assert!(executed(&mut db).is_empty());
+}
+
+#[test]
+fn test_unchanged_results_cut_off_recomputation() {
+    let mut db = Database::new();
+    db.set_source("test.zk", SOURCE);
+    db.contracts("test.zk");
+    db.take_executed();
+
+    // Trailing blanks give the same AST, so later phases are reused.
+    db.set_source("test.zk", &format!("{}   ", SOURCE));
+    db.contracts("test.zk");
+    assert_eq!(executed(&mut db), vec![Query::Tokens, Query::Ast]);
+
+    db.set_source("test.zk", &SOURCE.replace("a + b", "a + c"));
+    let contracts = db.contracts("test.zk");
+    assert!(matches!(
+        contracts.as_ref(),
+        Err(CompileError::SymbolTable(_))
+    ));
+    assert_eq!(
+        executed(&mut db),
+        vec![
+            Query::Tokens,
+            Query::Ast,
+            Query::SymbolTable,
+            Query::Contracts
+        ]
+    );
+}
+
+#[test]
+fn test_files_are_independent() {
+    let mut db = Database::new();
+    db.set_source("a.zk", SOURCE);
+    db.set_source("b.zk", "contract {");
+    assert!(db.ast("a.zk").is_ok());
+    assert!(matches!(
+        db.contracts("b.zk").as_ref(),
+        Err(CompileError::Parse(_))
+    ));
+    assert_eq!(db.tokens("a.zk").len(), 24);
+    db.take_executed();
+
+    db.set_source("b.zk", "contract B {}");
+    db.ast("a.zk");
+    db.ast("b.zk");
+    assert_eq!(
+        db.take_executed(),
+        vec![
+            (Query::Tokens, "b.zk".to_string()),
+            (Query::Ast, "b.zk".to_string())
+        ]
+    );
+
+    db.remove_source("b.zk");
+    assert!(db.ast("a.zk").is_ok());
+}
@@ -0,0 +1,65 @@
+use num_bigint::{BigInt, BigUint};
+use zoker_compiler::abi::{encode, AbiValue};
+use zoker_compiler::deploy::{constructor_params, deployment_payload};
+use zoker_compiler::driver::Compiler;
+use zoker_compiler::symbol::SymbolType;
+
+#[test]
+fn test_deployment_payload() {
+    let mut compiler = Compiler::new();
+    let source = "contract Test { function get() returns (uint) { return 1; } }";
+    let artifact = compiler
+        .compile(source)
+        .unwrap()
+        .artifacts
+        .unwrap()
+        .remove(0);
+    let code = [0x60, 0x80, 0x60, 0x40];
+    assert!(constructor_params(&artifact).is_empty());
+    assert_eq!(deployment_payload(&artifact, &code, &[]).unwrap(), code);
+
+    let source = "contract Test {\
+           constructor(uint8 a, int8 b, address c) {}\
+           function get() returns (uint) { return 1; }\
+        }";
+    let artifact = compiler
+        .compile(source)
+        .unwrap()
+        .artifacts
+        .unwrap()
+        .remove(0);
+    assert_eq!(
+        constructor_params(&artifact),
+        vec![SymbolType::Uint(8), SymbolType::Int(8), SymbolType::Address]
+    );
+    let args = vec![
+        AbiValue::Uint(BigUint::from(255u32)),
+        AbiValue::Int(BigInt::from(-128)),
+        AbiValue::Address(vec![0x11; 20]),
+    ];
+    let payload = deployment_payload(&artifact, &code, &args).unwrap();
+    assert_eq!(payload[..4], code);
+    assert_eq!(payload[4..], encode(&args).unwrap()[..]);
+
+    let error = |args: &[AbiValue]| {
+        deployment_payload(&artifact, &code, args)
+            .unwrap_err()
+            .error
+    };
+    assert_eq!(
+        error(&args[..2]),
+        "`constructor` expects 3 arguments but 2 were given"
+    );
+    let mut out_of_range = args.clone();
+    out_of_range[0] = AbiValue::Uint(BigUint::from(256u32));
+    assert_eq!(
+        error(&out_of_range),
+        "argument 1 of `constructor` expects `uint8` but `uint256` was given"
+    );
+    let mut wrong_type = args.clone();
+    wrong_type[2] = AbiValue::Bool(true);
+    assert_eq!(
+        error(&wrong_type),
+        "argument 3 of `constructor` expects `address` but `bool` was given"
+    );
+}
@@ -0,0 +1,182 @@
+use zoker_compiler::desugar::desugar_program;
+use zoker_compiler::rewriter::rewrite_program;
+use zoker_parser::ast::{ExpressionType, Operator, Program, Statement, StatementType};
+use zoker_parser::parser::parse_program;
+
+fn desugar(source: &str) -> (Program, usize) {
+    let mut program = parse_program(source).unwrap();
+    let desugared = desugar_program(&mut program);
+    (program, desugared)
+}
+
+/// Statements of the body of the first function of the first contract.
+fn body(program: &Program) -> &Vec<Statement> {
+    let Program::GlobalStatements(statements) = program;
+    let members = match &statements[0].node {
+        StatementType::ContractStatement { members, .. } => members,
+        _ => panic!("not a contract"),
+    };
+    let function = match &members.node {
+        StatementType::MemberStatement { statements } => &statements[0],
+        _ => panic!("not members"),
+    };
+    match &function.node {
+        StatementType::FunctionStatement {
+            statement: Some(statement),
+            ..
+        } => match &statement.node {
+            StatementType::CompoundStatement { statements, .. } => statements,
+            _ => panic!("not a block"),
+        },
+        _ => panic!("not a function"),
+    }
+}
+
+fn expression(statement: &Statement) -> &ExpressionType {
+    match &statement.node {
+        StatementType::Expression { expression } => &expression.node,
+        _ => panic!("not an expression statement"),
+    }
+}
+
+fn assert_assigns(statement: &Statement, name: &str, expected: Operator) {
+    match expression(statement) {
+        ExpressionType::AssignExpression {
+            left,
+            operator: Operator::Assign,
+            right,
+        } => {
+            assert_eq!(
+                left.node,
+                ExpressionType::Identifier {
+                    value: name.to_string()
+                }
+            );
+            match &right.node {
+                ExpressionType::BinaryExpression { left, operator, .. } => {
+                    assert_eq!(*operator, expected);
+                    assert_eq!(
+                        left.node,
+                        ExpressionType::Identifier {
+                            value: name.to_string()
+                        }
+                    );
+                }
+                node => panic!("{:?} is not a binary expression", node),
+            }
+        }
+        node => panic!("{:?} is not an assignment", node),
+    }
+}
+
+#[test]
+fn test_desugar_compound_assignment() {
+    let (program, desugared) = desugar(
+        "contract Test {\
+           function f(uint a) returns (uint) {\
+             a += 2;\
+             a <<= 1;\
+             return a;\
+           }\
+         }",
+    );
+    assert_eq!(desugared, 2);
+    let statements = body(&program);
+    assert_assigns(&statements[0], "a", Operator::Add);
+    assert_assigns(&statements[1], "a", Operator::LShift);
+}
+
+#[test]
+fn test_desugar_increment() {
+    let (program, desugared) = desugar(
+        "contract Test {\
+           function f(uint a) returns (uint) {\
+             a++;\
+             --a;\
+             uint b = a++;\
+             return b;\
+           }\
+         }",
+    );
+    assert_eq!(desugared, 2);
+    let statements = body(&program);
+    assert_assigns(&statements[0], "a", Operator::Add);
+    assert_assigns(&statements[1], "a", Operator::Sub);
+    match &statements[2].node {
+        StatementType::InitializerStatement {
+            default: Some(default),
+            ..
+        } => match &default.node {
+            ExpressionType::UnaryExpression { operator, .. } => {
+                assert_eq!(*operator, Operator::PostfixPlusPlus)
+            }
+            node => panic!("{:?} is not an increment", node),
+        },
+        node => panic!("{:?} is not an initializer", node),
+    }
+}
+
+#[test]
+fn test_desugar_ternary_statement() {
+    let (program, desugared) = desugar(
+        "contract Test {\
+           function f(uint a, bool c) returns (uint) {\
+             c ? a += 1 : a++;\
+             return a;\
+           }\
+         }",
+    );
+    assert_eq!(desugared, 3);
+    match expression(&body(&program)[0]) {
+        ExpressionType::IfExpression {
+            if_statement,
+            else_statement: Some(else_statement),
+            ..
+        } => {
+            for (branch, operator) in [
+                (if_statement, Operator::Add),
+                (else_statement, Operator::Add),
+            ] {
+                match &branch.node {
+                    StatementType::CompoundStatement { statements, .. } => {
+                        assert_assigns(&statements[0], "a", operator)
+                    }
+                    node => panic!("{:?} is not a block", node),
+                }
+            }
+        }
+        node => panic!("{:?} is not an if", node),
+    }
+}
+
+#[test]
+fn test_desugar_keeps_side_effects() {
+    let (program, desugared) = desugar(
+        "contract Test {\
+           uint[] values;\
+           function next() returns (uint) { return 0; }\
+           function f() returns (uint) {\
+             values[next()] += 1;\
+             return 0;\
+           }\
+         }",
+    );
+    assert_eq!(desugared, 0);
+    let Program::GlobalStatements(statements) = &program;
+    assert!(format!("{:?}", statements[0]).contains("AddAssign"));
+}
+
+#[test]
+fn test_desugared_program_rewrites() {
+    let (program, _) = desugar(
+        "contract Test {\
+           function f(uint a, bool c) returns (uint) {\
+             a *= 3;\
+             a++;\
+             c ? a++ : a--;\
+             return a;\
+           }\
+         }",
+    );
+    assert!(rewrite_program(&program).is_ok());
+}
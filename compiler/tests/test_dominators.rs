@@ -0,0 +1,69 @@
+use zoker_compiler::cfg::{ControlFlowGraph, Terminator};
+use zoker_compiler::dominators::{loop_depth, natural_loops, DominatorTree};
+use zoker_compiler::rewriter::rewrite_program;
+use zoker_parser::parser;
+
+fn cfg(source: &str) -> ControlFlowGraph {
+    let program = parser::parse_program(source).unwrap();
+    let contract = rewrite_program(&program).unwrap().remove(0);
+    ControlFlowGraph::new(&contract.functions[0])
+}
+
+#[test]
+fn test_dominators_of_branch() {
+    let cfg = cfg("contract Test {\
+           function f(uint a, bool c) returns (uint) {\
+             if c { a = a + 1; } else { a = a - 1; };\
+             return a;\
+           }\
+        }");
+    let dominators = DominatorTree::new(&cfg);
+    let (then, otherwise) = match &cfg.block(cfg.entry).terminator {
+        Terminator::Branch {
+            then, otherwise, ..
+        } => (*then, *otherwise),
+        terminator => panic!("unexpected terminator {:?}", terminator),
+    };
+    let join = cfg.successors(then)[0];
+    assert_eq!(dominators.immediate_dominator(cfg.entry), None);
+    assert_eq!(dominators.immediate_dominator(then), Some(cfg.entry));
+    // Neither arm dominates the join, both paths lead to it
+    assert_eq!(dominators.immediate_dominator(join), Some(cfg.entry));
+    assert!(!dominators.dominates(then, join));
+    assert!(!dominators.dominates(otherwise, join));
+    assert!(dominators.dominates(cfg.entry, cfg.exit));
+    assert!(dominators.dominates(join, join));
+    assert_eq!(dominators.children(cfg.entry), vec![then, otherwise, join]);
+    assert!(natural_loops(&cfg, &dominators).is_empty());
+}
+
+#[test]
+fn test_nested_natural_loops() {
+    let cfg = cfg("contract Test {\
+           function f(uint a, uint[] v) returns (uint) {\
+             for i in v {\
+               for j in v { a = a + j; };\
+               a = a + i;\
+             };\
+             return a;\
+           }\
+        }");
+    let dominators = DominatorTree::new(&cfg);
+    let loops = natural_loops(&cfg, &dominators);
+    assert_eq!(loops.len(), 2);
+    let (outer, inner) = (&loops[0], &loops[1]);
+    assert_eq!(outer.latches.len(), 1);
+    assert_eq!(inner.latches.len(), 1);
+    assert!(outer.contains(inner.header));
+    assert!(inner.blocks.iter().all(|block| outer.contains(*block)));
+    assert!(!outer.contains(cfg.entry));
+    assert!(!outer.contains(cfg.exit));
+    let inner_body = match &cfg.block(inner.header).terminator {
+        Terminator::Loop { body, .. } => *body,
+        terminator => panic!("unexpected terminator {:?}", terminator),
+    };
+    assert_eq!(loop_depth(&loops, inner_body), 2);
+    assert_eq!(loop_depth(&loops, outer.header), 1);
+    assert_eq!(loop_depth(&loops, cfg.exit), 0);
+    assert!(dominators.dominates(outer.header, inner.header));
+}
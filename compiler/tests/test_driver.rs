@@ -0,0 +1,91 @@
+use zoker_compiler::driver::{Compiler, Stage, StageOutput};
+use zoker_compiler::error::{CompileError, RewriteError, RewriteErrorType};
+use zoker_parser::location::Location;
+
+const SOURCE: &str = "contract Test {\
+       function half(uint a) returns (uint) {\
+         return a / 2;\
+       }\
+    }";
+
+#[test]
+fn test_compiler_stages() {
+    let mut stages = vec![];
+    let mut compiler = Compiler::new();
+    compiler.observe(|output| stages.push(output.stage()));
+    let compilation = compiler.compile(SOURCE).unwrap();
+    drop(compiler);
+    assert_eq!(
+        stages,
+        vec![
+            Stage::Parse,
+            Stage::Desugar,
+            Stage::Resolve,
+            Stage::Check,
+            Stage::Lower,
+            Stage::Optimize,
+            Stage::Emit
+        ]
+    );
+    let artifacts = compilation.artifacts.unwrap();
+    assert_eq!(artifacts.len(), 1);
+    assert_eq!(artifacts[0].contract.name, "Test");
+    assert_eq!(artifacts[0].size.contract, "Test");
+    let operations = format!("{:?}", artifacts[0].contract.functions[0].operations);
+    assert!(operations.contains("Shr") && !operations.contains("Div"));
+}
+
+#[test]
+fn test_compiler_stop_after() {
+    let mut lowered = String::new();
+    let mut compiler = Compiler::new();
+    compiler.stop_after = Stage::Lower;
+    compiler.observe(|output| {
+        if let StageOutput::Lower(contracts) = output {
+            lowered = format!("{:?}", contracts[0].functions[0].operations);
+        }
+    });
+    let compilation = compiler.compile(SOURCE).unwrap();
+    drop(compiler);
+    assert!(compilation.program.is_some());
+    assert!(compilation.symbol_table.is_some());
+    assert!(compilation.artifacts.is_none());
+    // Not optimized, `a / 2` isn't reduced to a shift yet
+    assert_eq!(
+        format!(
+            "{:?}",
+            compilation.contracts.unwrap()[0].functions[0].operations
+        ),
+        lowered
+    );
+    assert!(lowered.contains("Div"));
+
+    let mut compiler = Compiler::new();
+    compiler.stop_after = Stage::Parse;
+    let compilation = compiler.compile(SOURCE).unwrap();
+    assert!(compilation.program.is_some());
+    assert!(compilation.symbol_table.is_none());
+}
+
+#[test]
+fn test_compiler_errors() {
+    let mut stages = vec![];
+    let mut compiler = Compiler::new();
+    compiler.observe(|output| stages.push(output.stage()));
+    let result = compiler.compile("contract Test { uint a = b; }");
+    drop(compiler);
+    assert!(matches!(result, Err(CompileError::SymbolTable(_))));
+    // Undeclared symbols are found by the check stage
+    assert_eq!(stages, vec![Stage::Parse, Stage::Desugar, Stage::Resolve]);
+
+    // State variables aren't lowered yet
+    let source = "contract T { uint s; function f() returns (uint) { return s; } }";
+    let error = Compiler::new().compile(source).unwrap_err();
+    assert_eq!(
+        error,
+        CompileError::Rewrite(RewriteError {
+            error: RewriteErrorType::UnsupportedError,
+            location: Location::new(0, 14),
+        })
+    );
+}
@@ -0,0 +1,41 @@
+use zoker_compiler::escape::{analyze_escapes, Escape};
+use zoker_compiler::rewriter::rewrite_program;
+use zoker_parser::parser;
+
+#[test]
+fn test_escapes() {
+    let program = parser::parse_program(
+        "contract Test {\
+           function keep(uint[] values) { uint[] storage stored = values; }\
+           function ignore(uint[] values) returns (uint) { return values[0]; }\
+           function f(uint[] a, uint[] b, uint[] c, uint[] d) returns (uint[]) {\
+             uint[] alias = a;\
+             keep(alias);\
+             ignore(b);\
+             uint[] storage stored = d;\
+             stored[0] = c[0];\
+             return d;\
+           }\
+        }",
+    )
+    .unwrap();
+    let contract = rewrite_program(&program).unwrap().remove(0);
+    let analyses = analyze_escapes(&contract);
+    assert_eq!(analyses["keep"].to_string(), "values#0: stored\n");
+    assert!(analyses["ignore"].escapes.is_empty());
+    let f = &analyses["f"];
+    assert_eq!(
+        f.to_string(),
+        "a#0: passed to `keep`\nalias#4: passed to `keep`\nb#1: local\nc#2: local\nd#3: stored, returned\n"
+    );
+    let names: Vec<&str> = f
+        .local_values()
+        .iter()
+        .map(|local| local.0.as_str())
+        .collect();
+    assert_eq!(names, vec!["b", "c"]);
+    assert_eq!(
+        f.escapes.values().next().unwrap(),
+        &vec![Escape::Call("keep".to_string())]
+    );
+}
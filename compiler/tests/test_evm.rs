@@ -0,0 +1,95 @@
+use zoker_compiler::driver::{Compiler, Stage};
+use zoker_compiler::error::{CompileError, SymbolTableErrorType};
+use zoker_compiler::evm::{require_opcode, Opcode};
+use zoker_compiler::settings::{CompileSettings, EvmVersion};
+use zoker_parser::location::Location;
+
+#[test]
+fn test_supported_opcodes() {
+    assert!(!EvmVersion::London.supports(Opcode::Push0));
+    assert!(!EvmVersion::Paris.supports(Opcode::Push0));
+    assert!(EvmVersion::Shanghai.supports(Opcode::Push0));
+    assert!(!EvmVersion::Shanghai.supports(Opcode::Tstore));
+    assert!(EvmVersion::Cancun.supports(Opcode::Mcopy));
+    assert!(EvmVersion::Cancun.supports(Opcode::Tload));
+}
+
+#[test]
+fn test_require_opcode() {
+    let location = Location::new(3, 5);
+    assert!(require_opcode(
+        EvmVersion::Cancun,
+        Opcode::Tstore,
+        "transient storage",
+        location
+    )
+    .is_ok());
+
+    let error = require_opcode(
+        EvmVersion::Shanghai,
+        Opcode::Tstore,
+        "transient storage",
+        location,
+    )
+    .unwrap_err();
+    assert_eq!(error.location, location);
+    assert_eq!(
+        error.to_string(),
+        "transient storage needs `TSTORE`, which the `shanghai` EVM doesn't have: \
+         target `cancun` or later"
+    );
+    assert!(matches!(
+        CompileError::from(error),
+        CompileError::EvmVersion(_)
+    ));
+}
+
+/// Checks `source` for `evm_version`, before lowering it.
+fn check(source: &str, evm_version: EvmVersion) -> Result<(), CompileError> {
+    let settings = CompileSettings {
+        evm_version,
+        ..CompileSettings::default()
+    };
+    let mut compiler = Compiler::with_settings(settings);
+    compiler.stop_after = Stage::Check;
+    compiler.compile(source).map(|_| ())
+}
+
+#[test]
+fn test_transient_storage() {
+    let source = "contract Lock {\
+           uint transient depth;\
+           function enter() { depth = depth + 1; }\
+        }";
+    assert!(check(source, EvmVersion::Cancun).is_ok());
+    match check(source, EvmVersion::Shanghai) {
+        Err(CompileError::EvmVersion(error)) => {
+            assert_eq!(
+                error.to_string(),
+                "transient state variable `depth` needs `TSTORE`, which the `shanghai` EVM \
+                 doesn't have: target `cancun` or later"
+            );
+            assert_eq!(error.location.column(), 31);
+        }
+        result => panic!("unexpected result {:?}", result),
+    }
+
+    let type_error = |source| match check(source, EvmVersion::Cancun) {
+        Err(CompileError::SymbolTable(error)) => match error.error {
+            SymbolTableErrorType::TypeError(message) => message,
+            error => panic!("unexpected error {:?}", error),
+        },
+        result => panic!("unexpected result {:?}", result),
+    };
+    let message = "transient `a` can only be declared as a state variable of a value type \
+                   without a value";
+    assert_eq!(
+        type_error("contract Test { function f() { uint transient a; } }"),
+        message
+    );
+    assert_eq!(
+        type_error("contract Test { uint transient a = 1; }"),
+        message
+    );
+    assert_eq!(type_error("contract Test { string transient a; }"), message);
+}
@@ -0,0 +1,112 @@
+use num_bigint::BigInt;
+use zoker_compiler::abi::canonical_type;
+use zoker_compiler::coercion::{implicit_conversion, literal_conversion, ConversionErrorKind};
+use zoker_compiler::error::{ExecutionErrorType, SymbolTableErrorType};
+use zoker_compiler::field::{field_modulus, field_warnings, FieldOperation};
+use zoker_compiler::interpreter::{Interpreter, Value};
+use zoker_compiler::symbol::SymbolType;
+use zoker_compiler::symbol_table::make_symbol_table;
+use zoker_parser::parser;
+
+#[test]
+fn test_field_conversions() {
+    let modulus = BigInt::from(field_modulus());
+    assert!(literal_conversion(&BigInt::from(0), &SymbolType::Field).is_ok());
+    assert!(literal_conversion(&(modulus.clone() - 1), &SymbolType::Field).is_ok());
+    assert!(literal_conversion(&modulus, &SymbolType::Field).is_err());
+    assert!(literal_conversion(&BigInt::from(-1), &SymbolType::Field).is_err());
+    assert_eq!(
+        implicit_conversion(&SymbolType::Uint256, &SymbolType::Field).map_err(|error| error.kind),
+        Err(ConversionErrorKind::Field)
+    );
+    assert_eq!(
+        implicit_conversion(&SymbolType::Field, &SymbolType::Uint(8))
+            .unwrap_err()
+            .to_string(),
+        "cannot implicitly convert `field` to `uint8`: \
+         convert with `uint_to_field` or `field_to_uint`, which check the range"
+    );
+    assert_eq!(canonical_type(&SymbolType::Field), "uint256");
+
+    let source = "contract Test {\
+           function f(field a, uint b) returns (field) {\
+             field c = a * 2 + uint_to_field(b);\
+             uint d = field_to_uint(c);\
+             return c;\
+           }\
+        }";
+    let program = parser::parse_program(source).unwrap();
+    assert!(make_symbol_table(&program).is_ok());
+
+    let program =
+        parser::parse_program("contract Test { function f(field a, uint b) { a + b; } }").unwrap();
+    match make_symbol_table(&program).unwrap_err().error {
+        SymbolTableErrorType::TypeError(message) => assert_eq!(
+            message,
+            "operands of types `field` and `uint` don't mix: \
+             convert with `uint_to_field` or `field_to_uint`, which check the range"
+        ),
+        error => panic!("unexpected error {:?}", error),
+    }
+}
+
+#[test]
+fn test_field_warnings() {
+    let source = "contract Test {\
+           field root;\
+           function f(field a, uint b) returns (uint) {\
+             field c = a - 1;\
+             c < a;\
+             root & 1;\
+             c % 2;\
+             -c >> 1;\
+             uint_to_field(b) >= 2;\
+             c == a;\
+             b < 2;\
+             return field_to_uint(c) < b ? 1 : 0;\
+           }\
+        }";
+    let program = parser::parse_program(source).unwrap();
+    let warnings = field_warnings(&program);
+    let operations: Vec<FieldOperation> = warnings.iter().map(|w| w.operation).collect();
+    assert_eq!(
+        operations,
+        vec![
+            FieldOperation::Comparison,
+            FieldOperation::Bitwise,
+            FieldOperation::Remainder,
+            FieldOperation::Bitwise,
+            FieldOperation::Comparison,
+        ]
+    );
+    assert_eq!(
+        warnings[0].to_string(),
+        "comparison of `field` elements assumes integer semantics, which don't hold modulo \
+         the prime: convert them with `field_to_uint` first"
+    );
+}
+
+#[test]
+fn test_interpret_field_builtins() {
+    let source = "contract Test {\
+           function f(uint b) returns (uint) {\
+             return field_to_uint(uint_to_field(b));\
+           }\
+        }";
+    let program = parser::parse_program(source).unwrap();
+    let mut interpreter = Interpreter::new(&program);
+    let modulus = BigInt::from(field_modulus());
+    assert_eq!(
+        interpreter.call("Test.f", vec![Value::Int(BigInt::from(7))]),
+        Ok(Value::Int(BigInt::from(7)))
+    );
+    let error = interpreter
+        .call("Test.f", vec![Value::Int(modulus)])
+        .unwrap_err();
+    assert_eq!(
+        error.error,
+        ExecutionErrorType::Revert(Some(String::from(
+            "value is not below the prime of the field"
+        )))
+    );
+}
@@ -0,0 +1,22 @@
+#![cfg(feature = "generator")]
+
+use zoker_compiler::symbol_table::make_symbol_table;
+use zoker_parser::generator::{generate_program, GeneratorConfig};
+use zoker_parser::parser;
+
+#[test]
+fn test_symbol_table_of_generated_programs() {
+    for seed in 0..200 {
+        let source = generate_program(GeneratorConfig {
+            seed,
+            contracts: 3,
+            functions: 5,
+            block_depth: 3,
+            ..GeneratorConfig::default()
+        });
+        let program = parser::parse_program(&source).unwrap();
+        if let Err(err) = make_symbol_table(&program) {
+            panic!("seed {} failed with {:?}\n{}", seed, err, source);
+        }
+    }
+}
@@ -0,0 +1,90 @@
+use num_bigint::BigUint;
+use zoker_compiler::groth16::{public_inputs, verifier_contract, G1Point, G2Point, VerifyingKey};
+use zoker_compiler::rewriter::rewrite_program;
+use zoker_compiler::symbol::Function;
+use zoker_parser::parser;
+
+fn function(source: &str) -> Function {
+    let program = parser::parse_program(source).unwrap();
+    rewrite_program(&program)
+        .unwrap()
+        .remove(0)
+        .functions
+        .remove(0)
+}
+
+fn g1(x: u32, y: u32) -> G1Point {
+    G1Point {
+        x: BigUint::from(x),
+        y: BigUint::from(y),
+    }
+}
+
+fn g2(values: [u32; 4]) -> G2Point {
+    G2Point {
+        x: [BigUint::from(values[0]), BigUint::from(values[1])],
+        y: [BigUint::from(values[2]), BigUint::from(values[3])],
+    }
+}
+
+fn key(ic: usize) -> VerifyingKey {
+    VerifyingKey {
+        alpha: g1(1, 2),
+        beta: g2([3, 4, 5, 6]),
+        gamma: g2([7, 8, 9, 10]),
+        delta: g2([11, 12, 13, 14]),
+        ic: (0..ic as u32).map(|i| g1(20 + i, 30 + i)).collect(),
+    }
+}
+
+#[test]
+fn test_verifier_contract() {
+    let cast = function(
+        "contract Vote {\
+           function cast(private uint choice, uint round) returns (uint) { return round; }\
+         }",
+    );
+    assert_eq!(public_inputs(&cast), 2);
+    let source = verifier_contract("CastVerifier", &cast, &key(3)).unwrap();
+    assert!(source.starts_with("// SPDX-License-Identifier: MIT\npragma solidity ^0.8.0;\n"));
+    assert!(source.contains("contract CastVerifier {\n"));
+    assert!(source.contains(
+        "    function verifyProof(uint256[2] calldata a, uint256[2][2] calldata b, \
+         uint256[2] calldata c, uint256[2] calldata input) external view returns (bool) {\n\
+         \x20       G1Point memory x = G1Point(20, 30);\n\
+         \x20       require(input[0] < SNARK_SCALAR_FIELD, \"input not in field\");\n\
+         \x20       x = add(x, mul(G1Point(21, 31), input[0]));\n\
+         \x20       require(input[1] < SNARK_SCALAR_FIELD, \"input not in field\");\n\
+         \x20       x = add(x, mul(G1Point(22, 32), input[1]));\n"
+    ));
+    assert!(source.contains(
+        "        p[1] = G1Point(1, 2);\n\
+         \x20       q[1] = G2Point([uint256(3), 4], [uint256(5), 6]);\n"
+    ));
+    assert!(source.ends_with("        return pairing(p, q);\n    }\n}\n"));
+
+    assert_eq!(
+        verifier_contract("CastVerifier", &cast, &key(2))
+            .unwrap_err()
+            .error,
+        "verifying key has 2 points in `ic` but `cast` has 2 public inputs, which need 3"
+    );
+    let mut outside = key(3);
+    outside.alpha.y = BigUint::parse_bytes(
+        b"30644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd47",
+        16,
+    )
+    .unwrap();
+    assert_eq!(
+        verifier_contract("CastVerifier", &cast, &outside)
+            .unwrap_err()
+            .error,
+        "verifying key has coordinates outside the base field of BN254"
+    );
+
+    // Without public inputs, proofs are checked against the constant term only
+    let secret = function("contract Vote { function check(private uint choice) { } }");
+    let source = verifier_contract("CheckVerifier", &secret, &key(1)).unwrap();
+    assert!(source.contains("uint256[2] calldata c) external view returns (bool) {"));
+    assert!(!source.contains("x = add(x"));
+}
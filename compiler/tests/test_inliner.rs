@@ -0,0 +1,112 @@
+use zoker_compiler::inliner::{inline_contract, InlineConfig};
+use zoker_compiler::rewriter::rewrite_program;
+use zoker_compiler::symbol::{Contract, Operation, OperationType};
+use zoker_parser::parser;
+
+fn compile(source: &str) -> Contract {
+    let program = parser::parse_program(source).unwrap();
+    rewrite_program(&program).unwrap().remove(0)
+}
+
+fn has_call(operations: &[Operation], name: &str) -> bool {
+    format!("{:?}", operations).contains(&format!("func: \"{}\"", name))
+}
+
+const SOURCE: &str = "contract Test {\
+       function add(uint a, uint b) returns (uint) {\
+         uint c = a + b;\
+         return c;\
+       }\
+       function twice(uint x) returns (uint) {\
+         return add(x, x) + add(1, x);\
+       }\
+       function down(uint n) returns (uint) {\
+         return down(n - 1);\
+       }\
+    }";
+
+#[test]
+fn test_inline_small_functions() {
+    let mut contract = compile(SOURCE);
+    inline_contract(&mut contract, &InlineConfig::default());
+    let twice = &contract.functions[1];
+    assert!(!has_call(&twice.operations, "add"));
+    // Two assignments of parameters and one of `c` per inlined call
+    assert_eq!(twice.operations.len(), 7);
+
+    let locals = twice
+        .operations
+        .iter()
+        .filter_map(|op| match &op.operation {
+            OperationType::Assign { left, .. } => left.as_symbol(),
+            _ => None,
+        })
+        .map(|symbol| (symbol.name, symbol.num))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        locals,
+        vec![
+            ("add.a".to_string(), 1),
+            ("add.b".to_string(), 2),
+            ("add.c".to_string(), 3),
+            ("add.a".to_string(), 4),
+            ("add.b".to_string(), 5),
+            ("add.c".to_string(), 6),
+        ]
+    );
+    match &twice.operations[6].operation {
+        OperationType::Return { ret } => match &ret.operation {
+            OperationType::Add { left, right } => {
+                assert_eq!(left.as_symbol().unwrap().num, 3);
+                assert_eq!(right.as_symbol().unwrap().num, 6);
+            }
+            op => panic!("expected addition, got {:?}", op),
+        },
+        op => panic!("expected return, got {:?}", op),
+    }
+}
+
+#[test]
+fn test_inline_recursion_guard() {
+    let mut contract = compile(SOURCE);
+    inline_contract(&mut contract, &InlineConfig::all());
+    assert!(has_call(&contract.functions[2].operations, "down"));
+}
+
+#[test]
+fn test_inline_size_threshold() {
+    let mut contract = compile(SOURCE);
+    let before = contract.clone();
+    inline_contract(
+        &mut contract,
+        &InlineConfig {
+            size_threshold: 2,
+            inline_single_use: false,
+            ..InlineConfig::default()
+        },
+    );
+    assert_eq!(contract, before);
+}
+
+#[test]
+fn test_inline_annotation() {
+    let source = "contract Test {\
+           @inline function add(uint a, uint b) returns (uint) {\
+             uint c = a + b;\
+             return c;\
+           }\
+           function twice(uint x) returns (uint) {\
+             return add(x, x) + add(1, x);\
+           }\
+        }";
+    let mut contract = compile(source);
+    inline_contract(
+        &mut contract,
+        &InlineConfig {
+            size_threshold: 2,
+            inline_single_use: false,
+            ..InlineConfig::default()
+        },
+    );
+    assert!(!has_call(&contract.functions[1].operations, "add"));
+}
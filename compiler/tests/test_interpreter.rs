@@ -0,0 +1,434 @@
+use num_bigint::BigInt;
+#[cfg(feature = "secp256k1")]
+use num_bigint::Sign;
+use zoker_compiler::error::ExecutionErrorType;
+use zoker_compiler::interpreter::{Interpreter, StepAction, Value};
+use zoker_parser::parser;
+
+fn int(value: i64) -> Value {
+    Value::Int(BigInt::from(value))
+}
+
+#[test]
+fn test_interpret_functions() {
+    let source = "contract Test {\
+           uint total = 10;\
+           uint[] values;\
+           function add(uint x) returns (uint) {\
+             total += x;\
+             values.push(x);\
+             return total;\
+           }\
+           function sum() returns (uint) {\
+             uint s = 0;\
+             for v in values { s = s + v; };\
+             return s;\
+           }\
+           function pick(uint x) returns (uint) {\
+             if x > 2 { return math::twice(x); };\
+             return x == 1 ? 100 : 200;\
+           }\
+         }\
+         module math {\
+           uint constant TWO = 2;\
+           function twice(uint x) returns (uint) { return x * TWO; }\
+         }";
+    let program = parser::parse_program(source).unwrap();
+    let mut interpreter = Interpreter::new(&program);
+    assert_eq!(interpreter.call("Test.add", vec![int(5)]), Ok(int(15)));
+    assert_eq!(interpreter.call("Test.add", vec![int(7)]), Ok(int(22)));
+    assert_eq!(interpreter.call("Test.sum", vec![]), Ok(int(12)));
+    assert_eq!(interpreter.state_variable("Test", "total"), Ok(int(22)));
+    assert_eq!(interpreter.call("Test.pick", vec![int(3)]), Ok(int(6)));
+    assert_eq!(interpreter.call("Test.pick", vec![int(1)]), Ok(int(100)));
+    assert_eq!(interpreter.call("math::twice", vec![int(4)]), Ok(int(8)));
+}
+
+#[test]
+fn test_interpret_multiple_returns() {
+    let source = "contract Test {\
+           function divmod(uint a, uint b) returns (uint, uint) {\
+             return (a / b, a % b);\
+           }\
+           function remainder(uint a, uint b) returns (uint) {\
+             uint r;\
+             (_, r) = divmod(a, b);\
+             return r;\
+           }\
+        }";
+    let program = parser::parse_program(source).unwrap();
+    let mut interpreter = Interpreter::new(&program);
+    assert_eq!(
+        interpreter.call("Test.divmod", vec![int(7), int(2)]),
+        Ok(Value::Tuple(vec![int(3), int(1)]))
+    );
+    assert_eq!(
+        interpreter.call("Test.remainder", vec![int(7), int(3)]),
+        Ok(int(1))
+    );
+}
+
+#[test]
+fn test_interpret_errors() {
+    let source = "function div(uint x) returns (uint) { return 10 / x; }\
+         function check(uint x) { assert(x < 3, \"too large\"); }";
+    let program = parser::parse_program(source).unwrap();
+    let mut interpreter = Interpreter::new(&program);
+    assert_eq!(
+        interpreter.call("div", vec![int(0)]).unwrap_err().error,
+        ExecutionErrorType::DivisionByZero
+    );
+    assert_eq!(interpreter.call("check", vec![int(1)]), Ok(Value::Unit));
+    let err = interpreter.call("check", vec![int(3)]).unwrap_err();
+    assert_eq!(
+        err.error,
+        ExecutionErrorType::Revert(Some("too large".to_string()))
+    );
+    assert_eq!(err.location.column(), 80);
+}
+
+#[test]
+fn test_interpret_hash_functions() {
+    let source = "function keccak(bytes b) returns (bytes32) { return keccak256(b); }\
+         function sha(bytes b) returns (bytes32) { return sha256(b); }\
+         function blake(bytes b) returns (bytes32) { return blake2s(b); }\
+         function twice(bytes b) returns (bytes32) { return sha256(sha256(b)); }";
+    let program = parser::parse_program(source).unwrap();
+    let mut interpreter = Interpreter::new(&program);
+    let mut hash = |function: &str, data: &str| match interpreter
+        .call(function, vec![Value::String(data.to_string())])
+    {
+        Ok(Value::Int(digest)) => format!("{:064x}", digest),
+        result => panic!("unexpected result {:?}", result),
+    };
+    assert_eq!(
+        hash("keccak", ""),
+        "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"
+    );
+    assert_eq!(
+        hash("keccak", "abc"),
+        "4e03657aea45a94fc7d47ba826c8d667c0d1e6e33a64a036ec44f58fa12d6c45"
+    );
+    assert_eq!(
+        hash("sha", "abc"),
+        "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+    );
+    assert_eq!(
+        hash("blake", "abc"),
+        "508c5e8c327c14e2e1a72ba34eeb452f37458b209ed63a294d999b4c86675982"
+    );
+    // The inner digest is hashed as 32 bytes, leading zeros included
+    assert_eq!(
+        hash("twice", "abc"),
+        "4f8b42c22dd3729b519ba6f68d2da7cc5b2d606d05daed5ad5128cc03e6c6358"
+    );
+}
+
+#[test]
+fn test_interpret_overflow() {
+    let source = "function next(uint8 a) returns (uint8) { uint8 b = a + 1; return b; }\
+         function wrap(uint8 a) returns (uint8) { uint8 b; unchecked { b = a + 1; }; return b; }\
+         function negative() returns (uint) { uint b = 0 - 1; return b; }\
+         function negate(int8 a) returns (int8) { return a / -1; }\
+         function huge() returns (uint) { return 2 ** 4000000000; }\
+         function shift(uint8 a) returns (uint8) { return (a << 7) + (a << 300); }";
+    let program = parser::parse_program(source).unwrap();
+    let mut interpreter = Interpreter::new(&program);
+    let overflow = ExecutionErrorType::Revert(Some("arithmetic overflow".to_string()));
+    assert_eq!(interpreter.call("next", vec![int(254)]), Ok(int(255)));
+    assert_eq!(
+        interpreter.call("next", vec![int(255)]).unwrap_err().error,
+        overflow
+    );
+    assert_eq!(interpreter.call("wrap", vec![int(255)]), Ok(int(0)));
+    assert_eq!(
+        interpreter.call("negative", vec![]).unwrap_err().error,
+        overflow
+    );
+    assert_eq!(interpreter.call("negate", vec![int(-127)]), Ok(int(127)));
+    assert_eq!(
+        interpreter
+            .call("negate", vec![int(-128)])
+            .unwrap_err()
+            .error,
+        overflow
+    );
+    assert_eq!(
+        interpreter.call("huge", vec![]).unwrap_err().error,
+        overflow
+    );
+    assert_eq!(interpreter.call("shift", vec![int(3)]), Ok(int(128)));
+}
+
+#[test]
+fn test_interpret_abi_functions() {
+    let source = "function pair(uint8 a, bool b) returns (bytes) { return abi.encode(a, b); }\
+         function packed(address a, bool b) returns (bytes) { return abi.encodePacked(a, b); }\
+         function small(bytes data) returns (uint8) { return abi.decode(data, (uint8)); }\
+         function hashed(uint a) returns (bytes32) { return sha256(abi.encode(a)); }";
+    let program = parser::parse_program(source).unwrap();
+    let mut interpreter = Interpreter::new(&program);
+    let word = |last: u8| {
+        let mut word = vec![0; 31];
+        word.push(last);
+        word
+    };
+    let mut expected = word(7);
+    expected.extend(word(1));
+    assert_eq!(
+        interpreter.call("pair", vec![int(7), Value::Bool(true)]),
+        Ok(Value::Bytes(expected))
+    );
+    let mut expected = vec![0x11; 20];
+    expected.push(0);
+    let address = BigInt::parse_bytes(&[b'1'; 40], 16).unwrap();
+    assert_eq!(
+        interpreter.call("packed", vec![Value::Int(address), Value::Bool(false)]),
+        Ok(Value::Bytes(expected))
+    );
+    assert_eq!(
+        interpreter.call("small", vec![Value::Bytes(word(7))]),
+        Ok(int(7))
+    );
+    let mut dirty = word(7);
+    dirty[0] = 1;
+    assert_eq!(
+        interpreter
+            .call("small", vec![Value::Bytes(dirty)])
+            .unwrap_err()
+            .error,
+        ExecutionErrorType::Revert(Some("`uint8` value is not padded with zeros".to_string()))
+    );
+    let digest = b"48428bdb7ddd829410d6bbb924fdeb3a3d7e88c2577bffae073b990c6f061d08";
+    assert_eq!(
+        interpreter.call("hashed", vec![int(7)]),
+        Ok(Value::Int(BigInt::parse_bytes(digest, 16).unwrap()))
+    );
+}
+
+#[cfg(feature = "secp256k1")]
+#[test]
+fn test_interpret_ecrecover() {
+    use k256::ecdsa::SigningKey;
+
+    let source = "function signer(bytes32 h, uint8 v, bytes32 r, bytes32 s) returns (address) {\
+           return ecrecover(h, v, r, s);\
+         }";
+    let program = parser::parse_program(source).unwrap();
+    let mut interpreter = Interpreter::new(&program);
+    let mut secret = [0; 32];
+    secret[31] = 1;
+    let key = SigningKey::from_slice(&secret).unwrap();
+    let hash = [7; 32];
+    let (signature, recovery_id) = key.sign_prehash_recoverable(&hash).unwrap();
+    let (r, s) = signature.split_bytes();
+    let word = |bytes: &[u8]| Value::Int(BigInt::from_bytes_be(Sign::Plus, bytes));
+    let mut call = |v: u8| {
+        let args = vec![word(&hash), int(v.into()), word(&r), word(&s)];
+        interpreter.call("signer", args)
+    };
+    let address = BigInt::parse_bytes(b"7e5f4552091a69125d5dfcb7b8c2659029395bdf", 16).unwrap();
+    assert_eq!(call(27 + recovery_id.to_byte()), Ok(Value::Int(address)));
+    assert_eq!(call(29), Ok(int(0)));
+}
+
+#[test]
+fn test_interpret_call_checks() {
+    let source = "function depth(uint n) returns (uint) { if n == 0 { return 0; }; return depth(n - 1) + 1; }\
+         function forever(uint n) returns (uint) { return forever(n + 1); }\
+         function small(uint8 a) returns (uint8) { return a; }";
+    let program = parser::parse_program(source).unwrap();
+    let mut interpreter = Interpreter::new(&program);
+    assert_eq!(interpreter.call("depth", vec![int(10)]), Ok(int(10)));
+    let err = interpreter.call("forever", vec![int(0)]).unwrap_err();
+    assert_eq!(err.error, ExecutionErrorType::CallDepthExceeded);
+    assert_eq!(err.location.column(), 141);
+
+    assert_eq!(
+        interpreter
+            .call("small", vec![int(1000)])
+            .unwrap_err()
+            .error,
+        ExecutionErrorType::Revert(Some("argument `a` is out of range".to_string()))
+    );
+    assert_eq!(
+        interpreter.call("small", vec![]).unwrap_err().error,
+        ExecutionErrorType::Revert(Some(
+            "`small` takes 1 argument(s) but 0 were given".to_string()
+        ))
+    );
+}
+
+#[test]
+fn test_interpret_require_revert() {
+    let source = "function check(uint x) { require(x < 3, \"too large\"); }\
+         function fail(uint x) { if x == 0 { revert(); }; revert(\"failed\"); }";
+    let program = parser::parse_program(source).unwrap();
+    let mut interpreter = Interpreter::new(&program);
+    assert_eq!(interpreter.call("check", vec![int(2)]), Ok(Value::Unit));
+    assert_eq!(
+        interpreter.call("check", vec![int(3)]).unwrap_err().error,
+        ExecutionErrorType::Revert(Some("too large".to_string()))
+    );
+    assert_eq!(
+        interpreter.call("fail", vec![int(0)]).unwrap_err().error,
+        ExecutionErrorType::Revert(None)
+    );
+    assert_eq!(
+        interpreter.call("fail", vec![int(1)]).unwrap_err().error,
+        ExecutionErrorType::Revert(Some("failed".to_string()))
+    );
+}
+
+#[test]
+fn test_interpret_do_while() {
+    let source = "function count(uint n) returns (uint) {\
+           uint i = 0;\
+           do { i += 1; } while (i < n);\
+           return i;\
+         }";
+    let program = parser::parse_program(source).unwrap();
+    let mut interpreter = Interpreter::new(&program);
+    assert_eq!(interpreter.call("count", vec![int(3)]), Ok(int(3)));
+    // The body runs once before the condition is checked
+    assert_eq!(interpreter.call("count", vec![int(0)]), Ok(int(1)));
+}
+
+#[test]
+fn test_debug_hook() {
+    let source = "contract Test {\n\
+           uint total = 1;\n\
+           function add(uint x) {\n\
+             uint y = x * 2;\n\
+             total += y;\n\
+             return;\n\
+           }\n\
+         }";
+    let program = parser::parse_program(source).unwrap();
+    let mut pauses = vec![];
+    {
+        let mut interpreter = Interpreter::new(&program);
+        interpreter.debug(|event| {
+            pauses.push((
+                event.statement.location.row(),
+                event.function.to_string(),
+                event.locals.clone(),
+                event.storage_diff.clone(),
+            ));
+            StepAction::Step
+        });
+        interpreter.add_breakpoint(4);
+        assert_eq!(interpreter.call("Test.add", vec![int(3)]), Ok(Value::Unit));
+    }
+    // Paused on the breakpoint, then stepped to the return
+    assert_eq!(pauses.len(), 2);
+    let (row, function, locals, storage_diff) = &pauses[0];
+    assert_eq!((*row, function.as_str()), (4, "Test.add"));
+    assert_eq!(
+        locals,
+        &vec![("x".to_string(), int(3)), ("y".to_string(), int(6))]
+    );
+    assert!(storage_diff.is_empty());
+    let (row, _, _, storage_diff) = &pauses[1];
+    assert_eq!(*row, 5);
+    assert_eq!(storage_diff.len(), 1);
+    assert_eq!(storage_diff[0].name, "total");
+    assert_eq!(
+        (&storage_diff[0].old, &storage_diff[0].new),
+        (&int(1), &int(7))
+    );
+}
+
+#[test]
+fn test_interpret_mapping() {
+    let source = "contract Token {\
+           mapping(uint => uint) balances;\
+           function mint(uint owner, uint amount) { balances[owner] += amount; }\
+           function balance(uint owner) returns (uint) { return balances[owner]; }\
+         }";
+    let program = parser::parse_program(source).unwrap();
+    let mut interpreter = Interpreter::new(&program);
+    assert_eq!(interpreter.call("Token.balance", vec![int(1)]), Ok(int(0)));
+    interpreter
+        .call("Token.mint", vec![int(1), int(5)])
+        .unwrap();
+    interpreter
+        .call("Token.mint", vec![int(1), int(2)])
+        .unwrap();
+    interpreter
+        .call("Token.mint", vec![int(2), int(3)])
+        .unwrap();
+    assert_eq!(interpreter.call("Token.balance", vec![int(1)]), Ok(int(7)));
+    assert_eq!(interpreter.call("Token.balance", vec![int(3)]), Ok(int(0)));
+    assert_eq!(
+        interpreter
+            .state_variable("Token", "balances")
+            .unwrap()
+            .to_string(),
+        "{1 => 7, 2 => 3}"
+    );
+}
+
+#[test]
+fn test_interpret_getters() {
+    let source = "contract Token {\
+           mapping(uint => uint) public balances;\
+           uint[] public limits;\
+           uint total;\
+           function mint(uint owner, uint amount) { balances[owner] += amount; }\
+           function limit(uint value) { limits.push(value); }\
+         }";
+    let program = parser::parse_program(source).unwrap();
+    let mut interpreter = Interpreter::new(&program);
+    interpreter
+        .call("Token.mint", vec![int(1), int(5)])
+        .unwrap();
+    interpreter.call("Token.limit", vec![int(9)]).unwrap();
+    assert_eq!(interpreter.call("Token.balances", vec![int(1)]), Ok(int(5)));
+    assert_eq!(interpreter.call("Token.balances", vec![int(2)]), Ok(int(0)));
+    assert_eq!(interpreter.call("Token.limits", vec![int(0)]), Ok(int(9)));
+    assert_eq!(
+        interpreter
+            .call("Token.limits", vec![int(1)])
+            .unwrap_err()
+            .error,
+        ExecutionErrorType::IndexOutOfBounds
+    );
+    assert_eq!(
+        interpreter.call("Token.total", vec![]).unwrap_err().error,
+        ExecutionErrorType::Unsupported(String::from("`Token.total`"))
+    );
+}
+
+#[test]
+fn test_interpret_libraries() {
+    let source = "library SafeMath {\
+           function add(uint256 a, uint256 b) internal returns (uint256) {\
+             require(a + b >= a, \"overflow\");\
+             return a + b;\
+           }\
+         }\
+         library Flags {\
+           function flip(bool a) returns (bool) { return !a; }\
+         }\
+         contract Token {\
+           using SafeMath for uint256;\
+           using Flags for bool;\
+           uint256 total;\
+           bool open;\
+           function deposit(uint256 amount) returns (uint256) {\
+             total = total.add(amount);\
+             open = open.flip();\
+             return SafeMath.add(total, 1);\
+           }\
+         }";
+    let program = parser::parse_program(source).unwrap();
+    let mut interpreter = Interpreter::new(&program);
+    assert_eq!(interpreter.call("Token.deposit", vec![int(5)]), Ok(int(6)));
+    assert_eq!(interpreter.call("Token.deposit", vec![int(2)]), Ok(int(8)));
+    assert_eq!(interpreter.state_variable("Token", "total"), Ok(int(7)));
+    assert_eq!(
+        interpreter.state_variable("Token", "open"),
+        Ok(Value::Bool(false))
+    );
+}
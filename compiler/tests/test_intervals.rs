@@ -0,0 +1,87 @@
+use num_bigint::BigInt;
+use zoker_compiler::intervals::{
+    comparison_warnings, eliminate_safe_bounds_checks, type_interval, Interval,
+};
+use zoker_compiler::rewriter::rewrite_program;
+use zoker_compiler::symbol::{Operation, OperationType, SymbolType};
+use zoker_parser::parser;
+
+fn count_checks(operations: &[Operation]) -> usize {
+    operations
+        .iter()
+        .map(|op| match &op.operation {
+            OperationType::BoundsCheck { .. } => 1,
+            _ => count_checks(&op.children().into_iter().cloned().collect::<Vec<_>>()),
+        })
+        .sum()
+}
+
+fn interval(min: i64, max: i64) -> Interval {
+    Interval::new(BigInt::from(min), BigInt::from(max))
+}
+
+#[test]
+fn test_interval_arithmetic() {
+    assert_eq!(
+        type_interval(&SymbolType::Int(8)),
+        Some(interval(-128, 127))
+    );
+    assert_eq!(type_interval(&SymbolType::Bool), None);
+    assert_eq!(interval(1, 3).mul(&interval(-2, 4)), interval(-6, 12));
+    assert_eq!(interval(10, 20).div(&interval(2, 5)), Some(interval(2, 10)));
+    assert_eq!(interval(10, 20).div(&interval(0, 5)), None);
+    assert_eq!(
+        interval(0, 255).rem(&interval(10, 10)),
+        Some(interval(0, 9))
+    );
+    assert_eq!(interval(1, 2).shl(&interval(0, 3)), Some(interval(1, 16)));
+    assert_eq!(
+        interval(0, 10).hull(&interval(20, 30)).to_string(),
+        "[0, 30]"
+    );
+}
+
+#[test]
+fn test_comparison_warnings() {
+    let source = "contract Test {\
+           uint constant LIMIT = 10;\
+           function check(uint x, uint8 small, int y) returns (bool) {\
+             bool a = x >= 0;\
+             bool b = small > 300;\
+             bool c = x % LIMIT < LIMIT;\
+             bool d = x > 5;\
+             bool e = y >= 0;\
+             bool f = LIMIT == 10;\
+             return a;\
+           }\
+        }";
+    let program = parser::parse_program(source).unwrap();
+    let warnings: Vec<String> = comparison_warnings(&program)
+        .iter()
+        .map(|warning| format!("{}: {}", warning.location, warning))
+        .collect();
+    assert_eq!(
+        warnings,
+        vec![
+            "line 0 column 111: comparison is always true",
+            "line 0 column 131: comparison is always false",
+            "line 0 column 156: comparison is always true",
+        ]
+    );
+}
+
+#[test]
+fn test_safe_bounds_checks_are_removed() {
+    let program = parser::parse_program(
+        "contract Test {\
+           function get(uint[256] values, uint[4] few, uint8 i) returns (uint) {\
+             return values[i] + few[i];\
+           }\
+        }",
+    )
+    .unwrap();
+    let mut contract = rewrite_program(&program).unwrap().remove(0);
+    assert_eq!(count_checks(&contract.functions[0].operations), 2);
+    assert_eq!(eliminate_safe_bounds_checks(&mut contract), 1);
+    assert_eq!(count_checks(&contract.functions[0].operations), 1);
+}
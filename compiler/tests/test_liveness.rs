@@ -0,0 +1,82 @@
+use zoker_compiler::cfg::ControlFlowGraph;
+use zoker_compiler::liveness::{allocate_slots, Liveness};
+use zoker_compiler::rewriter::rewrite_program;
+use zoker_compiler::symbol::Function;
+use zoker_parser::parser;
+
+fn function(source: &str) -> Function {
+    let program = parser::parse_program(source).unwrap();
+    rewrite_program(&program)
+        .unwrap()
+        .remove(0)
+        .functions
+        .remove(0)
+}
+
+fn names(live: &std::collections::BTreeSet<(String, u32, bool)>) -> Vec<&str> {
+    live.iter().map(|(name, _, _)| name.as_str()).collect()
+}
+
+#[test]
+fn test_liveness_across_loop() {
+    let function = function(
+        "contract Test {\
+           function f(uint a, uint[] v) returns (uint) {\
+             uint b = a + 1;\
+             for i in v { b = b + i; b = b + a; };\
+             return b;\
+           }\
+        }",
+    );
+    let cfg = ControlFlowGraph::new(&function);
+    let liveness = Liveness::new(&cfg);
+    assert_eq!(names(liveness.live_in(cfg.entry)), vec!["a", "v"]);
+    // The loop header reads `v` and keeps `a` and `b` alive for the next iteration
+    let header = cfg.successors(cfg.entry)[0];
+    assert_eq!(names(liveness.live_in(header)), vec!["a", "b", "v"]);
+    assert!(liveness.live_in(cfg.exit).is_empty());
+    assert!(liveness
+        .to_string()
+        .starts_with("b0:\n  in:  [a#0(public), v#1(public)]\n"));
+}
+
+#[test]
+fn test_slots_are_reused() {
+    let function = function(
+        "contract Test {\
+           function f(uint a) returns (uint) {\
+             uint b = a + 1;\
+             a = b + 1;\
+             uint c = a + 2;\
+             return c;\
+           }\
+        }",
+    );
+    let slots = allocate_slots(&function);
+    assert_eq!(slots.slot_count, 1);
+    assert_eq!(slots.slots.len(), 3);
+}
+
+#[test]
+fn test_interfering_locals_get_distinct_slots() {
+    let function = function(
+        "contract Test {\
+           function f(uint a, uint d) returns (uint) {\
+             uint b = a + 1;\
+             uint c = a + b;\
+             return c;\
+           }\
+        }",
+    );
+    let slots = allocate_slots(&function);
+    let slot = |name: &str| {
+        let (local, slot) = slots.slots.iter().find(|(l, _)| l.0 == name).unwrap();
+        (slot, slots.offset(local).unwrap())
+    };
+    assert_eq!(slot("a"), (&0, 0));
+    assert_eq!(slot("d"), (&1, 32));
+    // `d` is never read, so `b` takes its slot
+    assert_eq!(slot("b"), (&1, 32));
+    assert_eq!(slot("c"), (&0, 0));
+    assert_eq!(slots.slot_count, 2);
+}
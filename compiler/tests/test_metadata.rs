@@ -0,0 +1,88 @@
+use zoker_compiler::driver::Compiler;
+use zoker_compiler::metadata::{append_metadata, split_metadata, COMPILER_VERSION};
+use zoker_compiler::settings::{CompileSettings, EvmVersion, OptLevel};
+
+const SOURCE: &str = "contract Test {\
+       function double(uint a) returns (uint) {\
+         return a * 2;\
+       }\
+    }";
+
+#[test]
+fn test_metadata() {
+    let mut compiler = Compiler::new();
+    let artifacts = compiler.compile(SOURCE).unwrap().artifacts.unwrap();
+    let metadata = &artifacts[0].metadata;
+    assert_eq!(
+        metadata.to_json(),
+        format!(
+            "{{\"compiler\":{{\"version\":\"{}\"}},\"contract\":\"Test\",\"language\":\"Zoker\",\
+             \"output\":{{\"abi\":[{{\"inputs\":[{{\"type\":\"uint256\"}}],\"name\":\"double\",\
+             \"outputs\":[{{\"type\":\"uint256\"}}],\"type\":\"function\"}}]}},\
+             \"settings\":{{\"boundsChecks\":true,\"defines\":{{\"flags\":[],\"values\":[]}},\
+             \"evmVersion\":\"cancun\",\"optLevel\":\"full\",\"target\":\"evm\"}},\
+             \"sources\":{{\"main.zok\":{{\"keccak256\":\"0x{}\"}}}},\"version\":1}}",
+            COMPILER_VERSION,
+            metadata.sources[0]
+                .1
+                .iter()
+                .map(|byte| format!("{:02x}", byte))
+                .collect::<String>()
+        )
+    );
+
+    // Other settings give other metadata
+    let mut compiler = Compiler::new();
+    compiler.settings.opt_level = OptLevel::None;
+    let unoptimized = compiler.compile(SOURCE).unwrap().artifacts.unwrap();
+    assert_ne!(unoptimized[0].metadata.hash(), metadata.hash());
+}
+
+#[test]
+fn test_metadata_trailer() {
+    let mut compiler = Compiler::new();
+    let artifacts = compiler.compile(SOURCE).unwrap().artifacts.unwrap();
+    let metadata = &artifacts[0].metadata;
+    let code = [0x60, 0x80, 0x60, 0x40];
+    let bytecode = append_metadata(&code, metadata);
+    assert_eq!(bytecode.len(), code.len() + 57);
+    assert_eq!(&bytecode[bytecode.len() - 2..], &[0x00, 0x37]);
+    assert_eq!(
+        split_metadata(&bytecode),
+        (&code[..], Some(metadata.hash()))
+    );
+    assert_eq!(split_metadata(&code), (&code[..], None));
+}
+
+#[test]
+fn test_compile_settings() {
+    let mut settings = CompileSettings::default();
+    settings.defines.flags.insert(String::from("debug"));
+    settings.defines.flags.insert(String::from("audit"));
+    let mut same = CompileSettings::default();
+    same.defines.flags.insert(String::from("audit"));
+    same.defines.flags.insert(String::from("debug"));
+    assert_eq!(settings.hash(), same.hash());
+    assert_eq!(
+        settings.to_json(),
+        "{\"boundsChecks\":true,\"defines\":{\"flags\":[\"audit\",\"debug\"],\"values\":[]},\
+         \"evmVersion\":\"cancun\",\"optLevel\":\"full\",\"target\":\"evm\"}"
+    );
+    let london = CompileSettings {
+        evm_version: EvmVersion::London,
+        ..settings.clone()
+    };
+    assert_ne!(london.hash(), settings.hash());
+
+    // The same source and settings give the same artifacts
+    let compile = || {
+        Compiler::with_settings(settings.clone())
+            .compile(SOURCE)
+            .unwrap()
+            .artifacts
+            .unwrap()
+    };
+    let artifacts = compile();
+    assert_eq!(artifacts, compile());
+    assert_eq!(artifacts[0].metadata.settings, settings);
+}
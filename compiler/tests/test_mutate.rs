@@ -0,0 +1,66 @@
+use num_bigint::BigUint;
+use zoker_compiler::interpreter::{Interpreter, Value};
+use zoker_compiler::mutate::{mutate, mutation_score, Mutation};
+use zoker_parser::ast::Operator;
+use zoker_parser::parser;
+
+#[test]
+fn test_mutate() {
+    let source = "function max(uint a, uint b) returns (uint) { return a > b ? a : b; }\
+         function one() returns (uint) { return 1; }\
+         @test \
+         function test_max() { assert(max(1, 2) == 2); }";
+    let program = parser::parse_program(source).unwrap();
+    let mutations: Vec<Mutation> = mutate(&program)
+        .into_iter()
+        .map(|mutant| mutant.mutation)
+        .collect();
+    assert_eq!(
+        mutations,
+        vec![
+            Mutation::NegateCondition,
+            Mutation::SwapOperator {
+                from: Operator::Gt,
+                to: Operator::Ge
+            },
+            Mutation::SwapOperator {
+                from: Operator::Gt,
+                to: Operator::Lt
+            },
+            Mutation::OffByOne {
+                from: BigUint::from(1u32),
+                to: BigUint::from(2u32)
+            },
+            Mutation::OffByOne {
+                from: BigUint::from(1u32),
+                to: BigUint::from(0u32)
+            },
+        ]
+    );
+
+    let mutant = &mutate(&program)[0];
+    assert_eq!(mutant.location.column(), 54);
+    let mut interpreter = Interpreter::new(&mutant.program);
+    let args = vec![Value::Int(3.into()), Value::Int(5.into())];
+    assert_eq!(interpreter.call("max", args), Ok(Value::Int(3.into())));
+}
+
+#[test]
+fn test_mutation_score() {
+    let source = "function max(uint a, uint b) returns (uint) { return a > b ? a : b; }\
+         @test \
+         function test_max() { assert(max(1, 2) == 2); assert(max(2, 2) == 2); }";
+    let program = parser::parse_program(source).unwrap();
+    let report = mutation_score(&program);
+    assert_eq!(report.killed, 2);
+    assert_eq!(
+        report.survived.iter().map(|(m, _)| m).collect::<Vec<_>>(),
+        vec![&Mutation::SwapOperator {
+            from: Operator::Gt,
+            to: Operator::Ge
+        }]
+    );
+    assert!(report
+        .to_string()
+        .starts_with("mutation score: 66.7% (2 of 3 killed)\n  survived at line 0 column"));
+}
@@ -0,0 +1,57 @@
+use num_bigint::BigUint;
+use num_traits::One;
+use zoker_compiler::peephole::{optimize, OptimizationLevel};
+use zoker_compiler::stack::StackOp::{self, *};
+
+fn push(value: u32) -> StackOp {
+    Push(BigUint::from(value))
+}
+
+#[test]
+fn test_basic_peephole() {
+    let mut ops = vec![
+        Dup(2),
+        Swap(1),
+        Swap(1),
+        push(0),
+        Add,
+        Dup(1),
+        Pop,
+        push(1),
+        Mul,
+        Swap(2),
+    ];
+    assert_eq!(optimize(&mut ops, OptimizationLevel::Basic), 4);
+    assert_eq!(ops, vec![Dup(2), Swap(2)]);
+
+    // Constants are only folded at the full level
+    let mut ops = vec![push(2), push(3), Add];
+    assert_eq!(optimize(&mut ops, OptimizationLevel::Basic), 0);
+    assert_eq!(optimize(&mut ops, OptimizationLevel::None), 0);
+    assert_eq!(ops, vec![push(2), push(3), Add]);
+}
+
+#[test]
+fn test_removal_exposes_more_rewrites() {
+    // Dropping `PUSH 0 ADD` brings the two swaps together
+    let mut ops = vec![Swap(1), push(0), Add, Swap(1), Dup(1)];
+    assert_eq!(optimize(&mut ops, OptimizationLevel::Basic), 2);
+    assert_eq!(ops, vec![Dup(1)]);
+}
+
+#[test]
+fn test_constant_folding() {
+    let mut ops = vec![push(2), push(3), Add, push(4), Mul, Dup(2)];
+    optimize(&mut ops, OptimizationLevel::Full);
+    assert_eq!(ops, vec![push(20), Dup(2)]);
+
+    // 3 - 5 wraps around
+    let mut ops = vec![push(5), push(3), Sub];
+    optimize(&mut ops, OptimizationLevel::Full);
+    let expected = (BigUint::one() << 256) - BigUint::from(2u32);
+    assert_eq!(ops, vec![Push(expected)]);
+
+    let mut ops = vec![push(5), push(3), Swap(1), Sub];
+    optimize(&mut ops, OptimizationLevel::Full);
+    assert_eq!(ops, vec![push(2)]);
+}
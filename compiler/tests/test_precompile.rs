@@ -0,0 +1,29 @@
+#![cfg(feature = "secp256k1")]
+
+use k256::ecdsa::SigningKey;
+use zoker_compiler::precompile::ecrecover;
+
+#[test]
+fn test_ecrecover() {
+    // The address of the private key `1` is well known.
+    let mut secret = [0; 32];
+    secret[31] = 1;
+    let key = SigningKey::from_slice(&secret).unwrap();
+    let hash = [7; 32];
+    let (signature, recovery_id) = key.sign_prehash_recoverable(&hash).unwrap();
+    let (r, s) = signature.split_bytes();
+    let mut r_bytes = [0; 32];
+    let mut s_bytes = [0; 32];
+    r_bytes.copy_from_slice(&r);
+    s_bytes.copy_from_slice(&s);
+
+    let address = ecrecover(&hash, 27 + recovery_id.to_byte(), &r_bytes, &s_bytes).unwrap();
+    assert_eq!(
+        address.to_vec(),
+        vec![
+            0x7e, 0x5f, 0x45, 0x52, 0x09, 0x1a, 0x69, 0x12, 0x5d, 0x5d, 0xfc, 0xb7, 0xb8, 0xc2,
+            0x65, 0x90, 0x29, 0x39, 0x5b, 0xdf
+        ]
+    );
+    assert_eq!(ecrecover(&hash, 29, &r_bytes, &s_bytes), None);
+}
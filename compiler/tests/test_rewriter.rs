@@ -1,5 +1,6 @@
+use zoker_compiler::builtin::BuiltinFunction;
 use zoker_compiler::rewriter::rewrite_program;
-use zoker_compiler::symbol::SymbolType;
+use zoker_compiler::symbol::{OperationType, SymbolType};
 use zoker_parser::parser;
 
 #[test]
@@ -55,3 +56,114 @@ fn test_rewriting_multiple_function() {
     assert_eq!(contracts[0].functions[1].params[1].num, 0);
     assert_eq!(contracts[0].functions[1].params[2].num, 1);
 }
+
+#[test]
+fn test_rewriting_builtin_call() {
+    let source = "contract Test {\
+           function hash(bytes data) returns (bytes32) {\
+             return sha256(data);\
+           }\
+        }";
+    let program = parser::parse_program(source).unwrap();
+    let contracts = rewrite_program(&program).unwrap();
+    let operation = &contracts[0].functions[0].operations[0].operation;
+    if let OperationType::Return { ret } = operation {
+        assert!(matches!(
+            ret.operation,
+            OperationType::Builtin {
+                function: BuiltinFunction::Sha256,
+                ..
+            }
+        ));
+    } else {
+        panic!("expected return operation, got {:?}", operation);
+    }
+}
+
+#[test]
+fn test_free_functions() {
+    let source = "function double(uint a) returns (uint) { return twice(a); }\
+        function twice(uint a) returns (uint) { return a + a; }\
+        function unused() { }\
+        contract Test {\
+          function f(uint a) returns (uint) { return double(a); }\
+        }\
+        contract Other {\
+          function twice(uint a) returns (uint) { return a; }\
+          function g(uint a) returns (uint) { return double(a); }\
+        }";
+    let program = parser::parse_program(source).unwrap();
+    let contracts = rewrite_program(&program).unwrap();
+    let names = |index: usize| -> Vec<(&str, bool)> {
+        contracts[index]
+            .functions
+            .iter()
+            .map(|function| (function.name.as_str(), function.is_free))
+            .collect()
+    };
+    // Only the free functions called, directly or not, are linked
+    assert_eq!(
+        names(0),
+        vec![("f", false), ("double", true), ("twice", true)]
+    );
+    // Functions of the contract shadow free functions
+    assert_eq!(
+        names(1),
+        vec![("twice", false), ("g", false), ("double", true)]
+    );
+}
+
+#[test]
+fn test_module_functions() {
+    let source = "module math {\
+          function clamp(uint a) returns (uint) { return low(a); }\
+          function low(uint a) returns (uint) { return bits::low(a); }\
+          module bits {\
+            function low(uint a) returns (uint) { return a; }\
+          }\
+        }\
+        contract Test {\
+          function low(uint a) returns (uint) { return a; }\
+          function f(uint a) returns (uint) { return math::clamp(a); }\
+        }";
+    let program = parser::parse_program(source).unwrap();
+    let contracts = rewrite_program(&program).unwrap();
+    let names: Vec<(&str, bool)> = contracts[0]
+        .functions
+        .iter()
+        .map(|function| (function.name.as_str(), function.is_free))
+        .collect();
+    // Calls inside a module resolve to the innermost module declaring the function
+    assert_eq!(
+        names,
+        vec![
+            ("low", false),
+            ("f", false),
+            ("math::clamp", true),
+            ("math::low", true),
+            ("math::bits::low", true)
+        ]
+    );
+}
+
+#[test]
+fn test_fixed_array_lengths() {
+    let source = "uint constant N = 3;\
+        contract Test {\
+           uint constant M = N * 2;\
+           function get(uint[N] a, uint[M] b) returns (uint) {\
+             return a[0] + b[0];\
+           }\
+        }";
+    let program = parser::parse_program(source).unwrap();
+    let contracts = rewrite_program(&program).unwrap();
+    let params = &contracts[0].functions[0].params;
+    assert_eq!(
+        params[0].symbol_type,
+        SymbolType::FixedArray(Box::new(SymbolType::Uint256), 3)
+    );
+    assert_eq!(
+        params[1].symbol_type,
+        SymbolType::FixedArray(Box::new(SymbolType::Uint256), 6)
+    );
+}
@@ -0,0 +1,148 @@
+use num_bigint::BigUint;
+use zoker_compiler::rewriter::rewrite_program;
+use zoker_compiler::sccp::{propagate_constants, propagate_function_constants};
+use zoker_compiler::symbol::{Contract, Operation, OperationType};
+use zoker_parser::parser;
+
+fn compile(source: &str) -> Contract {
+    let program = parser::parse_program(source).unwrap();
+    rewrite_program(&program).unwrap().remove(0)
+}
+
+fn constant(operation: &Operation) -> Option<BigUint> {
+    match &operation.operation {
+        OperationType::Constant { value } => Some(value.clone()),
+        _ => None,
+    }
+}
+
+fn assigned(operation: &Operation) -> &Operation {
+    match &operation.operation {
+        OperationType::Assign { right, .. } => right,
+        op => panic!("unexpected operation {:?}", op),
+    }
+}
+
+fn returned(operation: &Operation) -> &Operation {
+    match &operation.operation {
+        OperationType::Return { ret } => ret,
+        op => panic!("unexpected operation {:?}", op),
+    }
+}
+
+#[test]
+fn test_constant_branch_is_removed() {
+    let mut contract = compile(
+        "contract Test {\
+           function f(uint a) returns (uint) {\
+             uint x = 2;\
+             uint y = x + 3;\
+             if y - 5 { a = a + 1; } else { a = a + y; };\
+             return a + x;\
+           }\
+        }",
+    );
+    let stats = propagate_constants(&mut contract);
+    assert_eq!(stats.removed_branches, 2);
+    let operations = &contract.functions[0].operations;
+    assert_eq!(operations.len(), 4);
+    assert_eq!(
+        constant(assigned(&operations[1])),
+        Some(BigUint::from(5u32))
+    );
+    // Only the else branch is left, with `y` replaced by its value
+    assert_eq!(assigned(&operations[2]).to_string(), "(a + 5)");
+    assert_eq!(returned(&operations[3]).to_string(), "(a + 2)");
+}
+
+#[test]
+fn test_values_join_after_unknown_branch() {
+    let mut contract = compile(
+        "contract Test {\
+           function f(uint a, bool c) returns (uint) {\
+             uint x = 1;\
+             uint y = 3;\
+             if c { x = 2; y = 3; } else { x = 1; };\
+             return x + y;\
+           }\
+        }",
+    );
+    let stats = propagate_function_constants(&mut contract.functions[0]);
+    assert_eq!(stats.removed_branches, 0);
+    let operations = &contract.functions[0].operations;
+    assert!(matches!(operations[2].operation, OperationType::If { .. }));
+    assert!(matches!(
+        operations[3].operation,
+        OperationType::Else { .. }
+    ));
+    // `x` differs between the branches, `y` doesn't
+    assert_eq!(returned(&operations[4]).to_string(), "(x + 3)");
+}
+
+#[test]
+fn test_loop_reaches_fixpoint() {
+    let mut contract = compile(
+        "contract Test {\
+           function f(uint[] v) returns (uint) {\
+             uint k = 4;\
+             uint s = 0;\
+             for i in v { s = s + k; };\
+             return s + k;\
+           }\
+        }",
+    );
+    propagate_constants(&mut contract);
+    let operations = &contract.functions[0].operations;
+    match &operations[2].operation {
+        OperationType::For { stmts, .. } => {
+            assert_eq!(assigned(&stmts[0]).to_string(), "(s + 4)")
+        }
+        op => panic!("unexpected operation {:?}", op),
+    }
+    assert_eq!(returned(&operations[3]).to_string(), "(s + 4)");
+}
+
+#[test]
+fn test_code_after_return_is_removed() {
+    let mut contract = compile(
+        "contract Test {\
+           function f(uint a) returns (uint) {\
+             if 1 { return a; };\
+             a = a + 1;\
+             return a;\
+           }\
+        }",
+    );
+    let stats = propagate_constants(&mut contract);
+    assert_eq!(stats.removed_branches, 1);
+    assert_eq!(stats.removed_unreachable, 2);
+    let operations = &contract.functions[0].operations;
+    assert_eq!(operations.len(), 1);
+    assert_eq!(returned(&operations[0]).to_string(), "a");
+    assert_eq!(
+        *returned(&operations[0]),
+        Operation::new_symbol(contract.functions[0].params[0].clone())
+    );
+}
+
+#[test]
+fn test_overflowing_values_are_not_folded() {
+    let mut contract = compile(
+        "contract Test {\
+           function f() returns (uint8) {\
+             uint8 x = 200;\
+             uint8 y = x + 100;\
+             return x + 50;\
+           }\
+        }",
+    );
+    propagate_constants(&mut contract);
+    let operations = &contract.functions[0].operations;
+    // `200 + 100` doesn't fit `uint8`, it's left to revert at runtime
+    assert_eq!(constant(assigned(&operations[1])), None);
+    assert_eq!(assigned(&operations[1]).to_string(), "(x + 100)");
+    assert_eq!(
+        constant(returned(&operations[2])),
+        Some(BigUint::from(250u32))
+    );
+}
@@ -0,0 +1,46 @@
+use zoker_compiler::snapshot::snapshot_symbol_table;
+use zoker_compiler::symbol_table::make_symbol_table;
+use zoker_parser::parser;
+use zoker_parser::snapshot::SnapshotConfig;
+
+#[test]
+fn test_snapshot_symbol_table() {
+    let source = "contract Test {\
+           uint total;\
+           event Added(uint amount);\
+           @inline function add(uint x) returns (uint) { total = total + x; return total; }\
+         }";
+    let program = parser::parse_program(source).unwrap();
+    let table = make_symbol_table(&program).unwrap();
+    let config = SnapshotConfig { locations: false };
+    let snapshot = snapshot_symbol_table(&table, &config);
+    let contract: Vec<&str> = snapshot
+        .lines()
+        .skip_while(|line| !line.starts_with("  table Contract"))
+        .collect();
+    assert_eq!(
+        contract,
+        vec![
+            "  table Contract Test",
+            "    event Added(uint256)",
+            "    symbol add: function(uint) returns (uint) declared @inline",
+            "    symbol total: uint declared storage",
+            "    table Function add nonpayable",
+            "      symbol x: uint declared parameter",
+            "      table Local #Compound",
+            "        symbol total: null read written",
+            "        symbol x: null read",
+        ]
+    );
+    assert!(snapshot.starts_with("table Global #Global\n"));
+
+    // Symbols are sorted whatever the order of their declarations
+    let reordered = "contract Test {\
+           @inline function add(uint x) returns (uint) { total = total + x; return total; }\
+           event Added(uint amount);\
+           uint total;\
+         }";
+    let program = parser::parse_program(reordered).unwrap();
+    let table = make_symbol_table(&program).unwrap();
+    assert_eq!(snapshot_symbol_table(&table, &config), snapshot);
+}
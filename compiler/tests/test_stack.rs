@@ -0,0 +1,139 @@
+use num_bigint::BigUint;
+use std::collections::BTreeSet;
+use zoker_compiler::liveness::Local;
+use zoker_compiler::stack::{schedule_block, StackOp};
+use zoker_compiler::symbol::{Operation, OperationType, Symbol, SymbolLocation, SymbolType};
+
+fn local(name: &str) -> Local {
+    (name.to_string(), 0, false)
+}
+
+fn sym(name: &str) -> Box<Operation> {
+    Box::new(Operation::new_symbol(Symbol::new(
+        name.to_string(),
+        0,
+        SymbolType::Uint256,
+        SymbolLocation::Unknown,
+        false,
+    )))
+}
+
+fn constant(value: u32) -> Box<Operation> {
+    Box::new(Operation::new(OperationType::Constant {
+        value: BigUint::from(value),
+    }))
+}
+
+fn assign(name: &str, right: OperationType) -> Operation {
+    Operation::new(OperationType::Assign {
+        left: sym(name),
+        right: Box::new(Operation::new(right)),
+    })
+}
+
+fn ret(name: &str) -> Operation {
+    Operation::new(OperationType::Return { ret: sym(name) })
+}
+
+#[test]
+fn test_operands_used_in_place() {
+    let operations = vec![
+        assign(
+            "c",
+            OperationType::Add {
+                left: sym("a"),
+                right: sym("b"),
+            },
+        ),
+        ret("c"),
+    ];
+    let schedule =
+        schedule_block(&[local("a"), local("b")], &operations, &BTreeSet::new()).unwrap();
+    assert_eq!(schedule.ops, vec![StackOp::Add]);
+    assert_eq!(schedule.stack, vec![None]);
+
+    // SUB takes its left operand from the top
+    let operations = vec![
+        assign(
+            "c",
+            OperationType::Sub {
+                left: sym("a"),
+                right: sym("b"),
+            },
+        ),
+        ret("c"),
+    ];
+    let schedule =
+        schedule_block(&[local("a"), local("b")], &operations, &BTreeSet::new()).unwrap();
+    assert_eq!(schedule.ops, vec![StackOp::Swap(1), StackOp::Sub]);
+}
+
+#[test]
+fn test_live_local_is_copied_and_replaced() {
+    let operations = vec![assign(
+        "a",
+        OperationType::Add {
+            left: sym("a"),
+            right: constant(1),
+        },
+    )];
+    let live_out = vec![local("a")].into_iter().collect();
+    let schedule = schedule_block(&[local("a")], &operations, &live_out).unwrap();
+    assert_eq!(
+        schedule.ops,
+        vec![
+            StackOp::Dup(1),
+            StackOp::Push(BigUint::from(1u32)),
+            StackOp::Add,
+            StackOp::Swap(1),
+            StackOp::Pop,
+        ]
+    );
+    assert_eq!(schedule.stack, vec![Some(local("a"))]);
+    assert_eq!(schedule.shuffles(), 3);
+    let listing: Vec<String> = schedule.ops.iter().map(|op| op.to_string()).collect();
+    assert_eq!(listing, vec!["DUP1", "PUSH 1", "ADD", "SWAP1", "POP"]);
+}
+
+#[test]
+fn test_dead_locals_are_popped() {
+    let operations = vec![
+        assign(
+            "d",
+            OperationType::Add {
+                left: sym("a"),
+                right: constant(1),
+            },
+        ),
+        ret("d"),
+    ];
+    let inputs = [local("a"), local("b"), local("c")];
+    let schedule = schedule_block(&inputs, &operations, &BTreeSet::new()).unwrap();
+    assert_eq!(
+        schedule.ops,
+        vec![
+            StackOp::Pop,
+            StackOp::Pop,
+            StackOp::Push(BigUint::from(1u32)),
+            StackOp::Add,
+        ]
+    );
+}
+
+#[test]
+fn test_unschedulable_blocks() {
+    // The bottom slot is 17 deep once the result is pushed
+    let inputs: Vec<Local> = (0..17).map(|i| local(&format!("x{}", i))).collect();
+    let operations = vec![assign(
+        "y",
+        OperationType::Add {
+            left: sym("x0"),
+            right: constant(1),
+        },
+    )];
+    let live_out = inputs.iter().cloned().collect();
+    assert_eq!(schedule_block(&inputs, &operations, &live_out), None);
+
+    let operations = vec![Operation::new_call("f".to_string(), vec![])];
+    assert_eq!(schedule_block(&[], &operations, &BTreeSet::new()), None);
+}
@@ -0,0 +1,78 @@
+use zoker_compiler::error::SymbolTableErrorType;
+use zoker_compiler::rewriter::rewrite_program;
+use zoker_compiler::stdlib::{std_module, std_modules};
+use zoker_compiler::symbol_table::{make_symbol_table, StateMutability};
+use zoker_parser::parser;
+
+#[test]
+fn test_std_modules() {
+    let paths: Vec<String> = std_modules().iter().map(|module| module.path()).collect();
+    assert_eq!(paths, vec!["std/math", "std/hash", "std/utils"]);
+    for path in &paths {
+        assert_eq!(std_module(path).unwrap().path(), *path);
+        let source = format!("import \"{}\"; contract Test {{ }}", path);
+        let program = parser::parse_program(&source).unwrap();
+        assert!(make_symbol_table(&program).is_ok());
+        assert!(rewrite_program(&program).is_ok());
+    }
+    assert!(std_module("std/unknown").is_none());
+    assert!(std_module("math").is_none());
+    // Modules are loaded once
+    assert!(std::ptr::eq(
+        std_module("std/math").unwrap(),
+        &std_modules()[0]
+    ));
+}
+
+#[test]
+fn test_std_import() {
+    let source = "import \"std/math\";\
+        import \"std/hash\";\
+        import \"std/math\";\
+        contract Test {\
+          function f(uint x) returns (uint) { return std::math::cube(x); }\
+          function g(bytes32 a) returns (bytes32) { return std::hash::pair(a, a); }\
+        }";
+    let program = parser::parse_program(source).unwrap();
+    let table = make_symbol_table(&program).unwrap();
+    let path = |path: &str| -> Vec<String> { path.split("::").map(String::from).collect() };
+    assert!(table.lookup_path(&path("std::math::square")).is_some());
+    assert!(table.lookup_path(&path("std::utils::ether")).is_none());
+    assert_eq!(
+        table.function_mutability("Test", "f"),
+        Some(StateMutability::Pure)
+    );
+
+    let contracts = rewrite_program(&program).unwrap();
+    let names: Vec<&str> = contracts[0]
+        .functions
+        .iter()
+        .map(|function| function.name.as_str())
+        .collect();
+    assert_eq!(
+        names,
+        vec![
+            "f",
+            "g",
+            "std::hash::pair",
+            "std::math::cube",
+            "std::math::square"
+        ]
+    );
+}
+
+#[test]
+fn test_std_import_errors() {
+    let error = |source: &str| {
+        let program = parser::parse_program(source).unwrap();
+        make_symbol_table(&program).unwrap_err().error
+    };
+    assert_eq!(
+        error("import \"std/random\"; contract Test { }"),
+        SymbolTableErrorType::UnknownImport("std/random".to_string())
+    );
+    assert_eq!(
+        error("contract Test { function f() returns (uint) { return std::math::square(2); } }"),
+        SymbolTableErrorType::NotDeclared("std::math::square".to_string())
+    );
+}
@@ -0,0 +1,123 @@
+use zoker_compiler::storage::{storage_layout, suggest_packing, transient_layout};
+use zoker_compiler::symbol_table::make_symbol_table;
+use zoker_parser::parser;
+
+#[test]
+fn test_storage_layout() {
+    let source = "contract Test {\
+           bool a;\
+           address owner;\
+           uint total;\
+           bool b;\
+           string name;\
+           bool c;\
+           function f(bool d) { }\
+        }";
+    let program = parser::parse_program(source).unwrap();
+    let table = make_symbol_table(&program).unwrap();
+    let layout = storage_layout(&table, "Test").unwrap();
+    let position = |name| {
+        let entry = layout.entry(name).unwrap();
+        (entry.slot, entry.offset)
+    };
+    // `a` and `owner` share the first slot
+    assert_eq!(position("a"), (0, 0));
+    assert_eq!(position("owner"), (0, 1));
+    assert_eq!(position("total"), (1, 0));
+    assert_eq!(position("b"), (2, 0));
+    // Dynamic values take a slot of their own
+    assert_eq!(position("name"), (3, 0));
+    assert_eq!(position("c"), (4, 0));
+    assert_eq!(layout.slot_count, 5);
+    assert!(layout.entry("d").is_none());
+    assert!(storage_layout(&table, "Other").is_none());
+}
+
+#[test]
+fn test_fixed_array_and_struct_layout() {
+    let source = "contract Test {\
+           struct Point { uint x; uint y; }\
+           enum Kind { A, B }\
+           uint[3] a;\
+           uint b;\
+           bool c;\
+           uint8[40] d;\
+           Point p;\
+           bool e;\
+           Kind k;\
+        }";
+    let program = parser::parse_program(source).unwrap();
+    let table = make_symbol_table(&program).unwrap();
+    let layout = storage_layout(&table, "Test").unwrap();
+    let position = |name| {
+        let entry = layout.entry(name).unwrap();
+        (entry.slot, entry.offset, entry.size)
+    };
+    // `a` takes slots 0 to 2
+    assert_eq!(position("a"), (0, 0, 96));
+    assert_eq!(position("b"), (3, 0, 32));
+    assert_eq!(position("c"), (4, 0, 1));
+    // 32 elements of `d` are packed in each slot
+    assert_eq!(position("d"), (5, 0, 64));
+    assert_eq!(position("p"), (7, 0, 64));
+    assert_eq!(position("e"), (9, 0, 1));
+    assert_eq!(position("k"), (9, 1, 1));
+    assert_eq!(layout.slot_count, 10);
+}
+
+#[test]
+fn test_packing_suggestion() {
+    let source = "contract Test {\
+           bool a;\
+           uint total;\
+           address owner;\
+           bool b;\
+           address spender;\
+        }\
+        contract Packed {\
+           address owner;\
+           bool a;\
+           uint total;\
+        }";
+    let program = parser::parse_program(source).unwrap();
+    let table = make_symbol_table(&program).unwrap();
+    let suggestions = suggest_packing(&table);
+    assert_eq!(suggestions.len(), 1);
+    let suggestion = &suggestions[0];
+    assert_eq!(
+        suggestion.order,
+        vec!["total", "owner", "a", "b", "spender"]
+    );
+    assert_eq!((suggestion.slots, suggestion.packed_slots), (4, 3));
+    assert_eq!(
+        suggestion.to_string(),
+        "state variables of `Test` use 4 slots, \
+         declaring them as `total, owner, a, b, spender` uses 3 (saves 1)"
+    );
+}
+
+#[test]
+fn test_transient_layout() {
+    let source = "contract Test {\
+           uint total;\
+           bool transient locked;\
+           address transient caller;\
+           uint transient depth;\
+        }";
+    let program = parser::parse_program(source).unwrap();
+    let table = make_symbol_table(&program).unwrap();
+    // Transient storage is separate from storage
+    let layout = storage_layout(&table, "Test").unwrap();
+    assert_eq!(layout.slot_count, 1);
+    assert!(layout.entry("locked").is_none());
+    let transient = transient_layout(&table, "Test").unwrap();
+    let positions: Vec<(&str, usize, usize)> = transient
+        .entries
+        .iter()
+        .map(|entry| (entry.name.as_str(), entry.slot, entry.offset))
+        .collect();
+    assert_eq!(
+        positions,
+        vec![("locked", 0, 0), ("caller", 0, 1), ("depth", 1, 0)]
+    );
+}
@@ -0,0 +1,56 @@
+use zoker_compiler::rewriter::rewrite_program;
+use zoker_compiler::strength::reduce_strength;
+use zoker_compiler::symbol::{Contract, OperationType};
+use zoker_parser::parser;
+
+fn compile(source: &str) -> Contract {
+    let program = parser::parse_program(source).unwrap();
+    rewrite_program(&program).unwrap().remove(0)
+}
+
+fn reduced(expression: &str) -> (String, usize) {
+    reduced_typed("uint", expression)
+}
+
+fn reduced_typed(typ: &str, expression: &str) -> (String, usize) {
+    let mut contract = compile(&format!(
+        "contract Test {{\
+           function f({typ} a) returns ({typ}) {{\
+             return {expression};\
+           }}\
+        }}",
+        typ = typ,
+        expression = expression
+    ));
+    let count = reduce_strength(&mut contract);
+    match &contract.functions[0].operations[0].operation {
+        OperationType::Return { ret } => (ret.to_string(), count),
+        op => panic!("unexpected operation {:?}", op),
+    }
+}
+
+#[test]
+fn test_powers_of_two_become_shifts() {
+    assert_eq!(reduced("a / 4"), ("(a >> 2)".to_string(), 1));
+    assert_eq!(reduced_typed("uint8", "a / 4"), ("(a >> 2)".to_string(), 1));
+    // Shifts would round negative values down and ignore the prime of fields
+    assert_eq!(reduced_typed("int", "a / 2"), ("(a / 2)".to_string(), 0));
+    assert_eq!(reduced_typed("field", "a / 2"), ("(a / 2)".to_string(), 0));
+    // Shifts would drop the overflow check
+    assert_eq!(reduced("a * 8"), ("(a * 8)".to_string(), 0));
+    assert_eq!(reduced("2 * a"), ("(2 * a)".to_string(), 0));
+    assert_eq!(reduced("a * 1 + a / 1"), ("(a + a)".to_string(), 2));
+    assert_eq!(reduced("a * 3"), ("(a * 3)".to_string(), 0));
+    assert_eq!(reduced("a / 6"), ("(a / 6)".to_string(), 0));
+}
+
+#[test]
+fn test_squares_become_multiplications() {
+    assert_eq!(reduced("a ** 2"), ("(a * a)".to_string(), 1));
+    assert_eq!(reduced("a ** 1"), ("a".to_string(), 1));
+    assert_eq!(reduced("a ** 0"), ("1".to_string(), 1));
+    // The base would be evaluated twice
+    assert_eq!(reduced("(a + 1) ** 2"), ("((a + 1) ** 2)".to_string(), 0));
+    // Reduced bottom up
+    assert_eq!(reduced("(a ** 2) / 4"), ("((a * a) >> 2)".to_string(), 2));
+}
@@ -0,0 +1,157 @@
+use num_bigint::BigInt;
+use zoker_compiler::coercion::literal_value;
+use zoker_compiler::error::SymbolTableErrorType;
+use zoker_compiler::structs::{struct_literal, StructLiteralErrorKind};
+use zoker_compiler::symbol::SymbolType;
+use zoker_compiler::symbol_table::make_symbol_table;
+use zoker_parser::parser;
+
+fn type_error(source: &str) -> String {
+    let program = parser::parse_program(source).unwrap();
+    match make_symbol_table(&program).unwrap_err().error {
+        SymbolTableErrorType::TypeError(message) => message,
+        error => panic!("unexpected error {:?}", error),
+    }
+}
+
+#[test]
+fn test_struct_literals() {
+    let source = "contract Test {\
+           struct Point { uint8 x; uint8 y; }\
+           function f() {\
+             Point a = Point({y: 2, x: 1});\
+             Point b = Point(1, 2);\
+           }\
+        }";
+    let program = parser::parse_program(source).unwrap();
+    let table = make_symbol_table(&program).unwrap();
+    let declaration = &table
+        .contract_table("Test")
+        .unwrap()
+        .lookup("Point")
+        .unwrap()
+        .symbol_type;
+    assert_eq!(
+        *declaration,
+        SymbolType::Struct {
+            name: "Point".to_string(),
+            fields: vec![
+                ("x".to_string(), SymbolType::Uint(8)),
+                ("y".to_string(), SymbolType::Uint(8)),
+            ],
+        }
+    );
+
+    // Both forms list the values in order of declaration
+    for source in &["Point({y: 2, x: 1})", "Point(1, 2)"] {
+        let expression = parser::parse_expression(source).unwrap();
+        let literal = struct_literal(declaration, &expression).unwrap().unwrap();
+        assert_eq!(
+            literal.symbol_type(),
+            SymbolType::UserDefined("Point".to_string())
+        );
+        let fields: Vec<(&str, Option<BigInt>)> = literal
+            .fields
+            .iter()
+            .map(|field| (field.name.as_str(), literal_value(field.value)))
+            .collect();
+        assert_eq!(
+            fields,
+            vec![("x", Some(BigInt::from(1))), ("y", Some(BigInt::from(2)))],
+            "{}",
+            source
+        );
+    }
+
+    let expression = parser::parse_expression("Point({x: 1, x: 2})").unwrap();
+    let error = struct_literal(declaration, &expression)
+        .unwrap()
+        .unwrap_err();
+    assert_eq!(
+        error.kind,
+        StructLiteralErrorKind::DuplicateField("x".to_string())
+    );
+    let expression = parser::parse_expression("f(1, 2)").unwrap();
+    assert!(struct_literal(&SymbolType::Uint256, &expression).is_none());
+}
+
+#[test]
+fn test_struct_literal_errors() {
+    let declaration = "contract Test {\
+           struct Point { uint8 x; uint8 y; }\
+           function f(bool c) { Point p = ";
+    let check = |literal: &str| type_error(&format!("{}{}; }} }}", declaration, literal));
+    assert_eq!(
+        check("Point(1, 2, 3)"),
+        "`Point` has 2 field(s) but 3 value(s) were given"
+    );
+    assert_eq!(check("Point({x: 1, z: 2})"), "`Point` has no field `z`");
+    assert_eq!(check("Point({y: 1})"), "missing field(s) `x` of `Point`");
+    assert_eq!(
+        check("Point({x: 1, y: 256})"),
+        "`Point` expects `uint8` for field `y` but `256` was given (the value is out of range)"
+    );
+    assert_eq!(
+        check("Point(1, c)"),
+        "`Point` expects `uint8` for field `y` but `bool` was given"
+    );
+
+    assert_eq!(
+        type_error("contract Test { struct Point { uint x; bool x; } }"),
+        "field `x` of `Point` is declared twice"
+    );
+    assert_eq!(
+        type_error("contract Test { struct Point { uint x = 1; } }"),
+        "fields of `Point` need a name and no default value"
+    );
+}
+
+#[test]
+fn test_recursive_structs() {
+    assert_eq!(
+        type_error("contract Test { struct S { S s; } }"),
+        "struct `S` contains itself through field `s`"
+    );
+    assert_eq!(
+        type_error("contract Test { struct A { uint x; B b; } struct B { A a; } }"),
+        "struct `A` contains itself through field `b`"
+    );
+    let source = "contract Test {\
+           struct Leaf { uint value; }\
+           struct Node { Leaf left; Leaf right; }\
+        }";
+    let program = parser::parse_program(source).unwrap();
+    assert!(make_symbol_table(&program).is_ok());
+}
+
+#[test]
+fn test_struct_member_access() {
+    let source = "contract Test {\
+           struct Point { uint8 x; uint8 y; }\
+           function f(Point p) returns (uint8) {\
+             uint8 sum = p.x + p.y;\
+             return sum;\
+           }\
+        }";
+    let program = parser::parse_program(source).unwrap();
+    assert!(make_symbol_table(&program).is_ok());
+
+    assert_eq!(
+        type_error(
+            "contract Test {\
+               struct Point { uint8 x; uint8 y; }\
+               function f(Point p) { uint8 z = p.z; }\
+            }"
+        ),
+        "`Point` has no field `z`"
+    );
+    assert_eq!(
+        type_error(
+            "contract Test {\
+               struct Point { uint8 x; uint8 y; }\
+               function f(Point p) { address owner = p.x; }\
+            }"
+        ),
+        "cannot implicitly convert `uint8` to `address`"
+    );
+}
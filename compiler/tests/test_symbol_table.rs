@@ -0,0 +1,1613 @@
+use num_bigint::BigUint;
+use zoker_compiler::annotation::{deprecated_uses, Annotation};
+use zoker_compiler::builtin::{type_members, BuiltinFunction, StateAccess};
+use zoker_compiler::error::SymbolTableErrorType;
+use zoker_compiler::symbol::{SymbolLocation, SymbolType};
+use zoker_compiler::symbol_table::{
+    make_sources_symbol_table, make_symbol_table, suggest_mutability, StateMutability, Symbol,
+    SymbolKind, SymbolTableType, SymbolUsage,
+};
+use zoker_parser::ast::{Program, Visibility};
+use zoker_parser::interface::{interface_source, InterfaceConfig};
+use zoker_parser::location::Location;
+use zoker_parser::parser;
+
+#[test]
+fn test_builtin_hash_functions() {
+    let source = "contract Test {\
+           function hash(bytes data) returns (bytes32) {\
+             return keccak256(data);\
+           }\
+           function hash2(bytes data) returns (bytes32) {\
+             return sha256(blake2s(data));\
+           }\
+        }";
+    let program = parser::parse_program(source).unwrap();
+    let table = make_symbol_table(&program).unwrap();
+    assert_eq!(table.table_type, SymbolTableType::Global);
+    for name in &["keccak256", "sha256", "blake2s"] {
+        let symbol = table.lookup(name).unwrap();
+        assert_eq!(
+            symbol.symbol_type,
+            SymbolType::Function {
+                params: vec![SymbolType::Bytes],
+                returns: vec![SymbolType::Bytes32],
+            }
+        );
+    }
+    assert_eq!(
+        BuiltinFunction::from_name("sha256"),
+        Some(BuiltinFunction::Sha256)
+    );
+}
+
+#[test]
+fn test_builtin_argument_count() {
+    let source = "contract Test {\
+           function hash(bytes a, bytes b) returns (bytes32) {\
+             return keccak256(a, b);\
+           }\
+        }";
+    let program = parser::parse_program(source).unwrap();
+    let err = make_symbol_table(&program).unwrap_err();
+    assert!(matches!(err.error, SymbolTableErrorType::TypeError(_)));
+}
+
+#[test]
+fn test_builtin_assertions() {
+    let source = "contract Test {\
+           uint total;\
+           function withdraw(uint amount) {\
+             require(amount <= total, \"insufficient\");\
+             assert(total >= amount);\
+             if amount == 0 { revert(\"empty\"); };\
+             total -= amount;\
+           }\
+        }";
+    let program = parser::parse_program(source).unwrap();
+    assert!(make_symbol_table(&program).is_ok());
+
+    let source = "contract Test { function check(uint a) { require(a); } }";
+    let program = parser::parse_program(source).unwrap();
+    let err = make_symbol_table(&program).unwrap_err();
+    assert!(matches!(err.error, SymbolTableErrorType::TypeError(_)));
+}
+
+#[test]
+fn test_not_declared_symbol() {
+    let source = "contract Test {\
+           function add(uint a) returns (uint) {\
+             return a + b;\
+           }\
+        }";
+    let program = parser::parse_program(source).unwrap();
+    let err = make_symbol_table(&program).unwrap_err();
+    assert_eq!(
+        err.error,
+        SymbolTableErrorType::NotDeclared("b".to_string())
+    );
+}
+
+#[test]
+fn test_builtin_abi_functions() {
+    let source = "contract Test {\
+           function pack(uint a, bool b) returns (bytes) {\
+             return abi.encode(a, b, abi.encodePacked(a));\
+           }\
+           function unpack(bytes data) {\
+             abi.decode(data, (uint, bool));\
+           }\
+        }";
+    let program = parser::parse_program(source).unwrap();
+    let table = make_symbol_table(&program).unwrap();
+    assert_eq!(
+        table.lookup("abi").unwrap().symbol_type,
+        SymbolType::Namespace(String::from("abi"))
+    );
+
+    let source = "contract Test {\
+           function unpack(bytes data) {\
+             abi.decode(data, data);\
+           }\
+        }";
+    let program = parser::parse_program(source).unwrap();
+    let err = make_symbol_table(&program).unwrap_err();
+    assert!(matches!(err.error, SymbolTableErrorType::TypeError(_)));
+}
+
+#[test]
+fn test_builtin_ecrecover() {
+    let source = "contract Test {\
+           function signer(bytes32 hash, uint v, bytes32 r, bytes32 s) returns (address) {\
+             return ecrecover(hash, v, r, s);\
+           }\
+        }";
+    let program = parser::parse_program(source).unwrap();
+    let table = make_symbol_table(&program).unwrap();
+    assert_eq!(
+        table.lookup("ecrecover").unwrap().symbol_type,
+        SymbolType::Function {
+            params: vec![
+                SymbolType::Bytes32,
+                SymbolType::Uint256,
+                SymbolType::Bytes32,
+                SymbolType::Bytes32
+            ],
+            returns: vec![SymbolType::Address],
+        }
+    );
+    assert_eq!(BuiltinFunction::Ecrecover.precompile_address(), Some(1));
+
+    let source = "contract Test {\
+           function signer(uint hash, uint v, bytes32 r, bytes32 s) returns (address) {\
+             return ecrecover(hash, v, r, s);\
+           }\
+        }";
+    let program = parser::parse_program(source).unwrap();
+    let err = make_symbol_table(&program).unwrap_err();
+    assert_eq!(
+        err.error,
+        SymbolTableErrorType::TypeError(String::from(
+            "`ecrecover` expects `bytes32` for argument 1 but `uint` was given"
+        ))
+    );
+}
+
+#[test]
+fn test_address_members() {
+    let source = "contract Test {\
+           function pay(address to, uint amount) returns (uint) {\
+             to.transfer(amount);\
+             to.send(amount);\
+             to.call(abi.encode(amount));\
+             return to.balance;\
+           }\
+        }";
+    let program = parser::parse_program(source).unwrap();
+    assert!(make_symbol_table(&program).is_ok());
+
+    let members = type_members(&SymbolType::Address).unwrap();
+    let transfer = members.iter().find(|m| m.name == "transfer").unwrap();
+    assert_eq!(transfer.state_access, StateAccess::Write);
+
+    let source = "contract Test {\
+           function pay(address to) {\
+             to.withdraw();\
+           }\
+        }";
+    let program = parser::parse_program(source).unwrap();
+    let err = make_symbol_table(&program).unwrap_err();
+    assert_eq!(
+        err.error,
+        SymbolTableErrorType::TypeError(String::from("`address` has no member `withdraw`"))
+    );
+
+    let source = "contract Test {\
+           function pay(address to) {\
+             to.transfer();\
+           }\
+        }";
+    let program = parser::parse_program(source).unwrap();
+    let err = make_symbol_table(&program).unwrap_err();
+    assert!(matches!(err.error, SymbolTableErrorType::TypeError(_)));
+}
+
+#[test]
+fn test_dynamic_array_members() {
+    let source = "contract Test {\
+           uint[] values;\
+           function add(uint value) returns (uint) {\
+             values.push(value);\
+             values.pop();\
+             return values.length;\
+           }\
+        }";
+    let program = parser::parse_program(source).unwrap();
+    let table = make_symbol_table(&program).unwrap();
+    let values = table.sub_tables[0].lookup("values").unwrap();
+    assert_eq!(
+        values.symbol_type,
+        SymbolType::Array(Box::new(SymbolType::Uint256))
+    );
+    assert_eq!(values.data_location, SymbolLocation::Storage);
+
+    let source = "contract Test { uint256[][] grid; }";
+    let program = parser::parse_program(source).unwrap();
+    let table = make_symbol_table(&program).unwrap();
+    assert_eq!(
+        table.sub_tables[0].lookup("grid").unwrap().symbol_type,
+        SymbolType::Array(Box::new(SymbolType::Array(Box::new(SymbolType::Uint256))))
+    );
+
+    let source = "contract Test {\
+           function add(uint[] memory values, uint value) {\
+             values.push(value);\
+           }\
+        }";
+    let program = parser::parse_program(source).unwrap();
+    let err = make_symbol_table(&program).unwrap_err();
+    assert_eq!(
+        err.error,
+        SymbolTableErrorType::TypeError(String::from("`push` is only available on storage arrays"))
+    );
+
+    let source = "contract Test {\
+           uint[] values;\
+           function add(bool value) {\
+             values.push(value);\
+           }\
+        }";
+    let program = parser::parse_program(source).unwrap();
+    let err = make_symbol_table(&program).unwrap_err();
+    assert_eq!(
+        err.error,
+        SymbolTableErrorType::TypeError(String::from(
+            "`uint[].push` expects `uint` for argument 1 but `bool` was given"
+        ))
+    );
+}
+
+#[test]
+fn test_symbol_table_with_syntax_errors() {
+    let source = "contract Test {\
+           uint a = ;\
+           uint + ;\
+           function f() returns (uint) {\
+             a = = 2;\
+             a\
+           }\
+        }";
+    let (program, errors) = parser::parse_program_tolerant(source);
+    assert_eq!(errors.len(), 3);
+    let table = make_symbol_table(&program.unwrap()).unwrap();
+    let contract = &table.sub_tables[0];
+    assert!(contract.lookup("a").is_some());
+    assert!(contract.lookup("f").is_some());
+}
+
+#[test]
+fn test_mutability_inference() {
+    let source = "contract Test {\
+           uint total = 1;\
+           uint[] values;\
+           function square(uint x) returns (uint) { return x * x; }\
+           function get() returns (uint) { return total; }\
+           function bump(uint x) returns (uint) { total += x; return total; }\
+           function append(uint x) { values.push(x); }\
+           function twice(uint a) returns (uint) { return square(a) + get(); }\
+           function later(uint a) returns (uint) { return early(a); }\
+           function early(uint a) returns (uint) { return bump(a); }\
+           function hash(bytes data) returns (bytes32) { return keccak256(data); }\
+        }";
+    let program = parser::parse_program(source).unwrap();
+    let table = make_symbol_table(&program).unwrap();
+    let mutability = |function| table.function_mutability("Test", function).unwrap();
+    assert_eq!(mutability("square"), StateMutability::Pure);
+    assert_eq!(mutability("get"), StateMutability::View);
+    assert_eq!(mutability("bump"), StateMutability::NonPayable);
+    assert_eq!(mutability("append"), StateMutability::NonPayable);
+    assert_eq!(mutability("twice"), StateMutability::View);
+    // Callees declared later are taken into account
+    assert_eq!(mutability("later"), StateMutability::NonPayable);
+    assert_eq!(mutability("hash"), StateMutability::Pure);
+
+    let suggestions: Vec<String> = suggest_mutability(&table)
+        .iter()
+        .map(|suggestion| suggestion.to_string())
+        .collect();
+    assert_eq!(
+        suggestions,
+        vec![
+            "function `Test.square` can be declared `pure`",
+            "function `Test.get` can be declared `view`",
+            "function `Test.twice` can be declared `view`",
+            "function `Test.hash` can be declared `pure`",
+        ]
+    );
+}
+
+#[test]
+fn test_declared_mutability() {
+    let source = "contract Test {\
+           uint total;\
+           function square(uint x) pure returns (uint) { return x * x; }\
+           function get() view returns (uint) { return total; }\
+           function peek() external view returns (uint) { return square(2); }\
+           function deposit() payable { }\
+           function bump() { total = total + 1; }\
+         }";
+    let program = parser::parse_program(source).unwrap();
+    let table = make_symbol_table(&program).unwrap();
+    let declared = |function: &str| {
+        table
+            .function_table("Test", function)
+            .unwrap()
+            .declared_mutability
+    };
+    assert_eq!(declared("square"), Some(StateMutability::Pure));
+    assert_eq!(declared("get"), Some(StateMutability::View));
+    assert_eq!(declared("deposit"), Some(StateMutability::Payable));
+    assert_eq!(declared("bump"), None);
+    let suggestions: Vec<String> = suggest_mutability(&table)
+        .iter()
+        .map(|suggestion| suggestion.to_string())
+        .collect();
+    assert_eq!(
+        suggestions,
+        vec!["function `Test.peek` can be declared `pure`"]
+    );
+
+    let error = |source: &str| {
+        let program = parser::parse_program(source).unwrap();
+        let error = make_symbol_table(&program).unwrap_err();
+        match error.error {
+            SymbolTableErrorType::TypeError(message) => (message, error.location),
+            error => panic!("unexpected error {:?}", error),
+        }
+    };
+    assert_eq!(
+        error("contract Test { uint total; function get() pure returns (uint) { return total; } }"),
+        (
+            String::from("`get` is declared `pure` but reads state"),
+            Location::new(0, 38)
+        )
+    );
+    assert_eq!(
+        error("contract Test { uint total; function set() view { total = 1; } }").0,
+        "`set` is declared `view` but writes state"
+    );
+    // Through the functions it calls
+    assert_eq!(
+        error(
+            "contract Test {\
+               uint total;\
+               function get() returns (uint) { return total; }\
+               function twice() pure returns (uint) { return get() * 2; }\
+             }"
+        )
+        .0,
+        "`twice` is declared `pure` but reads state"
+    );
+}
+
+#[test]
+fn test_event_topics() {
+    let source = "contract Token {\
+           event Transfer(address from, address to, uint amount);\
+           event Approval(address owner, address spender, uint256 amount);\
+           function f() { }\
+        }";
+    let program = parser::parse_program(source).unwrap();
+    let table = make_symbol_table(&program).unwrap();
+    let events = &table.contract_table("Token").unwrap().events;
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[0].signature, "Transfer(address,address,uint256)");
+    assert_eq!(
+        events[0].topic[..4],
+        [0xdd, 0xf2, 0x52, 0xad],
+        "topic of the ERC-20 Transfer event"
+    );
+    assert_eq!(events[1].signature, "Approval(address,address,uint256)");
+    assert_eq!(events[1].topic[..4], [0x8c, 0x5b, 0xe1, 0xe5]);
+
+    // Overloads are allowed, redeclarations with the same types are not
+    let source = "contract Token {\
+           event Transfer(address to, uint amount);\
+           event Transfer(address to);\
+           event Transfer(address recipient, uint256 value);\
+        }";
+    let program = parser::parse_program(source).unwrap();
+    let err = make_symbol_table(&program).unwrap_err();
+    assert_eq!(
+        err.error,
+        SymbolTableErrorType::DuplicateEvent("Transfer(address,uint256)".to_string())
+    );
+}
+
+#[test]
+fn test_match_exhaustiveness() {
+    let source = "contract Test {\
+           enum Color { Red, Green, Blue }\
+           function f(Color c, uint x) {\
+             match c {\
+               Color.Red => { x = 1; },\
+               Color.Green => { x = 2; },\
+               Color.Blue => { x = 3; },\
+             };\
+             match c { Color.Red => { x = 1; }, _ => { x = 0; } };\
+           }\
+        }";
+    let program = parser::parse_program(source).unwrap();
+    let table = make_symbol_table(&program).unwrap();
+    assert_eq!(
+        table
+            .contract_table("Test")
+            .unwrap()
+            .lookup("Color")
+            .unwrap()
+            .symbol_type,
+        SymbolType::Enum {
+            name: "Color".to_string(),
+            variants: vec!["Red".to_string(), "Green".to_string(), "Blue".to_string()],
+        }
+    );
+
+    let source = "contract Test {\
+           enum Color { Red, Green, Blue }\
+           function f(Color c, uint x) {\
+             match c { Color.Green => { x = 2; } };\
+           }\
+        }";
+    let program = parser::parse_program(source).unwrap();
+    let err = make_symbol_table(&program).unwrap_err();
+    assert_eq!(
+        err.error,
+        SymbolTableErrorType::NonExhaustiveMatch {
+            enum_name: "Color".to_string(),
+            missing: vec!["Red".to_string(), "Blue".to_string()],
+        }
+    );
+
+    let source = "contract Test {\
+           enum Color { Red, Green }\
+           function f(Color c, uint x) {\
+             match c { Color.Yellow => { x = 2; }, _ => { } };\
+           }\
+        }";
+    let program = parser::parse_program(source).unwrap();
+    let err = make_symbol_table(&program).unwrap_err();
+    assert_eq!(
+        err.error,
+        SymbolTableErrorType::TypeError("`Color` has no variant `Yellow`".to_string())
+    );
+
+    let source = "contract Test {\
+           enum Color { Red, Green }\
+           enum Shape { Circle }\
+           function f(Color c, uint x) {\
+             match c { Shape.Circle => { x = 2; }, _ => { } };\
+           }\
+        }";
+    let program = parser::parse_program(source).unwrap();
+    let err = make_symbol_table(&program).unwrap_err();
+    assert_eq!(
+        err.error,
+        SymbolTableErrorType::TypeError(
+            "pattern of type `Shape` doesn't match a value of type `Color`".to_string()
+        )
+    );
+}
+
+#[test]
+fn test_constant_array_sizes() {
+    let source = "contract Test {\
+           uint constant N = 4;\
+           uint constant M = 2 * N;\
+           uint[N] a;\
+           bytes32[M + 1] b;\
+           uint[2 ** 3][N] c;\
+           function f(uint[N] x) returns (uint) { return x.length; }\
+        }";
+    let program = parser::parse_program(source).unwrap();
+    let table = make_symbol_table(&program).unwrap();
+    let contract = table.contract_table("Test").unwrap();
+    assert_eq!(contract.lookup("M").unwrap().constant, Some(8u32.into()));
+    assert_eq!(
+        contract.lookup("a").unwrap().symbol_type,
+        SymbolType::FixedArray(Box::new(SymbolType::Uint256), 4)
+    );
+    assert_eq!(
+        contract.lookup("b").unwrap().symbol_type.to_string(),
+        "bytes32[9]"
+    );
+    assert_eq!(
+        contract.lookup("c").unwrap().symbol_type.to_string(),
+        "uint[8][4]"
+    );
+
+    let check = |source: &str| {
+        let program = parser::parse_program(source).unwrap();
+        make_symbol_table(&program).unwrap_err().error
+    };
+    let type_error = |message: &str| SymbolTableErrorType::TypeError(message.to_string());
+    assert_eq!(
+        check("contract Test { uint n = 4; uint[n] a; }"),
+        type_error("array length is not a compile-time constant")
+    );
+    assert_eq!(
+        check("contract Test { function f(uint n) { uint[n + 1] a; } }"),
+        type_error("array length is not a compile-time constant")
+    );
+    assert_eq!(
+        check("contract Test { uint constant N = 2; uint[N - 2][3] a; }"),
+        type_error("array length must be greater than zero")
+    );
+    assert_eq!(
+        check("contract Test { uint n = 4; uint constant N = n; }"),
+        type_error("value of constant `N` is not a compile-time constant")
+    );
+    assert_eq!(
+        check("contract Test { uint constant N = 4; function f() { N = 5; } }"),
+        type_error("cannot assign to constant `N`")
+    );
+}
+
+#[test]
+fn test_contract_member_access() {
+    let token = "contract Token {\
+           private uint secret;\
+           uint total;\
+           function balanceOf(address owner) returns (uint) { return 1; }\
+           function mint(uint amount) { total = amount; }\
+        }";
+    let source = format!(
+        "{} contract Wallet {{\
+           Token token;\
+           function balance(address owner) returns (uint) {{ return token.balanceOf(owner); }}\
+           function mint() {{ token.mint(1); }}\
+        }}",
+        token
+    );
+    let program = parser::parse_program(&source).unwrap();
+    let table = make_symbol_table(&program).unwrap();
+    assert_eq!(
+        table.function_mutability("Wallet", "balance"),
+        Some(StateMutability::View)
+    );
+    assert_eq!(
+        table.function_mutability("Wallet", "mint"),
+        Some(StateMutability::NonPayable)
+    );
+
+    let check = |body: &str| {
+        let source = format!(
+            "{} contract Wallet {{ Token token; function f(bool c) {{ {} }} }}",
+            token, body
+        );
+        let program = parser::parse_program(&source).unwrap();
+        match make_symbol_table(&program).unwrap_err().error {
+            SymbolTableErrorType::TypeError(message) => message,
+            error => panic!("unexpected error {:?}", error),
+        }
+    };
+    assert_eq!(check("token.burn(1);"), "`Token` has no member `burn`");
+    assert_eq!(check("token.secret;"), "`secret` is private in `Token`");
+    assert_eq!(
+        check("token.mint(1, 2);"),
+        "`Token.mint` takes 1 argument(s) but 2 were given"
+    );
+    assert_eq!(
+        check("token.mint(c);"),
+        "`Token.mint` expects `uint` for argument 1 but `bool` was given"
+    );
+    assert_eq!(
+        check("bool b = token.balanceOf(msg.sender);"),
+        "cannot implicitly convert `uint` to `bool`"
+    );
+}
+
+#[test]
+fn test_forward_references() {
+    let source = "contract Test {\
+           function get() returns (uint) { return total; }\
+           function paint(Color c, uint x) { match c { Color.Red => { x = 1; }, _ => { } }; }\
+           uint[N] values;\
+           uint constant N = M + 1;\
+           uint constant M = 2;\
+           uint total;\
+           enum Color { Red, Green }\
+        }";
+    let program = parser::parse_program(source).unwrap();
+    let table = make_symbol_table(&program).unwrap();
+    assert_eq!(
+        table.function_mutability("Test", "get"),
+        Some(StateMutability::View)
+    );
+    assert_eq!(
+        table
+            .contract_table("Test")
+            .unwrap()
+            .lookup("values")
+            .unwrap()
+            .symbol_type,
+        SymbolType::FixedArray(Box::new(SymbolType::Uint256), 3)
+    );
+
+    let source = "contract Test { function set(bool c) { total = c; } uint total; }";
+    let program = parser::parse_program(source).unwrap();
+    assert_eq!(
+        make_symbol_table(&program).unwrap_err().error,
+        SymbolTableErrorType::TypeError("cannot implicitly convert `bool` to `uint`".to_string())
+    );
+}
+
+#[test]
+fn test_free_functions() {
+    let source = "function funds(address a) returns (uint) { return a.balance; }\
+        contract Test {\
+          function owner(address a) returns (uint) { return funds(a); }\
+          function helper(address a) returns (uint) { return later(a); }\
+        }\
+        function later(address a) returns (uint) { return funds(a); }";
+    let program = parser::parse_program(source).unwrap();
+    let table = make_symbol_table(&program).unwrap();
+    assert_eq!(
+        table.lookup("funds").unwrap().symbol_type,
+        SymbolType::Function {
+            params: vec![SymbolType::Address],
+            returns: vec![SymbolType::Uint256],
+        }
+    );
+    assert_eq!(
+        table.function_mutability("Test", "owner"),
+        Some(StateMutability::View)
+    );
+    assert_eq!(
+        table.function_mutability("Test", "helper"),
+        Some(StateMutability::View)
+    );
+
+    // Free functions can't see the members of contracts
+    let source = "function get() returns (uint) { return total; }\
+        contract Test { uint total; }";
+    let program = parser::parse_program(source).unwrap();
+    assert_eq!(
+        make_symbol_table(&program).unwrap_err().error,
+        SymbolTableErrorType::NotDeclared("total".to_string())
+    );
+}
+
+#[test]
+fn test_file_constants() {
+    let source = "contract A { uint[SIZE] values; uint fee = FEE * 2; }\
+        uint256 constant FEE = 100;\
+        uint constant SIZE = FEE / 25;\
+        contract B { uint constant SMALL = SIZE; uint[SMALL] values; }";
+    let program = parser::parse_program(source).unwrap();
+    let table = make_symbol_table(&program).unwrap();
+    assert_eq!(
+        table.lookup("FEE").unwrap().constant,
+        Some(BigUint::from(100u32))
+    );
+    for contract in &["A", "B"] {
+        assert_eq!(
+            table
+                .contract_table(contract)
+                .unwrap()
+                .lookup("values")
+                .unwrap()
+                .symbol_type,
+            SymbolType::FixedArray(Box::new(SymbolType::Uint256), 4)
+        );
+    }
+
+    let error = |source: &str| {
+        let program = parser::parse_program(source).unwrap();
+        make_symbol_table(&program).unwrap_err().error
+    };
+    assert_eq!(
+        error("uint total; contract Test { }"),
+        SymbolTableErrorType::TypeError(
+            "`total` must be a constant to be declared outside contracts".to_string()
+        )
+    );
+    assert_eq!(
+        error("uint constant FEE = 1; contract Test { function f() { FEE = 2; } }"),
+        SymbolTableErrorType::TypeError("cannot assign to constant `FEE`".to_string())
+    );
+}
+
+#[test]
+fn test_modules() {
+    let source = "contract Test {\
+          uint total;\
+          function f(uint x) returns (uint) { return math::clamp(x) + math::bits::low(x); }\
+          function g(uint x) returns (uint) { return math::add(x); }\
+          function add(uint x) returns (uint) { total = x; return x; }\
+        }\
+        module math {\
+          uint constant MAX = 10;\
+          function clamp(uint x) returns (uint) { return bits::low(x) + MAX; }\
+          function add(address a) returns (uint) { return a.balance; }\
+          module bits {\
+            function low(uint x) returns (uint) { return x; }\
+          }\
+        }";
+    let program = parser::parse_program(source).unwrap();
+    let table = make_symbol_table(&program).unwrap();
+    assert_eq!(
+        table.lookup("math").unwrap().symbol_type,
+        SymbolType::Module("math".to_string())
+    );
+    let math = table.module_table("math").unwrap();
+    assert_eq!(math.table_type, SymbolTableType::Module);
+    assert_eq!(
+        table
+            .lookup_path(&["math".to_string(), "MAX".to_string()])
+            .unwrap()
+            .constant,
+        Some(BigUint::from(10u32))
+    );
+    assert!(table
+        .lookup_path(&["math".to_string(), "bits".to_string(), "low".to_string()])
+        .is_some());
+    assert_eq!(
+        table.function_mutability("Test", "f"),
+        Some(StateMutability::Pure)
+    );
+    // `math::add` is not the `add` of the contract
+    assert_eq!(
+        table.function_mutability("Test", "g"),
+        Some(StateMutability::View)
+    );
+
+    let error = |source: &str| {
+        let program = parser::parse_program(source).unwrap();
+        make_symbol_table(&program).unwrap_err().error
+    };
+    assert_eq!(
+        error("module math { } contract Test { function f() { math::clamp(1); } }"),
+        SymbolTableErrorType::NotDeclared("math::clamp".to_string())
+    );
+    // Members of contracts aren't visible in modules
+    assert_eq!(
+        error("contract Test { uint total; } module math { function f() returns (uint) { return total; } }"),
+        SymbolTableErrorType::NotDeclared("total".to_string())
+    );
+    assert_eq!(
+        error("module math { uint total; }"),
+        SymbolTableErrorType::TypeError(
+            "`total` must be a constant to be declared outside contracts".to_string()
+        )
+    );
+}
+
+#[test]
+fn test_deeply_nested_expression() {
+    let depth = 10_000;
+    let source = |last: &str| {
+        let operands = vec!["a"; depth].join(" + ");
+        format!(
+            "contract Test {{ function f(uint a) returns (uint) {{ return {} + {}; }} }}",
+            operands, last
+        )
+    };
+    let program = parser::parse_program(&source("1")).unwrap();
+    assert!(make_symbol_table(&program).is_ok());
+
+    // Errors deep in the expression are still reported
+    let program = parser::parse_program(&source("b")).unwrap();
+    assert_eq!(
+        make_symbol_table(&program).unwrap_err().error,
+        SymbolTableErrorType::NotDeclared("b".to_string())
+    );
+}
+
+#[test]
+fn test_annotations() {
+    let source = "@deprecated contract Old { }\
+        contract Test {\
+          @inline function f(uint x) returns (uint) { return old(x) + math::old(x); }\
+          @deprecated(\"use f\") function old(uint x) returns (uint) { return x; }\
+          function g(uint x) returns (uint) { return old(x); }\
+        }\
+        module math {\
+          @deprecated function old(uint x) returns (uint) { return x; }\
+        }";
+    let program = parser::parse_program(source).unwrap();
+    let table = make_symbol_table(&program).unwrap();
+    assert_eq!(
+        table.lookup("Old").unwrap().annotations,
+        vec![Annotation::Deprecated(None)]
+    );
+    let contract = table.contract_table("Test").unwrap();
+    assert_eq!(
+        contract.lookup("f").unwrap().annotations,
+        vec![Annotation::Inline]
+    );
+
+    let warnings: Vec<String> = deprecated_uses(&table)
+        .iter()
+        .map(|warning| warning.to_string())
+        .collect();
+    assert_eq!(
+        warnings,
+        vec![
+            "`old` is deprecated: use f",
+            "`math::old` is deprecated",
+            "`old` is deprecated: use f",
+        ]
+    );
+
+    let error = |source: &str| {
+        let program = parser::parse_program(source).unwrap();
+        make_symbol_table(&program).unwrap_err().error
+    };
+    let type_error = |message: &str| SymbolTableErrorType::TypeError(message.to_string());
+    assert_eq!(
+        error("@cached function f() { }"),
+        type_error("unknown annotation `@cached`")
+    );
+    assert_eq!(
+        error("@inline contract Test { }"),
+        type_error("`@inline` only applies to functions")
+    );
+    assert_eq!(
+        error("@test(1) function f() { }"),
+        type_error("`@test` takes no arguments")
+    );
+    assert_eq!(
+        error("@deprecated(1) function f() { }"),
+        type_error("`@deprecated` takes an optional message string")
+    );
+    assert_eq!(
+        error("@inline @inline function f() { }"),
+        type_error("duplicate annotation `@inline`")
+    );
+}
+
+#[test]
+fn test_mapping() {
+    let source = "contract Token {\
+          mapping(address => uint256) balances;\
+          mapping(address => mapping(address => uint)) allowed;\
+          function balance(address owner) returns (uint) { return balances[owner]; }\
+          function approve(address owner, address spender, uint amount) {\
+            allowed[owner][spender] = amount;\
+          }\
+        }";
+    let program = parser::parse_program(source).unwrap();
+    let table = make_symbol_table(&program).unwrap();
+    let contract = table.contract_table("Token").unwrap();
+    let balances = contract.lookup("balances").unwrap();
+    assert_eq!(
+        balances.symbol_type,
+        SymbolType::Mapping(Box::new(SymbolType::Address), Box::new(SymbolType::Uint256))
+    );
+    assert_eq!(balances.data_location, SymbolLocation::Storage);
+    assert_eq!(
+        contract.lookup("allowed").unwrap().symbol_type.to_string(),
+        "mapping(address => mapping(address => uint))"
+    );
+
+    let error = |source: &str| {
+        let program = parser::parse_program(source).unwrap();
+        make_symbol_table(&program).unwrap_err().error
+    };
+    let message = |name: &str| {
+        SymbolTableErrorType::TypeError(format!(
+            "mapping `{}` can only be declared as a state variable without a value",
+            name
+        ))
+    };
+    assert_eq!(
+        error("contract Test { function f(mapping(uint => uint) m) { } }"),
+        message("m")
+    );
+    assert_eq!(
+        error("contract Test { function f() { mapping(uint => uint) m; } }"),
+        message("m")
+    );
+}
+
+#[test]
+fn test_state_variable_defaults() {
+    let source = "contract Test {\
+           uint constant N = 4;\
+           uint a = N * 2;\
+           uint b = msg.value + N;\
+           uint8 c = 255;\
+           function f() { uint d = a; }\
+        }";
+    let program = parser::parse_program(source).unwrap();
+    assert!(make_symbol_table(&program).is_ok());
+
+    let check = |source: &str| {
+        let program = parser::parse_program(source).unwrap();
+        make_symbol_table(&program).unwrap_err()
+    };
+    let err = check("contract Test { uint a = 1; uint b = 2 * (a + N); uint constant N = 4; }");
+    assert_eq!(
+        err.error,
+        SymbolTableErrorType::TypeError(
+            "value of state variable `b` reads state variable `a`".to_string()
+        )
+    );
+    assert_eq!(err.location, Location::new(0, 43));
+    assert_eq!(
+        check("contract Test { uint8 a = 256; }").error,
+        SymbolTableErrorType::TypeError(
+            "cannot implicitly convert `256` to `uint8`: the value is out of range".to_string()
+        )
+    );
+}
+
+#[test]
+fn test_constructor() {
+    let source = "contract Test {\
+           uint total;\
+           constructor(uint initial) { total = initial; }\
+        }";
+    let program = parser::parse_program(source).unwrap();
+    let table = make_symbol_table(&program).unwrap();
+    let constructor = table.contract_table("Test").unwrap().constructor().unwrap();
+    assert_eq!(
+        constructor.symbol_type,
+        SymbolType::Function {
+            params: vec![SymbolType::Uint256],
+            returns: vec![],
+        }
+    );
+
+    let source = "contract Test { constructor() {} constructor(uint a) {} }";
+    let program = parser::parse_program(source).unwrap();
+    let err = make_symbol_table(&program).unwrap_err();
+    assert_eq!(
+        err.error,
+        SymbolTableErrorType::TypeError("`Test` has more than one constructor".to_string())
+    );
+    assert_eq!(err.location, Location::new(0, 34));
+}
+
+#[test]
+fn test_symbol_usages() {
+    let source = "contract Test {\
+           uint total;\
+           uint count;\
+           uint[] values;\
+           function update(uint x) { total = x; count += x; values[x] = 1; x++; }\
+        }";
+    let program = parser::parse_program(source).unwrap();
+    let table = make_symbol_table(&program).unwrap();
+    let function = table
+        .contract_table("Test")
+        .unwrap()
+        .sub_tables
+        .iter()
+        .find(|table| table.name == "update")
+        .unwrap();
+    let usage = |name: &str| function.sub_tables[0].symbols[name].usage.clone();
+    assert_eq!(usage("total"), SymbolUsage::Written);
+    assert_eq!(usage("count"), SymbolUsage::ReadWritten);
+    assert_eq!(usage("values"), SymbolUsage::Written);
+    // Read as the index, then incremented
+    assert_eq!(usage("x"), SymbolUsage::ReadWritten);
+    assert!(usage("x").is_read() && usage("x").is_written());
+}
+
+#[test]
+fn test_parameter_symbols() {
+    let source = "contract Test {\
+           uint total;\
+           uint[] values;\
+           function add(uint x, address to) returns (uint) {\
+             uint y = x;\
+             for i in values { y += i; };\
+             return y;\
+           }\
+        }";
+    let program = parser::parse_program(source).unwrap();
+    let table = make_symbol_table(&program).unwrap();
+    let function = table.function_table("Test", "add").unwrap();
+    let params: Vec<&str> = function
+        .parameters()
+        .iter()
+        .map(|param| param.name.as_str())
+        .collect();
+    assert_eq!(params, vec!["x", "to"]);
+    let body = &function.sub_tables[0];
+    assert_eq!(body.symbols["y"].kind, SymbolKind::Variable);
+    assert_eq!(body.symbols["x"].kind, SymbolKind::Item);
+    assert_eq!(body.sub_tables[0].symbols["i"].kind, SymbolKind::Variable);
+    let contract = table.contract_table("Test").unwrap();
+    assert_eq!(contract.symbols["total"].kind, SymbolKind::Variable);
+    assert_eq!(contract.symbols["add"].kind, SymbolKind::Item);
+}
+
+#[test]
+fn test_duplicate_declarations() {
+    let token = parser::parse_program(
+        "contract Token { function supply() returns (uint) { return TOTAL; } }\
+         uint constant TOTAL = 10;",
+    )
+    .unwrap();
+    let vault = parser::parse_program(
+        "contract Vault { uint x; }\
+         function double(uint a) returns (uint) { return a * 2; }",
+    )
+    .unwrap();
+    assert!(make_sources_symbol_table(&[("token.zok", &token), ("vault.zok", &vault)]).is_ok());
+
+    let other = parser::parse_program("function helper() {}\ncontract Token { uint y; }").unwrap();
+    let err =
+        make_sources_symbol_table(&[("token.zok", &token), ("other.zok", &other)]).unwrap_err();
+    assert_eq!(
+        err.error,
+        SymbolTableErrorType::DuplicateDeclaration {
+            name: "Token".to_string(),
+            first_source: "token.zok".to_string(),
+            first: Location::new(0, 10),
+            source: "other.zok".to_string(),
+        }
+    );
+    assert_eq!(err.location, Location::new(1, 11));
+
+    let program = parser::parse_program("function f() {} function f() {}").unwrap();
+    let err = make_symbol_table(&program).unwrap_err();
+    assert!(matches!(
+        err.error,
+        SymbolTableErrorType::DuplicateDeclaration { ref name, .. } if name == "f"
+    ));
+}
+
+#[test]
+fn test_condition_types() {
+    let source = "contract Test {\
+           uint[] values;\
+           function pick(uint x) returns (uint) {\
+             for v in values { x += v; };\
+             if x > 2 { return 1; };\
+             return x == 1 ? 100 : 200;\
+           }\
+        }";
+    let program = parser::parse_program(source).unwrap();
+    assert!(make_symbol_table(&program).is_ok());
+
+    let error = |source: &str| {
+        let program = parser::parse_program(source).unwrap();
+        match make_symbol_table(&program).unwrap_err().error {
+            SymbolTableErrorType::TypeError(message) => message,
+            error => panic!("unexpected error {:?}", error),
+        }
+    };
+    assert_eq!(
+        error("function f(uint x) { if x { x = 1; }; }"),
+        "condition of `if` must be `bool`, found `uint`"
+    );
+    assert_eq!(
+        error("function f(address a) returns (uint) { return a ? 1 : 2; }"),
+        "condition of `ternary` must be `bool`, found `address`"
+    );
+    assert_eq!(
+        error("function f(uint x) { do { x += 1; } while (x); }"),
+        "condition of `while` must be `bool`, found `uint`"
+    );
+    assert_eq!(
+        error("function f(uint x) { for v in x { x += v; }; }"),
+        "`for` loop can't iterate over `uint`, which is not an array"
+    );
+}
+
+#[test]
+fn test_return_arity() {
+    let source = "contract Test {\
+           function divmod(uint a, uint b) returns (uint256, uint256) {\
+             return (a / b, a % b);\
+           }\
+           function swap(uint a, uint b) returns (uint, uint) {\
+             return divmod(b, a);\
+           }\
+           function check(uint a) returns (uint256, bool) {\
+             if a > 2 { return (a, a == 3); };\
+             return (0, a == 1);\
+           }\
+           function stop(uint a) { if a > 2 { return; }; }\
+        }";
+    let program = parser::parse_program(source).unwrap();
+    assert!(make_symbol_table(&program).is_ok());
+
+    let error = |source: &str| {
+        let program = parser::parse_program(source).unwrap();
+        let error = make_symbol_table(&program).unwrap_err();
+        match error.error {
+            SymbolTableErrorType::TypeError(message) => (message, error.location),
+            error => panic!("unexpected error {:?}", error),
+        }
+    };
+    assert_eq!(
+        error("function f(uint a) returns (uint, bool) { return a; }"),
+        (
+            String::from("`f` returns 2 values but `return` gives 1 value"),
+            Location::new(0, 50)
+        )
+    );
+    assert_eq!(
+        error("function f(uint a) returns (uint) { return (a, a); }").0,
+        "`f` returns 1 value but `return` gives 2 values"
+    );
+    assert_eq!(
+        error("function f(uint a) returns (uint) { return; }").0,
+        "`f` returns 1 value but `return` gives 0 values"
+    );
+    assert_eq!(
+        error("function f(uint a) { return a; }").0,
+        "`f` returns 0 values but `return` gives 1 value"
+    );
+    assert_eq!(
+        error(
+            "function g() returns (uint, uint) { return (1, 2); } \
+             function f() returns (uint) { return g(); }"
+        )
+        .0,
+        "`f` returns 1 value but `return` gives 2 values"
+    );
+    assert_eq!(
+        error("function f(uint16 a) returns (uint8, bool) { return (a, a == 1); }").0,
+        "cannot implicitly convert `uint16` to `uint8`: narrowing conversion may lose bits"
+    );
+}
+
+#[test]
+fn test_destructuring() {
+    let source = "contract Math {\
+           function divmod(uint a, uint b) returns (uint, uint) { return (a / b, a % b); }\
+         }\
+         contract Test {\
+           uint total;\
+           function divmod(uint a, uint b) returns (uint, uint8) {\
+             return (a / b, 1);\
+           }\
+           function f(uint a, Math math) returns (uint) {\
+             uint q;\
+             uint r;\
+             uint[2] parts;\
+             (q, r) = divmod(a, 3);\
+             (_, parts[1]) = divmod(a, 5);\
+             (total, ) = math.divmod(a, 7);\
+             (q, r) = (r, q);\
+             return q + r;\
+           }\
+        }";
+    let program = parser::parse_program(source).unwrap();
+    make_symbol_table(&program).unwrap();
+
+    let error = |body: &str| {
+        let source = format!(
+            "function divmod(uint a, uint b) returns (uint, uint) {{ return (a / b, a % b); }} \
+             function f(uint a) {{ uint8 q; uint r; {} }}",
+            body
+        );
+        let program = parser::parse_program(&source).unwrap();
+        let error = make_symbol_table(&program).unwrap_err();
+        match error.error {
+            SymbolTableErrorType::TypeError(message) => (message, error.location),
+            error => panic!("unexpected error {:?}", error),
+        }
+    };
+    assert_eq!(
+        error("(r, r, _) = divmod(a, 2);"),
+        (
+            String::from("`divmod` returns 2 values but destructuring takes 3 values"),
+            Location::new(0, 131)
+        )
+    );
+    assert_eq!(
+        error("(r, _, r) = (1, 2);").0,
+        "tuple has 2 values but destructuring takes 3 values"
+    );
+    assert_eq!(
+        error("(q, r) = divmod(a, 2);"),
+        (
+            String::from(
+                "cannot implicitly convert `uint` to `uint8`: narrowing conversion may lose bits"
+            ),
+            Location::new(0, 120)
+        )
+    );
+    assert_eq!(
+        error("(r, q) = (a, 300);").0,
+        "cannot implicitly convert `300` to `uint8`: the value is out of range"
+    );
+    assert_eq!(
+        error("(r, q) = a;").0,
+        "only a tuple or a call to a declared function can be destructured"
+    );
+    assert_eq!(
+        error("(r, a + 1) = divmod(a, 2);").0,
+        "destructuring can only assign to variables and their elements"
+    );
+}
+
+#[test]
+fn test_visibility() {
+    let source = "contract Token {\
+           uint public supply;\
+           uint internal fee;\
+           uint private secret;\
+           private uint owner;\
+           function transfer(uint a) external { }\
+           function burn(uint a) public { fee = a; secret = a; }\
+           function mint(uint a) internal { burn(a); }\
+         }\
+         contract Bank {\
+           function deposit(Token token) { token.transfer(token.supply); }\
+         }";
+    let program = parser::parse_program(source).unwrap();
+    let table = make_symbol_table(&program).unwrap();
+    let token = table
+        .sub_tables
+        .iter()
+        .find(|table| table.name == "Token")
+        .unwrap();
+    let visibilities: Vec<(&str, Visibility)> = ["supply", "fee", "secret", "owner", "transfer"]
+        .iter()
+        .map(|name| (*name, token.symbols[*name].visibility))
+        .collect();
+    assert_eq!(
+        visibilities,
+        vec![
+            ("supply", Visibility::Public),
+            ("fee", Visibility::Internal),
+            ("secret", Visibility::Private),
+            ("owner", Visibility::Private),
+            ("transfer", Visibility::External),
+        ]
+    );
+
+    let error = |source: &str| {
+        let program = parser::parse_program(source).unwrap();
+        match make_symbol_table(&program).unwrap_err().error {
+            SymbolTableErrorType::TypeError(message) => message,
+            error => panic!("unexpected error {:?}", error),
+        }
+    };
+    let bank = |body: &str| {
+        format!(
+            "contract Token {{\
+               uint internal fee;\
+               function burn() private {{ }}\
+               function transfer() external {{ }}\
+             }}\
+             contract Bank {{ function f(Token token) {{ {} }} }}",
+            body
+        )
+    };
+    assert_eq!(error(&bank("token.fee;")), "`fee` is internal in `Token`");
+    assert_eq!(
+        error(&bank("token.burn();")),
+        "`burn` is private in `Token`"
+    );
+    assert_eq!(
+        error("contract Test { function f() external { } function g() { f(); } }"),
+        "`f` is external and can only be called through a value of the contract"
+    );
+    assert_eq!(
+        error("contract Test { uint external total; }"),
+        "state variable `total` can't be `external`"
+    );
+    assert_eq!(
+        error("function f() public { }"),
+        "`f` can't have a visibility outside a contract"
+    );
+    assert_eq!(
+        error("contract Test { function f() { uint public a = 1; } }"),
+        "`a` can't have a visibility outside a contract"
+    );
+}
+
+#[test]
+fn test_getters() {
+    let source = "contract Token {\
+           uint public supply;\
+           mapping(address => uint8) public balances;\
+           uint[3][] public limits;\
+           uint total;\
+         }\
+         contract Bank {\
+           function f(Token token, address owner) view returns (uint) {\
+             uint8 balance = token.balances(owner);\
+             return token.supply() + balance + token.limits(0, 2);\
+           }\
+         }";
+    let program = parser::parse_program(source).unwrap();
+    let table = make_symbol_table(&program).unwrap();
+    let token = &table.sub_tables[0];
+    assert_eq!(
+        token.symbols["balances"].getter(),
+        Some(SymbolType::Function {
+            params: vec![SymbolType::Address],
+            returns: vec![SymbolType::Uint(8)],
+        })
+    );
+    assert_eq!(
+        token.symbols["limits"].getter(),
+        Some(SymbolType::Function {
+            params: vec![SymbolType::Uint256, SymbolType::Uint256],
+            returns: vec![SymbolType::Uint256],
+        })
+    );
+    // Only state variables declared `public` have getters
+    assert_eq!(token.symbols["total"].getter(), None);
+
+    let error = |body: &str| {
+        let source = format!(
+            "contract Token {{ mapping(address => uint) public balances; }}\
+             contract Bank {{ function f(Token token, address owner) {{ {} }} }}",
+            body
+        );
+        let program = parser::parse_program(&source).unwrap();
+        match make_symbol_table(&program).unwrap_err().error {
+            SymbolTableErrorType::TypeError(message) => message,
+            error => panic!("unexpected error {:?}", error),
+        }
+    };
+    assert_eq!(
+        error("token.balances();"),
+        "`Token.balances` takes 1 argument(s) but 0 were given"
+    );
+    assert_eq!(
+        error("uint8 balance = token.balances(owner);"),
+        "cannot implicitly convert `uint` to `uint8`: narrowing conversion may lose bits"
+    );
+}
+
+#[test]
+fn test_interfaces() {
+    let source = "interface IToken {\
+           event Transfer(address to, uint amount);\
+           function transfer(address to, uint amount) external returns (bool);\
+           function supply() external view returns (uint);\
+         }\
+         contract Token is IToken {\
+           uint public supply;\
+           function transfer(address to, uint amount) external returns (bool) { return amount > 0; }\
+         }\
+         contract Wallet {\
+           function pay(IToken token, address to) returns (bool) {\
+             return token.transfer(to, token.supply());\
+           }\
+         }";
+    let program = parser::parse_program(source).unwrap();
+    let table = make_symbol_table(&program).unwrap();
+    assert_eq!(table.sub_tables[0].table_type, SymbolTableType::Interface);
+    // Calls through an interface may write state unless declared otherwise
+    let wallet = &table.sub_tables[2];
+    assert_eq!(
+        wallet.sub_tables[0].mutability,
+        Some(StateMutability::NonPayable)
+    );
+
+    let error = |source: &str| {
+        let program = parser::parse_program(source).unwrap();
+        match make_symbol_table(&program).unwrap_err().error {
+            SymbolTableErrorType::TypeError(message) => message,
+            error => panic!("unexpected error {:?}", error),
+        }
+    };
+    let token = |members: &str| {
+        format!(
+            "interface IToken {{ function transfer(address to, uint amount) returns (bool); }}\
+             contract Token is IToken {{ {} }}",
+            members
+        )
+    };
+    assert_eq!(
+        error(&token("")),
+        "`Token` doesn't implement `transfer(address,uint256) returns (bool)` of `IToken`"
+    );
+    assert_eq!(
+        error(&token(
+            "function transfer(address to, uint amount) returns (uint) { return 1; }"
+        )),
+        "`Token.transfer` doesn't match `transfer(address,uint256) returns (bool)` of `IToken`"
+    );
+    assert_eq!(
+        error(&token(
+            "function transfer(address to, uint amount) internal returns (bool) { return amount > 0; }"
+        )),
+        "`Token.transfer` is internal but implements a function of `IToken`"
+    );
+    assert_eq!(
+        error("contract Token is IToken { } interface IToken { }"),
+        "`IToken` is not an interface declared before `Token`"
+    );
+    assert_eq!(
+        error("contract Base { } contract Token is Base { }"),
+        "`Base` is not an interface declared before `Token`"
+    );
+    assert_eq!(
+        error("interface IToken { function supply() returns (uint) { return 1; } }"),
+        "function `supply` of interface `IToken` can't have a body"
+    );
+    assert_eq!(
+        error("contract Token { function supply() returns (uint); }"),
+        "function `supply` has no body"
+    );
+    assert_eq!(
+        error("interface IToken { uint supply; }"),
+        "interface `IToken` can't declare variable `supply`"
+    );
+    assert_eq!(
+        error("interface IToken { constructor() { } }"),
+        "interface `IToken` can't have a constructor"
+    );
+    assert_eq!(
+        error("interface IOwned { } interface IToken is IOwned { }"),
+        "interface `IToken` can't implement other interfaces"
+    );
+}
+
+#[test]
+fn test_libraries() {
+    let library = "library SafeMath {\
+           uint256 constant ONE = 1;\
+           function add(uint256 a, uint256 b) internal returns (uint256) { return a + b; }\
+           function inc(uint256 a) returns (uint256) { return add(a, ONE); }\
+           function neg(bool a) returns (bool) { return !a; }\
+         }";
+    let token = |members: &str| format!("{} contract Token {{ {} }}", library, members);
+    let source = token(
+        "using SafeMath for uint256;\
+         uint256 total;\
+         function deposit(uint256 amount) returns (uint256) {\
+           total = total.add(amount).inc();\
+           return SafeMath.add(total, amount);\
+         }",
+    );
+    let program = parser::parse_program(&source).unwrap();
+    let table = make_symbol_table(&program).unwrap();
+    assert_eq!(table.sub_tables[0].table_type, SymbolTableType::Library);
+    let contract = &table.sub_tables[1];
+    assert_eq!(contract.using.len(), 1);
+    assert_eq!(contract.using[0].library, "SafeMath");
+    assert_eq!(contract.using[0].target, SymbolType::Uint256);
+
+    let error = |source: &str| {
+        let program = parser::parse_program(source).unwrap();
+        match make_symbol_table(&program).unwrap_err().error {
+            SymbolTableErrorType::TypeError(message) => message,
+            error => panic!("unexpected error {:?}", error),
+        }
+    };
+    // Functions aren't attached to types other than the target, nor without `using`
+    assert!(error(&token(
+        "using SafeMath for uint256;\
+         function f(bool x) returns (bool) { return x.add(x); }"
+    ))
+    .contains("add"));
+    assert!(error(&token(
+        "function f(uint256 x) returns (uint256) { return x.add(x); }"
+    ))
+    .contains("add"));
+    assert!(error(&token(
+        "using SafeMath for uint256;\
+         function f(uint256 x) returns (uint256) { return x.add(x, x); }"
+    ))
+    .contains("argument"));
+    assert_eq!(
+        error("contract Math { } contract Token { using Math for uint256; }"),
+        "`Math` is not a library declared before `Token`"
+    );
+    assert_eq!(
+        error("library SafeMath { uint256 total; }"),
+        "library `SafeMath` can't declare variable `total`"
+    );
+    assert_eq!(
+        error("library SafeMath { constructor() { } }"),
+        "library `SafeMath` can't have a constructor"
+    );
+    assert_eq!(
+        error("interface IMath { } library SafeMath is IMath { }"),
+        "library `SafeMath` can't implement interfaces"
+    );
+    assert_eq!(
+        error(&format!(
+            "{} interface IToken {{ using SafeMath for uint256; }}",
+            library
+        )),
+        "interface `IToken` can't attach libraries"
+    );
+    assert_eq!(
+        error("library SafeMath { function add(uint256 a) returns (uint256); }"),
+        "function `add` has no body"
+    );
+}
+
+#[test]
+fn test_extracted_interface() {
+    let counter = "contract Counter {\
+           uint public count;\
+           mapping(address => uint[]) public history;\
+           event Counted(address from, uint count);\
+           function add(uint amount) returns (uint) { count += amount; return count; }\
+           function reset() internal { count = 0; }\
+         }";
+    let program = parser::parse_program(counter).unwrap();
+    let Program::GlobalStatements(statements) = &program;
+    let interface = interface_source(&statements[0], &InterfaceConfig::default()).unwrap();
+    let source = format!(
+        "{}\n{}",
+        interface,
+        counter.replacen("Counter", "Counter is ICounter", 1)
+    );
+    let program = parser::parse_program(&source).unwrap();
+    let table = make_symbol_table(&program).unwrap();
+    assert_eq!(table.sub_tables[0].table_type, SymbolTableType::Interface);
+}
+
+#[test]
+fn test_do_while_scope() {
+    let source = "function f(uint n) {\
+           uint i = 0;\
+           do { uint step = 2; i += step; } while (i < n);\
+         }";
+    let program = parser::parse_program(source).unwrap();
+    let table = make_symbol_table(&program).unwrap();
+    let body = &table.sub_tables[0].sub_tables[0];
+    let do_while = &body.sub_tables[0];
+    assert_eq!(do_while.name, "#DoWhile");
+    assert_eq!(do_while.symbols["i"].usage, SymbolUsage::Read);
+    assert_eq!(
+        do_while.sub_tables[0].symbols["step"].usage,
+        SymbolUsage::Declared
+    );
+
+    // Locals of the body are out of scope in the condition
+    let source = "function f() { do { uint step = 2; } while (step < 3); }";
+    let program = parser::parse_program(source).unwrap();
+    let err = make_symbol_table(&program).unwrap_err();
+    assert_eq!(
+        err.error,
+        SymbolTableErrorType::NotDeclared("step".to_string())
+    );
+}
+
+#[test]
+fn test_branch_types() {
+    let source = "contract Test {\
+           function pick(uint x, uint8 y) returns (uint) {\
+             uint a = x > 1 ? x : y;\
+             uint b = if x > 2 { x } else { 3 };\
+             if x > 3 { a = 1; };\
+             return a + b;\
+           }\
+        }";
+    let program = parser::parse_program(source).unwrap();
+    assert!(make_symbol_table(&program).is_ok());
+
+    let error = |source: &str| {
+        let program = parser::parse_program(source).unwrap();
+        let err = make_symbol_table(&program).unwrap_err();
+        match err.error {
+            SymbolTableErrorType::TypeError(message) => (message, err.location),
+            error => panic!("unexpected error {:?}", error),
+        }
+    };
+    assert_eq!(
+        error("function f(uint x, address a) { uint b = x > 1 ? x : a; }"),
+        (
+            "branches of `ternary` have different types `uint` and `address`".to_string(),
+            Location::new(0, 54)
+        )
+    );
+    let (message, _) = error("function f(uint x, int y) { x = if x > 1 { x } else { y }; }");
+    assert_eq!(
+        message,
+        "branches of `if` have different types `uint` and `int`"
+    );
+    let (message, _) = error("function f(uint x) { x = if x > 1 { 2 }; }");
+    assert_eq!(message, "`if` used as a value must have an `else` branch");
+}
+
+#[test]
+fn test_symbol_orderings() {
+    let source = "contract Test {\n\
+         \x20   uint zeta;\n\
+         \x20   function beta(uint b) returns (uint) { uint a = zeta; return a + b; }\n\
+         \x20   uint alpha;\n\
+         }";
+    let program = parser::parse_program(source).unwrap();
+    let table = make_symbol_table(&program).unwrap();
+    let names = |symbols: Vec<&Symbol>| -> Vec<String> {
+        symbols.iter().map(|symbol| symbol.name.clone()).collect()
+    };
+    let contract = table.contract_table("Test").unwrap();
+    assert_eq!(
+        names(contract.symbols_in_source_order()),
+        ["zeta", "beta", "alpha"]
+    );
+    assert_eq!(
+        names(contract.symbols_sorted_by_name()),
+        ["alpha", "beta", "zeta"]
+    );
+}
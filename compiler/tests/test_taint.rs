@@ -0,0 +1,47 @@
+use zoker_compiler::symbol_table::make_symbol_table;
+use zoker_compiler::taint::{taint_warnings, Sink};
+use zoker_parser::parser;
+
+#[test]
+fn test_taint_warnings() {
+    let source = "contract Wallet {\
+           address owner;\
+           uint[] deposits;\
+           function withdraw(address to, uint amount) { to.transfer(amount); }\
+           function set_owner(address next) { address candidate = next; owner = candidate; }\
+           function kill() { selfdestruct(msg.sender); }\
+           function safe_kill(address to) { assert(msg.sender == owner); selfdestruct(to); }\
+           function deposit(uint amount) { deposits.push(amount); }\
+           function refund() { owner.transfer(10); }\
+         }";
+    let program = parser::parse_program(source).unwrap();
+    make_symbol_table(&program).unwrap();
+    let warnings = taint_warnings(&program);
+    let flows: Vec<(&str, &Sink, Vec<&str>)> = warnings
+        .iter()
+        .map(|warning| {
+            let sources = warning.sources.iter().map(String::as_str).collect();
+            (warning.function.as_str(), &warning.sink, sources)
+        })
+        .collect();
+    assert_eq!(
+        flows,
+        vec![
+            (
+                "Wallet.withdraw",
+                &Sink::ExternalCall("transfer".to_string()),
+                vec!["amount", "to"]
+            ),
+            (
+                "Wallet.set_owner",
+                &Sink::OwnershipWrite("owner".to_string()),
+                vec!["next"]
+            ),
+            ("Wallet.kill", &Sink::Selfdestruct, vec!["msg.sender"]),
+        ]
+    );
+    assert_eq!(
+        warnings[1].to_string(),
+        "write to `owner` in `Wallet.set_owner` depends on `next` without a check of `msg.sender`"
+    );
+}
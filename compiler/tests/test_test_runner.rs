@@ -0,0 +1,54 @@
+use zoker_compiler::error::{CompileError, ExecutionErrorType, SymbolTableErrorType};
+use zoker_compiler::test_runner::{run_tests, TestOutcome};
+
+const SOURCE: &str = "contract Counter {\
+       uint count;\
+       function increment() returns (uint) { count += 1; return count; }\
+       @test function test_increment() {\
+         increment();\
+         assert(increment() == 2, \"counts from zero\");\
+       }\
+       @test function test_fresh_state() { assert(increment() == 2, \"state is shared\"); }\
+     }\
+     #[cfg(test)]\
+     module checks {\
+       @test function test_math() { assert(1 + 1 == 2); }\
+     }";
+
+#[test]
+fn test_run_tests() {
+    let report = run_tests(SOURCE).unwrap();
+    let names: Vec<&str> = report.results.iter().map(|r| r.name.as_str()).collect();
+    assert_eq!(
+        names,
+        vec![
+            "Counter.test_increment",
+            "Counter.test_fresh_state",
+            "checks::test_math"
+        ]
+    );
+    assert_eq!(report.passed(), 2);
+    assert_eq!(report.failed(), 1);
+    match &report.results[1].outcome {
+        TestOutcome::Failed(err) => assert_eq!(
+            err.error,
+            ExecutionErrorType::Revert(Some("state is shared".to_string()))
+        ),
+        TestOutcome::Passed => panic!("test is expected to fail"),
+    }
+    let output = report.to_string();
+    assert!(output.contains("test Counter.test_increment ... ok"));
+    assert!(output.contains("Counter.test_fresh_state: assertion failed: state is shared at line"));
+    assert!(output.ends_with("test result: FAILED. 2 passed; 1 failed"));
+}
+
+#[test]
+fn test_test_parameters() {
+    match run_tests("@test function f(uint x) { }").unwrap_err() {
+        CompileError::SymbolTable(err) => assert_eq!(
+            err.error,
+            SymbolTableErrorType::TypeError("test function `f` can't take parameters".to_string())
+        ),
+        err => panic!("unexpected error {:?}", err),
+    }
+}
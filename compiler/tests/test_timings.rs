@@ -0,0 +1,40 @@
+use zoker_compiler::error::CompileError;
+use zoker_compiler::timings::{
+    compile_with_timings, LEXING, PARSING, REWRITING, SYMBOL_ANALYSIS, SYMBOL_TABLE,
+};
+
+#[test]
+fn test_compile_timings() {
+    let source = "contract Test {\
+           function add(uint a, uint b) returns (uint) {\
+             return a + b;\
+           }\
+        }";
+    let (result, timings) = compile_with_timings(source);
+    assert_eq!(result.unwrap().len(), 1);
+    let phases = timings
+        .phases
+        .iter()
+        .map(|(phase, _)| phase.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        phases,
+        vec![LEXING, PARSING, SYMBOL_TABLE, SYMBOL_ANALYSIS, REWRITING]
+    );
+    assert!(timings.get(PARSING).unwrap() <= timings.total());
+    let summary = timings.to_string();
+    assert!(summary.contains("symbol analysis"));
+    assert!(summary.lines().last().unwrap().starts_with("total"));
+}
+
+#[test]
+fn test_compile_timings_stop_at_error() {
+    let (result, timings) = compile_with_timings("contract Test { uint a = b; }");
+    assert!(matches!(result, Err(CompileError::SymbolTable(_))));
+    assert_eq!(timings.phases.len(), 4);
+    assert!(timings.get(REWRITING).is_none());
+
+    let (result, timings) = compile_with_timings("contract Test { uint a = \"; }");
+    assert!(matches!(result, Err(CompileError::Parse(_))));
+    assert_eq!(timings.phases.len(), 1);
+}
@@ -0,0 +1,35 @@
+use zoker_compiler::driver::Compiler;
+use zoker_compiler::trace::symbolize;
+use zoker_parser::location::Location;
+
+#[test]
+fn test_symbolize_trace() {
+    let source = "contract Test {\n\
+           function f(uint x) returns (uint) { return x; }\n\
+           function g(uint x, uint y) returns (uint) { return x + y; }\n\
+         }";
+    let compilation = Compiler::new().compile(source).unwrap();
+    let artifact = &compilation.artifacts.unwrap()[0];
+    let entries = &artifact.source_map.entries;
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].function, "f");
+    assert_eq!(entries[0].location.row(), 1);
+    assert_eq!(entries[1].start, entries[0].start + entries[0].length);
+    assert_eq!(entries[1].start + entries[1].length, artifact.size.runtime);
+
+    let g = entries[1].start;
+    let trace = vec![0, 1, 2, g, g + 1, g + 2, artifact.size.runtime];
+    let frames = symbolize(&trace, artifact);
+    assert_eq!(frames.len(), 3);
+    assert_eq!(frames[0].function, None);
+    assert_eq!(frames[0].pcs, vec![0, 1, 2]);
+    assert_eq!(frames[1].function, Some("g".to_string()));
+    assert_eq!(frames[1].location, Some(entries[1].location));
+    assert_eq!(frames[1].pcs.len(), 3);
+    assert_eq!(frames[2].function, None);
+    assert_eq!(
+        frames[1].to_string(),
+        format!("g at {} (3 steps)", entries[1].location)
+    );
+    assert_ne!(entries[1].location, Location::default());
+}
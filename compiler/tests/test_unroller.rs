@@ -0,0 +1,62 @@
+use num_bigint::BigUint;
+use zoker_compiler::rewriter::rewrite_program;
+use zoker_compiler::symbol::{Contract, OperationType};
+use zoker_compiler::unroller::{unroll_contract, UnrollConfig};
+use zoker_parser::parser;
+
+fn compile(source: &str) -> Contract {
+    let program = parser::parse_program(source).unwrap();
+    rewrite_program(&program).unwrap().remove(0)
+}
+
+const SOURCE: &str = "contract Test {\
+       function sum(uint a) returns (uint) {\
+         for i in 2 + 1 { a = a + i; };\
+         for j in 2 { for k in 2 { a = a + k; }; };\
+         for m in a { a = a + m; };\
+         return a;\
+       }\
+    }";
+
+#[test]
+fn test_unroll_constant_loops() {
+    let mut contract = compile(SOURCE);
+    assert_eq!(unroll_contract(&mut contract, &UnrollConfig::default()), 3);
+    let operations = &contract.functions[0].operations;
+    // 3 iterations of 2 operations, 2 * (1 + 2 * 2) for the nested loops,
+    // the loop over `a` and the return
+    assert_eq!(operations.len(), 6 + 10 + 2);
+    match &operations[2].operation {
+        OperationType::Assign { left, right } => {
+            assert_eq!(left.as_symbol().unwrap().name, "i");
+            assert_eq!(
+                right.operation,
+                OperationType::Constant {
+                    value: BigUint::from(1u32)
+                }
+            );
+        }
+        op => panic!("expected assignment, got {:?}", op),
+    }
+    assert!(matches!(
+        operations[16].operation,
+        OperationType::For { .. }
+    ));
+}
+
+#[test]
+fn test_unroll_limit() {
+    let mut contract = compile(SOURCE);
+    let config = UnrollConfig {
+        max_iterations: 2,
+        ..UnrollConfig::default()
+    };
+    assert_eq!(unroll_contract(&mut contract, &config), 2);
+
+    let mut contract = compile(SOURCE);
+    let config = UnrollConfig {
+        max_size: 1,
+        ..UnrollConfig::default()
+    };
+    assert_eq!(unroll_contract(&mut contract, &config), 0);
+}
@@ -0,0 +1,56 @@
+use zoker_compiler::driver::Compiler;
+use zoker_compiler::metadata::append_metadata;
+use zoker_compiler::settings::{CompileSettings, OptLevel};
+use zoker_compiler::verify::{matches, VerificationResult};
+
+const TOKEN: &str = "contract Token {\
+       function double(uint a) returns (uint) { return a * 2; }\
+    }";
+const VAULT: &str = "contract Vault {\
+       function triple(uint a) returns (uint) { return a * 3; }\
+    }";
+
+fn deployed(source: &str, name: &str) -> Vec<u8> {
+    let mut compiler = Compiler::new();
+    compiler.source_name = name.to_string();
+    let artifacts = compiler.compile(source).unwrap().artifacts.unwrap();
+    append_metadata(&[0x60, 0x80], &artifacts[0].metadata)
+}
+
+#[test]
+fn test_verify() {
+    let settings = CompileSettings::default();
+    let sources = [("token.zok", TOKEN), ("vault.zok", VAULT)];
+    let bytecode = deployed(VAULT, "vault.zok");
+    let result = matches(&sources, &settings, &bytecode).unwrap();
+    assert_eq!(
+        result,
+        VerificationResult::Match {
+            source: "vault.zok".to_string(),
+            contract: "Vault".to_string()
+        }
+    );
+    assert_eq!(
+        result.to_string(),
+        "matches contract `Vault` of `vault.zok`"
+    );
+
+    let unoptimized = CompileSettings {
+        opt_level: OptLevel::None,
+        ..settings.clone()
+    };
+    assert_eq!(
+        matches(&sources, &unoptimized, &bytecode).unwrap(),
+        VerificationResult::Mismatch
+    );
+    let edited = [("vault.zok", &VAULT.replace('3', "4")[..])];
+    assert_eq!(
+        matches(&edited, &settings, &bytecode).unwrap(),
+        VerificationResult::Mismatch
+    );
+    assert_eq!(
+        matches(&sources, &settings, &[0x60, 0x80]).unwrap(),
+        VerificationResult::NoMetadata
+    );
+    assert!(matches(&[("broken.zok", "contract {")], &settings, &bytecode).is_err());
+}
@@ -0,0 +1,126 @@
+use num_bigint::BigInt;
+use zoker_compiler::interpreter::Value;
+use zoker_compiler::rewriter::rewrite_program;
+use zoker_compiler::symbol::Function;
+use zoker_compiler::witness::{parse_witness_input, witness_schema};
+use zoker_parser::parser;
+
+fn function(source: &str) -> Function {
+    let program = parser::parse_program(source).unwrap();
+    rewrite_program(&program)
+        .unwrap()
+        .remove(0)
+        .functions
+        .remove(0)
+}
+
+fn int(value: i64) -> Value {
+    Value::Int(BigInt::from(value))
+}
+
+#[test]
+fn test_witness_schema() {
+    let vote = function(
+        "contract Vote {\
+           function cast(private uint8 choice, uint round, private bool[2] flags) { }\
+         }",
+    );
+    assert_eq!(
+        witness_schema(&vote).unwrap(),
+        "{\"$schema\":\"https://json-schema.org/draft/2020-12/schema\",\
+         \"additionalProperties\":false,\"properties\":{\
+         \"private\":{\"additionalProperties\":false,\"properties\":{\
+         \"choice\":{\"description\":\"uint8\",\"pattern\":\"^(0x[0-9a-fA-F]+|[0-9]+)$\",\
+         \"type\":\"string\"},\
+         \"flags\":{\"items\":{\"type\":\"boolean\"},\"maxItems\":2,\"minItems\":2,\
+         \"type\":\"array\"}},\
+         \"required\":[\"choice\",\"flags\"],\"type\":\"object\"},\
+         \"public\":{\"additionalProperties\":false,\"properties\":{\
+         \"round\":{\"description\":\"uint\",\"pattern\":\"^(0x[0-9a-fA-F]+|[0-9]+)$\",\
+         \"type\":\"string\"}},\
+         \"required\":[\"round\"],\"type\":\"object\"}},\
+         \"required\":[\"private\",\"public\"],\"title\":\"cast\",\"type\":\"object\"}"
+    );
+}
+
+#[test]
+fn test_parse_witness_input() {
+    let vote = function(
+        "contract Vote {\
+           function cast(private uint8 choice, int round, private bool[2] flags, address voter) { }\
+         }",
+    );
+    let input = "{\
+           \"public\": {\"round\": \"-0x2a\", \"voter\": \"0x00000000000000000000000000000000000000ff\"},\
+           \"private\": {\"choice\": \"255\", \"flags\": [true, false]}\
+         }";
+    assert_eq!(
+        parse_witness_input(&vote, input),
+        Ok(vec![
+            int(255),
+            int(-42),
+            Value::Array(vec![Value::Bool(true), Value::Bool(false)]),
+            int(255),
+        ])
+    );
+
+    let error = |input: &str| parse_witness_input(&vote, input).unwrap_err().error;
+    let grouped = |private: &str, public: &str| {
+        error(&format!(
+            "{{\"private\": {{{}}}, \"public\": {{{}}}}}",
+            private, public
+        ))
+    };
+    let public = "\"round\": \"0\", \"voter\": \"0x00000000000000000000000000000000000000ff\"";
+    assert_eq!(
+        grouped("\"choice\": \"256\", \"flags\": [true, true]", public),
+        "`private.choice`: 256 is out of range of `uint8`"
+    );
+    assert_eq!(
+        grouped("\"choice\": 1, \"flags\": [true, true]", public),
+        "`private.choice`: expected `uint8`, found a number"
+    );
+    assert_eq!(
+        grouped("\"choice\": \"1\", \"flags\": [true]", public),
+        "`private.flags`: expected 2 items, found 1"
+    );
+    assert_eq!(
+        grouped("\"flags\": [true, true]", public),
+        "`private.choice`: missing"
+    );
+    assert_eq!(
+        grouped(
+            "\"choice\": \"1\", \"flags\": [true, true], \"round\": \"1\"",
+            public
+        ),
+        "`private.round`: `cast` has no private parameter `round`"
+    );
+    assert_eq!(
+        grouped("\"choice\": \"1\", \"flags\": [true, 1]", public),
+        "`private.flags[1]`: expected `bool`, found a number"
+    );
+    assert_eq!(
+        grouped(
+            "\"choice\": \"1\", \"flags\": [true, true]",
+            "\"round\": \"0\", \"voter\": \"0xff\""
+        ),
+        "`public.voter`: expected `address`, found a string"
+    );
+    assert_eq!(error("{\"private\": {}}"), "`public`: missing");
+    assert_eq!(
+        error("{\"private\": {}, \"public\": {}, \"secret\": {}}"),
+        "`secret`: expected `private` or `public`"
+    );
+    assert_eq!(
+        error("[]"),
+        "witness input of `cast` must be an object, found an array"
+    );
+    assert_eq!(
+        error("{\"private\": {} \"public\": {}}"),
+        "invalid JSON at byte 15: expected `,`"
+    );
+    assert_eq!(
+        error(&"[".repeat(200_000)),
+        "invalid JSON at byte 128: nested deeper than 128 levels"
+    );
+}
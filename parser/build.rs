@@ -1,5 +1,32 @@
 extern crate lalrpop;
 
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+/// Alternative of `Recovery` in the grammar, recovering from errors.
+const RECOVERY: &str = "    !,\n";
+/// Alternative replacing it in the strict parsers, which can't be parsed.
+const NO_RECOVERY: &str = "    \"EOF\" => unreachable!(),\n";
+
 fn main() {
-    lalrpop::process_root().unwrap()
+    // Tolerant parsers, recovering from syntax errors
+    lalrpop::process_root().unwrap();
+
+    // Strict parsers, failing on the first syntax error. Recovering may take
+    // quadratic time in the depth of the input, e.g. on unclosed braces.
+    let grammar = fs::read_to_string("src/zok.lalrpop").unwrap();
+    assert_eq!(grammar.matches(RECOVERY).count(), 1);
+    let strict = PathBuf::from(env::var("OUT_DIR").unwrap()).join("strict");
+    fs::create_dir_all(&strict).unwrap();
+    fs::write(
+        strict.join("zok.lalrpop"),
+        grammar.replace(RECOVERY, NO_RECOVERY),
+    )
+    .unwrap();
+    lalrpop::Configuration::new()
+        .set_in_dir(&strict)
+        .set_out_dir(&strict)
+        .process()
+        .unwrap();
 }
@@ -1,15 +1,19 @@
 use num_bigint::BigUint;
+use std::fmt;
 
 use crate::location::Location;
 
+/// Name of the function declared by `constructor(..) { .. }`, which no identifier can have.
+pub const CONSTRUCTOR: &str = "constructor";
+
 // https://rust-lang.github.io/rust-clippy/master/index.html#large_enum_variant
 #[allow(clippy::large_enum_variant)]
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Program {
     GlobalStatements(Vec<Statement>),
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Located<T> {
     pub location: Location,
     pub node: T,
@@ -17,25 +21,82 @@ pub struct Located<T> {
 
 pub type Statement = Located<StatementType>;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum StatementType {
     // Global Statement
     FunctionStatement {
+        annotations: Vec<Annotation>,
         function_name: Box<Expression>,
         parameters: Box<Expression>,
-        statement: Box<Statement>,
+        /// Body of the function, `None` for a declaration ending with `;`
+        statement: Option<Box<Statement>>,
         returns: Option<Box<Expression>>,
+        /// Visibility written after the parameters, `public` if omitted
+        visibility: Option<Visibility>,
+        /// State mutability written after the visibility, inferred if omitted
+        mutability: Option<Mutability>,
+        /// Doc comment written before the function, one line per `///`,
+        /// at the location of its first line
+        doc: Option<Located<String>>,
     },
     ContractStatement {
+        annotations: Vec<Annotation>,
+        kind: ContractKind,
         contract_name: Box<Expression>,
+        /// Interfaces the contract implements, written after `is`
+        bases: Vec<Expression>,
         members: Box<Statement>,
+        /// Doc comment written before the contract, one line per `///`,
+        /// at the location of its first line
+        doc: Option<Located<String>>,
+    },
+    /// Event declared in a contract, e.g. `event Transfer(address to, uint amount);`
+    EventStatement {
+        event_name: Box<Expression>,
+        parameters: Box<Expression>,
+    },
+    /// Enum declared in a contract, e.g. `enum Color { Red, Green }`
+    EnumStatement {
+        enum_name: Box<Expression>,
+        variants: Vec<Expression>,
+    },
+    /// Struct declared in a contract, e.g. `struct Point { uint x; uint y; }`
+    StructStatement {
+        struct_name: Box<Expression>,
+        fields: Vec<Statement>,
+    },
+    /// Module grouping functions, constants and other modules, e.g. `module math { .. }`
+    ModuleStatement {
+        module_name: Box<Expression>,
+        members: Vec<Statement>,
+    },
+    /// Item compiled only if its condition holds, e.g. `#[cfg(feature = "testnet")] function f() { }`
+    ConditionalStatement {
+        condition: CfgPredicate,
+        statement: Box<Statement>,
+    },
+    /// Functions of a library attached to a type, e.g. `using SafeMath for uint256;`
+    UsingStatement {
+        library: Box<Expression>,
+        target: Type,
+    },
+    /// Module of the standard library made available as `std::<name>`, e.g. `import "std/math";`
+    ImportStatement {
+        path: String,
     },
     InitializerStatement {
         variable_type: Type,
         is_private: bool,
+        /// Visibility of a state variable, written after its type, e.g. `uint public total`
+        visibility: Option<Visibility>,
+        /// Declared `constant`, its value is known at compile time
+        is_constant: bool,
         data_location: Option<Specifier>,
         variable: Option<Box<Expression>>,
         default: Option<Box<Expression>>,
+        /// Doc comment written before a state variable, one line per `///`,
+        /// at the location of its first line
+        doc: Option<Located<String>>,
     },
     // Local Statement
     CompoundStatement {
@@ -45,17 +106,28 @@ pub enum StatementType {
     MemberStatement {
         statements: Vec<Statement>,
     },
+    /// Block without bounds checks, e.g. `unchecked { a[i] }`
+    UncheckedStatement {
+        statement: Box<Statement>,
+    },
+    /// Loop whose body runs before its condition is checked, e.g. `do { i += 1; } while (i < n)`
+    DoWhileStatement {
+        statement: Box<Statement>,
+        condition: Box<Expression>,
+    },
     ReturnStatement {
         ret: Option<Box<Expression>>,
     },
     Expression {
         expression: Box<Expression>,
     },
+    /// Statement which couldn't be parsed, left by error recovery
+    Error,
 }
 
 pub type Expression = Located<ExpressionType>;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum ExpressionType {
     AssignExpression {
         left: Box<Expression>,
@@ -76,11 +148,28 @@ pub enum ExpressionType {
         function_name: Box<Expression>,
         arguments: Box<Expression>,
     },
+    MemberExpression {
+        expression: Box<Expression>,
+        member: Box<Expression>,
+    },
+    IndexExpression {
+        expression: Box<Expression>,
+        index: Box<Expression>,
+    },
+    /// Member of a module, e.g. `math::clamp`
+    PathExpression {
+        module: Box<Expression>,
+        member: Box<Expression>,
+    },
     IfExpression {
         condition: Box<Expression>,
         if_statement: Box<Statement>,
         else_statement: Option<Box<Statement>>,
     },
+    MatchExpression {
+        value: Box<Expression>,
+        arms: Vec<MatchArm>,
+    },
     ForEachExpression {
         iterator: Box<Expression>,
         vector: Box<Expression>,
@@ -100,12 +189,135 @@ pub enum ExpressionType {
     Tuple {
         items: Vec<Option<Expression>>,
     },
+    TypeList {
+        types: Vec<Type>,
+    },
     Number {
         value: BigUint,
+        /// Base the number is written in, e.g. `Hexadecimal` for `0xff`
+        radix: Radix,
     },
     Identifier {
         value: String,
     },
+    /// String literal with escape sequences decoded into `value` and kept as written in `raw`.
+    StringLiteral {
+        value: String,
+        raw: String,
+    },
+    /// Struct built with named fields, e.g. `Point({x: 1, y: 2})`
+    StructLiteral {
+        struct_name: Box<Expression>,
+        fields: Vec<StructField>,
+    },
+    /// Expression which couldn't be parsed, left by error recovery
+    Error,
+}
+
+/// Named field of a struct literal, e.g. `x: 1`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StructField {
+    pub name: Box<Expression>,
+    pub value: Box<Expression>,
+}
+
+/// Arm of a `match`, e.g. `Color.Red => { .. }`. The pattern `_` matches any value.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatchArm {
+    pub pattern: Box<Expression>,
+    pub statement: Box<Statement>,
+}
+
+impl MatchArm {
+    pub fn is_wildcard(&self) -> bool {
+        self.pattern.node.is_placeholder()
+    }
+}
+
+/// Annotation of a function or contract, e.g. `@inline` or `@deprecated("use transfer")`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Annotation {
+    pub location: Location,
+    pub name: String,
+    pub arguments: Vec<Expression>,
+}
+
+/// Condition of a `#[cfg(..)]` attribute, evaluated against the defines of a compilation.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CfgPredicate {
+    /// Name which is defined, e.g. `debug`
+    Flag(String),
+    /// Name defined with a value, e.g. `feature = "testnet"`
+    Value {
+        name: String,
+        value: String,
+    },
+    Not(Box<CfgPredicate>),
+    All(Vec<CfgPredicate>),
+    Any(Vec<CfgPredicate>),
+}
+
+impl StatementType {
+    /// Whether this declares the constructor of a contract.
+    pub fn is_constructor(&self) -> bool {
+        match self {
+            StatementType::FunctionStatement { function_name, .. } => {
+                function_name.node.identifier_name().as_deref() == Some(CONSTRUCTOR)
+            }
+            _ => false,
+        }
+    }
+
+    /// Annotations of a function or contract, empty for any other statement.
+    pub fn annotations(&self) -> &[Annotation] {
+        match self {
+            StatementType::FunctionStatement { annotations, .. }
+            | StatementType::ContractStatement { annotations, .. } => annotations,
+            _ => &[],
+        }
+    }
+
+    /// Annotation named `name`, e.g. `inline` for `@inline`.
+    pub fn annotation(&self, name: &str) -> Option<&Annotation> {
+        self.annotations()
+            .iter()
+            .find(|annotation| annotation.name == name)
+    }
+
+    /// Doc comment of a contract, function or variable, with its location.
+    pub fn doc_comment(&self) -> Option<&Located<String>> {
+        match self {
+            StatementType::FunctionStatement { doc, .. }
+            | StatementType::ContractStatement { doc, .. }
+            | StatementType::InitializerStatement { doc, .. } => doc.as_ref(),
+            _ => None,
+        }
+    }
+
+    /// Text of the doc comment of a contract, function or variable.
+    pub fn doc(&self) -> Option<&str> {
+        self.doc_comment().map(|doc| doc.node.as_str())
+    }
+
+    /// Adds `line`, written at `location`, before the doc comment of the declaration,
+    /// or of the one compiled under a condition. Doc comments of other statements are dropped.
+    pub fn document(&mut self, location: Location, line: String) {
+        match self {
+            StatementType::FunctionStatement { doc, .. }
+            | StatementType::ContractStatement { doc, .. }
+            | StatementType::InitializerStatement { doc, .. } => {
+                let node = match doc.take() {
+                    Some(rest) => format!("{}\n{}", line, rest.node),
+                    None => line,
+                };
+                *doc = Some(Located { location, node });
+            }
+            StatementType::ConditionalStatement { statement, .. } => {
+                statement.node.document(location, line)
+            }
+            _ => {}
+        }
+    }
 }
 
 impl ExpressionType {
@@ -116,9 +328,40 @@ impl ExpressionType {
             None
         }
     }
+
+    /// Whether this is `_`, which ignores a value when destructuring or matching.
+    pub fn is_placeholder(&self) -> bool {
+        self.identifier_name().as_deref() == Some("_")
+    }
+
+    /// Name of an identifier or a chain of member accesses, e.g. `abi.encode`.
+    pub fn path_name(&self) -> Option<String> {
+        match self {
+            ExpressionType::Identifier { value } => Some(value.clone()),
+            ExpressionType::MemberExpression { expression, member } => Some(format!(
+                "{}.{}",
+                expression.node.path_name()?,
+                member.node.identifier_name()?
+            )),
+            _ => None,
+        }
+    }
+
+    /// Segments of an identifier or a path into modules, e.g. `["math", "clamp"]`.
+    pub fn module_path(&self) -> Option<Vec<String>> {
+        match self {
+            ExpressionType::Identifier { value } => Some(vec![value.clone()]),
+            ExpressionType::PathExpression { module, member } => {
+                let mut path = module.node.module_path()?;
+                path.push(member.node.identifier_name()?);
+                Some(path)
+            }
+            _ => None,
+        }
+    }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Operator {
     // Arithmetic Operator
     Add,
@@ -178,25 +421,122 @@ pub enum Operator {
     RShift,
 }
 
-#[derive(Debug, PartialEq)]
+/// Base of a number literal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Radix {
+    Decimal,
+    /// Written with a `0x` prefix, e.g. `0xDEADBEEF`
+    Hexadecimal,
+}
+
+impl Radix {
+    /// `value` as written in this base, e.g. `0xff` for 255 in hexadecimal.
+    pub fn format(self, value: &BigUint) -> String {
+        match self {
+            Radix::Decimal => value.to_string(),
+            Radix::Hexadecimal => format!("0x{:x}", value),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum Specifier {
     Memory,
     Storage,
+    /// Storage cleared at the end of each transaction, see EIP-1153
+    Transient,
+}
+
+/// Who can access a function or state variable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Visibility {
+    /// Accessible from anywhere
+    Public,
+    /// Only accessible in the contract declaring it
+    Private,
+    /// Only accessible in the contract declaring it and the contracts deriving from it
+    Internal,
+    /// Only callable through a value of the contract, for functions
+    External,
+}
+
+impl fmt::Display for Visibility {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Visibility::Public => write!(f, "public"),
+            Visibility::Private => write!(f, "private"),
+            Visibility::Internal => write!(f, "internal"),
+            Visibility::External => write!(f, "external"),
+        }
+    }
+}
+
+/// Keyword declaring a contract-like item.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContractKind {
+    Contract,
+    /// Functions without bodies, which implementing contracts must declare
+    Interface,
+    /// Functions without state, called through the library or attached to types with `using`
+    Library,
+}
+
+impl fmt::Display for ContractKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ContractKind::Contract => write!(f, "contract"),
+            ContractKind::Interface => write!(f, "interface"),
+            ContractKind::Library => write!(f, "library"),
+        }
+    }
+}
+
+/// State mutability declared by a function.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mutability {
+    /// Neither reads nor writes state
+    Pure,
+    /// Reads state without writing it
+    View,
+    /// Receives ether with its calls
+    Payable,
+}
+
+impl fmt::Display for Mutability {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Mutability::Pure => write!(f, "pure"),
+            Mutability::View => write!(f, "view"),
+            Mutability::Payable => write!(f, "payable"),
+        }
+    }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Type {
     // Static size
     Uint256,
     Int256,
+    /// Unsigned integer narrower than 256 bits, e.g. `uint8`
+    Uint(u16),
+    /// Signed integer narrower than 256 bits, e.g. `int8`
+    Int(u16),
     Bytes32,
     Bool,
+    /// Element of the scalar field of BN254, the curve of the proofs
+    Field,
 
     // Dynamic size
     Bytes,
     String,
     Address,
+    Array(Box<Type>),
+    /// Array whose length is a constant expression, e.g. `uint[2 * N]`
+    FixedArray(Box<Type>, Box<Expression>),
+    /// Type declared in the program, e.g. an enum
+    UserDefined(String),
+    /// Mapping from keys to values, e.g. `mapping(address => uint256)`
+    Mapping(Box<Type>, Box<Type>),
     // To be supported..
-    // Mapping,
     // Var,
 }
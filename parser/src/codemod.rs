@@ -0,0 +1,132 @@
+//! Scripted rewrites of sources, for mechanical migrations.
+//!
+//! A rule applies an action to the nodes found by a query, e.g. renaming the
+//! calls of a builtin with `FunctionCall[name=sha3]`. The actions edit the text
+//! of the source at the tokens of the matched nodes, so everything else, layout
+//! included, is kept as written. Nodes only know where they start, so actions
+//! edit a single token or insert text, and never replace a whole node.
+
+use crate::complexity::{program_nodes, Node};
+use crate::error::ParseError;
+use crate::lexer::{make_tokenizer, Tok};
+use crate::location::Location;
+use crate::parser;
+use crate::query::{name_expression, Query, QueryError};
+use std::collections::HashMap;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Action {
+    /// Replaces the name of the node, see the `name` attribute of queries
+    Rename(String),
+    /// Inserts text where the node starts
+    InsertBefore(String),
+    /// Replaces the first token of the node, e.g. a number or a keyword
+    ReplaceToken(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rule {
+    pub query: Query,
+    pub action: Action,
+}
+
+impl Rule {
+    pub fn new(query: &str, action: Action) -> Result<Self, QueryError> {
+        Ok(Rule {
+            query: Query::parse(query)?,
+            action,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CodemodError {
+    /// The source to rewrite doesn't parse
+    Parse(ParseError),
+    /// Two rules replace the same text, at the location of the second one
+    Conflict(Location),
+}
+
+impl fmt::Display for CodemodError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CodemodError::Parse(err) => write!(f, "{}", err),
+            CodemodError::Conflict(location) => {
+                write!(f, "conflicting rewrites at {}", location)
+            }
+        }
+    }
+}
+
+/// Replacement of the bytes `start..end` of the source.
+struct Edit {
+    start: usize,
+    end: usize,
+    text: String,
+    location: Location,
+}
+
+/// Byte range of each token of `source`, by the location where it starts.
+fn token_spans(source: &str) -> HashMap<Location, (usize, usize)> {
+    // A token ends where the character following it starts, so the last one
+    // is followed by a line break
+    let source = format!("{}\n", source);
+    // Offset of each character, by its location as counted by the lexer
+    let mut offsets = HashMap::new();
+    let mut location = Location::new(0, 0);
+    for (offset, c) in source.char_indices() {
+        if c == '\n' {
+            location.new_line();
+        } else {
+            location.go_right();
+        }
+        offsets.insert(location, offset);
+    }
+    make_tokenizer(&source)
+        .flatten()
+        .filter(|(_, token, _)| *token != Tok::EOF)
+        .filter_map(|(start, _, end)| Some((start, (*offsets.get(&start)?, *offsets.get(&end)?))))
+        .collect()
+}
+
+/// Rewrites `source` with `rules`, applied to the nodes of the source as parsed.
+pub fn apply(rules: &[Rule], source: &str) -> Result<String, CodemodError> {
+    let program = parser::parse_program(source).map_err(CodemodError::Parse)?;
+    let spans = token_spans(source);
+    let mut edits = vec![];
+    for rule in rules {
+        for node in rule.query.find(program_nodes(&program)) {
+            edits.extend(edit(&rule.action, &node, &spans));
+        }
+    }
+    edits.sort_by_key(|edit| (edit.start, edit.end));
+
+    let mut rewritten = String::new();
+    let mut copied = 0;
+    for edit in edits {
+        if edit.start < copied {
+            return Err(CodemodError::Conflict(edit.location));
+        }
+        rewritten.push_str(&source[copied..edit.start]);
+        rewritten.push_str(&edit.text);
+        copied = edit.end;
+    }
+    rewritten.push_str(&source[copied..]);
+    Ok(rewritten)
+}
+
+fn edit(action: &Action, node: &Node, spans: &HashMap<Location, (usize, usize)>) -> Option<Edit> {
+    let (location, text, replaces) = match action {
+        Action::Rename(name) => (name_expression(node)?.location, name, true),
+        Action::InsertBefore(text) => (node.location(), text, false),
+        Action::ReplaceToken(text) => (node.location(), text, true),
+    };
+    let (start, end) = *spans.get(&location)?;
+    Some(Edit {
+        start,
+        end: if replaces { end } else { start },
+        text: text.clone(),
+        location,
+    })
+}
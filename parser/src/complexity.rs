@@ -0,0 +1,305 @@
+//! Depth and size of ASTs.
+//!
+//! Embedders such as a web playground or a language server bound the resources
+//! spent on a program by checking its AST before the compiler walks it. The AST
+//! is measured without recursion, so that measuring can't overflow the stack.
+//! Dropping an AST recurses as deep as the AST though, so its depth is bounded
+//! before it's built, by the nesting depth of the tokens, see `config`.
+
+use crate::ast::{Expression, ExpressionType, Program, Statement, StatementType};
+use crate::config::{ParseConfig, ParseLimit};
+use crate::error::{ParseError, ParseErrorType};
+use crate::location::Location;
+
+/// Statement or expression of the AST.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Node<'a> {
+    Statement(&'a Statement),
+    Expression(&'a Expression),
+}
+
+impl<'a> Node<'a> {
+    pub fn location(&self) -> Location {
+        match self {
+            Node::Statement(statement) => statement.location,
+            Node::Expression(expression) => expression.location,
+        }
+    }
+
+    /// Nodes directly under this one, in order of appearance.
+    pub fn children(&self) -> Vec<Node<'a>> {
+        let statement = |statement: &'a Statement| Node::Statement(statement);
+        let expression = |expression: &'a Expression| Node::Expression(expression);
+        let mut children = vec![];
+        match self {
+            Node::Statement(node) => match &node.node {
+                StatementType::FunctionStatement {
+                    annotations,
+                    function_name,
+                    parameters,
+                    statement: body,
+                    returns,
+                    ..
+                } => {
+                    children.extend(
+                        annotations
+                            .iter()
+                            .flat_map(|a| &a.arguments)
+                            .map(expression),
+                    );
+                    children.push(expression(function_name));
+                    children.push(expression(parameters));
+                    children.extend(returns.as_deref().map(expression));
+                    children.extend(body.as_deref().map(statement));
+                }
+                StatementType::ContractStatement {
+                    annotations,
+                    contract_name,
+                    bases,
+                    members,
+                    ..
+                } => {
+                    children.extend(
+                        annotations
+                            .iter()
+                            .flat_map(|a| &a.arguments)
+                            .map(expression),
+                    );
+                    children.push(expression(contract_name));
+                    children.extend(bases.iter().map(expression));
+                    children.push(statement(members));
+                }
+                StatementType::EventStatement {
+                    event_name,
+                    parameters,
+                } => {
+                    children.push(expression(event_name));
+                    children.push(expression(parameters));
+                }
+                StatementType::UsingStatement { library, .. } => {
+                    children.push(expression(library));
+                }
+                StatementType::EnumStatement {
+                    enum_name,
+                    variants,
+                } => {
+                    children.push(expression(enum_name));
+                    children.extend(variants.iter().map(expression));
+                }
+                StatementType::StructStatement {
+                    struct_name,
+                    fields,
+                } => {
+                    children.push(expression(struct_name));
+                    children.extend(fields.iter().map(statement));
+                }
+                StatementType::ModuleStatement {
+                    module_name,
+                    members,
+                } => {
+                    children.push(expression(module_name));
+                    children.extend(members.iter().map(statement));
+                }
+                StatementType::InitializerStatement {
+                    variable, default, ..
+                } => {
+                    children.extend(variable.as_deref().map(expression));
+                    children.extend(default.as_deref().map(expression));
+                }
+                StatementType::CompoundStatement {
+                    statements,
+                    return_value,
+                } => {
+                    children.extend(statements.iter().map(statement));
+                    children.extend(return_value.as_deref().map(expression));
+                }
+                StatementType::MemberStatement { statements } => {
+                    children.extend(statements.iter().map(statement));
+                }
+                StatementType::UncheckedStatement { statement: body }
+                | StatementType::ConditionalStatement {
+                    statement: body, ..
+                } => {
+                    children.push(statement(body));
+                }
+                StatementType::DoWhileStatement {
+                    statement: body,
+                    condition,
+                } => {
+                    children.push(statement(body));
+                    children.push(expression(condition));
+                }
+                StatementType::ReturnStatement { ret } => {
+                    children.extend(ret.as_deref().map(expression));
+                }
+                StatementType::Expression { expression: value } => {
+                    children.push(expression(value));
+                }
+                StatementType::ImportStatement { .. } | StatementType::Error => {}
+            },
+            Node::Expression(node) => match &node.node {
+                ExpressionType::AssignExpression { left, right, .. }
+                | ExpressionType::BinaryExpression { left, right, .. } => {
+                    children.push(expression(left));
+                    children.push(expression(right));
+                }
+                ExpressionType::TernaryExpression {
+                    condition,
+                    expr1,
+                    expr2,
+                } => {
+                    children.push(expression(condition));
+                    children.push(expression(expr1));
+                    children.push(expression(expr2));
+                }
+                ExpressionType::FunctionCallExpression {
+                    function_name,
+                    arguments,
+                } => {
+                    children.push(expression(function_name));
+                    children.push(expression(arguments));
+                }
+                ExpressionType::MemberExpression {
+                    expression: base,
+                    member,
+                } => {
+                    children.push(expression(base));
+                    children.push(expression(member));
+                }
+                ExpressionType::PathExpression { module, member } => {
+                    children.push(expression(module));
+                    children.push(expression(member));
+                }
+                ExpressionType::IndexExpression {
+                    expression: base,
+                    index,
+                } => {
+                    children.push(expression(base));
+                    children.push(expression(index));
+                }
+                ExpressionType::IfExpression {
+                    condition,
+                    if_statement,
+                    else_statement,
+                } => {
+                    children.push(expression(condition));
+                    children.push(statement(if_statement));
+                    children.extend(else_statement.as_deref().map(statement));
+                }
+                ExpressionType::MatchExpression { value, arms } => {
+                    children.push(expression(value));
+                    for arm in arms {
+                        children.push(expression(&arm.pattern));
+                        children.push(statement(&arm.statement));
+                    }
+                }
+                ExpressionType::ForEachExpression {
+                    iterator,
+                    vector,
+                    statement: body,
+                    else_statement,
+                } => {
+                    children.push(expression(iterator));
+                    children.push(expression(vector));
+                    children.push(statement(body));
+                    children.extend(else_statement.as_deref().map(statement));
+                }
+                ExpressionType::UnaryExpression {
+                    expression: operand,
+                    ..
+                } => children.push(expression(operand)),
+                ExpressionType::Parameters { parameters } => {
+                    children.extend(parameters.iter().map(statement));
+                }
+                ExpressionType::Arguments { arguments } => {
+                    children.extend(arguments.iter().map(expression));
+                }
+                ExpressionType::Tuple { items } => {
+                    children.extend(items.iter().flatten().map(expression));
+                }
+                ExpressionType::StructLiteral {
+                    struct_name,
+                    fields,
+                } => {
+                    children.push(expression(struct_name));
+                    for field in fields {
+                        children.push(expression(&field.name));
+                        children.push(expression(&field.value));
+                    }
+                }
+                ExpressionType::TypeList { .. }
+                | ExpressionType::Number { .. }
+                | ExpressionType::Identifier { .. }
+                | ExpressionType::StringLiteral { .. }
+                | ExpressionType::Error => {}
+            },
+        }
+        children
+    }
+}
+
+/// Top-level nodes of `program`.
+pub fn program_nodes(program: &Program) -> Vec<Node<'_>> {
+    match program {
+        Program::GlobalStatements(statements) => statements.iter().map(Node::Statement).collect(),
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Complexity {
+    /// Nodes on the longest path from a root, the roots included
+    pub depth: usize,
+    pub nodes: usize,
+}
+
+/// Measures the trees rooted at `roots`, stopping at the first node for which
+/// `exceeds` holds.
+fn walk(
+    roots: Vec<Node>,
+    exceeds: impl Fn(&Complexity) -> Option<ParseLimit>,
+) -> Result<Complexity, (ParseLimit, Location)> {
+    let mut complexity = Complexity::default();
+    let mut pending: Vec<(Node, usize)> = roots.into_iter().rev().map(|root| (root, 1)).collect();
+    while let Some((node, depth)) = pending.pop() {
+        complexity.nodes += 1;
+        complexity.depth = complexity.depth.max(depth);
+        if let Some(limit) = exceeds(&complexity) {
+            return Err((limit, node.location()));
+        }
+        pending.extend(
+            node.children()
+                .into_iter()
+                .rev()
+                .map(|child| (child, depth + 1)),
+        );
+    }
+    Ok(complexity)
+}
+
+/// Depth and number of nodes of the trees rooted at `roots`.
+pub fn measure(roots: Vec<Node>) -> Complexity {
+    walk(roots, |_| None).unwrap()
+}
+
+/// Rejects trees deeper or larger than allowed by `config`, at the first node over the limit.
+pub fn check_complexity(roots: Vec<Node>, config: &ParseConfig) -> Result<Complexity, ParseError> {
+    let exceeds = |complexity: &Complexity| {
+        if complexity.depth > config.max_ast_depth {
+            Some(ParseLimit::AstDepth)
+        } else if complexity.nodes > config.max_ast_nodes {
+            Some(ParseLimit::AstNodes)
+        } else {
+            None
+        }
+    };
+    walk(roots, exceeds).map_err(|(limit, location)| {
+        let max = match limit {
+            ParseLimit::AstDepth => config.max_ast_depth,
+            _ => config.max_ast_nodes,
+        };
+        ParseError {
+            error: ParseErrorType::ProgramTooComplex { limit, max },
+            location,
+        }
+    })
+}
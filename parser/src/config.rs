@@ -0,0 +1,178 @@
+//! Limits on the sources accepted by the parser.
+//!
+//! The parser may be exposed to untrusted sources, e.g. in a web playground.
+//! The limits of the source are checked on the tokens, before the parser builds
+//! anything, so that adversarial inputs are rejected with a diagnostic. The
+//! limits of the AST are checked once it is built, see `complexity`.
+//!
+//! The nesting depth bounds the depth of the AST built by the parser: besides
+//! brackets, every operator may nest the expression it belongs to one level
+//! deeper, e.g. `a = b = c` or `a + b + c`. The operators of a group of brackets
+//! count until its next `,` or `;`.
+
+use crate::error::{LexicalError, LexicalErrorType};
+use crate::lexer::LexResult;
+use crate::location::Location;
+use crate::token::Tok;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseConfig {
+    /// Nesting depth of parentheses, brackets, braces and operators
+    pub max_expression_depth: usize,
+    /// Size of the source in bytes
+    pub max_source_size: usize,
+    pub max_identifiers: usize,
+    /// Nodes on the longest path from the root of the AST
+    pub max_ast_depth: usize,
+    /// Statements and expressions in the AST
+    pub max_ast_nodes: usize,
+}
+
+impl Default for ParseConfig {
+    fn default() -> Self {
+        ParseConfig {
+            max_expression_depth: 256,
+            max_source_size: 1 << 20,
+            max_identifiers: 100_000,
+            max_ast_depth: 1_000,
+            max_ast_nodes: 1_000_000,
+        }
+    }
+}
+
+/// Limit of `ParseConfig` exceeded by a source.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ParseLimit {
+    ExpressionDepth,
+    SourceSize,
+    Identifiers,
+    AstDepth,
+    AstNodes,
+}
+
+impl fmt::Display for ParseLimit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseLimit::ExpressionDepth => write!(f, "nesting depth"),
+            ParseLimit::SourceSize => write!(f, "source size in bytes"),
+            ParseLimit::Identifiers => write!(f, "number of identifiers"),
+            ParseLimit::AstDepth => write!(f, "depth of the AST"),
+            ParseLimit::AstNodes => write!(f, "number of nodes of the AST"),
+        }
+    }
+}
+
+fn limit_error(limit: ParseLimit, max: usize, location: Location) -> LexicalError {
+    LexicalError {
+        error: LexicalErrorType::LimitExceeded { limit, max },
+        location,
+    }
+}
+
+/// Checks the size of `source` before it is tokenized.
+pub(crate) fn check_source_size(source: &str, config: &ParseConfig) -> Result<(), LexicalError> {
+    if source.len() > config.max_source_size {
+        let max = config.max_source_size;
+        return Err(limit_error(
+            ParseLimit::SourceSize,
+            max,
+            Location::default(),
+        ));
+    }
+    Ok(())
+}
+
+/// Whether `tok` combines expressions, nesting them one level deeper in the AST.
+fn is_operator(tok: &Tok) -> bool {
+    matches!(
+        tok,
+        Tok::Mul
+            | Tok::Div
+            | Tok::Mod
+            | Tok::Pow
+            | Tok::LShift
+            | Tok::RShift
+            | Tok::Plus
+            | Tok::Minus
+            | Tok::Not
+            | Tok::PlusPlus
+            | Tok::MinusMinus
+            | Tok::Assign
+            | Tok::BitAndAssign
+            | Tok::BitXorAssign
+            | Tok::BitOrAssign
+            | Tok::LShiftAssign
+            | Tok::RShiftAssign
+            | Tok::AddAssign
+            | Tok::SubAssign
+            | Tok::MulAssign
+            | Tok::DivAssign
+            | Tok::ModAssign
+            | Tok::Lt
+            | Tok::Le
+            | Tok::Gt
+            | Tok::Ge
+            | Tok::Eq
+            | Tok::NotEq
+            | Tok::And
+            | Tok::Or
+            | Tok::BitAnd
+            | Tok::BitXor
+            | Tok::BitOr
+            | Tok::Question
+            | Tok::Dot
+            | Tok::ColonColon
+    )
+}
+
+/// Passes `tokens` through until one exceeds a limit of `config`, which gives an error instead.
+pub(crate) fn limit_tokens<'a>(
+    tokens: impl Iterator<Item = LexResult> + 'a,
+    config: &'a ParseConfig,
+) -> impl Iterator<Item = LexResult> + 'a {
+    let mut depth: usize = 0;
+    // Operators of every open group of brackets, the outermost first
+    let mut operators: Vec<usize> = vec![0];
+    let mut identifiers = 0;
+    tokens.map(move |token| {
+        let (location, tok, _) = token.as_ref().map_err(Clone::clone)?;
+        let exceeded = |depth: usize| {
+            let max = config.max_expression_depth;
+            if depth > max {
+                Err(limit_error(ParseLimit::ExpressionDepth, max, *location))
+            } else {
+                Ok(())
+            }
+        };
+        match tok {
+            Tok::LPar | Tok::LSqb | Tok::LBrace => {
+                depth += 1;
+                operators.push(0);
+                exceeded(depth)?;
+            }
+            Tok::RPar | Tok::RSqb | Tok::RBrace if operators.len() > 1 => {
+                depth -= 1 + operators.pop().unwrap();
+            }
+            Tok::Comma | Tok::Semi => {
+                let group = operators.last_mut().unwrap();
+                depth -= *group;
+                *group = 0;
+            }
+            tok if is_operator(tok) => {
+                depth += 1;
+                *operators.last_mut().unwrap() += 1;
+                exceeded(depth)?;
+            }
+            Tok::Identifier { .. } => {
+                identifiers += 1;
+                if identifiers > config.max_identifiers {
+                    let max = config.max_identifiers;
+                    return Err(limit_error(ParseLimit::Identifiers, max, *location));
+                }
+            }
+            _ => {}
+        }
+        token
+    })
+}
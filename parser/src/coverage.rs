@@ -0,0 +1,547 @@
+//! Records which syntax of the grammar is exercised by a corpus of programs.
+//!
+//! Coverage is tracked per AST variant, operator, type, location specifier, visibility
+//! and mutability, e.g. `Expression::TernaryExpression` or `Operator::LShiftAssign`.
+
+use crate::ast;
+use crate::error::ParseError;
+use crate::parser;
+use std::collections::BTreeMap;
+use std::fmt;
+
+const STATEMENTS: &[&str] = &[
+    "FunctionStatement",
+    "ContractStatement",
+    "EventStatement",
+    "UsingStatement",
+    "EnumStatement",
+    "StructStatement",
+    "ModuleStatement",
+    "ImportStatement",
+    "ConditionalStatement",
+    "InitializerStatement",
+    "CompoundStatement",
+    "MemberStatement",
+    "UncheckedStatement",
+    "DoWhileStatement",
+    "ReturnStatement",
+    "Expression",
+    "Error",
+];
+
+const EXPRESSIONS: &[&str] = &[
+    "AssignExpression",
+    "TernaryExpression",
+    "BinaryExpression",
+    "FunctionCallExpression",
+    "MemberExpression",
+    "PathExpression",
+    "IndexExpression",
+    "IfExpression",
+    "ForEachExpression",
+    "MatchExpression",
+    "UnaryExpression",
+    "Parameters",
+    "Arguments",
+    "Tuple",
+    "TypeList",
+    "Number",
+    "Identifier",
+    "StringLiteral",
+    "StructLiteral",
+    "Error",
+];
+
+const OPERATORS: &[&str] = &[
+    "Add",
+    "Sub",
+    "Mul",
+    "Div",
+    "Mod",
+    "Pow",
+    "Plus",
+    "Minus",
+    "Not",
+    "PrefixPlusPlus",
+    "PrefixMinusMinus",
+    "PostfixPlusPlus",
+    "PostfixMinusMinus",
+    "Assign",
+    "BitAndAssign",
+    "BitXorAssign",
+    "BitOrAssign",
+    "LShiftAssign",
+    "RShiftAssign",
+    "AddAssign",
+    "SubAssign",
+    "MulAssign",
+    "DivAssign",
+    "ModAssign",
+    "Lt",
+    "Le",
+    "Gt",
+    "Ge",
+    "Eq",
+    "NotEq",
+    "And",
+    "Or",
+    "BitAnd",
+    "BitXor",
+    "BitOr",
+    "LShift",
+    "RShift",
+];
+
+const TYPES: &[&str] = &[
+    "Uint256",
+    "Int256",
+    "Uint",
+    "Int",
+    "Bytes32",
+    "Bool",
+    "Field",
+    "Bytes",
+    "String",
+    "Address",
+    "Array",
+    "FixedArray",
+    "UserDefined",
+    "Mapping",
+];
+
+const SPECIFIERS: &[&str] = &["Memory", "Storage", "Transient"];
+
+const VISIBILITIES: &[&str] = &["Public", "Private", "Internal", "External"];
+
+const MUTABILITIES: &[&str] = &["Pure", "View", "Payable"];
+
+const CONTRACT_KINDS: &[&str] = &["Contract", "Interface", "Library"];
+
+fn all_items() -> Vec<String> {
+    let groups: &[(&str, &[&str])] = &[
+        ("Statement", STATEMENTS),
+        ("Expression", EXPRESSIONS),
+        ("Operator", OPERATORS),
+        ("Type", TYPES),
+        ("Specifier", SPECIFIERS),
+        ("Visibility", VISIBILITIES),
+        ("Mutability", MUTABILITIES),
+        ("ContractKind", CONTRACT_KINDS),
+    ];
+    groups
+        .iter()
+        .flat_map(|(group, names)| names.iter().map(move |name| format!("{}::{}", group, name)))
+        .collect()
+}
+
+#[derive(Debug, Default)]
+pub struct GrammarCoverage {
+    hits: BTreeMap<String, usize>,
+}
+
+impl GrammarCoverage {
+    pub fn new() -> Self {
+        GrammarCoverage::default()
+    }
+
+    /// Parses `source` as a program and records its syntax.
+    pub fn record_source(&mut self, source: &str) -> Result<(), ParseError> {
+        let program = parser::parse_program(source)?;
+        self.record_program(&program);
+        Ok(())
+    }
+
+    pub fn record_program(&mut self, program: &ast::Program) {
+        match program {
+            ast::Program::GlobalStatements(stmts) => {
+                for stmt in stmts {
+                    self.record_statement(stmt);
+                }
+            }
+        }
+    }
+
+    pub fn record_statement(&mut self, stmt: &ast::Statement) {
+        match &stmt.node {
+            ast::StatementType::FunctionStatement {
+                annotations,
+                function_name,
+                parameters,
+                statement,
+                returns,
+                visibility,
+                mutability,
+                ..
+            } => {
+                self.hit("Statement::FunctionStatement");
+                if let Some(visibility) = visibility {
+                    self.hit(&format!("Visibility::{:?}", visibility));
+                }
+                if let Some(mutability) = mutability {
+                    self.hit(&format!("Mutability::{:?}", mutability));
+                }
+                for argument in annotations.iter().flat_map(|a| &a.arguments) {
+                    self.record_expression(argument);
+                }
+                self.record_expression(function_name);
+                self.record_expression(parameters);
+                if let Some(statement) = statement {
+                    self.record_statement(statement);
+                }
+                if let Some(returns) = returns {
+                    self.record_expression(returns);
+                }
+            }
+            ast::StatementType::ContractStatement {
+                annotations,
+                kind,
+                contract_name,
+                bases,
+                members,
+                ..
+            } => {
+                self.hit("Statement::ContractStatement");
+                self.hit(&format!("ContractKind::{:?}", kind));
+                for argument in annotations.iter().flat_map(|a| &a.arguments) {
+                    self.record_expression(argument);
+                }
+                self.record_expression(contract_name);
+                for base in bases {
+                    self.record_expression(base);
+                }
+                self.record_statement(members);
+            }
+            ast::StatementType::EventStatement {
+                event_name,
+                parameters,
+            } => {
+                self.hit("Statement::EventStatement");
+                self.record_expression(event_name);
+                self.record_expression(parameters);
+            }
+            ast::StatementType::UsingStatement { library, target } => {
+                self.hit("Statement::UsingStatement");
+                self.record_expression(library);
+                self.record_type(target);
+            }
+            ast::StatementType::EnumStatement {
+                enum_name,
+                variants,
+            } => {
+                self.hit("Statement::EnumStatement");
+                self.record_expression(enum_name);
+                for variant in variants {
+                    self.record_expression(variant);
+                }
+            }
+            ast::StatementType::StructStatement {
+                struct_name,
+                fields,
+            } => {
+                self.hit("Statement::StructStatement");
+                self.record_expression(struct_name);
+                for field in fields {
+                    self.record_statement(field);
+                }
+            }
+            ast::StatementType::ModuleStatement {
+                module_name,
+                members,
+            } => {
+                self.hit("Statement::ModuleStatement");
+                self.record_expression(module_name);
+                for member in members {
+                    self.record_statement(member);
+                }
+            }
+            ast::StatementType::InitializerStatement {
+                variable_type,
+                visibility,
+                data_location,
+                variable,
+                default,
+                ..
+            } => {
+                self.hit("Statement::InitializerStatement");
+                self.record_type(variable_type);
+                if let Some(visibility) = visibility {
+                    self.hit(&format!("Visibility::{:?}", visibility));
+                }
+                if let Some(specifier) = data_location {
+                    self.hit(&format!("Specifier::{:?}", specifier));
+                }
+                if let Some(variable) = variable {
+                    self.record_expression(variable);
+                }
+                if let Some(default) = default {
+                    self.record_expression(default);
+                }
+            }
+            ast::StatementType::CompoundStatement {
+                statements,
+                return_value,
+            } => {
+                self.hit("Statement::CompoundStatement");
+                for stmt in statements {
+                    self.record_statement(stmt);
+                }
+                if let Some(value) = return_value {
+                    self.record_expression(value);
+                }
+            }
+            ast::StatementType::MemberStatement { statements } => {
+                self.hit("Statement::MemberStatement");
+                for stmt in statements {
+                    self.record_statement(stmt);
+                }
+            }
+            ast::StatementType::ConditionalStatement { statement, .. } => {
+                self.hit("Statement::ConditionalStatement");
+                self.record_statement(statement);
+            }
+            ast::StatementType::UncheckedStatement { statement } => {
+                self.hit("Statement::UncheckedStatement");
+                self.record_statement(statement);
+            }
+            ast::StatementType::DoWhileStatement {
+                statement,
+                condition,
+            } => {
+                self.hit("Statement::DoWhileStatement");
+                self.record_statement(statement);
+                self.record_expression(condition);
+            }
+            ast::StatementType::ReturnStatement { ret } => {
+                self.hit("Statement::ReturnStatement");
+                if let Some(ret) = ret {
+                    self.record_expression(ret);
+                }
+            }
+            ast::StatementType::Expression { expression } => {
+                self.hit("Statement::Expression");
+                self.record_expression(expression);
+            }
+            ast::StatementType::ImportStatement { .. } => self.hit("Statement::ImportStatement"),
+            ast::StatementType::Error => self.hit("Statement::Error"),
+        }
+    }
+
+    pub fn record_expression(&mut self, expr: &ast::Expression) {
+        match &expr.node {
+            ast::ExpressionType::AssignExpression {
+                left,
+                operator,
+                right,
+            } => {
+                self.hit("Expression::AssignExpression");
+                self.record_operator(operator);
+                self.record_expression(left);
+                self.record_expression(right);
+            }
+            ast::ExpressionType::BinaryExpression {
+                left,
+                operator,
+                right,
+            } => {
+                self.hit("Expression::BinaryExpression");
+                self.record_operator(operator);
+                self.record_expression(left);
+                self.record_expression(right);
+            }
+            ast::ExpressionType::TernaryExpression {
+                condition,
+                expr1,
+                expr2,
+            } => {
+                self.hit("Expression::TernaryExpression");
+                self.record_expression(condition);
+                self.record_expression(expr1);
+                self.record_expression(expr2);
+            }
+            ast::ExpressionType::FunctionCallExpression {
+                function_name,
+                arguments,
+            } => {
+                self.hit("Expression::FunctionCallExpression");
+                self.record_expression(function_name);
+                self.record_expression(arguments);
+            }
+            ast::ExpressionType::MemberExpression { expression, member } => {
+                self.hit("Expression::MemberExpression");
+                self.record_expression(expression);
+                self.record_expression(member);
+            }
+            ast::ExpressionType::PathExpression { module, member } => {
+                self.hit("Expression::PathExpression");
+                self.record_expression(module);
+                self.record_expression(member);
+            }
+            ast::ExpressionType::IndexExpression { expression, index } => {
+                self.hit("Expression::IndexExpression");
+                self.record_expression(expression);
+                self.record_expression(index);
+            }
+            ast::ExpressionType::IfExpression {
+                condition,
+                if_statement,
+                else_statement,
+            } => {
+                self.hit("Expression::IfExpression");
+                self.record_expression(condition);
+                self.record_statement(if_statement);
+                if let Some(stmt) = else_statement {
+                    self.record_statement(stmt);
+                }
+            }
+            ast::ExpressionType::MatchExpression { value, arms } => {
+                self.hit("Expression::MatchExpression");
+                self.record_expression(value);
+                for arm in arms {
+                    self.record_expression(&arm.pattern);
+                    self.record_statement(&arm.statement);
+                }
+            }
+            ast::ExpressionType::ForEachExpression {
+                iterator,
+                vector,
+                statement,
+                else_statement,
+            } => {
+                self.hit("Expression::ForEachExpression");
+                self.record_expression(iterator);
+                self.record_expression(vector);
+                self.record_statement(statement);
+                if let Some(stmt) = else_statement {
+                    self.record_statement(stmt);
+                }
+            }
+            ast::ExpressionType::UnaryExpression {
+                operator,
+                expression,
+            } => {
+                self.hit("Expression::UnaryExpression");
+                self.record_operator(operator);
+                self.record_expression(expression);
+            }
+            ast::ExpressionType::Parameters { parameters } => {
+                self.hit("Expression::Parameters");
+                for param in parameters {
+                    self.record_statement(param);
+                }
+            }
+            ast::ExpressionType::Arguments { arguments } => {
+                self.hit("Expression::Arguments");
+                for arg in arguments {
+                    self.record_expression(arg);
+                }
+            }
+            ast::ExpressionType::Tuple { items } => {
+                self.hit("Expression::Tuple");
+                for item in items.iter().flatten() {
+                    self.record_expression(item);
+                }
+            }
+            ast::ExpressionType::TypeList { types } => {
+                self.hit("Expression::TypeList");
+                for typ in types {
+                    self.record_type(typ);
+                }
+            }
+            ast::ExpressionType::Number { .. } => self.hit("Expression::Number"),
+            ast::ExpressionType::Identifier { .. } => self.hit("Expression::Identifier"),
+            ast::ExpressionType::StringLiteral { .. } => self.hit("Expression::StringLiteral"),
+            ast::ExpressionType::StructLiteral {
+                struct_name,
+                fields,
+            } => {
+                self.hit("Expression::StructLiteral");
+                self.record_expression(struct_name);
+                for field in fields {
+                    self.record_expression(&field.name);
+                    self.record_expression(&field.value);
+                }
+            }
+            ast::ExpressionType::Error => self.hit("Expression::Error"),
+        }
+    }
+
+    fn record_operator(&mut self, operator: &ast::Operator) {
+        self.hit(&format!("Operator::{:?}", operator));
+    }
+
+    fn record_type(&mut self, typ: &ast::Type) {
+        match typ {
+            ast::Type::Array(element) => {
+                self.hit("Type::Array");
+                self.record_type(element);
+            }
+            ast::Type::FixedArray(element, size) => {
+                self.hit("Type::FixedArray");
+                self.record_type(element);
+                self.record_expression(size);
+            }
+            ast::Type::Uint(_) => self.hit("Type::Uint"),
+            ast::Type::Int(_) => self.hit("Type::Int"),
+            ast::Type::UserDefined(_) => self.hit("Type::UserDefined"),
+            ast::Type::Mapping(key, value) => {
+                self.hit("Type::Mapping");
+                self.record_type(key);
+                self.record_type(value);
+            }
+            _ => self.hit(&format!("Type::{:?}", typ)),
+        }
+    }
+
+    fn hit(&mut self, item: &str) {
+        *self.hits.entry(item.to_string()).or_insert(0) += 1;
+    }
+
+    pub fn report(&self) -> CoverageReport {
+        let mut covered = vec![];
+        let mut uncovered = vec![];
+        for item in all_items() {
+            match self.hits.get(&item) {
+                Some(count) => covered.push((item, *count)),
+                None => uncovered.push(item),
+            }
+        }
+        CoverageReport { covered, uncovered }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct CoverageReport {
+    /// Exercised items with the number of times they were seen.
+    pub covered: Vec<(String, usize)>,
+    pub uncovered: Vec<String>,
+}
+
+impl CoverageReport {
+    /// Ratio of exercised items, between 0 and 1.
+    pub fn ratio(&self) -> f64 {
+        let total = self.covered.len() + self.uncovered.len();
+        self.covered.len() as f64 / total as f64
+    }
+}
+
+impl fmt::Display for CoverageReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "grammar coverage: {}/{} ({:.1}%)",
+            self.covered.len(),
+            self.covered.len() + self.uncovered.len(),
+            self.ratio() * 100.0
+        )?;
+        for (item, count) in &self.covered {
+            writeln!(f, "  {:<32} {}", item, count)?;
+        }
+        if !self.uncovered.is_empty() {
+            writeln!(f, "not covered:")?;
+            for item in &self.uncovered {
+                writeln!(f, "  {}", item)?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,265 @@
+//! API documentation of contracts, from the doc comments of their declarations.
+//!
+//! Each contract of a program gives a `ContractDoc` with its functions and state
+//! variables, written as JSON with sorted keys for tools or as Markdown for
+//! readers. Private state variables aren't part of the API and are left out,
+//! undocumented declarations are kept with a `null` doc.
+
+use crate::ast::{Expression, ExpressionType, Located, Program, Statement, StatementType};
+use crate::snapshot::type_name;
+use std::fmt::Write;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParameterDoc {
+    /// Name of the parameter, `None` for unnamed return values
+    pub name: Option<String>,
+    pub parameter_type: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FunctionDoc {
+    pub name: String,
+    pub doc: Option<String>,
+    pub parameters: Vec<ParameterDoc>,
+    pub returns: Vec<ParameterDoc>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct VariableDoc {
+    pub name: String,
+    pub variable_type: String,
+    pub doc: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContractDoc {
+    pub name: String,
+    pub doc: Option<String>,
+    pub functions: Vec<FunctionDoc>,
+    pub variables: Vec<VariableDoc>,
+}
+
+/// Documentation of the contracts of `program`, in order of declaration.
+pub fn contract_docs(program: &Program) -> Vec<ContractDoc> {
+    let Program::GlobalStatements(statements) = program;
+    declarations(statements)
+        .filter_map(|statement| match &statement.node {
+            StatementType::ContractStatement {
+                contract_name,
+                members,
+                doc,
+                ..
+            } => Some(contract_doc(contract_name, members, doc)),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Declarations of `statements`, including the ones compiled under a condition.
+fn declarations(statements: &[Statement]) -> impl Iterator<Item = &Statement> {
+    statements.iter().map(|mut statement| {
+        while let StatementType::ConditionalStatement {
+            statement: inner, ..
+        } = &statement.node
+        {
+            statement = inner;
+        }
+        statement
+    })
+}
+
+fn contract_doc(
+    name: &Expression,
+    members: &Statement,
+    doc: &Option<Located<String>>,
+) -> ContractDoc {
+    let mut contract = ContractDoc {
+        name: name.node.identifier_name().unwrap_or_default(),
+        doc: doc.as_ref().map(|doc| doc.node.clone()),
+        functions: vec![],
+        variables: vec![],
+    };
+    let members = match &members.node {
+        StatementType::MemberStatement { statements } => statements.as_slice(),
+        _ => &[],
+    };
+    for member in declarations(members) {
+        match &member.node {
+            StatementType::FunctionStatement {
+                function_name,
+                parameters,
+                returns,
+                doc,
+                ..
+            } => contract.functions.push(FunctionDoc {
+                name: function_name.node.identifier_name().unwrap_or_default(),
+                doc: doc.as_ref().map(|doc| doc.node.clone()),
+                parameters: parameter_docs(parameters),
+                returns: returns.as_deref().map(parameter_docs).unwrap_or_default(),
+            }),
+            StatementType::InitializerStatement {
+                variable_type,
+                is_private: false,
+                variable: Some(variable),
+                doc,
+                ..
+            } => contract.variables.push(VariableDoc {
+                name: variable.node.identifier_name().unwrap_or_default(),
+                variable_type: type_name(variable_type),
+                doc: doc.as_ref().map(|doc| doc.node.clone()),
+            }),
+            _ => {}
+        }
+    }
+    contract
+}
+
+fn parameter_docs(parameters: &Expression) -> Vec<ParameterDoc> {
+    let parameters = match &parameters.node {
+        ExpressionType::Parameters { parameters } => parameters,
+        _ => return vec![],
+    };
+    parameters
+        .iter()
+        .filter_map(|parameter| match &parameter.node {
+            StatementType::InitializerStatement {
+                variable_type,
+                variable,
+                ..
+            } => Some(ParameterDoc {
+                name: variable
+                    .as_ref()
+                    .and_then(|variable| variable.node.identifier_name()),
+                parameter_type: type_name(variable_type),
+            }),
+            _ => None,
+        })
+        .collect()
+}
+
+fn json_string(value: &str) -> String {
+    let mut json = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            c if (c as u32) < 0x20 => write!(json, "\\u{:04x}", c as u32).unwrap(),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+fn json_option(value: &Option<String>) -> String {
+    value
+        .as_deref()
+        .map_or_else(|| String::from("null"), json_string)
+}
+
+fn json_parameters(parameters: &[ParameterDoc]) -> String {
+    let parameters: Vec<String> = parameters
+        .iter()
+        .map(|parameter| {
+            format!(
+                "{{\"name\":{},\"type\":{}}}",
+                json_option(&parameter.name),
+                json_string(&parameter.parameter_type)
+            )
+        })
+        .collect();
+    format!("[{}]", parameters.join(","))
+}
+
+/// Parameters as written in a signature, e.g. `(address to, uint256 amount)`.
+fn signature(parameters: &[ParameterDoc]) -> String {
+    let parameters: Vec<String> = parameters
+        .iter()
+        .map(|parameter| match &parameter.name {
+            Some(name) => format!("{} {}", parameter.parameter_type, name),
+            None => parameter.parameter_type.clone(),
+        })
+        .collect();
+    format!("({})", parameters.join(", "))
+}
+
+impl FunctionDoc {
+    /// Signature of the function, e.g. `transfer(address to) returns (bool)`.
+    pub fn signature(&self) -> String {
+        let mut signature = format!("{}{}", self.name, signature(&self.parameters));
+        if !self.returns.is_empty() {
+            write!(signature, " returns {}", self::signature(&self.returns)).unwrap();
+        }
+        signature
+    }
+}
+
+impl ContractDoc {
+    /// Compact JSON with sorted keys.
+    pub fn to_json(&self) -> String {
+        let functions: Vec<String> = self
+            .functions
+            .iter()
+            .map(|function| {
+                format!(
+                    "{{\"doc\":{},\"name\":{},\"parameters\":{},\"returns\":{}}}",
+                    json_option(&function.doc),
+                    json_string(&function.name),
+                    json_parameters(&function.parameters),
+                    json_parameters(&function.returns)
+                )
+            })
+            .collect();
+        let variables: Vec<String> = self
+            .variables
+            .iter()
+            .map(|variable| {
+                format!(
+                    "{{\"doc\":{},\"name\":{},\"type\":{}}}",
+                    json_option(&variable.doc),
+                    json_string(&variable.name),
+                    json_string(&variable.variable_type)
+                )
+            })
+            .collect();
+        format!(
+            "{{\"doc\":{},\"functions\":[{}],\"name\":{},\"variables\":[{}]}}",
+            json_option(&self.doc),
+            functions.join(","),
+            json_string(&self.name),
+            variables.join(",")
+        )
+    }
+
+    /// Page with a section per function and state variable, which are left out if there are none.
+    pub fn to_markdown(&self) -> String {
+        let mut markdown = format!("# {}\n", self.name);
+        let paragraph = |markdown: &mut String, doc: &Option<String>| {
+            if let Some(doc) = doc {
+                write!(markdown, "\n{}\n", doc).unwrap();
+            }
+        };
+        paragraph(&mut markdown, &self.doc);
+        if !self.functions.is_empty() {
+            markdown.push_str("\n## Functions\n");
+            for function in &self.functions {
+                write!(markdown, "\n### `{}`\n", function.signature()).unwrap();
+                paragraph(&mut markdown, &function.doc);
+            }
+        }
+        if !self.variables.is_empty() {
+            markdown.push_str("\n## State variables\n");
+            for variable in &self.variables {
+                write!(
+                    markdown,
+                    "\n### `{} {}`\n",
+                    variable.variable_type, variable.name
+                )
+                .unwrap();
+                paragraph(&mut markdown, &variable.doc);
+            }
+        }
+        markdown
+    }
+}
@@ -1,23 +1,24 @@
 use lalrpop_util::ParseError as LalrpopError;
 
+use crate::config::ParseLimit;
 use crate::location::Location;
 use crate::token::Tok;
 use std::error::Error;
 use std::fmt;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct ParseError {
     pub error: ParseErrorType,
     pub location: Location,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct LexicalError {
     pub error: LexicalErrorType,
     pub location: Location,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum ParseErrorType {
     /// Parser encountered an unexpected end of input
     EOF,
@@ -29,11 +30,26 @@ pub enum ParseErrorType {
     UnrecognizedToken(Tok, Option<String>),
     /// Maps to `User` type from `lalrpop-util`
     Lexical(LexicalErrorType),
+    /// AST exceeding a limit of `ParseConfig` once parsed
+    ProgramTooComplex { limit: ParseLimit, max: usize },
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum LexicalErrorType {
-    UnrecognizedToken { tok: char },
+    UnrecognizedToken {
+        tok: char,
+    },
+    /// Escape sequence in a string literal which is not supported, e.g. `\q`
+    InvalidEscape(String),
+    /// String literal which is not closed before the end of line or file
+    UnterminatedString,
+    /// Block comment which is not closed before the end of file, at its opening `/*`
+    UnterminatedComment,
+    /// Source exceeding a limit of `ParseConfig`
+    LimitExceeded {
+        limit: ParseLimit,
+        max: usize,
+    },
     OtherError(String),
 }
 
@@ -93,6 +109,10 @@ impl fmt::Display for ParseErrorType {
         match self {
             ParseErrorType::InvalidToken => write!(f, "Got invalid token"),
             ParseErrorType::UnrecognizedToken(_tok, _opts) => write!(f, "Got unexpected token"),
+            ParseErrorType::Lexical(LexicalErrorType::LimitExceeded { limit, max })
+            | ParseErrorType::ProgramTooComplex { limit, max } => {
+                write!(f, "{} exceeds the limit of {}", limit, max)
+            }
             _ => write!(f, "Got parser Error"),
         }
     }
@@ -0,0 +1,281 @@
+//! Generator of random well-formed zoker programs.
+//!
+//! Generated programs only use variables and functions declared before them,
+//! so they are accepted by the parser and by the symbol table builder.
+//! The same seed always generates the same program.
+
+const BINARY_OPERATORS: &[&str] = &["+", "-", "*", "/", "%", "&", "|", "^", "<<", ">>"];
+/// Operators between literals which can't give a negative or out of range value
+const CONSTANT_OPERATORS: &[&str] = &["+", "*", "&", "|", "^"];
+const COMPARISON_OPERATORS: &[&str] = &["<", "<=", ">", ">=", "==", "!="];
+const ASSIGN_OPERATORS: &[&str] = &["=", "+=", "-=", "*=", "&=", "|="];
+
+/// Shape of generated programs. Every count is an upper bound.
+#[derive(Debug, Clone)]
+pub struct GeneratorConfig {
+    pub seed: u64,
+    pub contracts: usize,
+    pub storage_variables: usize,
+    pub functions: usize,
+    pub parameters: usize,
+    pub statements: usize,
+    pub expression_depth: usize,
+    pub block_depth: usize,
+}
+
+impl Default for GeneratorConfig {
+    fn default() -> Self {
+        GeneratorConfig {
+            seed: 0,
+            contracts: 2,
+            storage_variables: 3,
+            functions: 3,
+            parameters: 3,
+            statements: 5,
+            expression_depth: 3,
+            block_depth: 2,
+        }
+    }
+}
+
+/// xorshift64* generator, good enough for shaping programs.
+struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    fn new(seed: u64) -> Self {
+        // splitmix64 step, so that close seeds give unrelated states
+        let mut state = seed.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        state = (state ^ (state >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        state = (state ^ (state >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        state ^= state >> 31;
+        Rng {
+            // xorshift never leaves the zero state
+            state: state.max(1),
+        }
+    }
+
+    fn next(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    /// Random number in `0..=max`.
+    fn up_to(&mut self, max: usize) -> usize {
+        (self.next() % (max as u64 + 1)) as usize
+    }
+
+    fn chance(&mut self, percent: u64) -> bool {
+        self.next() % 100 < percent
+    }
+
+    fn pick<'a>(&mut self, items: &'a [&'a str]) -> &'a str {
+        items[self.up_to(items.len() - 1)]
+    }
+}
+
+pub struct Generator {
+    config: GeneratorConfig,
+    rng: Rng,
+    /// Variables visible from the current position, one list per scope.
+    scopes: Vec<Vec<String>>,
+    /// Functions of the current contract with their arity.
+    functions: Vec<(String, usize)>,
+    names: usize,
+}
+
+impl Generator {
+    pub fn new(config: GeneratorConfig) -> Self {
+        let rng = Rng::new(config.seed);
+        Generator {
+            config,
+            rng,
+            scopes: vec![],
+            functions: vec![],
+            names: 0,
+        }
+    }
+
+    /// Generates the source code of a program.
+    pub fn generate(&mut self) -> String {
+        let contracts = 1 + self.rng.up_to(self.config.contracts.saturating_sub(1));
+        (0..contracts)
+            .map(|i| self.contract(i))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn contract(&mut self, index: usize) -> String {
+        self.functions.clear();
+        self.scopes = vec![vec![]];
+        let mut members = vec![];
+        for _ in 0..self.rng.up_to(self.config.storage_variables) {
+            let name = self.new_name("s");
+            // State variables are initialized with constants, not from other state
+            let value = self.rng.up_to(1000);
+            members.push(format!("    uint {} = {};", name, value));
+            self.declare(name);
+        }
+        for _ in 0..1 + self.rng.up_to(self.config.functions.saturating_sub(1)) {
+            members.push(self.function());
+        }
+        self.scopes.clear();
+        format!("contract C{} {{\n{}\n}}\n", index, members.join("\n"))
+    }
+
+    fn function(&mut self) -> String {
+        let name = self.new_name("f");
+        let arity = self.rng.up_to(self.config.parameters);
+        self.scopes.push(vec![]);
+        let params = (0..arity)
+            .map(|_| {
+                let param = self.new_name("p");
+                self.declare(param.clone());
+                format!("uint {}", param)
+            })
+            .collect::<Vec<_>>();
+        let body = self.block(1);
+        self.scopes.pop();
+        // Registered after the body, so functions never call themselves.
+        self.functions.push((name.clone(), arity));
+        format!(
+            "    function {}({}) returns (uint) {}",
+            name,
+            params.join(", "),
+            body
+        )
+    }
+
+    fn block(&mut self, depth: usize) -> String {
+        self.scopes.push(vec![]);
+        let indent = "    ".repeat(depth + 1);
+        let mut lines = vec![];
+        for _ in 0..self.rng.up_to(self.config.statements) {
+            let statement = self.statement(depth);
+            lines.push(format!("{}{};\n", indent, statement));
+        }
+        let value = self.expression(self.config.expression_depth);
+        lines.push(format!("{}{}\n", indent, value));
+        self.scopes.pop();
+        format!("{{\n{}{}}}", lines.concat(), "    ".repeat(depth))
+    }
+
+    fn statement(&mut self, depth: usize) -> String {
+        let nested = depth < self.config.block_depth;
+        match self.rng.up_to(4) {
+            0 if nested => {
+                let condition = self.condition(self.config.expression_depth);
+                let then = self.block(depth + 1);
+                if self.rng.chance(50) {
+                    let otherwise = self.block(depth + 1);
+                    format!("if {} {} else {}", condition, then, otherwise)
+                } else {
+                    format!("if {} {}", condition, then)
+                }
+            }
+            1 if nested => self.block(depth + 1),
+            2 if self.has_variables() => {
+                let variable = self.variable();
+                let operator = self.rng.pick(ASSIGN_OPERATORS);
+                let value = self.expression(self.config.expression_depth);
+                format!("{} {} {}", variable, operator, value)
+            }
+            3 if self.has_variables() => {
+                let variable = self.variable();
+                if self.rng.chance(50) {
+                    format!("{}++", variable)
+                } else {
+                    format!("--{}", variable)
+                }
+            }
+            _ => {
+                let name = self.new_name("v");
+                let value = self.expression(self.config.expression_depth);
+                self.declare(name.clone());
+                format!("uint {} = {}", name, value)
+            }
+        }
+    }
+
+    fn condition(&mut self, depth: usize) -> String {
+        let left = self.expression(depth.saturating_sub(1));
+        let operator = self.rng.pick(COMPARISON_OPERATORS);
+        let right = self.expression(depth.saturating_sub(1));
+        format!("({}) {} ({})", left, operator, right)
+    }
+
+    fn expression(&mut self, depth: usize) -> String {
+        if depth == 0 || self.rng.chance(30) {
+            return self.terminal();
+        }
+        match self.rng.up_to(3) {
+            0 if !self.functions.is_empty() => {
+                let (name, arity) =
+                    self.functions[self.rng.up_to(self.functions.len() - 1)].clone();
+                let args = (0..arity)
+                    .map(|_| self.expression(depth - 1))
+                    .collect::<Vec<_>>();
+                format!("{}({})", name, args.join(", "))
+            }
+            1 => {
+                let condition = self.condition(depth);
+                let then = self.expression(depth - 1);
+                let otherwise = self.expression(depth - 1);
+                format!("({}) ? ({}) : ({})", condition, then, otherwise)
+            }
+            _ => {
+                let left = self.expression(depth - 1);
+                let right = self.expression(depth - 1);
+                // Literals are evaluated by the symbol table, which rejects negative values
+                let operator = if is_constant(&left) && is_constant(&right) {
+                    self.rng.pick(CONSTANT_OPERATORS)
+                } else {
+                    self.rng.pick(BINARY_OPERATORS)
+                };
+                format!("({}) {} ({})", left, operator, right)
+            }
+        }
+    }
+
+    fn terminal(&mut self) -> String {
+        if self.has_variables() && self.rng.chance(60) {
+            self.variable()
+        } else {
+            self.rng.up_to(1000).to_string()
+        }
+    }
+
+    fn has_variables(&self) -> bool {
+        self.scopes.iter().any(|scope| !scope.is_empty())
+    }
+
+    fn variable(&mut self) -> String {
+        let visible = self.scopes.concat();
+        visible[self.rng.up_to(visible.len() - 1)].clone()
+    }
+
+    fn declare(&mut self, name: String) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.push(name);
+        }
+    }
+
+    fn new_name(&mut self, prefix: &str) -> String {
+        self.names += 1;
+        format!("{}{}", prefix, self.names)
+    }
+}
+
+/// Whether `expression` only combines literals, names of variables and functions
+/// starting with a letter.
+fn is_constant(expression: &str) -> bool {
+    !expression.chars().any(|c| c.is_ascii_alphabetic())
+}
+
+/// Generates a program with the given configuration.
+pub fn generate_program(config: GeneratorConfig) -> String {
+    Generator::new(config).generate()
+}
@@ -0,0 +1,80 @@
+use crate::lexer::get_keywords;
+
+/// Associativity of operators sharing a precedence level.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Associativity {
+    Left,
+    Right,
+    /// Operators of this level can't be chained without parentheses, e.g. `a ** b ** c`.
+    None,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PrecedenceLevel {
+    pub operators: Vec<&'static str>,
+    pub associativity: Associativity,
+}
+
+impl PrecedenceLevel {
+    fn new(operators: Vec<&'static str>, associativity: Associativity) -> Self {
+        PrecedenceLevel {
+            operators,
+            associativity,
+        }
+    }
+}
+
+/// Description of the grammar accepted by the parser,
+/// for tools such as formatters and highlighters.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GrammarInfo {
+    /// Operator levels from the loosest to the tightest binding.
+    pub precedence: Vec<PrecedenceLevel>,
+    /// Reserved words which can't be used as identifiers, sorted.
+    pub keywords: Vec<String>,
+}
+
+impl GrammarInfo {
+    /// Index of the level of `operator` in `precedence`.
+    /// Operators which are both unary and binary, e.g. `-`, return their binary level.
+    pub fn precedence_of(&self, operator: &str) -> Option<usize> {
+        self.precedence
+            .iter()
+            .position(|level| level.operators.contains(&operator))
+    }
+}
+
+/// Must be kept in sync with the expression rules of `zok.lalrpop`.
+pub fn grammar_info() -> GrammarInfo {
+    let precedence = vec![
+        PrecedenceLevel::new(
+            vec![
+                "=", "&=", "|=", "^=", "<<=", ">>=", "+=", "-=", "*=", "/=", "%=",
+            ],
+            Associativity::Right,
+        ),
+        PrecedenceLevel::new(vec!["?:"], Associativity::Right),
+        PrecedenceLevel::new(vec!["||"], Associativity::Left),
+        PrecedenceLevel::new(vec!["&&"], Associativity::Left),
+        PrecedenceLevel::new(vec!["|"], Associativity::Left),
+        PrecedenceLevel::new(vec!["^"], Associativity::Left),
+        PrecedenceLevel::new(vec!["&"], Associativity::Left),
+        PrecedenceLevel::new(vec!["==", "!="], Associativity::Left),
+        PrecedenceLevel::new(vec!["<", "<=", ">", ">="], Associativity::Left),
+        PrecedenceLevel::new(vec!["<<", ">>"], Associativity::Left),
+        PrecedenceLevel::new(vec!["+", "-"], Associativity::Left),
+        PrecedenceLevel::new(vec!["*", "/", "%"], Associativity::Left),
+        PrecedenceLevel::new(vec!["**"], Associativity::None),
+        // Prefix and postfix unary operators
+        PrecedenceLevel::new(vec!["!", "++", "--"], Associativity::None),
+        // Member access, function call and indexing
+        PrecedenceLevel::new(vec![".", "()", "[]"], Associativity::Left),
+    ];
+    let mut keywords = get_keywords().into_keys().collect::<Vec<_>>();
+    keywords.sort();
+
+    GrammarInfo {
+        precedence,
+        keywords,
+    }
+}
@@ -0,0 +1,203 @@
+//! Interfaces extracted from contracts, e.g. `ICounter` from `Counter`.
+//!
+//! The interface declares, without bodies, the functions which can be called from
+//! outside the contract and the getters of its `public` state variables, followed by
+//! its events. Types declared in the contract are referred to by name only, they
+//! aren't declared in the interface.
+
+use crate::ast::{
+    ContractKind, Expression, ExpressionType, Located, Mutability, Statement, StatementType, Type,
+    Visibility,
+};
+use crate::unparse::unparse_statement;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct InterfaceConfig {
+    /// Name of the interface, `I` followed by the name of the contract if `None`
+    pub name: Option<String>,
+    /// Declares a getter for each state variable declared `public`
+    pub getters: bool,
+}
+
+impl Default for InterfaceConfig {
+    fn default() -> Self {
+        InterfaceConfig {
+            name: None,
+            getters: true,
+        }
+    }
+}
+
+/// Interface of `contract`, `None` if it isn't a contract.
+pub fn extract_interface(contract: &Statement, config: &InterfaceConfig) -> Option<Statement> {
+    let (contract_name, members) = match &contract.node {
+        StatementType::ContractStatement {
+            kind: ContractKind::Contract,
+            contract_name,
+            members,
+            ..
+        } => (contract_name, members),
+        _ => return None,
+    };
+    let statements = match &members.node {
+        StatementType::MemberStatement { statements } => statements,
+        _ => return None,
+    };
+    let name = config
+        .name
+        .clone()
+        .unwrap_or_else(|| format!("I{}", contract_name.node.identifier_name().unwrap()));
+    let mut functions = vec![];
+    let mut events = vec![];
+    for member in statements {
+        match declaration(member, config) {
+            Some(declaration) if is_event(&declaration.node) => events.push(declaration),
+            Some(declaration) => functions.push(declaration),
+            None => {}
+        }
+    }
+    functions.extend(events);
+    Some(Statement {
+        location: contract.location,
+        node: StatementType::ContractStatement {
+            annotations: vec![],
+            kind: ContractKind::Interface,
+            contract_name: Box::new(Expression {
+                location: contract_name.location,
+                node: ExpressionType::Identifier { value: name },
+            }),
+            bases: vec![],
+            members: Box::new(Statement {
+                location: members.location,
+                node: StatementType::MemberStatement {
+                    statements: functions,
+                },
+            }),
+            doc: None,
+        },
+    })
+}
+
+/// Source text of the interface of `contract`, `None` if it isn't a contract.
+pub fn interface_source(contract: &Statement, config: &InterfaceConfig) -> Option<String> {
+    extract_interface(contract, config).map(|interface| unparse_statement(&interface))
+}
+
+fn is_event(statement: &StatementType) -> bool {
+    match statement {
+        StatementType::ConditionalStatement { statement, .. } => is_event(&statement.node),
+        StatementType::EventStatement { .. } => true,
+        _ => false,
+    }
+}
+
+/// Declaration which `member` of the contract adds to its interface, if any.
+fn declaration(member: &Statement, config: &InterfaceConfig) -> Option<Statement> {
+    let node = match &member.node {
+        StatementType::ConditionalStatement {
+            condition,
+            statement,
+        } => StatementType::ConditionalStatement {
+            condition: condition.clone(),
+            statement: Box::new(declaration(statement, config)?),
+        },
+        StatementType::EventStatement { .. } => member.node.clone(),
+        StatementType::FunctionStatement {
+            function_name,
+            parameters,
+            returns,
+            visibility,
+            mutability,
+            doc,
+            ..
+        } => {
+            if member.node.is_constructor() || !is_external(*visibility) {
+                return None;
+            }
+            StatementType::FunctionStatement {
+                annotations: vec![],
+                function_name: function_name.clone(),
+                parameters: parameters.clone(),
+                statement: None,
+                returns: returns.clone(),
+                visibility: Some(Visibility::External),
+                mutability: *mutability,
+                doc: doc.clone(),
+            }
+        }
+        StatementType::InitializerStatement {
+            variable_type,
+            visibility: Some(Visibility::Public),
+            variable: Some(variable),
+            doc,
+            ..
+        } if config.getters => getter(member, variable_type, variable, doc),
+        _ => return None,
+    };
+    Some(Statement {
+        location: member.location,
+        node,
+    })
+}
+
+/// Whether a function of this visibility can be called through a value of the contract.
+fn is_external(visibility: Option<Visibility>) -> bool {
+    !matches!(
+        visibility,
+        Some(Visibility::Private) | Some(Visibility::Internal)
+    )
+}
+
+/// Getter of a state variable, which takes an index for each level of arrays
+/// and a key for each level of mappings, and returns the value reached.
+fn getter(
+    variable: &Statement,
+    variable_type: &Type,
+    name: &Expression,
+    doc: &Option<Located<String>>,
+) -> StatementType {
+    let parameter = |parameter_type: Type| Statement {
+        location: variable.location,
+        node: StatementType::InitializerStatement {
+            variable_type: parameter_type,
+            is_private: false,
+            visibility: None,
+            is_constant: false,
+            data_location: None,
+            variable: None,
+            default: None,
+            doc: None,
+        },
+    };
+    let mut parameters = vec![];
+    let mut value = variable_type;
+    loop {
+        value = match value {
+            Type::Array(element) | Type::FixedArray(element, _) => {
+                parameters.push(parameter(Type::Uint256));
+                element
+            }
+            Type::Mapping(key, element) => {
+                parameters.push(parameter(key.as_ref().clone()));
+                element
+            }
+            _ => break,
+        };
+    }
+    let list = |parameters: Vec<Statement>| {
+        Box::new(Expression {
+            location: variable.location,
+            node: ExpressionType::Parameters { parameters },
+        })
+    };
+    StatementType::FunctionStatement {
+        annotations: vec![],
+        function_name: Box::new(name.clone()),
+        parameters: list(parameters),
+        statement: None,
+        returns: Some(list(vec![parameter(value.clone())])),
+        visibility: Some(Visibility::External),
+        mutability: Some(Mutability::View),
+        doc: doc.clone(),
+    }
+}
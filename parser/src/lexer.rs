@@ -1,3 +1,4 @@
+use crate::ast::Radix;
 use crate::error::{LexicalError, LexicalErrorType};
 use crate::location::Location;
 pub use crate::token::Tok;
@@ -7,11 +8,11 @@ pub type LexResult = Result<Spanned, LexicalError>;
 
 use num_bigint::BigUint;
 use std::collections::HashMap;
-use std::str::FromStr;
+use std::iter::Peekable;
 use unic_ucd_ident::{is_xid_continue, is_xid_start};
 
 pub struct Lexer<T: Iterator<Item = char>> {
-    chars: T,
+    chars: Peekable<T>,
     location: Location,
     chr: Option<char>,
     keywords: HashMap<String, Tok>,
@@ -21,28 +22,56 @@ pub fn make_tokenizer(source: &'_ str) -> impl Iterator<Item = LexResult> + '_ {
     Lexer::new(source.chars())
 }
 
-fn get_keywords() -> HashMap<String, Tok> {
+pub(crate) fn get_keywords() -> HashMap<String, Tok> {
     let mut keywords = HashMap::new();
     keywords.insert(String::from("uint256"), Tok::Uint256);
     keywords.insert(String::from("uint"), Tok::Uint256);
     keywords.insert(String::from("int256"), Tok::Int256);
     keywords.insert(String::from("int"), Tok::Int256);
+    for bits in (8..256).step_by(8) {
+        keywords.insert(format!("uint{}", bits), Tok::SizedUint { bits });
+        keywords.insert(format!("int{}", bits), Tok::SizedInt { bits });
+    }
     keywords.insert(String::from("bytes32"), Tok::Bytes32);
     keywords.insert(String::from("bool"), Tok::Bool);
+    keywords.insert(String::from("field"), Tok::Field);
     keywords.insert(String::from("bytes"), Tok::Bytes);
     keywords.insert(String::from("string"), Tok::String);
     keywords.insert(String::from("address"), Tok::Address);
     keywords.insert(String::from("function"), Tok::Function);
     keywords.insert(String::from("contract"), Tok::Contract);
-    keywords.insert(String::from("memory"), Tok::Contract);
-    keywords.insert(String::from("storage"), Tok::Contract);
+    keywords.insert(String::from("interface"), Tok::Interface);
+    keywords.insert(String::from("library"), Tok::Library);
+    keywords.insert(String::from("is"), Tok::Is);
+    keywords.insert(String::from("using"), Tok::Using);
+    keywords.insert(String::from("event"), Tok::Event);
+    keywords.insert(String::from("enum"), Tok::Enum);
+    keywords.insert(String::from("struct"), Tok::Struct);
+    keywords.insert(String::from("module"), Tok::Module);
+    keywords.insert(String::from("import"), Tok::Import);
+    keywords.insert(String::from("constant"), Tok::Constant);
+    keywords.insert(String::from("match"), Tok::Match);
+    keywords.insert(String::from("memory"), Tok::Memory);
+    keywords.insert(String::from("storage"), Tok::Storage);
+    keywords.insert(String::from("transient"), Tok::Transient);
     keywords.insert(String::from("if"), Tok::If);
     keywords.insert(String::from("else"), Tok::Else);
     keywords.insert(String::from("for"), Tok::For);
+    keywords.insert(String::from("do"), Tok::Do);
+    keywords.insert(String::from("while"), Tok::While);
     keywords.insert(String::from("in"), Tok::In);
     keywords.insert(String::from("returns"), Tok::Returns);
     keywords.insert(String::from("return"), Tok::Return);
     keywords.insert(String::from("private"), Tok::Private);
+    keywords.insert(String::from("public"), Tok::Public);
+    keywords.insert(String::from("internal"), Tok::Internal);
+    keywords.insert(String::from("external"), Tok::External);
+    keywords.insert(String::from("pure"), Tok::Pure);
+    keywords.insert(String::from("view"), Tok::View);
+    keywords.insert(String::from("payable"), Tok::Payable);
+    keywords.insert(String::from("unchecked"), Tok::Unchecked);
+    keywords.insert(String::from("mapping"), Tok::Mapping);
+    keywords.insert(String::from("constructor"), Tok::Constructor);
 
     keywords
 }
@@ -53,7 +82,7 @@ where
 {
     fn new(input: T) -> Self {
         Lexer {
-            chars: input,
+            chars: input.peekable(),
             location: Location::new(0, 0),
             chr: None,
             keywords: get_keywords(),
@@ -64,20 +93,31 @@ where
         if self.chr.is_none() {
             self.next_char();
         }
-        if let Some(c) = self.chr {
+        while let Some(c) = self.chr {
             let start = self.location;
-            let token = if self.is_identifier_start(c) {
+            let token = if c == '/' && self.chars.peek() == Some(&'/') {
+                self.next_char();
+                if self.chars.peek() != Some(&'/') {
+                    self.skip_line_comment();
+                    continue;
+                }
+                self.lex_doc_comment()?
+            } else if c == '/' && self.chars.peek() == Some(&'*') {
+                match self.lex_block_comment()? {
+                    Some(token) => token,
+                    None => continue,
+                }
+            } else if self.is_identifier_start(c) {
                 self.consume_identifier(c)?
             } else {
                 self.consume_special_character(c)?
             };
             let end = self.location;
             self.skip_blank();
-            Ok((start, token, end))
-        } else {
-            // End Of File
-            Ok((self.location, Tok::EOF, self.location))
+            return Ok((start, token, end));
         }
+        // End Of File
+        Ok((self.location, Tok::EOF, self.location))
     }
 
     fn next_char(&mut self) {
@@ -135,6 +175,10 @@ where
                 break;
             }
         }
+        // Raw string literal such as `r"C:\path"`
+        if text == "r" && self.chr == Some('"') {
+            return self.lex_literal('"', true);
+        }
         if self.keywords.contains_key(&text) {
             Ok(self.keywords[&text].clone())
         } else {
@@ -145,7 +189,7 @@ where
     fn consume_special_character(&mut self, c: char) -> Result<Tok, LexicalError> {
         match c {
             '0'..='9' => self.lex_number(c),
-            '"' | '\'' => self.lex_literal(c),
+            '"' | '\'' => self.lex_literal(c, false),
             _ => self.consume_multiple_special_character(),
         }
     }
@@ -176,6 +220,10 @@ where
                     token = Some(Tok::Comma);
                     break;
                 }
+                "." => {
+                    token = Some(Tok::Dot);
+                    break;
+                }
                 "{" => {
                     token = Some(Tok::LBrace);
                     break;
@@ -192,6 +240,14 @@ where
                     token = Some(Tok::RPar);
                     break;
                 }
+                "[" => {
+                    token = Some(Tok::LSqb);
+                    break;
+                }
+                "]" => {
+                    token = Some(Tok::RSqb);
+                    break;
+                }
                 ";" => {
                     token = Some(Tok::Semi);
                     break;
@@ -244,6 +300,22 @@ where
                     token = Some(Tok::Eq);
                     break;
                 }
+                "=>" => {
+                    token = Some(Tok::FatArrow);
+                    break;
+                }
+                "#" => {
+                    token = Some(Tok::Hash);
+                    break;
+                }
+                "@" => {
+                    token = Some(Tok::At);
+                    break;
+                }
+                "::" => {
+                    token = Some(Tok::ColonColon);
+                    break;
+                }
                 "!=" => {
                     token = Some(Tok::NotEq);
                     break;
@@ -280,6 +352,83 @@ where
         self.check_token(token)
     }
 
+    /// Skips the rest of the line after `//`, the second slash being the current character.
+    fn skip_line_comment(&mut self) {
+        while self.chr.is_some_and(|c| c != '\n') {
+            self.next_char();
+        }
+        self.skip_blank();
+    }
+
+    /// Lexes a block comment, in which other block comments can be nested. The opening
+    /// slash is the current character. `/** ... */` is a doc comment, others are skipped.
+    fn lex_block_comment(&mut self) -> Result<Option<Tok>, LexicalError> {
+        let start = self.location;
+        self.next_char();
+        self.next_char();
+        let is_doc = self.chr == Some('*') && self.chars.peek() != Some(&'/');
+        if is_doc {
+            self.next_char();
+        }
+        let mut text = String::new();
+        let mut depth = 1;
+        while depth > 0 {
+            match (self.chr, self.chars.peek()) {
+                (Some('*'), Some('/')) => {
+                    self.next_char();
+                    depth -= 1;
+                    if depth > 0 {
+                        text.push_str("*/");
+                    }
+                }
+                (Some('/'), Some('*')) => {
+                    self.next_char();
+                    depth += 1;
+                    text.push_str("/*");
+                }
+                (Some(c), _) => text.push(c),
+                (None, _) => {
+                    return Err(LexicalError {
+                        error: LexicalErrorType::UnterminatedComment,
+                        location: start,
+                    })
+                }
+            }
+            self.next_char();
+        }
+        if !is_doc {
+            self.skip_blank();
+            return Ok(None);
+        }
+        // Lines without the leading `*` of each, as in `///` comments
+        let lines: Vec<&str> = text
+            .lines()
+            .map(|line| {
+                let line = line.trim();
+                let line = line.strip_prefix('*').unwrap_or(line);
+                line.strip_prefix(' ').unwrap_or(line)
+            })
+            .collect();
+        Ok(Some(Tok::DocComment {
+            text: lines.join("\n").trim_matches('\n').to_string(),
+        }))
+    }
+
+    /// Rest of the line after `///`, the second slash being the current character.
+    fn lex_doc_comment(&mut self) -> Result<Tok, LexicalError> {
+        self.next_char();
+        self.next_char();
+        let mut text = String::new();
+        while let Some(c) = self.chr.filter(|c| *c != '\n') {
+            text.push(c);
+            self.next_char();
+        }
+        let text = text.strip_prefix(' ').unwrap_or(&text).trim_end();
+        Ok(Tok::DocComment {
+            text: text.to_string(),
+        })
+    }
+
     fn check_token(&self, token: Option<Tok>) -> Result<Tok, LexicalError> {
         if let Some(t) = token {
             Ok(t)
@@ -293,58 +442,204 @@ where
         }
     }
 
+    /// Lexes a number, in hexadecimal after a `0x` prefix. Digits can be separated by `_`.
     fn lex_number(&mut self, c: char) -> Result<Tok, LexicalError> {
         let mut text = String::new();
-        text.push(c);
+        let radix = if c == '0' && matches!(self.chars.peek(), Some('x') | Some('X')) {
+            self.next_char();
+            Radix::Hexadecimal
+        } else {
+            text.push(c);
+            Radix::Decimal
+        };
+        let base = match radix {
+            Radix::Decimal => 10,
+            Radix::Hexadecimal => 16,
+        };
         loop {
             self.next_char();
-            if let Some(c) = self.chr {
-                match c {
-                    '0'..='9' => text.push(c),
-                    '_' => {
-                        self.next_char();
-                        if let Some(c) = self.chr {
-                            if let '0'..='9' = c {
-                                text.push(c);
-                            } else {
-                                return Err(LexicalError {
-                                    error: LexicalErrorType::UnrecognizedToken { tok: c },
-                                    location: self.location,
-                                });
-                            }
-                        } else {
+            match self.chr {
+                Some(c) if c.is_digit(base) => text.push(c),
+                Some('_') if !text.is_empty() => {
+                    self.next_char();
+                    match self.chr {
+                        Some(c) if c.is_digit(base) => text.push(c),
+                        c => {
                             return Err(LexicalError {
-                                error: LexicalErrorType::UnrecognizedToken { tok: c },
+                                error: LexicalErrorType::UnrecognizedToken {
+                                    tok: c.unwrap_or('_'),
+                                },
                                 location: self.location,
-                            });
+                            })
                         }
                     }
-                    _ => break,
                 }
-            } else {
-                break;
+                _ => break,
             }
         }
+        if text.is_empty() {
+            // `0x` without digits
+            return Err(LexicalError {
+                error: LexicalErrorType::UnrecognizedToken {
+                    tok: self.chr.unwrap_or('x'),
+                },
+                location: self.location,
+            });
+        }
         Ok(Tok::Num {
-            number: BigUint::from_str(&text)?,
+            number: BigUint::parse_bytes(text.as_bytes(), base).unwrap(),
+            radix,
         })
     }
 
-    fn lex_literal(&mut self, c: char) -> Result<Tok, LexicalError> {
-        let mut text = String::new();
-        let first = c;
+    /// Lexes a string literal starting at the opening `quote`.
+    /// Tripled quotes start a multi-line literal and raw literals keep backslashes as written.
+    fn lex_literal(&mut self, quote: char, is_raw: bool) -> Result<Tok, LexicalError> {
+        let start = self.location;
+        self.next_char();
+        let is_triple = if self.chr == Some(quote) {
+            self.next_char();
+            if self.chr != Some(quote) {
+                // Empty literal such as `""`
+                return Ok(Tok::Literal {
+                    literal: String::new(),
+                    raw: String::new(),
+                });
+            }
+            self.next_char();
+            true
+        } else {
+            false
+        };
+        let mut literal = String::new();
+        let mut raw = String::new();
+        let mut quotes = 0;
         loop {
+            match self.chr {
+                Some(c) if c == quote => {
+                    if !is_triple {
+                        break;
+                    }
+                    quotes += 1;
+                    if quotes == 3 {
+                        break;
+                    }
+                    self.next_char();
+                    continue;
+                }
+                Some('\n') if !is_triple => {
+                    return Err(LexicalError {
+                        error: LexicalErrorType::UnterminatedString,
+                        location: start,
+                    });
+                }
+                None => {
+                    return Err(LexicalError {
+                        error: LexicalErrorType::UnterminatedString,
+                        location: start,
+                    });
+                }
+                _ => {}
+            }
+            // Quotes which did not close a triple-quoted literal are part of it.
+            for _ in 0..quotes {
+                literal.push(quote);
+                raw.push(quote);
+            }
+            quotes = 0;
+            match self.chr {
+                Some('\\') if !is_raw => {
+                    let location = self.location;
+                    self.next_char();
+                    let (value, escape) = self.lex_escape(location)?;
+                    literal.push(value);
+                    raw.push('\\');
+                    raw.push_str(&escape);
+                }
+                Some(c) => {
+                    literal.push(c);
+                    raw.push(c);
+                }
+                None => unreachable!(),
+            }
             self.next_char();
-            if let Some(c) = self.chr {
-                if first == c {
-                    break;
+        }
+        // Consume the closing quote.
+        self.next_char();
+        Ok(Tok::Literal { literal, raw })
+    }
+
+    /// Decodes the escape sequence following a backslash at `location`.
+    /// Returns the decoded character and the escape as written, without the backslash.
+    fn lex_escape(&mut self, location: Location) -> Result<(char, String), LexicalError> {
+        let c = match self.chr {
+            Some(c) => c,
+            None => {
+                return Err(LexicalError {
+                    error: LexicalErrorType::UnterminatedString,
+                    location,
+                })
+            }
+        };
+        let mut escape = c.to_string();
+        let value = match c {
+            'n' => Some('\n'),
+            't' => Some('\t'),
+            'r' => Some('\r'),
+            '0' => Some('\0'),
+            '\\' | '"' | '\'' => Some(c),
+            'x' => {
+                let mut digits = String::new();
+                for _ in 0..2 {
+                    self.next_char();
+                    if let Some(c) = self.chr {
+                        escape.push(c);
+                        digits.push(c);
+                    }
                 }
-                text.push(c);
-            } else {
-                break;
+                // Bytes above `0x7f` are decoded to the code point of the same value.
+                if digits.len() == 2 && digits.chars().all(|c| c.is_ascii_hexdigit()) {
+                    u8::from_str_radix(&digits, 16).ok().map(char::from)
+                } else {
+                    None
+                }
+            }
+            'u' => {
+                let mut digits = String::new();
+                self.next_char();
+                if self.chr == Some('{') {
+                    escape.push('{');
+                    loop {
+                        self.next_char();
+                        match self.chr {
+                            Some('}') => {
+                                escape.push('}');
+                                break;
+                            }
+                            Some(c) if c.is_ascii_hexdigit() && digits.len() < 6 => {
+                                escape.push(c);
+                                digits.push(c);
+                            }
+                            _ => {
+                                digits.clear();
+                                break;
+                            }
+                        }
+                    }
+                }
+                u32::from_str_radix(&digits, 16)
+                    .ok()
+                    .and_then(std::char::from_u32)
             }
+            _ => None,
+        };
+        match value {
+            Some(value) => Ok((value, escape)),
+            None => Err(LexicalError {
+                error: LexicalErrorType::InvalidEscape(format!("\\{}", escape)),
+                location,
+            }),
         }
-        Ok(Tok::Literal { literal: text })
     }
 }
 
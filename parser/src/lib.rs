@@ -4,14 +4,40 @@
 use lalrpop_util::lalrpop_mod;
 
 pub mod ast;
+pub mod codemod;
+pub mod complexity;
+pub mod config;
+pub mod coverage;
+pub mod docgen;
 pub mod error;
+#[cfg(feature = "generator")]
+pub mod generator;
+pub mod grammar;
+pub mod interface;
 pub mod lexer;
 pub mod location;
+pub mod natspec;
 pub mod parser;
+pub mod prelude;
 pub mod print;
+pub mod query;
+pub mod snapshot;
 pub mod token;
+pub mod trace;
+pub mod unparse;
 
+pub use grammar::grammar_info;
+
+// Generated parsers, use the entry points of `prelude` instead
 lalrpop_mod!(
     #[allow(clippy::all)]
+    #[doc(hidden)]
     pub zok
 );
+// Same parsers without error recovery, see `build.rs`
+lalrpop_mod!(
+    #[allow(clippy::all)]
+    #[doc(hidden)]
+    pub strict_zok,
+    "/strict/zok.rs"
+);
@@ -0,0 +1,257 @@
+//! Tags of doc comments, in the NatSpec format of Solidity.
+//!
+//! A doc comment starts with its notice, or with tags each on their own line:
+//!
+//! ```text
+//! /// Moves tokens to another account
+//! /// @param to Account receiving the tokens
+//! /// @param amount Number of tokens to move
+//! /// @return Whether the tokens were moved
+//! ```
+//!
+//! Lines without a tag continue the previous one. Tags other than `@notice`,
+//! `@param` and `@return`, e.g. `@dev`, are skipped. The `@param` tags of each
+//! declaration are checked against its parameters, and mismatches are reported
+//! as warnings at the location of the doc comment.
+
+use crate::ast::{Expression, ExpressionType, Program, Statement, StatementType};
+use crate::location::Location;
+use std::collections::HashSet;
+use std::fmt;
+
+/// Tags of a doc comment.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NatSpec {
+    pub notice: Option<String>,
+    /// Names of the parameters with their descriptions, in order of the comment
+    pub params: Vec<(String, String)>,
+    pub returns: Vec<String>,
+}
+
+/// Tag of the line being read.
+enum Tag {
+    Notice,
+    Param,
+    Return,
+    Other,
+}
+
+impl NatSpec {
+    /// Tags of the doc comment `doc`, without the slashes.
+    pub fn parse(doc: &str) -> Self {
+        let mut natspec = NatSpec::default();
+        let mut tag = Tag::Notice;
+        for line in doc.lines().map(str::trim) {
+            let text = match line.strip_prefix('@') {
+                Some(tagged) => {
+                    let (name, text) = split_word(tagged);
+                    tag = match name {
+                        "notice" => Tag::Notice,
+                        "param" => Tag::Param,
+                        "return" => Tag::Return,
+                        _ => Tag::Other,
+                    };
+                    match tag {
+                        Tag::Param => {
+                            let (name, text) = split_word(text);
+                            natspec.params.push((name.to_string(), String::new()));
+                            text
+                        }
+                        Tag::Return => {
+                            natspec.returns.push(String::new());
+                            text
+                        }
+                        _ => text,
+                    }
+                }
+                None => line,
+            };
+            let description = match tag {
+                Tag::Notice => natspec.notice.get_or_insert_with(String::new),
+                Tag::Param => &mut natspec.params.last_mut().unwrap().1,
+                Tag::Return => natspec.returns.last_mut().unwrap(),
+                Tag::Other => continue,
+            };
+            if !description.is_empty() && !text.is_empty() {
+                description.push(' ');
+            }
+            description.push_str(text);
+        }
+        natspec
+    }
+}
+
+/// First word of `text` and the rest after the spaces following it.
+fn split_word(text: &str) -> (&str, &str) {
+    match text.find(char::is_whitespace) {
+        Some(end) => (&text[..end], text[end..].trim_start()),
+        None => (text, ""),
+    }
+}
+
+/// Difference between the tags of a doc comment and the declaration it documents.
+#[derive(Debug, Clone, PartialEq)]
+pub enum NatSpecMismatch {
+    /// `@param` naming no parameter of the declaration
+    UnknownParameter(String),
+    /// Parameter with several `@param` tags
+    DuplicateParameter(String),
+    /// More `@return` tags than returned values
+    ExtraReturns { documented: usize, returned: usize },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct NatSpecWarning {
+    /// Name of the declaration, e.g. `Token.transfer` for a function of a contract
+    pub declaration: String,
+    pub mismatch: NatSpecMismatch,
+    /// Location of the doc comment
+    pub location: Location,
+}
+
+impl fmt::Display for NatSpecWarning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.mismatch {
+            NatSpecMismatch::UnknownParameter(name) => write!(
+                f,
+                "`@param {}` doesn't match any parameter of `{}`",
+                name, self.declaration
+            ),
+            NatSpecMismatch::DuplicateParameter(name) => write!(
+                f,
+                "parameter `{}` of `{}` is documented more than once",
+                name, self.declaration
+            ),
+            NatSpecMismatch::ExtraReturns {
+                documented,
+                returned,
+            } => write!(
+                f,
+                "`{}` documents {} return values but returns {}",
+                self.declaration, documented, returned
+            ),
+        }
+    }
+}
+
+/// Mismatches between the doc comments of `program` and the declarations they document.
+pub fn natspec_warnings(program: &Program) -> Vec<NatSpecWarning> {
+    let Program::GlobalStatements(statements) = program;
+    let mut warnings = vec![];
+    check_declarations(statements, None, &mut warnings);
+    warnings
+}
+
+fn check_declarations(
+    statements: &[Statement],
+    scope: Option<&str>,
+    warnings: &mut Vec<NatSpecWarning>,
+) {
+    for statement in statements {
+        let name = declared_name(&statement.node);
+        let qualified = match (scope, &name) {
+            (Some(scope), Some(name)) => format!("{}.{}", scope, name),
+            (None, Some(name)) => name.clone(),
+            (_, None) => String::new(),
+        };
+        if let Some(doc) = statement.node.doc_comment() {
+            check_declaration(
+                &statement.node,
+                &qualified,
+                &doc.node,
+                doc.location,
+                warnings,
+            );
+        }
+        match &statement.node {
+            StatementType::ContractStatement { members, .. } => {
+                if let StatementType::MemberStatement { statements } = &members.node {
+                    check_declarations(statements, Some(&qualified), warnings);
+                }
+            }
+            StatementType::ModuleStatement { members, .. } => {
+                check_declarations(members, Some(&qualified), warnings);
+            }
+            StatementType::ConditionalStatement { statement, .. } => {
+                check_declarations(std::slice::from_ref(statement), scope, warnings);
+            }
+            _ => {}
+        }
+    }
+}
+
+fn declared_name(statement: &StatementType) -> Option<String> {
+    match statement {
+        StatementType::FunctionStatement {
+            function_name: name,
+            ..
+        }
+        | StatementType::ContractStatement {
+            contract_name: name,
+            ..
+        }
+        | StatementType::ModuleStatement {
+            module_name: name, ..
+        }
+        | StatementType::InitializerStatement {
+            variable: Some(name),
+            ..
+        } => name.node.identifier_name(),
+        _ => None,
+    }
+}
+
+fn check_declaration(
+    statement: &StatementType,
+    declaration: &str,
+    doc: &str,
+    location: Location,
+    warnings: &mut Vec<NatSpecWarning>,
+) {
+    let natspec = NatSpec::parse(doc);
+    let (parameters, returned) = match statement {
+        StatementType::FunctionStatement {
+            parameters,
+            returns,
+            ..
+        } => (
+            parameter_names(parameters),
+            returns
+                .as_deref()
+                .map_or(0, |returns| parameter_names(returns).len()),
+        ),
+        _ => (vec![], 0),
+    };
+    let mut warn = |mismatch| {
+        warnings.push(NatSpecWarning {
+            declaration: declaration.to_string(),
+            mismatch,
+            location,
+        })
+    };
+    let mut documented = HashSet::new();
+    for (name, _) in &natspec.params {
+        if !parameters.contains(name) {
+            warn(NatSpecMismatch::UnknownParameter(name.clone()));
+        } else if !documented.insert(name) {
+            warn(NatSpecMismatch::DuplicateParameter(name.clone()));
+        }
+    }
+    if natspec.returns.len() > returned {
+        warn(NatSpecMismatch::ExtraReturns {
+            documented: natspec.returns.len(),
+            returned,
+        });
+    }
+}
+
+/// Names of `parameters`, empty strings for unnamed ones, e.g. return values.
+fn parameter_names(parameters: &Expression) -> Vec<String> {
+    match &parameters.node {
+        ExpressionType::Parameters { parameters } => parameters
+            .iter()
+            .map(|parameter| declared_name(&parameter.node).unwrap_or_default())
+            .collect(),
+        _ => vec![],
+    }
+}
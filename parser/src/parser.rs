@@ -1,15 +1,70 @@
 use crate::ast;
+use crate::complexity::{check_complexity, program_nodes, Node};
+use crate::config::{self, ParseConfig};
 use crate::error::ParseError;
 use crate::lexer;
-use crate::zok;
+use crate::trace::{self, ParseTrace, Traceable};
+use crate::{strict_zok, zok};
+
+/// Parses with error recovery, giving the AST with placeholders and the recovered errors.
+macro_rules! do_tolerant_lalr_parsing {
+    ($lxr: expr, $parser: ident) => {{
+        let mut errors = vec![];
+        let result = zok::$parser::new().parse(&mut errors, $lxr);
+        let errors = errors
+            .into_iter()
+            .map(|recovery| ParseError::from(recovery.error))
+            .collect::<Vec<_>>();
+        (result.map_err(ParseError::from), errors)
+    }};
+}
+
+/// Fails on the first error, without trying to recover from it.
+macro_rules! do_strict_lalr_parsing {
+    ($lxr: expr, $parser: ident) => {{
+        strict_zok::$parser::new()
+            .parse(&mut vec![], $lxr)
+            .map_err(ParseError::from)
+    }};
+}
 
 macro_rules! do_lalr_parsing {
     ($input: expr, $parser: ident) => {{
-        let lxr = lexer::make_tokenizer($input);
-        match zok::$parser::new().parse(lxr) {
-            Err(err) => Err(ParseError::from(err)),
-            Ok(top) => Ok(top),
+        if trace::is_enabled() {
+            let (result, trace) = do_traced_lalr_parsing!($input, $parser);
+            eprint!("{}", trace);
+            result
+        } else {
+            do_strict_lalr_parsing!(lexer::make_tokenizer($input), $parser)
+        }
+    }};
+}
+
+/// Rejects sources exceeding the limits of `config` before parsing them.
+macro_rules! do_limited_lalr_parsing {
+    ($input: expr, $config: expr, $parser: ident) => {{
+        config::check_source_size($input, $config)
+            .map_err(|error| ParseError::from(lalrpop_util::ParseError::User { error }))?;
+        do_strict_lalr_parsing!(
+            config::limit_tokens(lexer::make_tokenizer($input), $config),
+            $parser
+        )
+    }};
+}
+
+macro_rules! do_traced_lalr_parsing {
+    ($input: expr, $parser: ident) => {{
+        let mut trace = ParseTrace::new();
+        let lxr = lexer::make_tokenizer($input).inspect(|tok| {
+            if let Ok((location, token, _)) = tok {
+                trace.token(*location, token);
+            }
+        });
+        let result = do_strict_lalr_parsing!(lxr, $parser);
+        if let Ok(top) = &result {
+            top.trace(&mut trace);
         }
+        (result, trace)
     }};
 }
 
@@ -24,3 +79,59 @@ pub fn parse_statement(source: &str) -> Result<ast::Statement, ParseError> {
 pub fn parse_program(source: &str) -> Result<ast::Program, ParseError> {
     do_lalr_parsing!(source, ProgramParser)
 }
+
+/// Parses a program from the tokens of its source, e.g. kept from an earlier lexing.
+pub fn parse_program_tokens(
+    tokens: impl IntoIterator<Item = lexer::LexResult>,
+) -> Result<ast::Program, ParseError> {
+    do_strict_lalr_parsing!(tokens.into_iter(), ProgramParser)
+}
+
+pub fn parse_expression_with(
+    source: &str,
+    config: &ParseConfig,
+) -> Result<ast::Expression, ParseError> {
+    let expression = do_limited_lalr_parsing!(source, config, ExpressionParser)?;
+    check_complexity(vec![Node::Expression(&expression)], config)?;
+    Ok(expression)
+}
+
+pub fn parse_statement_with(
+    source: &str,
+    config: &ParseConfig,
+) -> Result<ast::Statement, ParseError> {
+    let statement = do_limited_lalr_parsing!(source, config, StatementParser)?;
+    check_complexity(vec![Node::Statement(&statement)], config)?;
+    Ok(statement)
+}
+
+pub fn parse_program_with(source: &str, config: &ParseConfig) -> Result<ast::Program, ParseError> {
+    let program = do_limited_lalr_parsing!(source, config, ProgramParser)?;
+    check_complexity(program_nodes(&program), config)?;
+    Ok(program)
+}
+
+pub fn parse_expression_traced(source: &str) -> (Result<ast::Expression, ParseError>, ParseTrace) {
+    do_traced_lalr_parsing!(source, ExpressionParser)
+}
+
+pub fn parse_statement_traced(source: &str) -> (Result<ast::Statement, ParseError>, ParseTrace) {
+    do_traced_lalr_parsing!(source, StatementParser)
+}
+
+pub fn parse_program_traced(source: &str) -> (Result<ast::Program, ParseError>, ParseTrace) {
+    do_traced_lalr_parsing!(source, ProgramParser)
+}
+
+/// Parses a program with syntax errors, replacing what couldn't be parsed by
+/// `StatementType::Error` and `ExpressionType::Error` nodes.
+/// The program is `None` only if the parser couldn't recover.
+pub fn parse_program_tolerant(source: &str) -> (Option<ast::Program>, Vec<ParseError>) {
+    match do_tolerant_lalr_parsing!(lexer::make_tokenizer(source), ProgramParser) {
+        (Ok(program), errors) => (Some(program), errors),
+        (Err(err), mut errors) => {
+            errors.push(err);
+            (None, errors)
+        }
+    }
+}
@@ -0,0 +1,30 @@
+//! Stable public API of the parser.
+//!
+//! Everything needed to parse sources and inspect the result: the entry points,
+//! the AST, locations, tokens and errors. Items outside the prelude, notably the
+//! parsers generated by LALRPOP in `zok`, may change between minor versions.
+//!
+//! ```
+//! use zoker_parser::prelude::*;
+//!
+//! let program = parse_program("contract Test { uint total; }").unwrap();
+//! let Program::GlobalStatements(statements) = program;
+//! assert!(matches!(statements[0].node, StatementType::ContractStatement { .. }));
+//! ```
+
+pub use crate::ast::{
+    Annotation, Expression, ExpressionType, Located, MatchArm, Operator, Program, Specifier,
+    Statement, StatementType, StructField, Type,
+};
+pub use crate::complexity::{check_complexity, program_nodes, Complexity, Node};
+pub use crate::config::{ParseConfig, ParseLimit};
+pub use crate::error::{LexicalError, LexicalErrorType, ParseError, ParseErrorType};
+pub use crate::lexer::{make_tokenizer, LexResult, Spanned};
+pub use crate::location::Location;
+pub use crate::parser::{
+    parse_expression, parse_expression_traced, parse_expression_with, parse_program,
+    parse_program_tolerant, parse_program_traced, parse_program_with, parse_statement,
+    parse_statement_traced, parse_statement_with,
+};
+pub use crate::token::Tok;
+pub use crate::trace::ParseTrace;
@@ -59,18 +59,28 @@ pub fn stmt_to_str(node: &ast::StatementType) -> PrintAST {
             parameters: params,
             statement: stmt,
             returns: ret,
+            visibility,
+            mutability,
+            ..
         } => {
-            let name = name_from_identifier(&id).unwrap();
+            let name = name_from_identifier(id).unwrap();
             let repr = String::from("[ Function Statement: ")
                 .add(name.as_str())
                 .add(" ] ");
-            let parameters = expr_to_str(&params.node);
-            let statement = stmt_to_str(&stmt.node);
-            let mut children = vec![parameters, statement];
+            let mut children = vec![expr_to_str(&params.node)];
+            if let Some(stmt) = stmt {
+                children.push(stmt_to_str(&stmt.node));
+            }
             if let Some(returns) = ret {
                 let returns = expr_to_str(&returns.node);
                 children.push(returns);
             }
+            if let Some(visibility) = visibility {
+                children.push(visibility_to_str(visibility));
+            }
+            if let Some(mutability) = mutability {
+                children.push(mutability_to_str(mutability));
+            }
             let children_size = children.iter().fold(0, |v, child| v + child.size);
             let size = usize::max(repr.len(), children_size);
 
@@ -85,15 +95,150 @@ pub fn stmt_to_str(node: &ast::StatementType) -> PrintAST {
             ast
         }
         ast::StatementType::ContractStatement {
+            kind,
             contract_name: name,
+            bases,
             members: stmts,
+            ..
+        } => {
+            let name = name_from_identifier(name).unwrap();
+            let repr = match kind {
+                ast::ContractKind::Contract => String::from("[ Contract Statement: "),
+                ast::ContractKind::Interface => String::from("[ Interface Statement: "),
+                ast::ContractKind::Library => String::from("[ Library Statement: "),
+            }
+            .add(name.as_str())
+            .add(" ] ");
+            let mut children: Vec<PrintAST> =
+                bases.iter().map(|base| expr_to_str(&base.node)).collect();
+            children.push(stmt_to_str(&stmts.node));
+            let children_size = children.iter().fold(0, |v, child| v + child.size);
+            let size = usize::max(repr.len(), children_size);
+
+            let mut ast = PrintAST {
+                repr,
+                size,
+                left_margin: 0,
+                right_margin: 0,
+                children,
+            };
+            ast.add_children_margin();
+            ast
+        }
+        ast::StatementType::EventStatement {
+            event_name: name,
+            parameters: params,
+        } => {
+            let name = name_from_identifier(name).unwrap();
+            let repr = String::from("[ Event Statement: ")
+                .add(name.as_str())
+                .add(" ] ");
+            let children = vec![expr_to_str(&params.node)];
+            let children_size = children.iter().fold(0, |v, child| v + child.size);
+            let size = usize::max(repr.len(), children_size);
+
+            let mut ast = PrintAST {
+                repr,
+                size,
+                left_margin: 0,
+                right_margin: 0,
+                children,
+            };
+            ast.add_children_margin();
+            ast
+        }
+        ast::StatementType::UsingStatement { library, target } => {
+            let name = name_from_identifier(library).unwrap();
+            let repr = String::from("[ Using Statement: ")
+                .add(name.as_str())
+                .add(" ] ");
+            let children = vec![type_to_str(target)];
+            let children_size = children.iter().fold(0, |v, child| v + child.size);
+            let size = usize::max(repr.len(), children_size);
+
+            let mut ast = PrintAST {
+                repr,
+                size,
+                left_margin: 0,
+                right_margin: 0,
+                children,
+            };
+            ast.add_children_margin();
+            ast
+        }
+        ast::StatementType::EnumStatement {
+            enum_name: name,
+            variants,
+        } => {
+            let name = name_from_identifier(name).unwrap();
+            let repr = String::from("[ Enum Statement: ")
+                .add(name.as_str())
+                .add(" ] ");
+            let children = variants
+                .iter()
+                .map(|variant| expr_to_str(&variant.node))
+                .collect::<Vec<_>>();
+            let children_size = children.iter().fold(0, |v, child| v + child.size);
+            let size = usize::max(repr.len(), children_size);
+
+            let mut ast = PrintAST {
+                repr,
+                size,
+                left_margin: 0,
+                right_margin: 0,
+                children,
+            };
+            ast.add_children_margin();
+            ast
+        }
+        ast::StatementType::StructStatement {
+            struct_name: name,
+            fields,
         } => {
-            let name = name_from_identifier(&name).unwrap();
-            let repr = String::from("[ Contract Statement: ")
+            let name = name_from_identifier(name).unwrap();
+            let repr = String::from("[ Struct Statement: ")
                 .add(name.as_str())
                 .add(" ] ");
-            let member = stmt_to_str(&stmts.node);
-            let children = vec![member];
+            let children = fields
+                .iter()
+                .map(|field| stmt_to_str(&field.node))
+                .collect::<Vec<_>>();
+            let children_size = children.iter().fold(0, |v, child| v + child.size);
+            let size = usize::max(repr.len(), children_size);
+
+            let mut ast = PrintAST {
+                repr,
+                size,
+                left_margin: 0,
+                right_margin: 0,
+                children,
+            };
+            ast.add_children_margin();
+            ast
+        }
+        ast::StatementType::ImportStatement { path } => {
+            let repr = format!("[ Import Statement : {} ] ", path);
+            let size = repr.len();
+            PrintAST {
+                repr,
+                size,
+                left_margin: 0,
+                right_margin: 0,
+                children: vec![],
+            }
+        }
+        ast::StatementType::ModuleStatement {
+            module_name: name,
+            members,
+        } => {
+            let name = name_from_identifier(name).unwrap();
+            let repr = String::from("[ Module Statement: ")
+                .add(name.as_str())
+                .add(" ] ");
+            let children = members
+                .iter()
+                .map(|member| stmt_to_str(&member.node))
+                .collect::<Vec<_>>();
             let children_size = children.iter().fold(0, |v, child| v + child.size);
             let size = usize::max(repr.len(), children_size);
 
@@ -109,14 +254,18 @@ pub fn stmt_to_str(node: &ast::StatementType) -> PrintAST {
         }
         ast::StatementType::InitializerStatement {
             variable_type: var_type,
+            visibility,
             data_location: loc,
             variable: var_name,
             default: default_val,
             ..
         } => {
             let repr = String::from("[ Initializer Statement ] ");
-            let variable_type = type_to_str(&var_type);
+            let variable_type = type_to_str(var_type);
             let mut children = vec![variable_type];
+            if let Some(visibility) = visibility {
+                children.push(visibility_to_str(visibility));
+            }
             if let Some(location) = loc {
                 let data_location = specifier_to_str(location);
                 children.push(data_location);
@@ -186,6 +335,57 @@ pub fn stmt_to_str(node: &ast::StatementType) -> PrintAST {
             ast.add_children_margin();
             ast
         }
+        ast::StatementType::ConditionalStatement {
+            statement: stmt, ..
+        } => {
+            let child = stmt_to_str(&stmt.node);
+            let repr = String::from("[ Conditional Statement ] ");
+            let size = usize::max(repr.len(), child.size);
+
+            let mut ast = PrintAST {
+                repr,
+                size,
+                left_margin: 0,
+                right_margin: 0,
+                children: vec![child],
+            };
+            ast.add_children_margin();
+            ast
+        }
+        ast::StatementType::UncheckedStatement { statement: stmt } => {
+            let child = stmt_to_str(&stmt.node);
+            let repr = String::from("[ Unchecked Statement ] ");
+            let size = usize::max(repr.len(), child.size);
+
+            let mut ast = PrintAST {
+                repr,
+                size,
+                left_margin: 0,
+                right_margin: 0,
+                children: vec![child],
+            };
+            ast.add_children_margin();
+            ast
+        }
+        ast::StatementType::DoWhileStatement {
+            statement: stmt,
+            condition: cond,
+        } => {
+            let statement = stmt_to_str(&stmt.node);
+            let condition = expr_to_str(&cond.node);
+            let repr = String::from("[ Do-while Statement ] ");
+            let size = usize::max(repr.len(), statement.size + condition.size);
+
+            let mut ast = PrintAST {
+                repr,
+                size,
+                left_margin: 0,
+                right_margin: 0,
+                children: vec![statement, condition],
+            };
+            ast.add_children_margin();
+            ast
+        }
         ast::StatementType::Expression { expression: expr } => expr_to_str(&expr.node),
         StatementType::ReturnStatement { ret } => {
             let repr = String::from("[ ReturnStatement ] ");
@@ -206,6 +406,17 @@ pub fn stmt_to_str(node: &ast::StatementType) -> PrintAST {
             ast.add_children_margin();
             ast
         }
+        StatementType::Error => {
+            let repr = String::from("[ Error Statement ] ");
+            let size = repr.len();
+            PrintAST {
+                repr,
+                size,
+                left_margin: 0,
+                right_margin: 0,
+                children: vec![],
+            }
+        }
     }
 }
 
@@ -297,6 +508,66 @@ pub fn expr_to_str(node: &ast::ExpressionType) -> PrintAST {
             ast.add_children_margin();
             ast
         }
+        ast::ExpressionType::MemberExpression {
+            expression: expr,
+            member: m,
+        } => {
+            let expression = expr_to_str(&expr.node);
+            let member = expr_to_str(&m.node);
+            let repr = String::from("[ Member Expression ] ");
+            let children_size = expression.size + member.size;
+            let size = usize::max(repr.len(), children_size);
+
+            let mut ast = PrintAST {
+                repr,
+                size,
+                left_margin: 0,
+                right_margin: 0,
+                children: vec![expression, member],
+            };
+            ast.add_children_margin();
+            ast
+        }
+        ast::ExpressionType::PathExpression {
+            module: m,
+            member: mem,
+        } => {
+            let module = expr_to_str(&m.node);
+            let member = expr_to_str(&mem.node);
+            let repr = String::from("[ Path Expression ] ");
+            let children_size = module.size + member.size;
+            let size = usize::max(repr.len(), children_size);
+
+            let mut ast = PrintAST {
+                repr,
+                size,
+                left_margin: 0,
+                right_margin: 0,
+                children: vec![module, member],
+            };
+            ast.add_children_margin();
+            ast
+        }
+        ast::ExpressionType::IndexExpression {
+            expression: expr,
+            index: i,
+        } => {
+            let expression = expr_to_str(&expr.node);
+            let index = expr_to_str(&i.node);
+            let repr = String::from("[ Index Expression ] ");
+            let children_size = expression.size + index.size;
+            let size = usize::max(repr.len(), children_size);
+
+            let mut ast = PrintAST {
+                repr,
+                size,
+                left_margin: 0,
+                right_margin: 0,
+                children: vec![expression, index],
+            };
+            ast.add_children_margin();
+            ast
+        }
         ast::ExpressionType::ForEachExpression {
             iterator: iter,
             vector: vec,
@@ -366,11 +637,31 @@ pub fn expr_to_str(node: &ast::ExpressionType) -> PrintAST {
             ast.add_children_margin();
             ast
         }
+        ast::ExpressionType::MatchExpression { value, arms } => {
+            let mut children = vec![expr_to_str(&value.node)];
+            for arm in arms {
+                children.push(expr_to_str(&arm.pattern.node));
+                children.push(stmt_to_str(&arm.statement.node));
+            }
+            let repr = String::from("[ Match Expression ] ");
+            let children_size = children.iter().fold(0, |v, child| v + child.size);
+            let size = usize::max(repr.len(), children_size);
+
+            let mut ast = PrintAST {
+                repr,
+                size,
+                left_margin: 0,
+                right_margin: 0,
+                children,
+            };
+            ast.add_children_margin();
+            ast
+        }
         ast::ExpressionType::UnaryExpression {
             operator: op,
             expression: expr,
         } => {
-            let operator = operator_to_str(&op);
+            let operator = operator_to_str(op);
             let expression = expr_to_str(&expr.node);
             let repr = String::from("[ UnaryExpression ] ");
             let children_size = operator.size + expression.size;
@@ -429,8 +720,8 @@ pub fn expr_to_str(node: &ast::ExpressionType) -> PrintAST {
             ast.add_children_margin();
             ast
         }
-        ast::ExpressionType::Number { value: v } => {
-            let repr = format!("[ Number : {} ] ", v);
+        ast::ExpressionType::Number { value: v, radix } => {
+            let repr = format!("[ Number : {} ] ", radix.format(v));
             let size = repr.len();
             PrintAST {
                 repr,
@@ -451,6 +742,54 @@ pub fn expr_to_str(node: &ast::ExpressionType) -> PrintAST {
                 children: vec![],
             }
         }
+        ast::ExpressionType::Error => {
+            let repr = String::from("[ Error Expression ] ");
+            let size = repr.len();
+            PrintAST {
+                repr,
+                size,
+                left_margin: 0,
+                right_margin: 0,
+                children: vec![],
+            }
+        }
+        ast::ExpressionType::StringLiteral { raw, .. } => {
+            let repr = format!("[ String : \"{}\" ] ", raw);
+            let size = repr.len();
+            PrintAST {
+                repr,
+                size,
+                left_margin: 0,
+                right_margin: 0,
+                children: vec![],
+            }
+        }
+        ast::ExpressionType::StructLiteral {
+            struct_name: name,
+            fields,
+        } => {
+            let name = name.node.path_name().unwrap();
+            let repr = String::from("[ Struct Literal: ")
+                .add(name.as_str())
+                .add(" ] ");
+            let mut children = vec![];
+            for field in fields {
+                children.push(expr_to_str(&field.name.node));
+                children.push(expr_to_str(&field.value.node));
+            }
+            let children_size = children.iter().fold(0, |v, child| v + child.size);
+            let size = usize::max(repr.len(), children_size);
+
+            let mut ast = PrintAST {
+                repr,
+                size,
+                left_margin: 0,
+                right_margin: 0,
+                children,
+            };
+            ast.add_children_margin();
+            ast
+        }
         ExpressionType::Tuple { items } => {
             let children = items
                 .iter()
@@ -472,6 +811,22 @@ pub fn expr_to_str(node: &ast::ExpressionType) -> PrintAST {
             let children_size = children.iter().fold(0, |v, child| v + child.size);
             let size = usize::max(repr.len(), children_size);
 
+            let mut ast = PrintAST {
+                repr,
+                size,
+                left_margin: 0,
+                right_margin: 0,
+                children,
+            };
+            ast.add_children_margin();
+            ast
+        }
+        ExpressionType::TypeList { types } => {
+            let children = types.iter().map(type_to_str).collect::<Vec<_>>();
+            let repr = String::from("[ Type List ] ");
+            let children_size = children.iter().fold(0, |v, child| v + child.size);
+            let size = usize::max(repr.len(), children_size);
+
             let mut ast = PrintAST {
                 repr,
                 size,
@@ -765,6 +1120,35 @@ fn specifier_to_str(node: &ast::Specifier) -> PrintAST {
             right_margin: 0,
             children: vec![],
         },
+        ast::Specifier::Transient => PrintAST {
+            repr: String::from("[ specifier : transient ] "),
+            size: 26,
+            left_margin: 0,
+            right_margin: 0,
+            children: vec![],
+        },
+    }
+}
+
+fn visibility_to_str(node: &ast::Visibility) -> PrintAST {
+    let repr = format!("[ visibility : {} ] ", node);
+    PrintAST {
+        size: repr.len(),
+        repr,
+        left_margin: 0,
+        right_margin: 0,
+        children: vec![],
+    }
+}
+
+fn mutability_to_str(node: &ast::Mutability) -> PrintAST {
+    let repr = format!("[ mutability : {} ] ", node);
+    PrintAST {
+        size: repr.len(),
+        repr,
+        left_margin: 0,
+        right_margin: 0,
+        children: vec![],
     }
 }
 
@@ -784,6 +1168,31 @@ pub fn type_to_str(node: &ast::Type) -> PrintAST {
             right_margin: 0,
             children: vec![],
         },
+        ast::Type::Uint(bits) | ast::Type::Int(bits) => {
+            let name = if let ast::Type::Uint(_) = node {
+                "uint"
+            } else {
+                "int"
+            };
+            let repr = format!("[ type : {}{} ] ", name, bits);
+            PrintAST {
+                size: repr.len(),
+                repr,
+                left_margin: 0,
+                right_margin: 0,
+                children: vec![],
+            }
+        }
+        ast::Type::UserDefined(name) => {
+            let repr = format!("[ type : {} ] ", name);
+            PrintAST {
+                size: repr.len(),
+                repr,
+                left_margin: 0,
+                right_margin: 0,
+                children: vec![],
+            }
+        }
         ast::Type::Bytes32 => PrintAST {
             repr: String::from("[ type : bytes32 ] "),
             size: 19,
@@ -798,6 +1207,13 @@ pub fn type_to_str(node: &ast::Type) -> PrintAST {
             right_margin: 0,
             children: vec![],
         },
+        ast::Type::Field => PrintAST {
+            repr: String::from("[ type : field ] "),
+            size: 17,
+            left_margin: 0,
+            right_margin: 0,
+            children: vec![],
+        },
         ast::Type::Bytes => PrintAST {
             repr: String::from("[ type : bytes ] "),
             size: 17,
@@ -819,6 +1235,53 @@ pub fn type_to_str(node: &ast::Type) -> PrintAST {
             right_margin: 0,
             children: vec![],
         },
+        ast::Type::Array(element) => {
+            let element_type = type_to_str(element);
+            let repr = String::from("[ type : array ] ");
+            let size = usize::max(repr.len(), element_type.size);
+
+            let mut ast = PrintAST {
+                repr,
+                size,
+                left_margin: 0,
+                right_margin: 0,
+                children: vec![element_type],
+            };
+            ast.add_children_margin();
+            ast
+        }
+        ast::Type::FixedArray(element, length) => {
+            let children = vec![type_to_str(element), expr_to_str(&length.node)];
+            let repr = String::from("[ type : fixed array ] ");
+            let children_size = children.iter().fold(0, |v, child| v + child.size);
+            let size = usize::max(repr.len(), children_size);
+
+            let mut ast = PrintAST {
+                repr,
+                size,
+                left_margin: 0,
+                right_margin: 0,
+                children,
+            };
+            ast.add_children_margin();
+            ast
+        }
+        ast::Type::Mapping(key, value) => {
+            let children = vec![type_to_str(key), type_to_str(value)];
+            let repr = String::from("[ type : mapping ] ");
+            let children_size = children.iter().fold(0, |v, child| v + child.size);
+            let size = usize::max(repr.len(), children_size);
+
+            let mut ast = PrintAST {
+                repr,
+                size,
+                left_margin: 0,
+                right_margin: 0,
+                children,
+            };
+            ast.add_children_margin();
+            ast
+        }
     }
 }
 
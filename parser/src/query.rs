@@ -0,0 +1,329 @@
+//! Structural queries over ASTs, for custom lints and codemods.
+//!
+//! A query is a list of selectors separated by spaces, each matching a node
+//! inside the nodes matched by the previous one, like CSS descendant selectors:
+//!
+//! `ContractStatement[name=Token] FunctionCall[name=transfer]`
+//!
+//! A selector is the kind of the node as written in snapshots, where the
+//! `Statement` or `Expression` suffix can be left out, or `*` for any kind,
+//! followed by attributes which must all have the given value:
+//!
+//! - `name`: name of a declaration, identifier, member or called function
+//! - `operator`: operator of an assignment or operation, e.g. `AddAssign`
+//! - `value`: value of a number or string literal
+//! - `annotation`: annotation of a function or contract, e.g. `inline`
+//!
+//! Values can be quoted, e.g. `String[value="a b"]`. Nodes are found in order of
+//! appearance, without recursion, and carry their location.
+
+use crate::ast::{Expression, ExpressionType, Program, StatementType};
+use crate::complexity::{program_nodes, Node};
+use crate::snapshot::label;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryError {
+    pub error: String,
+    /// Offset in the query of the character which couldn't be parsed
+    pub position: usize,
+}
+
+impl fmt::Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} at offset {}", self.error, self.position)
+    }
+}
+
+const ATTRIBUTES: &[&str] = &["name", "operator", "value", "annotation"];
+
+/// Pattern matching a single node.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Selector {
+    /// Kind of the node, any kind if `None`
+    pub kind: Option<String>,
+    /// Attributes of the node with their values
+    pub attributes: Vec<(String, String)>,
+}
+
+impl Selector {
+    pub fn kind(kind: &str) -> Self {
+        Selector {
+            kind: Some(kind.to_string()),
+            attributes: vec![],
+        }
+    }
+
+    pub fn any() -> Self {
+        Selector::default()
+    }
+
+    pub fn with(mut self, attribute: &str, value: &str) -> Self {
+        self.attributes
+            .push((attribute.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn matches(&self, node: &Node) -> bool {
+        let label = label(node);
+        let node_kind = label.split(' ').next().unwrap();
+        let kind_matches = match &self.kind {
+            Some(kind) => {
+                node_kind == kind
+                    || node_kind == format!("{}Statement", kind)
+                    || node_kind == format!("{}Expression", kind)
+            }
+            None => true,
+        };
+        kind_matches
+            && self
+                .attributes
+                .iter()
+                .all(|(attribute, value)| has_attribute(node, attribute, value))
+    }
+}
+
+/// Selectors of nodes nested in each other, the last one matching the nodes found.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Query {
+    pub selectors: Vec<Selector>,
+}
+
+impl Query {
+    pub fn new(selectors: Vec<Selector>) -> Self {
+        Query { selectors }
+    }
+
+    pub fn parse(query: &str) -> Result<Self, QueryError> {
+        QueryParser {
+            chars: query.char_indices().collect(),
+            position: 0,
+            length: query.len(),
+        }
+        .parse()
+    }
+
+    /// Nodes of the trees rooted at `roots` matching the query, in order of appearance.
+    pub fn find<'a>(&self, roots: Vec<Node<'a>>) -> Vec<Node<'a>> {
+        let last = match self.selectors.len().checked_sub(1) {
+            Some(last) => last,
+            None => return vec![],
+        };
+        let mut found = vec![];
+        // Nodes with the number of selectors matched by their ancestors
+        let mut pending: Vec<(Node, usize)> =
+            roots.into_iter().rev().map(|root| (root, 0)).collect();
+        while let Some((node, matched)) = pending.pop() {
+            let matches = self.selectors[matched].matches(&node);
+            if matched == last && matches {
+                found.push(node);
+            }
+            let matched = if matched < last && matches {
+                matched + 1
+            } else {
+                matched
+            };
+            pending.extend(
+                node.children()
+                    .into_iter()
+                    .rev()
+                    .map(|child| (child, matched)),
+            );
+        }
+        found
+    }
+}
+
+/// Nodes of `program` matching `query`, e.g. `FunctionCall[name=transfer]`.
+pub fn query<'a>(program: &'a Program, query: &str) -> Result<Vec<Node<'a>>, QueryError> {
+    Ok(Query::parse(query)?.find(program_nodes(program)))
+}
+
+fn has_attribute(node: &Node, attribute: &str, value: &str) -> bool {
+    let actual = match attribute {
+        "name" => node_name(node),
+        "operator" => match node {
+            Node::Expression(expression) => match &expression.node {
+                ExpressionType::AssignExpression { operator, .. }
+                | ExpressionType::BinaryExpression { operator, .. }
+                | ExpressionType::UnaryExpression { operator, .. } => {
+                    Some(format!("{:?}", operator))
+                }
+                _ => None,
+            },
+            Node::Statement(_) => None,
+        },
+        "value" => match node {
+            Node::Expression(expression) => match &expression.node {
+                ExpressionType::Number { value, .. } => Some(value.to_string()),
+                ExpressionType::StringLiteral { value, .. } => Some(value.clone()),
+                _ => None,
+            },
+            Node::Statement(_) => None,
+        },
+        "annotation" => {
+            return match node {
+                Node::Statement(statement) => statement.node.annotation(value).is_some(),
+                Node::Expression(_) => false,
+            }
+        }
+        _ => None,
+    };
+    actual.as_deref() == Some(value)
+}
+
+fn node_name(node: &Node) -> Option<String> {
+    name_expression(node)?.node.identifier_name()
+}
+
+/// Identifier holding the name of `node`, see the `name` attribute.
+pub(crate) fn name_expression<'a>(node: &Node<'a>) -> Option<&'a Expression> {
+    match *node {
+        Node::Statement(statement) => match &statement.node {
+            StatementType::FunctionStatement {
+                function_name: name,
+                ..
+            }
+            | StatementType::ContractStatement {
+                contract_name: name,
+                ..
+            }
+            | StatementType::EventStatement {
+                event_name: name, ..
+            }
+            | StatementType::EnumStatement {
+                enum_name: name, ..
+            }
+            | StatementType::StructStatement {
+                struct_name: name, ..
+            }
+            | StatementType::ModuleStatement {
+                module_name: name, ..
+            }
+            | StatementType::InitializerStatement {
+                variable: Some(name),
+                ..
+            } => Some(name),
+            _ => None,
+        },
+        Node::Expression(expression) => match &expression.node {
+            ExpressionType::Identifier { .. } => Some(expression),
+            ExpressionType::MemberExpression { member, .. }
+            | ExpressionType::PathExpression { member, .. } => Some(member),
+            // Last segment of the called path, e.g. `transfer` of `token.transfer(..)`
+            ExpressionType::FunctionCallExpression { function_name, .. } => {
+                name_expression(&Node::Expression(function_name))
+            }
+            _ => None,
+        },
+    }
+}
+
+struct QueryParser {
+    chars: Vec<(usize, char)>,
+    position: usize,
+    length: usize,
+}
+
+impl QueryParser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).map(|(_, c)| *c)
+    }
+
+    fn offset(&self) -> usize {
+        self.chars
+            .get(self.position)
+            .map_or(self.length, |(offset, _)| *offset)
+    }
+
+    fn error<T>(&self, error: &str) -> Result<T, QueryError> {
+        Err(QueryError {
+            error: error.to_string(),
+            position: self.offset(),
+        })
+    }
+
+    fn skip_spaces(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.position += 1;
+        }
+    }
+
+    fn word(&mut self) -> String {
+        let mut word = String::new();
+        while let Some(c) = self.peek().filter(|c| c.is_alphanumeric() || *c == '_') {
+            word.push(c);
+            self.position += 1;
+        }
+        word
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), QueryError> {
+        if self.peek() != Some(expected) {
+            return self.error(&format!("expected `{}`", expected));
+        }
+        self.position += 1;
+        Ok(())
+    }
+
+    fn parse(mut self) -> Result<Query, QueryError> {
+        let mut selectors = vec![];
+        self.skip_spaces();
+        while self.peek().is_some() {
+            selectors.push(self.selector()?);
+            self.skip_spaces();
+        }
+        if selectors.is_empty() {
+            return self.error("empty query");
+        }
+        Ok(Query::new(selectors))
+    }
+
+    fn selector(&mut self) -> Result<Selector, QueryError> {
+        let mut selector = if self.peek() == Some('*') {
+            self.position += 1;
+            Selector::any()
+        } else {
+            let kind = self.word();
+            if kind.is_empty() {
+                return self.error("expected a node kind or `*`");
+            }
+            Selector::kind(&kind)
+        };
+        while self.peek() == Some('[') {
+            self.position += 1;
+            let start = self.position;
+            let attribute = self.word();
+            if !ATTRIBUTES.contains(&attribute.as_str()) {
+                self.position = start;
+                return self.error("expected `name`, `operator`, `value` or `annotation`");
+            }
+            self.expect('=')?;
+            let value = self.value()?;
+            self.expect(']')?;
+            selector = selector.with(&attribute, &value);
+        }
+        match self.peek() {
+            Some(c) if !c.is_whitespace() => self.error("unexpected character"),
+            _ => Ok(selector),
+        }
+    }
+
+    fn value(&mut self) -> Result<String, QueryError> {
+        let mut value = String::new();
+        if self.peek() == Some('"') {
+            self.position += 1;
+            while let Some(c) = self.peek().filter(|c| *c != '"') {
+                value.push(c);
+                self.position += 1;
+            }
+            self.expect('"')?;
+            return Ok(value);
+        }
+        while let Some(c) = self.peek().filter(|c| *c != ']') {
+            value.push(c);
+            self.position += 1;
+        }
+        Ok(value)
+    }
+}
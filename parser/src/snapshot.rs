@@ -0,0 +1,228 @@
+//! Canonical text of ASTs, for golden tests of the parser output.
+//!
+//! Each node is written on its own line, indented under its parent, with its kind
+//! and the attributes which aren't nodes themselves, e.g. `BinaryExpression Add`.
+//! The text depends on nothing but the AST: locations are written as `row:column`
+//! and can be left out, so that snapshots don't change when only the layout of
+//! the source does. The AST is walked without recursion, like in `complexity`.
+
+use crate::ast::{CfgPredicate, ContractKind, ExpressionType, Program, StatementType, Type};
+use crate::complexity::{program_nodes, Node};
+use crate::location::Location;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SnapshotConfig {
+    /// Writes the location of every node
+    pub locations: bool,
+}
+
+impl Default for SnapshotConfig {
+    fn default() -> Self {
+        SnapshotConfig { locations: true }
+    }
+}
+
+/// Canonical text of `program`.
+pub fn snapshot_program(program: &Program, config: &SnapshotConfig) -> String {
+    snapshot_nodes(program_nodes(program), config)
+}
+
+/// Canonical text of the trees rooted at `roots`, one line per node.
+pub fn snapshot_nodes(roots: Vec<Node>, config: &SnapshotConfig) -> String {
+    let mut snapshot = String::new();
+    let mut pending: Vec<(Node, usize)> = roots.into_iter().rev().map(|root| (root, 0)).collect();
+    while let Some((node, depth)) = pending.pop() {
+        snapshot.push_str(&"  ".repeat(depth));
+        snapshot.push_str(&label(&node));
+        if config.locations {
+            snapshot.push_str(&format!(" @{}", location(node.location())));
+        }
+        snapshot.push('\n');
+        pending.extend(
+            node.children()
+                .into_iter()
+                .rev()
+                .map(|child| (child, depth + 1)),
+        );
+    }
+    snapshot
+}
+
+/// Location as written in snapshots, e.g. `3:14`.
+pub fn location(location: Location) -> String {
+    format!("{}:{}", location.row(), location.column())
+}
+
+/// Kind of `node` with its attributes which aren't nodes.
+pub(crate) fn label(node: &Node) -> String {
+    match node {
+        Node::Statement(statement) => {
+            let annotations: String = statement
+                .node
+                .annotations()
+                .iter()
+                .map(|annotation| format!(" @{}", annotation.name))
+                .collect();
+            match &statement.node {
+                StatementType::FunctionStatement {
+                    visibility,
+                    mutability,
+                    ..
+                } => {
+                    let mut label = format!("FunctionStatement{}", annotations);
+                    if let Some(visibility) = visibility {
+                        label.push_str(&format!(" {}", visibility));
+                    }
+                    if let Some(mutability) = mutability {
+                        label.push_str(&format!(" {}", mutability));
+                    }
+                    label
+                }
+                StatementType::ContractStatement { kind, .. } => {
+                    let mut label = format!("ContractStatement{}", annotations);
+                    if *kind != ContractKind::Contract {
+                        label.push_str(&format!(" {}", kind));
+                    }
+                    label
+                }
+                StatementType::EventStatement { .. } => String::from("EventStatement"),
+                StatementType::UsingStatement { target, .. } => {
+                    format!("UsingStatement {}", type_name(target))
+                }
+                StatementType::EnumStatement { .. } => String::from("EnumStatement"),
+                StatementType::StructStatement { .. } => String::from("StructStatement"),
+                StatementType::ModuleStatement { .. } => String::from("ModuleStatement"),
+                StatementType::ConditionalStatement { condition, .. } => {
+                    format!("ConditionalStatement cfg({})", predicate(condition))
+                }
+                StatementType::ImportStatement { path } => format!("ImportStatement {:?}", path),
+                StatementType::InitializerStatement {
+                    variable_type,
+                    is_private,
+                    visibility,
+                    is_constant,
+                    data_location,
+                    ..
+                } => {
+                    let mut label = format!("InitializerStatement {}", type_name(variable_type));
+                    if *is_private {
+                        label.push_str(" private");
+                    }
+                    if let Some(visibility) = visibility {
+                        label.push_str(&format!(" {}", visibility));
+                    }
+                    if *is_constant {
+                        label.push_str(" constant");
+                    }
+                    if let Some(data_location) = data_location {
+                        label.push_str(&format!(" {:?}", data_location).to_lowercase());
+                    }
+                    label
+                }
+                StatementType::CompoundStatement { .. } => String::from("CompoundStatement"),
+                StatementType::MemberStatement { .. } => String::from("MemberStatement"),
+                StatementType::UncheckedStatement { .. } => String::from("UncheckedStatement"),
+                StatementType::DoWhileStatement { .. } => String::from("DoWhileStatement"),
+                StatementType::ReturnStatement { .. } => String::from("ReturnStatement"),
+                StatementType::Expression { .. } => String::from("Expression"),
+                StatementType::Error => String::from("Error"),
+            }
+        }
+        Node::Expression(expression) => match &expression.node {
+            ExpressionType::AssignExpression { operator, .. } => {
+                format!("AssignExpression {:?}", operator)
+            }
+            ExpressionType::BinaryExpression { operator, .. } => {
+                format!("BinaryExpression {:?}", operator)
+            }
+            ExpressionType::UnaryExpression { operator, .. } => {
+                format!("UnaryExpression {:?}", operator)
+            }
+            ExpressionType::TernaryExpression { .. } => String::from("TernaryExpression"),
+            ExpressionType::FunctionCallExpression { .. } => String::from("FunctionCallExpression"),
+            ExpressionType::MemberExpression { .. } => String::from("MemberExpression"),
+            ExpressionType::IndexExpression { .. } => String::from("IndexExpression"),
+            ExpressionType::PathExpression { .. } => String::from("PathExpression"),
+            ExpressionType::IfExpression { .. } => String::from("IfExpression"),
+            ExpressionType::MatchExpression { .. } => String::from("MatchExpression"),
+            ExpressionType::ForEachExpression { .. } => String::from("ForEachExpression"),
+            ExpressionType::Parameters { .. } => String::from("Parameters"),
+            ExpressionType::Arguments { .. } => String::from("Arguments"),
+            ExpressionType::Tuple { items } => {
+                // Items left out, e.g. the first one of `(, b)`
+                let holes: Vec<String> = items
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, item)| item.is_none())
+                    .map(|(i, _)| i.to_string())
+                    .collect();
+                if holes.is_empty() {
+                    String::from("Tuple")
+                } else {
+                    format!("Tuple holes={}", holes.join(","))
+                }
+            }
+            ExpressionType::TypeList { types } => {
+                let types: Vec<String> = types.iter().map(type_name).collect();
+                format!("TypeList ({})", types.join(", "))
+            }
+            ExpressionType::Number { value, radix } => format!("Number {}", radix.format(value)),
+            ExpressionType::Identifier { value } => format!("Identifier {}", value),
+            ExpressionType::StringLiteral { value, .. } => format!("StringLiteral {:?}", value),
+            ExpressionType::StructLiteral { .. } => String::from("StructLiteral"),
+            ExpressionType::Error => String::from("Error"),
+        },
+    }
+}
+
+/// Node and its descendants on a single line, e.g. `(BinaryExpression Mul (Number 2) (Identifier N))`.
+fn inline(node: Node) -> String {
+    let children = node.children();
+    if children.is_empty() {
+        return format!("({})", label(&node));
+    }
+    let children: Vec<String> = children.into_iter().map(inline).collect();
+    format!("({} {})", label(&node), children.join(" "))
+}
+
+/// Type as written in the source, with the length of a fixed array inline.
+pub fn type_name(typ: &Type) -> String {
+    match typ {
+        Type::Uint256 => String::from("uint256"),
+        Type::Int256 => String::from("int256"),
+        Type::Uint(bits) => format!("uint{}", bits),
+        Type::Int(bits) => format!("int{}", bits),
+        Type::Bytes32 => String::from("bytes32"),
+        Type::Bool => String::from("bool"),
+        Type::Field => String::from("field"),
+        Type::Bytes => String::from("bytes"),
+        Type::String => String::from("string"),
+        Type::Address => String::from("address"),
+        Type::Array(element) => format!("{}[]", type_name(element)),
+        Type::FixedArray(element, length) => {
+            format!(
+                "{}[{}]",
+                type_name(element),
+                inline(Node::Expression(length))
+            )
+        }
+        Type::UserDefined(name) => name.clone(),
+        Type::Mapping(key, value) => {
+            format!("mapping({} => {})", type_name(key), type_name(value))
+        }
+    }
+}
+
+fn predicate(predicate: &CfgPredicate) -> String {
+    let list = |predicates: &[CfgPredicate]| {
+        let predicates: Vec<String> = predicates.iter().map(self::predicate).collect();
+        predicates.join(", ")
+    };
+    match predicate {
+        CfgPredicate::Flag(name) => name.clone(),
+        CfgPredicate::Value { name, value } => format!("{} = {:?}", name, value),
+        CfgPredicate::Not(predicate) => format!("not({})", self::predicate(predicate)),
+        CfgPredicate::All(predicates) => format!("all({})", list(predicates)),
+        CfgPredicate::Any(predicates) => format!("any({})", list(predicates)),
+    }
+}
@@ -1,3 +1,4 @@
+use crate::ast::Radix;
 use num_bigint::BigUint;
 
 /// Zoker source code can be tokenized in a sequence of these tokens.
@@ -52,7 +53,12 @@ pub enum Tok {
     // Static size
     Uint256,
     Int256,
+    // `uint8` to `uint248`
+    SizedUint { bits: u16 },
+    // `int8` to `int248`
+    SizedInt { bits: u16 },
     Bytes32,
+    Field,
     Bool,
     // Dynamic size
     Bytes,
@@ -62,27 +68,64 @@ pub enum Tok {
     // Keyword
     Function,
     Contract,
+    Interface,
+    Library,
+    Is,
+    Using,
+    Event,
+    Enum,
+    Struct,
+    Module,
+    Import,
+    Constant,
+    Match,
     Memory,
     Storage,
+    Transient,
     If,
     Else,
     For,
+    Do,
+    While,
     In,
     Returns,
     Return,
     Private,
+    Public,
+    Internal,
+    External,
+    Pure,
+    View,
+    Payable,
+    Unchecked,
+    Mapping,
+    Constructor,
     // Mark
     LPar,
     RPar,
+    LSqb,
+    RSqb,
     LBrace,
     RBrace,
     Semi,
     Comma,
     Question,
     Colon,
+    // `#` of an attribute, e.g. `#[cfg(debug)]`
+    Hash,
+    // `@` of an annotation, e.g. `@inline`
+    At,
+    // `::` between a module and its members
+    ColonColon,
+    Dot,
+    // `=>` between the pattern and the body of a match arm
+    FatArrow,
     // variable
-    Num { number: BigUint },
+    Num { number: BigUint, radix: Radix },
     Identifier { name: String },
-    Literal { literal: String },
+    Literal { literal: String, raw: String },
+    // Line of a doc comment, e.g. `/// Sends tokens`, or a whole `/** ... */` block,
+    // without the delimiters
+    DocComment { text: String },
     EOF,
 }
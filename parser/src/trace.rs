@@ -0,0 +1,372 @@
+//! Step-by-step trace of parsing, to diagnose why a construct parses unexpectedly.
+//!
+//! Tracing is enabled with the `parser::parse_*_traced` functions, or for every parse
+//! by setting the `ZOKER_PARSE_TRACE` environment variable, which prints to stderr.
+
+use crate::ast;
+use crate::location::Location;
+use crate::token::Tok;
+use std::env;
+use std::fmt;
+
+pub const TRACE_ENV: &str = "ZOKER_PARSE_TRACE";
+
+pub fn is_enabled() -> bool {
+    env::var(TRACE_ENV).is_ok_and(|value| !value.is_empty() && value != "0")
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TraceEvent {
+    /// Token handed from the lexer to the parser
+    Token { location: Location, token: Tok },
+    /// AST node built by a reduction, e.g. `Expression::BinaryExpression`
+    Node { location: Location, node: String },
+}
+
+impl fmt::Display for TraceEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TraceEvent::Token { location, token } => {
+                write!(
+                    f,
+                    "token  {:>3}:{:<3} {:?}",
+                    location.row(),
+                    location.column(),
+                    token
+                )
+            }
+            TraceEvent::Node { location, node } => {
+                write!(
+                    f,
+                    "reduce {:>3}:{:<3} {}",
+                    location.row(),
+                    location.column(),
+                    node
+                )
+            }
+        }
+    }
+}
+
+/// Tokens in the order the parser consumed them, followed by the nodes of the AST
+/// in the order they were reduced, children before their parent.
+#[derive(Debug, Default, PartialEq)]
+pub struct ParseTrace {
+    pub events: Vec<TraceEvent>,
+}
+
+impl ParseTrace {
+    pub fn new() -> Self {
+        ParseTrace::default()
+    }
+
+    pub fn token(&mut self, location: Location, token: &Tok) {
+        self.events.push(TraceEvent::Token {
+            location,
+            token: token.clone(),
+        });
+    }
+
+    fn node(&mut self, location: Location, group: &str, node: &str) {
+        self.events.push(TraceEvent::Node {
+            location,
+            node: format!("{}::{}", group, node),
+        });
+    }
+}
+
+impl fmt::Display for ParseTrace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for event in &self.events {
+            writeln!(f, "{}", event)?;
+        }
+        Ok(())
+    }
+}
+
+/// AST nodes that can be replayed into a `ParseTrace`.
+pub trait Traceable {
+    fn trace(&self, trace: &mut ParseTrace);
+}
+
+impl Traceable for ast::Program {
+    fn trace(&self, trace: &mut ParseTrace) {
+        match self {
+            ast::Program::GlobalStatements(stmts) => {
+                for stmt in stmts {
+                    stmt.trace(trace);
+                }
+                let location = stmts.first().map_or_else(Location::default, |s| s.location);
+                trace.node(location, "Program", "GlobalStatements");
+            }
+        }
+    }
+}
+
+impl Traceable for ast::Statement {
+    fn trace(&self, trace: &mut ParseTrace) {
+        let name = match &self.node {
+            ast::StatementType::FunctionStatement {
+                annotations,
+                function_name,
+                parameters,
+                statement,
+                returns,
+                ..
+            } => {
+                for argument in annotations.iter().flat_map(|a| &a.arguments) {
+                    argument.trace(trace);
+                }
+                function_name.trace(trace);
+                parameters.trace(trace);
+                if let Some(returns) = returns {
+                    returns.trace(trace);
+                }
+                if let Some(statement) = statement {
+                    statement.trace(trace);
+                }
+                "FunctionStatement"
+            }
+            ast::StatementType::ContractStatement {
+                annotations,
+                contract_name,
+                bases,
+                members,
+                ..
+            } => {
+                for argument in annotations.iter().flat_map(|a| &a.arguments) {
+                    argument.trace(trace);
+                }
+                contract_name.trace(trace);
+                for base in bases {
+                    base.trace(trace);
+                }
+                members.trace(trace);
+                "ContractStatement"
+            }
+            ast::StatementType::EventStatement {
+                event_name,
+                parameters,
+            } => {
+                event_name.trace(trace);
+                parameters.trace(trace);
+                "EventStatement"
+            }
+            ast::StatementType::UsingStatement { library, .. } => {
+                library.trace(trace);
+                "UsingStatement"
+            }
+            ast::StatementType::EnumStatement {
+                enum_name,
+                variants,
+            } => {
+                enum_name.trace(trace);
+                for variant in variants {
+                    variant.trace(trace);
+                }
+                "EnumStatement"
+            }
+            ast::StatementType::StructStatement {
+                struct_name,
+                fields,
+            } => {
+                struct_name.trace(trace);
+                for field in fields {
+                    field.trace(trace);
+                }
+                "StructStatement"
+            }
+            ast::StatementType::ModuleStatement {
+                module_name,
+                members,
+            } => {
+                module_name.trace(trace);
+                for member in members {
+                    member.trace(trace);
+                }
+                "ModuleStatement"
+            }
+            ast::StatementType::InitializerStatement {
+                variable, default, ..
+            } => {
+                if let Some(variable) = variable {
+                    variable.trace(trace);
+                }
+                if let Some(default) = default {
+                    default.trace(trace);
+                }
+                "InitializerStatement"
+            }
+            ast::StatementType::CompoundStatement {
+                statements,
+                return_value,
+            } => {
+                for stmt in statements {
+                    stmt.trace(trace);
+                }
+                if let Some(value) = return_value {
+                    value.trace(trace);
+                }
+                "CompoundStatement"
+            }
+            ast::StatementType::MemberStatement { statements } => {
+                for stmt in statements {
+                    stmt.trace(trace);
+                }
+                "MemberStatement"
+            }
+            ast::StatementType::ConditionalStatement { statement, .. } => {
+                statement.trace(trace);
+                "ConditionalStatement"
+            }
+            ast::StatementType::UncheckedStatement { statement } => {
+                statement.trace(trace);
+                "UncheckedStatement"
+            }
+            ast::StatementType::DoWhileStatement {
+                statement,
+                condition,
+            } => {
+                statement.trace(trace);
+                condition.trace(trace);
+                "DoWhileStatement"
+            }
+            ast::StatementType::ReturnStatement { ret } => {
+                if let Some(ret) = ret {
+                    ret.trace(trace);
+                }
+                "ReturnStatement"
+            }
+            ast::StatementType::Expression { expression } => {
+                expression.trace(trace);
+                "Expression"
+            }
+            ast::StatementType::ImportStatement { .. } => "ImportStatement",
+            ast::StatementType::Error => "Error",
+        };
+        trace.node(self.location, "Statement", name);
+    }
+}
+
+impl Traceable for ast::Expression {
+    fn trace(&self, trace: &mut ParseTrace) {
+        let name = match &self.node {
+            ast::ExpressionType::AssignExpression { left, right, .. } => {
+                left.trace(trace);
+                right.trace(trace);
+                "AssignExpression"
+            }
+            ast::ExpressionType::BinaryExpression { left, right, .. } => {
+                left.trace(trace);
+                right.trace(trace);
+                "BinaryExpression"
+            }
+            ast::ExpressionType::TernaryExpression {
+                condition,
+                expr1,
+                expr2,
+            } => {
+                condition.trace(trace);
+                expr1.trace(trace);
+                expr2.trace(trace);
+                "TernaryExpression"
+            }
+            ast::ExpressionType::FunctionCallExpression {
+                function_name,
+                arguments,
+            } => {
+                function_name.trace(trace);
+                arguments.trace(trace);
+                "FunctionCallExpression"
+            }
+            ast::ExpressionType::MemberExpression { expression, member } => {
+                expression.trace(trace);
+                member.trace(trace);
+                "MemberExpression"
+            }
+            ast::ExpressionType::PathExpression { module, member } => {
+                module.trace(trace);
+                member.trace(trace);
+                "PathExpression"
+            }
+            ast::ExpressionType::IndexExpression { expression, index } => {
+                expression.trace(trace);
+                index.trace(trace);
+                "IndexExpression"
+            }
+            ast::ExpressionType::IfExpression {
+                condition,
+                if_statement,
+                else_statement,
+            } => {
+                condition.trace(trace);
+                if_statement.trace(trace);
+                if let Some(stmt) = else_statement {
+                    stmt.trace(trace);
+                }
+                "IfExpression"
+            }
+            ast::ExpressionType::MatchExpression { value, arms } => {
+                value.trace(trace);
+                for arm in arms {
+                    arm.pattern.trace(trace);
+                    arm.statement.trace(trace);
+                }
+                "MatchExpression"
+            }
+            ast::ExpressionType::ForEachExpression {
+                iterator,
+                vector,
+                statement,
+                else_statement,
+            } => {
+                iterator.trace(trace);
+                vector.trace(trace);
+                statement.trace(trace);
+                if let Some(stmt) = else_statement {
+                    stmt.trace(trace);
+                }
+                "ForEachExpression"
+            }
+            ast::ExpressionType::UnaryExpression { expression, .. } => {
+                expression.trace(trace);
+                "UnaryExpression"
+            }
+            ast::ExpressionType::Parameters { parameters } => {
+                for param in parameters {
+                    param.trace(trace);
+                }
+                "Parameters"
+            }
+            ast::ExpressionType::Arguments { arguments } => {
+                for arg in arguments {
+                    arg.trace(trace);
+                }
+                "Arguments"
+            }
+            ast::ExpressionType::Tuple { items } => {
+                for item in items.iter().flatten() {
+                    item.trace(trace);
+                }
+                "Tuple"
+            }
+            ast::ExpressionType::TypeList { .. } => "TypeList",
+            ast::ExpressionType::Number { .. } => "Number",
+            ast::ExpressionType::Identifier { .. } => "Identifier",
+            ast::ExpressionType::StringLiteral { .. } => "StringLiteral",
+            ast::ExpressionType::StructLiteral {
+                struct_name,
+                fields,
+            } => {
+                struct_name.trace(trace);
+                for field in fields {
+                    field.name.trace(trace);
+                    field.value.trace(trace);
+                }
+                "StructLiteral"
+            }
+            ast::ExpressionType::Error => "Error",
+        };
+        trace.node(self.location, "Expression", name);
+    }
+}
@@ -0,0 +1,591 @@
+//! Source text of ASTs, which parses back to the same tree.
+//!
+//! Layout and comments other than doc comments aren't kept: members are written one
+//! per line, blocks are indented by four spaces and operands are parenthesized
+//! only where the grammar requires it. Nodes left by error recovery are written
+//! as nothing, so the text of an AST with errors doesn't parse back.
+
+use crate::ast::{
+    Annotation, CfgPredicate, Expression, ExpressionType, Located, Operator, Program, Specifier,
+    Statement, StatementType, Type,
+};
+
+const INDENT: &str = "    ";
+
+/// Binding strength of expressions, an operand binding looser than its position
+/// allows is parenthesized.
+const LOOSEST: u8 = 0;
+const LOGICAL_OR: u8 = 1;
+const COMPARISON: u8 = 7;
+const SHIFT: u8 = 8;
+const ADDITION: u8 = 9;
+const UNARY: u8 = 12;
+const VALUE: u8 = 13;
+
+/// Source text of `program`, with a blank line between global statements.
+pub fn unparse_program(program: &Program) -> String {
+    let Program::GlobalStatements(statements) = program;
+    let statements: Vec<String> = statements
+        .iter()
+        .map(|statement| member(statement, 0))
+        .collect();
+    let mut source = statements.join("\n\n");
+    source.push('\n');
+    source
+}
+
+/// Source text of `statement` as written in a block, without the `;` ending it.
+pub fn unparse_statement(statement: &Statement) -> String {
+    self::statement(statement, 0)
+}
+
+/// Source text of `expression`.
+pub fn unparse_expression(expression: &Expression) -> String {
+    self::expression(expression, 0)
+}
+
+/// Type as written in the source, e.g. `mapping(address => uint256[2 * N])`.
+pub fn unparse_type(typ: &Type) -> String {
+    match typ {
+        Type::Uint256 => String::from("uint256"),
+        Type::Int256 => String::from("int256"),
+        Type::Uint(bits) => format!("uint{}", bits),
+        Type::Int(bits) => format!("int{}", bits),
+        Type::Bytes32 => String::from("bytes32"),
+        Type::Bool => String::from("bool"),
+        Type::Field => String::from("field"),
+        Type::Bytes => String::from("bytes"),
+        Type::String => String::from("string"),
+        Type::Address => String::from("address"),
+        Type::Array(element) => format!("{}[]", unparse_type(element)),
+        Type::FixedArray(element, length) => {
+            format!("{}[{}]", unparse_type(element), unparse_expression(length))
+        }
+        Type::UserDefined(name) => name.clone(),
+        Type::Mapping(key, value) => {
+            format!("mapping({} => {})", unparse_type(key), unparse_type(value))
+        }
+    }
+}
+
+fn indent(depth: usize) -> String {
+    INDENT.repeat(depth)
+}
+
+/// Declaration in a contract, a module or the global scope, followed by `;` if the grammar
+/// requires one there. Lines after the first are indented at `depth`.
+fn member(statement: &Statement, depth: usize) -> String {
+    let source = self::statement(statement, depth);
+    if needs_semicolon(&statement.node) {
+        format!("{};", source)
+    } else {
+        source
+    }
+}
+
+fn needs_semicolon(statement: &StatementType) -> bool {
+    match statement {
+        StatementType::ConditionalStatement { statement, .. } => needs_semicolon(&statement.node),
+        StatementType::FunctionStatement { .. }
+        | StatementType::ContractStatement { .. }
+        | StatementType::EnumStatement { .. }
+        | StatementType::StructStatement { .. }
+        | StatementType::ModuleStatement { .. }
+        | StatementType::ImportStatement { .. } => false,
+        _ => true,
+    }
+}
+
+fn statement(statement: &Statement, depth: usize) -> String {
+    match &statement.node {
+        StatementType::FunctionStatement {
+            annotations,
+            function_name,
+            parameters,
+            statement: body,
+            returns,
+            visibility,
+            mutability,
+            doc,
+        } => {
+            let mut source = header(doc, annotations, depth);
+            if statement.node.is_constructor() {
+                source.push_str("constructor");
+            } else {
+                source.push_str("function ");
+                source.push_str(&expression(function_name, depth));
+            }
+            source.push_str(&expression(parameters, depth));
+            if let Some(visibility) = visibility {
+                source.push_str(&format!(" {}", visibility));
+            }
+            if let Some(mutability) = mutability {
+                source.push_str(&format!(" {}", mutability));
+            }
+            if let Some(returns) = returns {
+                source.push_str(&format!(" returns {}", expression(returns, depth)));
+            }
+            match body {
+                Some(body) => source.push_str(&format!(" {}", self::statement(body, depth))),
+                None => source.push(';'),
+            }
+            source
+        }
+        StatementType::ContractStatement {
+            annotations,
+            kind,
+            contract_name,
+            bases,
+            members,
+            doc,
+        } => {
+            let mut source = header(doc, annotations, depth);
+            source.push_str(&format!("{} {}", kind, expression(contract_name, depth)));
+            if !bases.is_empty() {
+                source.push_str(&format!(" is {}", list(bases, depth)));
+            }
+            source.push(' ');
+            source.push_str(&self::statement(members, depth));
+            source
+        }
+        StatementType::EventStatement {
+            event_name,
+            parameters,
+        } => format!(
+            "event {}{}",
+            expression(event_name, depth),
+            expression(parameters, depth)
+        ),
+        StatementType::UsingStatement { library, target } => format!(
+            "using {} for {}",
+            expression(library, depth),
+            unparse_type(target)
+        ),
+        StatementType::EnumStatement {
+            enum_name,
+            variants,
+        } => format!(
+            "enum {} {{ {} }}",
+            expression(enum_name, depth),
+            list(variants, depth)
+        ),
+        StatementType::StructStatement {
+            struct_name,
+            fields,
+        } => format!(
+            "struct {} {}",
+            expression(struct_name, depth),
+            block(fields.iter().map(|field| member(field, depth + 1)), depth)
+        ),
+        StatementType::ModuleStatement {
+            module_name,
+            members,
+        } => format!(
+            "module {} {}",
+            expression(module_name, depth),
+            block(members.iter().map(|item| member(item, depth + 1)), depth)
+        ),
+        StatementType::ConditionalStatement {
+            condition,
+            statement,
+        } => format!(
+            "#[cfg({})]\n{}{}",
+            predicate(condition),
+            indent(depth),
+            self::statement(statement, depth)
+        ),
+        StatementType::ImportStatement { path } => format!("import {};", literal(path)),
+        StatementType::InitializerStatement {
+            variable_type,
+            is_private,
+            visibility,
+            is_constant,
+            data_location,
+            variable,
+            default,
+            doc,
+        } => {
+            let mut source = header(doc, &[], depth);
+            if *is_private {
+                source.push_str("private ");
+            }
+            source.push_str(&unparse_type(variable_type));
+            if let Some(visibility) = visibility {
+                source.push_str(&format!(" {}", visibility));
+            }
+            if *is_constant {
+                source.push_str(" constant");
+            }
+            if let Some(data_location) = data_location {
+                source.push_str(match data_location {
+                    Specifier::Memory => " memory",
+                    Specifier::Storage => " storage",
+                    Specifier::Transient => " transient",
+                });
+            }
+            if let Some(variable) = variable {
+                source.push_str(&format!(" {}", expression(variable, depth)));
+            }
+            if let Some(default) = default {
+                source.push_str(&format!(" = {}", expression(default, depth)));
+            }
+            source
+        }
+        StatementType::CompoundStatement {
+            statements,
+            return_value,
+        } => {
+            let mut lines: Vec<String> = statements
+                .iter()
+                .map(|statement| format!("{};", self::statement(statement, depth + 1)))
+                .collect();
+            if let Some(return_value) = return_value {
+                lines.push(expression(return_value, depth + 1));
+            }
+            block(lines.into_iter(), depth)
+        }
+        StatementType::MemberStatement { statements } => block(
+            statements
+                .iter()
+                .map(|statement| member(statement, depth + 1)),
+            depth,
+        ),
+        StatementType::UncheckedStatement { statement } => {
+            format!("unchecked {}", self::statement(statement, depth))
+        }
+        StatementType::DoWhileStatement {
+            statement,
+            condition,
+        } => format!(
+            "do {} while {}",
+            self::statement(statement, depth),
+            expression(condition, depth)
+        ),
+        StatementType::ReturnStatement { ret } => match ret {
+            Some(ret) => format!("return {}", expression(ret, depth)),
+            None => String::from("return"),
+        },
+        StatementType::Expression { expression } => self::expression(expression, depth),
+        StatementType::Error => String::new(),
+    }
+}
+
+/// Doc comment and annotations written on the lines before a declaration.
+fn header(doc: &Option<Located<String>>, annotations: &[Annotation], depth: usize) -> String {
+    let mut source = String::new();
+    if let Some(doc) = doc {
+        for line in doc.node.lines() {
+            if line.is_empty() {
+                source.push_str("///");
+            } else {
+                source.push_str(&format!("/// {}", line));
+            }
+            source.push('\n');
+            source.push_str(&indent(depth));
+        }
+    }
+    for annotation in annotations {
+        source.push_str(&format!("@{}", annotation.name));
+        if !annotation.arguments.is_empty() {
+            source.push_str(&format!("({})", list(&annotation.arguments, depth)));
+        }
+        source.push('\n');
+        source.push_str(&indent(depth));
+    }
+    source
+}
+
+/// Braces around `lines`, each on its own line and indented one level deeper than `depth`.
+fn block(lines: impl Iterator<Item = String>, depth: usize) -> String {
+    let lines: Vec<String> = lines
+        .map(|line| format!("{}{}\n", indent(depth + 1), line))
+        .collect();
+    if lines.is_empty() {
+        String::from("{}")
+    } else {
+        format!("{{\n{}{}}}", lines.concat(), indent(depth))
+    }
+}
+
+fn list(expressions: &[Expression], depth: usize) -> String {
+    let expressions: Vec<String> = expressions
+        .iter()
+        .map(|item| expression(item, depth))
+        .collect();
+    expressions.join(", ")
+}
+
+fn expression(expression: &Expression, depth: usize) -> String {
+    match &expression.node {
+        ExpressionType::AssignExpression {
+            left,
+            operator,
+            right,
+        } => format!(
+            "{} {} {}",
+            self::expression(left, depth),
+            symbol(operator),
+            self::expression(right, depth)
+        ),
+        ExpressionType::TernaryExpression {
+            condition,
+            expr1,
+            expr2,
+        } => format!(
+            "{} ? {} : {}",
+            operand(condition, LOGICAL_OR, depth),
+            self::expression(expr1, depth),
+            self::expression(expr2, depth)
+        ),
+        ExpressionType::BinaryExpression {
+            left,
+            operator,
+            right,
+        } => {
+            let precedence = precedence(&expression.node);
+            let (left_precedence, right_precedence) = match operator {
+                // Both operands of `**` are values, e.g. `(a + 1) ** 2`
+                Operator::Pow => (VALUE, VALUE),
+                // Comparisons don't chain with shifts on their right
+                Operator::Lt | Operator::Le | Operator::Gt | Operator::Ge => (COMPARISON, ADDITION),
+                _ => (precedence, precedence + 1),
+            };
+            format!(
+                "{} {} {}",
+                operand(left, left_precedence, depth),
+                symbol(operator),
+                operand(right, right_precedence, depth)
+            )
+        }
+        ExpressionType::UnaryExpression {
+            operator,
+            expression,
+        } => match operator {
+            Operator::PostfixPlusPlus | Operator::PostfixMinusMinus => {
+                format!("{}{}", operand(expression, VALUE, depth), symbol(operator))
+            }
+            _ => format!("{}{}", symbol(operator), operand(expression, VALUE, depth)),
+        },
+        ExpressionType::FunctionCallExpression {
+            function_name,
+            arguments,
+        } => format!(
+            "{}{}",
+            self::expression(function_name, depth),
+            self::expression(arguments, depth)
+        ),
+        ExpressionType::MemberExpression { expression, member } => format!(
+            "{}.{}",
+            operand(expression, VALUE, depth),
+            self::expression(member, depth)
+        ),
+        ExpressionType::IndexExpression { expression, index } => format!(
+            "{}[{}]",
+            operand(expression, VALUE, depth),
+            self::expression(index, depth)
+        ),
+        ExpressionType::PathExpression { module, member } => format!(
+            "{}::{}",
+            self::expression(module, depth),
+            self::expression(member, depth)
+        ),
+        ExpressionType::IfExpression {
+            condition,
+            if_statement,
+            else_statement,
+        } => {
+            let mut source = format!(
+                "if {} {}",
+                self::expression(condition, depth),
+                statement(if_statement, depth)
+            );
+            if let Some(else_statement) = else_statement {
+                source.push_str(&format!(" else {}", statement(else_statement, depth)));
+            }
+            source
+        }
+        ExpressionType::MatchExpression { value, arms } => {
+            let arms = arms.iter().map(|arm| {
+                format!(
+                    "{} => {},",
+                    self::expression(&arm.pattern, depth + 1),
+                    statement(&arm.statement, depth + 1)
+                )
+            });
+            format!(
+                "match {} {}",
+                self::expression(value, depth),
+                block(arms, depth)
+            )
+        }
+        ExpressionType::ForEachExpression {
+            iterator,
+            vector,
+            statement: body,
+            else_statement,
+        } => {
+            let mut source = format!(
+                "for {} in {} {}",
+                self::expression(iterator, depth),
+                self::expression(vector, depth),
+                statement(body, depth)
+            );
+            if let Some(else_statement) = else_statement {
+                source.push_str(&format!(" else {}", statement(else_statement, depth)));
+            }
+            source
+        }
+        ExpressionType::Parameters { parameters } => {
+            let parameters: Vec<String> = parameters
+                .iter()
+                .map(|parameter| statement(parameter, depth))
+                .collect();
+            format!("({})", parameters.join(", "))
+        }
+        ExpressionType::Arguments { arguments } => format!("({})", list(arguments, depth)),
+        ExpressionType::Tuple { items } => {
+            let items: Vec<String> = items
+                .iter()
+                .map(|item| {
+                    item.as_ref()
+                        .map_or_else(String::new, |item| self::expression(item, depth))
+                })
+                .collect();
+            format!("({})", items.join(", "))
+        }
+        ExpressionType::TypeList { types } => {
+            let types: Vec<String> = types.iter().map(unparse_type).collect();
+            format!("({})", types.join(", "))
+        }
+        ExpressionType::Number { value, radix } => radix.format(value),
+        ExpressionType::Identifier { value } => value.clone(),
+        ExpressionType::StringLiteral { value, .. } => literal(value),
+        ExpressionType::StructLiteral {
+            struct_name,
+            fields,
+        } => {
+            let fields: Vec<String> = fields
+                .iter()
+                .map(|field| {
+                    format!(
+                        "{}: {}",
+                        self::expression(&field.name, depth),
+                        self::expression(&field.value, depth)
+                    )
+                })
+                .collect();
+            format!(
+                "{}({{{}}})",
+                self::expression(struct_name, depth),
+                fields.join(", ")
+            )
+        }
+        ExpressionType::Error => String::new(),
+    }
+}
+
+/// `expression` in a position which takes expressions binding at least as tightly as `minimum`.
+fn operand(expression: &Expression, minimum: u8, depth: usize) -> String {
+    if precedence(&expression.node) < minimum {
+        format!("({})", self::expression(expression, depth))
+    } else {
+        self::expression(expression, depth)
+    }
+}
+
+fn precedence(expression: &ExpressionType) -> u8 {
+    match expression {
+        ExpressionType::BinaryExpression { operator, .. } => match operator {
+            Operator::Or => LOGICAL_OR,
+            Operator::And => 2,
+            Operator::BitOr => 3,
+            Operator::BitXor => 4,
+            Operator::BitAnd => 5,
+            Operator::Eq | Operator::NotEq => 6,
+            Operator::Lt | Operator::Le | Operator::Gt | Operator::Ge => COMPARISON,
+            Operator::LShift | Operator::RShift => SHIFT,
+            Operator::Add | Operator::Sub => ADDITION,
+            Operator::Mul | Operator::Div | Operator::Mod => 10,
+            _ => 11,
+        },
+        ExpressionType::UnaryExpression { .. } => UNARY,
+        ExpressionType::AssignExpression { .. }
+        | ExpressionType::TernaryExpression { .. }
+        | ExpressionType::IfExpression { .. }
+        | ExpressionType::MatchExpression { .. }
+        | ExpressionType::ForEachExpression { .. }
+        | ExpressionType::Tuple { .. } => LOOSEST,
+        _ => VALUE,
+    }
+}
+
+fn symbol(operator: &Operator) -> &'static str {
+    match operator {
+        Operator::Add | Operator::Plus => "+",
+        Operator::Sub | Operator::Minus => "-",
+        Operator::Mul => "*",
+        Operator::Div => "/",
+        Operator::Mod => "%",
+        Operator::Pow => "**",
+        Operator::Not => "!",
+        Operator::PrefixPlusPlus | Operator::PostfixPlusPlus => "++",
+        Operator::PrefixMinusMinus | Operator::PostfixMinusMinus => "--",
+        Operator::Assign => "=",
+        Operator::BitAndAssign => "&=",
+        Operator::BitXorAssign => "^=",
+        Operator::BitOrAssign => "|=",
+        Operator::LShiftAssign => "<<=",
+        Operator::RShiftAssign => ">>=",
+        Operator::AddAssign => "+=",
+        Operator::SubAssign => "-=",
+        Operator::MulAssign => "*=",
+        Operator::DivAssign => "/=",
+        Operator::ModAssign => "%=",
+        Operator::Lt => "<",
+        Operator::Le => "<=",
+        Operator::Gt => ">",
+        Operator::Ge => ">=",
+        Operator::Eq => "==",
+        Operator::NotEq => "!=",
+        Operator::And => "&&",
+        Operator::Or => "||",
+        Operator::BitAnd => "&",
+        Operator::BitXor => "^",
+        Operator::BitOr => "|",
+        Operator::LShift => "<<",
+        Operator::RShift => ">>",
+    }
+}
+
+/// String literal whose value is `value`, escaping what can't be written as is.
+fn literal(value: &str) -> String {
+    let mut literal = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '"' => literal.push_str("\\\""),
+            '\\' => literal.push_str("\\\\"),
+            '\n' => literal.push_str("\\n"),
+            '\t' => literal.push_str("\\t"),
+            '\r' => literal.push_str("\\r"),
+            '\0' => literal.push_str("\\0"),
+            c if c.is_control() => literal.push_str(&format!("\\u{{{:x}}}", c as u32)),
+            c => literal.push(c),
+        }
+    }
+    literal.push('"');
+    literal
+}
+
+fn predicate(predicate: &CfgPredicate) -> String {
+    let list = |predicates: &[CfgPredicate]| {
+        let predicates: Vec<String> = predicates.iter().map(self::predicate).collect();
+        predicates.join(", ")
+    };
+    match predicate {
+        CfgPredicate::Flag(name) => name.clone(),
+        CfgPredicate::Value { name, value } => format!("{} = {}", name, literal(value)),
+        CfgPredicate::Not(predicate) => format!("not({})", self::predicate(predicate)),
+        CfgPredicate::All(predicates) => format!("all({})", list(predicates)),
+        CfgPredicate::Any(predicates) => format!("any({})", list(predicates)),
+    }
+}
@@ -20,7 +20,7 @@ fn check_bin_expr_in_expr(
 
 fn check_number_in_expression(expression: ast::Expression) -> Result<BigUint, error::ParseError> {
     match expression.node {
-        ast::ExpressionType::Number { value: v } => Ok(v),
+        ast::ExpressionType::Number { value: v, .. } => Ok(v),
         _ => Err(error::ParseError {
             location: expression.location,
             error: error::ParseErrorType::InvalidToken,
@@ -104,6 +104,68 @@ fn test_lexer() {
     assert!(res.is_ok());
 }
 
+#[test]
+fn test_line_comments() {
+    let source = "// Test contract\n\
+                  contract Test { // members\n\
+                  \x20   uint i = 3; // initial value\n\
+                  \x20   // function f() {}\n\
+                  \x20   /// Doc comment\n\
+                  \x20   string s = \"a // b\";\n\
+                  } // end";
+    let program = parser::parse_program(source).unwrap();
+    let ast::Program::GlobalStatements(statements) = &program;
+    assert_eq!(statements.len(), 1);
+    let members = match &statements[0].node {
+        ast::StatementType::ContractStatement { members, .. } => match &members.node {
+            ast::StatementType::MemberStatement { statements } => statements,
+            _ => unreachable!(),
+        },
+        _ => unreachable!(),
+    };
+    assert_eq!(members.len(), 2);
+    assert_eq!(members[1].node.doc(), Some("Doc comment"));
+    assert_eq!(members[0].location.row(), 2);
+
+    // A single slash is still a division
+    let expr = parser::parse_expression("a / b // c").unwrap();
+    let (_, operator, _) = check_bin_expr_in_expr(expr).unwrap();
+    assert_eq!(operator, ast::Operator::Div);
+}
+
+#[test]
+fn test_block_comments() {
+    let source = "/* Test\n\
+                  \x20  contract */\n\
+                  contract Test {\n\
+                  \x20   uint i = /* three */ 3;\n\
+                  \x20   /* outer /* nested */ uint j = 4; */\n\
+                  \x20   /**/ uint k = 5;/*/ still a comment */\n\
+                  }";
+    let program = parser::parse_program(source).unwrap();
+    let ast::Program::GlobalStatements(statements) = &program;
+    let members = match &statements[0].node {
+        ast::StatementType::ContractStatement { members, .. } => match &members.node {
+            ast::StatementType::MemberStatement { statements } => statements,
+            _ => unreachable!(),
+        },
+        _ => unreachable!(),
+    };
+    assert_eq!(members.len(), 2);
+
+    let expr = parser::parse_expression("a /* b */ * c").unwrap();
+    let (_, operator, _) = check_bin_expr_in_expr(expr).unwrap();
+    assert_eq!(operator, ast::Operator::Mul);
+
+    // Reported at the opening of the outer comment
+    let err = parser::parse_program("uint a;\n  /* outer /* nested */\nuint b;").unwrap_err();
+    assert_eq!(
+        err.error,
+        error::ParseErrorType::Lexical(error::LexicalErrorType::UnterminatedComment)
+    );
+    assert_eq!((err.location.row(), err.location.column()), (1, 4));
+}
+
 #[test]
 fn test_bigint() {
     let bigint = parser::parse_expression("1_021_000_000_000_000_000_000_000_000_000_000_000_000_000_000_000_000_000_000_000_000_000_000_000_000_000_000_000_000");
@@ -111,3 +173,497 @@ fn test_bigint() {
     assert!(bigint.is_ok());
     assert_eq!(bigint.unwrap(), BigUint::from_str("1021000000000000000000000000000000000000000000000000000000000000000000000000000000000000").unwrap())
 }
+
+#[test]
+fn test_hex_number() {
+    let expr = parser::parse_expression("0xDEAD_beef + 255").unwrap();
+    let (left, _, right) = check_bin_expr_in_expr(expr).unwrap();
+    assert_eq!(
+        left.node,
+        ast::ExpressionType::Number {
+            value: BigUint::from(0xdead_beef_u32),
+            radix: ast::Radix::Hexadecimal,
+        }
+    );
+    assert_eq!(
+        right.node,
+        ast::ExpressionType::Number {
+            value: BigUint::from(255u32),
+            radix: ast::Radix::Decimal,
+        }
+    );
+    assert_eq!(right.location.column(), 15);
+    assert_eq!(
+        ast::Radix::Hexadecimal.format(&BigUint::from(255u32)),
+        "0xff"
+    );
+
+    assert!(parser::parse_expression("0x").is_err());
+    assert!(parser::parse_expression("0xfg").is_err());
+    assert!(parser::parse_expression("0x_1").is_err());
+}
+
+fn check_string_in_expression(expression: ast::Expression) -> (String, String) {
+    match expression.node {
+        ast::ExpressionType::StringLiteral { value, raw } => (value, raw),
+        _ => panic!("expected string literal"),
+    }
+}
+
+#[test]
+fn test_string_literal_escape() {
+    let expr = parser::parse_expression(r#""a\tb\n\"c\"""#).unwrap();
+    let (value, raw) = check_string_in_expression(expr);
+    assert_eq!(value, "a\tb\n\"c\"");
+    assert_eq!(raw, r#"a\tb\n\"c\""#);
+
+    let expr = parser::parse_expression(r#"'\x41\u{1F600}\u{e9}'"#).unwrap();
+    let (value, raw) = check_string_in_expression(expr);
+    assert_eq!(value, "A\u{1F600}\u{e9}");
+    assert_eq!(raw, r#"\x41\u{1F600}\u{e9}"#);
+}
+
+#[test]
+fn test_string_literal_error() {
+    let err = parser::parse_expression(r#"a = "ok\q""#).unwrap_err();
+    assert_eq!(
+        err.error,
+        error::ParseErrorType::Lexical(error::LexicalErrorType::InvalidEscape(String::from("\\q")))
+    );
+    assert_eq!(err.location.column(), 8);
+
+    assert!(parser::parse_expression(r#""\x4""#).is_err());
+    assert!(parser::parse_expression(r#""\u{110000}""#).is_err());
+    assert!(parser::parse_expression(r#""\u41""#).is_err());
+
+    let err = parser::parse_expression("\"abc").unwrap_err();
+    assert_eq!(
+        err.error,
+        error::ParseErrorType::Lexical(error::LexicalErrorType::UnterminatedString)
+    );
+}
+
+#[test]
+fn test_raw_and_multi_line_string_literal() {
+    let expr = parser::parse_expression(r#"r"C:\path\n""#).unwrap();
+    let (value, raw) = check_string_in_expression(expr);
+    assert_eq!(value, r"C:\path\n");
+    assert_eq!(raw, r"C:\path\n");
+
+    let source = "\"\"\"{\n  \"name\": \"transfer\"\n}\"\"\"";
+    let expr = parser::parse_expression(source).unwrap();
+    let (value, _) = check_string_in_expression(expr);
+    assert_eq!(value, "{\n  \"name\": \"transfer\"\n}");
+
+    let expr = parser::parse_expression(r#""""a""b\t""""#).unwrap();
+    let (value, raw) = check_string_in_expression(expr);
+    assert_eq!(value, "a\"\"b\t");
+    assert_eq!(raw, "a\"\"b\\t");
+
+    let expr = parser::parse_expression(r#""""#).unwrap();
+    assert_eq!(check_string_in_expression(expr).0, "");
+
+    let err = parser::parse_expression("a = \"\"\"abc\n\"\"").unwrap_err();
+    assert_eq!(
+        err.error,
+        error::ParseErrorType::Lexical(error::LexicalErrorType::UnterminatedString)
+    );
+    assert_eq!(err.location.column(), 5);
+}
+
+#[test]
+fn test_trailing_comma_ast() {
+    let expr = parser::parse_expression("add(1, 2,)").unwrap();
+    if let ast::ExpressionType::FunctionCallExpression { arguments, .. } = expr.node {
+        assert_matches::assert_matches!(
+            arguments.node,
+            ast::ExpressionType::Arguments { ref arguments } if arguments.len() == 2
+        );
+    } else {
+        panic!("expected function call");
+    }
+}
+
+#[test]
+fn test_error_tolerant_ast() {
+    let source = "contract Test {\n\
+                    uint a = ;\n\
+                    uint + ;\n\
+                    function f() {\n\
+                      g(1, +);\n\
+                      a = = 2;\n\
+                      a\n\
+                    }\n\
+                  }";
+    assert!(parser::parse_program(source).is_err());
+    let (program, errors) = parser::parse_program_tolerant(source);
+    assert_eq!(errors.len(), 4);
+    assert_eq!(errors[0].location.row(), 1);
+    let ast::Program::GlobalStatements(stmts) = program.unwrap();
+    let members = match &stmts[0].node {
+        ast::StatementType::ContractStatement { members, .. } => members,
+        _ => panic!("contract statement is expected"),
+    };
+    let members = match &members.node {
+        ast::StatementType::MemberStatement { statements } => statements,
+        _ => panic!("member statement is expected"),
+    };
+    assert_eq!(members.len(), 3);
+    match &members[0].node {
+        ast::StatementType::InitializerStatement {
+            default: Some(default),
+            ..
+        } => assert_eq!(default.node, ast::ExpressionType::Error),
+        _ => panic!("initializer is expected"),
+    }
+    assert_eq!(members[1].node, ast::StatementType::Error);
+
+    let (program, errors) = parser::parse_program_tolerant("contract Test {");
+    assert!(program.is_none());
+    assert_eq!(errors.len(), 1);
+}
+
+#[test]
+fn test_strict_parsing_does_not_recover() {
+    // Recovering from the blocks missing their `;` takes time cubic in their depth
+    let blocks = format!("{}{}", "{".repeat(16_000), "}".repeat(16_000));
+    let source = format!("contract Test {{ function f() {{ {} }} }}", blocks);
+    let start = std::time::Instant::now();
+    let error = parser::parse_program(&source).unwrap_err();
+    assert_eq!(
+        error.error,
+        error::ParseErrorType::UnrecognizedToken(
+            zoker_parser::token::Tok::RBrace,
+            Some(String::from("\";\""))
+        )
+    );
+    assert!(start.elapsed() < std::time::Duration::from_secs(5));
+}
+
+#[test]
+fn test_module_ast() {
+    let program = parser::parse_program(
+        "module math {\
+           uint constant MAX = 10;\
+           module bits { }\
+           function clamp(uint x) returns (uint) { return x; }\
+         }",
+    )
+    .unwrap();
+    let ast::Program::GlobalStatements(stmts) = program;
+    match &stmts[0].node {
+        ast::StatementType::ModuleStatement {
+            module_name,
+            members,
+        } => {
+            assert_eq!(module_name.node.identifier_name().unwrap(), "math");
+            assert_eq!(members.len(), 3);
+            assert_matches::assert_matches!(
+                members[1].node,
+                ast::StatementType::ModuleStatement { .. }
+            );
+        }
+        _ => panic!("module statement is expected"),
+    }
+
+    let expr = parser::parse_expression("math::bits::clamp(x)").unwrap();
+    if let ast::ExpressionType::FunctionCallExpression { function_name, .. } = expr.node {
+        assert_eq!(
+            function_name.node.module_path().unwrap(),
+            vec!["math", "bits", "clamp"]
+        );
+    } else {
+        panic!("expected function call");
+    }
+    // `::` doesn't split into colons
+    assert!(parser::parse_expression("c ? a::b : d").is_ok());
+}
+
+#[test]
+fn test_import_ast() {
+    let program = parser::parse_program("import \"std/math\"; contract Test { }").unwrap();
+    let ast::Program::GlobalStatements(stmts) = program;
+    assert_eq!(
+        stmts[0].node,
+        ast::StatementType::ImportStatement {
+            path: "std/math".to_string()
+        }
+    );
+    assert!(parser::parse_program("contract Test { import \"std/math\"; }").is_err());
+}
+
+#[test]
+fn test_cfg_attribute_ast() {
+    let program =
+        parser::parse_program("#[cfg(not(feature = \"testnet\"))] contract Test { }").unwrap();
+    let ast::Program::GlobalStatements(stmts) = program;
+    match &stmts[0].node {
+        ast::StatementType::ConditionalStatement {
+            condition,
+            statement,
+        } => {
+            assert_eq!(
+                *condition,
+                ast::CfgPredicate::Not(Box::new(ast::CfgPredicate::Value {
+                    name: "feature".to_string(),
+                    value: "testnet".to_string()
+                }))
+            );
+            assert_matches::assert_matches!(
+                statement.node,
+                ast::StatementType::ContractStatement { .. }
+            );
+        }
+        _ => panic!("conditional statement is expected"),
+    }
+
+    let err = parser::parse_program("#[cfg(either(a, b))] contract Test { }").unwrap_err();
+    assert_eq!(
+        err.error,
+        error::ParseErrorType::Lexical(error::LexicalErrorType::OtherError(
+            "unknown cfg predicate `either`".to_string()
+        ))
+    );
+    let err = parser::parse_program("#[inline(a)] contract Test { }").unwrap_err();
+    assert_eq!(
+        err.error,
+        error::ParseErrorType::Lexical(error::LexicalErrorType::OtherError(
+            "unknown attribute `inline`".to_string()
+        ))
+    );
+}
+
+#[test]
+fn test_annotation_ast() {
+    let program = parser::parse_program(
+        "@deprecated(\"use Token\") contract Test {\
+           @inline @test function f() { }\
+         }\
+         function g() { }",
+    )
+    .unwrap();
+    let ast::Program::GlobalStatements(stmts) = program;
+    let annotations = stmts[0].node.annotations();
+    assert_eq!(annotations.len(), 1);
+    assert_eq!(annotations[0].name, "deprecated");
+    assert_matches::assert_matches!(
+        &annotations[0].arguments[0].node,
+        ast::ExpressionType::StringLiteral { value, .. } if value == "use Token"
+    );
+    let members = match &stmts[0].node {
+        ast::StatementType::ContractStatement { members, .. } => members,
+        _ => panic!("contract statement is expected"),
+    };
+    let function = match &members.node {
+        ast::StatementType::MemberStatement { statements } => &statements[0],
+        _ => panic!("member statement is expected"),
+    };
+    let names: Vec<&str> = function
+        .node
+        .annotations()
+        .iter()
+        .map(|annotation| annotation.name.as_str())
+        .collect();
+    assert_eq!(names, vec!["inline", "test"]);
+    assert!(function
+        .node
+        .annotation("test")
+        .unwrap()
+        .arguments
+        .is_empty());
+    assert!(stmts[1].node.annotations().is_empty());
+
+    assert!(parser::parse_program("@inline uint x;").is_err());
+}
+
+#[test]
+fn test_visibility_ast() {
+    let program = parser::parse_program(
+        "contract Test {\
+           uint public total;\
+           Color private color;\
+           uint internal constant MAX = 10;\
+           uint count;\
+           function f(uint a) external returns (uint) { return a; }\
+           function g() private { }\
+           function h() { }\
+         }",
+    )
+    .unwrap();
+    let ast::Program::GlobalStatements(stmts) = program;
+    let members = match &stmts[0].node {
+        ast::StatementType::ContractStatement { members, .. } => members,
+        _ => panic!("contract statement is expected"),
+    };
+    let visibilities: Vec<Option<ast::Visibility>> = match &members.node {
+        ast::StatementType::MemberStatement { statements } => statements
+            .iter()
+            .map(|statement| match &statement.node {
+                ast::StatementType::InitializerStatement { visibility, .. }
+                | ast::StatementType::FunctionStatement { visibility, .. } => *visibility,
+                _ => panic!("member is expected"),
+            })
+            .collect(),
+        _ => panic!("member statement is expected"),
+    };
+    assert_eq!(
+        visibilities,
+        vec![
+            Some(ast::Visibility::Public),
+            Some(ast::Visibility::Private),
+            Some(ast::Visibility::Internal),
+            None,
+            Some(ast::Visibility::External),
+            Some(ast::Visibility::Private),
+            None,
+        ]
+    );
+
+    assert!(parser::parse_program("contract Test { function f() { } public }").is_err());
+}
+
+#[test]
+fn test_mutability_ast() {
+    let program = parser::parse_program(
+        "contract Test {\
+           function f() public pure { }\
+           function g() view { }\
+           function h() external payable { }\
+           function i() { }\
+         }",
+    )
+    .unwrap();
+    let ast::Program::GlobalStatements(stmts) = program;
+    let members = match &stmts[0].node {
+        ast::StatementType::ContractStatement { members, .. } => members,
+        _ => panic!("contract statement is expected"),
+    };
+    let mutabilities: Vec<Option<ast::Mutability>> = match &members.node {
+        ast::StatementType::MemberStatement { statements } => statements
+            .iter()
+            .map(|statement| match &statement.node {
+                ast::StatementType::FunctionStatement { mutability, .. } => *mutability,
+                _ => panic!("function statement is expected"),
+            })
+            .collect(),
+        _ => panic!("member statement is expected"),
+    };
+    assert_eq!(
+        mutabilities,
+        vec![
+            Some(ast::Mutability::Pure),
+            Some(ast::Mutability::View),
+            Some(ast::Mutability::Payable),
+            None,
+        ]
+    );
+
+    // The visibility comes first
+    assert!(parser::parse_program("contract Test { function f() pure public { } }").is_err());
+}
+
+#[test]
+fn test_destructuring_ast() {
+    let expression = parser::parse_expression("(q, _, ) = divmod(a, b)").unwrap();
+    let (left, operator) = match expression.node {
+        ast::ExpressionType::AssignExpression { left, operator, .. } => (left, operator),
+        _ => panic!("assign expression is expected"),
+    };
+    assert_eq!(operator, ast::Operator::Assign);
+    let items = match left.node {
+        ast::ExpressionType::Tuple { items } => items,
+        _ => panic!("tuple is expected"),
+    };
+    assert_eq!(items.len(), 3);
+    assert_eq!(
+        items[0].as_ref().unwrap().node.identifier_name().unwrap(),
+        "q"
+    );
+    assert!(items[1].as_ref().unwrap().node.is_placeholder());
+    assert!(items[2].is_none());
+
+    // Only plain assignments destructure
+    assert!(parser::parse_expression("(a, b) += g()").is_err());
+}
+
+#[test]
+fn test_interface_ast() {
+    let program = parser::parse_program(
+        "interface IToken {\
+           function transfer(address to, uint amount) external returns (bool);\
+         }\
+         contract Token is IToken, IOwned { }",
+    )
+    .unwrap();
+    let ast::Program::GlobalStatements(stmts) = program;
+    let contracts: Vec<(ast::ContractKind, Vec<String>, &ast::Statement)> = stmts
+        .iter()
+        .map(|statement| match &statement.node {
+            ast::StatementType::ContractStatement {
+                kind,
+                bases,
+                members,
+                ..
+            } => (
+                *kind,
+                bases
+                    .iter()
+                    .map(|base| base.node.identifier_name().unwrap())
+                    .collect(),
+                members.as_ref(),
+            ),
+            _ => panic!("contract statement is expected"),
+        })
+        .collect();
+    assert_eq!(contracts[0].0, ast::ContractKind::Interface);
+    assert!(contracts[0].1.is_empty());
+    assert_eq!(contracts[1].0, ast::ContractKind::Contract);
+    assert_eq!(contracts[1].1, vec!["IToken", "IOwned"]);
+    match &contracts[0].2.node {
+        ast::StatementType::MemberStatement { statements } => match &statements[0].node {
+            ast::StatementType::FunctionStatement {
+                statement,
+                visibility,
+                ..
+            } => {
+                assert!(statement.is_none());
+                assert_eq!(*visibility, Some(ast::Visibility::External));
+            }
+            _ => panic!("function statement is expected"),
+        },
+        _ => panic!("member statement is expected"),
+    }
+}
+
+#[test]
+fn test_library_ast() {
+    let program = parser::parse_program(
+        "library SafeMath {\
+           function add(uint256 a, uint256 b) internal returns (uint256) { return a + b; }\
+         }\
+         contract Token {\
+           using SafeMath for uint256;\
+         }",
+    )
+    .unwrap();
+    let ast::Program::GlobalStatements(stmts) = program;
+    match &stmts[0].node {
+        ast::StatementType::ContractStatement { kind, .. } => {
+            assert_eq!(*kind, ast::ContractKind::Library);
+        }
+        _ => panic!("contract statement is expected"),
+    }
+    match &stmts[1].node {
+        ast::StatementType::ContractStatement { members, .. } => match &members.node {
+            ast::StatementType::MemberStatement { statements } => match &statements[0].node {
+                ast::StatementType::UsingStatement { library, target } => {
+                    assert_eq!(library.node.identifier_name().unwrap(), "SafeMath");
+                    assert_eq!(*target, ast::Type::Uint256);
+                }
+                _ => panic!("using statement is expected"),
+            },
+            _ => panic!("member statement is expected"),
+        },
+        _ => panic!("contract statement is expected"),
+    }
+}
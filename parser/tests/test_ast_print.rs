@@ -144,6 +144,14 @@ fn test_print_for_each_statement_ast2() {
     assert_eq!(ast.print_ast(), "                                         [ AssignExpression ]                                          \n[ Identifier : sum ] [ assign-op : += ]                       [ For Expression ]                       \n                                        [ Identifier : i ] [ Identifier : vec ] [ Compound Statement ] \n                                                                                  [ Identifier : i ]   \n");
 }
 
+#[test]
+fn test_print_do_while_statement_ast1() {
+    let stmt = parser::parse_statement("do { i += 1; } while (i < n)").unwrap();
+
+    let ast = print::stmt_to_str(&stmt.node);
+    assert_eq!(ast.print_ast(), "                                           [ Do-while Statement ]                                             \n               [ Compound Statement ]                                  [ BinaryExpression ]                   \n                [ AssignExpression ]                 [ Identifier : i ] [ compare-op : < ] [ Identifier : n ] \n[ Identifier : i ] [ assign-op : += ] [ Number : 1 ]                                                          \n");
+}
+
 #[test]
 fn test_print_contract_statement_ast1() {
     let program =
@@ -0,0 +1,51 @@
+use zoker_parser::codemod::{apply, Action, CodemodError, Rule};
+use zoker_parser::location::Location;
+
+#[test]
+fn test_codemod() {
+    let source = "contract Token {\n\
+         \x20   function digest(bytes data) returns (bytes32) { return sha3(data); }\n\
+         \x20   function legacy() {}\n\
+         }\n\
+         function sha3x() { sha3x(); }";
+    let rules = vec![
+        Rule::new(
+            "FunctionCall[name=sha3]",
+            Action::Rename("keccak256".to_string()),
+        )
+        .unwrap(),
+        Rule::new(
+            "Contract[name=Token] Function[name=legacy]",
+            Action::InsertBefore("@deprecated ".to_string()),
+        )
+        .unwrap(),
+    ];
+    assert_eq!(
+        apply(&rules, source).unwrap(),
+        "contract Token {\n\
+         \x20   function digest(bytes data) returns (bytes32) { return keccak256(data); }\n\
+         \x20   @deprecated function legacy() {}\n\
+         }\n\
+         function sha3x() { sha3x(); }"
+    );
+
+    let rules =
+        vec![Rule::new("Number[value=10]", Action::ReplaceToken("20".to_string())).unwrap()];
+    assert_eq!(
+        apply(&rules, "uint constant A = 10;").unwrap(),
+        "uint constant A = 20;"
+    );
+
+    let rules = vec![
+        Rule::new("Identifier[name=a]", Action::Rename("b".to_string())).unwrap(),
+        Rule::new("*[name=a]", Action::Rename("c".to_string())).unwrap(),
+    ];
+    assert_eq!(
+        apply(&rules, "function f(uint a) { a; }"),
+        Err(CodemodError::Conflict(Location::new(0, 17)))
+    );
+    assert!(matches!(
+        apply(&rules, "function f( {"),
+        Err(CodemodError::Parse(_))
+    ));
+}
@@ -0,0 +1,73 @@
+use zoker_parser::complexity::{measure, program_nodes, Complexity, Node};
+use zoker_parser::config::{ParseConfig, ParseLimit};
+use zoker_parser::error::ParseErrorType;
+use zoker_parser::parser;
+
+#[test]
+fn test_measure() {
+    let expression = parser::parse_expression("a + b * c").unwrap();
+    assert_eq!(
+        measure(vec![Node::Expression(&expression)]),
+        Complexity { depth: 3, nodes: 5 }
+    );
+
+    let program = parser::parse_program("contract Test { uint total; }").unwrap();
+    // Contract, its name, its members, the initializer and the variable name
+    assert_eq!(
+        measure(program_nodes(&program)),
+        Complexity { depth: 4, nodes: 5 }
+    );
+}
+
+#[test]
+fn test_program_too_complex() {
+    let config = ParseConfig {
+        max_ast_depth: 2,
+        ..ParseConfig::default()
+    };
+    let error = parser::parse_expression_with("a + b * c", &config).unwrap_err();
+    assert_eq!(
+        error.error,
+        ParseErrorType::ProgramTooComplex {
+            limit: ParseLimit::AstDepth,
+            max: 2,
+        }
+    );
+    // `b` is the first node too deep
+    assert_eq!(error.location.column(), 5);
+
+    let config = ParseConfig {
+        max_ast_nodes: 4,
+        ..ParseConfig::default()
+    };
+    let error = parser::parse_expression_with("a + b * c", &config).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "number of nodes of the AST exceeds the limit of 4"
+    );
+
+    // Long chains of operators nest without parentheses
+    let source = format!(
+        "contract Test {{ function f(uint a) {{ a = {}; }} }}",
+        vec!["a"; 5_000].join(" + ")
+    );
+    let config = ParseConfig {
+        max_expression_depth: 10_000,
+        ..ParseConfig::default()
+    };
+    let error = parser::parse_program_with(&source, &config).unwrap_err();
+    assert_eq!(
+        error.error,
+        ParseErrorType::ProgramTooComplex {
+            limit: ParseLimit::AstDepth,
+            max: 1_000,
+        }
+    );
+
+    // Rejected before the parser builds an AST too deep to drop
+    for operator in &[" = a", " + a", ".a"] {
+        let source = format!("return a{}", operator.repeat(99_000));
+        let error = parser::parse_statement_with(&source, &ParseConfig::default()).unwrap_err();
+        assert_eq!(error.to_string(), "nesting depth exceeds the limit of 256");
+    }
+}
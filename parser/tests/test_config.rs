@@ -0,0 +1,68 @@
+use zoker_parser::config::{ParseConfig, ParseLimit};
+use zoker_parser::error::{LexicalErrorType, ParseErrorType};
+use zoker_parser::parser;
+
+fn exceeded(source: &str, config: &ParseConfig) -> (ParseLimit, usize) {
+    match parser::parse_program_with(source, config)
+        .unwrap_err()
+        .error
+    {
+        ParseErrorType::Lexical(LexicalErrorType::LimitExceeded { limit, max }) => (limit, max),
+        error => panic!("unexpected error {:?}", error),
+    }
+}
+
+#[test]
+fn test_parse_limits() {
+    let source = "contract Test { function f(uint a) { a = (a + 1) * 2; } }";
+    let config = ParseConfig::default();
+    assert!(parser::parse_program_with(source, &config).is_ok());
+
+    let config = ParseConfig {
+        max_expression_depth: 2,
+        ..ParseConfig::default()
+    };
+    assert_eq!(exceeded(source, &config), (ParseLimit::ExpressionDepth, 2));
+    let config = ParseConfig {
+        max_source_size: 16,
+        ..ParseConfig::default()
+    };
+    assert_eq!(exceeded(source, &config), (ParseLimit::SourceSize, 16));
+    let config = ParseConfig {
+        max_identifiers: 3,
+        ..ParseConfig::default()
+    };
+    let error = parser::parse_program_with(source, &config).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "number of identifiers exceeds the limit of 3"
+    );
+
+    // Adversarial nesting is rejected before it reaches the parser
+    let nested = format!("{}1{}", "(".repeat(100_000), ")".repeat(100_000));
+    let error = parser::parse_expression_with(&nested, &ParseConfig::default()).unwrap_err();
+    assert_eq!(
+        error.error,
+        ParseErrorType::Lexical(LexicalErrorType::LimitExceeded {
+            limit: ParseLimit::ExpressionDepth,
+            max: 256,
+        })
+    );
+    assert_eq!(error.location.column(), 257);
+
+    // Chains of operators nest as deep as brackets
+    let config = ParseConfig {
+        max_expression_depth: 3,
+        ..ParseConfig::default()
+    };
+    let chain = "contract Test { function f(uint a) { a = a + a * a; } }";
+    assert_eq!(exceeded(chain, &config), (ParseLimit::ExpressionDepth, 3));
+    let chain = "contract Test { function f(uint a) { a = a; a = a; } }";
+    assert!(parser::parse_program_with(chain, &config).is_ok());
+    let separated = "f(a + b, a + b); a = a + b; ".repeat(1_000);
+    let source = format!(
+        "contract Test {{ function f(uint a, uint b) {{ {} }} }}",
+        separated
+    );
+    assert!(parser::parse_program_with(&source, &ParseConfig::default()).is_ok());
+}
@@ -0,0 +1,35 @@
+use zoker_parser::coverage::GrammarCoverage;
+
+#[test]
+fn test_grammar_coverage() {
+    let mut coverage = GrammarCoverage::new();
+    coverage
+        .record_source(
+            "contract Test { uint[] storage a; function f(uint b) returns (uint) { b += 1; b } }",
+        )
+        .unwrap();
+    coverage
+        .record_source("contract Other { function g() { if a < 1 { 1 } } }")
+        .unwrap();
+    assert!(coverage.record_source("contract {").is_err());
+
+    let report = coverage.report();
+    assert!(report
+        .covered
+        .contains(&("Statement::FunctionStatement".to_string(), 2)));
+    assert!(report.covered.contains(&("Type::Array".to_string(), 1)));
+    assert!(report
+        .covered
+        .contains(&("Operator::AddAssign".to_string(), 1)));
+    assert!(report
+        .covered
+        .contains(&("Specifier::Storage".to_string(), 1)));
+    assert!(report
+        .uncovered
+        .contains(&"Expression::TernaryExpression".to_string()));
+    assert!(report.uncovered.contains(&"Specifier::Memory".to_string()));
+    assert!(report.ratio() > 0.0 && report.ratio() < 1.0);
+    assert!(report
+        .to_string()
+        .starts_with(&format!("grammar coverage: {}/", report.covered.len())));
+}
@@ -0,0 +1,116 @@
+use zoker_parser::ast::{Program, StatementType};
+use zoker_parser::docgen::contract_docs;
+use zoker_parser::parser;
+
+const SOURCE: &str = "/// Fungible token\n\
+     /// with a fixed supply\n\
+     contract Token {\n\
+     \x20   /// Total number of tokens\n\
+     \x20   uint supply;\n\
+     \x20   private address owner;\n\
+     \x20   ///Moves `amount` tokens to `to`\n\
+     \x20   @inline\n\
+     \x20   function transfer(address to, uint amount) returns (bool) { return true; }\n\
+     \x20   function burn() {}\n\
+     }";
+
+#[test]
+fn test_doc_comments() {
+    let program = parser::parse_program(SOURCE).unwrap();
+    let Program::GlobalStatements(statements) = &program;
+    assert_eq!(
+        statements[0].node.doc(),
+        Some("Fungible token\nwith a fixed supply")
+    );
+    let members = match &statements[0].node {
+        StatementType::ContractStatement { members, .. } => match &members.node {
+            StatementType::MemberStatement { statements } => statements,
+            _ => unreachable!(),
+        },
+        _ => unreachable!(),
+    };
+    let docs: Vec<Option<&str>> = members.iter().map(|member| member.node.doc()).collect();
+    assert_eq!(
+        docs,
+        vec![
+            Some("Total number of tokens"),
+            None,
+            Some("Moves `amount` tokens to `to`"),
+            None
+        ]
+    );
+
+    // Doc comments aren't statements of function bodies
+    assert!(parser::parse_program("function f() { /// doc\n uint a; }").is_err());
+}
+
+#[test]
+fn test_contract_docs() {
+    let program = parser::parse_program(SOURCE).unwrap();
+    let docs = contract_docs(&program);
+    assert_eq!(docs.len(), 1);
+    assert_eq!(
+        docs[0].functions[0].signature(),
+        "transfer(address to, uint256 amount) returns (bool)"
+    );
+    assert_eq!(
+        docs[0].to_json(),
+        "{\"doc\":\"Fungible token\\nwith a fixed supply\",\"functions\":[\
+         {\"doc\":\"Moves `amount` tokens to `to`\",\"name\":\"transfer\",\
+         \"parameters\":[{\"name\":\"to\",\"type\":\"address\"},{\"name\":\"amount\",\"type\":\"uint256\"}],\
+         \"returns\":[{\"name\":null,\"type\":\"bool\"}]},\
+         {\"doc\":null,\"name\":\"burn\",\"parameters\":[],\"returns\":[]}],\
+         \"name\":\"Token\",\
+         \"variables\":[{\"doc\":\"Total number of tokens\",\"name\":\"supply\",\"type\":\"uint256\"}]}"
+    );
+    assert_eq!(
+        docs[0].to_markdown(),
+        "# Token\n\
+         \n\
+         Fungible token\n\
+         with a fixed supply\n\
+         \n\
+         ## Functions\n\
+         \n\
+         ### `transfer(address to, uint256 amount) returns (bool)`\n\
+         \n\
+         Moves `amount` tokens to `to`\n\
+         \n\
+         ### `burn()`\n\
+         \n\
+         ## State variables\n\
+         \n\
+         ### `uint256 supply`\n\
+         \n\
+         Total number of tokens\n"
+    );
+}
+
+#[test]
+fn test_block_doc_comments() {
+    let source = "/**\n\
+     \x20* Fungible token\n\
+     \x20*\n\
+     \x20* with a fixed supply\n\
+     \x20*/\n\
+     contract Token {\n\
+     \x20   /** Total number of tokens */\n\
+     \x20   uint supply;\n\
+     \x20   /**/ uint burnt;\n\
+     \x20   /* Not a doc comment */\n\
+     \x20   function burn() {}\n\
+     }";
+    let program = parser::parse_program(source).unwrap();
+    let docs = contract_docs(&program);
+    assert_eq!(
+        docs[0].doc.as_deref(),
+        Some("Fungible token\n\nwith a fixed supply")
+    );
+    let variables: Vec<Option<&str>> = docs[0]
+        .variables
+        .iter()
+        .map(|variable| variable.doc.as_deref())
+        .collect();
+    assert_eq!(variables, vec![Some("Total number of tokens"), None]);
+    assert_eq!(docs[0].functions[0].doc, None);
+}
@@ -0,0 +1,60 @@
+#![cfg(feature = "generator")]
+
+use zoker_parser::generator::{generate_program, GeneratorConfig};
+use zoker_parser::parser;
+use zoker_parser::snapshot::{snapshot_program, SnapshotConfig};
+use zoker_parser::unparse::unparse_program;
+
+#[test]
+fn test_generated_programs_parse() {
+    for seed in 0..200 {
+        let source = generate_program(GeneratorConfig {
+            seed,
+            ..GeneratorConfig::default()
+        });
+        if let Err(err) = parser::parse_program(&source) {
+            panic!("seed {} failed with {:?}\n{}", seed, err, source);
+        }
+    }
+}
+
+#[test]
+fn test_generator_is_deterministic() {
+    let config = GeneratorConfig {
+        seed: 7,
+        contracts: 3,
+        expression_depth: 5,
+        ..GeneratorConfig::default()
+    };
+    assert_eq!(generate_program(config.clone()), generate_program(config));
+    assert_ne!(
+        generate_program(GeneratorConfig::default()),
+        generate_program(GeneratorConfig {
+            seed: 1,
+            ..GeneratorConfig::default()
+        })
+    );
+}
+
+#[test]
+fn test_generated_programs_unparse() {
+    let config = SnapshotConfig { locations: false };
+    for seed in 0..200 {
+        let source = generate_program(GeneratorConfig {
+            seed,
+            ..GeneratorConfig::default()
+        });
+        let program = parser::parse_program(&source).unwrap();
+        let unparsed = unparse_program(&program);
+        match parser::parse_program(&unparsed) {
+            Ok(reparsed) => assert_eq!(
+                snapshot_program(&reparsed, &config),
+                snapshot_program(&program, &config),
+                "seed {}\n{}",
+                seed,
+                unparsed
+            ),
+            Err(err) => panic!("seed {} failed with {:?}\n{}", seed, err, unparsed),
+        }
+    }
+}
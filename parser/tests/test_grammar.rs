@@ -84,3 +84,117 @@ fn test_bigint_parser() {
     let bigint = parser::parse_expression("1_021_000_000_000_000_000_000_000_000_000_000_000_000_000_000_000_000_000_000_000_000_000_000_000_000_000_000_000_000");
     assert!(bigint.is_ok());
 }
+
+#[test]
+fn test_member_expression_parser() {
+    assert!(parser::parse_expression("abi.encode").is_ok());
+    assert!(parser::parse_expression("abi.encode(a, b)").is_ok());
+    assert!(parser::parse_expression("a.b.c(1) + 2").is_ok());
+    assert!(parser::parse_expression("abi.decode(data, (uint, bool))").is_ok());
+    assert!(parser::parse_expression("a.").is_err());
+    assert!(parser::parse_expression("a.1").is_err());
+}
+
+#[test]
+fn test_array_type_parser() {
+    assert!(parser::parse_statement("uint[] values").is_ok());
+    assert!(parser::parse_statement("uint[][] storage values").is_ok());
+    assert!(parser::parse_statement("uint[ values").is_err());
+    assert!(parser::parse_program("contract Test { address[] owners; }").is_ok());
+}
+
+#[test]
+fn test_constructor_parser() {
+    let program =
+        parser::parse_program("contract Test { constructor(uint a) { x = a; } uint x; }").unwrap();
+    let zoker_parser::ast::Program::GlobalStatements(statements) = program;
+    match &statements[0].node {
+        zoker_parser::ast::StatementType::ContractStatement { members, .. } => {
+            match &members.node {
+                zoker_parser::ast::StatementType::MemberStatement { statements } => {
+                    assert!(statements[0].node.is_constructor());
+                    assert!(!statements[1].node.is_constructor());
+                }
+                _ => panic!("expected members"),
+            }
+        }
+        _ => panic!("expected a contract"),
+    }
+    assert!(parser::parse_program("constructor() {}").is_err());
+    assert!(parser::parse_program("contract Test { function constructor() {} }").is_err());
+    assert!(parser::parse_program("contract Test { constructor() returns (uint) {} }").is_err());
+}
+
+#[test]
+fn test_do_while_parser() {
+    assert!(parser::parse_statement("do { i += 1; } while (i < n)").is_ok());
+    assert!(parser::parse_statement("do { i += 1; } while i < n").is_ok());
+    assert!(parser::parse_program(
+        "function f(uint n) { uint i = 0; do { i += 1; } while (i < n); }"
+    )
+    .is_ok());
+    assert!(parser::parse_statement("do i += 1 while (i < n)").is_err());
+    assert!(parser::parse_statement("do { i += 1; }").is_err());
+}
+
+#[test]
+fn test_mapping_type_parser() {
+    use zoker_parser::ast::{StatementType, Type};
+
+    let statement = parser::parse_statement("mapping(address => uint256) balances").unwrap();
+    match statement.node {
+        StatementType::InitializerStatement { variable_type, .. } => assert_eq!(
+            variable_type,
+            Type::Mapping(Box::new(Type::Address), Box::new(Type::Uint256))
+        ),
+        _ => panic!("expected an initializer"),
+    }
+    assert!(
+        parser::parse_statement("mapping(address => mapping(address => uint)) allowed").is_ok()
+    );
+    assert!(parser::parse_statement("mapping(address => uint[]) lists").is_ok());
+    assert!(parser::parse_statement("mapping(address) balances").is_err());
+    assert!(parser::parse_statement("mapping(address => ) balances").is_err());
+}
+
+#[test]
+fn test_trailing_comma_parser() {
+    assert!(parser::parse_expression("add(1, 2,)").is_ok());
+    assert!(parser::parse_expression("abi.decode(data, (uint, bool),)").is_ok());
+    assert!(parser::parse_expression("add(,)").is_err());
+    assert!(parser::parse_expression("add(1,,)").is_err());
+    assert!(parser::parse_program(
+        "contract Test { function add(uint a, uint b,) returns (uint,) { a } }"
+    )
+    .is_ok());
+    assert!(parser::parse_program("contract Test { function add(,) { a } }").is_err());
+}
+
+#[test]
+fn test_grammar_info() {
+    use zoker_parser::grammar::Associativity;
+
+    let info = zoker_parser::grammar_info();
+    assert!(info.keywords.contains(&"contract".to_string()));
+    assert!(info.keywords.contains(&"storage".to_string()));
+    assert!(info.precedence_of("||") < info.precedence_of("&&"));
+    assert!(info.precedence_of("<") < info.precedence_of("<<"));
+    assert!(info.precedence_of("+") < info.precedence_of("*"));
+    assert_eq!(info.precedence_of("=="), info.precedence_of("!="));
+    assert_eq!(info.precedence_of("[]"), info.precedence_of("."));
+    assert_eq!(info.precedence_of("=>"), None);
+    let assign = info.precedence_of("=").unwrap();
+    assert_eq!(info.precedence[assign].associativity, Associativity::Right);
+    for keyword in &info.keywords {
+        assert!(parser::parse_expression(&format!("{} = 1", keyword)).is_err());
+    }
+}
+
+#[test]
+fn test_index_expression_parser() {
+    assert!(parser::parse_expression("a[1]").is_ok());
+    assert!(parser::parse_expression("a[i + 1][j] = b.c[0]").is_ok());
+    assert!(parser::parse_expression("a[]").is_err());
+    assert!(parser::parse_statement("unchecked { a[i] = 1; }").is_ok());
+    assert!(parser::parse_statement("unchecked a[i]").is_err());
+}
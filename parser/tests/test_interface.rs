@@ -0,0 +1,79 @@
+use zoker_parser::ast::{ContractKind, Program, StatementType};
+use zoker_parser::complexity::Node;
+use zoker_parser::interface::{extract_interface, interface_source, InterfaceConfig};
+use zoker_parser::parser;
+use zoker_parser::snapshot::{snapshot_nodes, SnapshotConfig};
+
+const COUNTER: &str = "contract Counter {
+  uint public count;
+  mapping(address => uint[]) public history;
+  uint private step;
+  event Counted(address from, uint count);
+  constructor(uint start) { count = start; }
+  /// Adds `amount` to the count
+  @inline function add(uint amount) external returns (uint) { count += amount; return count; }
+  function reset() internal { count = 0; }
+  #[cfg(debug)]
+  function peek() view returns (uint) { return count; }
+}";
+
+fn contract(source: &str) -> zoker_parser::ast::Statement {
+    let Program::GlobalStatements(mut statements) = parser::parse_program(source).unwrap();
+    statements.remove(0)
+}
+
+#[test]
+fn test_interface_source() {
+    let counter = contract(COUNTER);
+    assert_eq!(
+        interface_source(&counter, &InterfaceConfig::default()).unwrap(),
+        "interface ICounter {
+    function count() external view returns (uint256);
+    function history(address, uint256) external view returns (uint256);
+    /// Adds `amount` to the count
+    function add(uint256 amount) external returns (uint256);
+    #[cfg(debug)]
+    function peek() external view returns (uint256);
+    event Counted(address from, uint256 count);
+}"
+    );
+
+    let config = InterfaceConfig {
+        name: Some(String::from("Countable")),
+        getters: false,
+    };
+    let interface = extract_interface(&counter, &config).unwrap();
+    match &interface.node {
+        StatementType::ContractStatement {
+            kind,
+            contract_name,
+            members,
+            ..
+        } => {
+            assert_eq!(*kind, ContractKind::Interface);
+            assert_eq!(
+                contract_name.node.identifier_name().as_deref(),
+                Some("Countable")
+            );
+            match &members.node {
+                StatementType::MemberStatement { statements } => assert_eq!(statements.len(), 3),
+                _ => panic!("no members"),
+            }
+        }
+        _ => panic!("not an interface"),
+    }
+
+    let interface = contract("interface ICounter { function add(uint amount) external; }");
+    assert_eq!(extract_interface(&interface, &config), None);
+}
+
+#[test]
+fn test_interface_parses() {
+    let config = SnapshotConfig { locations: false };
+    let interface = extract_interface(&contract(COUNTER), &InterfaceConfig::default()).unwrap();
+    let source = interface_source(&contract(COUNTER), &InterfaceConfig::default()).unwrap();
+    assert_eq!(
+        snapshot_nodes(vec![Node::Statement(&contract(&source))], &config),
+        snapshot_nodes(vec![Node::Statement(&interface)], &config)
+    );
+}
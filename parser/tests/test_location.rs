@@ -1,3 +1,5 @@
+#![allow(clippy::assertions_on_constants)]
+
 use zoker_parser::ast::ExpressionType::{BinaryExpression, IfExpression};
 use zoker_parser::location::Location;
 use zoker_parser::parser;
@@ -71,3 +73,16 @@ fn test_ast_location2() {
         assert!(false);
     }
 }
+
+#[test]
+fn test_multi_line_string_location() {
+    let expr = parser::parse_expression("\"\"\"a\nb\"\"\" + c");
+    assert!(expr.is_ok());
+    let expr = expr.unwrap();
+    if let BinaryExpression { left, right, .. } = expr.node {
+        assert_eq!(left.location, Location::new(0, 1));
+        assert_eq!(right.location, Location::new(1, 9));
+    } else {
+        panic!("unexpected expression");
+    }
+}
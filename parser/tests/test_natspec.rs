@@ -0,0 +1,75 @@
+use zoker_parser::location::Location;
+use zoker_parser::natspec::{natspec_warnings, NatSpec, NatSpecMismatch};
+use zoker_parser::parser;
+
+#[test]
+fn test_parse_natspec() {
+    let natspec = NatSpec::parse(
+        "Moves tokens\n\
+         to another account\n\
+         @dev Skipped\n\
+         @param to Account receiving\n\
+         the tokens\n\
+         @param amount\n\
+         @return Whether the tokens were moved",
+    );
+    assert_eq!(
+        natspec,
+        NatSpec {
+            notice: Some(String::from("Moves tokens to another account")),
+            params: vec![
+                (
+                    String::from("to"),
+                    String::from("Account receiving the tokens")
+                ),
+                (String::from("amount"), String::new()),
+            ],
+            returns: vec![String::from("Whether the tokens were moved")],
+        }
+    );
+    assert_eq!(
+        NatSpec::parse("@notice Burns tokens").notice.as_deref(),
+        Some("Burns tokens")
+    );
+}
+
+#[test]
+fn test_natspec_warnings() {
+    let source = "contract Token {\n\
+         \x20   /// @param to Receiver\n\
+         \x20   /// @param amount Tokens\n\
+         \x20   /// @return Success\n\
+         \x20   function transfer(address to, uint amount) returns (bool) { return true; }\n\
+         \x20   /// @param from Sender\n\
+         \x20   /// @param amount Tokens\n\
+         \x20   /// @param amount Again\n\
+         \x20   /// @return Nothing\n\
+         \x20   function burn(uint amount) {}\n\
+         }";
+    let program = parser::parse_program(source).unwrap();
+    let warnings = natspec_warnings(&program);
+    let mismatches: Vec<&NatSpecMismatch> =
+        warnings.iter().map(|warning| &warning.mismatch).collect();
+    assert_eq!(
+        mismatches,
+        vec![
+            &NatSpecMismatch::UnknownParameter(String::from("from")),
+            &NatSpecMismatch::DuplicateParameter(String::from("amount")),
+            &NatSpecMismatch::ExtraReturns {
+                documented: 1,
+                returned: 0
+            },
+        ]
+    );
+    assert!(warnings
+        .iter()
+        .all(|warning| warning.location == Location::new(5, 6)));
+    assert_eq!(
+        warnings[0].to_string(),
+        "`@param from` doesn't match any parameter of `Token.burn`"
+    );
+    assert_eq!(
+        warnings[2].to_string(),
+        "`Token.burn` documents 1 return values but returns 0"
+    );
+}
@@ -0,0 +1,18 @@
+use zoker_parser::prelude::*;
+
+#[test]
+fn test_prelude() {
+    let expression = parse_expression("a + 1").unwrap();
+    match expression.node {
+        ExpressionType::BinaryExpression { operator, .. } => assert_eq!(operator, Operator::Add),
+        node => panic!("unexpected expression {:?}", node),
+    }
+
+    let error: ParseError = parse_program("contract Test {").unwrap_err();
+    assert_eq!(error.error, ParseErrorType::EOF);
+
+    let tokens: Vec<Tok> = make_tokenizer("uint x")
+        .map(|token| token.unwrap().1)
+        .collect();
+    assert_eq!(tokens.len(), 2);
+}
@@ -0,0 +1,74 @@
+use zoker_parser::ast::ExpressionType;
+use zoker_parser::complexity::Node;
+use zoker_parser::location::Location;
+use zoker_parser::parser;
+use zoker_parser::query::{query, Query, QueryError, Selector};
+
+#[test]
+fn test_query() {
+    let source = "contract Token {\n\
+           function send(address to, uint amount) { to.transfer(amount); total += amount; }\n\
+           @inline function fee(uint amount) returns (uint) { return amount / 100; }\n\
+           uint total;\n\
+         }\n\
+         contract Vault { function drain(address to) { to.transfer(1); } }";
+    let program = parser::parse_program(source).unwrap();
+
+    let calls = query(&program, "FunctionCall[name=transfer]").unwrap();
+    let locations: Vec<Location> = calls.iter().map(Node::location).collect();
+    assert_eq!(locations, vec![Location::new(1, 43), Location::new(5, 48)]);
+
+    // Only inside the matching ancestors
+    let calls = query(&program, "Contract[name=Token] FunctionCall[name=transfer]").unwrap();
+    assert_eq!(calls.len(), 1);
+
+    let assignments = query(&program, "Assign[operator=AddAssign] Identifier").unwrap();
+    let names: Vec<String> = assignments
+        .iter()
+        .map(|node| match node {
+            Node::Expression(expression) => expression.node.identifier_name().unwrap(),
+            _ => panic!("expected an expression"),
+        })
+        .collect();
+    assert_eq!(names, vec!["total", "amount"]);
+
+    let inline = query(&program, "*[annotation=inline] Number[value=100]").unwrap();
+    assert!(matches!(
+        inline[..],
+        [Node::Expression(expression)] if matches!(expression.node, ExpressionType::Number { .. })
+    ));
+
+    let selectors = Query::new(vec![
+        Selector::kind("FunctionStatement").with("name", "drain"),
+        Selector::any().with("name", "to"),
+    ]);
+    assert_eq!(
+        Query::parse("FunctionStatement[name=drain] *[name=\"to\"]").unwrap(),
+        selectors
+    );
+    assert_eq!(
+        selectors
+            .find(zoker_parser::complexity::program_nodes(&program))
+            .len(),
+        3
+    );
+}
+
+#[test]
+fn test_query_errors() {
+    let error = |source: &str| Query::parse(source).unwrap_err();
+    assert_eq!(
+        error("  "),
+        QueryError {
+            error: "empty query".to_string(),
+            position: 2
+        }
+    );
+    assert_eq!(error("Identifier[kind=x]").position, 11);
+    assert_eq!(error("Identifier[name=x").position, 17);
+    assert_eq!(error("Call]").error, "unexpected character");
+    assert_eq!(
+        error("[name=x]").to_string(),
+        "expected a node kind or `*` at offset 0"
+    );
+}
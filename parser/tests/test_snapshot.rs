@@ -0,0 +1,54 @@
+use zoker_parser::parser;
+use zoker_parser::snapshot::{snapshot_program, SnapshotConfig};
+
+const SOURCE: &str = "#[cfg(not(debug))]\ncontract Test {\n  private uint[2 * N] values;\n  @inline function f(uint x) returns (uint) { return x + 1; }\n}";
+
+#[test]
+fn test_snapshot_program() {
+    let program = parser::parse_program(SOURCE).unwrap();
+    let config = SnapshotConfig { locations: false };
+    assert_eq!(
+        snapshot_program(&program, &config),
+        "ConditionalStatement cfg(not(debug))
+  ContractStatement
+    Identifier Test
+    MemberStatement
+      InitializerStatement uint256[(BinaryExpression Mul (Number 2) (Identifier N))] private
+        Identifier values
+      FunctionStatement @inline
+        Identifier f
+        Parameters
+          InitializerStatement uint256
+            Identifier x
+        Parameters
+          InitializerStatement uint256
+        CompoundStatement
+          ReturnStatement
+            BinaryExpression Add
+              Identifier x
+              Number 1
+"
+    );
+}
+
+#[test]
+fn test_snapshot_locations() {
+    let program = parser::parse_program(SOURCE).unwrap();
+    let snapshot = snapshot_program(&program, &SnapshotConfig::default());
+    assert!(snapshot.starts_with("ConditionalStatement cfg(not(debug)) @"));
+    assert!(snapshot
+        .lines()
+        .all(|line| line.rsplit(' ').next().unwrap().starts_with('@')));
+
+    // Only the locations depend on the layout
+    let reformatted = parser::parse_program(&SOURCE.replace('\n', "\n\n  ")).unwrap();
+    let config = SnapshotConfig { locations: false };
+    assert_ne!(
+        snapshot,
+        snapshot_program(&reformatted, &SnapshotConfig::default())
+    );
+    assert_eq!(
+        snapshot_program(&program, &config),
+        snapshot_program(&reformatted, &config)
+    );
+}
@@ -0,0 +1,56 @@
+use zoker_parser::location::Location;
+use zoker_parser::parser;
+use zoker_parser::token::Tok;
+use zoker_parser::trace::TraceEvent;
+
+#[test]
+fn test_parse_trace() {
+    let (result, trace) = parser::parse_expression_traced("a = 1 + b");
+    assert!(result.is_ok());
+    let tokens = trace
+        .events
+        .iter()
+        .filter_map(|event| match event {
+            TraceEvent::Token { token, .. } => Some(token.clone()),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(tokens.len(), 5);
+    assert_eq!(tokens[1], Tok::Assign);
+    let nodes = trace
+        .events
+        .iter()
+        .filter_map(|event| match event {
+            TraceEvent::Node { node, .. } => Some(node.as_str()),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        nodes,
+        vec![
+            "Expression::Identifier",
+            "Expression::Number",
+            "Expression::Identifier",
+            "Expression::BinaryExpression",
+            "Expression::AssignExpression",
+        ]
+    );
+    assert_eq!(
+        trace.events.last(),
+        Some(&TraceEvent::Node {
+            location: Location::new(0, 3),
+            node: "Expression::AssignExpression".to_string(),
+        })
+    );
+}
+
+#[test]
+fn test_parse_trace_on_error() {
+    let (result, trace) = parser::parse_program_traced("contract Test { uint a = ; }");
+    assert!(result.is_err());
+    assert!(trace
+        .events
+        .iter()
+        .all(|event| matches!(event, TraceEvent::Token { .. })));
+    assert!(trace.to_string().contains("Semi"));
+}
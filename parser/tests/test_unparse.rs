@@ -0,0 +1,80 @@
+use zoker_parser::parser;
+use zoker_parser::snapshot::{snapshot_program, SnapshotConfig};
+use zoker_parser::unparse::{unparse_expression, unparse_program};
+
+const SOURCE: &str = r#"import "std/math";
+module math { function clamp(uint x) returns (uint) { return x; } }
+/// Counts up
+@deprecated("use Counter2")
+contract Counter is ICounter {
+  uint public count = 0x10;
+  mapping(address => uint[2 * N]) private balances;
+  event Counted(address from, uint count);
+  enum Color { Red, Green }
+  struct Point { uint x; uint y; }
+  constructor(uint start) { count = start; }
+  #[cfg(feature = "testnet")]
+  /// Adds `amount`
+  function add(uint amount) external payable returns (uint total) {
+    count += (amount + 1) * 2 ** 3 - -amount;
+    (count, ) = (count << 1 < 2 ? 1 : 2, 3);
+    Point p = Point({x: 1, y: math::clamp(count)});
+    unchecked { balances[msg.sender][0] = p.x; };
+    do { count--; } while count > 10 && !paused;
+    for i in balances[msg.sender] { count = i; } else { count = 0; };
+    match Color.Red { Color.Red => { count = 1; }, _ => {} };
+    abi.decode("a\"b\n", (uint, bool));
+    if count > 1 { count } else { 1 }
+  }
+}"#;
+
+fn snapshot(source: &str) -> String {
+    let program = parser::parse_program(source).unwrap();
+    snapshot_program(&program, &SnapshotConfig { locations: false })
+}
+
+#[test]
+fn test_unparse_round_trip() {
+    let program = parser::parse_program(SOURCE).unwrap();
+    let source = unparse_program(&program);
+    assert_eq!(snapshot(&source), snapshot(SOURCE), "{}", source);
+    let program = parser::parse_program(&source).unwrap();
+    assert_eq!(unparse_program(&program), source);
+}
+
+#[test]
+fn test_unparse_layout() {
+    let source =
+        "contract A { /// Doc\n function f(uint x) view returns (uint) { uint y = x; y } }";
+    let program = parser::parse_program(source).unwrap();
+    assert_eq!(
+        unparse_program(&program),
+        "contract A {
+    /// Doc
+    function f(uint256 x) view returns (uint256) {
+        uint256 y = x;
+        y
+    }
+}
+"
+    );
+}
+
+#[test]
+fn test_unparse_parentheses() {
+    for source in [
+        "(a + b) * c",
+        "a - (b - c)",
+        "a - b - c",
+        "a < b == c < d",
+        "a == (b == c)",
+        "a < (b << 1)",
+        "(a + 1) ** b",
+        "(a || b) && c",
+        "(a ? b : c) ? d : e",
+        "(a + b).c",
+    ] {
+        let expression = parser::parse_expression(source).unwrap();
+        assert_eq!(unparse_expression(&expression), source);
+    }
+}